@@ -0,0 +1,88 @@
+//! Parallel patch extraction benchmarks.
+//!
+//! Measures `StreamSession::extract_prioritized_patches_for_active_streams`
+//! — the step `GatInMemoryStreamRepository::batch_generate_frames_atomic`
+//! runs before committing frames — on a `GenerationPool` of 1 to 8 threads,
+//! for sessions of 1, 8 and 32 active streams. The 1-thread arm is the
+//! sequential baseline; with a single stream the only parallelism left is
+//! across the document's top-level members, so `streams/1` shows how much
+//! of the speedup comes from subtrees rather than streams.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use pjson_rs::domain::{
+    aggregates::{StreamSession, stream_session::SessionConfig},
+    services::GenerationPool,
+    value_objects::{JsonData, Priority},
+};
+use std::hint::black_box;
+
+const STREAM_COUNTS: [usize; 3] = [1, 8, 32];
+const THREAD_COUNTS: [usize; 4] = [1, 2, 4, 8];
+
+/// A dashboard-shaped document with several sizeable top-level members.
+fn document(seed: usize) -> JsonData {
+    let records = |name: &str, count: usize| {
+        (0..count)
+            .map(|i| {
+                serde_json::json!({
+                    "id": i,
+                    "name": format!("{name} {seed}-{i}"),
+                    "score": (i * 7 + seed) % 100,
+                    "tags": ["alpha", "beta", "gamma"],
+                    "details": {"created": "2024-01-01T00:00:00Z", "active": i % 2 == 0},
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    serde_json::json!({
+        "id": seed,
+        "status": "ok",
+        "users": records("user", 200),
+        "orders": records("order", 200),
+        "products": records("product", 200),
+        "events": records("event", 200),
+        "meta": {"title": "Report", "version": 3, "notes": "x".repeat(512)},
+    })
+    .into()
+}
+
+fn session_with_streams(count: usize) -> StreamSession {
+    let mut session = StreamSession::new(SessionConfig {
+        max_concurrent_streams: count,
+        ..Default::default()
+    });
+    session.activate().unwrap();
+    for seed in 0..count {
+        let stream_id = session.create_stream(document(seed)).unwrap();
+        session.start_stream(stream_id).unwrap();
+    }
+    session
+}
+
+fn bench_active_stream_extraction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parallel_generation_active_streams");
+    group.sample_size(20);
+
+    for streams in STREAM_COUNTS {
+        let session = session_with_streams(streams);
+        group.throughput(Throughput::Elements(streams as u64));
+        for threads in THREAD_COUNTS {
+            let pool = GenerationPool::with_threads(threads).unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("streams/{streams}"), threads),
+                &threads,
+                |b, _| {
+                    b.iter(|| {
+                        black_box(pool.install(|| {
+                            session.extract_prioritized_patches_for_active_streams(Priority::MIN)
+                        }))
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_active_stream_extraction);
+criterion_main!(benches);
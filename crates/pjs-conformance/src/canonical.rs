@@ -0,0 +1,123 @@
+//! Canonical form of `application/pjs+json` frames.
+//!
+//! Two frames are equivalent when they change a client's document the same
+//! way, so the canonical form drops what the protocol leaves to the
+//! producer: `@timestamp`, `@seq` (checked by the
+//! [`validator`](crate::validator) instead), `@base_path` (folded into each
+//! patch path) and whether chunk metadata sits on the frame or the patch.
+//! Patches are ordered by path — stably, so chunks of one array keep their
+//! order — and frames by content within each run of equal type and
+//! priority, since only the order across priorities is specified.
+
+use serde_json::Value;
+
+/// Canonical form of one `application/pjs+json` frame.
+///
+/// Values that are not frame objects are returned unchanged.
+pub fn canonical_frame(frame: &Value) -> Value {
+    let Value::Object(fields) = frame else {
+        return frame.clone();
+    };
+    let mut fields = fields.clone();
+    fields.remove("@timestamp");
+    fields.remove("@seq");
+
+    let base = match fields.remove("@base_path") {
+        Some(Value::String(base)) => base,
+        _ => String::new(),
+    };
+    let frame_chunk = fields.remove("@array_metadata");
+    if let Some(Value::Array(patches)) = fields.get_mut("@patches") {
+        for patch in patches.iter_mut() {
+            if let Some(Value::String(path)) = patch.get_mut("path") {
+                path.insert_str(0, &base);
+            }
+        }
+        if let Some(Value::Object(mut metadata)) = frame_chunk
+            && let Some(Value::String(array)) = metadata.remove("path")
+            && let Some(Value::Object(patch)) = patches
+                .iter_mut()
+                .find(|patch| path_of(patch) == format!("{array}/-"))
+        {
+            patch.insert("array_metadata".into(), Value::Object(metadata));
+        }
+        patches.sort_by(|a, b| path_of(a).cmp(path_of(b)));
+    }
+    Value::Object(fields)
+}
+
+/// Canonical form of a frame sequence.
+pub fn canonical_sequence(frames: &[Value]) -> Vec<Value> {
+    let mut canonical: Vec<Value> = frames.iter().map(canonical_frame).collect();
+    for run in canonical.chunk_by_mut(|a, b| run_key(a) == run_key(b)) {
+        run.sort_by_cached_key(Value::to_string);
+    }
+    canonical
+}
+
+fn path_of(patch: &Value) -> &str {
+    patch
+        .get("path")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+fn run_key(frame: &Value) -> (Option<&Value>, Option<&Value>) {
+    (frame.get("@type"), frame.get("@priority"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn folds_base_path_and_frame_chunk_metadata_into_patches() {
+        let frame = json!({
+            "@type": "patch",
+            "@seq": 3,
+            "@priority": 50,
+            "@timestamp": 1,
+            "@base_path": "/user",
+            "@array_metadata": {"path": "/user/posts", "total_items": 4, "chunk_index": 0, "chunk_size": 2},
+            "@patches": [
+                {"op": "add", "path": "/posts/-", "value": [1, 2]},
+                {"op": "replace", "path": "/name", "value": "Alice"}
+            ]
+        });
+
+        assert_eq!(
+            canonical_frame(&frame),
+            json!({
+                "@type": "patch",
+                "@priority": 50,
+                "@patches": [
+                    {"op": "replace", "path": "/user/name", "value": "Alice"},
+                    {
+                        "op": "add",
+                        "path": "/user/posts/-",
+                        "value": [1, 2],
+                        "array_metadata": {"total_items": 4, "chunk_index": 0, "chunk_size": 2}
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn orders_frames_only_within_a_priority() {
+        let patch = |priority: u8, path: &str| json!({"@type": "patch", "@priority": priority, "@patches": [{"op": "replace", "path": path, "value": 1}]});
+        let delivered = [patch(80, "/b"), patch(80, "/a"), patch(50, "/c")];
+        let reordered = [patch(80, "/a"), patch(80, "/b"), patch(50, "/c")];
+        assert_eq!(
+            canonical_sequence(&delivered),
+            canonical_sequence(&reordered)
+        );
+
+        let inverted = [patch(50, "/c"), patch(80, "/a"), patch(80, "/b")];
+        assert_ne!(
+            canonical_sequence(&inverted),
+            canonical_sequence(&reordered)
+        );
+    }
+}
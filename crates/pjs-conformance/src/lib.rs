@@ -0,0 +1,46 @@
+//! Conformance test vectors and validator for the PJS protocol.
+//!
+//! The vectors in `vectors/` are plain JSON, so implementations outside this
+//! workspace (the JavaScript client, third-party servers) can run them too:
+//!
+//! - `generation.json`: input documents with a priority configuration, the
+//!   document a client must hold after replaying the generated frames, and
+//!   the expected frame sequence per implementation.
+//! - `reconstruction.json`: `application/pjs+json` frames in delivery order —
+//!   including out-of-order and duplicated delivery — with the document they
+//!   must reconstruct to, or `"rejected"` when a conforming client must
+//!   refuse them.
+//!
+//! Frames are compared in the canonical form of [`canonical`], and every
+//! generated sequence is checked by the reference [`validator`]. Vectors
+//! list the [`Capability`]s they need; targets that lack one report the
+//! vector as skipped rather than failed.
+//!
+//! # Example
+//!
+//! ```
+//! use pjs_conformance::{Outcome, generation_vectors, generators, run_generation};
+//!
+//! let vectors = generation_vectors().unwrap();
+//! for generator in generators() {
+//!     for result in run_generation(generator.as_ref(), &vectors) {
+//!         assert!(!matches!(result.outcome, Outcome::Failed(_)), "{result}");
+//!     }
+//! }
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod canonical;
+pub mod runner;
+pub mod targets;
+pub mod validator;
+pub mod vectors;
+
+pub use runner::{CaseResult, Outcome, run_generation, run_reconstruction};
+pub use targets::{FrameGenerator, FrameReconstructor, generators, reconstructors};
+pub use validator::{Violation, validate_sequence};
+pub use vectors::{
+    Capability, Expectation, GenerationConfig, GenerationVector, ReconstructionVector,
+    generation_vectors, reconstruction_vectors,
+};
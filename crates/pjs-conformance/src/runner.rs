@@ -0,0 +1,149 @@
+//! Running vectors against targets.
+
+use crate::canonical::canonical_sequence;
+use crate::targets::{FrameGenerator, FrameReconstructor};
+use crate::validator::validate_sequence;
+use crate::vectors::{Capability, Expectation, GenerationVector, ReconstructionVector};
+use pjson_rs::stream::pjs_json::{decode_frame, encode_frame};
+use pjson_rs_domain::value_objects::StreamId;
+use serde_json::Value;
+use std::fmt;
+
+/// Result of running one vector against one target.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The target behaved as the vector requires
+    Passed,
+    /// The target lacks these capabilities, so the vector was not run
+    Skipped(Vec<Capability>),
+    /// The target did not behave as the vector requires
+    Failed(String),
+}
+
+/// A vector's [`Outcome`] on a target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseResult {
+    /// Vector name
+    pub vector: String,
+    /// Target name
+    pub target: &'static str,
+    /// What happened
+    pub outcome: Outcome,
+}
+
+impl fmt::Display for CaseResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on {}: ", self.vector, self.target)?;
+        match &self.outcome {
+            Outcome::Passed => write!(f, "passed"),
+            Outcome::Skipped(missing) => write!(f, "skipped, needs {missing:?}"),
+            Outcome::Failed(reason) => write!(f, "FAILED: {reason}"),
+        }
+    }
+}
+
+/// Run every generation vector against `target`.
+///
+/// Each generated sequence must pass [`validate_sequence`], reconstruct to
+/// the vector's expected document and, where the vector lists frames for
+/// `target`, match them in canonical form.
+pub fn run_generation(
+    target: &dyn FrameGenerator,
+    vectors: &[GenerationVector],
+) -> Vec<CaseResult> {
+    vectors
+        .iter()
+        .map(|vector| CaseResult {
+            vector: vector.name.clone(),
+            target: target.name(),
+            outcome: match missing(&vector.requires(), target.capabilities()) {
+                Some(missing) => Outcome::Skipped(missing),
+                None => outcome(check_generation(target, vector)),
+            },
+        })
+        .collect()
+}
+
+/// Run every reconstruction vector against `target`.
+pub fn run_reconstruction(
+    target: &dyn FrameReconstructor,
+    vectors: &[ReconstructionVector],
+) -> Vec<CaseResult> {
+    vectors
+        .iter()
+        .map(|vector| CaseResult {
+            vector: vector.name.clone(),
+            target: target.name(),
+            outcome: match missing(&vector.requires, target.capabilities()) {
+                Some(missing) => Outcome::Skipped(missing),
+                None => outcome(check_reconstruction(target, vector)),
+            },
+        })
+        .collect()
+}
+
+fn check_generation(target: &dyn FrameGenerator, vector: &GenerationVector) -> Result<(), String> {
+    let frames = target
+        .generate(&vector.input, &vector.config)?
+        .iter()
+        .map(encode_frame)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let document = validate_sequence(&frames).map_err(|e| e.to_string())?;
+    if &document != vector.expected_document() {
+        return Err(format!(
+            "frames reconstruct to {document}, expected {}",
+            vector.expected_document()
+        ));
+    }
+
+    if let Some(expected) = vector.frames.get(target.name()) {
+        let actual = canonical_sequence(&frames);
+        if actual != canonical_sequence(expected) {
+            return Err(format!("unexpected frames: {}", Value::Array(actual)));
+        }
+    }
+    Ok(())
+}
+
+fn check_reconstruction(
+    target: &dyn FrameReconstructor,
+    vector: &ReconstructionVector,
+) -> Result<(), String> {
+    let stream_id = StreamId::new();
+    let mut frames = Vec::with_capacity(vector.frames.len());
+    for frame in &vector.frames {
+        if let Some(frame) = decode_frame(stream_id, frame).map_err(|e| e.to_string())? {
+            frames.push(frame);
+        }
+    }
+
+    match (target.reconstruct(&frames), &vector.expected) {
+        (Ok(actual), Expectation::Document(expected)) if &actual == expected => Ok(()),
+        (Ok(actual), Expectation::Document(expected)) => {
+            Err(format!("reconstructed {actual}, expected {expected}"))
+        }
+        (Err(reason), Expectation::Document(_)) => Err(format!("refused the frames: {reason}")),
+        (Ok(actual), Expectation::Rejected) => {
+            Err(format!("accepted the frames, reconstructing {actual}"))
+        }
+        (Err(_), Expectation::Rejected) => Ok(()),
+    }
+}
+
+fn missing(requires: &[Capability], supported: &[Capability]) -> Option<Vec<Capability>> {
+    let missing: Vec<_> = requires
+        .iter()
+        .filter(|capability| !supported.contains(capability))
+        .copied()
+        .collect();
+    (!missing.is_empty()).then_some(missing)
+}
+
+fn outcome(result: Result<(), String>) -> Outcome {
+    match result {
+        Ok(()) => Outcome::Passed,
+        Err(reason) => Outcome::Failed(reason),
+    }
+}
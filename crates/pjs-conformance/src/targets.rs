@@ -0,0 +1,245 @@
+//! The implementations in this workspace the vectors run against.
+
+use crate::vectors::{Capability, GenerationConfig};
+use pjs_wasm::{FrameData, PjsParser, PjsReconstructor};
+use pjson_rs::stream::priority::StreamerConfig;
+use pjson_rs::stream::{JsonReconstructor, PriorityStreamFrame, PriorityStreamer};
+use pjson_rs_domain::entities::stream::{ArrayChunking, StreamConfig};
+use pjson_rs_domain::entities::{Frame, Stream};
+use pjson_rs_domain::value_objects::{JsonPathQuery, Priority, SessionId, StreamId};
+use serde_json::Value;
+
+/// A producer of frame sequences.
+pub trait FrameGenerator {
+    /// Name keying this target's expected frames in generation vectors
+    fn name(&self) -> &'static str;
+
+    /// Optional behaviour this target supports
+    fn capabilities(&self) -> &'static [Capability];
+
+    /// Stream `input` under `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the implementation's error, rendered as text.
+    fn generate(&self, input: &Value, config: &GenerationConfig) -> Result<Vec<Frame>, String>;
+}
+
+/// A client rebuilding documents from frame sequences.
+pub trait FrameReconstructor {
+    /// Name of this target in reports
+    fn name(&self) -> &'static str;
+
+    /// Optional behaviour this target supports
+    fn capabilities(&self) -> &'static [Capability];
+
+    /// Apply `frames` in the order given and return the resulting document.
+    ///
+    /// # Errors
+    ///
+    /// Returns the implementation's error, rendered as text, if it refuses
+    /// the frames.
+    fn reconstruct(&self, frames: &[Frame]) -> Result<Value, String>;
+}
+
+/// Every generator in this workspace.
+pub fn generators() -> Vec<Box<dyn FrameGenerator>> {
+    vec![
+        Box::new(StreamGenerator),
+        Box::new(PriorityStreamerGenerator),
+        Box::new(WasmGenerator),
+    ]
+}
+
+/// Every reconstructor in this workspace.
+pub fn reconstructors() -> Vec<Box<dyn FrameReconstructor>> {
+    vec![
+        Box::new(JsonReconstructorTarget),
+        Box::new(WasmReconstructor),
+    ]
+}
+
+/// The domain [`Stream`] entity, as driven by the HTTP and WebSocket
+/// transports: a skeleton frame, then every patch at or above the threshold
+/// in its own frame, then a completion frame without a digest.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamGenerator;
+
+impl FrameGenerator for StreamGenerator {
+    fn name(&self) -> &'static str {
+        "stream"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::PriorityThreshold,
+            Capability::PriorityRules,
+            Capability::ArrayChunking,
+        ]
+    }
+
+    fn generate(&self, input: &Value, config: &GenerationConfig) -> Result<Vec<Frame>, String> {
+        let priority_rules = config
+            .priority_rules
+            .iter()
+            .map(|(rule, priority)| Ok((rule.clone(), priority_of(*priority)?)))
+            .collect::<Result<_, String>>()?;
+        let array_chunking = config
+            .array_chunking
+            .iter()
+            .map(|(path, chunk_size)| (path.clone(), ArrayChunking::new(*chunk_size)))
+            .collect();
+        let stream_config = StreamConfig {
+            priority_rules,
+            array_chunking,
+            ..StreamConfig::default()
+        };
+
+        let mut stream = Stream::new(SessionId::new(), input.clone().into(), stream_config);
+        stream.start_streaming().map_err(|e| e.to_string())?;
+        let mut frames = vec![stream.create_skeleton_frame().map_err(|e| e.to_string())?];
+        frames.extend(
+            stream
+                .create_patch_frames(min_priority(config)?, usize::MAX)
+                .map_err(|e| e.to_string())?,
+        );
+        frames.push(
+            stream
+                .create_completion_frame(None)
+                .map_err(|e| e.to_string())?,
+        );
+        Ok(frames)
+    }
+}
+
+/// [`PriorityStreamer::analyze`], its frames converted with
+/// [`PriorityStreamFrame::to_frame`].
+///
+/// Rules keyed by a field name become `$..name` queries. Claims no
+/// [`Capability::ArrayChunking`]: the streamer chunks every array by
+/// [`StreamerConfig::max_patch_size`], not per path.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PriorityStreamerGenerator;
+
+impl FrameGenerator for PriorityStreamerGenerator {
+    fn name(&self) -> &'static str {
+        "priority_streamer"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[Capability::PriorityThreshold, Capability::PriorityRules]
+    }
+
+    fn generate(&self, input: &Value, config: &GenerationConfig) -> Result<Vec<Frame>, String> {
+        let priority_rules = config
+            .priority_rules
+            .iter()
+            .map(|(rule, priority)| {
+                let query = if rule.starts_with('$') {
+                    JsonPathQuery::new(rule)
+                } else {
+                    JsonPathQuery::new(format!("$..{rule}"))
+                }
+                .map_err(|e| e.to_string())?;
+                Ok((query, priority_of(*priority)?))
+            })
+            .collect::<Result<_, String>>()?;
+        let streamer = PriorityStreamer::with_config(StreamerConfig {
+            priority_threshold: min_priority(config)?,
+            priority_rules,
+            ..StreamerConfig::default()
+        });
+        let plan = streamer.analyze(input).map_err(|e| e.to_string())?;
+        let stream_id = StreamId::new();
+        plan.frames()
+            .zip(0..)
+            .map(|(frame, sequence)| frame.to_frame(stream_id, sequence))
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// The WebAssembly `PjsParser.generateFrames` export with default
+/// configuration.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WasmGenerator;
+
+impl FrameGenerator for WasmGenerator {
+    fn name(&self) -> &'static str {
+        "wasm"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[Capability::PriorityThreshold]
+    }
+
+    fn generate(&self, input: &Value, config: &GenerationConfig) -> Result<Vec<Frame>, String> {
+        PjsParser::new().frames_for(&input.to_string(), min_priority(config)?.value())
+    }
+}
+
+/// [`JsonReconstructor`], fed every frame with its sequence number before
+/// processing the queue.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonReconstructorTarget;
+
+impl FrameReconstructor for JsonReconstructorTarget {
+    fn name(&self) -> &'static str {
+        "json_reconstructor"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[Capability::Reordering, Capability::Deduplication]
+    }
+
+    fn reconstruct(&self, frames: &[Frame]) -> Result<Value, String> {
+        let mut reconstructor = JsonReconstructor::new();
+        for frame in frames {
+            let sequence = frame.sequence();
+            let frame = PriorityStreamFrame::try_from(frame).map_err(|e| e.to_string())?;
+            reconstructor.add_sequenced_frame(sequence, frame);
+        }
+        reconstructor
+            .process_all_frames()
+            .map_err(|e| e.to_string())?;
+        if !reconstructor.is_complete() {
+            return Err(format!(
+                "incomplete, missing frames {:?}",
+                reconstructor.missing_sequences()
+            ));
+        }
+        Ok(reconstructor.current_state().clone())
+    }
+}
+
+/// The WebAssembly `PjsReconstructor.applyFrame` export.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WasmReconstructor;
+
+impl FrameReconstructor for WasmReconstructor {
+    fn name(&self) -> &'static str {
+        "wasm"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[]
+    }
+
+    fn reconstruct(&self, frames: &[Frame]) -> Result<Value, String> {
+        let mut reconstructor = PjsReconstructor::new();
+        for frame in frames {
+            reconstructor.apply_frame_data(&FrameData::from(frame))?;
+        }
+        Ok(reconstructor.document().clone())
+    }
+}
+
+fn priority_of(value: u8) -> Result<Priority, String> {
+    Priority::new(value).map_err(|e| e.to_string())
+}
+
+/// The vector's threshold, or the lowest priority so that nothing is left
+/// out.
+fn min_priority(config: &GenerationConfig) -> Result<Priority, String> {
+    priority_of(config.min_priority.unwrap_or(1))
+}
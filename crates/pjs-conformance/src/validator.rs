@@ -0,0 +1,313 @@
+//! Reference validator for `application/pjs+json` frame sequences.
+//!
+//! A conforming producer emits, in order: one skeleton frame, patch frames
+//! of non-increasing priority, and one completion frame whose digest (if
+//! any) matches the document the preceding frames reconstruct to. Sequence
+//! numbers are consecutive; heartbeats may appear anywhere and are ignored.
+//! Patches are applied with JSON Patch semantics, with `add` to `<array>/-`
+//! appending every item of its array value (spec §3.4). As in the
+//! reference clients, a patch whose target does not exist in the document
+//! is skipped rather than failing the stream: producers may address
+//! structure the skeleton leaves out, such as elements of arrays that are
+//! only filled in later or members below a depth limit.
+
+use crate::canonical::canonical_frame;
+use pjson_rs_domain::value_objects::{ContentDigest, IntegrityError, verify_completion};
+use serde_json::Value;
+use thiserror::Error;
+
+/// A way in which a frame sequence breaks the protocol.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum Violation {
+    /// The frames are malformed or out of protocol order
+    #[error("frame {index}: {reason}")]
+    Structure {
+        /// Position of the offending frame in the sequence
+        index: usize,
+        /// What is wrong with it
+        reason: String,
+    },
+    /// A patch is malformed
+    #[error("frame {index}: invalid patch at {path}: {reason}")]
+    Patch {
+        /// Position of the offending frame in the sequence
+        index: usize,
+        /// JSON Pointer the patch targets
+        path: String,
+        /// Why it does not apply
+        reason: String,
+    },
+    /// The completion digest does not verify against the reconstruction
+    #[error("completion digest does not verify: {0}")]
+    Checksum(#[from] IntegrityError),
+}
+
+/// Check `frames` against the protocol and return the document they
+/// reconstruct to.
+///
+/// Signed completion digests are rejected, since no key is available to
+/// verify them.
+///
+/// # Errors
+///
+/// Returns the first [`Violation`] found.
+pub fn validate_sequence(frames: &[Value]) -> Result<Value, Violation> {
+    let mut document = Value::Null;
+    let mut previous_seq = None;
+    let mut patch_priority = None;
+    let mut position = Position::BeforeSkeleton;
+
+    for (index, frame) in frames.iter().enumerate() {
+        let structure = |reason: &str| Violation::Structure {
+            index,
+            reason: reason.to_string(),
+        };
+        let frame_type = frame
+            .get("@type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| structure("missing @type"))?;
+        if frame_type == "heartbeat" {
+            continue;
+        }
+
+        let seq = frame
+            .get("@seq")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| structure("missing @seq"))?;
+        if let Some(previous) = previous_seq
+            && seq != previous + 1
+        {
+            return Err(structure(&format!("@seq {seq} does not follow {previous}")));
+        }
+        previous_seq = Some(seq);
+
+        let frame = canonical_frame(frame);
+        match (frame_type, position) {
+            (_, Position::Completed) => return Err(structure("frame after completion")),
+            ("skeleton", Position::BeforeSkeleton) => {
+                document = frame.get("data").cloned().unwrap_or(Value::Null);
+                position = Position::Streaming;
+            }
+            (_, Position::BeforeSkeleton) => {
+                return Err(structure("first frame is not a skeleton"));
+            }
+            ("patch", Position::Streaming) => {
+                let priority = frame
+                    .get("@priority")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| structure("missing @priority"))?;
+                if patch_priority.is_some_and(|previous| priority > previous) {
+                    return Err(structure(&format!(
+                        "priority {priority} follows a lower-priority patch frame"
+                    )));
+                }
+                patch_priority = Some(priority);
+
+                let patches = frame
+                    .get("@patches")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| structure("missing @patches"))?;
+                for patch in patches {
+                    apply_patch(&mut document, patch).map_err(|reason| Violation::Patch {
+                        index,
+                        path: patch
+                            .get("path")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        reason,
+                    })?;
+                }
+            }
+            ("complete", Position::Streaming) => {
+                let checksum = match frame.get("@checksum") {
+                    Some(Value::String(digest)) => Some(digest.parse::<ContentDigest>()?),
+                    Some(_) => return Err(structure("@checksum is not a string")),
+                    None => None,
+                };
+                verify_completion(checksum.as_ref(), &document, None)?;
+                position = Position::Completed;
+            }
+            ("skeleton", Position::Streaming) => return Err(structure("second skeleton")),
+            (other, Position::Streaming) => {
+                return Err(structure(&format!("unexpected {other} frame")));
+            }
+        }
+    }
+
+    match position {
+        Position::Completed => Ok(document),
+        _ => Err(Violation::Structure {
+            index: frames.len(),
+            reason: "no completion frame".to_string(),
+        }),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Position {
+    BeforeSkeleton,
+    Streaming,
+    Completed,
+}
+
+/// Apply one canonical patch to `document`, skipping it if its target does
+/// not exist.
+fn apply_patch(document: &mut Value, patch: &Value) -> Result<(), String> {
+    let op = patch
+        .get("op")
+        .and_then(Value::as_str)
+        .ok_or("patch has no op")?;
+    let path = patch
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or("patch has no path")?;
+    let value = || patch.get("value").ok_or("patch has no value");
+
+    // The codec writes the whole-document pointer as `/`.
+    if path.is_empty() || path == "/" {
+        return match op {
+            "replace" | "add" => {
+                *document = value()?.clone();
+                Ok(())
+            }
+            other => Err(format!("cannot {other} the whole document")),
+        };
+    }
+
+    let (parent, token) = path.rsplit_once('/').ok_or("path is not a JSON Pointer")?;
+    let token = token.replace("~1", "/").replace("~0", "~");
+    if !matches!(op, "replace" | "add" | "remove") {
+        return Err(format!("unsupported operation {op:?}"));
+    }
+    let Some(container) = document.pointer_mut(parent) else {
+        return Ok(());
+    };
+    let index = |len: usize| token.parse::<usize>().ok().filter(|index| *index < len);
+
+    match (op, container) {
+        ("add", Value::Array(items)) if token == "-" => match value()? {
+            Value::Array(values) => items.extend(values.iter().cloned()),
+            _ => return Err("appended value is not an array".to_string()),
+        },
+        ("replace" | "add", Value::Object(members)) => {
+            members.insert(token, value()?.clone());
+        }
+        ("replace", Value::Array(items)) => match index(items.len()) {
+            Some(index) => items[index] = value()?.clone(),
+            None => return Ok(()),
+        },
+        ("add", Value::Array(items)) => match index(items.len() + 1) {
+            Some(index) => items.insert(index, value()?.clone()),
+            None => return Ok(()),
+        },
+        ("remove", Value::Object(members)) => {
+            members.remove(&token);
+        }
+        ("remove", Value::Array(items)) => match index(items.len()) {
+            Some(index) => {
+                items.remove(index);
+            }
+            None => return Ok(()),
+        },
+        _ => return Ok(()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn stream(patches: &[Value], document: &Value) -> Vec<Value> {
+        let mut frames = vec![
+            json!({"@type": "skeleton", "@seq": 0, "@priority": 100, "data": {"a": null, "items": []}}),
+        ];
+        frames.extend(patches.iter().cloned());
+        let seq = frames
+            .iter()
+            .filter(|frame| frame["@type"] != "heartbeat")
+            .count();
+        frames.push(json!({
+            "@type": "complete",
+            "@seq": seq,
+            "@checksum": ContentDigest::compute(document, None).to_string()
+        }));
+        frames
+    }
+
+    #[test]
+    fn replays_a_conforming_sequence() {
+        let document = json!({"a": 1, "items": [1, 2, 3]});
+        let frames = stream(
+            &[
+                json!({"@type": "patch", "@seq": 1, "@priority": 80, "@patches": [{"op": "replace", "path": "/a", "value": 1}]}),
+                json!({"@type": "heartbeat", "@seq": 9}),
+                json!({"@type": "patch", "@seq": 2, "@priority": 50, "@base_path": "", "@patches": [
+                    {"op": "add", "path": "/items/-", "value": [1, 2]},
+                    {"op": "add", "path": "/items/-", "value": [3]}
+                ]}),
+            ],
+            &document,
+        );
+
+        assert_eq!(validate_sequence(&frames), Ok(document));
+    }
+
+    #[test]
+    fn rejects_protocol_order_violations() {
+        let document = json!({"a": 1, "items": []});
+        let low = json!({"@type": "patch", "@seq": 1, "@priority": 25, "@patches": [{"op": "replace", "path": "/a", "value": 1}]});
+        let high = json!({"@type": "patch", "@seq": 2, "@priority": 80, "@patches": [{"op": "replace", "path": "/a", "value": 1}]});
+        assert!(matches!(
+            validate_sequence(&stream(&[low, high], &document)),
+            Err(Violation::Structure { index: 2, .. })
+        ));
+
+        let mut gap = stream(&[], &document);
+        gap[1]["@seq"] = 5.into();
+        assert!(matches!(
+            validate_sequence(&gap),
+            Err(Violation::Structure { index: 1, .. })
+        ));
+
+        let truncated = &stream(&[], &document)[..1];
+        assert!(matches!(
+            validate_sequence(truncated),
+            Err(Violation::Structure { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn skips_patches_without_a_target() {
+        let document = json!({"a": null, "items": []});
+        let frames = stream(
+            &[
+                json!({"@type": "patch", "@seq": 1, "@priority": 50, "@patches": [
+                    {"op": "replace", "path": "/b/c", "value": 1},
+                    {"op": "replace", "path": "/items/0/id", "value": 1}
+                ]}),
+            ],
+            &document,
+        );
+
+        assert_eq!(validate_sequence(&frames), Ok(document));
+    }
+
+    #[test]
+    fn rejects_malformed_patches_and_digest_mismatches() {
+        let document = json!({"a": null, "items": []});
+        let copy = json!({"@type": "patch", "@seq": 1, "@priority": 50, "@patches": [{"op": "copy", "from": "/a", "path": "/b"}]});
+        assert!(matches!(
+            validate_sequence(&stream(&[copy], &document)),
+            Err(Violation::Patch { index: 1, .. })
+        ));
+
+        let frames = stream(&[], &json!({"a": 2, "items": []}));
+        assert!(matches!(
+            validate_sequence(&frames),
+            Err(Violation::Checksum(IntegrityError::Mismatch { .. }))
+        ));
+    }
+}
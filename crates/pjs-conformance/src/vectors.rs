@@ -0,0 +1,130 @@
+//! Test-vector format and the vectors shipped with this crate.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+const GENERATION: &str = include_str!("../vectors/generation.json");
+const RECONSTRUCTION: &str = include_str!("../vectors/reconstruction.json");
+
+/// Optional protocol behaviour a vector depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Patches below a minimum priority left out of the stream
+    PriorityThreshold,
+    /// Custom priority rules keyed by field name or JSONPath query
+    PriorityRules,
+    /// Large arrays streamed as ordered `append` chunks with array metadata
+    ArrayChunking,
+    /// Frames applied in sequence order whatever order they arrive in
+    Reordering,
+    /// Frames delivered more than once applied only once
+    Deduplication,
+}
+
+/// A document to stream, and what streaming it must produce.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenerationVector {
+    /// Unique vector name
+    pub name: String,
+    /// What the vector exercises
+    pub description: String,
+    /// Source document
+    pub input: Value,
+    /// Priority configuration to stream `input` with
+    #[serde(default)]
+    pub config: GenerationConfig,
+    /// Document a client holds after replaying the frames; `input` when
+    /// absent
+    #[serde(default)]
+    pub reconstruction: Option<Value>,
+    /// Expected frame sequence per target name, in the form of
+    /// [`crate::canonical::canonical_sequence`]
+    #[serde(default)]
+    pub frames: BTreeMap<String, Vec<Value>>,
+}
+
+impl GenerationVector {
+    /// Capabilities a generator needs to honour [`Self::config`].
+    pub fn requires(&self) -> Vec<Capability> {
+        let mut requires = Vec::new();
+        if self.config.min_priority.is_some() {
+            requires.push(Capability::PriorityThreshold);
+        }
+        if !self.config.priority_rules.is_empty() {
+            requires.push(Capability::PriorityRules);
+        }
+        if !self.config.array_chunking.is_empty() {
+            requires.push(Capability::ArrayChunking);
+        }
+        requires
+    }
+
+    /// Document the generated frames must reconstruct to.
+    pub fn expected_document(&self) -> &Value {
+        self.reconstruction.as_ref().unwrap_or(&self.input)
+    }
+}
+
+/// Priority configuration of a [`GenerationVector`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenerationConfig {
+    /// Patches below this priority are not sent; every patch is sent when
+    /// absent
+    #[serde(default)]
+    pub min_priority: Option<u8>,
+    /// Priority overrides keyed by field name or JSONPath query
+    #[serde(default)]
+    pub priority_rules: BTreeMap<String, u8>,
+    /// Chunk size per array, keyed by JSON path (e.g. `$.rows`)
+    #[serde(default)]
+    pub array_chunking: BTreeMap<String, usize>,
+}
+
+/// A frame delivery and the document it must reconstruct to.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReconstructionVector {
+    /// Unique vector name
+    pub name: String,
+    /// What the vector exercises
+    pub description: String,
+    /// Capabilities a client needs to pass the vector
+    #[serde(default)]
+    pub requires: Vec<Capability>,
+    /// `application/pjs+json` frames in delivery order
+    pub frames: Vec<Value>,
+    /// Required result of applying `frames`
+    pub expected: Expectation,
+}
+
+/// Required result of a [`ReconstructionVector`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expectation {
+    /// The client ends up holding this document
+    Document(Value),
+    /// The client refuses the delivery, e.g. on a digest mismatch
+    Rejected,
+}
+
+/// The generation vectors shipped in `vectors/generation.json`.
+///
+/// # Errors
+///
+/// Returns an error if the vector file is malformed.
+pub fn generation_vectors() -> Result<Vec<GenerationVector>, serde_json::Error> {
+    serde_json::from_str(GENERATION)
+}
+
+/// The reconstruction vectors shipped in `vectors/reconstruction.json`.
+///
+/// # Errors
+///
+/// Returns an error if the vector file is malformed.
+pub fn reconstruction_vectors() -> Result<Vec<ReconstructionVector>, serde_json::Error> {
+    serde_json::from_str(RECONSTRUCTION)
+}
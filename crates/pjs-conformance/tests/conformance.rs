@@ -0,0 +1,96 @@
+//! Runs every shipped vector against every implementation in the workspace.
+
+use pjs_conformance::{
+    Capability, CaseResult, Outcome, generation_vectors, generators, reconstruction_vectors,
+    reconstructors, run_generation, run_reconstruction,
+};
+use std::collections::HashSet;
+
+fn assert_no_failures(results: &[CaseResult]) {
+    let failures: Vec<_> = results
+        .iter()
+        .filter(|result| matches!(result.outcome, Outcome::Failed(_)))
+        .map(ToString::to_string)
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn generators_conform() {
+    let vectors = generation_vectors().unwrap();
+    for generator in generators() {
+        assert_no_failures(&run_generation(generator.as_ref(), &vectors));
+    }
+}
+
+#[test]
+fn reconstructors_conform() {
+    let vectors = reconstruction_vectors().unwrap();
+    for reconstructor in reconstructors() {
+        assert_no_failures(&run_reconstruction(reconstructor.as_ref(), &vectors));
+    }
+}
+
+#[test]
+fn every_target_runs_the_baseline_vectors() {
+    let vectors = generation_vectors().unwrap();
+    for generator in generators() {
+        let results = run_generation(generator.as_ref(), &vectors);
+        for (vector, result) in vectors.iter().zip(&results) {
+            if vector.requires().is_empty() {
+                assert_eq!(result.outcome, Outcome::Passed, "{result}");
+                assert!(
+                    vector.frames.contains_key(generator.name()),
+                    "{} has no frames for {}",
+                    vector.name,
+                    generator.name()
+                );
+            }
+        }
+    }
+
+    let vectors = reconstruction_vectors().unwrap();
+    for reconstructor in reconstructors() {
+        let results = run_reconstruction(reconstructor.as_ref(), &vectors);
+        for (vector, result) in vectors.iter().zip(&results) {
+            if vector.requires.is_empty() {
+                assert_eq!(result.outcome, Outcome::Passed, "{result}");
+            }
+        }
+    }
+}
+
+#[test]
+fn unsupported_capabilities_are_skipped() {
+    let vectors = reconstruction_vectors().unwrap();
+    for reconstructor in reconstructors() {
+        for (vector, result) in vectors
+            .iter()
+            .zip(run_reconstruction(reconstructor.as_ref(), &vectors))
+        {
+            let missing: Vec<Capability> = vector
+                .requires
+                .iter()
+                .filter(|capability| !reconstructor.capabilities().contains(capability))
+                .copied()
+                .collect();
+            if !missing.is_empty() {
+                assert_eq!(result.outcome, Outcome::Skipped(missing), "{result}");
+            }
+        }
+    }
+}
+
+#[test]
+fn vector_names_are_unique() {
+    let generation = generation_vectors().unwrap();
+    let reconstruction = reconstruction_vectors().unwrap();
+    let mut names = HashSet::new();
+    for name in generation
+        .iter()
+        .map(|vector| &vector.name)
+        .chain(reconstruction.iter().map(|vector| &vector.name))
+    {
+        assert!(names.insert(name), "duplicate vector {name}");
+    }
+}
@@ -2,8 +2,9 @@
 
 use crate::application::dto::{PriorityDto, SessionIdDto, StreamIdDto};
 use crate::domain::{
-    aggregates::stream_session::SessionConfig, entities::stream::StreamConfig,
-    value_objects::JsonData,
+    aggregates::stream_session::SessionConfig,
+    entities::stream::StreamConfig,
+    value_objects::{ContentDigest, JsonData},
 };
 use serde::{Deserialize, Serialize};
 
@@ -53,6 +54,23 @@ pub struct GenerateFramesCommand {
     pub max_frames: usize,
 }
 
+/// Push a new version of a live stream's source document
+///
+/// The stream must have been started; it stays open for further updates
+/// until completed. Produces the frames carrying the diff against the
+/// previous version, highest priority first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateStreamDataCommand {
+    /// Identifier of the parent session.
+    pub session_id: SessionIdDto,
+    /// Identifier of the stream being updated.
+    pub stream_id: StreamIdDto,
+    /// New version of the stream's JSON document.
+    pub source_data: JsonData,
+    /// Maximum number of frames the diff is spread over.
+    pub max_frames: usize,
+}
+
 /// Complete a stream successfully
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompleteStreamCommand {
@@ -60,8 +78,17 @@ pub struct CompleteStreamCommand {
     pub session_id: SessionIdDto,
     /// Identifier of the stream being completed.
     pub stream_id: StreamIdDto,
-    /// Optional payload checksum used to verify integrity.
-    pub checksum: Option<String>,
+    /// Optional digest of the completed document used to verify integrity.
+    pub checksum: Option<ContentDigest>,
+}
+
+/// Cancel a stream on behalf of the client, releasing its stored frames
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelStreamCommand {
+    /// Identifier of the parent session.
+    pub session_id: SessionIdDto,
+    /// Identifier of the stream being cancelled.
+    pub stream_id: StreamIdDto,
 }
 
 /// Close session gracefully
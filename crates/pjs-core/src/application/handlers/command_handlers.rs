@@ -342,6 +342,56 @@ where
     }
 }
 
+impl<R, P, F> CommandHandlerGat<CancelStreamCommand> for SessionCommandHandler<R, P, F>
+where
+    R: StreamRepositoryGat + Send + Sync,
+    P: EventPublisherGat + Send + Sync,
+    F: FrameStoreGat + Send + Sync,
+{
+    type Response = ();
+
+    type HandleFuture<'a>
+        = impl std::future::Future<Output = ApplicationResult<Self::Response>> + Send + 'a
+    where
+        Self: 'a;
+
+    fn handle(&self, command: CancelStreamCommand) -> Self::HandleFuture<'_> {
+        async move {
+            let stream_id: StreamId = command.stream_id.into();
+            let events = self
+                .repository
+                .cancel_stream_atomic(command.session_id.into(), stream_id)
+                .await
+                .map_err(|e| match e {
+                    crate::domain::DomainError::SessionNotFound(_) => ApplicationError::NotFound(
+                        format!("Session {} not found", command.session_id),
+                    ),
+                    crate::domain::DomainError::StreamNotFound(_) => ApplicationError::NotFound(
+                        format!("Stream {} not found", command.stream_id),
+                    ),
+                    // Already completed, failed or cancelled.
+                    crate::domain::DomainError::InvalidStateTransition(message) => {
+                        ApplicationError::Conflict(message)
+                    }
+                    other => ApplicationError::Domain(other),
+                })?;
+
+            self.event_publisher
+                .publish_batch(events)
+                .await
+                .map_err(ApplicationError::Domain)?;
+
+            // Nobody will page through a cancelled stream's frames again.
+            self.frame_store
+                .delete_frames_for_stream(stream_id)
+                .await
+                .map_err(ApplicationError::Domain)?;
+
+            Ok(())
+        }
+    }
+}
+
 impl<R, P, F> CommandHandlerGat<GenerateFramesCommand> for SessionCommandHandler<R, P, F>
 where
     R: StreamRepositoryGat + Send + Sync,
@@ -421,6 +471,73 @@ where
     }
 }
 
+impl<R, P, F> CommandHandlerGat<UpdateStreamDataCommand> for SessionCommandHandler<R, P, F>
+where
+    R: StreamRepositoryGat + Send + Sync,
+    P: EventPublisherGat + Send + Sync,
+    F: FrameStoreGat + Send + Sync,
+{
+    type Response = Vec<Frame>;
+
+    type HandleFuture<'a>
+        = impl std::future::Future<Output = ApplicationResult<Self::Response>> + Send + 'a
+    where
+        Self: 'a;
+
+    fn handle(&self, command: UpdateStreamDataCommand) -> Self::HandleFuture<'_> {
+        async move {
+            CommandValidator::validate_update_stream_data(&command)
+                .map_err(|errors| ApplicationError::Validation(errors.join("; ")))?;
+
+            // Atomic per-session read-modify-write (#457): the diff must be
+            // taken against the version this update replaces.
+            let (frames, events) = self
+                .repository
+                .update_stream_data_atomic(
+                    command.session_id.into(),
+                    command.stream_id.into(),
+                    command.source_data,
+                    command.max_frames,
+                )
+                .await
+                .map_err(|e| match e {
+                    crate::domain::DomainError::SessionNotFound(_) => ApplicationError::NotFound(
+                        format!("Session {} not found", command.session_id),
+                    ),
+                    crate::domain::DomainError::StreamNotFound(_) => ApplicationError::NotFound(
+                        format!("Stream {} not found", command.stream_id),
+                    ),
+                    // Not started yet, or already finished: the caller's
+                    // request conflicts with the stream's lifecycle.
+                    crate::domain::DomainError::InvalidStreamState(message) => {
+                        ApplicationError::Conflict(message)
+                    }
+                    other => ApplicationError::Domain(other),
+                })?;
+
+            // Publish before any fallible I/O: see GenerateFramesCommand.
+            self.event_publisher
+                .publish_batch(events)
+                .await
+                .map_err(ApplicationError::Domain)?;
+
+            #[cfg(feature = "metrics")]
+            metrics::counter!("pjs_frames_total").increment(frames.len() as u64);
+
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            self.train_from_frames(command.session_id.into(), &frames)
+                .await;
+
+            self.frame_store
+                .append_frames(command.stream_id.into(), frames.clone())
+                .await
+                .map_err(ApplicationError::Domain)?;
+
+            Ok(frames)
+        }
+    }
+}
+
 impl<R, P, F> CommandHandlerGat<BatchGenerateFramesCommand> for SessionCommandHandler<R, P, F>
 where
     R: StreamRepositoryGat + Send + Sync,
@@ -579,6 +696,31 @@ impl CommandValidator {
         }
     }
 
+    /// Validate UpdateStreamDataCommand
+    pub fn validate_update_stream_data(
+        command: &UpdateStreamDataCommand,
+    ) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if command.source_data.is_null() {
+            errors.push("source_data cannot be null".to_string());
+        }
+
+        if command.max_frames == 0 {
+            errors.push("max_frames must be greater than 0".to_string());
+        }
+
+        if command.max_frames > MAX_FRAMES_PER_REQUEST {
+            errors.push(format!("max_frames cannot exceed {MAX_FRAMES_PER_REQUEST}"));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Validate GenerateFramesCommand
     pub fn validate_generate_frames(command: &GenerateFramesCommand) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
@@ -1027,7 +1169,10 @@ mod tests {
         let complete_stream_cmd = CompleteStreamCommand {
             session_id: session_id.into(),
             stream_id: stream_id.into(),
-            checksum: Some("abc123".to_string()),
+            checksum: Some(crate::domain::value_objects::ContentDigest::compute(
+                &serde_json::json!({"test": "data"}),
+                None,
+            )),
         };
 
         let result = handler.handle(complete_stream_cmd).await;
@@ -1323,6 +1468,166 @@ mod tests {
         assert_eq!(page.total_matching, frames.len());
     }
 
+    #[tokio::test]
+    async fn test_cancel_stream_releases_stored_frames() {
+        use crate::domain::ports::FrameStoreGat;
+        use crate::infrastructure::adapters::InMemoryFrameStore;
+
+        let repository = Arc::new(MockRepository::new());
+        let frame_store = Arc::new(InMemoryFrameStore::new());
+        let handler = SessionCommandHandler::with_stores(
+            repository.clone(),
+            Arc::new(MockEventPublisher),
+            Arc::new(crate::domain::ports::NoopDictionaryStore),
+            frame_store.clone(),
+        );
+
+        let session_id = handler
+            .handle(CreateSessionCommand {
+                config: SessionConfig::default(),
+                client_info: None,
+                user_agent: None,
+                ip_address: None,
+            })
+            .await
+            .unwrap();
+        let stream_id = handler
+            .handle(CreateStreamCommand {
+                session_id: session_id.into(),
+                source_data: serde_json::json!({"items": [1, 2, 3, 4]}).into(),
+                config: None,
+            })
+            .await
+            .unwrap();
+        handler
+            .handle(StartStreamCommand {
+                session_id: session_id.into(),
+                stream_id: stream_id.into(),
+            })
+            .await
+            .unwrap();
+        handler
+            .handle(GenerateFramesCommand {
+                session_id: session_id.into(),
+                stream_id: stream_id.into(),
+                priority_threshold: crate::application::dto::PriorityDto::new(1).unwrap(),
+                max_frames: 8,
+            })
+            .await
+            .unwrap();
+
+        let cancel = CancelStreamCommand {
+            session_id: session_id.into(),
+            stream_id: stream_id.into(),
+        };
+        handler.handle(cancel.clone()).await.unwrap();
+
+        let page = frame_store
+            .get_frames(stream_id, None, None, None)
+            .await
+            .unwrap();
+        assert!(page.frames.is_empty(), "cancelled stream keeps no frames");
+
+        let session = repository.find_session(session_id).await.unwrap().unwrap();
+        assert_eq!(session.stats().cancelled_streams, 1);
+        assert_eq!(session.stats().active_streams, 0);
+
+        // A stream can only be cancelled once.
+        let result = handler.handle(cancel).await;
+        assert!(matches!(result, Err(ApplicationError::Conflict(_))));
+
+        let result = handler
+            .handle(CancelStreamCommand {
+                session_id: session_id.into(),
+                stream_id: StreamId::new().into(),
+            })
+            .await;
+        assert!(matches!(result, Err(ApplicationError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_stream_data_streams_diff_frames() {
+        use crate::domain::ports::FrameStoreGat;
+        use crate::infrastructure::adapters::InMemoryFrameStore;
+
+        let repository = Arc::new(MockRepository::new());
+        let frame_store = Arc::new(InMemoryFrameStore::new());
+        let handler = SessionCommandHandler::with_stores(
+            repository.clone(),
+            Arc::new(MockEventPublisher),
+            Arc::new(crate::domain::ports::NoopDictionaryStore),
+            frame_store.clone(),
+        );
+
+        let session_id = handler
+            .handle(CreateSessionCommand {
+                config: SessionConfig::default(),
+                client_info: None,
+                user_agent: None,
+                ip_address: None,
+            })
+            .await
+            .unwrap();
+        let stream_id = handler
+            .handle(CreateStreamCommand {
+                session_id: session_id.into(),
+                source_data: serde_json::json!({"status": "ok", "load": 0.5}).into(),
+                config: None,
+            })
+            .await
+            .unwrap();
+
+        let update = |source_data: serde_json::Value| UpdateStreamDataCommand {
+            session_id: session_id.into(),
+            stream_id: stream_id.into(),
+            source_data: source_data.into(),
+            max_frames: 8,
+        };
+
+        // Updates need a started stream.
+        let result = handler
+            .handle(update(serde_json::json!({"status": "ok", "load": 0.7})))
+            .await;
+        assert!(matches!(result, Err(ApplicationError::Conflict(_))));
+
+        handler
+            .handle(StartStreamCommand {
+                session_id: session_id.into(),
+                stream_id: stream_id.into(),
+            })
+            .await
+            .unwrap();
+
+        let frames = handler
+            .handle(update(serde_json::json!({"status": "ok", "load": 0.7})))
+            .await
+            .unwrap();
+        assert_eq!(frames.len(), 1, "one changed field fits one frame");
+
+        let unchanged = handler
+            .handle(update(serde_json::json!({"status": "ok", "load": 0.7})))
+            .await
+            .unwrap();
+        assert!(unchanged.is_empty());
+
+        let page = frame_store
+            .get_frames(stream_id, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(page.frames.len(), 1);
+
+        let session = repository.find_session(session_id).await.unwrap().unwrap();
+        assert_eq!(session.streams()[&stream_id].version(), 2);
+
+        let invalid = handler
+            .handle(UpdateStreamDataCommand {
+                max_frames: 0,
+                ..update(serde_json::Value::Null)
+            })
+            .await;
+        assert!(matches!(invalid, Err(ApplicationError::Validation(_))));
+    }
+
     #[tokio::test]
     async fn test_generate_frames_publishes_events_before_frame_store_failure() {
         // Regression test: create_stream_patch_frames_atomic drains the
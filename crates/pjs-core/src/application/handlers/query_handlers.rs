@@ -4,11 +4,12 @@ use crate::{
     application::{ApplicationError, ApplicationResult, handlers::QueryHandlerGat, queries::*},
     domain::{
         SessionState,
-        entities::Stream,
+        entities::{Frame, Stream},
         ports::{
             FrameStoreGat, SessionPagination, SessionQueryCriteria, SortOrder as RepoSortOrder,
             StreamRepositoryGat, StreamStoreGat,
         },
+        value_objects::{JsonData, JsonPathQuery},
     },
 };
 use std::{marker::PhantomData, sync::Arc, time::Instant};
@@ -364,7 +365,7 @@ where
                 })?;
 
             // Validate stream exists within the session.
-            session.stream(query.stream_id.into()).ok_or_else(|| {
+            let stream = session.stream(query.stream_id.into()).ok_or_else(|| {
                 ApplicationError::NotFound(format!("Stream {} not found", query.stream_id))
             })?;
 
@@ -394,14 +395,47 @@ where
                 .await
                 .map_err(ApplicationError::Domain)?;
 
+            let frames = match &query.path_filter {
+                Some(path_filter) => {
+                    filter_frames_by_path(page.frames, path_filter, stream.source_data())
+                }
+                None => page.frames,
+            };
+
             Ok(FramesResponse {
-                frames: page.frames,
+                frames,
                 total_count: page.total_matching,
             })
         }
     }
 }
 
+/// Narrow a page of frames to the patches that touch a node `path_filter`
+/// selects in `document`: patches at a selected node, inside one, or
+/// replacing a value that contains one. Patch frames left empty are dropped;
+/// other frames pass through.
+///
+/// The query is evaluated once against the stream's current document, so
+/// filters (`[?@.public]`) reflect its latest version, and the page's
+/// `total_count` still counts frames before path filtering.
+fn filter_frames_by_path(
+    frames: Vec<Frame>,
+    path_filter: &JsonPathQuery,
+    document: Option<&JsonData>,
+) -> Vec<Frame> {
+    let selected = document.map_or_else(Vec::new, |document| path_filter.select_paths(document));
+    frames
+        .into_iter()
+        .filter_map(|frame| {
+            frame.retain_patches(|path| {
+                selected
+                    .iter()
+                    .any(|node| node == path || node.is_prefix_of(path) || path.is_prefix_of(node))
+            })
+        })
+        .collect()
+}
+
 /// Handler for system statistics
 #[derive(Debug)]
 pub struct SystemQueryHandler<R>
@@ -858,6 +892,7 @@ mod tests {
             since_sequence: None,
             priority_filter: None,
             limit: None,
+            path_filter: None,
         };
 
         let result: ApplicationResult<FramesResponse> =
@@ -885,6 +920,7 @@ mod tests {
             since_sequence: None,
             priority_filter: None,
             limit: None,
+            path_filter: None,
         };
 
         let result: ApplicationResult<FramesResponse> =
@@ -915,6 +951,7 @@ mod tests {
             since_sequence: None,
             priority_filter: None,
             limit: None,
+            path_filter: None,
         };
 
         let result = QueryHandlerGat::handle(&handler, query).await.unwrap();
@@ -964,6 +1001,7 @@ mod tests {
             since_sequence: None,
             priority_filter: None,
             limit: None,
+            path_filter: None,
         };
 
         let result = QueryHandlerGat::handle(&handler, query).await.unwrap();
@@ -1026,6 +1064,7 @@ mod tests {
             since_sequence: Some(1),
             priority_filter: Some(Priority::HIGH.into()),
             limit: Some(10),
+            path_filter: None,
         };
 
         let result = QueryHandlerGat::handle(&handler, query).await.unwrap();
@@ -1041,6 +1080,83 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_get_stream_frames_applies_path_filter() {
+        use crate::domain::{
+            entities::frame::FramePatch,
+            ports::FrameStoreGat,
+            value_objects::{JsonData, JsonPath, JsonPathQuery, Priority},
+        };
+        use crate::infrastructure::adapters::InMemoryFrameStore;
+
+        let session_repository = Arc::new(MockRepository::new());
+        let mut session = StreamSession::new(SessionConfig::default());
+        let _ = session.activate();
+        let session_id = session.id();
+        let stream_id = session
+            .create_stream(JsonData::from(serde_json::json!({
+                "posts": [
+                    {"title": "a", "public": true},
+                    {"title": "b", "public": false}
+                ],
+                "total": 2
+            })))
+            .unwrap();
+        session_repository.add_session(session);
+
+        let frame_store = Arc::new(InMemoryFrameStore::new());
+        let set = |path: &str| FramePatch::set(JsonPath::new(path).unwrap(), JsonData::Null);
+        let frames = vec![
+            crate::domain::entities::Frame::skeleton(stream_id, 0, JsonData::Null),
+            crate::domain::entities::Frame::patch(
+                stream_id,
+                1,
+                Priority::HIGH,
+                vec![set("$.posts[0].title"), set("$.posts[1].title")],
+            )
+            .unwrap(),
+            crate::domain::entities::Frame::patch(
+                stream_id,
+                2,
+                Priority::LOW,
+                vec![set("$.total")],
+            )
+            .unwrap(),
+        ];
+        frame_store.append_frames(stream_id, frames).await.unwrap();
+
+        let handler =
+            StreamQueryHandler::new(session_repository, Arc::new(MockStreamStore), frame_store);
+        let query = GetStreamFramesQuery {
+            session_id: session_id.into(),
+            stream_id: stream_id.into(),
+            since_sequence: None,
+            priority_filter: None,
+            limit: None,
+            path_filter: Some(JsonPathQuery::new("$.posts[?@.public == true].title").unwrap()),
+        };
+
+        let result = QueryHandlerGat::handle(&handler, query).await.unwrap();
+        // The skeleton passes through; the `$.total` frame has no matching
+        // patch left and is dropped.
+        assert_eq!(
+            result
+                .frames
+                .iter()
+                .map(crate::domain::entities::Frame::sequence)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        let JsonData::Array(patches) = result.frames[1].payload().get("patches").unwrap() else {
+            panic!("patches must be an array");
+        };
+        assert_eq!(patches.len(), 1);
+        assert_eq!(
+            patches[0].get("path"),
+            Some(&JsonData::String("$.posts[0].title".to_string()))
+        );
+    }
+
     #[tokio::test]
     async fn test_get_stream_frames_caps_limit_to_max() {
         use crate::domain::{
@@ -1082,6 +1198,7 @@ mod tests {
             since_sequence: None,
             priority_filter: None,
             limit: Some(999_999),
+            path_filter: None,
         };
 
         let result = QueryHandlerGat::handle(&handler, query).await.unwrap();
@@ -1513,9 +1630,9 @@ mod tests {
         // Session with a real byte-carrying patch frame batch.
         let mut heavy = make_session(None);
         let heavy_stream = heavy
-            .create_stream(JsonData::String(
-                "hello world payload, quite a few bytes here".to_owned(),
-            ))
+            .create_stream(JsonData::from(serde_json::json!({
+                "message": "hello world payload, quite a few bytes here"
+            })))
             .unwrap();
         heavy.start_stream(heavy_stream).unwrap();
         heavy
@@ -10,8 +10,8 @@ pub mod queries;
 pub mod shared;
 
 pub use commands::{
-    BatchGenerateFramesCommand, CloseSessionCommand, CompleteStreamCommand, CreateSessionCommand,
-    CreateStreamCommand, GenerateFramesCommand, StartStreamCommand,
+    BatchGenerateFramesCommand, CancelStreamCommand, CloseSessionCommand, CompleteStreamCommand,
+    CreateSessionCommand, CreateStreamCommand, GenerateFramesCommand, StartStreamCommand,
 };
 pub use queries::{
     FramesResponse, GetActiveSessionsQuery, GetSessionHealthQuery, GetSessionQuery,
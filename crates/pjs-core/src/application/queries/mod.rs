@@ -8,6 +8,7 @@ use crate::domain::{
         stream_session::{SessionHealth, SessionStats},
     },
     entities::{Frame, Stream},
+    value_objects::JsonPathQuery,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -66,6 +67,11 @@ pub struct GetStreamFramesQuery {
     pub priority_filter: Option<PriorityDto>,
     /// Maximum number of frames to return.
     pub limit: Option<usize>,
+    /// Return only patches touching a node this query selects in the
+    /// stream's current document; see
+    /// [`StreamQueryHandler`](crate::application::handlers::StreamQueryHandler).
+    #[serde(default)]
+    pub path_filter: Option<JsonPathQuery>,
 }
 
 /// Get session statistics and metrics
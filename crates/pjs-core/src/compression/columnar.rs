@@ -0,0 +1,470 @@
+//! Columnar encoding for homogeneous arrays of objects.
+//!
+//! List payloads are mostly arrays of same-shaped objects, where the repeated
+//! keys dominate the size. [`CompressionStrategy::Columnar`] transposes each
+//! such array into a table: its keys once, then one column of values per key.
+//!
+//! # Wire format
+//!
+//! A table replaces the array in place, as a single-member object keyed by
+//! the sentinel byte ([`DICT_SENTINEL`]):
+//!
+//! ```text
+//! {"\u{7F}": {"keys": ["id", "name"], "cols": [<column>, <column>]}}
+//! ```
+//!
+//! Each column holds one value per row, in row order, as one of:
+//!
+//! - a plain array `[v0, v1, ...]`, whose values are encoded recursively;
+//! - `{"dict": [distinct values], "idx": [i0, i1, ...]}`, for scalar columns
+//!   with repeated values;
+//! - `{"delta": [v0, v1 - v0, v2 - v1, ...]}`, for integer columns.
+//!
+//! Every choice is measured rather than modelled: a column takes whichever
+//! form serializes smallest, and an array only becomes a table when the table
+//! is smaller than the array. Object keys in the data that start with the
+//! sentinel are escaped with a second one, the same scheme dictionary strings
+//! use, so a table can never be confused with data. Tables carry everything
+//! needed to decode them, so each frame decodes on its own.
+//!
+//! [`CompressionStrategy::Columnar`]: super::CompressionStrategy::Columnar
+
+use super::DICT_SENTINEL;
+use crate::domain::{DomainError, DomainResult};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+
+/// Key of the single-member object that marks a table.
+const TABLE_KEY: &str = "\u{7F}";
+
+/// Encode every homogeneous array of at least `min_rows` objects in `data`
+/// as a table, wherever that makes it smaller.
+pub(crate) fn encode(data: &JsonValue, min_rows: usize) -> DomainResult<JsonValue> {
+    encode_value(data, min_rows.max(1))
+}
+
+/// Decode the tables in `data`, failing once the bytes re-expanded from table
+/// headers and column dictionaries exceed `max_expansion`.
+pub(crate) fn decode(data: &JsonValue, max_expansion: usize) -> DomainResult<JsonValue> {
+    Decoder {
+        remaining: max_expansion,
+    }
+    .decode_value(data)
+}
+
+fn encode_value(value: &JsonValue, min_rows: usize) -> DomainResult<JsonValue> {
+    match value {
+        JsonValue::Object(obj) => {
+            let mut encoded = Map::with_capacity(obj.len());
+            for (key, value) in obj {
+                encoded.insert(escape_key(key), encode_value(value, min_rows)?);
+            }
+            Ok(JsonValue::Object(encoded))
+        }
+        JsonValue::Array(arr) => {
+            let rows = arr
+                .iter()
+                .map(|row| encode_value(row, min_rows))
+                .collect::<DomainResult<Vec<_>>>()?;
+            let table = table_keys(arr, min_rows)
+                .map(|keys| encode_table(&keys, &rows))
+                .transpose()?;
+            let plain = JsonValue::Array(rows);
+            match table {
+                Some(table) if serialized_len(&table)? < serialized_len(&plain)? => Ok(table),
+                _ => Ok(plain),
+            }
+        }
+        _ => Ok(value.clone()),
+    }
+}
+
+/// The shared keys of `arr`, if it holds at least `min_rows` objects that
+/// all have the same, non-empty key set.
+fn table_keys(arr: &[JsonValue], min_rows: usize) -> Option<Vec<&String>> {
+    if arr.len() < min_rows {
+        return None;
+    }
+    let first = arr.first()?.as_object()?;
+    if first.is_empty() {
+        return None;
+    }
+    arr.iter()
+        .all(|row| {
+            row.as_object()
+                .is_some_and(|row| row.keys().eq(first.keys()))
+        })
+        .then(|| first.keys().collect())
+}
+
+/// Build a table from already-encoded `rows` sharing `keys`.
+fn encode_table(keys: &[&String], rows: &[JsonValue]) -> DomainResult<JsonValue> {
+    let mut columns = Vec::with_capacity(keys.len());
+    for key in keys {
+        let escaped = escape_key(key);
+        let values: Vec<JsonValue> = rows
+            .iter()
+            .map(|row| row.get(&escaped).cloned().unwrap_or(JsonValue::Null))
+            .collect();
+        columns.push(encode_column(values)?);
+    }
+
+    let mut table = Map::with_capacity(2);
+    table.insert(
+        "keys".to_string(),
+        JsonValue::Array(keys.iter().map(|&key| key.clone().into()).collect()),
+    );
+    table.insert("cols".to_string(), JsonValue::Array(columns));
+    let mut marker = Map::with_capacity(1);
+    marker.insert(TABLE_KEY.to_string(), JsonValue::Object(table));
+
+    Ok(JsonValue::Object(marker))
+}
+
+/// The smallest of a column's plain, dictionary and delta forms.
+fn encode_column(values: Vec<JsonValue>) -> DomainResult<JsonValue> {
+    let candidates = [dictionary_column(&values), delta_column(&values)];
+    let mut best = JsonValue::Array(values);
+    let mut best_len = serialized_len(&best)?;
+    for candidate in candidates.into_iter().flatten() {
+        let len = serialized_len(&candidate)?;
+        if len < best_len {
+            best = candidate;
+            best_len = len;
+        }
+    }
+    Ok(best)
+}
+
+/// `{"dict": [...], "idx": [...]}` for a column of scalars with repeats.
+fn dictionary_column(values: &[JsonValue]) -> Option<JsonValue> {
+    if values.iter().any(|v| v.is_object() || v.is_array()) {
+        return None;
+    }
+    let mut dict = Vec::new();
+    let mut positions: HashMap<String, u64> = HashMap::new();
+    let mut idx = Vec::with_capacity(values.len());
+    for value in values {
+        let next = dict.len() as u64;
+        let index = *positions.entry(value.to_string()).or_insert_with(|| {
+            dict.push(value.clone());
+            next
+        });
+        idx.push(JsonValue::from(index));
+    }
+    if dict.len() == values.len() {
+        return None;
+    }
+    let mut column = Map::with_capacity(2);
+    column.insert("dict".to_string(), JsonValue::Array(dict));
+    column.insert("idx".to_string(), JsonValue::Array(idx));
+    Some(JsonValue::Object(column))
+}
+
+/// `{"delta": [...]}` for a column of integers, each stored as its
+/// difference from the previous one.
+fn delta_column(values: &[JsonValue]) -> Option<JsonValue> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mut previous = 0i64;
+    let mut deltas = Vec::with_capacity(values.len());
+    for value in values {
+        let current = value.as_i64()?;
+        deltas.push(JsonValue::from(current.checked_sub(previous)?));
+        previous = current;
+    }
+    let mut column = Map::with_capacity(1);
+    column.insert("delta".to_string(), JsonValue::Array(deltas));
+    Some(JsonValue::Object(column))
+}
+
+fn escape_key(key: &str) -> String {
+    if key.starts_with(DICT_SENTINEL) {
+        format!("{DICT_SENTINEL}{key}")
+    } else {
+        key.to_string()
+    }
+}
+
+fn serialized_len(value: &JsonValue) -> DomainResult<usize> {
+    serde_json::to_string(value)
+        .map(|s| s.len())
+        .map_err(|e| DomainError::CompressionError(format!("JSON serialization failed: {e}")))
+}
+
+fn malformed(message: impl std::fmt::Display) -> DomainError {
+    DomainError::CompressionError(format!("malformed columnar table: {message}"))
+}
+
+/// Decoding state: the expansion budget left.
+struct Decoder {
+    remaining: usize,
+}
+
+impl Decoder {
+    /// Charge `bytes` of re-expanded output against the budget.
+    fn charge(&mut self, bytes: usize) -> DomainResult<()> {
+        self.remaining = self.remaining.checked_sub(bytes).ok_or_else(|| {
+            DomainError::CompressionError("columnar decompressed size exceeds maximum".to_string())
+        })?;
+        Ok(())
+    }
+
+    fn decode_value(&mut self, value: &JsonValue) -> DomainResult<JsonValue> {
+        match value {
+            JsonValue::Object(obj) => {
+                if obj.len() == 1
+                    && let Some(table) = obj.get(TABLE_KEY)
+                {
+                    return self.decode_table(table);
+                }
+                let mut decoded = Map::with_capacity(obj.len());
+                for (key, value) in obj {
+                    decoded.insert(unescape_key(key)?, self.decode_value(value)?);
+                }
+                Ok(JsonValue::Object(decoded))
+            }
+            JsonValue::Array(arr) => Ok(JsonValue::Array(
+                arr.iter()
+                    .map(|item| self.decode_value(item))
+                    .collect::<DomainResult<_>>()?,
+            )),
+            _ => Ok(value.clone()),
+        }
+    }
+
+    fn decode_table(&mut self, table: &JsonValue) -> DomainResult<JsonValue> {
+        let table = table
+            .as_object()
+            .filter(|table| table.len() == 2)
+            .ok_or_else(|| malformed("expected an object with `keys` and `cols`"))?;
+        let keys = table
+            .get("keys")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(|| malformed("missing `keys`"))?;
+        let columns = table
+            .get("cols")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(|| malformed("missing `cols`"))?;
+        if keys.is_empty() || keys.len() != columns.len() {
+            return Err(malformed(format!(
+                "{} keys for {} columns",
+                keys.len(),
+                columns.len()
+            )));
+        }
+        let keys = keys
+            .iter()
+            .map(|key| key.as_str().ok_or_else(|| malformed("non-string key")))
+            .collect::<DomainResult<Vec<_>>>()?;
+        let mut unique = keys.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        if unique.len() != keys.len() {
+            return Err(malformed("duplicate key"));
+        }
+
+        let mut decoded = Vec::with_capacity(columns.len());
+        for column in columns {
+            decoded.push(self.decode_column(column)?.into_iter());
+        }
+        let rows = decoded[0].len();
+        if decoded.iter().any(|column| column.len() != rows) {
+            return Err(malformed("columns have different lengths"));
+        }
+        let row_key_bytes: usize = keys.iter().map(|key| key.len()).sum();
+        self.charge(row_key_bytes.saturating_mul(rows))?;
+
+        let mut out = Vec::with_capacity(rows);
+        for _ in 0..rows {
+            let mut row = Map::with_capacity(keys.len());
+            for (key, column) in keys.iter().zip(decoded.iter_mut()) {
+                let value = column.next().ok_or_else(|| malformed("short column"))?;
+                row.insert((*key).to_string(), value);
+            }
+            out.push(JsonValue::Object(row));
+        }
+        Ok(JsonValue::Array(out))
+    }
+
+    fn decode_column(&mut self, column: &JsonValue) -> DomainResult<Vec<JsonValue>> {
+        match column {
+            JsonValue::Array(values) => values.iter().map(|v| self.decode_value(v)).collect(),
+            JsonValue::Object(obj) if obj.len() == 2 => {
+                let dict = obj
+                    .get("dict")
+                    .and_then(JsonValue::as_array)
+                    .ok_or_else(|| malformed("missing `dict`"))?;
+                let idx = obj
+                    .get("idx")
+                    .and_then(JsonValue::as_array)
+                    .ok_or_else(|| malformed("missing `idx`"))?;
+                if dict.iter().any(|v| v.is_object() || v.is_array()) {
+                    return Err(malformed("non-scalar dictionary value"));
+                }
+                let sizes = dict
+                    .iter()
+                    .map(serialized_len)
+                    .collect::<DomainResult<Vec<_>>>()?;
+                idx.iter()
+                    .map(|index| {
+                        let index = index
+                            .as_u64()
+                            .and_then(|index| usize::try_from(index).ok())
+                            .filter(|&index| index < dict.len())
+                            .ok_or_else(|| {
+                                malformed(format!("invalid dictionary index {index}"))
+                            })?;
+                        self.charge(sizes[index])?;
+                        Ok(dict[index].clone())
+                    })
+                    .collect()
+            }
+            JsonValue::Object(obj) if obj.len() == 1 => {
+                let deltas = obj
+                    .get("delta")
+                    .and_then(JsonValue::as_array)
+                    .ok_or_else(|| malformed("missing `delta`"))?;
+                let mut current = 0i64;
+                deltas
+                    .iter()
+                    .map(|delta| {
+                        current = delta
+                            .as_i64()
+                            .and_then(|delta| current.checked_add(delta))
+                            .ok_or_else(|| malformed(format!("invalid delta {delta}")))?;
+                        Ok(JsonValue::from(current))
+                    })
+                    .collect()
+            }
+            _ => Err(malformed("unknown column encoding")),
+        }
+    }
+}
+
+/// Reverse [`escape_key`]; a sentinel-led key that is not escaped was never
+/// produced by the encoder.
+fn unescape_key(key: &str) -> DomainResult<String> {
+    match key.strip_prefix(DICT_SENTINEL) {
+        None => Ok(key.to_string()),
+        Some(rest) if rest.starts_with(DICT_SENTINEL) => Ok(rest.to_string()),
+        Some(_) => Err(malformed(format!("unescaped sentinel key {key:?}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BUDGET: usize = 10_485_760;
+
+    fn users(count: usize) -> JsonValue {
+        JsonValue::Array(
+            (0..count)
+                .map(|i| {
+                    json!({
+                        "id": 1000 + i,
+                        "name": format!("user {i}"),
+                        "role": if i % 5 == 0 { "admin" } else { "member" },
+                        "score": i as f64 / 4.0,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_homogeneous_array_becomes_smaller_table_and_round_trips() {
+        let data = json!({"users": users(20), "total": 20});
+        let encoded = encode(&data, 3).unwrap();
+
+        let table = &encoded["users"][TABLE_KEY];
+        assert_eq!(table["keys"], json!(["id", "name", "role", "score"]));
+        assert!(
+            table["cols"][0].get("delta").is_some(),
+            "ids are delta coded"
+        );
+        assert!(
+            table["cols"][2].get("dict").is_some(),
+            "roles are dictionary coded"
+        );
+        assert!(table["cols"][1].is_array(), "unique names stay plain");
+        assert!(encoded.to_string().len() < data.to_string().len() * 2 / 3);
+
+        assert_eq!(decode(&encoded, BUDGET).unwrap(), data);
+    }
+
+    #[test]
+    fn test_arrays_that_do_not_pay_off_stay_plain() {
+        let data = json!({
+            "mixed": [{"a": 1}, {"b": 2}, {"a": 3}],
+            "short": [{"a": 1}, {"a": 2}],
+            "empty": [{}, {}, {}],
+            "scalars": [1, 2, 3],
+        });
+        assert_eq!(encode(&data, 2).unwrap(), data);
+        assert_eq!(
+            encode(&json!({"rows": users(5)}), 10).unwrap()["rows"],
+            users(5)
+        );
+    }
+
+    #[test]
+    fn test_nested_tables_and_sentinel_keys_round_trip() {
+        let data = json!([
+            {"\u{7F}": "a", "items": [{"sku": "x-1", "qty": 1}, {"sku": "x-2", "qty": 2}, {"sku": "x-3", "qty": 3}]},
+            {"\u{7F}": "b", "items": [{"sku": "y-1", "qty": 1}, {"sku": "y-2", "qty": 1}, {"sku": "y-3", "qty": 1}]},
+            {"\u{7F}": "c", "items": []},
+        ]);
+        let encoded = encode(&data, 2).unwrap();
+        assert_eq!(decode(&encoded, BUDGET).unwrap(), data);
+
+        let lone = json!({"\u{7F}": {"keys": ["a"], "cols": [[1]]}});
+        assert_eq!(decode(&encode(&lone, 2).unwrap(), BUDGET).unwrap(), lone);
+    }
+
+    #[test]
+    fn test_delta_column_handles_extreme_integers() {
+        let data = json!([{"n": i64::MIN}, {"n": i64::MAX}, {"n": 0}, {"n": u64::MAX}]);
+        assert_eq!(decode(&encode(&data, 2).unwrap(), BUDGET).unwrap(), data);
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_tables() {
+        for table in [
+            json!({"keys": ["a"], "cols": []}),
+            json!({"keys": ["a", "a"], "cols": [[1], [2]]}),
+            json!({"keys": ["a", "b"], "cols": [[1], [1, 2]]}),
+            json!({"keys": ["a"], "cols": [{"dict": ["x"], "idx": [1]}]}),
+            json!({"keys": ["a"], "cols": [{"dict": [[1]], "idx": [0]}]}),
+            json!({"keys": ["a"], "cols": [{"delta": [i64::MAX, 1]}]}),
+            json!({"keys": ["a"], "cols": [{"rle": [1]}]}),
+            json!({"keys": [1], "cols": [[1]]}),
+        ] {
+            let encoded = json!({ TABLE_KEY: table });
+            assert!(
+                matches!(
+                    decode(&encoded, BUDGET),
+                    Err(DomainError::CompressionError(_))
+                ),
+                "{encoded}"
+            );
+        }
+        assert!(decode(&json!({"\u{7F}x": 1}), BUDGET).is_err());
+    }
+
+    #[test]
+    fn test_decode_bounds_dictionary_expansion() {
+        let encoded = json!({ TABLE_KEY: {
+            "keys": ["a"],
+            "cols": [{"dict": ["x".repeat(1000)], "idx": vec![0; 100]}],
+        }});
+        assert!(decode(&encoded, 200_000).is_ok());
+        assert!(matches!(
+            decode(&encoded, 50_000),
+            Err(DomainError::CompressionError(_))
+        ));
+    }
+}
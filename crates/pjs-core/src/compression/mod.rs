@@ -3,7 +3,9 @@
 //! Implements intelligent compression strategies based on JSON schema analysis
 //! to optimize bandwidth usage while maintaining streaming capabilities.
 
+pub(crate) mod columnar;
 pub mod secure;
+pub mod shared_context;
 
 #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
 pub mod zstd;
@@ -23,7 +25,8 @@ use std::collections::HashMap;
 pub(crate) const DICT_SENTINEL: char = '\u{7F}';
 
 /// Configuration constants for compression algorithms
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct CompressionConfig {
     /// Minimum array length for pattern analysis
     pub min_array_length: usize,
@@ -80,6 +83,11 @@ pub struct CompressionConfig {
     pub min_compression_potential: f32,
     /// Minimum array size for numeric sequence analysis
     pub min_numeric_sequence_size: usize,
+    /// Let [`SchemaAnalyzer`] select [`CompressionStrategy::Columnar`]. Off by
+    /// default; an explicit [`SchemaCompressor::with_strategy`] works either way.
+    pub columnar: bool,
+    /// Minimum number of objects in an array for columnar encoding
+    pub min_columnar_rows: usize,
 }
 
 impl Default for CompressionConfig {
@@ -95,6 +103,8 @@ impl Default for CompressionConfig {
             run_length_threshold: 20.0,
             min_compression_potential: 0.4,
             min_numeric_sequence_size: 3,
+            columnar: false,
+            min_columnar_rows: 3,
         }
     }
 }
@@ -186,6 +196,12 @@ pub enum CompressionStrategy {
         /// Per-field base values used for the delta-encoding pass.
         numeric_deltas: HashMap<String, f64>,
     },
+    /// Columnar encoding for homogeneous arrays of objects: each array whose
+    /// elements share one key set becomes its keys plus one column per key,
+    /// each column dictionary-, delta- or plain-encoded. Tables are carried
+    /// in-band, so every frame decodes on its own; see the `columnar` module
+    /// for the wire format.
+    Columnar,
 }
 
 /// Schema analyzer for determining optimal compression strategy
@@ -197,6 +213,8 @@ pub struct SchemaAnalyzer {
     numeric_fields: HashMap<String, NumericStats>,
     /// String repetition analysis
     string_repetitions: HashMap<String, u32>,
+    /// Modelled wire-byte saving of columnar encoding
+    columnar_savings: i64,
     /// Configuration for compression algorithms
     config: CompressionConfig,
 }
@@ -221,6 +239,7 @@ impl SchemaAnalyzer {
             patterns: HashMap::new(),
             numeric_fields: HashMap::new(),
             string_repetitions: HashMap::new(),
+            columnar_savings: 0,
             config: CompressionConfig::default(),
         }
     }
@@ -231,6 +250,7 @@ impl SchemaAnalyzer {
             patterns: HashMap::new(),
             numeric_fields: HashMap::new(),
             string_repetitions: HashMap::new(),
+            columnar_savings: 0,
             config,
         }
     }
@@ -241,6 +261,7 @@ impl SchemaAnalyzer {
         self.patterns.clear();
         self.numeric_fields.clear();
         self.string_repetitions.clear();
+        self.columnar_savings = 0;
 
         // Perform deep analysis
         self.analyze_recursive(data, "")?;
@@ -303,6 +324,10 @@ impl SchemaAnalyzer {
                 })
                 .count();
 
+            if matching_count == arr.len() && arr.len() >= self.config.min_columnar_rows {
+                self.columnar_savings += columnar_savings(first.keys(), arr.len());
+            }
+
             if matching_count > self.config.min_frequency_count as usize {
                 let info = PatternInfo {
                     frequency: matching_count as u32,
@@ -433,6 +458,15 @@ impl SchemaAnalyzer {
             }
         }
 
+        // A table subsumes per-column dictionaries, so prefer it whenever its
+        // modelled saving beats the value dictionary's.
+        if self.config.columnar
+            && self.columnar_savings >= self.config.min_net_savings as i64
+            && self.columnar_savings > dict_net_savings
+        {
+            return Ok(CompressionStrategy::Columnar);
+        }
+
         // Choose strategy based on scores
         match (
             string_dict_selected,
@@ -467,6 +501,19 @@ impl SchemaAnalyzer {
     }
 }
 
+/// Modelled wire-byte saving of encoding `rows` objects sharing `keys` as a
+/// table: every row but one stops repeating each `"key":` and its separator,
+/// and sheds its braces, against one bracket pair and separator per column
+/// plus a fixed table envelope. Like [`build_dictionary`]'s model, this only
+/// drives selection; the encoder itself keeps whichever form measures smaller.
+fn columnar_savings<'a>(keys: impl Iterator<Item = &'a String>, rows: usize) -> i64 {
+    let (key_bytes, columns) = keys.fold((0i64, 0i64), |(bytes, count), key| {
+        (bytes + key.len() as i64 + 4, count + 1)
+    });
+    let rows = rows as i64;
+    (rows - 1) * key_bytes + 2 * rows - 3 * columns - 26
+}
+
 /// Number of base-10 digits in `n`'s decimal representation (`0` has 1 digit).
 fn decimal_digits(n: u16) -> usize {
     n.to_string().len()
@@ -678,9 +725,25 @@ impl SchemaCompressor {
                 string_dict,
                 numeric_deltas,
             } => self.compress_hybrid(data, string_dict, numeric_deltas),
+
+            CompressionStrategy::Columnar => self.compress_columnar(data),
         }
     }
 
+    /// Columnar encoding of homogeneous object arrays
+    fn compress_columnar(&self, data: &JsonValue) -> DomainResult<CompressedData> {
+        let metadata = HashMap::new();
+        let compressed = columnar::encode(data, self.config.min_columnar_rows)?;
+        let compressed_size = wire_size(&compressed, &metadata)?;
+
+        Ok(CompressedData {
+            strategy: self.strategy.clone(),
+            compressed_size,
+            data: compressed,
+            compression_metadata: metadata,
+        })
+    }
+
     /// Dictionary-based compression
     fn compress_with_dictionary(
         &self,
@@ -1310,6 +1373,7 @@ mod tests {
                 string_dict: dictionary.clone(),
                 numeric_deltas: base_values.clone(),
             },
+            CompressionStrategy::Columnar,
         ] {
             let compressor = SchemaCompressor::with_strategy(strategy);
             let result = compressor.compress(&data).unwrap();
@@ -1322,6 +1386,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_schema_analyzer_selects_columnar_only_when_enabled() {
+        let data = json!({
+            "users": (0..8).map(|i| json!({
+                "name": format!("user {i}"),
+                "role": "admin",
+                "status": "active",
+                "department": "engineering",
+            })).collect::<Vec<_>>()
+        });
+
+        let strategy = SchemaAnalyzer::new().analyze(&data).unwrap();
+        assert!(matches!(strategy, CompressionStrategy::Dictionary { .. }));
+
+        let config = CompressionConfig {
+            columnar: true,
+            ..Default::default()
+        };
+        let strategy = SchemaAnalyzer::with_config(config.clone())
+            .analyze(&data)
+            .unwrap();
+        assert_eq!(strategy, CompressionStrategy::Columnar);
+
+        // Too few rows to qualify: falls back to the value-level strategies.
+        let few = json!({"users": data["users"].as_array().unwrap()[..2].to_vec()});
+        let strategy = SchemaAnalyzer::with_config(config).analyze(&few).unwrap();
+        assert_ne!(strategy, CompressionStrategy::Columnar);
+    }
+
+    #[test]
+    fn test_columnar_compression_beats_dictionary_on_object_arrays() {
+        let data = json!({
+            "products": (0..50).map(|i| json!({
+                "id": 1000 + i,
+                "name": format!("Product {i}"),
+                "category": (["Electronics", "Books", "Garden"][i % 3]),
+                "in_stock": i % 4 != 0,
+            })).collect::<Vec<_>>()
+        });
+        let original_size = serde_json::to_string(&data).unwrap().len();
+
+        let dictionary = match SchemaAnalyzer::new().analyze(&data).unwrap() {
+            CompressionStrategy::Dictionary { dictionary } => dictionary,
+            other => panic!("expected dictionary strategy, got {other:?}"),
+        };
+        let by_dictionary =
+            SchemaCompressor::with_strategy(CompressionStrategy::Dictionary { dictionary })
+                .compress(&data)
+                .unwrap();
+        let by_columns = SchemaCompressor::with_strategy(CompressionStrategy::Columnar)
+            .compress(&data)
+            .unwrap();
+
+        assert!(by_columns.compression_metadata.is_empty());
+        assert!(by_columns.compressed_size < by_dictionary.compressed_size);
+        assert!(
+            by_columns.compression_ratio(original_size) < 0.5,
+            "got {} of {original_size} bytes",
+            by_columns.compressed_size
+        );
+    }
+
     #[test]
     fn test_build_dictionary_caps_index_at_u16_max_without_overflow() {
         // Regression test for issue #333 C3: the dictionary index is a `u16`. Before the fix,
@@ -8,10 +8,11 @@
 //!
 //! [`SecureCompressor::decompress_protected`] and [`SecureCompressor::decompress_nested`] route
 //! decompression through `CompressionBombProtector`, which streams the decoder output and aborts
-//! if decompressed size or ratio exceeds configured limits. These methods are fully implemented
-//! and tested, but currently have no production callers in this codebase. The only live usage
-//! of [`SecureCompressor`] today is outbound compression via [`SecureCompressor::compress`],
-//! which compresses trusted server output without decompression.
+//! if decompressed size or ratio exceeds configured limits. The same protected decoding backs
+//! [`SecureDecompressionContext::decompress_layers`], which the HTTP `JsonBody` extractor uses to
+//! decode request bodies sent with `Content-Encoding`. A read error caused by a breached limit
+//! surfaces as [`Error::SecurityError`]; input that simply does not decode surfaces as
+//! [`Error::CompressionError`].
 //!
 //! # In-process only
 //!
@@ -64,6 +65,11 @@ pub enum ByteCodec {
     ///
     /// Requires `feature = "compression"`.
     Brotli,
+    /// Zstandard (RFC 8878). Self-identifying via its frame magic.
+    ///
+    /// Requires `feature = "compression"` on a non-`wasm32` target.
+    #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+    Zstd,
     /// Trained zstd dictionary compression.
     ///
     /// A single `Arc<ZstdDictionary>` is the canonical sharing primitive. The inner
@@ -80,23 +86,41 @@ pub enum ByteCodec {
     ZstdDict(std::sync::Arc<crate::compression::zstd::ZstdDictionary>),
 }
 
+impl ByteCodec {
+    /// Short codec name for logs and error messages; unlike `Debug`, never
+    /// includes dictionary bytes.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Deflate => "deflate",
+            Self::Gzip => "gzip",
+            Self::Brotli => "brotli",
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            Self::Zstd => "zstd",
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            Self::ZstdDict(_) => "zstd-dict",
+        }
+    }
+}
+
 /// Quality knob for byte-level codecs.
 ///
-/// Maps to codec-specific levels: deflate 1/6/9 and brotli quality 1/5/11.
+/// Maps to codec-specific levels: deflate 1/6/9, brotli quality 1/5/11 and
+/// zstd level 1/3/19.
 #[derive(Debug, Clone, Copy, Default)]
 pub enum CompressionQuality {
-    /// Speed-optimised: deflate level 1, brotli quality 1.
+    /// Speed-optimised: deflate level 1, brotli quality 1, zstd level 1.
     Fast,
-    /// Balanced speed/ratio (default): deflate level 6, brotli quality 5.
+    /// Balanced speed/ratio (default): deflate level 6, brotli quality 5, zstd level 3.
     #[default]
     Balanced,
-    /// Maximum ratio: deflate level 9, brotli quality 11.
+    /// Maximum ratio: deflate level 9, brotli quality 11, zstd level 19.
     Best,
 }
 
 impl CompressionQuality {
     #[cfg(feature = "compression")]
-    fn flate2_level(self) -> flate2::Compression {
+    pub(crate) fn flate2_level(self) -> flate2::Compression {
         match self {
             Self::Fast => flate2::Compression::fast(),
             Self::Balanced => flate2::Compression::default(),
@@ -105,13 +129,22 @@ impl CompressionQuality {
     }
 
     #[cfg(feature = "compression")]
-    fn brotli_quality(self) -> i32 {
+    pub(crate) fn brotli_quality(self) -> i32 {
         match self {
             Self::Fast => 1,
             Self::Balanced => 5,
             Self::Best => 11,
         }
     }
+
+    #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+    pub(crate) fn zstd_level(self) -> i32 {
+        match self {
+            Self::Fast => 1,
+            Self::Balanced => crate::compression::zstd::DEFAULT_LEVEL,
+            Self::Best => 19,
+        }
+    }
 }
 
 /// Compressed bytes with security metadata and codec identification.
@@ -214,7 +247,12 @@ impl SecureCompressor {
     pub fn decompress_protected(&self, compressed: &SecureCompressedData) -> Result<Vec<u8>> {
         self.detector
             .validate_pre_decompression(compressed.data.len())?;
-        self.decode_with_protection(&compressed.data, compressed.codec.clone(), None)
+        Self::decode_with_protection(
+            &self.detector,
+            &compressed.data,
+            compressed.codec.clone(),
+            None,
+        )
     }
 
     /// Decompress nested/chained compression with depth tracking.
@@ -228,7 +266,12 @@ impl SecureCompressor {
     ) -> Result<Vec<u8>> {
         self.detector
             .validate_pre_decompression(compressed.data.len())?;
-        self.decode_with_protection(&compressed.data, compressed.codec.clone(), Some(depth))
+        Self::decode_with_protection(
+            &self.detector,
+            &compressed.data,
+            compressed.codec.clone(),
+            Some(depth),
+        )
     }
 
     /// Encode `data` with the configured codec. Returns compressed bytes only.
@@ -271,6 +314,12 @@ impl SecureCompressor {
                 Ok(out)
             }
 
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            ByteCodec::Zstd => {
+                zstd::stream::encode_all(Cursor::new(data), self.quality.zstd_level())
+                    .map_err(|e| Error::CompressionError(format!("zstd encode: {e}")))
+            }
+
             #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
             ByteCodec::ZstdDict(dict) => {
                 crate::compression::zstd::ZstdDictCompressor::compress(data, dict.as_ref())
@@ -287,7 +336,7 @@ impl SecureCompressor {
     ///
     /// `depth` is `Some(n)` for nested decompression (depth-limited) or `None` for a flat call.
     fn decode_with_protection(
-        &self,
+        detector: &CompressionBombDetector,
         data: &[u8],
         codec: ByteCodec,
         depth: Option<usize>,
@@ -298,44 +347,54 @@ impl SecureCompressor {
             ($decoder:expr) => {{
                 let compressed_size = data.len();
                 let mut out = Vec::new();
-                let result = if let Some(d) = depth {
+                let (result, stats) = if let Some(d) = depth {
                     let mut protector =
-                        self.detector
-                            .protect_nested_reader($decoder, compressed_size, d)?;
+                        detector.protect_nested_reader($decoder, compressed_size, d)?;
                     let r = protector.read_to_end(&mut out);
                     let stats = protector.stats();
-                    self.log_decompression_stats(&stats);
+                    log_decompression_stats(&stats);
                     if stats.compression_depth > 0 {
                         warn!(
                             "Nested decompression detected at depth {}",
                             stats.compression_depth
                         );
                     }
-                    r
+                    (r, stats)
                 } else {
-                    let mut protector = self.detector.protect_reader($decoder, compressed_size);
+                    let mut protector = detector.protect_reader($decoder, compressed_size);
                     let r = protector.read_to_end(&mut out);
                     let stats = protector.stats();
-                    self.log_decompression_stats(&stats);
-                    r
+                    log_decompression_stats(&stats);
+                    (r, stats)
                 };
                 match result {
                     Ok(_) => {
-                        self.detector.validate_result(compressed_size, out.len())?;
+                        detector.validate_result(compressed_size, out.len())?;
                         Ok(out)
                     }
-                    Err(e) => {
+                    // A limit breach and undecodable input both surface as read
+                    // errors; only the former is a security event.
+                    Err(e)
+                        if detector
+                            .validate_result(compressed_size, stats.decompressed_size)
+                            .is_err() =>
+                    {
                         warn!("Decompression failed: {}", e);
                         Err(Error::SecurityError(format!(
                             "Protected decompression failed: {}",
                             e
                         )))
                     }
+                    Err(e) => Err(Error::CompressionError(format!(
+                        "{} decode: {}",
+                        codec.name(),
+                        e
+                    ))),
                 }
             }};
         }
 
-        match codec {
+        match &codec {
             ByteCodec::None => run!(Cursor::new(data)),
 
             #[cfg(feature = "compression")]
@@ -347,6 +406,13 @@ impl SecureCompressor {
             #[cfg(feature = "compression")]
             ByteCodec::Brotli => run!(brotli::Decompressor::new(Cursor::new(data), 4096)),
 
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            ByteCodec::Zstd => {
+                let decoder = zstd::stream::read::Decoder::new(Cursor::new(data))
+                    .map_err(|e| Error::CompressionError(format!("zstd decoder init: {e}")))?;
+                run!(decoder)
+            }
+
             // ZstdDict uses the streaming decoder so every decompressed byte
             // passes through the CompressionBombProtector's read loop (run!).
             // Bulk `zstd::bulk::Decompressor::decompress` is intentionally
@@ -367,13 +433,13 @@ impl SecureCompressor {
             ),
         }
     }
+}
 
-    fn log_decompression_stats(&self, stats: &CompressionStats) {
-        info!(
-            "Decompression stats: {}B -> {}B (ratio: {:.2}x, depth: {})",
-            stats.compressed_size, stats.decompressed_size, stats.ratio, stats.compression_depth
-        );
-    }
+fn log_decompression_stats(stats: &CompressionStats) {
+    info!(
+        "Decompression stats: {}B -> {}B (ratio: {:.2}x, depth: {})",
+        stats.compressed_size, stats.decompressed_size, stats.ratio, stats.compression_depth
+    );
 }
 
 /// Secure decompression context for streaming operations.
@@ -440,6 +506,53 @@ impl SecureDecompressionContext {
         }
     }
 
+    /// Undo a chain of encodings listed in the order they were applied — the
+    /// order of an HTTP `Content-Encoding` header.
+    ///
+    /// Layers are removed last to first, each through a bomb-protected reader
+    /// one level deeper than the one before, so a chain deeper than
+    /// `max_compression_depth` is rejected and every layer's output is held to
+    /// the size and ratio limits. The call counts as one of the context's
+    /// concurrent streams while it runs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::SecurityError`] if a limit is exceeded or no stream
+    /// slot is free, and [`Error::CompressionError`] if a layer does not
+    /// decode.
+    pub fn decompress_layers(&mut self, data: &[u8], codecs: &[ByteCodec]) -> Result<Vec<u8>> {
+        if self.active_streams >= self.max_concurrent_streams {
+            return Err(Error::SecurityError(format!(
+                "Too many concurrent decompression streams: {}/{}",
+                self.active_streams, self.max_concurrent_streams
+            )));
+        }
+        self.active_streams += 1;
+
+        let mut decoded = data.to_vec();
+        let mut result = Ok(());
+        for (layer, codec) in codecs.iter().rev().enumerate() {
+            result = self
+                .detector
+                .validate_pre_decompression(decoded.len())
+                .and_then(|()| {
+                    SecureCompressor::decode_with_protection(
+                        &self.detector,
+                        &decoded,
+                        codec.clone(),
+                        Some(self.current_depth + layer),
+                    )
+                })
+                .map(|output| decoded = output);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.active_streams -= 1;
+        result.map(|()| decoded)
+    }
+
     /// Get current context statistics.
     pub fn stats(&self) -> DecompressionContextStats {
         DecompressionContextStats {
@@ -624,8 +737,8 @@ mod tests {
 
             let result = c.decompress_protected(&compressed);
             assert!(
-                result.is_err(),
-                "wrong codec must produce an error, not garbage"
+                matches!(result, Err(Error::CompressionError(_))),
+                "wrong codec must produce a decode error, not garbage"
             );
         }
 
@@ -651,10 +764,68 @@ mod tests {
             let strict_compressor = SecureCompressor::new(detector, ByteCodec::Gzip);
             let result = strict_compressor.decompress_protected(&compressed);
             assert!(
-                result.is_err(),
+                matches!(result, Err(Error::SecurityError(_))),
                 "bomb detector must stop oversized decompression"
             );
         }
+
+        #[test]
+        fn test_context_decompresses_layers_in_reverse_order() {
+            let data = repetitive_json();
+            let gzip = SecureCompressor::with_default_security(ByteCodec::Gzip)
+                .compress(&data)
+                .unwrap();
+            let both = SecureCompressor::with_default_security(ByteCodec::Brotli)
+                .compress(&gzip.data)
+                .unwrap();
+
+            let mut context =
+                SecureDecompressionContext::new(CompressionBombDetector::default(), 1);
+            let decoded = context
+                .decompress_layers(&both.data, &[ByteCodec::Gzip, ByteCodec::Brotli])
+                .unwrap();
+            assert_eq!(decoded, data);
+            assert_eq!(context.stats().active_streams, 0);
+
+            assert!(matches!(
+                context.decompress_layers(&both.data, &[ByteCodec::Brotli, ByteCodec::Gzip]),
+                Err(Error::CompressionError(_))
+            ));
+        }
+
+        #[test]
+        fn test_context_limits_layer_depth_and_size() {
+            let data = repetitive_json();
+            let gzip = SecureCompressor::with_default_security(ByteCodec::Gzip)
+                .compress(&data)
+                .unwrap();
+
+            let mut shallow = SecureDecompressionContext::new(
+                CompressionBombDetector::new(CompressionBombConfig {
+                    max_compression_depth: 0,
+                    ..Default::default()
+                }),
+                1,
+            );
+            assert!(matches!(
+                shallow.decompress_layers(&gzip.data, &[ByteCodec::None, ByteCodec::Gzip]),
+                Err(Error::SecurityError(_))
+            ));
+
+            let mut small = SecureDecompressionContext::new(
+                CompressionBombDetector::new(CompressionBombConfig {
+                    max_decompressed_size: 200,
+                    check_interval_bytes: 64,
+                    ..Default::default()
+                }),
+                1,
+            );
+            assert!(matches!(
+                small.decompress_layers(&gzip.data, &[ByteCodec::Gzip]),
+                Err(Error::SecurityError(_))
+            ));
+            assert_eq!(small.stats().active_streams, 0);
+        }
     }
 
     #[test]
@@ -768,6 +939,19 @@ mod tests {
             ZstdDictCompressor::train(&samples, MAX_DICT_SIZE).unwrap()
         }
 
+        #[test]
+        fn test_zstd_roundtrip_via_secure_compressor() {
+            let compressor = SecureCompressor::with_default_security(ByteCodec::Zstd);
+            let data = repetitive_json();
+
+            let compressed = compressor.compress(&data).unwrap();
+            assert_eq!(compressed.codec, ByteCodec::Zstd);
+            assert!(compressed.data.len() < data.len(), "zstd must reduce size");
+
+            let decompressed = compressor.decompress_protected(&compressed).unwrap();
+            assert_eq!(decompressed, data);
+        }
+
         #[test]
         fn test_zstd_dict_roundtrip_via_secure_compressor() {
             let dict = Arc::new(trained_dict());
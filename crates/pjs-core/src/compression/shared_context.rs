@@ -0,0 +1,668 @@
+//! Byte-level compression with one codec window shared across a stream's frames.
+//!
+//! [`SecureCompressor`](super::secure::SecureCompressor) compresses every
+//! payload on its own, so a small patch frame pays for its codec header and
+//! finds nothing to match against. [`SharedContextCompressor`] keeps a single
+//! deflate, gzip, brotli or zstd stream open for the whole PJS stream and
+//! flushes it at the end of every frame: each frame's output is complete,
+//! decodable as soon as it arrives, and can back-reference everything sent
+//! before it. [`SharedContextDecompressor`] is the matching receiver; it must
+//! see every frame, in order, exactly once.
+//!
+//! With [`ByteCodec::ZstdDict`] the window starts out primed with the trained
+//! session dictionary, so even the first frame compresses well.
+//!
+//! # Security
+//!
+//! Each frame's output is capped while it is decoded, not after: at
+//! `max_decompressed_size` per frame, and at `max_ratio` times the compressed
+//! bytes received so far for the stream as a whole. The stream-wide ratio is
+//! what bounds a shared window — a single frame can legitimately expand far
+//! beyond `max_ratio` by referencing earlier frames. A decoder that has
+//! failed once refuses every later frame, since its window no longer matches
+//! the sender's.
+
+use super::secure::{ByteCodec, CompressionQuality};
+use crate::{
+    Error, Result,
+    security::{CompressionBombDetector, CompressionBombError},
+};
+use std::io::{self, Write};
+
+/// Running totals for one side of a shared-context stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SharedContextStats {
+    /// Frames processed.
+    pub frames: u64,
+    /// Uncompressed bytes, across all frames.
+    pub uncompressed_bytes: u64,
+    /// Compressed bytes, across all frames.
+    pub compressed_bytes: u64,
+}
+
+impl SharedContextStats {
+    /// Uncompressed bytes per compressed byte, or `1.0` before any output.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.compressed_bytes == 0 {
+            return 1.0;
+        }
+        self.uncompressed_bytes as f64 / self.compressed_bytes as f64
+    }
+}
+
+/// Compresses a stream's frames through one persistent codec window.
+///
+/// # Examples
+///
+/// ```rust
+/// use pjson_rs::compression::{
+///     secure::ByteCodec,
+///     shared_context::{SharedContextCompressor, SharedContextDecompressor},
+/// };
+///
+/// let mut compressor = SharedContextCompressor::new(ByteCodec::None).unwrap();
+/// let mut decompressor = SharedContextDecompressor::with_default_security(ByteCodec::None).unwrap();
+///
+/// for frame in [&br#"{"a":1}"#[..], br#"{"a":2}"#] {
+///     let bytes = compressor.compress_frame(frame).unwrap();
+///     assert_eq!(decompressor.decompress_frame(&bytes).unwrap(), frame);
+/// }
+/// ```
+pub struct SharedContextCompressor {
+    codec: ByteCodec,
+    encoder: Encoder,
+    stats: SharedContextStats,
+}
+
+impl SharedContextCompressor {
+    /// Open a shared context for `codec` at the default quality.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CompressionError`] if the codec cannot be initialised
+    /// or its feature is not enabled.
+    pub fn new(codec: ByteCodec) -> Result<Self> {
+        Self::with_quality(codec, CompressionQuality::default())
+    }
+
+    /// Open a shared context for `codec` at `quality`.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`Self::new`].
+    pub fn with_quality(codec: ByteCodec, quality: CompressionQuality) -> Result<Self> {
+        Ok(Self {
+            encoder: Encoder::new(&codec, quality)?,
+            codec,
+            stats: SharedContextStats::default(),
+        })
+    }
+
+    /// Compress one frame and flush, returning the bytes that carry it.
+    ///
+    /// The result decodes completely once every earlier frame's bytes have
+    /// been fed to the matching [`SharedContextDecompressor`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CompressionError`] if the codec fails; the context
+    /// must not be used after that.
+    pub fn compress_frame(&mut self, frame: &[u8]) -> Result<Vec<u8>> {
+        let out = self.encoder.encode(frame).map_err(|e| {
+            Error::CompressionError(format!("{} shared-context encode: {e}", self.codec.name()))
+        })?;
+        self.stats.frames += 1;
+        self.stats.uncompressed_bytes += frame.len() as u64;
+        self.stats.compressed_bytes += out.len() as u64;
+        Ok(out)
+    }
+
+    /// Close the stream, returning the codec's end-of-stream bytes.
+    ///
+    /// Frames decode without them, but a consumer that checks for a complete
+    /// stream — a gzip trailer, brotli's last block, the end of a zstd frame —
+    /// needs them sent after the last frame, as HTTP `Content-Encoding` does.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CompressionError`] if the codec fails.
+    pub fn finish(mut self) -> Result<Vec<u8>> {
+        let out = self.encoder.finish().map_err(|e| {
+            Error::CompressionError(format!("{} shared-context finish: {e}", self.codec.name()))
+        })?;
+        self.stats.compressed_bytes += out.len() as u64;
+        Ok(out)
+    }
+
+    /// The codec this context compresses with.
+    pub fn codec(&self) -> &ByteCodec {
+        &self.codec
+    }
+
+    /// Totals across every frame compressed so far.
+    pub fn stats(&self) -> SharedContextStats {
+        self.stats
+    }
+}
+
+impl std::fmt::Debug for SharedContextCompressor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedContextCompressor")
+            .field("codec", &self.codec)
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Decompresses the frames of one [`SharedContextCompressor`] stream.
+pub struct SharedContextDecompressor {
+    detector: CompressionBombDetector,
+    codec: ByteCodec,
+    decoder: Decoder,
+    stats: SharedContextStats,
+    failed: bool,
+}
+
+impl SharedContextDecompressor {
+    /// Open the receiving side of a shared context for `codec`, enforcing
+    /// `detector`'s limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CompressionError`] if the codec cannot be initialised
+    /// or its feature is not enabled.
+    pub fn new(detector: CompressionBombDetector, codec: ByteCodec) -> Result<Self> {
+        Ok(Self {
+            decoder: Decoder::new(&codec)?,
+            detector,
+            codec,
+            stats: SharedContextStats::default(),
+            failed: false,
+        })
+    }
+
+    /// Open with default security settings.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`Self::new`].
+    pub fn with_default_security(codec: ByteCodec) -> Result<Self> {
+        Self::new(CompressionBombDetector::default(), codec)
+    }
+
+    /// Decompress the next frame's bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::SecurityError`] if the frame exceeds the size or
+    /// stream-wide ratio limit, and [`Error::CompressionError`] if the bytes
+    /// do not decode; either way, every later call fails too.
+    pub fn decompress_frame(&mut self, bytes: &[u8]) -> Result<Vec<u8>> {
+        if self.failed {
+            return Err(Error::CompressionError(
+                "shared-context stream is unusable after an earlier error".into(),
+            ));
+        }
+        let result = self.decode(bytes);
+        self.failed = result.is_err();
+        result
+    }
+
+    fn decode(&mut self, bytes: &[u8]) -> Result<Vec<u8>> {
+        self.detector.validate_pre_decompression(bytes.len())?;
+        let config = self.detector.config();
+
+        let compressed = self.stats.compressed_bytes + bytes.len() as u64;
+        let ratio_budget = ((config.max_ratio * compressed as f64) as u64)
+            .saturating_sub(self.stats.uncompressed_bytes);
+        let ratio_bound = ratio_budget < config.max_decompressed_size as u64;
+        let limit = if ratio_bound {
+            ratio_budget as usize
+        } else {
+            config.max_decompressed_size
+        };
+
+        let out = self.decoder.decode(bytes, limit).map_err(|e| {
+            if e.kind() != io::ErrorKind::OutOfMemory {
+                return Error::CompressionError(format!(
+                    "{} shared-context decode: {e}",
+                    self.codec.name()
+                ));
+            }
+            let error = if ratio_bound {
+                CompressionBombError::RatioExceeded {
+                    ratio: (self.stats.uncompressed_bytes + ratio_budget + 1) as f64
+                        / compressed as f64,
+                    max_ratio: config.max_ratio,
+                }
+            } else {
+                CompressionBombError::SizeExceeded {
+                    size: limit + 1,
+                    max_size: config.max_decompressed_size,
+                }
+            };
+            Error::SecurityError(error.to_string())
+        })?;
+
+        self.stats.frames += 1;
+        self.stats.compressed_bytes = compressed;
+        self.stats.uncompressed_bytes += out.len() as u64;
+        Ok(out)
+    }
+
+    /// The codec this context decompresses.
+    pub fn codec(&self) -> &ByteCodec {
+        &self.codec
+    }
+
+    /// Totals across every frame decompressed so far.
+    pub fn stats(&self) -> SharedContextStats {
+        self.stats
+    }
+}
+
+impl std::fmt::Debug for SharedContextDecompressor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedContextDecompressor")
+            .field("codec", &self.codec)
+            .field("stats", &self.stats)
+            .field("failed", &self.failed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The open encoder for each codec, writing into a buffer drained per frame.
+enum Encoder {
+    None,
+    #[cfg(feature = "compression")]
+    Deflate(flate2::write::DeflateEncoder<Vec<u8>>),
+    #[cfg(feature = "compression")]
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+    #[cfg(feature = "compression")]
+    Brotli(Box<brotli::CompressorWriter<Vec<u8>>>),
+    #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+}
+
+impl Encoder {
+    #[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+    fn new(codec: &ByteCodec, quality: CompressionQuality) -> Result<Self> {
+        match codec {
+            ByteCodec::None => Ok(Self::None),
+
+            #[cfg(feature = "compression")]
+            ByteCodec::Deflate => Ok(Self::Deflate(flate2::write::DeflateEncoder::new(
+                Vec::new(),
+                quality.flate2_level(),
+            ))),
+
+            #[cfg(feature = "compression")]
+            ByteCodec::Gzip => Ok(Self::Gzip(flate2::write::GzEncoder::new(
+                Vec::new(),
+                quality.flate2_level(),
+            ))),
+
+            #[cfg(feature = "compression")]
+            ByteCodec::Brotli => Ok(Self::Brotli(Box::new(brotli::CompressorWriter::new(
+                Vec::new(),
+                4096,
+                quality.brotli_quality() as u32,
+                22,
+            )))),
+
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            ByteCodec::Zstd => zstd::stream::write::Encoder::new(Vec::new(), quality.zstd_level())
+                .map(Self::Zstd)
+                .map_err(|e| Error::CompressionError(format!("zstd encoder init: {e}"))),
+
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            ByteCodec::ZstdDict(dict) => zstd::stream::write::Encoder::with_dictionary(
+                Vec::new(),
+                quality.zstd_level(),
+                dict.as_bytes(),
+            )
+            .map(Self::Zstd)
+            .map_err(|e| Error::CompressionError(format!("zstd encoder init: {e}"))),
+
+            #[cfg(not(feature = "compression"))]
+            ByteCodec::Deflate | ByteCodec::Gzip | ByteCodec::Brotli => Err(
+                Error::CompressionError("feature `compression` is not enabled".into()),
+            ),
+        }
+    }
+
+    /// Write `frame`, flush, and take everything the codec emitted.
+    fn encode(&mut self, frame: &[u8]) -> io::Result<Vec<u8>> {
+        #[cfg(feature = "compression")]
+        fn flushed<W: Write>(writer: &mut W, frame: &[u8]) -> io::Result<()> {
+            writer.write_all(frame)?;
+            writer.flush()
+        }
+        match self {
+            Self::None => Ok(frame.to_vec()),
+            #[cfg(feature = "compression")]
+            Self::Deflate(encoder) => {
+                flushed(encoder, frame)?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+            #[cfg(feature = "compression")]
+            Self::Gzip(encoder) => {
+                flushed(encoder, frame)?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+            #[cfg(feature = "compression")]
+            Self::Brotli(encoder) => {
+                flushed(encoder.as_mut(), frame)?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            Self::Zstd(encoder) => {
+                flushed(encoder, frame)?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+        }
+    }
+
+    /// End the stream and take whatever the codec still had to emit.
+    fn finish(self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(Vec::new()),
+            #[cfg(feature = "compression")]
+            Self::Deflate(encoder) => encoder.finish(),
+            #[cfg(feature = "compression")]
+            Self::Gzip(encoder) => encoder.finish(),
+            #[cfg(feature = "compression")]
+            Self::Brotli(encoder) => Ok(encoder.into_inner()),
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            Self::Zstd(encoder) => encoder.finish(),
+        }
+    }
+}
+
+/// Output sink that fails with [`io::ErrorKind::OutOfMemory`] past `limit`.
+struct CappedBuffer {
+    buf: Vec<u8>,
+    limit: usize,
+}
+
+impl CappedBuffer {
+    fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            limit: 0,
+        }
+    }
+}
+
+impl Write for CappedBuffer {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.buf.len() + data.len() > self.limit {
+            return Err(io::Error::new(
+                io::ErrorKind::OutOfMemory,
+                "frame exceeds its decompression limit",
+            ));
+        }
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The open decoder for each codec, writing into a capped buffer drained per frame.
+enum Decoder {
+    None(CappedBuffer),
+    #[cfg(feature = "compression")]
+    Deflate(flate2::write::DeflateDecoder<CappedBuffer>),
+    #[cfg(feature = "compression")]
+    Gzip(flate2::write::GzDecoder<CappedBuffer>),
+    #[cfg(feature = "compression")]
+    Brotli(Box<brotli::DecompressorWriter<CappedBuffer>>),
+    #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+    Zstd(zstd::stream::write::Decoder<'static, CappedBuffer>),
+}
+
+impl Decoder {
+    fn new(codec: &ByteCodec) -> Result<Self> {
+        match codec {
+            ByteCodec::None => Ok(Self::None(CappedBuffer::new())),
+
+            #[cfg(feature = "compression")]
+            ByteCodec::Deflate => Ok(Self::Deflate(flate2::write::DeflateDecoder::new(
+                CappedBuffer::new(),
+            ))),
+
+            #[cfg(feature = "compression")]
+            ByteCodec::Gzip => Ok(Self::Gzip(flate2::write::GzDecoder::new(
+                CappedBuffer::new(),
+            ))),
+
+            #[cfg(feature = "compression")]
+            ByteCodec::Brotli => Ok(Self::Brotli(Box::new(brotli::DecompressorWriter::new(
+                CappedBuffer::new(),
+                4096,
+            )))),
+
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            ByteCodec::Zstd => zstd::stream::write::Decoder::new(CappedBuffer::new())
+                .map(Self::Zstd)
+                .map_err(|e| Error::CompressionError(format!("zstd decoder init: {e}"))),
+
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            ByteCodec::ZstdDict(dict) => {
+                zstd::stream::write::Decoder::with_dictionary(CappedBuffer::new(), dict.as_bytes())
+                    .map(Self::Zstd)
+                    .map_err(|e| Error::CompressionError(format!("zstd decoder init: {e}")))
+            }
+
+            #[cfg(not(feature = "compression"))]
+            ByteCodec::Deflate | ByteCodec::Gzip | ByteCodec::Brotli => Err(
+                Error::CompressionError("feature `compression` is not enabled".into()),
+            ),
+        }
+    }
+
+    /// Feed one frame's bytes, flush, and take at most `limit` decoded bytes.
+    fn decode(&mut self, bytes: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+        fn drained<W: Write>(
+            writer: &mut W,
+            sink: fn(&mut W) -> &mut CappedBuffer,
+            bytes: &[u8],
+            limit: usize,
+        ) -> io::Result<Vec<u8>> {
+            sink(writer).limit = limit;
+            writer.write_all(bytes)?;
+            writer.flush()?;
+            Ok(std::mem::take(&mut sink(writer).buf))
+        }
+        match self {
+            Self::None(sink) => drained(sink, |sink| sink, bytes, limit),
+            #[cfg(feature = "compression")]
+            Self::Deflate(decoder) => drained(decoder, |d| d.get_mut(), bytes, limit),
+            #[cfg(feature = "compression")]
+            Self::Gzip(decoder) => drained(decoder, |d| d.get_mut(), bytes, limit),
+            #[cfg(feature = "compression")]
+            Self::Brotli(decoder) => drained(decoder.as_mut(), |d| d.get_mut(), bytes, limit),
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            Self::Zstd(decoder) => drained(decoder, |d| d.get_mut(), bytes, limit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::CompressionBombConfig;
+
+    /// Small, similar patch payloads of the kind a stream sends one per frame.
+    fn patch_frames() -> Vec<Vec<u8>> {
+        (0..40)
+            .map(|i| {
+                format!(
+                    r#"{{"path":"$.orders[{i}]","operation":"set","value":{{"id":{},"status":"shipped","customer":"customer-{}"}}}}"#,
+                    5000 + i,
+                    i % 7
+                )
+                .into_bytes()
+            })
+            .collect()
+    }
+
+    fn round_trip(codec: ByteCodec) -> (SharedContextStats, SharedContextStats) {
+        let mut compressor = SharedContextCompressor::new(codec.clone()).unwrap();
+        let mut decompressor = SharedContextDecompressor::with_default_security(codec).unwrap();
+        for frame in patch_frames() {
+            let bytes = compressor.compress_frame(&frame).unwrap();
+            assert_eq!(decompressor.decompress_frame(&bytes).unwrap(), frame);
+        }
+        assert_eq!(compressor.stats(), decompressor.stats());
+        (compressor.stats(), decompressor.stats())
+    }
+
+    #[test]
+    fn test_none_passes_frames_through() {
+        let (stats, _) = round_trip(ByteCodec::None);
+        assert_eq!(stats.frames, 40);
+        assert_eq!(stats.compressed_bytes, stats.uncompressed_bytes);
+    }
+
+    #[test]
+    fn test_decoder_failure_is_sticky() {
+        let mut decompressor = SharedContextDecompressor::new(
+            CompressionBombDetector::new(CompressionBombConfig {
+                max_decompressed_size: 4,
+                ..Default::default()
+            }),
+            ByteCodec::None,
+        )
+        .unwrap();
+        assert!(matches!(
+            decompressor.decompress_frame(b"too long"),
+            Err(Error::SecurityError(_))
+        ));
+        assert!(decompressor.decompress_frame(b"ok").is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    mod compression_tests {
+        use super::*;
+        use crate::compression::secure::SecureCompressor;
+
+        fn isolated_size(codec: ByteCodec) -> u64 {
+            let compressor = SecureCompressor::with_default_security(codec);
+            patch_frames()
+                .iter()
+                .map(|frame| compressor.compress(frame).unwrap().data.len() as u64)
+                .sum()
+        }
+
+        #[test]
+        fn test_shared_window_beats_isolated_frames() {
+            let mut codecs = vec![ByteCodec::Deflate, ByteCodec::Gzip, ByteCodec::Brotli];
+            #[cfg(not(target_arch = "wasm32"))]
+            codecs.push(ByteCodec::Zstd);
+
+            for codec in codecs {
+                let (stats, _) = round_trip(codec.clone());
+                let isolated = isolated_size(codec.clone());
+                assert!(
+                    stats.compressed_bytes * 2 < isolated,
+                    "{codec:?}: shared {} vs isolated {isolated}",
+                    stats.compressed_bytes
+                );
+                assert!(stats.compression_ratio() > 2.0, "{codec:?}");
+            }
+        }
+
+        #[test]
+        fn test_finished_stream_is_one_complete_encoding() {
+            let mut codecs = vec![ByteCodec::Deflate, ByteCodec::Gzip, ByteCodec::Brotli];
+            #[cfg(not(target_arch = "wasm32"))]
+            codecs.push(ByteCodec::Zstd);
+
+            for codec in codecs {
+                let mut compressor = SharedContextCompressor::new(codec.clone()).unwrap();
+                let mut stream = Vec::new();
+                for frame in patch_frames() {
+                    stream.extend(compressor.compress_frame(&frame).unwrap());
+                }
+                stream.extend(compressor.finish().unwrap());
+
+                let decoded = SecureCompressor::with_default_security(codec.clone())
+                    .decompress_protected(&crate::compression::secure::SecureCompressedData {
+                        data: stream,
+                        original_size: 0,
+                        compression_ratio: 0.0,
+                        codec: codec.clone(),
+                    })
+                    .unwrap();
+                assert_eq!(decoded, patch_frames().concat(), "{codec:?}");
+            }
+        }
+
+        #[test]
+        fn test_frames_depend_on_earlier_frames() {
+            let frame = &patch_frames()[0];
+            let mut compressor = SharedContextCompressor::new(ByteCodec::Deflate).unwrap();
+            let first = compressor.compress_frame(frame).unwrap();
+            let repeat = compressor.compress_frame(frame).unwrap();
+            assert!(repeat.len() < first.len() / 4);
+
+            let mut in_order =
+                SharedContextDecompressor::with_default_security(ByteCodec::Deflate).unwrap();
+            in_order.decompress_frame(&first).unwrap();
+            assert_eq!(in_order.decompress_frame(&repeat).unwrap(), *frame);
+
+            let mut late_joiner =
+                SharedContextDecompressor::with_default_security(ByteCodec::Deflate).unwrap();
+            let without_window = late_joiner.decompress_frame(&repeat);
+            assert_ne!(without_window.ok().as_deref(), Some(&frame[..]));
+        }
+
+        #[test]
+        fn test_stream_wide_ratio_is_enforced() {
+            let mut compressor = SharedContextCompressor::new(ByteCodec::Brotli).unwrap();
+            let mut decompressor = SharedContextDecompressor::new(
+                CompressionBombDetector::new(CompressionBombConfig {
+                    max_ratio: 20.0,
+                    ..Default::default()
+                }),
+                ByteCodec::Brotli,
+            )
+            .unwrap();
+
+            let bomb = vec![b'a'; 1 << 20];
+            let bytes = compressor.compress_frame(&bomb).unwrap();
+            assert!(matches!(
+                decompressor.decompress_frame(&bytes),
+                Err(Error::SecurityError(_))
+            ));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        #[test]
+        fn test_zstd_dictionary_primes_the_window() {
+            use crate::compression::zstd::{MAX_DICT_SIZE, N_TRAIN, ZstdDictCompressor};
+            use std::sync::Arc;
+
+            let samples: Vec<Vec<u8>> = patch_frames().into_iter().take(N_TRAIN).collect();
+            let dict = Arc::new(ZstdDictCompressor::train(&samples, MAX_DICT_SIZE).unwrap());
+            let codec = ByteCodec::ZstdDict(dict);
+
+            let first = &patch_frames()[0];
+            let primed = SharedContextCompressor::new(codec.clone())
+                .unwrap()
+                .compress_frame(first)
+                .unwrap();
+            let cold = SharedContextCompressor::new(ByteCodec::Zstd)
+                .unwrap()
+                .compress_frame(first)
+                .unwrap();
+            assert!(primed.len() < cold.len());
+
+            round_trip(codec);
+        }
+    }
+}
@@ -2,10 +2,23 @@
 //!
 //! This module provides centralized configuration for all components,
 //! replacing hardcoded constants with configurable values.
+//!
+//! Configurations can be built in code (the profile constructors below),
+//! loaded from TOML/JSON files with `PJS_`-prefixed environment overrides via
+//! [`ConfigLoader`], and shared with hot-reload of the runtime-safe subset via
+//! [`ReloadableConfig`].
 
+pub mod loader;
+pub mod reload;
 pub mod security;
 
 use crate::compression::CompressionConfig;
+use crate::domain::Priority;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+pub use loader::{ConfigFormat, ConfigLoader, LayeredConfig};
+pub use reload::{ReloadReport, ReloadableConfig};
 pub use security::SecurityConfig;
 
 /// Errors produced by [`PjsConfig::validate`] and its sub-config validators.
@@ -28,10 +41,50 @@ pub enum ConfigError {
         /// Human-readable description of the violated constraint
         message: &'static str,
     },
+
+    /// A configuration file could not be read.
+    #[error("failed to read config file `{}`: {source}", path.display())]
+    Io {
+        /// Path of the file that failed to load
+        path: PathBuf,
+        /// Underlying I/O error
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// A configuration source could not be parsed, or a value has the wrong type.
+    #[error("invalid config from {origin}: {message}")]
+    Parse {
+        /// Where the offending input came from (file path, `"environment"`, ...)
+        origin: String,
+        /// Parser or deserializer error message
+        message: String,
+    },
+
+    /// A file or environment variable names a key that does not exist.
+    ///
+    /// Rejected rather than ignored so a typo such as
+    /// `PJS_STREAMING__MAX_FRAME_SIZ` cannot silently leave a default in force.
+    #[error("unknown config key `{key}` from {origin}")]
+    UnknownKey {
+        /// Dotted path of the unknown key (e.g. `"streaming.max_frame_siz"`)
+        key: String,
+        /// Where the key came from
+        origin: String,
+    },
+
+    /// A reload changes a key whose current value is baked into objects
+    /// built at startup, so applying it live would be silently ignored.
+    #[error("config key `{key}` cannot change without a restart")]
+    RequiresRestart {
+        /// Dotted path of the key (e.g. `"security.network.rate_limiting.enabled"`)
+        key: &'static str,
+    },
 }
 
 /// Global configuration for PJS library components
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PjsConfig {
     /// Security configuration and limits
     pub security: SecurityConfig,
@@ -46,7 +99,8 @@ pub struct PjsConfig {
 }
 
 /// Configuration for JSON parsers
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ParserConfig {
     /// Maximum input size in MB
     pub max_input_size_mb: usize,
@@ -59,7 +113,8 @@ pub struct ParserConfig {
 }
 
 /// Configuration for streaming operations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct StreamingConfig {
     /// Maximum frame size in bytes
     pub max_frame_size: usize,
@@ -69,10 +124,14 @@ pub struct StreamingConfig {
     pub operation_timeout_ms: u64,
     /// Maximum bandwidth in bytes per second
     pub max_bandwidth_bps: u64,
+    /// Default minimum frame priority applied when a request does not ask
+    /// for one. Hot-reloadable through [`ReloadableConfig`].
+    pub priority_threshold: u8,
 }
 
 /// Configuration for SIMD acceleration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SimdConfig {
     /// Batch size for SIMD operations
     pub batch_size: usize,
@@ -104,6 +163,7 @@ impl Default for StreamingConfig {
             default_chunk_size: 1024,
             operation_timeout_ms: 5000,   // 5 seconds
             max_bandwidth_bps: 1_000_000, // 1MB/s
+            priority_threshold: Priority::BACKGROUND.value(),
         }
     }
 }
@@ -125,8 +185,9 @@ impl StreamingConfig {
     ///
     /// # Errors
     ///
-    /// Returns [`ConfigError::MustBePositive`] when `max_frame_size` or
-    /// `operation_timeout_ms` is zero.
+    /// Returns [`ConfigError::MustBePositive`] when `max_frame_size`,
+    /// `operation_timeout_ms` or `priority_threshold` is zero (`0` is not a
+    /// valid [`Priority`]).
     ///
     /// # Examples
     ///
@@ -148,6 +209,12 @@ impl StreamingConfig {
                 field: "operation_timeout_ms",
             });
         }
+        if self.priority_threshold == 0 {
+            return Err(ConfigError::MustBePositive {
+                section: "streaming",
+                field: "priority_threshold",
+            });
+        }
         Ok(())
     }
 }
@@ -263,6 +330,7 @@ impl PjsConfig {
                 default_chunk_size: 512,
                 operation_timeout_ms: 1000,    // 1 second
                 max_bandwidth_bps: 10_000_000, // 10MB/s
+                priority_threshold: Priority::BACKGROUND.value(),
             },
             simd: SimdConfig {
                 batch_size: 50,
@@ -290,6 +358,7 @@ impl PjsConfig {
                 default_chunk_size: 4096,
                 operation_timeout_ms: 30000,    // 30 seconds
                 max_bandwidth_bps: 100_000_000, // 100MB/s
+                priority_threshold: Priority::BACKGROUND.value(),
             },
             simd: SimdConfig {
                 batch_size: 500,
@@ -316,6 +385,8 @@ impl PjsConfig {
                 run_length_threshold: 10.0, // Lower threshold
                 min_compression_potential: 0.3,
                 min_numeric_sequence_size: 2,
+                columnar: false,
+                min_columnar_rows: 3,
             },
             parser: ParserConfig {
                 max_input_size_mb: 10,
@@ -328,6 +399,8 @@ impl PjsConfig {
                 default_chunk_size: 256,
                 operation_timeout_ms: 10000, // 10 seconds
                 max_bandwidth_bps: 100_000,  // 100KB/s
+                // Constrained links skip background-tier frames by default
+                priority_threshold: Priority::LOW.value(),
             },
             simd: SimdConfig {
                 batch_size: 25,
@@ -412,6 +485,33 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_streaming_rejects_zero_priority_threshold() {
+        let cfg = StreamingConfig {
+            priority_threshold: 0,
+            ..StreamingConfig::default()
+        };
+        let err = cfg.validate().unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::MustBePositive {
+                section: "streaming",
+                field: "priority_threshold"
+            }
+        ));
+    }
+
+    #[test]
+    fn test_profiles_validate() {
+        for config in [
+            PjsConfig::low_latency(),
+            PjsConfig::high_throughput(),
+            PjsConfig::mobile(),
+        ] {
+            config.validate().expect("built-in profiles must be valid");
+        }
+    }
+
     #[test]
     fn test_simd_rejects_non_power_of_two_alignment() {
         let cfg = SimdConfig {
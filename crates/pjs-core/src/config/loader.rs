@@ -0,0 +1,634 @@
+//! Layered loading of [`PjsConfig`] from files and environment variables
+//!
+//! Layers are applied in order, each overriding the previous one:
+//!
+//! 1. a base configuration (defaults, or a profile such as
+//!    [`PjsConfig::high_throughput`]),
+//! 2. zero or more TOML/JSON sources, in the order they were added,
+//! 3. environment variables carrying the configured prefix (`PJS_` by default).
+//!
+//! Sources may be partial: only the keys they mention are overridden. Keys
+//! that do not exist in [`PjsConfig`] are rejected with
+//! [`ConfigError::UnknownKey`] rather than ignored, and the merged result is
+//! checked with [`PjsConfig::validate`] before it is returned.
+//!
+//! # Environment variables
+//!
+//! Variable names map onto the config tree by stripping the prefix, splitting
+//! on a double underscore and lower-casing each segment, so nested keys can
+//! keep their own single underscores:
+//!
+//! | Variable | Key |
+//! |---|---|
+//! | `PJS_STREAMING__MAX_FRAME_SIZE=32768` | `streaming.max_frame_size` |
+//! | `PJS_SECURITY__NETWORK__RATE_LIMITING__ENABLED=false` | `security.network.rate_limiting.enabled` |
+//!
+//! Values are parsed as JSON scalars (`42`, `true`, `0.5`) and fall back to a
+//! plain string when they are not valid JSON.
+//!
+//! The same layering works for any [`LayeredConfig`] type, so an application
+//! that embeds `PjsConfig` in a larger configuration document (as the
+//! `pjs-server` binary does) can load the whole document with one loader.
+//!
+//! # Examples
+//!
+//! ```
+//! use pjson_rs::config::{ConfigFormat, ConfigLoader};
+//!
+//! let config = ConfigLoader::new()
+//!     .inline(ConfigFormat::Json, r#"{"streaming": {"max_frame_size": 32768}}"#)
+//!     .env_vars([("PJS_PARSER__ENABLE_SEMANTICS", "false")])
+//!     .load()
+//!     .expect("valid configuration");
+//!
+//! assert_eq!(config.streaming.max_frame_size, 32768);
+//! assert!(!config.parser.enable_semantics);
+//! ```
+
+use super::{ConfigError, PjsConfig};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Default prefix for environment variable overrides.
+pub const DEFAULT_ENV_PREFIX: &str = "PJS_";
+
+/// Separator between nesting levels in environment variable names.
+const ENV_NESTING_SEPARATOR: &str = "__";
+
+/// Origin label used in errors raised by environment overrides.
+const ENV_ORIGIN: &str = "environment";
+
+/// On-disk format of a configuration source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// TOML document (requires the `config-toml` feature)
+    Toml,
+    /// JSON document
+    Json,
+}
+
+impl ConfigFormat {
+    /// Infer the format from a file extension (`.toml` or `.json`).
+    ///
+    /// Returns `None` for any other or missing extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    fn parse(self, contents: &str, origin: &str) -> Result<Value, ConfigError> {
+        let parse_error = |message: String| ConfigError::Parse {
+            origin: origin.to_string(),
+            message,
+        };
+
+        match self {
+            Self::Json => serde_json::from_str(contents).map_err(|e| parse_error(e.to_string())),
+            #[cfg(feature = "config-toml")]
+            Self::Toml => toml::from_str(contents).map_err(|e| parse_error(e.to_string())),
+            #[cfg(not(feature = "config-toml"))]
+            Self::Toml => Err(parse_error(
+                "TOML support requires the `config-toml` feature".to_string(),
+            )),
+        }
+    }
+}
+
+/// A configuration document that [`ConfigLoader`] can layer and validate.
+///
+/// The serialized form of the base value defines the set of known keys:
+/// every key a source or environment variable may override must be present
+/// when the base is serialized (use `Option` leaves rather than optional
+/// sections so absent values still appear as `null`).
+pub trait LayeredConfig: Serialize + DeserializeOwned {
+    /// Check the merged configuration for internal consistency.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConfigError`] describing the first violated constraint.
+    fn validate(&self) -> Result<(), ConfigError>;
+}
+
+impl LayeredConfig for PjsConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        PjsConfig::validate(self)
+    }
+}
+
+/// A single file or in-memory layer.
+#[derive(Debug, Clone)]
+enum ConfigSource {
+    File(PathBuf),
+    Inline {
+        format: ConfigFormat,
+        contents: String,
+    },
+}
+
+/// Where environment overrides are read from.
+#[derive(Debug, Clone)]
+enum EnvSource {
+    /// No environment layer
+    Disabled,
+    /// The process environment, read at [`ConfigLoader::load`] time
+    Process,
+    /// A fixed set of variables (tests, or callers that pre-filter the environment)
+    Explicit(Vec<(String, String)>),
+}
+
+/// Builder that merges defaults, files and environment variables into a
+/// validated [`PjsConfig`] (or any other [`LayeredConfig`]).
+///
+/// A loader is cheap to clone and can be kept around to re-run the same
+/// layering later; [`ReloadableConfig`](super::ReloadableConfig) does exactly
+/// that to pick up edited files.
+#[derive(Debug, Clone)]
+pub struct ConfigLoader<T = PjsConfig> {
+    base: T,
+    sources: Vec<ConfigSource>,
+    env_prefix: String,
+    env: EnvSource,
+}
+
+impl Default for ConfigLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigLoader {
+    /// Create a loader starting from [`PjsConfig::default`], with no file
+    /// sources and no environment layer.
+    pub fn new() -> Self {
+        Self::with_base(PjsConfig::default())
+    }
+}
+
+impl<T: LayeredConfig> ConfigLoader<T> {
+    /// Create a loader starting from the given base configuration (typically
+    /// one of the `PjsConfig` profiles).
+    pub fn with_base(base: T) -> Self {
+        Self {
+            base,
+            sources: Vec::new(),
+            env_prefix: DEFAULT_ENV_PREFIX.to_string(),
+            env: EnvSource::Disabled,
+        }
+    }
+
+    /// Add a configuration file. The format is inferred from its extension.
+    ///
+    /// The file is read when [`Self::load`] runs, not here, so a loader can be
+    /// built before the file exists and re-run after it changes.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sources.push(ConfigSource::File(path.into()));
+        self
+    }
+
+    /// Add an in-memory configuration document.
+    pub fn inline(mut self, format: ConfigFormat, contents: impl Into<String>) -> Self {
+        self.sources.push(ConfigSource::Inline {
+            format,
+            contents: contents.into(),
+        });
+        self
+    }
+
+    /// Apply overrides from the process environment.
+    pub fn env(mut self) -> Self {
+        self.env = EnvSource::Process;
+        self
+    }
+
+    /// Apply overrides from an explicit set of variables instead of the
+    /// process environment. Variables without the configured prefix are ignored.
+    pub fn env_vars<K, V>(mut self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.env = EnvSource::Explicit(
+            vars.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Change the environment variable prefix (default [`DEFAULT_ENV_PREFIX`]).
+    pub fn env_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.env_prefix = prefix.into();
+        self
+    }
+
+    /// Merge every layer and validate the result.
+    ///
+    /// # Errors
+    ///
+    /// - [`ConfigError::Io`] if a file source cannot be read.
+    /// - [`ConfigError::Parse`] if a source is malformed, has an unsupported
+    ///   extension, or a value has the wrong type for its key.
+    /// - [`ConfigError::UnknownKey`] if a source or variable names a key that
+    ///   does not exist.
+    /// - Any error from [`LayeredConfig::validate`] (for `PjsConfig`,
+    ///   [`PjsConfig::validate`]) for the merged configuration.
+    pub fn load(&self) -> Result<T, ConfigError> {
+        let mut tree = serde_json::to_value(&self.base).map_err(|e| ConfigError::Parse {
+            origin: "base configuration".to_string(),
+            message: e.to_string(),
+        })?;
+
+        for source in &self.sources {
+            let (origin, layer) = match source {
+                ConfigSource::File(path) => {
+                    let origin = path.display().to_string();
+                    let format =
+                        ConfigFormat::from_path(path).ok_or_else(|| ConfigError::Parse {
+                            origin: origin.clone(),
+                            message: "unsupported config file extension \
+                                      (expected .toml or .json)"
+                                .to_string(),
+                        })?;
+                    let contents =
+                        std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+                            path: path.clone(),
+                            source,
+                        })?;
+                    let layer = format.parse(&contents, &origin)?;
+                    (origin, layer)
+                }
+                ConfigSource::Inline { format, contents } => {
+                    let origin = "inline source".to_string();
+                    let layer = format.parse(contents, &origin)?;
+                    (origin, layer)
+                }
+            };
+            merge_layer(&mut tree, layer, &mut Vec::new(), &origin)?;
+        }
+
+        match &self.env {
+            EnvSource::Disabled => {}
+            EnvSource::Process => self.apply_env(&mut tree, std::env::vars())?,
+            EnvSource::Explicit(vars) => self.apply_env(&mut tree, vars.iter().cloned())?,
+        }
+
+        let config: T = serde_json::from_value(tree).map_err(|e| ConfigError::Parse {
+            origin: "merged configuration".to_string(),
+            message: e.to_string(),
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn apply_env(
+        &self,
+        tree: &mut Value,
+        vars: impl Iterator<Item = (String, String)>,
+    ) -> Result<(), ConfigError> {
+        // Sorted so that, when two variables address the same key through
+        // different casing, the outcome does not depend on environment order.
+        let mut overrides: Vec<(Vec<String>, String)> = vars
+            .filter_map(|(name, value)| {
+                let rest = name.strip_prefix(&self.env_prefix)?;
+                let segments = rest
+                    .split(ENV_NESTING_SEPARATOR)
+                    .map(str::to_ascii_lowercase)
+                    .collect();
+                Some((segments, value))
+            })
+            .collect();
+        overrides.sort();
+
+        for (segments, raw) in overrides {
+            let value = serde_json::from_str(&raw).unwrap_or(Value::String(raw));
+            set_existing(tree, &segments, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Recursively overlay `layer` onto `target`, rejecting keys `target` lacks.
+fn merge_layer(
+    target: &mut Value,
+    layer: Value,
+    path: &mut Vec<String>,
+    origin: &str,
+) -> Result<(), ConfigError> {
+    match (target, layer) {
+        (Value::Object(target), Value::Object(layer)) => {
+            for (key, value) in layer {
+                let Some(slot) = target.get_mut(&key) else {
+                    path.push(key);
+                    return Err(unknown_key(path, origin));
+                };
+                path.push(key);
+                merge_layer(slot, value, path, origin)?;
+                path.pop();
+            }
+            Ok(())
+        }
+        (target, layer) => {
+            *target = layer;
+            Ok(())
+        }
+    }
+}
+
+/// Replace the value at `segments`, which must already exist in `tree`.
+fn set_existing(tree: &mut Value, segments: &[String], value: Value) -> Result<(), ConfigError> {
+    let mut node = tree;
+    for (depth, segment) in segments.iter().enumerate() {
+        node = match node {
+            Value::Object(map) => match map.get_mut(segment) {
+                Some(child) => child,
+                None => return Err(unknown_key(&segments[..=depth], ENV_ORIGIN)),
+            },
+            _ => return Err(unknown_key(&segments[..=depth], ENV_ORIGIN)),
+        };
+    }
+
+    if matches!(node, Value::Object(_)) {
+        // Allow a whole section to be replaced with a JSON object, but keep
+        // the unknown-key check for its contents.
+        let Value::Object(fields) = value else {
+            return Err(ConfigError::Parse {
+                origin: ENV_ORIGIN.to_string(),
+                message: format!("`{}` is a section, not a value", segments.join(".")),
+            });
+        };
+        return merge_layer(
+            node,
+            Value::Object(fields),
+            &mut segments.to_vec(),
+            ENV_ORIGIN,
+        );
+    }
+
+    *node = value;
+    Ok(())
+}
+
+fn unknown_key(path: &[String], origin: &str) -> ConfigError {
+    ConfigError::UnknownKey {
+        key: path.join("."),
+        origin: origin.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_without_layers_returns_base() {
+        let config = ConfigLoader::with_base(PjsConfig::mobile())
+            .load()
+            .expect("profile is valid");
+        assert_eq!(config.streaming.max_frame_size, 8 * 1024);
+    }
+
+    #[test]
+    fn test_json_source_overrides_only_mentioned_keys() {
+        let config = ConfigLoader::new()
+            .inline(
+                ConfigFormat::Json,
+                r#"{"streaming": {"max_frame_size": 4096}, "security": {"json": {"max_depth": 32}}}"#,
+            )
+            .load()
+            .unwrap();
+
+        assert_eq!(config.streaming.max_frame_size, 4096);
+        assert_eq!(config.security.json.max_depth, 32);
+        assert_eq!(
+            config.streaming.operation_timeout_ms,
+            PjsConfig::default().streaming.operation_timeout_ms
+        );
+    }
+
+    #[cfg(feature = "config-toml")]
+    #[test]
+    fn test_toml_source() {
+        let config = ConfigLoader::new()
+            .inline(
+                ConfigFormat::Toml,
+                r#"
+                [parser]
+                enable_semantics = false
+
+                [security.network.rate_limiting]
+                max_requests_per_window = 5
+                "#,
+            )
+            .load()
+            .unwrap();
+
+        assert!(!config.parser.enable_semantics);
+        assert_eq!(
+            config
+                .security
+                .network
+                .rate_limiting
+                .max_requests_per_window,
+            5
+        );
+    }
+
+    #[test]
+    fn test_later_sources_win() {
+        let config = ConfigLoader::new()
+            .inline(ConfigFormat::Json, r#"{"simd": {"batch_size": 10}}"#)
+            .inline(ConfigFormat::Json, r#"{"simd": {"batch_size": 20}}"#)
+            .load()
+            .unwrap();
+        assert_eq!(config.simd.batch_size, 20);
+    }
+
+    #[test]
+    fn test_env_overrides_files() {
+        let config = ConfigLoader::new()
+            .inline(
+                ConfigFormat::Json,
+                r#"{"streaming": {"max_frame_size": 4096}}"#,
+            )
+            .env_vars([
+                ("PJS_STREAMING__MAX_FRAME_SIZE", "8192"),
+                ("PJS_SECURITY__NETWORK__RATE_LIMITING__ENABLED", "false"),
+                ("UNRELATED_VARIABLE", "ignored"),
+            ])
+            .load()
+            .unwrap();
+
+        assert_eq!(config.streaming.max_frame_size, 8192);
+        assert!(!config.security.network.rate_limiting.enabled);
+    }
+
+    #[test]
+    fn test_custom_env_prefix() {
+        let config = ConfigLoader::new()
+            .env_prefix("APP_PJS_")
+            .env_vars([
+                ("APP_PJS_SIMD__BATCH_SIZE", "7"),
+                ("PJS_SIMD__BATCH_SIZE", "9"),
+            ])
+            .load()
+            .unwrap();
+        assert_eq!(config.simd.batch_size, 7);
+    }
+
+    #[test]
+    fn test_unknown_file_key_is_rejected() {
+        let err = ConfigLoader::new()
+            .inline(ConfigFormat::Json, r#"{"streaming": {"max_frame_siz": 1}}"#)
+            .load()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::UnknownKey { ref key, .. } if key == "streaming.max_frame_siz"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_env_key_is_rejected() {
+        let err = ConfigLoader::new()
+            .env_vars([("PJS_STREAMING__NOPE", "1")])
+            .load()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::UnknownKey { ref key, ref origin } if key == "streaming.nope" && origin == "environment"
+        ));
+    }
+
+    #[test]
+    fn test_wrong_value_type_is_parse_error() {
+        let err = ConfigLoader::new()
+            .env_vars([("PJS_STREAMING__MAX_FRAME_SIZE", "large")])
+            .load()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { .. }));
+    }
+
+    #[test]
+    fn test_merged_config_is_validated() {
+        let err = ConfigLoader::new()
+            .env_vars([("PJS_SIMD__AVX512_ALIGNMENT", "3")])
+            .load()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InconsistentBounds {
+                section: "simd",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_missing_file_is_io_error() {
+        let err = ConfigLoader::new()
+            .file("/nonexistent/pjs-config-test.json")
+            .load()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Io { .. }));
+    }
+
+    #[test]
+    fn test_unsupported_extension_is_parse_error() {
+        let err = ConfigLoader::new()
+            .file("/nonexistent/pjs.yaml")
+            .load()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { .. }));
+    }
+
+    #[test]
+    fn test_file_source_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("pjs-config-loader-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{"parser": {"max_input_size_mb": 3}}"#).unwrap();
+
+        let result = ConfigLoader::new().file(&path).load();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(result.unwrap().parser.max_input_size_mb, 3);
+    }
+
+    #[derive(Debug, Serialize, serde::Deserialize)]
+    #[serde(default)]
+    struct AppConfig {
+        listen: String,
+        workers: usize,
+        #[serde(flatten)]
+        pjs: PjsConfig,
+    }
+
+    impl Default for AppConfig {
+        fn default() -> Self {
+            Self {
+                listen: "127.0.0.1:3000".to_string(),
+                workers: 4,
+                pjs: PjsConfig::default(),
+            }
+        }
+    }
+
+    impl LayeredConfig for AppConfig {
+        fn validate(&self) -> Result<(), ConfigError> {
+            if self.workers == 0 {
+                return Err(ConfigError::MustBePositive {
+                    section: "app",
+                    field: "workers",
+                });
+            }
+            self.pjs.validate()
+        }
+    }
+
+    #[test]
+    fn test_custom_layered_config_embeds_pjs_config() {
+        let config = ConfigLoader::with_base(AppConfig::default())
+            .inline(
+                ConfigFormat::Json,
+                r#"{"listen": "0.0.0.0:8080", "streaming": {"max_frame_size": 2048}}"#,
+            )
+            .env_vars([("PJS_WORKERS", "8")])
+            .load()
+            .unwrap();
+
+        assert_eq!(config.listen, "0.0.0.0:8080");
+        assert_eq!(config.workers, 8);
+        assert_eq!(config.pjs.streaming.max_frame_size, 2048);
+    }
+
+    #[test]
+    fn test_custom_layered_config_is_validated() {
+        let err = ConfigLoader::with_base(AppConfig::default())
+            .env_vars([("PJS_WORKERS", "0")])
+            .load()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::MustBePositive {
+                field: "workers",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("pjs.TOML")),
+            Some(ConfigFormat::Toml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("pjs.json")),
+            Some(ConfigFormat::Json)
+        );
+        assert_eq!(ConfigFormat::from_path(Path::new("pjs")), None);
+    }
+}
@@ -0,0 +1,437 @@
+//! Shared configuration with hot-reload of the runtime-safe subset
+//!
+//! Most of [`PjsConfig`] is baked into long-lived objects at startup (buffer
+//! pools, parsers, listeners), so changing it requires a restart. A small
+//! subset only affects per-request decisions and can be swapped while the
+//! process runs:
+//!
+//! - `security.network.rate_limiting` (request window, per-IP connection cap,
+//!   message rate, burst allowance)
+//! - `streaming.priority_threshold`
+//!
+//! [`ReloadableConfig`] publishes the current configuration over a
+//! [`tokio::sync::watch`] channel. [`ReloadableConfig::apply`] takes a full
+//! candidate configuration, validates it, publishes only its safe subset and
+//! reports every other section that differs as requiring a restart.
+//!
+//! `security.network.rate_limiting.enabled` is the exception: it decides
+//! whether a limiter is installed at all, so a candidate that flips it is
+//! rejected with [`ConfigError::RequiresRestart`] instead of being reported
+//! as applied while the running limiters stay as they are.
+
+use super::{ConfigError, ConfigLoader, PjsConfig};
+use crate::security::WebSocketRateLimiter;
+use std::sync::{Arc, Weak};
+use tokio::sync::watch;
+
+/// Sections compared by [`ReloadableConfig::apply`] to detect changes that
+/// were *not* applied. Listed at the granularity an operator edits them.
+const RESTART_SECTIONS: &[&str] = &[
+    "security.json",
+    "security.buffers",
+    "security.network",
+    "security.sessions",
+    "compression",
+    "parser",
+    "streaming",
+    "simd",
+];
+
+/// Outcome of a successful [`ReloadableConfig::apply`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadReport {
+    /// Hot-reloadable keys whose value changed and is now in force.
+    pub applied: Vec<&'static str>,
+    /// Sections that differ from the running configuration but were left
+    /// untouched because they only take effect after a restart.
+    pub requires_restart: Vec<&'static str>,
+}
+
+impl ReloadReport {
+    /// Whether the candidate configuration was identical to the running one.
+    pub fn is_unchanged(&self) -> bool {
+        self.applied.is_empty() && self.requires_restart.is_empty()
+    }
+}
+
+/// A validated [`PjsConfig`] shared across the process, with hot-reload of
+/// rate limits and priority thresholds.
+///
+/// # Examples
+///
+/// ```
+/// use pjson_rs::config::{PjsConfig, ReloadableConfig};
+///
+/// let config = ReloadableConfig::new(PjsConfig::default()).unwrap();
+///
+/// let mut candidate = PjsConfig::default();
+/// candidate.streaming.priority_threshold = 50;
+/// candidate.parser.max_input_size_mb = 1; // needs a restart
+///
+/// let report = config.apply(candidate).unwrap();
+/// assert_eq!(report.applied, ["streaming.priority_threshold"]);
+/// assert_eq!(report.requires_restart, ["parser"]);
+/// assert_eq!(config.current().streaming.priority_threshold, 50);
+/// assert_eq!(config.current().parser.max_input_size_mb, 100);
+/// ```
+#[derive(Debug)]
+pub struct ReloadableConfig {
+    sender: watch::Sender<Arc<PjsConfig>>,
+    loader: Option<ConfigLoader>,
+}
+
+impl ReloadableConfig {
+    /// Wrap an already-built configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`ConfigError`] from [`PjsConfig::validate`].
+    pub fn new(config: PjsConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        let (sender, _) = watch::channel(Arc::new(config));
+        Ok(Self {
+            sender,
+            loader: None,
+        })
+    }
+
+    /// Load the initial configuration with `loader` and keep the loader so
+    /// [`Self::reload`] can re-run it later.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from [`ConfigLoader::load`].
+    pub fn from_loader(loader: ConfigLoader) -> Result<Self, ConfigError> {
+        let config = loader.load()?;
+        let (sender, _) = watch::channel(Arc::new(config));
+        Ok(Self {
+            sender,
+            loader: Some(loader),
+        })
+    }
+
+    /// Snapshot of the configuration currently in force.
+    pub fn current(&self) -> Arc<PjsConfig> {
+        self.sender.borrow().clone()
+    }
+
+    /// Subscribe to configuration changes.
+    ///
+    /// The receiver is notified only when [`Self::apply`] actually changed a
+    /// hot-reloadable value.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<PjsConfig>> {
+        self.sender.subscribe()
+    }
+
+    /// Apply the hot-reloadable subset of `candidate`.
+    ///
+    /// The whole candidate is validated first, so a file that would be
+    /// rejected at startup is also rejected here and nothing changes.
+    ///
+    /// # Errors
+    ///
+    /// - The first [`ConfigError`] from [`PjsConfig::validate`].
+    /// - [`ConfigError::RequiresRestart`] if the candidate turns rate limiting
+    ///   on or off.
+    pub fn apply(&self, candidate: PjsConfig) -> Result<ReloadReport, ConfigError> {
+        candidate.validate()?;
+
+        let current = self.current();
+        if current.security.network.rate_limiting.enabled
+            != candidate.security.network.rate_limiting.enabled
+        {
+            return Err(ConfigError::RequiresRestart {
+                key: "security.network.rate_limiting.enabled",
+            });
+        }
+        let mut next = (*current).clone();
+        let mut report = ReloadReport::default();
+
+        if section(&current, "security.network.rate_limiting")
+            != section(&candidate, "security.network.rate_limiting")
+        {
+            next.security.network.rate_limiting = candidate.security.network.rate_limiting.clone();
+            report.applied.push("security.network.rate_limiting");
+        }
+        if current.streaming.priority_threshold != candidate.streaming.priority_threshold {
+            next.streaming.priority_threshold = candidate.streaming.priority_threshold;
+            report.applied.push("streaming.priority_threshold");
+        }
+
+        // After copying the safe subset, any remaining difference between the
+        // candidate and `next` is one this method refuses to apply live.
+        for name in RESTART_SECTIONS {
+            if section(&next, name) != section(&candidate, name) {
+                report.requires_restart.push(name);
+            }
+        }
+
+        if !report.applied.is_empty() {
+            self.sender.send_replace(Arc::new(next));
+        }
+        if !report.requires_restart.is_empty() {
+            tracing::warn!(
+                sections = ?report.requires_restart,
+                "configuration changes ignored until restart"
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Re-run the loader this config was built with and [`Self::apply`] the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Parse`] if this config was not created with
+    /// [`Self::from_loader`], or any error from [`ConfigLoader::load`].
+    pub fn reload(&self) -> Result<ReloadReport, ConfigError> {
+        let loader = self.loader.as_ref().ok_or_else(|| ConfigError::Parse {
+            origin: "reload".to_string(),
+            message: "configuration was not created from a ConfigLoader".to_string(),
+        })?;
+        self.apply(loader.load()?)
+    }
+
+    /// Keep `limiter`'s limits in sync with `security.network.rate_limiting`.
+    ///
+    /// Applies the current limits immediately, then spawns a task that
+    /// re-applies them after every change (see
+    /// [`RateLimitConfig::with_rate_limiting`](crate::security::RateLimitConfig::with_rate_limiting)
+    /// for which fields are copied). The task holds only a `Weak` reference
+    /// to the limiter and exits once the limiter or this config is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a Tokio runtime.
+    pub fn bind_rate_limiter(
+        &self,
+        limiter: &Arc<WebSocketRateLimiter>,
+    ) -> tokio::task::JoinHandle<()> {
+        apply_rate_limits(limiter, &self.current());
+
+        let weak: Weak<WebSocketRateLimiter> = Arc::downgrade(limiter);
+        let mut receiver = self.subscribe();
+        tokio::spawn(async move {
+            while receiver.changed().await.is_ok() {
+                let Some(limiter) = weak.upgrade() else {
+                    break;
+                };
+                let config = receiver.borrow_and_update().clone();
+                apply_rate_limits(&limiter, &config);
+                tracing::info!("rate limits reloaded from configuration");
+            }
+        })
+    }
+}
+
+fn apply_rate_limits(limiter: &WebSocketRateLimiter, config: &PjsConfig) {
+    limiter.update_config(
+        limiter
+            .config()
+            .with_rate_limiting(&config.security.network.rate_limiting),
+    );
+}
+
+/// Serialized view of one dotted section, used for change detection so the
+/// config structs do not need `PartialEq` (several hold floats).
+fn section(config: &PjsConfig, dotted: &str) -> Option<serde_json::Value> {
+    let mut value = serde_json::to_value(config).ok()?;
+    for key in dotted.split('.') {
+        value = value.get_mut(key)?.take();
+    }
+    // A sibling that is hot-reloadable must not make its parent section look
+    // changed, so strip it before comparing.
+    match (dotted, &mut value) {
+        ("security.network", serde_json::Value::Object(map)) => {
+            map.remove("rate_limiting");
+        }
+        ("streaming", serde_json::Value::Object(map)) => {
+            map.remove("priority_threshold");
+        }
+        _ => {}
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigFormat;
+    use crate::security::RateLimitConfig;
+    use std::time::Duration;
+
+    #[test]
+    fn test_new_rejects_invalid_config() {
+        let mut config = PjsConfig::default();
+        config.streaming.max_frame_size = 0;
+        assert!(ReloadableConfig::new(config).is_err());
+    }
+
+    #[test]
+    fn test_apply_identical_config_is_unchanged() {
+        let config = ReloadableConfig::new(PjsConfig::default()).unwrap();
+        let receiver = config.subscribe();
+
+        let report = config.apply(PjsConfig::default()).unwrap();
+
+        assert!(report.is_unchanged());
+        assert!(!receiver.has_changed().unwrap());
+    }
+
+    #[test]
+    fn test_apply_rate_limits_notifies_subscribers() {
+        let config = ReloadableConfig::new(PjsConfig::default()).unwrap();
+        let mut receiver = config.subscribe();
+
+        let mut candidate = PjsConfig::default();
+        candidate
+            .security
+            .network
+            .rate_limiting
+            .max_requests_per_window = 3;
+        let report = config.apply(candidate).unwrap();
+
+        assert_eq!(report.applied, ["security.network.rate_limiting"]);
+        assert!(report.requires_restart.is_empty());
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(
+            receiver
+                .borrow_and_update()
+                .security
+                .network
+                .rate_limiting
+                .max_requests_per_window,
+            3
+        );
+    }
+
+    #[test]
+    fn test_apply_leaves_restart_sections_untouched() {
+        let config = ReloadableConfig::new(PjsConfig::default()).unwrap();
+
+        let mut candidate = PjsConfig::default();
+        candidate.security.network.max_concurrent_connections = 1;
+        candidate.simd.batch_size = 1;
+        let report = config.apply(candidate).unwrap();
+
+        assert!(report.applied.is_empty());
+        assert_eq!(report.requires_restart, ["security.network", "simd"]);
+        assert_eq!(
+            config.current().security.network.max_concurrent_connections,
+            PjsConfig::default()
+                .security
+                .network
+                .max_concurrent_connections
+        );
+    }
+
+    #[test]
+    fn test_apply_rejects_invalid_candidate() {
+        let config = ReloadableConfig::new(PjsConfig::default()).unwrap();
+
+        let mut candidate = PjsConfig::default();
+        candidate.streaming.priority_threshold = 0;
+
+        assert!(config.apply(candidate).is_err());
+        assert_eq!(
+            config.current().streaming.priority_threshold,
+            PjsConfig::default().streaming.priority_threshold
+        );
+    }
+
+    #[test]
+    fn test_apply_rejects_toggling_rate_limiting() {
+        let config = ReloadableConfig::new(PjsConfig::default()).unwrap();
+        let mut receiver = config.subscribe();
+
+        let mut candidate = PjsConfig::default();
+        candidate.security.network.rate_limiting.enabled =
+            !candidate.security.network.rate_limiting.enabled;
+        candidate.streaming.priority_threshold = 50;
+
+        assert!(matches!(
+            config.apply(candidate),
+            Err(ConfigError::RequiresRestart {
+                key: "security.network.rate_limiting.enabled"
+            })
+        ));
+        assert!(!receiver.has_changed().unwrap());
+        assert_eq!(
+            receiver.borrow_and_update().streaming.priority_threshold,
+            PjsConfig::default().streaming.priority_threshold
+        );
+    }
+
+    #[test]
+    fn test_reload_without_loader_errors() {
+        let config = ReloadableConfig::new(PjsConfig::default()).unwrap();
+        assert!(matches!(config.reload(), Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_reload_rereads_file() {
+        let path =
+            std::env::temp_dir().join(format!("pjs-config-reload-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{"streaming": {"priority_threshold": 20}}"#).unwrap();
+
+        let config = ReloadableConfig::from_loader(ConfigLoader::new().file(&path)).unwrap();
+        assert_eq!(config.current().streaming.priority_threshold, 20);
+
+        std::fs::write(&path, r#"{"streaming": {"priority_threshold": 70}}"#).unwrap();
+        let report = config.reload();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(report.unwrap().applied, ["streaming.priority_threshold"]);
+        assert_eq!(config.current().streaming.priority_threshold, 70);
+    }
+
+    #[test]
+    fn test_from_loader_propagates_errors() {
+        let loader = ConfigLoader::new().inline(ConfigFormat::Json, "{not json");
+        assert!(ReloadableConfig::from_loader(loader).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bind_rate_limiter_follows_reloads() {
+        let config = ReloadableConfig::new(PjsConfig::default()).unwrap();
+        let limiter = Arc::new(WebSocketRateLimiter::new(RateLimitConfig {
+            max_frame_size: 1234,
+            ..RateLimitConfig::default()
+        }));
+
+        let task = config.bind_rate_limiter(&limiter);
+        let defaults = &PjsConfig::default().security.network.rate_limiting;
+        assert_eq!(
+            limiter.config().max_requests_per_window,
+            defaults.max_requests_per_window
+        );
+
+        let mut candidate = PjsConfig::default();
+        candidate
+            .security
+            .network
+            .rate_limiting
+            .max_requests_per_window = 2;
+        candidate
+            .security
+            .network
+            .rate_limiting
+            .window_duration_secs = 5;
+        config.apply(candidate).unwrap();
+
+        for _ in 0..100 {
+            if limiter.config().max_requests_per_window == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let applied = limiter.config();
+        assert_eq!(applied.max_requests_per_window, 2);
+        assert_eq!(applied.window_duration, Duration::from_secs(5));
+        assert_eq!(applied.max_frame_size, 1234);
+
+        drop(config);
+        task.await.unwrap();
+    }
+}
@@ -7,6 +7,7 @@ use std::time::Duration;
 
 /// Security configuration for the PJS system
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct SecurityConfig {
     /// JSON processing limits
     pub json: JsonLimits,
@@ -32,6 +33,7 @@ pub const MAX_SAFE_JSON_DEPTH: usize = 512;
 
 /// JSON processing security limits
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct JsonLimits {
     /// Maximum JSON input size in bytes
     pub max_input_size: usize,
@@ -51,6 +53,7 @@ pub struct JsonLimits {
 
 /// Buffer management security limits
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BufferLimits {
     /// Maximum individual buffer size
     pub max_buffer_size: usize,
@@ -70,6 +73,7 @@ pub struct BufferLimits {
 
 /// Network and connection security limits
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NetworkLimits {
     /// Maximum WebSocket frame size
     pub max_websocket_frame_size: usize,
@@ -95,6 +99,7 @@ pub struct NetworkLimits {
 
 /// Rate limiting configuration for DoS protection
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RateLimitingConfig {
     /// Maximum requests per time window per IP
     pub max_requests_per_window: u32,
@@ -117,6 +122,7 @@ pub struct RateLimitingConfig {
 
 /// Session management security limits
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SessionLimits {
     /// Maximum session ID length
     pub max_session_id_length: usize,
@@ -205,7 +211,8 @@ impl SecurityConfig {
     ///
     /// # Errors
     ///
-    /// Returns [`ConfigError::MustBePositive`] when a size field is zero.
+    /// Returns [`ConfigError::MustBePositive`] when a size field or the
+    /// rate-limiting window is zero.
     /// Returns [`ConfigError::InconsistentBounds`] when min > max for session
     /// ID length, or `max_depth` exceeds [`MAX_SAFE_JSON_DEPTH`].
     ///
@@ -272,6 +279,11 @@ impl SecurityConfig {
             "max_http_payload_size",
             self.network.max_http_payload_size
         );
+        must_be_positive!(
+            "security.network.rate_limiting",
+            "window_duration_secs",
+            self.network.rate_limiting.window_duration_secs
+        );
         must_be_positive!(
             "security.sessions",
             "max_session_id_length",
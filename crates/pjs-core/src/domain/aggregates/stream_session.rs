@@ -2,16 +2,14 @@
 
 use crate::domain::{
     DomainError, DomainResult,
-    entities::{
-        Frame, Stream,
-        frame::FramePatch,
-        stream::{StreamConfig, StreamState},
-    },
+    entities::{Frame, Stream, frame::FramePatch, stream::StreamConfig},
     events::{DomainEvent, SessionState},
     ports::{SystemTimeProvider, TimeProvider},
+    services::extract_patches_parallel,
     value_objects::{JsonData, Priority, SessionId, StreamId},
 };
 use chrono::{DateTime, Utc};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
@@ -115,13 +113,17 @@ pub struct SessionStats {
     pub completed_streams: u64,
     /// Number of streams that terminated with an error.
     pub failed_streams: u64,
+    /// Number of streams a client cancelled before they finished.
+    #[serde(default)]
+    pub cancelled_streams: u64,
     /// Total number of frames emitted by the session.
     pub total_frames: u64,
     /// Total estimated payload bytes emitted by the session, accumulated
     /// from [`Frame::estimated_size`] over every frame batch produced by
-    /// [`StreamSession::create_stream_patch_frames`] and
+    /// [`StreamSession::create_stream_patch_frames`],
+    /// [`StreamSession::next_stream_patch_frames`] and
     /// [`StreamSession::create_priority_frames`]. Counts bytes emitted over
-    /// the wire, not distinct payload volume: patch generation is
+    /// the wire, not distinct payload volume: full patch generation is
     /// content-idempotent, so repeated polling against unchanged source data
     /// keeps adding the same bytes rather than counting them once.
     pub total_bytes: u64,
@@ -377,6 +379,8 @@ impl StreamSession {
 
     /// Compute prioritized patches for `stream_id` without mutating any
     /// state — the expensive half of [`Self::create_stream_patch_frames`].
+    /// The document's top-level members are traversed in parallel, as in
+    /// [`Self::extract_prioritized_patches_for_active_streams`].
     /// See [`Stream::extract_prioritized_patches`] for why this is safe to
     /// call without holding any lock a caller might otherwise need around
     /// the mutating half, [`Self::commit_patch_frames_for_stream`].
@@ -390,7 +394,7 @@ impl StreamSession {
             .get(&stream_id)
             .ok_or_else(|| DomainError::StreamNotFound(stream_id.to_string()))?;
 
-        stream.extract_prioritized_patches(priority_threshold)
+        extract_patches_parallel(stream, priority_threshold)
     }
 
     /// Commit already-extracted prioritized patches (from
@@ -426,8 +430,43 @@ impl StreamSession {
         let frames = stream.commit_patch_frames(patches, max_frames)?;
         let bytes_after = stream.stats().total_bytes;
 
+        self.record_frames_batched(&frames, bytes_after - bytes_before);
+        Ok(frames)
+    }
+
+    /// Generate the next batch of patch frames for a child stream through
+    /// the aggregate root, resuming where the previous batch stopped (see
+    /// [`Stream::next_patch_frames`]).
+    ///
+    /// Session statistics and [`DomainEvent::FramesBatched`] are handled as
+    /// in [`Self::commit_patch_frames_for_stream`]. Unlike
+    /// [`Self::create_stream_patch_frames`], the cost is proportional to the
+    /// frames returned rather than to the document, so it is cheap enough to
+    /// run under a repository's per-session lock.
+    pub fn next_stream_patch_frames(
+        &mut self,
+        stream_id: StreamId,
+        priority_threshold: Priority,
+        max_frames: usize,
+    ) -> DomainResult<Vec<Frame>> {
+        let stream = self
+            .streams
+            .get_mut(&stream_id)
+            .ok_or_else(|| DomainError::StreamNotFound(stream_id.to_string()))?;
+
+        let bytes_before = stream.stats().total_bytes;
+        let frames = stream.next_patch_frames(priority_threshold, max_frames)?;
+        let bytes_after = stream.stats().total_bytes;
+
+        self.record_frames_batched(&frames, bytes_after - bytes_before);
+        Ok(frames)
+    }
+
+    /// Fold a batch of frames committed on a child stream into session
+    /// statistics, raising [`DomainEvent::FramesBatched`] if it is not empty.
+    fn record_frames_batched(&mut self, frames: &[Frame], bytes: u64) {
         self.stats.total_frames += frames.len() as u64;
-        self.stats.total_bytes += bytes_after - bytes_before;
+        self.stats.total_bytes += bytes;
         self.update_timestamp();
 
         if !frames.is_empty() {
@@ -437,8 +476,53 @@ impl StreamSession {
                 timestamp: self.time_provider.now(),
             });
         }
+    }
 
-        Ok(frames)
+    /// Push a new version of `stream_id`'s source document and commit the
+    /// diff against the previous version as patch frames, highest priority
+    /// first (see [`Stream::update_source`]).
+    ///
+    /// The stream moves to
+    /// [`StreamState::Live`](crate::domain::entities::stream::StreamState::Live)
+    /// and keeps accepting updates. Session statistics are updated as in
+    /// [`Self::commit_patch_frames_for_stream`], and a
+    /// [`DomainEvent::StreamUpdated`] event is raised even when the new
+    /// version is identical and no frames are produced, so observers can
+    /// track the version.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::StreamNotFound`] for an unknown stream and
+    /// [`DomainError::InvalidStreamState`] unless it is `Streaming` or `Live`.
+    pub fn update_stream_data(
+        &mut self,
+        stream_id: StreamId,
+        source_data: JsonData,
+        max_frames: usize,
+    ) -> DomainResult<Vec<Frame>> {
+        if !self.is_active() {
+            return Err(DomainError::InvalidSessionState(
+                "Session is not active".to_string(),
+            ));
+        }
+
+        let stream = self
+            .streams
+            .get_mut(&stream_id)
+            .ok_or_else(|| DomainError::StreamNotFound(stream_id.to_string()))?;
+        let patches = stream.update_source(source_data)?;
+        let patch_count = patches.len();
+        let version = stream.version();
+
+        self.add_event(DomainEvent::StreamUpdated {
+            session_id: self.id,
+            stream_id,
+            version,
+            patch_count,
+            timestamp: self.time_provider.now(),
+        });
+
+        self.commit_patch_frames_for_stream(stream_id, patches, max_frames)
     }
 
     /// Activate session
@@ -510,7 +594,7 @@ impl StreamSession {
     /// transactional rollback) never has to reason about two independently
     /// fallible calls. In practice the second call cannot fail here: a
     /// freshly created stream is always in
-    /// [`StreamState::Preparing`],
+    /// [`StreamState::Preparing`](crate::domain::entities::stream::StreamState::Preparing),
     /// which [`Stream::is_active`](crate::domain::entities::Stream::is_active)
     /// treats as active — the only condition `update_stream_config` checks —
     /// and nothing else can run between the two calls within one `&mut self`
@@ -627,6 +711,34 @@ impl StreamSession {
         Ok(())
     }
 
+    /// Cancel a specific stream on behalf of the client.
+    ///
+    /// Allowed while the stream is preparing, streaming or live; a stream
+    /// that already completed, failed or was cancelled yields
+    /// [`DomainError::InvalidStateTransition`].
+    pub fn cancel_stream(&mut self, stream_id: StreamId) -> DomainResult<()> {
+        let stream = self
+            .streams
+            .get_mut(&stream_id)
+            .ok_or_else(|| DomainError::StreamNotFound(stream_id.to_string()))?;
+
+        stream.cancel()?;
+
+        // Update session stats
+        self.stats.active_streams = self.stats.active_streams.saturating_sub(1);
+        self.stats.cancelled_streams += 1;
+
+        self.update_timestamp();
+
+        self.add_event(DomainEvent::StreamCancelled {
+            session_id: self.id,
+            stream_id,
+            timestamp: self.time_provider.now(),
+        });
+
+        Ok(())
+    }
+
     /// Create frames for all active streams based on priority.
     ///
     /// `priority_threshold` is an inclusive minimum (`>=`): a patch is
@@ -668,13 +780,27 @@ impl StreamSession {
     /// counterpart. Note that commit step is itself proportional to the
     /// total number of patches extracted across every `Streaming` stream,
     /// not to `max_frames`/`batch_size` — see that method's docs.
+    ///
+    /// Streams, and the top-level members of each stream's document, are
+    /// traversed in parallel on the current rayon pool (see
+    /// [`GenerationPool::install`](crate::domain::services::GenerationPool::install)
+    /// to bound it). The result is ordered by stream creation time, then
+    /// stream id, and each stream's patches match
+    /// [`Stream::extract_prioritized_patches`], so the output does not depend
+    /// on scheduling.
     pub fn extract_prioritized_patches_for_active_streams(
         &self,
         priority_threshold: Priority,
     ) -> Vec<(StreamId, Vec<(FramePatch, Priority)>)> {
-        self.streams
+        let mut streams: Vec<_> = self
+            .streams
             .iter()
-            .filter(|(_, stream)| matches!(stream.state(), StreamState::Streaming))
+            .filter(|(_, stream)| stream.can_emit_frames())
+            .collect();
+        streams.sort_by_key(|(stream_id, stream)| (stream.created_at(), stream_id.as_uuid()));
+
+        streams
+            .into_par_iter()
             .filter_map(|(stream_id, stream)| {
                 // The `Streaming`-state precondition is already guaranteed by
                 // the `filter` above. `Stream::extract_prioritized_patches`
@@ -685,7 +811,7 @@ impl StreamSession {
                 // structurally, and `Stream` derives `Deserialize`, so the
                 // log below is defensive/future-proofing against a state no
                 // current caller can construct, not a live path.
-                match stream.extract_prioritized_patches(priority_threshold) {
+                match extract_patches_parallel(stream, priority_threshold) {
                     Ok(patches) => Some((*stream_id, patches)),
                     Err(error) => {
                         tracing::debug!(
@@ -770,7 +896,7 @@ impl StreamSession {
             let Some(stream) = self.streams.get(&stream_id) else {
                 continue;
             };
-            if !matches!(stream.state(), StreamState::Streaming) {
+            if !stream.can_emit_frames() {
                 continue;
             }
             for (priority, frame_patches) in Stream::chunk_patches_for_commit(patches, 5) {
@@ -1038,6 +1164,39 @@ mod tests {
         assert_eq!(session.stats().completed_streams, 1);
     }
 
+    #[test]
+    fn test_cancel_stream_updates_stats_and_emits_event() {
+        let mut session = StreamSession::new(SessionConfig::default());
+        assert!(session.activate().is_ok());
+
+        let stream_id = session
+            .create_stream(JsonData::String("data".to_string()))
+            .unwrap();
+        assert!(session.start_stream(stream_id).is_ok());
+        session.take_events();
+
+        assert!(session.cancel_stream(stream_id).is_ok());
+        assert_eq!(session.stats().active_streams, 0);
+        assert_eq!(session.stats().cancelled_streams, 1);
+        assert_eq!(
+            session.stream(stream_id).unwrap().state(),
+            &crate::domain::entities::stream::StreamState::Cancelled
+        );
+        let events: Vec<_> = session.take_events().into_iter().collect();
+        assert!(matches!(
+            events.as_slice(),
+            [DomainEvent::StreamCancelled { stream_id: id, .. }] if *id == stream_id
+        ));
+
+        // A cancelled stream cannot be cancelled (or completed) again.
+        assert!(matches!(
+            session.cancel_stream(stream_id),
+            Err(DomainError::InvalidStateTransition(_))
+        ));
+        assert!(session.complete_stream(stream_id).is_err());
+        assert_eq!(session.stats().cancelled_streams, 1);
+    }
+
     #[test]
     fn test_create_stream_with_config_none_behaves_like_create_stream() {
         let mut session = StreamSession::new(SessionConfig::default());
@@ -1314,6 +1473,90 @@ mod tests {
         assert!(matches!(result, Err(DomainError::InvalidStreamState(_))));
     }
 
+    #[test]
+    fn test_update_stream_data_commits_diff_and_raises_event() {
+        use crate::domain::entities::stream::StreamState;
+
+        let mut session = StreamSession::new(SessionConfig::default());
+        session.activate().unwrap();
+        let stream_id = session
+            .create_stream(serde_json::json!({"id": 1, "price": 10, "tags": ["a"]}).into())
+            .unwrap();
+        session.start_stream(stream_id).unwrap();
+        session.take_events();
+
+        let frames = session
+            .update_stream_data(
+                stream_id,
+                serde_json::json!({"id": 1, "price": 12, "tags": ["a", "b"]}).into(),
+                16,
+            )
+            .unwrap();
+
+        let patch_count: usize = frames
+            .iter()
+            .map(|frame| match frame.payload() {
+                JsonData::Object(payload) => match payload.get("patches") {
+                    Some(JsonData::Array(patches)) => patches.len(),
+                    _ => 0,
+                },
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(patch_count, 2);
+        assert_eq!(session.stats().total_frames, frames.len() as u64);
+
+        let stream = &session.streams()[&stream_id];
+        assert_eq!(stream.state(), &StreamState::Live);
+        assert_eq!(stream.version(), 1);
+
+        let events: Vec<_> = session.take_events().into_iter().collect();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            DomainEvent::StreamUpdated {
+                version: 1,
+                patch_count: 2,
+                ..
+            }
+        )));
+
+        // Live streams still take part in cross-stream priority batches.
+        assert_eq!(
+            session
+                .extract_prioritized_patches_for_active_streams(Priority::BACKGROUND)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_active_stream_extraction_is_ordered_and_matches_sequential() {
+        let mut session = StreamSession::new(SessionConfig::default());
+        session.activate().unwrap();
+        for i in 0..5 {
+            let data: JsonData = serde_json::json!({
+                "id": i,
+                "items": (0..20).map(|n| serde_json::json!({"n": n})).collect::<Vec<_>>(),
+            })
+            .into();
+            let stream_id = session.create_stream(data).unwrap();
+            session.start_stream(stream_id).unwrap();
+        }
+
+        let extracted = session.extract_prioritized_patches_for_active_streams(Priority::MIN);
+        assert_eq!(extracted.len(), 5);
+
+        let mut expected: Vec<_> = session.streams.values().collect();
+        expected.sort_by_key(|stream| (stream.created_at(), stream.id().as_uuid()));
+        for ((stream_id, patches), stream) in extracted.iter().zip(expected) {
+            assert_eq!(*stream_id, stream.id());
+            assert_eq!(
+                *patches,
+                stream.extract_prioritized_patches(Priority::MIN).unwrap()
+            );
+        }
+    }
+
     #[test]
     fn test_concurrent_stream_limit() {
         let config = SessionConfig {
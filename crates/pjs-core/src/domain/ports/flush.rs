@@ -0,0 +1,39 @@
+//! Port: persist or hand off buffered state before the process exits.
+//!
+//! Intentionally **not** under `ports::gat` — flushing happens once per
+//! process lifetime, during [`ShutdownCoordinator::shutdown`](crate::infrastructure::shutdown::ShutdownCoordinator::shutdown),
+//! so the boxed future costs nothing that matters and keeps the trait
+//! dyn-compatible: the coordinator holds an arbitrary mix of repositories and
+//! publishers as `Arc<dyn Flush>`.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::Result;
+
+/// Heap-allocated future returned by [`Flush::flush`].
+pub type FlushFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Port for adapters that buffer writes or events and must hand them off
+/// before shutdown completes.
+///
+/// # Contract
+///
+/// - `flush` is called at most once, after in-flight streams have drained (or
+///   their drain deadline has passed), so no new writes race with it in a
+///   well-behaved server.
+/// - Implementations must tolerate being flushed with nothing buffered.
+/// - An adapter with no buffered state (e.g. a purely in-memory repository)
+///   returns `Ok(())` immediately; implementing the trait anyway lets callers
+///   register every adapter uniformly and swap in a durable backend later
+///   without touching the shutdown wiring.
+pub trait Flush: Send + Sync {
+    /// Persist or deliver everything accepted so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if buffered state could not be handed off; the
+    /// coordinator records it in its report and carries on flushing the
+    /// remaining targets.
+    fn flush(&self) -> FlushFuture<'_>;
+}
@@ -157,14 +157,30 @@ gat_port! {
             stream_id: StreamId
         ) -> Vec<DomainEvent>;
 
+        /// Atomically cancel `stream_id` within `session_id`, returning every
+        /// event currently pending on the session.
+        ///
+        /// Same atomicity contract as [`Self::complete_stream_atomic`],
+        /// applying `StreamSession::cancel_stream` instead. Returns
+        /// `DomainError::SessionNotFound` if `session_id` does not exist, or
+        /// whatever error `StreamSession::cancel_stream` returns (e.g. the
+        /// stream already finished).
+        async fn cancel_stream_atomic(
+            &self,
+            session_id: SessionId,
+            stream_id: StreamId
+        ) -> Vec<DomainEvent>;
+
         /// Atomically generate patch frames for `stream_id` within
         /// `session_id`, returning the frames and every event currently
         /// pending on the session.
         ///
         /// Same atomicity contract as [`Self::create_stream_atomic`], applying
-        /// `StreamSession::create_stream_patch_frames` instead. Returns
-        /// `DomainError::SessionNotFound` if `session_id` does not exist, or
-        /// whatever error `StreamSession::create_stream_patch_frames` returns.
+        /// `StreamSession::next_stream_patch_frames` instead: each call
+        /// returns the next batch of the stream's document, not the whole
+        /// document again. Returns `DomainError::SessionNotFound` if
+        /// `session_id` does not exist, or whatever error
+        /// `StreamSession::next_stream_patch_frames` returns.
         async fn create_stream_patch_frames_atomic(
             &self,
             session_id: SessionId,
@@ -173,6 +189,22 @@ gat_port! {
             max_frames: usize
         ) -> (Vec<Frame>, Vec<DomainEvent>);
 
+        /// Atomically push a new version of `stream_id`'s source document,
+        /// returning the diff frames and every event currently pending on the
+        /// session.
+        ///
+        /// Same atomicity contract as [`Self::create_stream_atomic`], applying
+        /// `StreamSession::update_stream_data` instead. Returns
+        /// `DomainError::SessionNotFound` if `session_id` does not exist, or
+        /// whatever error `StreamSession::update_stream_data` returns.
+        async fn update_stream_data_atomic(
+            &self,
+            session_id: SessionId,
+            stream_id: StreamId,
+            source_data: JsonData,
+            max_frames: usize
+        ) -> (Vec<Frame>, Vec<DomainEvent>);
+
         /// Atomically generate priority frames across every `Streaming`
         /// stream in `session_id`, returning the frames and every event
         /// currently pending on the session.
@@ -7,6 +7,7 @@
 //! by defining abstract interfaces that decouple the domain from infrastructure concerns.
 
 pub mod dictionary_store;
+pub mod flush;
 pub mod gat;
 pub mod repositories;
 pub mod writer;
@@ -14,6 +15,9 @@ pub mod writer;
 // Dictionary store port
 pub use dictionary_store::{DictionaryFuture, DictionaryStore, NoopDictionaryStore};
 
+// Shutdown flush port
+pub use flush::{Flush, FlushFuture};
+
 // GAT traits (canonical interfaces)
 pub use gat::{
     CacheGat, ConnectionMonitorGat, EventPublisherGat, EventStoreGat, FrameRepositoryGat,
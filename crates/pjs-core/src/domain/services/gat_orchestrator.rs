@@ -9,6 +9,7 @@ use crate::domain::{
     entities::Frame,
     events::DomainEvent,
     ports::gat::{EventPublisherGat, StreamRepositoryGat},
+    services::GenerationPool,
     value_objects::{Priority, SessionId, StreamId},
 };
 use chrono::Utc;
@@ -64,6 +65,25 @@ pub struct OrchestratorConfig {
     pub max_concurrent_streams: usize,
     /// Frames at or above this priority are escalated to [`Priority::CRITICAL`].
     pub priority_boost_threshold: Priority,
+    /// Worker threads for parallel patch extraction across streams; `0`
+    /// uses rayon's global pool, one thread per core. See
+    /// [`Self::generation_pool`].
+    pub generation_threads: usize,
+}
+
+impl OrchestratorConfig {
+    /// The [`GenerationPool`] patch extraction runs on, sized by
+    /// [`Self::generation_threads`];
+    /// [`GatInMemoryStreamRepository::from_config`](crate::infrastructure::adapters::GatInMemoryStreamRepository::from_config)
+    /// builds a repository on it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::ResourceExhausted`](crate::domain::DomainError::ResourceExhausted)
+    /// if the worker threads cannot be spawned.
+    pub fn generation_pool(&self) -> DomainResult<GenerationPool> {
+        GenerationPool::with_threads(self.generation_threads)
+    }
 }
 
 impl Default for OrchestratorConfig {
@@ -73,6 +93,7 @@ impl Default for OrchestratorConfig {
             cache_ttl: Duration::from_secs(300), // 5 minutes
             max_concurrent_streams: 10,
             priority_boost_threshold: Priority::HIGH,
+            generation_threads: 0,
         }
     }
 }
@@ -430,12 +451,21 @@ mod tests {
             = impl Future<Output = DomainResult<Vec<DomainEvent>>> + Send + 'a
         where
             Self: 'a;
+        type CancelStreamAtomicFuture<'a>
+            = impl Future<Output = DomainResult<Vec<DomainEvent>>> + Send + 'a
+        where
+            Self: 'a;
 
         type CreateStreamPatchFramesAtomicFuture<'a>
             = impl Future<Output = DomainResult<(Vec<Frame>, Vec<DomainEvent>)>> + Send + 'a
         where
             Self: 'a;
 
+        type UpdateStreamDataAtomicFuture<'a>
+            = impl Future<Output = DomainResult<(Vec<Frame>, Vec<DomainEvent>)>> + Send + 'a
+        where
+            Self: 'a;
+
         type BatchGenerateFramesAtomicFuture<'a>
             = impl Future<Output = DomainResult<(Vec<Frame>, Vec<DomainEvent>)>> + Send + 'a
         where
@@ -547,6 +577,26 @@ mod tests {
             }
         }
 
+        fn cancel_stream_atomic(
+            &self,
+            session_id: SessionId,
+            stream_id: StreamId,
+        ) -> Self::CancelStreamAtomicFuture<'_> {
+            async move {
+                let mut sessions = self.sessions.write().await;
+                let session = sessions
+                    .iter_mut()
+                    .find(|s| s.id() == session_id)
+                    .ok_or_else(|| {
+                        crate::domain::DomainError::SessionNotFound(format!(
+                            "Session {session_id} not found"
+                        ))
+                    })?;
+                session.cancel_stream(stream_id)?;
+                Ok(session.take_events().into_iter().collect())
+            }
+        }
+
         fn create_stream_patch_frames_atomic(
             &self,
             session_id: SessionId,
@@ -564,11 +614,30 @@ mod tests {
                             "Session {session_id} not found"
                         ))
                     })?;
-                let frames = session.create_stream_patch_frames(
-                    stream_id,
-                    priority_threshold,
-                    max_frames,
-                )?;
+                let frames =
+                    session.next_stream_patch_frames(stream_id, priority_threshold, max_frames)?;
+                Ok((frames, session.take_events().into_iter().collect()))
+            }
+        }
+
+        fn update_stream_data_atomic(
+            &self,
+            session_id: SessionId,
+            stream_id: StreamId,
+            source_data: JsonData,
+            max_frames: usize,
+        ) -> Self::UpdateStreamDataAtomicFuture<'_> {
+            async move {
+                let mut sessions = self.sessions.write().await;
+                let session = sessions
+                    .iter_mut()
+                    .find(|s| s.id() == session_id)
+                    .ok_or_else(|| {
+                        crate::domain::DomainError::SessionNotFound(format!(
+                            "Session {session_id} not found"
+                        ))
+                    })?;
+                let frames = session.update_stream_data(stream_id, source_data, max_frames)?;
                 Ok((frames, session.take_events().into_iter().collect()))
             }
         }
@@ -0,0 +1,146 @@
+//! Parallel patch extraction.
+//!
+//! Extracting patches is CPU-bound: one traversal per stream, and within a
+//! stream one per top-level member of the document (see
+//! [`FrameGenerator::subtrees`]). [`extract_patches_parallel`] runs those
+//! traversals with rayon on whichever pool it is called from, and
+//! [`GenerationPool`] bounds how many threads that is. Results are merged
+//! in document order, so the output is the same as the sequential
+//! [`Stream::extract_prioritized_patches`] whatever the thread count.
+
+use crate::domain::{
+    DomainError, DomainResult,
+    entities::{Stream, frame::FramePatch},
+    services::FrameGenerator,
+    value_objects::Priority,
+};
+use rayon::prelude::*;
+use std::sync::Arc;
+
+/// The threads patch extraction runs on: rayon's global pool (one worker
+/// per core) or a dedicated pool with a fixed thread budget.
+///
+/// Cheap to clone; clones share the pool.
+///
+/// # Examples
+///
+/// ```
+/// use pjson_rs::domain::services::GenerationPool;
+///
+/// let pool = GenerationPool::with_threads(2).unwrap();
+/// assert_eq!(pool.current_num_threads(), 2);
+/// assert_eq!(pool.install(|| rayon::current_num_threads()), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GenerationPool {
+    pool: Option<Arc<rayon::ThreadPool>>,
+}
+
+impl GenerationPool {
+    /// Run on rayon's global pool.
+    pub fn global() -> Self {
+        Self::default()
+    }
+
+    /// A dedicated pool of `threads` workers; `0` means the global pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::ResourceExhausted`] if the worker threads
+    /// cannot be spawned.
+    pub fn with_threads(threads: usize) -> DomainResult<Self> {
+        if threads == 0 {
+            return Ok(Self::global());
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("pjs-generation-{index}"))
+            .build()
+            .map_err(|e| DomainError::ResourceExhausted(format!("frame generation pool: {e}")))?;
+        Ok(Self {
+            pool: Some(Arc::new(pool)),
+        })
+    }
+
+    /// Number of worker threads extraction runs on.
+    pub fn current_num_threads(&self) -> usize {
+        match &self.pool {
+            Some(pool) => pool.current_num_threads(),
+            None => rayon::current_num_threads(),
+        }
+    }
+
+    /// Run `op` with this pool as rayon's current pool, blocking until it
+    /// returns.
+    pub fn install<R, F>(&self, op: F) -> R
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+}
+
+/// [`Stream::extract_prioritized_patches`], with the document's top-level
+/// members traversed in parallel on the current rayon pool.
+///
+/// # Errors
+///
+/// The same as [`Stream::extract_prioritized_patches`].
+pub fn extract_patches_parallel(
+    stream: &Stream,
+    priority_threshold: Priority,
+) -> DomainResult<Vec<(FramePatch, Priority)>> {
+    stream.extract_prioritized_patches_with(|generator, source_data| {
+        let parts: Vec<_> = generator
+            .subtrees(source_data)
+            .into_par_iter()
+            .map(|(path, value)| {
+                generator.subtree_patches(source_data, &path, value, priority_threshold)
+            })
+            .collect();
+        FrameGenerator::merge_patches(parts)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::{JsonData, SessionId};
+
+    #[test]
+    fn test_parallel_extraction_matches_sequential() {
+        let document: JsonData = serde_json::json!({
+            "id": 1,
+            "users": (0..50)
+                .map(|i| serde_json::json!({"id": i, "name": format!("user {i}")}))
+                .collect::<Vec<_>>(),
+            "meta": {"title": "Report", "tags": ["a", "b"], "nested": {"status": "ok"}},
+            "notes": "x".repeat(2000),
+        })
+        .into();
+        let mut stream = Stream::new(SessionId::new(), document, Default::default());
+        stream.start_streaming().unwrap();
+
+        let sequential = stream.extract_prioritized_patches(Priority::MIN).unwrap();
+        for threads in [1, 4] {
+            let pool = GenerationPool::with_threads(threads).unwrap();
+            let parallel = pool
+                .install(|| extract_patches_parallel(&stream, Priority::MIN))
+                .unwrap();
+            assert_eq!(parallel, sequential, "{threads} threads");
+        }
+    }
+
+    #[test]
+    fn test_parallel_extraction_keeps_stream_preconditions() {
+        let stream = Stream::new(SessionId::new(), JsonData::Null, Default::default());
+        assert!(matches!(
+            extract_patches_parallel(&stream, Priority::MIN),
+            Err(DomainError::InvalidStreamState(_))
+        ));
+    }
+}
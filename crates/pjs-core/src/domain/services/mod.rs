@@ -4,9 +4,17 @@
 //! to implement complex business workflows using Clean Architecture principles.
 
 pub mod gat_orchestrator;
+pub mod generation_pool;
 pub mod validation_service;
 
 pub use gat_orchestrator::{
     GatOrchestratorFactory, GatStreamingOrchestrator, HealthStatus, OrchestratorConfig,
 };
+pub use generation_pool::{GenerationPool, extract_patches_parallel};
 pub use validation_service::ValidationService;
+
+// Stateless domain services shared with pjs-domain (WASM-compatible)
+pub use pjson_rs_domain::services::{
+    FrameGenerator, PjsPriority, PriorityHeuristicConfig, compute_priority, compute_priority_in,
+    diff_documents,
+};
@@ -76,6 +76,10 @@ pub enum Error {
     #[error("Compression error: {0}")]
     CompressionError(String),
 
+    /// Reconstructed document does not match the stream's completion digest
+    #[error("Integrity check failed: {0}")]
+    Integrity(#[from] crate::domain::value_objects::IntegrityError),
+
     /// Generic error for other cases
     #[error("{0}")]
     Other(String),
@@ -199,6 +203,7 @@ impl Error {
             Self::Utf8(_) => "encoding",
             Self::SecurityError(_) => "security",
             Self::CompressionError(_) => "compression",
+            Self::Integrity(_) => "integrity",
             Self::Other(_) => "other",
         }
     }
@@ -328,6 +333,10 @@ mod tests {
         assert_eq!(Error::invalid_session("test").category(), "client");
         assert_eq!(Error::invalid_url("test").category(), "client");
         assert_eq!(Error::utf8("test").category(), "encoding");
+        assert_eq!(
+            Error::from(crate::domain::value_objects::IntegrityError::KeyRequired).category(),
+            "integrity"
+        );
         assert_eq!(Error::other("test").category(), "other");
     }
 
@@ -12,7 +12,7 @@ use tokio::sync::mpsc;
 use crate::domain::{
     DomainResult,
     events::{DomainEvent, EventId},
-    ports::EventPublisherGat,
+    ports::{EventPublisherGat, Flush, FlushFuture},
     value_objects::SessionId,
 };
 use crate::infrastructure::bounded_channel::{ByteBoundedSender, Envelope, byte_bounded_channel};
@@ -344,6 +344,19 @@ impl Default for InMemoryEventPublisher {
     }
 }
 
+/// Closes the streaming channel, if any: events already queued stay
+/// receivable, and the consumer then sees end-of-stream instead of waiting
+/// forever on a publisher that is shutting down. Events published after the
+/// flush are still logged and delivered to callbacks, just not to the channel.
+impl Flush for InMemoryEventPublisher {
+    fn flush(&self) -> FlushFuture<'_> {
+        Box::pin(async move {
+            self.channel_tx.write().await.take();
+            Ok(())
+        })
+    }
+}
+
 impl EventPublisherGat for InMemoryEventPublisher {
     type PublishFuture<'a>
         = impl std::future::Future<Output = DomainResult<()>> + Send + 'a
@@ -568,6 +581,27 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_flush_closes_streaming_channel_after_queued_events() {
+        let (publisher, mut rx) = InMemoryEventPublisher::with_channel();
+        let session_id = SessionId::new();
+        publisher
+            .publish(DomainEvent::SessionActivated {
+                session_id,
+                timestamp: chrono::Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        publisher.flush().await.unwrap();
+
+        assert!(rx.recv().await.is_some(), "queued event must survive flush");
+        assert!(rx.recv().await.is_none(), "channel must close after flush");
+        // Flushing twice, or with no channel at all, is harmless.
+        publisher.flush().await.unwrap();
+        InMemoryEventPublisher::new().flush().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_streaming_channel_is_bounded() {
         // Regression test for #314: `with_channel` used to return an
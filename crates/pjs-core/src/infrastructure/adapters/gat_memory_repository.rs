@@ -26,14 +26,15 @@
 //! shard write lock — which blocks every other session hashing to the same
 //! shard, not just the target session — for the duration of its mutation
 //! (see #457). That closure never `.await`s, so the hold is always CPU-bound,
-//! not I/O-bound. `create_stream_patch_frames_atomic` and
-//! `batch_generate_frames_atomic` narrow this further: both extract patches
-//! (a traversal proportional to the source payload, not to `max_frames`)
-//! from a snapshot taken *before* acquiring the lock — the latter doing so
-//! once per stream — but the commit step that still runs under the lock is
-//! itself proportional to the total patches extracted (it clones and
-//! re-chunks all of them into frames), not to `max_frames`/`batch_size` —
-//! see [`StreamSession::commit_priority_frames`]'s docs (#477).
+//! not I/O-bound. `create_stream_patch_frames_atomic` keeps it short by
+//! resuming the stream's patch cursor, walking only as much of the source
+//! payload as the returned frames carry. `batch_generate_frames_atomic`
+//! extracts patches (a traversal proportional to the source payload, not to
+//! `max_frames`) once per stream from a snapshot taken *before* acquiring
+//! the lock, but the commit step that still runs under the lock is itself
+//! proportional to the total patches extracted (it clones and re-chunks all
+//! of them into frames), not to `max_frames`/`batch_size` — see
+//! [`StreamSession::commit_priority_frames`]'s docs (#477).
 //!
 //! # Iteration Consistency
 //!
@@ -55,20 +56,31 @@ use crate::domain::{
     aggregates::StreamSession,
     entities::{
         Frame, Stream,
+        frame::FramePatch,
         stream::{StreamConfig, StreamState},
     },
     events::DomainEvent,
     ports::{
-        PriorityDistribution, SessionHealthSnapshot, SessionPagination, SessionQueryCriteria,
-        SessionQueryResult, SessionSortField, SortOrder, StreamFilter, StreamRepositoryGat,
-        StreamStatistics, StreamStatus, StreamStoreGat,
+        Flush, FlushFuture, PriorityDistribution, SessionHealthSnapshot, SessionPagination,
+        SessionQueryCriteria, SessionQueryResult, SessionSortField, SortOrder, StreamFilter,
+        StreamRepositoryGat, StreamStatistics, StreamStatus, StreamStoreGat,
     },
+    services::{GenerationPool, OrchestratorConfig},
     value_objects::{JsonData, Priority, SessionId, StreamId},
 };
 
 use super::generic_store::{SessionStore, StreamStore};
 use super::limits::{MAX_HEALTH_METRICS, MAX_RESULTS_LIMIT, MAX_SCAN_LIMIT};
 
+/// Times [`StreamRepositoryGat::batch_generate_frames_atomic`] re-extracts
+/// streams updated concurrently before giving up with
+/// [`DomainError::ConcurrencyConflict`].
+const MAX_BATCH_COMMIT_ATTEMPTS: usize = 8;
+
+/// Patches extracted for one stream, with the source version they were
+/// extracted from.
+type ExtractedPatches = Vec<(StreamId, u64, Vec<(FramePatch, Priority)>)>;
+
 // ============================================================================
 // Session Stats Cache (MEM-002)
 // ============================================================================
@@ -141,6 +153,7 @@ fn current_timestamp_secs() -> u64 {
 pub struct GatInMemoryStreamRepository {
     store: SessionStore,
     stats_cache: DashMap<SessionId, CachedSessionStats>,
+    generation_pool: GenerationPool,
 }
 
 impl Clone for GatInMemoryStreamRepository {
@@ -149,6 +162,7 @@ impl Clone for GatInMemoryStreamRepository {
             store: self.store.clone(),
             // Clone the cache to preserve cached statistics
             stats_cache: self.stats_cache.clone(),
+            generation_pool: self.generation_pool.clone(),
         }
     }
 }
@@ -165,9 +179,29 @@ impl GatInMemoryStreamRepository {
         Self {
             store: SessionStore::new(),
             stats_cache: DashMap::new(),
+            generation_pool: GenerationPool::global(),
         }
     }
 
+    /// An empty repository extracting patches on the thread budget of
+    /// `config`, see [`OrchestratorConfig::generation_threads`].
+    ///
+    /// # Errors
+    ///
+    /// See [`OrchestratorConfig::generation_pool`].
+    pub fn from_config(config: &OrchestratorConfig) -> DomainResult<Self> {
+        Ok(Self::new().with_generation_pool(config.generation_pool()?))
+    }
+
+    /// Extract patches for [`StreamRepositoryGat::batch_generate_frames_atomic`]
+    /// on `pool` instead of rayon's global pool, e.g. to cap its threads with
+    /// [`GenerationPool::with_threads`].
+    #[must_use]
+    pub fn with_generation_pool(mut self, pool: GenerationPool) -> Self {
+        self.generation_pool = pool;
+        self
+    }
+
     /// Get number of stored sessions
     pub fn session_count(&self) -> usize {
         self.store.count()
@@ -316,6 +350,38 @@ impl GatInMemoryStreamRepository {
     }
 }
 
+/// Source version of `stream_id` in `session`, used to detect a live update
+/// landing between a lock-free patch extraction and its commit.
+fn stream_version(session: &StreamSession, stream_id: StreamId) -> Option<u64> {
+    session
+        .streams()
+        .get(&stream_id)
+        .map(|stream| stream.version())
+}
+
+/// Run `extract` against `session` on `pool`, from a `spawn_blocking` task
+/// so the traversal never stalls the async runtime.
+async fn extract_off_runtime<F>(
+    pool: GenerationPool,
+    session: StreamSession,
+    extract: F,
+) -> DomainResult<ExtractedPatches>
+where
+    F: FnOnce(&StreamSession) -> DomainResult<ExtractedPatches> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || pool.install(|| extract(&session)))
+        .await
+        .map_err(|e| DomainError::InternalError(format!("patch extraction task: {e}")))?
+}
+
+/// Nothing is buffered: every write is applied to the map before the
+/// repository call returns, so flushing is a no-op.
+impl Flush for GatInMemoryStreamRepository {
+    fn flush(&self) -> FlushFuture<'_> {
+        Box::pin(std::future::ready(Ok(())))
+    }
+}
+
 impl StreamRepositoryGat for GatInMemoryStreamRepository {
     type FindSessionFuture<'a>
         = impl Future<Output = DomainResult<Option<StreamSession>>> + Send + 'a
@@ -341,12 +407,21 @@ impl StreamRepositoryGat for GatInMemoryStreamRepository {
         = impl Future<Output = DomainResult<Vec<DomainEvent>>> + Send + 'a
     where
         Self: 'a;
+    type CancelStreamAtomicFuture<'a>
+        = impl Future<Output = DomainResult<Vec<DomainEvent>>> + Send + 'a
+    where
+        Self: 'a;
 
     type CreateStreamPatchFramesAtomicFuture<'a>
         = impl Future<Output = DomainResult<(Vec<Frame>, Vec<DomainEvent>)>> + Send + 'a
     where
         Self: 'a;
 
+    type UpdateStreamDataAtomicFuture<'a>
+        = impl Future<Output = DomainResult<(Vec<Frame>, Vec<DomainEvent>)>> + Send + 'a
+    where
+        Self: 'a;
+
     type BatchGenerateFramesAtomicFuture<'a>
         = impl Future<Output = DomainResult<(Vec<Frame>, Vec<DomainEvent>)>> + Send + 'a
     where
@@ -456,24 +531,36 @@ impl StreamRepositoryGat for GatInMemoryStreamRepository {
         }
     }
 
-    /// Atomically generate patch frames for a stream, holding the `DashMap`
-    /// shard lock for `session_id` for the full read-modify-write so a
-    /// concurrent mutation of the same session cannot lose an update (#457).
-    /// Extracts prioritized patches outside any lock, then holds the
-    /// `DashMap` shard lock only for the commit step.
+    /// Atomically cancel a stream under the same per-session lock as
+    /// [`Self::complete_stream_atomic`].
+    fn cancel_stream_atomic(
+        &self,
+        session_id: SessionId,
+        stream_id: StreamId,
+    ) -> Self::CancelStreamAtomicFuture<'_> {
+        async move {
+            let result = self.atomic_session_update(session_id, |session| {
+                session.cancel_stream(stream_id)?;
+                Ok(session.take_events().into_iter().collect())
+            });
+            if result.is_ok() {
+                self.invalidate_stats_cache(&session_id);
+            }
+            result
+        }
+    }
+
+    /// Atomically generate the next batch of patch frames for a stream,
+    /// holding the `DashMap` shard lock for `session_id` for the full
+    /// read-modify-write so a concurrent mutation of the same session cannot
+    /// lose an update (#457).
     ///
-    /// `extract_prioritized_patches_for_stream` traverses the stream's full
-    /// `source_data` — proportional to payload size, not to `max_frames` —
-    /// so running it inside `update_with`'s critical section would block
-    /// every other session hashing to the same `DashMap` shard for that
-    /// whole traversal. `source_data` never changes after stream creation,
-    /// so extracting from a snapshot taken here is safe; the commit step
-    /// re-checks the stream is still `Streaming` in case a concurrent call
-    /// completed or failed it in between (see `Stream::commit_patch_frames`).
-    /// That commit step is itself not `max_frames`-bounded either: it clones
-    /// every extracted patch while chunking it into frames, so the lock is
-    /// held proportional to the number of patches extracted, not to
-    /// `max_frames` — see `Stream::commit_patch_frames`'s docs.
+    /// `StreamSession::next_stream_patch_frames` resumes the stream's patch
+    /// cursor and only walks as much of `source_data` as the returned
+    /// frames carry, so the lock is held proportional to `max_frames`
+    /// worth of patches rather than to the payload size. The cursor is
+    /// stream state, so unlike [`Self::batch_generate_frames_atomic`] there
+    /// is nothing to precompute outside the lock.
     fn create_stream_patch_frames_atomic(
         &self,
         session_id: SessionId,
@@ -482,15 +569,34 @@ impl StreamRepositoryGat for GatInMemoryStreamRepository {
         max_frames: usize,
     ) -> Self::CreateStreamPatchFramesAtomicFuture<'_> {
         async move {
-            let session = self.store.get(&session_id).ok_or_else(|| {
-                DomainError::SessionNotFound(format!("Session {session_id} not found"))
-            })?;
-            let patches =
-                session.extract_prioritized_patches_for_stream(stream_id, priority_threshold)?;
-
             let result = self.atomic_session_update(session_id, |session| {
                 let frames =
-                    session.commit_patch_frames_for_stream(stream_id, patches, max_frames)?;
+                    session.next_stream_patch_frames(stream_id, priority_threshold, max_frames)?;
+                Ok((frames, session.take_events().into_iter().collect()))
+            });
+            if result.is_ok() {
+                self.invalidate_stats_cache(&session_id);
+            }
+            result
+        }
+    }
+
+    /// Atomically push a new source version for a stream, holding the
+    /// `DashMap` shard lock for `session_id` for the full read-modify-write.
+    /// Unlike [`Self::batch_generate_frames_atomic`] the diff cannot be
+    /// computed outside the lock: it must be taken against the version the
+    /// commit replaces, or two concurrent updates could both diff against
+    /// the same base and one of them would be lost to subscribers.
+    fn update_stream_data_atomic(
+        &self,
+        session_id: SessionId,
+        stream_id: StreamId,
+        source_data: JsonData,
+        max_frames: usize,
+    ) -> Self::UpdateStreamDataAtomicFuture<'_> {
+        async move {
+            let result = self.atomic_session_update(session_id, |session| {
+                let frames = session.update_stream_data(stream_id, source_data, max_frames)?;
                 Ok((frames, session.take_events().into_iter().collect()))
             });
             if result.is_ok() {
@@ -505,11 +611,18 @@ impl StreamRepositoryGat for GatInMemoryStreamRepository {
     /// the full read-modify-write so a concurrent mutation of the same
     /// session cannot lose an update (#457, #477). Extracts prioritized
     /// patches for every stream outside any lock, then holds the `DashMap`
-    /// shard lock only for the commit step — same rationale and pattern as
-    /// [`Self::create_stream_patch_frames_atomic`], applied per stream
-    /// instead of to a single one. That commit step is proportional to the
-    /// total patches extracted across every `Streaming` stream, not to
-    /// `max_frames`/`batch_size` — see
+    /// shard lock only for the commit step, since extraction traverses each
+    /// stream's full `source_data` — proportional to payload size, not to
+    /// `max_frames`. Extraction runs on the repository's
+    /// [`GenerationPool`], streams and their top-level subtrees in parallel,
+    /// from a `spawn_blocking` task so it never stalls the async runtime. If
+    /// a stream's source version moved on by the time the lock is held (a
+    /// concurrent [`Self::update_stream_data_atomic`]), nothing is committed:
+    /// the lock is released, the updated streams are re-extracted from a
+    /// fresh snapshot the same way, and the commit is retried, up to
+    /// [`MAX_BATCH_COMMIT_ATTEMPTS`] times. That commit step is proportional
+    /// to the total patches extracted across every `Streaming` stream, not
+    /// to `max_frames`/`batch_size` — see
     /// [`StreamSession::commit_priority_frames`]'s docs. Streams that
     /// transition out of `Streaming` between extraction and commit (e.g.
     /// completed concurrently) are skipped rather than failing the whole
@@ -522,20 +635,96 @@ impl StreamRepositoryGat for GatInMemoryStreamRepository {
         max_frames: usize,
     ) -> Self::BatchGenerateFramesAtomicFuture<'_> {
         async move {
-            let session = self.store.get(&session_id).ok_or_else(|| {
-                DomainError::SessionNotFound(format!("Session {session_id} not found"))
-            })?;
-            let extracted =
-                session.extract_prioritized_patches_for_active_streams(priority_threshold);
-
-            let result = self.atomic_session_update(session_id, |session| {
-                let frames = session.commit_priority_frames(extracted, max_frames)?;
-                Ok((frames, session.take_events().into_iter().collect()))
-            });
-            if result.is_ok() {
-                self.invalidate_stats_cache(&session_id);
+            let snapshot = || {
+                self.store.get(&session_id).ok_or_else(|| {
+                    DomainError::SessionNotFound(format!("Session {session_id} not found"))
+                })
+            };
+            let mut extracted =
+                extract_off_runtime(self.generation_pool.clone(), snapshot()?, move |session| {
+                    Ok(session
+                        .extract_prioritized_patches_for_active_streams(priority_threshold)
+                        .into_iter()
+                        .filter_map(|(stream_id, patches)| {
+                            Some((stream_id, stream_version(session, stream_id)?, patches))
+                        })
+                        .collect())
+                })
+                .await?;
+
+            for _ in 0..MAX_BATCH_COMMIT_ATTEMPTS {
+                let committed = self.atomic_session_update(session_id, |session| {
+                    let stale: Vec<StreamId> = extracted
+                        .iter()
+                        .filter(|(stream_id, version, _)| {
+                            stream_version(session, *stream_id) != Some(*version)
+                        })
+                        .map(|(stream_id, _, _)| *stream_id)
+                        .collect();
+                    if !stale.is_empty() {
+                        return Ok(Err(stale));
+                    }
+                    let patches = std::mem::take(&mut extracted)
+                        .into_iter()
+                        .map(|(stream_id, _, patches)| (stream_id, patches))
+                        .collect();
+                    let frames = session.commit_priority_frames(patches, max_frames)?;
+                    Ok(Ok((frames, session.take_events().into_iter().collect())))
+                })?;
+                let stale = match committed {
+                    Ok(result) => {
+                        self.invalidate_stats_cache(&session_id);
+                        return Ok(result);
+                    }
+                    Err(stale) => stale,
+                };
+
+                // Streams no longer `Streaming` are left out, as the commit
+                // would skip them anyway.
+                let stale_ids = stale.clone();
+                let fresh = extract_off_runtime(
+                    self.generation_pool.clone(),
+                    snapshot()?,
+                    move |session| {
+                        stale_ids
+                            .into_iter()
+                            .filter_map(|stream_id| {
+                                let stream = session.streams().get(&stream_id)?;
+                                if !stream.can_emit_frames() {
+                                    return None;
+                                }
+                                Some(
+                                    session
+                                        .extract_prioritized_patches_for_stream(
+                                            stream_id,
+                                            priority_threshold,
+                                        )
+                                        .map(|patches| (stream_id, stream.version(), patches)),
+                                )
+                            })
+                            .collect()
+                    },
+                )
+                .await?;
+                let mut fresh: std::collections::HashMap<_, _> = fresh
+                    .into_iter()
+                    .map(|(stream_id, version, patches)| (stream_id, (version, patches)))
+                    .collect();
+                extracted = extracted
+                    .into_iter()
+                    .filter_map(|(stream_id, version, patches)| {
+                        if !stale.contains(&stream_id) {
+                            return Some((stream_id, version, patches));
+                        }
+                        let (version, patches) = fresh.remove(&stream_id)?;
+                        Some((stream_id, version, patches))
+                    })
+                    .collect();
             }
-            result
+
+            Err(DomainError::ConcurrencyConflict(format!(
+                "session {session_id} streams kept changing during frame generation"
+            )))
         }
     }
 
@@ -742,7 +931,10 @@ impl GatInMemoryStreamStore {
         matches!(
             (state, status),
             (StreamState::Preparing, StreamStatus::Created)
-                | (StreamState::Streaming, StreamStatus::Active)
+                | (
+                    StreamState::Streaming | StreamState::Live,
+                    StreamStatus::Active
+                )
                 | (StreamState::Completed, StreamStatus::Completed)
                 | (StreamState::Failed, StreamStatus::Failed)
                 | (StreamState::Cancelled, StreamStatus::Cancelled)
@@ -792,6 +984,13 @@ impl GatInMemoryStreamStore {
     }
 }
 
+/// Nothing is buffered, as for [`GatInMemoryStreamRepository`].
+impl Flush for GatInMemoryStreamStore {
+    fn flush(&self) -> FlushFuture<'_> {
+        Box::pin(std::future::ready(Ok(())))
+    }
+}
+
 impl StreamStoreGat for GatInMemoryStreamStore {
     type StoreStreamFuture<'a>
         = impl Future<Output = DomainResult<()>> + Send + 'a
@@ -1402,9 +1601,9 @@ mod tests {
         let mut heavy = StreamSession::new(SessionConfig::default());
         heavy.activate().unwrap();
         let heavy_stream = heavy
-            .create_stream(JsonData::String(
-                "hello world payload, quite a few bytes here".to_string(),
-            ))
+            .create_stream(JsonData::from(serde_json::json!({
+                "message": "hello world payload, quite a few bytes here"
+            })))
             .unwrap();
         heavy.start_stream(heavy_stream).unwrap();
         heavy
@@ -2170,6 +2369,87 @@ mod tests {
         assert!(session.stats().total_bytes > 0);
     }
 
+    #[tokio::test]
+    async fn test_create_stream_patch_frames_atomic_resumes_across_calls() {
+        let repo = GatInMemoryStreamRepository::new();
+
+        let mut session = StreamSession::new(SessionConfig::default());
+        session.activate().unwrap();
+        let session_id = session.id();
+        let stream_id = session
+            .create_stream(
+                serde_json::json!({"id": "abc", "name": "Ada", "items": [1, 2, 3]}).into(),
+            )
+            .unwrap();
+        session.start_stream(stream_id).unwrap();
+        let _ = session.take_events();
+        repo.save_session(session).await.unwrap();
+
+        let mut priorities = Vec::new();
+        loop {
+            let (frames, events) = repo
+                .create_stream_patch_frames_atomic(session_id, stream_id, Priority::BACKGROUND, 1)
+                .await
+                .unwrap();
+            if frames.is_empty() {
+                assert!(events.is_empty());
+                break;
+            }
+            assert_eq!(frames.len(), 1);
+            priorities.push(frames[0].priority());
+        }
+
+        // One frame per priority level, highest first, each sent once.
+        assert_eq!(priorities.len(), 3);
+        assert!(priorities.is_sorted_by(|a, b| a > b), "{priorities:?}");
+        assert_eq!(priorities[0], Priority::CRITICAL);
+    }
+
+    #[tokio::test]
+    async fn test_update_stream_data_atomic_streams_diff_and_keeps_stream_active() {
+        let repo = GatInMemoryStreamRepository::new();
+
+        let mut session = StreamSession::new(SessionConfig::default());
+        session.activate().unwrap();
+        let session_id = session.id();
+        let stream_id = session
+            .create_stream(serde_json::json!({"id": "abc", "count": 1}).into())
+            .unwrap();
+        session.start_stream(stream_id).unwrap();
+        let _ = session.take_events();
+        repo.save_session(session).await.unwrap();
+
+        let (frames, events) = repo
+            .update_stream_data_atomic(
+                session_id,
+                stream_id,
+                serde_json::json!({"id": "abc", "count": 2}).into(),
+                16,
+            )
+            .await
+            .unwrap();
+        assert_eq!(frames.len(), 1);
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, DomainEvent::StreamUpdated { version: 1, .. }))
+        );
+
+        // Generation after an update restarts on the latest version.
+        let (frames, _) = repo
+            .create_stream_patch_frames_atomic(session_id, stream_id, Priority::BACKGROUND, 16)
+            .await
+            .unwrap();
+        let payloads = serde_json::to_string(
+            &frames
+                .iter()
+                .map(|frame| frame.payload())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert!(payloads.contains("\"value\":2"), "{payloads}");
+    }
+
     // ===== batch_generate_frames_atomic tests (#477) =====
 
     #[tokio::test]
@@ -2351,6 +2631,66 @@ mod tests {
         assert_eq!(session.stats().total_frames, (N * MAX_FRAMES) as u64);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_batch_generate_frames_atomic_races_stream_updates() {
+        use std::sync::Arc;
+
+        const N: usize = 20;
+
+        let repo = Arc::new(GatInMemoryStreamRepository::new());
+        let mut session = StreamSession::new(SessionConfig::default());
+        session.activate().unwrap();
+        let session_id = session.id();
+        let stream_id = session
+            .create_stream(serde_json::json!({"id": "abc", "count": 0}).into())
+            .unwrap();
+        session.start_stream(stream_id).unwrap();
+        let _ = session.take_events();
+        repo.save_session(session).await.unwrap();
+
+        let barrier = Arc::new(tokio::sync::Barrier::new(2 * N));
+        let mut handles = Vec::new();
+        for count in 1..=N {
+            let (updater, update_barrier) = (Arc::clone(&repo), Arc::clone(&barrier));
+            handles.push(tokio::spawn(async move {
+                update_barrier.wait().await;
+                updater
+                    .update_stream_data_atomic(
+                        session_id,
+                        stream_id,
+                        serde_json::json!({"id": "abc", "count": count}).into(),
+                        16,
+                    )
+                    .await
+                    .map(|_| ())
+            }));
+            let (generator, batch_barrier) = (Arc::clone(&repo), Arc::clone(&barrier));
+            handles.push(tokio::spawn(async move {
+                batch_barrier.wait().await;
+                generator
+                    .batch_generate_frames_atomic(session_id, Priority::BACKGROUND, 16)
+                    .await
+                    .map(|_| ())
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        let session = repo.find_session(session_id).await.unwrap().unwrap();
+        assert_eq!(session.streams()[&stream_id].version(), N as u64);
+    }
+
+    #[test]
+    fn test_from_config_uses_the_configured_thread_budget() {
+        let config = OrchestratorConfig {
+            generation_threads: 2,
+            ..OrchestratorConfig::default()
+        };
+        let repo = GatInMemoryStreamRepository::from_config(&config).unwrap();
+        assert_eq!(repo.generation_pool.current_num_threads(), 2);
+    }
+
     #[tokio::test]
     async fn test_close_session_atomic_closes_and_returns_events() {
         let repo = GatInMemoryStreamRepository::new();
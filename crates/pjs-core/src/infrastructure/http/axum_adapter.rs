@@ -3,20 +3,21 @@
 use crate::domain::value_objects::JsonData;
 use axum::{
     Json, Router,
-    extract::DefaultBodyLimit,
+    extract::{DefaultBodyLimit, FromRef},
     http::{
         HeaderValue, Method, StatusCode,
         header::{AUTHORIZATION, CONTENT_TYPE},
     },
     middleware,
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{get, post, put},
 };
 use serde::{Deserialize, Serialize};
 use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+use tokio::sync::watch;
 use tower::limit::GlobalConcurrencyLimitLayer;
 use tower_http::{
     cors::{AllowOrigin, CorsLayer},
@@ -32,6 +33,7 @@ use crate::{
         },
         queries::SortOrder,
     },
+    config::{PjsConfig, ReloadableConfig, security::JsonLimits},
     domain::{
         SessionState,
         aggregates::stream_session::SessionHealth,
@@ -40,11 +42,12 @@ use crate::{
             DictionaryStore, EventPublisherGat, FrameStoreGat, NoopDictionaryStore,
             SessionSortField, StreamRepositoryGat, StreamStoreGat,
         },
-        value_objects::{SessionId, StreamId},
+        value_objects::{JsonPathQuery, Priority, SessionId, StreamId},
     },
     infrastructure::{
         adapters::InMemoryFrameStore,
         http::middleware::{RateLimitMiddleware, security_middleware},
+        shutdown::DrainSignal,
     },
 };
 
@@ -57,8 +60,8 @@ use super::handlers::{
         session_health,
     },
     streams::{
-        create_stream, generate_frames, get_stream, get_stream_frames, start_stream,
-        stream_stream_frames,
+        cancel_stream, create_stream, generate_frames, get_stream, get_stream_frames, start_stream,
+        stream_stream_frames, update_stream_data,
     },
 };
 
@@ -221,6 +224,9 @@ where
     pub(crate) stream_query_handler: Arc<StreamQueryHandler<R, S, F>>,
     pub(crate) system_handler: Arc<SystemQueryHandler<R>>,
     pub(crate) dictionary_store: Arc<dyn DictionaryStore>,
+    pub(crate) drain_signal: Option<DrainSignal>,
+    pub(crate) json_limits: JsonLimits,
+    pub(crate) config: Option<watch::Receiver<Arc<PjsConfig>>>,
 }
 
 impl<R, P, S, F> Clone for PjsAppState<R, P, S, F>
@@ -237,6 +243,9 @@ where
             stream_query_handler: self.stream_query_handler.clone(),
             system_handler: self.system_handler.clone(),
             dictionary_store: self.dictionary_store.clone(),
+            drain_signal: self.drain_signal.clone(),
+            json_limits: self.json_limits.clone(),
+            config: self.config.clone(),
         }
     }
 }
@@ -317,8 +326,65 @@ where
             )),
             system_handler: Arc::new(SystemQueryHandler::with_start_time(repository, started_at)),
             dictionary_store,
+            drain_signal: None,
+            json_limits: JsonLimits::default(),
+            config: None,
         }
     }
+
+    /// Make frame-streaming routes cooperate with graceful shutdown.
+    ///
+    /// While `signal` is draining, new frame streams are refused with
+    /// `503 Service Unavailable` ([`PjsError::ShuttingDown`]) and streams
+    /// already in flight send only their critical frames, ending with a resume
+    /// hint if they cannot finish — see
+    /// [`BatchFrameStream::with_drain_signal`](super::BatchFrameStream::with_drain_signal).
+    pub fn with_drain_signal(mut self, signal: DrainSignal) -> Self {
+        self.drain_signal = Some(signal);
+        self
+    }
+
+    /// Limits enforced while parsing JSON request bodies (stream creation
+    /// and live updates), see [`JsonBody`](super::JsonBody).
+    ///
+    /// Defaults to [`JsonLimits::default`]. The router's 10 MiB
+    /// `DefaultBodyLimit` still applies on top of `max_input_size`.
+    pub fn with_json_limits(mut self, limits: JsonLimits) -> Self {
+        self.json_limits = limits;
+        self
+    }
+
+    /// Follow the hot-reloadable settings of `config`.
+    ///
+    /// `POST .../generate-frames` requests that omit `priority_threshold` use
+    /// `streaming.priority_threshold` as it stands when the request arrives,
+    /// so [`ReloadableConfig::apply`] takes effect without a restart. Without
+    /// a config the default is [`Priority::BACKGROUND`].
+    pub fn with_reloadable_config(mut self, config: &ReloadableConfig) -> Self {
+        self.config = Some(config.subscribe());
+        self
+    }
+
+    /// Priority threshold for frame generation when the request sets none.
+    pub(crate) fn default_priority_threshold(&self) -> u8 {
+        self.config
+            .as_ref()
+            .map_or(Priority::BACKGROUND.value(), |config| {
+                config.borrow().streaming.priority_threshold
+            })
+    }
+}
+
+impl<R, P, S, F> FromRef<PjsAppState<R, P, S, F>> for JsonLimits
+where
+    R: StreamRepositoryGat + Send + Sync + 'static,
+    P: EventPublisherGat + Send + Sync + 'static,
+    S: StreamStoreGat + Send + Sync + 'static,
+    F: FrameStoreGat + Send + Sync + 'static,
+{
+    fn from_ref(state: &PjsAppState<R, P, S, F>) -> Self {
+        state.json_limits.clone()
+    }
 }
 
 /// Request to create a new streaming session
@@ -346,6 +412,9 @@ pub struct CreateSessionResponse {
 }
 
 /// Request to start streaming data
+///
+/// Read from the request body by [`JsonBody`](super::JsonBody), which parses
+/// `data` straight into [`JsonData`] under the state's JSON limits.
 #[derive(Debug, Deserialize)]
 pub struct StartStreamRequest {
     /// JSON payload to be decomposed into priority frames.
@@ -374,7 +443,10 @@ pub struct StreamParams {
 ///
 /// Both fields are optional; defaults match the lowest-cost configuration that
 /// still drives the priority pipeline:
-/// - `priority_threshold` defaults to [`crate::domain::value_objects::Priority::BACKGROUND`] (10) — accepts every frame.
+/// - `priority_threshold` defaults to the live `streaming.priority_threshold`
+///   when the state follows a [`ReloadableConfig`] (see
+///   [`PjsAppState::with_reloadable_config`]), otherwise to
+///   [`Priority::BACKGROUND`] (10) — accepts every frame.
 /// - `max_frames` defaults to 16 — bounded so a single request cannot emit an
 ///   unbounded number of frames.
 ///
@@ -389,7 +461,22 @@ pub struct GenerateFramesRequest {
     pub max_frames: Option<usize>,
 }
 
-/// Response body for `POST .../streams/{stream_id}/generate-frames`.
+/// Request body for pushing a new version of a live stream's document.
+///
+/// `max_frames` defaults to 16 and is validated like
+/// [`GenerateFramesRequest::max_frames`].
+#[derive(Debug, Deserialize)]
+pub struct UpdateStreamDataRequest {
+    /// New version of the stream's JSON document.
+    ///
+    /// A `null` payload is rejected with `400 Bad Request`.
+    pub data: JsonData,
+    /// Maximum number of frames the diff is spread over.
+    pub max_frames: Option<usize>,
+}
+
+/// Response body for `POST .../streams/{stream_id}/generate-frames` and
+/// `PUT .../streams/{stream_id}/data`.
 ///
 /// Returns the frames produced by the stream's priority extractor, in the
 /// same shape as `GET .../frames` but freshly generated (and fed into the
@@ -626,6 +713,52 @@ where
     apply_common_layers(merged, config, Some(rate_limit))
 }
 
+/// Create PJS-enabled Axum router with JWT Bearer authentication, optionally
+/// rate limited.
+///
+/// The JWT counterpart of [`create_pjs_router_with_auth`] /
+/// [`create_pjs_router_with_rate_limit_and_auth`]: `/pjs/health` stays public,
+/// every other route requires a valid `Authorization: Bearer` token, and the
+/// layer ordering (including rate limiting sitting outside auth) is the same
+/// as documented on [`create_pjs_router_with_rate_limit_and_auth`].
+///
+/// # Errors
+///
+/// Returns [`PjsError::HttpError`] if `config` contains invalid CORS origins.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+/// use pjson_rs::infrastructure::http::{
+///     HttpServerConfig, auth::{JwtAuthLayer, JwtConfig},
+///     create_pjs_router_with_jwt_auth,
+/// };
+///
+/// let auth = JwtAuthLayer::<serde_json::Value>::new(JwtConfig {
+///     decoding_key: DecodingKey::from_secret(b"my-secret"),
+///     validation: Validation::new(Algorithm::HS256),
+/// });
+/// let router =
+///     create_pjs_router_with_jwt_auth::<R, P, S, _>(&HttpServerConfig::default(), None, auth)?;
+/// ```
+#[cfg(feature = "http-auth-jwt")]
+pub fn create_pjs_router_with_jwt_auth<R, P, S, C>(
+    config: &HttpServerConfig,
+    rate_limit: Option<RateLimitMiddleware>,
+    auth: crate::infrastructure::http::auth::JwtAuthLayer<C>,
+) -> Result<Router<PjsAppState<R, P, S>>, PjsError>
+where
+    R: StreamRepositoryGat + Send + Sync + 'static,
+    P: EventPublisherGat + Send + Sync + 'static,
+    S: StreamStoreGat + Send + Sync + 'static,
+    C: serde::de::DeserializeOwned + Send + Sync + 'static,
+{
+    let protected = protected_routes::<R, P, S>().layer(auth);
+    let merged = public_routes::<R, P, S>().merge(protected);
+    apply_common_layers(merged, config, rate_limit)
+}
+
 // ── Route table helpers ────────────────────────────────────────────────────────────
 
 /// Routes that are always public — no authentication applied.
@@ -678,9 +811,13 @@ where
             "/pjs/sessions/{session_id}/streams/{stream_id}/generate-frames",
             post(generate_frames::<R, P, S>),
         )
+        .route(
+            "/pjs/sessions/{session_id}/streams/{stream_id}/data",
+            put(update_stream_data::<R, P, S>),
+        )
         .route(
             "/pjs/sessions/{session_id}/streams/{stream_id}",
-            get(get_stream::<R, P, S>),
+            get(get_stream::<R, P, S>).delete(cancel_stream::<R, P, S>),
         )
         .route(
             "/pjs/sessions/{session_id}/streams/{stream_id}/frames",
@@ -904,6 +1041,15 @@ pub(crate) fn parse_sort_order(raw: String) -> Result<SortOrder, PjsError> {
         .map_err(|_| PjsError::InvalidSortOrder(raw))
 }
 
+/// Parse a raw `path` query-string value into a [`JsonPathQuery`], mapping failure to
+/// [`PjsError::InvalidPathQuery`].
+///
+/// Kept as a raw `String` on [`FrameQueryParams`] for the same reason as
+/// [`parse_sort_field`]: a bad query is rejected with the API's standard JSON error envelope.
+pub(crate) fn parse_path_query(raw: String) -> Result<JsonPathQuery, PjsError> {
+    JsonPathQuery::new(raw).map_err(|e| PjsError::InvalidPathQuery(e.to_string()))
+}
+
 /// Pagination parameters
 #[derive(Debug, Deserialize)]
 pub struct PaginationParams {
@@ -957,6 +1103,10 @@ pub struct FrameQueryParams {
     pub priority: Option<u8>,
     /// Maximum number of frames to return.
     pub limit: Option<usize>,
+    /// JSONPath query (RFC 9535, e.g. `$.user.posts[*].title`); only
+    /// patches touching a node it selects are returned. An invalid query is
+    /// rejected with `400 Bad Request`.
+    pub path: Option<String>,
 }
 
 // HTTP rate limiting is implemented by `RateLimitMiddleware`
@@ -1002,6 +1152,23 @@ pub enum PjsError {
     #[error("Invalid sort order: {0} (expected one of: asc, ascending, desc, descending)")]
     InvalidSortOrder(String),
 
+    /// A JSONPath query parameter could not be parsed.
+    #[error("Invalid path query: {0}")]
+    InvalidPathQuery(String),
+
+    /// The request body is not a well-formed JSON document of the expected
+    /// shape.
+    #[error("Invalid request body: {0}")]
+    InvalidBody(String),
+
+    /// The request body, or a value in it, exceeds a configured limit.
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    /// The request body is not declared as JSON.
+    #[error("Unsupported media type: expected application/json")]
+    UnsupportedMediaType,
+
     /// Generic HTTP-layer error not covered by other variants.
     ///
     /// # Invariant
@@ -1026,6 +1193,16 @@ pub enum PjsError {
     /// server startup.
     #[error("HTTP error: {0}")]
     HttpError(String),
+
+    /// The server is draining for shutdown and accepts no new streams.
+    #[error("Server is shutting down")]
+    ShuttingDown,
+}
+
+impl From<std::convert::Infallible> for PjsError {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
 }
 
 impl IntoResponse for PjsError {
@@ -1052,7 +1229,14 @@ impl IntoResponse for PjsError {
             PjsError::InvalidSessionState(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             PjsError::InvalidSortField(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             PjsError::InvalidSortOrder(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            PjsError::InvalidPathQuery(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            PjsError::InvalidBody(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            PjsError::PayloadTooLarge(_) => (StatusCode::PAYLOAD_TOO_LARGE, self.to_string()),
+            PjsError::UnsupportedMediaType => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, self.to_string())
+            }
             PjsError::HttpError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            PjsError::ShuttingDown => (StatusCode::SERVICE_UNAVAILABLE, self.to_string()),
         };
 
         let body = Json(serde_json::json!({
@@ -1932,6 +2116,144 @@ mod tests {
         );
     }
 
+    /// `PUT .../data` turns a started stream live and answers with the diff
+    /// frames for each new version.
+    #[tokio::test]
+    async fn update_stream_data_route_streams_diff_frames() {
+        use axum::body::to_bytes;
+        use axum::http::{Method, Request};
+        use tower::ServiceExt;
+
+        let state = PjsAppState::new(
+            Arc::new(MockRepository::new()),
+            Arc::new(MockEventPublisher),
+            Arc::new(MockStreamStore),
+        );
+        let router =
+            create_pjs_router_with_config::<MockRepository, MockEventPublisher, MockStreamStore>(
+                &HttpServerConfig::default(),
+            )
+            .expect("router should build")
+            .with_state(state);
+
+        let json_request = |method: Method, uri: String, body: serde_json::Value| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let resp = router
+            .clone()
+            .oneshot(json_request(
+                Method::POST,
+                "/pjs/sessions".into(),
+                serde_json::json!({}),
+            ))
+            .await
+            .unwrap();
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let session: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let session_id = session["session_id"].as_str().unwrap().to_string();
+
+        let resp = router
+            .clone()
+            .oneshot(json_request(
+                Method::POST,
+                format!("/pjs/sessions/{session_id}/streams"),
+                serde_json::json!({ "data": { "id": 7, "visitors": 10 } }),
+            ))
+            .await
+            .unwrap();
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let stream: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let stream_id = stream["stream_id"].as_str().unwrap().to_string();
+        let data_uri = format!("/pjs/sessions/{session_id}/streams/{stream_id}/data");
+
+        // Not started yet: updates are rejected.
+        let resp = router
+            .clone()
+            .oneshot(json_request(
+                Method::PUT,
+                data_uri.clone(),
+                serde_json::json!({ "data": { "id": 7, "visitors": 11 } }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        let start = Request::builder()
+            .method(Method::POST)
+            .uri(format!(
+                "/pjs/sessions/{session_id}/streams/{stream_id}/start"
+            ))
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let resp = router.clone().oneshot(start).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = router
+            .clone()
+            .oneshot(json_request(
+                Method::PUT,
+                data_uri.clone(),
+                serde_json::json!({ "data": { "id": 7, "visitors": 11 } }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["frame_count"], 1);
+        let patches = &payload["frames"][0]["payload"]["patches"];
+        assert_eq!(patches[0]["path"], "$.visitors");
+        assert_eq!(patches[0]["value"], 11);
+
+        let resp = router
+            .clone()
+            .oneshot(json_request(
+                Method::PUT,
+                data_uri,
+                serde_json::json!({ "data": null }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        // Subscribers can narrow stored frames to part of the document.
+        let frames_uri = format!("/pjs/sessions/{session_id}/streams/{stream_id}/frames");
+        let get = |uri: String| {
+            Request::builder()
+                .method(Method::GET)
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+        let resp = router
+            .clone()
+            .oneshot(get(format!("{frames_uri}?path=$.visitors")))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let frames = payload["frames"].as_array().unwrap();
+        assert!(!frames.is_empty());
+        for frame in frames {
+            for patch in frame["payload"]["patches"].as_array().unwrap() {
+                assert_eq!(patch["path"], "$.visitors");
+            }
+        }
+
+        let resp = router
+            .oneshot(get(format!("{frames_uri}?path=%24%5B")))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
     /// End-to-end dictionary path: drive `generate-frames` enough times to
     /// cross the `N_TRAIN` threshold, then assert the dictionary endpoint
     /// transitions from `404 Not Found` to `200 OK`. This is the chain that
@@ -1981,12 +2303,13 @@ mod tests {
 
         // Source data with N_TRAIN+ leaf patches keeps the test self-contained:
         // a single generate-frames call yields enough samples to cross the
-        // training threshold.
+        // training threshold. Each value is over half the default
+        // `max_frame_size`, so no two patches share a frame.
         let mut payload = serde_json::Map::new();
         for i in 0..(N_TRAIN + 4) {
             payload.insert(
                 format!("field_{i}"),
-                serde_json::Value::String(format!("value_{i}")),
+                serde_json::Value::String(format!("value_{i}_{}", "x".repeat(40 * 1024))),
             );
         }
         let create_stream = Request::builder()
@@ -2027,7 +2350,7 @@ mod tests {
         );
 
         // Generate enough frames to cross N_TRAIN. With max_frames at least
-        // N_TRAIN+4, every leaf patch lands in its own frame.
+        // N_TRAIN+4, every leaf patch is sent in this one call.
         let max_frames = N_TRAIN + 4;
         let generate = Request::builder()
             .method(Method::POST)
@@ -2105,4 +2428,103 @@ mod tests {
         let resp = router.oneshot(req).await.unwrap();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
+
+    /// A generate-frames request without `priority_threshold` follows the
+    /// live `streaming.priority_threshold`: after a reload, the same document
+    /// streams only the frames above the new threshold.
+    #[tokio::test]
+    async fn generate_frames_route_follows_reloaded_priority_threshold() {
+        use crate::config::{PjsConfig, ReloadableConfig};
+        use axum::body::to_bytes;
+        use axum::http::{Method, Request};
+        use tower::ServiceExt;
+
+        let config = ReloadableConfig::new(PjsConfig::default()).unwrap();
+        let state = PjsAppState::new(
+            Arc::new(MockRepository::new()),
+            Arc::new(MockEventPublisher),
+            Arc::new(MockStreamStore),
+        )
+        .with_reloadable_config(&config);
+        let router =
+            create_pjs_router_with_config::<MockRepository, MockEventPublisher, MockStreamStore>(
+                &HttpServerConfig::default(),
+            )
+            .expect("router should build")
+            .with_state(state);
+
+        let send = |request: Request<axum::body::Body>| {
+            let router = router.clone();
+            async move {
+                let resp = router.oneshot(request).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::OK);
+                let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+        let post = |uri: String, body: serde_json::Value| {
+            Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let session = send(post("/pjs/sessions".into(), serde_json::json!({}))).await;
+        let session_id = session["session_id"].as_str().unwrap().to_string();
+        let frame_priorities = |frames: serde_json::Value| -> Vec<u64> {
+            frames["frames"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|frame| frame["priority"].as_u64().unwrap())
+                .collect()
+        };
+        let generate = |session_id: String| {
+            let send = &send;
+            async move {
+                let stream = send(post(
+                    format!("/pjs/sessions/{session_id}/streams"),
+                    serde_json::json!({
+                        "data": { "id": 7, "notes": "archived", "tags": ["a", "b"] }
+                    }),
+                ))
+                .await;
+                let stream_id = stream["stream_id"].as_str().unwrap().to_string();
+                send(post(
+                    format!("/pjs/sessions/{session_id}/streams/{stream_id}/start"),
+                    serde_json::json!({}),
+                ))
+                .await;
+                send(post(
+                    format!("/pjs/sessions/{session_id}/streams/{stream_id}/generate-frames"),
+                    serde_json::json!({}),
+                ))
+                .await
+            }
+        };
+
+        let before = frame_priorities(generate(session_id.clone()).await);
+        assert!(
+            before
+                .iter()
+                .any(|&p| p < u64::from(Priority::CRITICAL.value())),
+            "the default threshold admits non-critical frames: {before:?}"
+        );
+
+        let mut candidate = PjsConfig::default();
+        candidate.streaming.priority_threshold = Priority::CRITICAL.value();
+        let report = config.apply(candidate).unwrap();
+        assert_eq!(report.applied, ["streaming.priority_threshold"]);
+
+        let after = frame_priorities(generate(session_id).await);
+        assert!(!after.is_empty());
+        assert!(
+            after
+                .iter()
+                .all(|&p| p >= u64::from(Priority::CRITICAL.value())),
+            "the reloaded threshold filters out non-critical frames: {after:?}"
+        );
+    }
 }
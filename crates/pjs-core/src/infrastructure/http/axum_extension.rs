@@ -5,17 +5,27 @@
 
 use axum::{
     Extension, Json,
-    extract::{Path, Query, Request, State},
+    extract::{ConnectInfo, Path, Request, State},
     http::{HeaderMap, StatusCode, header},
     middleware::Next,
     response::{IntoResponse, Response},
 };
-use futures::StreamExt;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::streaming::{ContentEncoding, batch_compressor, compress_batch, set_content_encoding};
+use crate::{Priority, PriorityStreamer, stream::PriorityStreamFrame};
 
-use crate::{Priority, PriorityStreamer};
+/// Upper bound on streams held across all clients, so the store stays
+/// bounded however many distinct addresses create streams.
+const MAX_STORED_STREAMS: usize = 10_000;
 
 /// Configuration for PJS extension
 #[derive(Debug, Clone)]
@@ -26,9 +36,17 @@ pub struct HttpExtensionConfig {
     pub auto_detect: bool,
     /// Default priority for streaming
     pub default_priority: Priority,
-    /// Maximum concurrent streams per client
+    /// Maximum number of streams one client address may hold at once.
+    ///
+    /// A stream created with `POST {route_prefix}/stream` counts against
+    /// its creator until it has been streamed to the end or has expired;
+    /// creating one more answers `429 Too Many Requests`. Clients are told
+    /// apart by the peer address axum's `ConnectInfo` reports, so the router
+    /// must be served with `into_make_service_with_connect_info::<SocketAddr>()`
+    /// — without it stream creation fails closed with `500`, rather than
+    /// charging every client to one shared budget.
     pub max_streams_per_client: usize,
-    /// Session timeout
+    /// How long a created stream stays retrievable before it is dropped.
     pub session_timeout: Duration,
     /// Origins allowed to receive `Access-Control-Allow-Origin` on the PJS
     /// routes mounted by [`PjsExtension::extend_router`] (including the SSE
@@ -85,12 +103,14 @@ impl Default for HttpExtensionConfig {
 pub struct PjsExtension {
     config: HttpExtensionConfig,
     streamer: Arc<PriorityStreamer>,
+    store: Arc<ExtensionStreamStore>,
 }
 
 impl PjsExtension {
     /// Build a new extension with the given configuration.
     pub fn new(config: HttpExtensionConfig) -> Self {
         Self {
+            store: Arc::new(ExtensionStreamStore::new(&config)),
             config,
             streamer: Arc::new(PriorityStreamer::new()),
         }
@@ -127,7 +147,8 @@ impl PjsExtension {
             )
             .route("/health", axum::routing::get(handle_pjs_health))
             .layer(Extension(self.config.clone()))
-            .layer(Extension(self.streamer.clone()));
+            .layer(Extension(self.streamer.clone()))
+            .layer(Extension(self.store.clone()));
 
         // `allowed_origins` defaults to empty (same-origin only, see its doc
         // for the CWE-942 rationale), so no CORS layer is added unless the
@@ -153,6 +174,99 @@ impl PjsExtension {
     }
 }
 
+/// A stream created by `POST /stream`, waiting to be served.
+struct StoredStream {
+    client: IpAddr,
+    created_at: Instant,
+    frames: Arc<[PriorityStreamFrame]>,
+}
+
+/// Streams created through a [`PjsExtension`], bounded per client and in
+/// total, and dropped once [`HttpExtensionConfig::session_timeout`] has
+/// passed since their creation.
+///
+/// A stream is removed as soon as its SSE response body is dropped — streamed
+/// to the end or abandoned part-way — so a client that never reads the body
+/// does not hold the slot until it expires. Expired entries are swept
+/// whenever a stream is created.
+struct ExtensionStreamStore {
+    streams: Mutex<HashMap<String, StoredStream>>,
+    max_streams_per_client: usize,
+    ttl: Duration,
+}
+
+impl ExtensionStreamStore {
+    fn new(config: &HttpExtensionConfig) -> Self {
+        Self {
+            streams: Mutex::new(HashMap::new()),
+            max_streams_per_client: config.max_streams_per_client,
+            ttl: config.session_timeout,
+        }
+    }
+
+    /// Store `frames` on behalf of `client`, returning the new stream's id.
+    fn insert(
+        &self,
+        client: IpAddr,
+        frames: Vec<PriorityStreamFrame>,
+    ) -> Result<String, StreamExtensionError> {
+        let mut streams = self.streams.lock();
+        let now = Instant::now();
+        streams.retain(|_, stream| now.duration_since(stream.created_at) < self.ttl);
+
+        let held = streams
+            .values()
+            .filter(|stream| stream.client == client)
+            .count();
+        if held >= self.max_streams_per_client {
+            return Err(StreamExtensionError::TooManyStreams(
+                self.max_streams_per_client,
+            ));
+        }
+        if streams.len() >= MAX_STORED_STREAMS {
+            return Err(StreamExtensionError::StoreFull);
+        }
+
+        let stream_id = uuid::Uuid::new_v4().to_string();
+        streams.insert(
+            stream_id.clone(),
+            StoredStream {
+                client,
+                created_at: now,
+                frames: frames.into(),
+            },
+        );
+        Ok(stream_id)
+    }
+
+    /// Frames of `stream_id`, unless it is unknown or expired.
+    fn get(&self, stream_id: &str) -> Option<Arc<[PriorityStreamFrame]>> {
+        let mut streams = self.streams.lock();
+        let stream = streams.get(stream_id)?;
+        if stream.created_at.elapsed() < self.ttl {
+            return Some(stream.frames.clone());
+        }
+        streams.remove(stream_id);
+        None
+    }
+
+    fn remove(&self, stream_id: &str) {
+        self.streams.lock().remove(stream_id);
+    }
+}
+
+/// Frees a stream's slot when the SSE body holding it is dropped.
+struct StreamSlot {
+    store: Arc<ExtensionStreamStore>,
+    stream_id: String,
+}
+
+impl Drop for StreamSlot {
+    fn drop(&mut self) {
+        self.store.remove(&self.stream_id);
+    }
+}
+
 /// Middleware that automatically detects PJS streaming requests
 #[allow(clippy::extra_unused_type_parameters)]
 async fn pjs_middleware<S>(
@@ -218,13 +332,26 @@ pub struct StreamResponse {
 }
 
 /// Handle stream creation request
+///
+/// Analyzes the posted document and stores its frames for
+/// `GET {route_prefix}/stream/{stream_id}/sse`. Without `ConnectInfo` the
+/// request fails with [`StreamExtensionError::MissingClientAddress`].
 async fn handle_stream_request(
     Extension(config): Extension<HttpExtensionConfig>,
     Extension(streamer): Extension<Arc<PriorityStreamer>>,
+    Extension(store): Extension<Arc<ExtensionStreamStore>>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
     headers: HeaderMap,
     Json(request): Json<StreamRequest>,
 ) -> Result<impl IntoResponse, StreamExtensionError> {
-    let stream_id = uuid::Uuid::new_v4().to_string();
+    let Some(Extension(ConnectInfo(peer))) = connect_info else {
+        tracing::error!(
+            "PjsExtension: no ConnectInfo on the request; serve the router with \
+             `into_make_service_with_connect_info::<SocketAddr>()`"
+        );
+        return Err(StreamExtensionError::MissingClientAddress);
+    };
+    let client = peer.ip().to_canonical();
 
     // Create streaming plan
     let plan = streamer
@@ -247,15 +374,15 @@ async fn handle_stream_request(
             .unwrap_or_else(|| "json".to_string())
     });
 
+    let estimated_frames = plan.remaining_frames();
+    let stream_id = store.insert(client, plan.frames.into())?;
+
     let response = StreamResponse {
         stream_id: stream_id.clone(),
-        format: format.clone(),
-        estimated_frames: plan.frames().count(),
+        format,
+        estimated_frames,
     };
 
-    // Store stream for later retrieval
-    // In production, this would use a proper store
-
     Ok((
         StatusCode::CREATED,
         [(
@@ -273,51 +400,76 @@ async fn handle_stream_request(
 /// wraps the PJS routes in, driven by [`HttpExtensionConfig::allowed_origins`]
 /// — see that field's docs for why this handler must not impose its own
 /// unconditional CORS policy (CWE-942).
+///
+/// The body is content-encoded as negotiated by `Accept-Encoding` (see
+/// [`ContentEncoding::from_accept_encoding`]), one flushed chunk per event,
+/// so events still arrive as they are sent.
 async fn handle_sse_stream(
-    Path(_stream_id): Path<String>,
-    Extension(streamer): Extension<Arc<PriorityStreamer>>,
-    Query(_params): Query<HashMap<String, String>>,
+    Path(stream_id): Path<String>,
+    Extension(store): Extension<Arc<ExtensionStreamStore>>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, StreamExtensionError> {
-    // In production, retrieve stream data from store using stream_id
-    let sample_data = serde_json::json!({
-        "products": [
-            {"id": 1, "name": "Product A", "price": 19.99, "category": "electronics"},
-            {"id": 2, "name": "Product B", "price": 29.99, "category": "books"},
-            {"id": 3, "name": "Product C", "price": 39.99, "category": "clothing"}
-        ],
-        "metadata": {
-            "total": 3,
-            "updated_at": "2024-01-01T00:00:00Z"
-        }
+    let frames = store
+        .get(&stream_id)
+        .ok_or_else(|| StreamExtensionError::StreamNotFound(stream_id.clone()))?;
+
+    let mut encoding = ContentEncoding::from_accept_encoding(&headers);
+    let mut compressor = batch_compressor(encoding).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "sending SSE stream uncompressed");
+        encoding = ContentEncoding::Identity;
+        None
     });
 
-    let plan = streamer
-        .analyze(&sample_data)
-        .map_err(|e| StreamExtensionError::AnalysisError(e.to_string()))?;
-
-    // Collect frames to avoid lifetime issues
-    let frames: Vec<_> = plan.frames().cloned().collect();
-    let stream = futures::stream::iter(frames).map(|frame| {
-        // JsonData::float rejects NaN/Infinity at construction (RFC 8259 §6), so
-        // any Frame built through the public API cannot contain non-finite floats and
-        // serialization is therefore infallible on this path.
-        let data = serde_json::to_string(&frame).expect(
-            "Frame serialization is infallible: JsonData rejects NaN/Infinity at construction",
-        );
-        Ok::<_, StreamExtensionError>(format!("data: {data}\n\n"))
-    });
+    // Dropped with the body, whether it was read to the end or not.
+    let slot = StreamSlot { store, stream_id };
+    let stream = async_stream::stream! {
+        let _slot = slot;
+        for frame in frames.iter() {
+            let (event, failed) = match serde_json::to_string(frame) {
+                Ok(data) => (format!("data: {data}\n\n"), false),
+                Err(e) => {
+                    tracing::error!(error = %e, "failed to serialize SSE frame");
+                    (sse_error_event(&e.to_string()), true)
+                }
+            };
+            match compress_batch(&mut compressor, event.into_bytes()) {
+                Ok(bytes) => yield Ok::<_, StreamExtensionError>(bytes),
+                Err(e) => {
+                    yield Err(StreamExtensionError::Compression(e.to_string()));
+                    return;
+                }
+            }
+            if failed {
+                break;
+            }
+        }
+        if let Some(compressor) = compressor {
+            yield compressor
+                .finish()
+                .map_err(|e| StreamExtensionError::Compression(e.to_string()));
+        }
+    };
 
-    let response = axum::response::Response::builder()
+    let mut response = axum::response::Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "text/event-stream")
         .header(header::CACHE_CONTROL, "no-cache")
         .header(header::CONNECTION, "keep-alive")
         .body(axum::body::Body::from_stream(stream))
         .map_err(|e| StreamExtensionError::ResponseError(e.to_string()))?;
+    set_content_encoding(&mut response, encoding);
 
     Ok(response)
 }
 
+/// An SSE `error` event ending a stream that could not be sent in full.
+fn sse_error_event(message: &str) -> String {
+    format!(
+        "event: error\ndata: {}\n\n",
+        serde_json::json!({ "error": message })
+    )
+}
+
 /// Health check for PJS extension
 async fn handle_pjs_health() -> Json<serde_json::Value> {
     Json(serde_json::json!({
@@ -347,6 +499,23 @@ pub enum StreamExtensionError {
     /// Requested stream identifier is unknown to the extension.
     #[error("Stream not found: {0}")]
     StreamNotFound(String),
+
+    /// The client already holds the maximum number of streams.
+    #[error("Too many streams: at most {0} per client")]
+    TooManyStreams(usize),
+
+    /// The extension holds as many streams as it will store.
+    #[error("Stream store is full")]
+    StoreFull,
+
+    /// The request carries no peer address to charge its stream to: the
+    /// router is not served with `into_make_service_with_connect_info`.
+    #[error("Client address unavailable")]
+    MissingClientAddress,
+
+    /// The stream body could not be content-encoded.
+    #[error("Compression error: {0}")]
+    Compression(String),
 }
 
 impl IntoResponse for StreamExtensionError {
@@ -357,6 +526,13 @@ impl IntoResponse for StreamExtensionError {
                 (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
             }
             StreamExtensionError::StreamNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            StreamExtensionError::TooManyStreams(_) => {
+                (StatusCode::TOO_MANY_REQUESTS, self.to_string())
+            }
+            StreamExtensionError::StoreFull => (StatusCode::SERVICE_UNAVAILABLE, self.to_string()),
+            StreamExtensionError::MissingClientAddress | StreamExtensionError::Compression(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
+            }
         };
 
         (status, Json(serde_json::json!({"error": message}))).into_response()
@@ -446,6 +622,235 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    const CLIENT: ([u8; 4], u16) = ([192, 0, 2, 1], 4000);
+
+    fn create_stream(data: JsonValue) -> axum::http::Request<axum::body::Body> {
+        create_stream_from(Some(SocketAddr::from(CLIENT)), data)
+    }
+
+    fn create_stream_from(
+        peer: Option<SocketAddr>,
+        data: JsonValue,
+    ) -> axum::http::Request<axum::body::Body> {
+        let mut request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/pjs/stream")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(
+                serde_json::json!({ "data": data }).to_string(),
+            ))
+            .unwrap();
+        if let Some(peer) = peer {
+            request.extensions_mut().insert(ConnectInfo(peer));
+        }
+        request
+    }
+
+    fn sse(location: &str) -> axum::http::Request<axum::body::Body> {
+        axum::http::Request::builder()
+            .uri(format!("{location}/sse"))
+            .body(axum::body::Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sse_streams_the_posted_document() {
+        let app = PjsExtension::new(HttpExtensionConfig::default()).extend_router(Router::new());
+        let document = serde_json::json!({ "id": 7, "items": ["needle-42"] });
+
+        let response = app.clone().oneshot(create_stream(document)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let location = response.headers()[header::LOCATION]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let created: JsonValue = serde_json::from_slice(&body).unwrap();
+
+        let response = app.clone().oneshot(sse(&location)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        assert!(
+            text.contains("needle-42"),
+            "must stream the posted document"
+        );
+        assert_eq!(
+            text.matches("data: ").count() as u64,
+            created["estimated_frames"].as_u64().unwrap()
+        );
+
+        // Streamed in full, so it no longer holds a slot.
+        let response = app.oneshot(sse(&location)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_sse_stream_honors_accept_encoding() {
+        use std::io::Read;
+
+        let app = PjsExtension::new(HttpExtensionConfig::default()).extend_router(Router::new());
+        let document = serde_json::json!({ "id": 7, "items": ["needle-42"] });
+        let mut locations = Vec::new();
+        for _ in 0..2 {
+            let response = app
+                .clone()
+                .oneshot(create_stream(document.clone()))
+                .await
+                .unwrap();
+            locations.push(
+                response.headers()[header::LOCATION]
+                    .to_str()
+                    .unwrap()
+                    .to_string(),
+            );
+        }
+
+        let plain = app.clone().oneshot(sse(&locations[0])).await.unwrap();
+        assert!(plain.headers().get(header::CONTENT_ENCODING).is_none());
+        let plain = axum::body::to_bytes(plain.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        let mut request = sse(&locations[1]);
+        request
+            .headers_mut()
+            .insert(header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, plain);
+    }
+
+    #[tokio::test]
+    async fn test_streams_are_limited_per_client() {
+        let config = HttpExtensionConfig {
+            max_streams_per_client: 1,
+            ..Default::default()
+        };
+        let app = PjsExtension::new(config).extend_router(Router::new());
+
+        let response = app
+            .clone()
+            .oneshot(create_stream(serde_json::json!({ "a": 1 })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = app
+            .oneshot(create_stream(serde_json::json!({ "b": 2 })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_clients_without_connect_info_cannot_exhaust_other_quotas() {
+        let config = HttpExtensionConfig {
+            max_streams_per_client: 1,
+            ..Default::default()
+        };
+        let app = PjsExtension::new(config).extend_router(Router::new());
+
+        for _ in 0..3 {
+            let response = app
+                .clone()
+                .oneshot(create_stream_from(None, serde_json::json!({ "a": 1 })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        for peer in [CLIENT, ([198, 51, 100, 7], 5000)] {
+            let response = app
+                .clone()
+                .oneshot(create_stream_from(
+                    Some(SocketAddr::from(peer)),
+                    serde_json::json!({ "a": 1 }),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dropped_sse_body_frees_the_slot() {
+        let config = HttpExtensionConfig {
+            max_streams_per_client: 1,
+            ..Default::default()
+        };
+        let app = PjsExtension::new(config).extend_router(Router::new());
+
+        let response = app
+            .clone()
+            .oneshot(create_stream(serde_json::json!({ "a": 1 })))
+            .await
+            .unwrap();
+        let location = response.headers()[header::LOCATION]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // The client hangs up without reading a byte of the body.
+        let response = app.clone().oneshot(sse(&location)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        drop(response);
+
+        let response = app.clone().oneshot(sse(&location)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = app
+            .oneshot(create_stream(serde_json::json!({ "b": 2 })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_unknown_streams_are_not_found() {
+        let config = HttpExtensionConfig {
+            session_timeout: Duration::ZERO,
+            max_streams_per_client: 1,
+            ..Default::default()
+        };
+        let app = PjsExtension::new(config).extend_router(Router::new());
+
+        for _ in 0..2 {
+            // Expired streams do not count against the client either.
+            let response = app
+                .clone()
+                .oneshot(create_stream(serde_json::json!({ "a": 1 })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let location = response.headers()[header::LOCATION]
+                .to_str()
+                .unwrap()
+                .to_string();
+
+            let response = app.clone().oneshot(sse(&location)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        let response = app
+            .oneshot(sse(&format!("/pjs/stream/{}", uuid::Uuid::new_v4())))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
     #[tokio::test]
     async fn test_auto_detection_middleware() {
         let config = HttpExtensionConfig::default();
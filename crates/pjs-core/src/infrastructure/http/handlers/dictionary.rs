@@ -137,6 +137,12 @@ mod tests {
             + 'a
         where
             Self: 'a;
+        type CancelStreamAtomicFuture<'a>
+            = impl std::future::Future<Output = crate::domain::DomainResult<Vec<DomainEvent>>>
+            + Send
+            + 'a
+        where
+            Self: 'a;
         type CreateStreamPatchFramesAtomicFuture<'a>
             = impl std::future::Future<
                 Output = crate::domain::DomainResult<(
@@ -147,6 +153,17 @@ mod tests {
             + 'a
         where
             Self: 'a;
+
+        type UpdateStreamDataAtomicFuture<'a>
+            = impl std::future::Future<
+                Output = crate::domain::DomainResult<(
+                    Vec<crate::domain::entities::Frame>,
+                    Vec<DomainEvent>,
+                )>,
+            > + Send
+            + 'a
+        where
+            Self: 'a;
         type BatchGenerateFramesAtomicFuture<'a>
             = impl std::future::Future<
                 Output = crate::domain::DomainResult<(
@@ -242,6 +259,20 @@ mod tests {
                 Ok(session.take_events().into_iter().collect())
             }
         }
+        fn cancel_stream_atomic(
+            &self,
+            sid: SessionId,
+            stream_id: StreamId,
+        ) -> Self::CancelStreamAtomicFuture<'_> {
+            async move {
+                let mut sessions = self.0.lock();
+                let session = sessions.get_mut(&sid).ok_or_else(|| {
+                    crate::domain::DomainError::SessionNotFound(format!("Session {sid} not found"))
+                })?;
+                session.cancel_stream(stream_id)?;
+                Ok(session.take_events().into_iter().collect())
+            }
+        }
         fn create_stream_patch_frames_atomic(
             &self,
             sid: SessionId,
@@ -254,11 +285,24 @@ mod tests {
                 let session = sessions.get_mut(&sid).ok_or_else(|| {
                     crate::domain::DomainError::SessionNotFound(format!("Session {sid} not found"))
                 })?;
-                let frames = session.create_stream_patch_frames(
-                    stream_id,
-                    priority_threshold,
-                    max_frames,
-                )?;
+                let frames =
+                    session.next_stream_patch_frames(stream_id, priority_threshold, max_frames)?;
+                Ok((frames, session.take_events().into_iter().collect()))
+            }
+        }
+        fn update_stream_data_atomic(
+            &self,
+            sid: SessionId,
+            stream_id: StreamId,
+            source_data: crate::domain::value_objects::JsonData,
+            max_frames: usize,
+        ) -> Self::UpdateStreamDataAtomicFuture<'_> {
+            async move {
+                let mut sessions = self.0.lock();
+                let session = sessions.get_mut(&sid).ok_or_else(|| {
+                    crate::domain::DomainError::SessionNotFound(format!("Session {sid} not found"))
+                })?;
+                let frames = session.update_stream_data(stream_id, source_data, max_frames)?;
                 Ok((frames, session.take_events().into_iter().collect()))
             }
         }
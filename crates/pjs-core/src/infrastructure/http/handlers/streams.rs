@@ -1,4 +1,5 @@
-//! Stream lifecycle handlers: create, start, generate frames, fetch, list frames.
+//! Stream lifecycle handlers: create, start, generate frames, update data,
+//! cancel, fetch, list frames.
 
 use axum::{
     Json,
@@ -10,7 +11,10 @@ use futures::stream;
 
 use crate::{
     application::{
-        commands::{CreateStreamCommand, GenerateFramesCommand, StartStreamCommand},
+        commands::{
+            CancelStreamCommand, CreateStreamCommand, GenerateFramesCommand, StartStreamCommand,
+            UpdateStreamDataCommand,
+        },
         dto::PriorityDto,
         handlers::{
             CommandHandlerGat, QueryHandlerGat, command_handlers::SessionCommandHandler,
@@ -28,21 +32,27 @@ use crate::{
         http::{
             axum_adapter::{
                 FrameQueryParams, GenerateFramesRequest, GenerateFramesResponse, PjsAppState,
-                PjsError, StartStreamRequest, parse_session_and_stream_id, parse_session_id,
+                PjsError, StartStreamRequest, UpdateStreamDataRequest, parse_path_query,
+                parse_session_and_stream_id, parse_session_id,
             },
+            json_body::JsonBody,
             streaming::{
-                BatchFrameStream, StreamFormat, create_streaming_response,
-                create_streaming_response_with_content_type,
+                BatchFrameStream, ContentEncoding, StreamFormat, create_streaming_response,
+                create_streaming_response_with_content_type, set_content_encoding,
             },
         },
+        shutdown::DrainSignal,
     },
 };
 
-/// Create a new stream within a session
+/// Create a new stream within a session.
+///
+/// The body is parsed straight into the stream's document by [`JsonBody`],
+/// under the state's [`JsonLimits`](crate::config::security::JsonLimits).
 pub(crate) async fn create_stream<R, P, S>(
     State(state): State<PjsAppState<R, P, S>>,
     AxumPath(session_id): AxumPath<String>,
-    Json(request): Json<StartStreamRequest>,
+    JsonBody(request): JsonBody<StartStreamRequest>,
 ) -> Result<Json<serde_json::Value>, PjsError>
 where
     R: StreamRepositoryGat + Send + Sync + 'static,
@@ -120,7 +130,7 @@ where
 
     let priority_value = request
         .priority_threshold
-        .unwrap_or(Priority::BACKGROUND.value());
+        .unwrap_or_else(|| state.default_priority_threshold());
     let priority_threshold =
         PriorityDto::new(priority_value).map_err(|e| PjsError::InvalidPriority(e.to_string()))?;
     let max_frames = request.max_frames.unwrap_or(16);
@@ -145,6 +155,79 @@ where
     }))
 }
 
+/// Push a new version of a stream's document.
+///
+/// Dispatches [`UpdateStreamDataCommand`]: the stream switches to live mode
+/// and the response carries the frames diffing the previous version against
+/// this one, highest priority first. The stream stays open for further
+/// updates until completed.
+pub(crate) async fn update_stream_data<R, P, S>(
+    State(state): State<PjsAppState<R, P, S>>,
+    AxumPath((session_id, stream_id)): AxumPath<(String, String)>,
+    JsonBody(request): JsonBody<UpdateStreamDataRequest>,
+) -> Result<Json<GenerateFramesResponse>, PjsError>
+where
+    R: StreamRepositoryGat + Send + Sync + 'static,
+    P: EventPublisherGat + Send + Sync + 'static,
+    S: StreamStoreGat + Send + Sync + 'static,
+{
+    let (session_id, stream_id) = parse_session_and_stream_id(session_id, stream_id)?;
+
+    let command = UpdateStreamDataCommand {
+        session_id: session_id.into(),
+        stream_id: stream_id.into(),
+        source_data: request.data,
+        max_frames: request.max_frames.unwrap_or(16),
+    };
+
+    let frames: Vec<Frame> = <SessionCommandHandler<R, P> as CommandHandlerGat<
+        UpdateStreamDataCommand,
+    >>::handle(&*state.command_handler, command)
+    .await
+    .map_err(PjsError::Application)?;
+
+    let frame_count = frames.len();
+    Ok(Json(GenerateFramesResponse {
+        frames,
+        frame_count,
+    }))
+}
+
+/// Cancel a stream that has not finished yet.
+///
+/// Dispatches [`CancelStreamCommand`]: the stream moves to `Cancelled`, a
+/// `StreamCancelled` event is published and its stored frames are released.
+/// Cancelling a stream that already completed, failed or was cancelled
+/// answers `409 Conflict`.
+pub(crate) async fn cancel_stream<R, P, S>(
+    State(state): State<PjsAppState<R, P, S>>,
+    AxumPath((session_id, stream_id)): AxumPath<(String, String)>,
+) -> Result<Json<serde_json::Value>, PjsError>
+where
+    R: StreamRepositoryGat + Send + Sync + 'static,
+    P: EventPublisherGat + Send + Sync + 'static,
+    S: StreamStoreGat + Send + Sync + 'static,
+{
+    let (session_id, stream_id) = parse_session_and_stream_id(session_id, stream_id)?;
+
+    let command = CancelStreamCommand {
+        session_id: session_id.into(),
+        stream_id: stream_id.into(),
+    };
+
+    <SessionCommandHandler<R, P> as CommandHandlerGat<CancelStreamCommand>>::handle(
+        &*state.command_handler,
+        command,
+    )
+    .await
+    .map_err(PjsError::Application)?;
+
+    Ok(Json(serde_json::json!({
+        "stream_id": stream_id.to_string(),
+        "status": "cancelled"
+    })))
+}
+
 /// Get stream information
 pub(crate) async fn get_stream<R, P, S>(
     State(state): State<PjsAppState<R, P, S>>,
@@ -196,6 +279,7 @@ where
         since_sequence: params.since_sequence,
         priority_filter,
         limit: params.limit,
+        path_filter: params.path.map(parse_path_query).transpose()?,
     };
 
     let response = <StreamQueryHandler<R, S, InMemoryFrameStore> as QueryHandlerGat<
@@ -225,12 +309,17 @@ where
 /// |---|---|---|
 /// | `text/event-stream` | Server-Sent Events | `text/event-stream` |
 /// | `application/x-ndjson` | Newline-delimited JSON | `application/x-ndjson` |
+/// | `application/pjs+json` | Spec frames, one per line (see [`crate::stream::pjs_json`]) | `application/pjs+json`, with `PJS-Version: 1.0` |
 /// | anything else — absent, `*/*`, `application/json`, `application/octet-stream`, unknown | JSON (NDJSON-of-objects) | `application/x-ndjson` |
 ///
 /// `application/octet-stream` does not select a binary wire format: this route has
 /// no real binary representation, so it falls back to NDJSON like every other
 /// unmatched `Accept`.
 ///
+/// The body is compressed per [`ContentEncoding::from_accept_encoding`] —
+/// `zstd`, `br` or `gzip`, each flushed after every frame so delivery stays
+/// incremental — and the response carries `Vary: Accept-Encoding`.
+///
 /// # Not a live tail
 ///
 /// This endpoint streams **one already-materialized page**, not a persistent
@@ -248,6 +337,15 @@ where
 ///
 /// The response carries `X-Total-Count`, mirroring [`FramesResponse::total_count`],
 /// since a frame-delimited stream has no envelope to carry it in-body.
+///
+/// # Client disconnects
+///
+/// A client that hangs up before the page was fully sent is taken to have
+/// abandoned the stream: a [`CancelStreamCommand`] is dispatched in the
+/// background, exactly as if it had called
+/// `DELETE /pjs/sessions/{session_id}/streams/{stream_id}`. Failures — most
+/// commonly a stream that already finished — are logged and otherwise
+/// ignored.
 pub(crate) async fn stream_stream_frames<R, P, S>(
     State(state): State<PjsAppState<R, P, S>>,
     AxumPath((session_id, stream_id)): AxumPath<(String, String)>,
@@ -259,6 +357,14 @@ where
     P: EventPublisherGat + Send + Sync + 'static,
     S: StreamStoreGat + Send + Sync + 'static,
 {
+    if state
+        .drain_signal
+        .as_ref()
+        .is_some_and(DrainSignal::is_draining)
+    {
+        return Err(PjsError::ShuttingDown);
+    }
+
     let (session_id, stream_id) = parse_session_and_stream_id(session_id, stream_id)?;
 
     let priority_filter = params
@@ -273,6 +379,7 @@ where
         since_sequence: params.since_sequence,
         priority_filter,
         limit: params.limit,
+        path_filter: params.path.map(parse_path_query).transpose()?,
     };
 
     let response = <StreamQueryHandler<R, S, InMemoryFrameStore> as QueryHandlerGat<
@@ -291,7 +398,31 @@ where
         other => other,
     };
 
-    let batch = BatchFrameStream::new(stream::iter(response.frames), format, 1);
+    let encoding = ContentEncoding::from_accept_encoding(&headers);
+    let mut batch = BatchFrameStream::new(stream::iter(response.frames), format, 1)
+        .with_content_encoding(encoding);
+    if let Some(signal) = state.drain_signal.clone() {
+        batch = batch.with_drain_signal(signal, stream_id, params.since_sequence);
+    }
+    let command_handler = state.command_handler.clone();
+    batch = batch.with_disconnect_hook(move || {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let command = CancelStreamCommand {
+            session_id: session_id.into(),
+            stream_id: stream_id.into(),
+        };
+        runtime.spawn(async move {
+            if let Err(e) = <SessionCommandHandler<R, P> as CommandHandlerGat<
+                CancelStreamCommand,
+            >>::handle(&*command_handler, command)
+            .await
+            {
+                tracing::debug!(%stream_id, error = %e, "stream not cancelled after client disconnect");
+            }
+        });
+    });
     let content_type = batch.content_type();
     let mut http_response = match format {
         StreamFormat::Json => {
@@ -304,6 +435,7 @@ where
         PjsError::HttpError("failed to build streaming response".into())
     })?;
 
+    set_content_encoding(&mut http_response, encoding);
     http_response
         .headers_mut()
         .insert("X-Total-Count", axum::http::HeaderValue::from(total_count));
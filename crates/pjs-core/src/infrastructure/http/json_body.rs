@@ -0,0 +1,523 @@
+//! JSON request bodies parsed straight into [`JsonData`].
+//!
+//! [`JsonBody`] replaces axum's `Json` extractor for bodies that end up as
+//! stream documents. The body is collected into a buffer from the global
+//! [`BufferPool`](crate::parser::BufferPool) and parsed by
+//! [`ZeroCopyParser::parse_json_data`], which builds the `JsonData` tree in
+//! one pass and enforces [`JsonLimits`] while it reads. An oversized or
+//! too-deep document is rejected before it is built, not after.
+//!
+//! A body sent with `Content-Encoding` is decoded first, through a
+//! [`SecureDecompressionContext`] whose output cap is the same
+//! `max_input_size`, so a compressed body cannot expand past what an
+//! uncompressed one may be.
+//!
+//! Decoding and parsing a large document is CPU-bound, so bodies above
+//! [`BLOCKING_PARSE_THRESHOLD`] and every encoded body (which may expand to
+//! `max_input_size`) are handled on Tokio's blocking pool instead of the
+//! async worker that received them.
+
+use axum::{
+    RequestExt,
+    extract::{FromRef, FromRequest, Request},
+    http::header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE},
+};
+use futures::StreamExt;
+use http_body_util::LengthLimitError;
+use std::collections::HashMap;
+
+use crate::{
+    Error,
+    compression::secure::{ByteCodec, SecureDecompressionContext},
+    config::{SecurityConfig, security::JsonLimits},
+    domain::{DomainError, value_objects::JsonData},
+    infrastructure::http::{
+        axum_adapter::{PjsError, StartStreamRequest, UpdateStreamDataRequest},
+        streaming::ContentEncoding,
+    },
+    parser::{PooledBuffer, ZeroCopyParser, global_buffer_pool},
+    security::{CompressionBombConfig, CompressionBombDetector},
+};
+
+/// Largest unencoded body [`JsonBody`] parses inline on the async worker;
+/// larger ones are decoded and parsed in `spawn_blocking`.
+pub const BLOCKING_PARSE_THRESHOLD: usize = 64 * 1024;
+
+/// Extractor for a JSON request body, parsed into [`JsonData`] and then
+/// converted to `T` with `TryFrom<JsonData>` (request types such as
+/// [`StartStreamRequest`] implement it).
+///
+/// The limits come from the router state through `JsonLimits: FromRef<S>`;
+/// [`PjsAppState`](super::PjsAppState) provides them (see
+/// [`PjsAppState::with_json_limits`](super::PjsAppState::with_json_limits)).
+/// The request must declare an `application/json` (or `+json`) content type.
+/// It may be sent with `Content-Encoding` `gzip`, `br` or `zstd` (as this
+/// build supports them), or a chain of them; both the encoded and the
+/// decoded body are held to `max_input_size`.
+///
+/// Rejections use the API's JSON error envelope:
+/// - [`PjsError::UnsupportedMediaType`] (`415`) for a non-JSON content type
+///   or an unsupported content coding,
+/// - [`PjsError::PayloadTooLarge`] (`413`) when the body exceeds
+///   `max_input_size` or the router's `DefaultBodyLimit` — before or after
+///   decoding — or a value in it exceeds another limit,
+/// - [`PjsError::InvalidBody`] (`400`) for a body that does not decode,
+///   malformed JSON, or a document `T` cannot be built from.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use pjson_rs::infrastructure::http::JsonBody;
+/// use pjson_rs::domain::value_objects::JsonData;
+///
+/// async fn ingest(JsonBody(document): JsonBody) -> String {
+///     format!("{} bytes in memory", document.memory_size())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonBody<T = JsonData>(pub T);
+
+impl<S, T> FromRequest<S> for JsonBody<T>
+where
+    S: Send + Sync,
+    JsonLimits: FromRef<S>,
+    T: TryFrom<JsonData>,
+    PjsError: From<T::Error>,
+{
+    type Rejection = PjsError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(&request) {
+            return Err(PjsError::UnsupportedMediaType);
+        }
+
+        let codecs = content_codings(&request)?;
+        let limits = JsonLimits::from_ref(state);
+        let body = read_body(request, limits.max_input_size).await?;
+        let length = body.buffer().map_or(0, |buffer| buffer.len());
+        let data = if codecs.is_empty() && length <= BLOCKING_PARSE_THRESHOLD {
+            parse_body(&body, &codecs, limits)?
+        } else {
+            tokio::task::spawn_blocking(move || parse_body(&body, &codecs, limits))
+                .await
+                .map_err(|e| PjsError::HttpError(format!("request body parse task: {e}")))??
+        };
+
+        Ok(JsonBody(T::try_from(data)?))
+    }
+}
+
+/// Decode `body` and parse it into [`JsonData`] under `limits`.
+fn parse_body(
+    body: &PooledBuffer,
+    codecs: &[ByteCodec],
+    limits: JsonLimits,
+) -> Result<JsonData, PjsError> {
+    let bytes = body.buffer().map(|buffer| buffer.as_slice()).unwrap_or(&[]);
+    let decoded = decode_body(bytes, codecs, limits.max_input_size)?;
+    let bytes = decoded.as_deref().unwrap_or(bytes);
+
+    let mut parser = ZeroCopyParser::with_security_config(SecurityConfig {
+        json: limits,
+        ..SecurityConfig::default()
+    });
+    parser.parse_json_data(bytes).map_err(|e| match e {
+        DomainError::SecurityViolation(message) => PjsError::PayloadTooLarge(message),
+        other => PjsError::InvalidBody(other.to_string()),
+    })
+}
+
+/// Whether the request declares a JSON media type, parameters ignored.
+fn has_json_content_type(request: &Request) -> bool {
+    let Some(content_type) = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let essence = content_type.split(';').next().unwrap_or("").trim();
+    essence.eq_ignore_ascii_case("application/json")
+        || essence
+            .rsplit_once('+')
+            .is_some_and(|(_, suffix)| suffix.eq_ignore_ascii_case("json"))
+}
+
+/// The request's `Content-Encoding` chain, in the order it was applied;
+/// `identity` entries are skipped.
+fn content_codings(request: &Request) -> Result<Vec<ByteCodec>, PjsError> {
+    let mut codecs = Vec::new();
+    for value in request.headers().get_all(CONTENT_ENCODING) {
+        let value = value.to_str().map_err(|_| PjsError::UnsupportedMediaType)?;
+        for token in value.split(',').filter(|token| !token.trim().is_empty()) {
+            let encoding =
+                ContentEncoding::from_token(token).ok_or(PjsError::UnsupportedMediaType)?;
+            if encoding != ContentEncoding::Identity {
+                codecs.push(
+                    encoding
+                        .byte_codec()
+                        .map_err(|_| PjsError::UnsupportedMediaType)?,
+                );
+            }
+        }
+    }
+    Ok(codecs)
+}
+
+/// Undo `codecs` on a body, capping the output at `max_size`; `None` when the
+/// body was not encoded.
+fn decode_body(
+    bytes: &[u8],
+    codecs: &[ByteCodec],
+    max_size: usize,
+) -> Result<Option<Vec<u8>>, PjsError> {
+    if codecs.is_empty() {
+        return Ok(None);
+    }
+    let detector = CompressionBombDetector::new(CompressionBombConfig {
+        max_compressed_size: max_size,
+        max_decompressed_size: max_size,
+        ..CompressionBombConfig::default()
+    });
+    SecureDecompressionContext::new(detector, 1)
+        .decompress_layers(bytes, codecs)
+        .map(Some)
+        .map_err(|e| match e {
+            Error::SecurityError(message) => PjsError::PayloadTooLarge(message),
+            other => PjsError::InvalidBody(other.to_string()),
+        })
+}
+
+/// Collect the request body into a pooled buffer, failing as soon as it
+/// grows past `max_size` or the router's `DefaultBodyLimit`.
+async fn read_body(request: Request, max_size: usize) -> Result<PooledBuffer, PjsError> {
+    let too_large = || PjsError::PayloadTooLarge(format!("body exceeds {max_size} bytes"));
+
+    let declared_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if declared_length.is_some_and(|length| length > max_size) {
+        return Err(too_large());
+    }
+
+    let mut body = global_buffer_pool()
+        .acquire_with_capacity(declared_length.unwrap_or(0))
+        .map_err(|e| {
+            tracing::warn!("No request body buffer available: {}", e);
+            PjsError::HttpError("request body buffer unavailable".to_string())
+        })?;
+
+    let mut chunks = request.into_limited_body().into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|e| {
+            if e.into_inner().downcast::<LengthLimitError>().is_ok() {
+                PjsError::PayloadTooLarge("body exceeds the router's body limit".to_string())
+            } else {
+                PjsError::InvalidBody("failed to read request body".to_string())
+            }
+        })?;
+        let buffer = body
+            .buffer_mut()
+            .expect("a pooled buffer holds its buffer until dropped");
+        if buffer.len() + chunk.len() > max_size {
+            return Err(too_large());
+        }
+        buffer.extend_from_slice(&chunk).map_err(|_| too_large())?;
+    }
+
+    Ok(body)
+}
+
+/// Split a request document into its members.
+fn request_object(data: JsonData) -> Result<HashMap<String, JsonData>, PjsError> {
+    match data {
+        JsonData::Object(object) => Ok(object),
+        _ => Err(PjsError::InvalidBody(
+            "request body must be a JSON object".to_string(),
+        )),
+    }
+}
+
+/// Take the required `data` member; an explicit `null` is left to the
+/// command's validation.
+fn document_field(object: &mut HashMap<String, JsonData>) -> Result<JsonData, PjsError> {
+    object
+        .remove("data")
+        .ok_or_else(|| PjsError::InvalidBody("missing field `data`".to_string()))
+}
+
+/// Take an optional non-negative integer member; `null` counts as absent.
+fn integer_field<N: TryFrom<i64>>(
+    object: &mut HashMap<String, JsonData>,
+    field: &str,
+) -> Result<Option<N>, PjsError> {
+    match object.remove(field) {
+        None | Some(JsonData::Null) => Ok(None),
+        Some(JsonData::Integer(integer)) => N::try_from(integer)
+            .map(Some)
+            .map_err(|_| PjsError::InvalidBody(format!("`{field}` is out of range: {integer}"))),
+        Some(_) => Err(PjsError::InvalidBody(format!(
+            "`{field}` must be an integer"
+        ))),
+    }
+}
+
+impl TryFrom<JsonData> for StartStreamRequest {
+    type Error = PjsError;
+
+    fn try_from(data: JsonData) -> Result<Self, Self::Error> {
+        let mut object = request_object(data)?;
+        Ok(Self {
+            data: document_field(&mut object)?,
+            priority_threshold: integer_field(&mut object, "priority_threshold")?,
+            max_frames: integer_field(&mut object, "max_frames")?,
+        })
+    }
+}
+
+impl TryFrom<JsonData> for UpdateStreamDataRequest {
+    type Error = PjsError;
+
+    fn try_from(data: JsonData) -> Result<Self, Self::Error> {
+        let mut object = request_object(data)?;
+        Ok(Self {
+            data: document_field(&mut object)?,
+            max_frames: integer_field(&mut object, "max_frames")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        body::Body,
+        extract::DefaultBodyLimit,
+        http::{Request as HttpRequest, StatusCode},
+        response::IntoResponse,
+        routing::post,
+    };
+    use tower::ServiceExt;
+
+    async fn start(JsonBody(request): JsonBody<StartStreamRequest>) -> String {
+        format!(
+            "{:?} {:?} {:?}",
+            request.data.get("id"),
+            request.priority_threshold,
+            request.max_frames
+        )
+    }
+
+    fn app(limits: JsonLimits) -> Router {
+        Router::new()
+            .route("/start", post(start))
+            .route(
+                "/document",
+                post(|JsonBody(data): JsonBody| async move { data.is_object().to_string() }),
+            )
+            .with_state(limits)
+    }
+
+    async fn send(app: Router, uri: &str, content_type: &str, body: &str) -> (StatusCode, String) {
+        let request = HttpRequest::post(uri)
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body.to_owned()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap().into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_request_fields_are_read_from_the_parsed_document() {
+        let (status, body) = send(
+            app(JsonLimits::default()),
+            "/start",
+            "application/json; charset=utf-8",
+            r#"{"data": {"id": 1}, "priority_threshold": 50, "max_frames": null, "extra": []}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "Some(Integer(1)) Some(50) None");
+
+        let (status, body) = send(
+            app(JsonLimits::default()),
+            "/document",
+            "application/merge-patch+json",
+            "{}",
+        )
+        .await;
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "true"));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_bodies_are_rejected() {
+        for (content_type, body, expected) in [
+            (
+                "text/plain",
+                r#"{"data": 1}"#,
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ),
+            ("application/json", r#"{"data": "#, StatusCode::BAD_REQUEST),
+            (
+                "application/json",
+                r#"{"max_frames": 1}"#,
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                "application/json",
+                r#"[{"data": 1}]"#,
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                "application/json",
+                r#"{"data": 1, "priority_threshold": 256}"#,
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                "application/json",
+                r#"{"data": 1, "max_frames": "many"}"#,
+                StatusCode::BAD_REQUEST,
+            ),
+        ] {
+            let (status, response) =
+                send(app(JsonLimits::default()), "/start", content_type, body).await;
+            assert_eq!(status, expected, "{body} answered {response}");
+            assert!(response.starts_with(r#"{"error":"#), "{response}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_limits_are_enforced_while_reading() {
+        let limits = JsonLimits {
+            max_input_size: 64,
+            max_depth: 3,
+            ..JsonLimits::default()
+        };
+        for body in [
+            format!(r#"{{"data": "{}"}}"#, "x".repeat(64)),
+            r#"{"data": {"a": {"b": {"c": 1}}}}"#.to_owned(),
+        ] {
+            let (status, _) = send(app(limits.clone()), "/start", "application/json", &body).await;
+            assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE, "{body}");
+        }
+
+        let (status, _) = send(
+            app(limits).layer(DefaultBodyLimit::max(8)),
+            "/start",
+            "application/json",
+            r#"{"data": 1}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_large_bodies_are_parsed_off_the_runtime() {
+        let items = "1, ".repeat(BLOCKING_PARSE_THRESHOLD / 3);
+        let body = format!(r#"{{"data": {{"id": 7, "items": [{items}1]}}}}"#);
+        assert!(body.len() > BLOCKING_PARSE_THRESHOLD);
+
+        let (status, response) = send(
+            app(JsonLimits::default()),
+            "/start",
+            "application/json",
+            &body,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{response}");
+        assert_eq!(response, "Some(Integer(7)) None None");
+
+        let truncated = &body[..body.len() - 1];
+        let (status, _) = send(
+            app(JsonLimits::default()),
+            "/start",
+            "application/json",
+            truncated,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let limits = JsonLimits {
+            max_array_length: 16,
+            ..JsonLimits::default()
+        };
+        let (status, _) = send(app(limits), "/start", "application/json", &body).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    async fn send_encoded(limits: JsonLimits, encoding: &str, body: Vec<u8>) -> StatusCode {
+        let request = HttpRequest::post("/start")
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_ENCODING, encoding)
+            .body(Body::from(body))
+            .unwrap();
+        app(limits).oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_content_coding_is_rejected() {
+        let status = send_encoded(
+            JsonLimits::default(),
+            "compress",
+            br#"{"data": 1}"#.to_vec(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let status = send_encoded(
+            JsonLimits::default(),
+            "identity",
+            br#"{"data": 1}"#.to_vec(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_encoded_bodies_are_decoded_under_the_input_limit() {
+        use crate::compression::secure::SecureCompressor;
+
+        let encode = |codec: ByteCodec, body: &[u8]| {
+            SecureCompressor::with_default_security(codec)
+                .compress(body)
+                .unwrap()
+                .data
+        };
+
+        let body = br#"{"data": {"id": 1}, "max_frames": 2}"#;
+        let gzip = encode(ByteCodec::Gzip, body);
+        assert_eq!(
+            send_encoded(JsonLimits::default(), "gzip", gzip.clone()).await,
+            StatusCode::OK
+        );
+        let chained = encode(ByteCodec::Brotli, &gzip);
+        assert_eq!(
+            send_encoded(JsonLimits::default(), "gzip, br", chained).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send_encoded(JsonLimits::default(), "br", gzip).await,
+            StatusCode::BAD_REQUEST
+        );
+
+        let limits = JsonLimits {
+            max_input_size: 4096,
+            ..JsonLimits::default()
+        };
+        let expanding = format!(r#"{{"data": "{}"}}"#, "x".repeat(64 * 1024));
+        let bomb = encode(ByteCodec::Gzip, expanding.as_bytes());
+        assert!(bomb.len() < limits.max_input_size);
+        assert_eq!(
+            send_encoded(limits, "gzip", bomb).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+}
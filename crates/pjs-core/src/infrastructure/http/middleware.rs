@@ -15,6 +15,10 @@ use std::{
 };
 use tower::{Layer, Service};
 
+use crate::security::rate_limit_store::{
+    API_KEY_HEADER, RateLimitDecision, RateLimitPolicy, RateLimitStore, RateLimitStoreError,
+};
+
 /// Middleware for performance monitoring and optimization
 #[derive(Clone)]
 pub struct PjsMiddleware {
@@ -237,10 +241,22 @@ impl RateLimitConfig {
 /// Adds X-RateLimit-* headers per RFC 6585
 #[derive(Clone)]
 pub struct RateLimitMiddleware {
-    limiter: std::sync::Arc<crate::security::rate_limit::WebSocketRateLimiter>,
+    backend: RateLimitBackend,
     trusted_proxies: Option<TrustedProxyConfig>,
 }
 
+/// Where [`RateLimitMiddleware`] keeps request budgets.
+#[derive(Clone)]
+enum RateLimitBackend {
+    /// Process-local exact sliding log.
+    Local(std::sync::Arc<crate::security::rate_limit::WebSocketRateLimiter>),
+    /// A [`RateLimitStore`] resolved through the latest [`RateLimitPolicy`].
+    Store(
+        std::sync::Arc<dyn RateLimitStore>,
+        tokio::sync::watch::Receiver<std::sync::Arc<RateLimitPolicy>>,
+    ),
+}
+
 impl RateLimitMiddleware {
     /// Build a fresh middleware with its own internal `WebSocketRateLimiter`.
     ///
@@ -262,7 +278,7 @@ impl RateLimitMiddleware {
         limiter.spawn_cleanup_task(crate::security::rate_limit::DEFAULT_CLEANUP_INTERVAL);
 
         Self {
-            limiter,
+            backend: RateLimitBackend::Local(limiter),
             trusted_proxies,
         }
     }
@@ -279,7 +295,37 @@ impl RateLimitMiddleware {
         limiter.spawn_cleanup_task(crate::security::rate_limit::DEFAULT_CLEANUP_INTERVAL);
 
         Self {
-            limiter,
+            backend: RateLimitBackend::Local(limiter),
+            trusted_proxies: None,
+        }
+    }
+
+    /// Charge requests against `store` as resolved by `policy` instead of a
+    /// process-local limiter.
+    ///
+    /// With a [`SharedRateLimitStore`](crate::security::rate_limit_store::SharedRateLimitStore)
+    /// every replica behind a load balancer draws from the same budget.
+    /// Requests carrying one of the policy's registered API keys in
+    /// `X-PJS-API-Key` are charged to that key rather than to their address
+    /// (see [`RateLimitPolicy`] for why unregistered keys are ignored).
+    ///
+    /// If the store fails, the request is rejected with `503` unless
+    /// [`RateLimitPolicy::with_fail_open`] is set.
+    pub fn from_store(store: std::sync::Arc<dyn RateLimitStore>, policy: RateLimitPolicy) -> Self {
+        let (_, policy) = tokio::sync::watch::channel(std::sync::Arc::new(policy));
+        Self::from_reloadable_store(store, policy)
+    }
+
+    /// Like [`Self::from_store`], but resolve every request with the policy
+    /// most recently sent on `policy`, so budgets can change without
+    /// rebuilding the router. Once the sender is dropped the last policy
+    /// stays in force.
+    pub fn from_reloadable_store(
+        store: std::sync::Arc<dyn RateLimitStore>,
+        policy: tokio::sync::watch::Receiver<std::sync::Arc<RateLimitPolicy>>,
+    ) -> Self {
+        Self {
+            backend: RateLimitBackend::Store(store, policy),
             trusted_proxies: None,
         }
     }
@@ -297,7 +343,7 @@ impl<S> Layer<S> for RateLimitMiddleware {
     fn layer(&self, inner: S) -> Self::Service {
         RateLimitService {
             inner,
-            limiter: self.limiter.clone(),
+            backend: self.backend.clone(),
             trusted_proxies: self.trusted_proxies.clone(),
         }
     }
@@ -307,7 +353,7 @@ impl<S> Layer<S> for RateLimitMiddleware {
 #[derive(Clone)]
 pub struct RateLimitService<S> {
     inner: S,
-    limiter: std::sync::Arc<crate::security::rate_limit::WebSocketRateLimiter>,
+    backend: RateLimitBackend,
     trusted_proxies: Option<TrustedProxyConfig>,
 }
 
@@ -325,13 +371,66 @@ where
     }
 
     fn call(&mut self, request: Request) -> Self::Future {
-        let limiter = self.limiter.clone();
+        let backend = self.backend.clone();
         let trusted_proxies = self.trusted_proxies.clone();
         let mut inner = self.inner.clone();
 
         Box::pin(async move {
             let client_ip = extract_client_ip(&request, trusted_proxies.as_ref());
 
+            let limiter = match backend {
+                RateLimitBackend::Local(limiter) => limiter,
+                RateLimitBackend::Store(store, policy) => {
+                    let policy = policy.borrow().clone();
+                    let api_key = request
+                        .headers()
+                        .get(API_KEY_HEADER)
+                        .and_then(|v| v.to_str().ok());
+                    let (key, algorithm) = policy.resolve(client_ip, api_key);
+
+                    return match store.check(&key, algorithm).await {
+                        Ok(decision) if decision.allowed => {
+                            let mut response = inner.call(request).await?;
+                            add_decision_headers(&mut response, &decision);
+                            Ok(response)
+                        }
+                        Ok(decision) => {
+                            let retry_after = duration_secs_ceil(decision.retry_after).max(1);
+                            let mut response = rate_limit_rejection(
+                                StatusCode::TOO_MANY_REQUESTS,
+                                retry_after,
+                                "Too Many Requests",
+                                &format!(
+                                    "Rate limit exceeded: {} requests for {key}",
+                                    decision.limit
+                                ),
+                            );
+                            add_decision_headers(&mut response, &decision);
+                            Ok(response)
+                        }
+                        Err(err) if policy.fail_open() => {
+                            tracing::warn!("rate limit store failed, admitting request: {err}");
+                            inner.call(request).await
+                        }
+                        Err(err) => {
+                            tracing::warn!("rate limit store failed, rejecting request: {err}");
+                            let retry_after = match err {
+                                RateLimitStoreError::CapacityExceeded { .. } => {
+                                    crate::security::rate_limit::DEFAULT_CLEANUP_INTERVAL.as_secs()
+                                }
+                                _ => 1,
+                            };
+                            Ok(rate_limit_rejection(
+                                StatusCode::SERVICE_UNAVAILABLE,
+                                retry_after,
+                                "Service Unavailable",
+                                &err.to_string(),
+                            ))
+                        }
+                    };
+                }
+            };
+
             // Check rate limit
             match limiter.check_request(client_ip) {
                 Ok(()) => {
@@ -348,19 +447,8 @@ where
                     let (status, retry_after, error_label) =
                         rate_limit_error_response_parts(&err, &limiter, client_ip);
 
-                    let error_body = serde_json::json!({
-                        "error": error_label,
-                        "message": err.to_string(),
-                        "retry_after": retry_after
-                    })
-                    .to_string();
-
-                    let mut response = Response::builder()
-                        .status(status)
-                        .header(header::CONTENT_TYPE, "application/json")
-                        .header("Retry-After", retry_after.to_string())
-                        .body(error_body.into())
-                        .unwrap_or_else(|_| Response::new(error_label.into()));
+                    let mut response =
+                        rate_limit_rejection(status, retry_after, error_label, &err.to_string());
 
                     // A `CapacityExceeded` rejection has no per-client bucket
                     // to describe — the IP was never admitted into the
@@ -379,6 +467,29 @@ where
     }
 }
 
+/// JSON `429`/`503` body shared by every rate-limit rejection, with a
+/// matching `Retry-After` header.
+fn rate_limit_rejection(
+    status: StatusCode,
+    retry_after: u64,
+    error_label: &'static str,
+    message: &str,
+) -> Response {
+    let error_body = serde_json::json!({
+        "error": error_label,
+        "message": message,
+        "retry_after": retry_after
+    })
+    .to_string();
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("Retry-After", retry_after.to_string())
+        .body(error_body.into())
+        .unwrap_or_else(|_| Response::new(error_label.into()))
+}
+
 /// Map a [`crate::security::rate_limit::RateLimitError`] to the `(status,
 /// retry_after_secs, error_label)` triple used to build the 429/503 response.
 ///
@@ -572,6 +683,27 @@ fn add_rate_limit_headers(
     }
 }
 
+/// Add X-RateLimit-* headers describing a [`RateLimitStore`] decision.
+fn add_decision_headers(response: &mut Response, decision: &RateLimitDecision) {
+    use std::time::SystemTime;
+
+    let headers = response.headers_mut();
+    headers.insert("X-RateLimit-Limit", HeaderValue::from(decision.limit));
+    headers.insert(
+        "X-RateLimit-Remaining",
+        HeaderValue::from(decision.remaining),
+    );
+
+    let reset_after_secs = duration_secs_ceil(decision.reset_after);
+    if let Some(reset_time) = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs().saturating_add(reset_after_secs))
+    {
+        headers.insert("X-RateLimit-Reset", HeaderValue::from(reset_time));
+    }
+}
+
 /// Connection upgrade middleware for WebSocket support
 pub async fn websocket_upgrade_middleware(
     headers: HeaderMap,
@@ -903,7 +1035,10 @@ mod tests {
         let middleware = RateLimitMiddleware::new(RateLimitConfig::default());
 
         assert!(
-            middleware.limiter.is_cleanup_task_spawned(),
+            matches!(
+                &middleware.backend,
+                RateLimitBackend::Local(limiter) if limiter.is_cleanup_task_spawned()
+            ),
             "RateLimitMiddleware::new must wire up periodic cleanup"
         );
     }
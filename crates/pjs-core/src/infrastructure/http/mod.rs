@@ -5,13 +5,20 @@ pub mod auth;
 pub mod axum_adapter;
 pub mod axum_extension;
 pub mod handlers;
+#[cfg(feature = "http-server")]
+pub mod json_body;
 #[cfg(feature = "metrics")]
 pub mod metrics;
 pub mod middleware;
+pub mod response_layer;
 #[cfg(feature = "http-server")]
 pub mod serve;
 pub mod streaming;
+#[cfg(feature = "http-tls")]
+pub mod tls;
 
+#[cfg(feature = "http-auth-jwt")]
+pub use axum_adapter::create_pjs_router_with_jwt_auth;
 pub use axum_adapter::{
     CreateSessionRequest, CreateSessionResponse, HttpServerConfig, PjsAppState, PjsError,
     StartStreamRequest, StreamParams, create_pjs_router, create_pjs_router_with_config,
@@ -20,10 +27,15 @@ pub use axum_adapter::{
 #[cfg(feature = "http-server")]
 pub use axum_adapter::{create_pjs_router_with_auth, create_pjs_router_with_rate_limit_and_auth};
 pub use axum_extension::{HttpExtensionConfig, PjsExtension};
+#[cfg(feature = "http-server")]
+pub use json_body::JsonBody;
 pub use middleware::{RateLimitConfig, RateLimitMiddleware, TrustedProxyConfig};
+pub use response_layer::{PJS_STREAM_HEADER, PjsResponseLayer};
+#[cfg(feature = "http-tls")]
+pub use serve::serve_tls_with_shutdown;
 #[cfg(feature = "http-server")]
-pub use serve::{ConnectionLimits, serve_with_limits};
+pub use serve::{ConnectionLimits, serve_with_limits, serve_with_shutdown};
 pub use streaming::{
-    BatchFrameStream, StreamFormat, StreamTransportError, create_streaming_response,
-    create_streaming_response_with_content_type,
+    BatchFrameStream, ContentEncoding, StreamFormat, StreamTransportError,
+    create_streaming_response, create_streaming_response_with_content_type, set_content_encoding,
 };
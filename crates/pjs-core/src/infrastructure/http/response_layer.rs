@@ -0,0 +1,481 @@
+//! Transparent PJS streaming for existing JSON handlers
+//!
+//! [`PjsResponseLayer`] wraps any axum route (or a whole router) returning
+//! `Json<T>`. Clients that negotiate a PJS format receive the same document
+//! as a prioritized frame stream; every other client receives the handler's
+//! response untouched.
+
+use axum::{
+    BoxError,
+    body::{Body, HttpBody as _},
+    extract::Request,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::Response,
+};
+use bytes::Bytes;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+
+use super::streaming::{
+    ContentEncoding, StreamFormat, batch_compressor, compress_batch, set_content_encoding,
+};
+use crate::{
+    domain::{
+        services::PjsPriority,
+        value_objects::{JsonPathQuery, Priority},
+    },
+    stream::{PriorityStreamer, priority::StreamerConfig},
+};
+
+/// Header a client sets to ask for a PJS stream without touching `Accept`.
+/// The stream is sent as newline-delimited JSON.
+pub const PJS_STREAM_HEADER: &str = "x-pjs-stream";
+
+/// Default cap on the size of a response body the layer will convert.
+const DEFAULT_MAX_BODY_SIZE: usize = 10 * 1024 * 1024; // 10MB
+
+/// Wire format a converted response is sent in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PjsFormat {
+    ServerSentEvents,
+    NdJson,
+}
+
+impl PjsFormat {
+    /// Format the client asked for, or `None` when it did not negotiate one.
+    ///
+    /// `Accept` is negotiated with [`StreamFormat::from_accept_header`], so a
+    /// client preferring `application/json` keeps getting plain JSON even if
+    /// it also lists `text/event-stream`. [`PJS_STREAM_HEADER`] selects
+    /// NDJSON unless `Accept` already picked SSE.
+    fn negotiate(headers: &HeaderMap) -> Option<Self> {
+        match StreamFormat::from_accept_header(headers) {
+            StreamFormat::ServerSentEvents => Some(Self::ServerSentEvents),
+            StreamFormat::NdJson => Some(Self::NdJson),
+            _ if headers.contains_key(PJS_STREAM_HEADER) => Some(Self::NdJson),
+            _ => None,
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::ServerSentEvents => "text/event-stream",
+            Self::NdJson => "application/x-ndjson",
+        }
+    }
+
+    fn encode(self, frame: &str) -> Vec<u8> {
+        match self {
+            Self::ServerSentEvents => format!("data: {frame}\n\n").into_bytes(),
+            Self::NdJson => format!("{frame}\n").into_bytes(),
+        }
+    }
+}
+
+/// Tower layer re-emitting JSON responses as prioritized PJS frame streams
+///
+/// When a request negotiates a PJS format — `Accept: text/event-stream`,
+/// `Accept: application/x-ndjson` or the [`PJS_STREAM_HEADER`] header — and
+/// the wrapped service answers `2xx` with an `application/json` body, the
+/// body is analyzed by a [`PriorityStreamer`] and sent as one frame per SSE
+/// event or NDJSON line: skeleton first, then patches from the highest
+/// priority down, then the completion frame carrying the document digest.
+/// Status and headers other than the body's own are kept. The stream is
+/// content-encoded as negotiated by `Accept-Encoding` (see
+/// [`ContentEncoding::from_accept_encoding`]), one flushed chunk per frame.
+///
+/// Everything else passes through unchanged: requests that did not
+/// negotiate PJS, error and non-JSON responses, bodies that are not valid
+/// JSON, and bodies without an exact size or larger than
+/// [`Self::with_max_body_size`] (the whole body must be buffered to analyze
+/// it). Every JSON response gets `Vary: accept, x-pjs-stream` so caches
+/// keep the two representations apart.
+///
+/// Apply one layer per route to give routes their own priorities:
+///
+/// ```
+/// use axum::{Json, Router, routing::get};
+/// use pjson_rs::domain::value_objects::{JsonPathQuery, Priority};
+/// use pjson_rs::infrastructure::http::PjsResponseLayer;
+///
+/// async fn feed() -> Json<serde_json::Value> {
+///     Json(serde_json::json!({ "headline": "…", "comments": [] }))
+/// }
+///
+/// let app: Router = Router::new().route(
+///     "/feed",
+///     get(feed).layer(
+///         PjsResponseLayer::new()
+///             .with_priority_rule(JsonPathQuery::new("$.headline").unwrap(), Priority::CRITICAL),
+///     ),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct PjsResponseLayer {
+    config: StreamerConfig,
+    max_body_size: usize,
+}
+
+impl PjsResponseLayer {
+    /// Layer using the default [`StreamerConfig`] heuristics.
+    pub fn new() -> Self {
+        Self::with_streamer_config(StreamerConfig::default())
+    }
+
+    /// Layer analyzing responses with `config`.
+    pub fn with_streamer_config(config: StreamerConfig) -> Self {
+        Self {
+            config,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Give every location `query` selects `priority`, overriding the name
+    /// heuristics (see [`StreamerConfig::priority_rules`]).
+    #[must_use]
+    pub fn with_priority_rule(mut self, query: JsonPathQuery, priority: Priority) -> Self {
+        self.config.priority_rules.push((query, priority));
+        self
+    }
+
+    /// Apply the priorities the handler's response type declares, see
+    /// [`PjsPriority`].
+    #[must_use]
+    pub fn with_priorities_of<T: PjsPriority + ?Sized>(mut self) -> Self {
+        self.config = self.config.with_priorities_of::<T>();
+        self
+    }
+
+    /// Largest response body, in bytes, the layer converts; larger ones are
+    /// sent as plain JSON.
+    #[must_use]
+    pub fn with_max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+}
+
+impl Default for PjsResponseLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for PjsResponseLayer {
+    type Service = PjsResponseService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PjsResponseService {
+            inner,
+            streamer: Arc::new(PriorityStreamer::with_config(self.config.clone())),
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+/// Tower service produced by [`PjsResponseLayer`].
+#[derive(Clone)]
+pub struct PjsResponseService<S> {
+    inner: S,
+    streamer: Arc<PriorityStreamer>,
+    max_body_size: usize,
+}
+
+impl<S> Service<Request> for PjsResponseService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let format = PjsFormat::negotiate(request.headers());
+        let encoding = ContentEncoding::from_accept_encoding(request.headers());
+        let streamer = self.streamer.clone();
+        let max_body_size = self.max_body_size;
+        let future = self.inner.call(request);
+
+        Box::pin(async move {
+            let mut response = future.await?;
+            if !is_json(response.headers()) {
+                return Ok(response);
+            }
+            response.headers_mut().append(
+                header::VARY,
+                HeaderValue::from_static("accept, x-pjs-stream"),
+            );
+
+            let Some(format) = format else {
+                return Ok(response);
+            };
+            let fits = response
+                .body()
+                .size_hint()
+                .exact()
+                .is_some_and(|size| size <= max_body_size as u64);
+            if !response.status().is_success() || !fits {
+                return Ok(response);
+            }
+
+            Ok(into_frame_stream(response, &streamer, format, encoding, max_body_size).await)
+        })
+    }
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+}
+
+/// Re-emit `response`'s JSON body as a frame stream in `format`, encoded
+/// with `encoding`, or hand the body back unchanged if it cannot be analyzed.
+async fn into_frame_stream(
+    response: Response,
+    streamer: &PriorityStreamer,
+    format: PjsFormat,
+    mut encoding: ContentEncoding,
+    max_body_size: usize,
+) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, max_body_size).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            // The body announced an exact size within the limit, so only a
+            // failing body stream ends up here; it is already consumed.
+            tracing::warn!(error = %e, "failed to buffer JSON response for PJS streaming");
+            parts.status = StatusCode::INTERNAL_SERVER_ERROR;
+            parts.headers.remove(header::CONTENT_LENGTH);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let plan = serde_json::from_slice(&bytes)
+        .ok()
+        .and_then(|document| streamer.analyze(&document).ok());
+    let Some(plan) = plan else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    let mut compressor = batch_compressor(encoding).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "sending PJS stream uncompressed");
+        encoding = ContentEncoding::Identity;
+        None
+    });
+
+    // The status line is already committed when the body is sent, so a
+    // frame that fails to serialize or compress ends the body with an
+    // error: the response is aborted instead of completing with frames
+    // missing.
+    let mut chunks: Vec<Result<Bytes, BoxError>> = Vec::new();
+    for frame in &plan.frames {
+        let chunk = serde_json::to_string(frame)
+            .map_err(BoxError::from)
+            .and_then(|frame| {
+                compress_batch(&mut compressor, format.encode(&frame)).map_err(BoxError::from)
+            });
+        match chunk {
+            Ok(chunk) => chunks.push(Ok(Bytes::from(chunk))),
+            Err(e) => {
+                tracing::error!(error = %e, "failed to encode PJS frame");
+                chunks.push(Err(e));
+                compressor = None;
+                break;
+            }
+        }
+    }
+    if let Some(compressor) = compressor {
+        chunks.push(compressor.finish().map(Bytes::from).map_err(BoxError::from));
+    }
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    parts
+        .headers
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    let mut response =
+        Response::from_parts(parts, Body::from_stream(futures::stream::iter(chunks)));
+    set_content_encoding(&mut response, encoding);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Json, Router, routing::get};
+    use serde_json::{Value as JsonValue, json};
+    use tower::ServiceExt;
+
+    async fn user() -> Json<JsonValue> {
+        Json(json!({ "id": 7, "name": "Ada", "bio": "…", "stats": { "posts": 3 } }))
+    }
+
+    fn app(layer: PjsResponseLayer) -> Router {
+        Router::new()
+            .route("/user", get(user))
+            .route(
+                "/missing",
+                get(|| async { (StatusCode::NOT_FOUND, Json(json!({ "error": "gone" }))) }),
+            )
+            .route("/text", get(|| async { "plain" }))
+            .layer(layer)
+    }
+
+    async fn get_with(app: Router, uri: &str, headers: &[(&str, &str)]) -> Response {
+        let mut request = Request::builder().uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sse_clients_get_a_prioritized_frame_stream() {
+        let response = get_with(
+            app(PjsResponseLayer::new()),
+            "/user",
+            &[("accept", "text/event-stream")],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+
+        let body = text(response).await;
+        let frames: Vec<JsonValue> = body
+            .split("\n\n")
+            .filter(|event| !event.is_empty())
+            .map(|event| serde_json::from_str(event.strip_prefix("data: ").unwrap()).unwrap())
+            .collect();
+        assert!(frames[0].get("Skeleton").is_some());
+        assert!(frames.last().unwrap().get("Complete").is_some());
+        // The critical `id` patch leads.
+        assert_eq!(frames[1]["Patch"]["patches"][0]["path"], "$.id");
+    }
+
+    #[tokio::test]
+    async fn test_pjs_header_selects_ndjson() {
+        let response = get_with(
+            app(PjsResponseLayer::new()),
+            "/user",
+            &[(PJS_STREAM_HEADER, "1")],
+        )
+        .await;
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/x-ndjson"
+        );
+        let body = text(response).await;
+        for line in body.lines() {
+            serde_json::from_str::<JsonValue>(line).expect("one frame per line");
+        }
+        assert!(body.lines().count() >= 3);
+    }
+
+    #[tokio::test]
+    async fn test_priority_rules_reorder_frames() {
+        let layer = PjsResponseLayer::new()
+            .with_priority_rule(JsonPathQuery::new("$.bio").unwrap(), Priority::CRITICAL)
+            .with_priority_rule(JsonPathQuery::new("$.id").unwrap(), Priority::BACKGROUND);
+        let body = text(get_with(app(layer), "/user", &[(PJS_STREAM_HEADER, "1")]).await).await;
+
+        let first_patch: JsonValue = serde_json::from_str(body.lines().nth(1).unwrap()).unwrap();
+        assert_eq!(first_patch["Patch"]["patches"][0]["path"], "$.bio");
+    }
+
+    #[tokio::test]
+    async fn test_other_responses_pass_through() {
+        // No PJS negotiated.
+        let response = get_with(
+            app(PjsResponseLayer::new()),
+            "/user",
+            &[("accept", "application/json")],
+        )
+        .await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()[header::VARY], "accept, x-pjs-stream");
+        let body: JsonValue = serde_json::from_str(&text(response).await).unwrap();
+        assert_eq!(body["name"], "Ada");
+
+        // Errors, non-JSON bodies and oversized bodies are left alone.
+        let sse = [("accept", "text/event-stream")];
+        let response = get_with(app(PjsResponseLayer::new()), "/missing", &sse).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+
+        let response = get_with(app(PjsResponseLayer::new()), "/text", &sse).await;
+        assert_eq!(text(response).await, "plain");
+
+        let layer = PjsResponseLayer::new().with_max_body_size(8);
+        let response = get_with(app(layer), "/user", &sse).await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_frame_stream_honors_accept_encoding() {
+        use std::io::Read;
+
+        let ndjson = [(PJS_STREAM_HEADER, "1")];
+        let plain = text(get_with(app(PjsResponseLayer::new()), "/user", &ndjson).await).await;
+
+        let response = get_with(
+            app(PjsResponseLayer::new()),
+            "/user",
+            &[(PJS_STREAM_HEADER, "1"), ("accept-encoding", "gzip")],
+        )
+        .await;
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert!(
+            response
+                .headers()
+                .get_all(header::VARY)
+                .iter()
+                .any(|vary| vary == "accept-encoding")
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, plain);
+
+        // Plain JSON responses are left to the application's own encoding.
+        let response = get_with(
+            app(PjsResponseLayer::new()),
+            "/user",
+            &[("accept-encoding", "gzip")],
+        )
+        .await;
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    }
+}
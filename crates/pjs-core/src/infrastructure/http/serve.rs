@@ -11,6 +11,7 @@
 //! does in place of `axum::serve`.
 
 use std::{
+    future::Future,
     io,
     net::{IpAddr, Ipv6Addr, SocketAddr},
     sync::Arc,
@@ -23,9 +24,13 @@ use hyper_util::{
     server::conn::auto::Builder,
     service::TowerToHyperService,
 };
-use tokio::{net::TcpListener, sync::Semaphore};
+use tokio::{
+    net::TcpListener,
+    sync::{Semaphore, watch},
+    task::JoinSet,
+};
 use tower::Layer;
-use tracing::{debug, error, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::security::rate_limit::{
     RateLimitConfig, RateLimitError, RateLimitGuard, WebSocketRateLimiter,
@@ -161,6 +166,21 @@ pub struct ConnectionLimits {
     /// deployment behind such a proxy must set this to `None` and rely on
     /// the proxy's own per-client limiting instead.
     pub max_connections_per_ip: Option<usize>,
+
+    /// How long [`serve_with_shutdown`] waits for open connections to finish
+    /// after its shutdown signal fires before closing them forcibly.
+    ///
+    /// Defaults to 30s, which matches the default
+    /// `terminationGracePeriodSeconds` of a Kubernetes pod: long enough for
+    /// an in-flight streaming response to deliver its remaining frames, short
+    /// enough that the orchestrator does not `SIGKILL` the process first.
+    /// Ignored by [`serve_with_limits`], which never shuts down.
+    ///
+    /// Connections that were upgraded to WebSocket have already left hyper's
+    /// control by the time shutdown starts and are not covered by this
+    /// deadline; their owner (e.g. `AxumWebSocketTransport`) is responsible
+    /// for closing them.
+    pub drain_timeout: Duration,
 }
 
 impl Default for ConnectionLimits {
@@ -171,10 +191,21 @@ impl Default for ConnectionLimits {
             max_connections: 1024,
             max_concurrent_streams: Some(128),
             max_connections_per_ip: Some(64),
+            drain_timeout: Duration::from_secs(30),
         }
     }
 }
 
+/// How accepted connections are turned into an I/O stream for hyper.
+#[derive(Clone)]
+enum Transport {
+    /// Serve plain-text HTTP on the accepted TCP stream.
+    Plain,
+    /// Perform a TLS handshake first (see [`super::tls`]).
+    #[cfg(feature = "http-tls")]
+    Tls(tokio_rustls::TlsAcceptor),
+}
+
 /// Serve `router` on `listener`, enforcing `limits` at the connection level.
 ///
 /// A drop-in replacement for `axum::serve(listener, router).await?` that
@@ -199,10 +230,10 @@ impl Default for ConnectionLimits {
 /// the loop either — mirroring axum's own `Listener::accept` retry
 /// behavior, connection-class errors (`ConnectionRefused`,
 /// `ConnectionAborted`, `ConnectionReset`) are retried immediately and any
-/// other error is logged and retried after a 1s backoff. In practice this
-/// function only returns if a future revision adds an explicit exit path;
-/// today it runs until the process is torn down, the same as
-/// `axum::serve(listener, app).await` today.
+/// other error is logged and retried after a 1s backoff. This function runs
+/// until the process is torn down, the same as
+/// `axum::serve(listener, app).await`; use [`serve_with_shutdown`] to stop
+/// it and drain open connections on a signal.
 ///
 /// # Examples
 ///
@@ -223,6 +254,113 @@ pub async fn serve_with_limits(
     router: Router,
     limits: ConnectionLimits,
 ) -> std::io::Result<()> {
+    serve_with_shutdown(listener, router, limits, std::future::pending()).await
+}
+
+/// Like [`serve_with_limits`], but stops once `shutdown` completes and
+/// drains the connections that are still open.
+///
+/// When `shutdown` resolves the listener is closed immediately, so new
+/// connection attempts are refused while the drain is in progress. Every
+/// open connection is then asked to shut down gracefully: HTTP/1
+/// connections finish the request in flight (including the remainder of a
+/// streaming response body) and close instead of waiting for the next
+/// request, and HTTP/2 connections send `GOAWAY` and finish their open
+/// streams. Connections still open after
+/// [`ConnectionLimits::drain_timeout`] are closed forcibly. The function
+/// returns once every connection is gone.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use axum::Router;
+/// use pjson_rs::infrastructure::http::{ConnectionLimits, serve_with_shutdown};
+/// use tokio::{net::TcpListener, sync::oneshot};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").await?;
+/// let (stop_tx, stop_rx) = oneshot::channel::<()>();
+/// let shutdown = async {
+///     let _ = stop_rx.await;
+/// };
+/// # drop(stop_tx);
+/// serve_with_shutdown(listener, Router::new(), ConnectionLimits::default(), shutdown).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn serve_with_shutdown<F>(
+    listener: TcpListener,
+    router: Router,
+    limits: ConnectionLimits,
+    shutdown: F,
+) -> std::io::Result<()>
+where
+    F: Future<Output = ()> + Send,
+{
+    serve(listener, router, limits, Transport::Plain, shutdown).await
+}
+
+/// Like [`serve_with_shutdown`], but terminates TLS on every accepted
+/// connection using `tls_config` (typically built with
+/// [`load_server_config`](super::tls::load_server_config)).
+///
+/// The TLS handshake must complete within
+/// [`ConnectionLimits::header_read_timeout`] (when set), so a client that
+/// opens a connection and stalls mid-handshake is dropped on the same
+/// schedule as one that stalls before sending headers. Both `h2` and
+/// `http/1.1` are offered via ALPN unless `tls_config` already lists its own
+/// protocols.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// use axum::Router;
+/// use pjson_rs::infrastructure::http::{
+///     ConnectionLimits, serve_tls_with_shutdown, tls::load_server_config,
+/// };
+/// use tokio::net::TcpListener;
+///
+/// let tls = load_server_config("cert.pem".as_ref(), "key.pem".as_ref())?;
+/// let listener = TcpListener::bind("0.0.0.0:8443").await?;
+/// let shutdown = std::future::pending::<()>();
+/// serve_tls_with_shutdown(listener, Router::new(), ConnectionLimits::default(), tls, shutdown)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "http-tls")]
+pub async fn serve_tls_with_shutdown<F>(
+    listener: TcpListener,
+    router: Router,
+    limits: ConnectionLimits,
+    tls_config: Arc<tokio_rustls::rustls::ServerConfig>,
+    shutdown: F,
+) -> std::io::Result<()>
+where
+    F: Future<Output = ()> + Send,
+{
+    let tls_config = if tls_config.alpn_protocols.is_empty() {
+        let mut config = (*tls_config).clone();
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Arc::new(config)
+    } else {
+        tls_config
+    };
+    let acceptor = tokio_rustls::TlsAcceptor::from(tls_config);
+    serve(listener, router, limits, Transport::Tls(acceptor), shutdown).await
+}
+
+async fn serve<F>(
+    listener: TcpListener,
+    router: Router,
+    limits: ConnectionLimits,
+    transport: Transport,
+    shutdown: F,
+) -> std::io::Result<()>
+where
+    F: Future<Output = ()> + Send,
+{
     let mut builder = Builder::new(TokioExecutor::new());
     builder.http1().timer(TokioTimer::new());
     builder.http2().timer(TokioTimer::new());
@@ -248,6 +386,11 @@ pub async fn serve_with_limits(
     let max_connection_duration = limits.max_connection_duration;
     let header_read_timeout = limits.header_read_timeout;
 
+    // Flipped to `true` once `shutdown` fires; every connection task watches
+    // it to start hyper's graceful shutdown for its own connection.
+    let (draining_tx, draining_rx) = watch::channel(false);
+    let mut connections = JoinSet::new();
+
     // Separate from any `RateLimitMiddleware` the router itself may apply:
     // sharing one `Arc<WebSocketRateLimiter>` would let an accept-level IP
     // flood saturate the middleware's tracked-client map and start
@@ -266,16 +409,32 @@ pub async fn serve_with_limits(
         limiter
     });
 
+    tokio::pin!(shutdown);
     loop {
         // Acquired before accept() so a full connection pool applies
         // backpressure at the accept loop rather than inside hyper. The
         // semaphore is never closed, so `Err` here is unreachable in
         // practice; treat it as a benign "stop serving" signal rather than
         // panicking on an accept-loop hot path.
-        let Ok(permit) = Arc::clone(&semaphore).acquire_owned().await else {
-            return Ok(());
+        let permit = tokio::select! {
+            biased;
+            () = &mut shutdown => break,
+            permit = Arc::clone(&semaphore).acquire_owned() => match permit {
+                Ok(permit) => permit,
+                Err(_) => break,
+            },
+        };
+        let (stream, peer_addr) = tokio::select! {
+            biased;
+            () = &mut shutdown => break,
+            accepted = accept_with_retry(&listener) => accepted,
         };
-        let (stream, peer_addr) = accept_with_retry(&listener).await;
+
+        // Reap finished connection tasks so the set only ever holds live
+        // connections; a panic in one is logged rather than propagated.
+        while let Some(finished) = connections.try_join_next() {
+            log_connection_task_result(finished);
+        }
 
         // Rejects fast, before the stream is ever wrapped or a task
         // spawned — this mitigates (does not fully close: `permit` above is
@@ -314,11 +473,12 @@ pub async fn serve_with_limits(
             None => None,
         };
 
-        let peer_service = Extension(ConnectInfo(peer_addr)).layer(router.clone());
-        let service = TowerToHyperService::new(peer_service);
+        let router = router.clone();
         let builder = Arc::clone(&builder);
+        let transport = transport.clone();
+        let draining = draining_rx.clone();
 
-        tokio::spawn(async move {
+        connections.spawn(async move {
             let _permit = permit;
             let _guard = guard;
 
@@ -349,23 +509,120 @@ pub async fn serve_with_limits(
                 return;
             }
 
-            let io = TokioIo::new(stream);
-            let conn = builder.serve_connection_with_upgrades(io, service);
+            let connection = async {
+                match transport {
+                    Transport::Plain => {
+                        serve_connection(&builder, TokioIo::new(stream), router, peer_addr, draining)
+                            .await
+                    }
+                    #[cfg(feature = "http-tls")]
+                    Transport::Tls(acceptor) => {
+                        let handshake = acceptor.accept(stream);
+                        let stream = match header_read_timeout {
+                            Some(timeout) => match tokio::time::timeout(timeout, handshake).await {
+                                Ok(result) => result,
+                                Err(_elapsed) => {
+                                    debug!(%peer_addr, "TLS handshake exceeded header_read_timeout, dropping");
+                                    return Ok(());
+                                }
+                            },
+                            None => handshake.await,
+                        };
+                        match stream {
+                            Ok(stream) => {
+                                serve_connection(&builder, TokioIo::new(stream), router, peer_addr, draining)
+                                    .await
+                            }
+                            Err(error) => {
+                                debug!(%peer_addr, %error, "TLS handshake failed");
+                                Ok(())
+                            }
+                        }
+                    }
+                }
+            };
+
             let result = match max_connection_duration {
-                Some(deadline) => match tokio::time::timeout(deadline, conn).await {
+                Some(deadline) => match tokio::time::timeout(deadline, connection).await {
                     Ok(result) => result,
                     Err(_elapsed) => {
                         debug!("connection exceeded max_connection_duration, dropping");
                         return;
                     }
                 },
-                None => conn.await,
+                None => connection.await,
             };
             if let Err(error) = result {
                 debug!(%error, "connection closed with error");
             }
         });
     }
+
+    // Refuse new connections for the rest of the drain rather than letting
+    // them queue in the kernel backlog until the process exits.
+    drop(listener);
+    let _ = draining_tx.send(true);
+
+    if !connections.is_empty() {
+        info!(
+            connections = connections.len(),
+            drain_timeout = ?limits.drain_timeout,
+            "shutdown requested, draining open connections"
+        );
+    }
+    let drained = tokio::time::timeout(limits.drain_timeout, async {
+        while let Some(finished) = connections.join_next().await {
+            log_connection_task_result(finished);
+        }
+    })
+    .await;
+    if drained.is_err() {
+        warn!(
+            connections = connections.len(),
+            "drain_timeout elapsed, closing remaining connections"
+        );
+        connections.shutdown().await;
+    }
+    Ok(())
+}
+
+/// Serve a single connection on `io`, switching it to graceful shutdown
+/// once `draining` flips to `true`.
+async fn serve_connection<I>(
+    builder: &Builder<TokioExecutor>,
+    io: I,
+    router: Router,
+    peer_addr: SocketAddr,
+    mut draining: watch::Receiver<bool>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    I: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+{
+    let peer_service = Extension(ConnectInfo(peer_addr)).layer(router);
+    let service = TowerToHyperService::new(peer_service);
+    let conn = builder.serve_connection_with_upgrades(io, service);
+    tokio::pin!(conn);
+
+    tokio::select! {
+        result = conn.as_mut() => result,
+        // `Err` means the accept loop is gone without having signalled a
+        // drain, which only happens when its task is torn down; shutting
+        // down gracefully is the right response either way.
+        () = async {
+            let _ = draining.wait_for(|draining| *draining).await;
+        } => {
+            conn.as_mut().graceful_shutdown();
+            conn.await
+        }
+    }
+}
+
+fn log_connection_task_result(result: Result<(), tokio::task::JoinError>) {
+    if let Err(join_err) = result
+        && join_err.is_panic()
+    {
+        error!(%join_err, "connection task panicked");
+    }
 }
 
 /// Masks an IPv6 address down to its /64 network prefix for accept-level
@@ -1,9 +1,14 @@
 //! Advanced streaming implementations for different protocols
 
-use crate::domain::entities::Frame;
+use crate::compression::{secure::ByteCodec, shared_context::SharedContextCompressor};
+use crate::domain::{entities::Frame, value_objects::StreamId};
+use crate::infrastructure::shutdown::{
+    DrainSignal, ResumePoint, StreamGuard, shutdown_error_frame,
+};
+use crate::stream::pjs_json::{self, PjsJsonError};
 use async_stream::try_stream;
 use axum::{
-    http::{HeaderMap, StatusCode, header},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::Response,
 };
 use futures::{Stream, StreamExt};
@@ -22,6 +27,8 @@ pub enum StreamFormat {
     ServerSentEvents,
     /// Binary PJS protocol
     Binary,
+    /// Native `application/pjs+json` frames (spec §3), one per line
+    PjsJson,
 }
 
 /// Maximum number of comma-separated `Accept` entries considered during content
@@ -39,6 +46,10 @@ const MAX_ACCEPT_ENTRIES: usize = 16;
 /// prefixed extension types aren't registered), so it is declared here.
 const X_NDJSON: Name<'static> = Name::new_unchecked("x-ndjson");
 
+/// Subtype of `application/pjs+json`, whose `+json` suffix [`mediatype`]
+/// keeps separately.
+const PJS: Name<'static> = Name::new_unchecked("pjs");
+
 /// Server-supported media ranges for this route, paired with the
 /// [`StreamFormat`] each selects.
 ///
@@ -47,7 +58,7 @@ const X_NDJSON: Name<'static> = Name::new_unchecked("x-ndjson");
 /// and `q` (e.g. a bare `*/*` or `application/*`, which match every entry
 /// here equally) — [`StreamFormat::Json`] listed first preserves the
 /// permissive default fallback.
-static SUPPORTED_MEDIA_TYPES: [(MediaType<'static>, StreamFormat); 4] = [
+static SUPPORTED_MEDIA_TYPES: [(MediaType<'static>, StreamFormat); 5] = [
     (
         MediaType::new(names::APPLICATION, names::JSON),
         StreamFormat::Json,
@@ -64,6 +75,10 @@ static SUPPORTED_MEDIA_TYPES: [(MediaType<'static>, StreamFormat); 4] = [
         MediaType::new(names::APPLICATION, names::OCTET_STREAM),
         StreamFormat::Binary,
     ),
+    (
+        MediaType::from_parts(names::APPLICATION, PJS, Some(names::JSON), &[]),
+        StreamFormat::PjsJson,
+    ),
 ];
 
 /// Whether `media_range` is eligible for negotiation.
@@ -211,10 +226,161 @@ impl StreamFormat {
             Self::NdJson => "application/x-ndjson",
             Self::ServerSentEvents => "text/event-stream",
             Self::Binary => "application/octet-stream",
+            Self::PjsJson => pjs_json::CONTENT_TYPE,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Content encoding
+// ---------------------------------------------------------------------------
+
+/// HTTP content coding applied to a streamed body.
+///
+/// Unlike a generic compression layer, which buffers until its codec decides
+/// to emit, a [`BatchFrameStream`] compresses each batch through one
+/// [`SharedContextCompressor`] and flushes it, so every chunk still reaches
+/// the client as soon as its batch is formatted — and later batches compress
+/// against earlier ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContentEncoding {
+    /// No content coding.
+    #[default]
+    Identity,
+    /// `gzip` (RFC 1952).
+    Gzip,
+    /// `br` (RFC 7932).
+    Brotli,
+    /// `zstd` (RFC 8878).
+    Zstd,
+}
+
+/// Codings this build can produce, in tie-break preference order.
+const SUPPORTED_CONTENT_ENCODINGS: &[ContentEncoding] = &[
+    #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+    ContentEncoding::Zstd,
+    #[cfg(feature = "compression")]
+    ContentEncoding::Brotli,
+    #[cfg(feature = "compression")]
+    ContentEncoding::Gzip,
+];
+
+impl ContentEncoding {
+    /// Picks a content coding for the request's `Accept-Encoding` header.
+    ///
+    /// The coding with the highest `q` among those this build supports wins;
+    /// ties go to `zstd`, then `br`, then `gzip`. `*` covers every coding the
+    /// header does not name, and `q=0` rejects a coding. Entries are bounded
+    /// the same way as `Accept` (`MAX_ACCEPT_ENTRIES`), and an entry with an
+    /// unparsable or non-finite `q` is dropped. A missing header, or one no
+    /// supported coding survives, selects [`Self::Identity`] — the body is
+    /// then sent uncompressed rather than refused.
+    pub fn from_accept_encoding(headers: &HeaderMap) -> Self {
+        let Some(accept) = headers
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return Self::Identity;
+        };
+
+        let mut named: Vec<(&str, f32)> = Vec::new();
+        let mut wildcard: Option<f32> = None;
+        for entry in accept.split(',').take(MAX_ACCEPT_ENTRIES) {
+            let mut parts = entry.split(';');
+            let coding = parts.next().unwrap_or("").trim();
+            if coding.is_empty() {
+                continue;
+            }
+            let q = parts
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .map_or(Some(1.0), |(_, q)| q.trim().parse::<f32>().ok());
+            let Some(q) = q.filter(|q| q.is_finite()) else {
+                continue;
+            };
+            let q = q.clamp(0.0, 1.0);
+            if coding == "*" {
+                wildcard = Some(q);
+            } else {
+                named.push((coding, q));
+            }
+        }
+
+        let mut best = (Self::Identity, 0.0);
+        for &encoding in SUPPORTED_CONTENT_ENCODINGS {
+            let token = encoding.token().unwrap_or_default();
+            let q = named
+                .iter()
+                .find(|(coding, _)| coding.eq_ignore_ascii_case(token))
+                .map(|&(_, q)| q)
+                .or(wildcard)
+                .unwrap_or(0.0);
+            if q > best.1 {
+                best = (encoding, q);
+            }
+        }
+        best.0
+    }
+
+    /// Parses one `Content-Encoding` token; `None` if it names a coding this
+    /// build cannot decode.
+    pub fn from_token(token: &str) -> Option<Self> {
+        let token = token.trim();
+        if token.eq_ignore_ascii_case("identity") {
+            return Some(Self::Identity);
+        }
+        SUPPORTED_CONTENT_ENCODINGS
+            .iter()
+            .copied()
+            .find(|encoding| {
+                encoding
+                    .token()
+                    .is_some_and(|t| t.eq_ignore_ascii_case(token))
+            })
+    }
+
+    /// Value for the response's `Content-Encoding` header; `None` for
+    /// [`Self::Identity`], which is sent without one.
+    pub fn token(self) -> Option<&'static str> {
+        match self {
+            Self::Identity => None,
+            Self::Gzip => Some("gzip"),
+            Self::Brotli => Some("br"),
+            Self::Zstd => Some("zstd"),
+        }
+    }
+
+    /// The byte codec that produces this coding.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StreamTransportError::Compression`] if this build cannot
+    /// produce it.
+    pub fn byte_codec(self) -> Result<ByteCodec, StreamTransportError> {
+        match self {
+            Self::Identity => Ok(ByteCodec::None),
+            Self::Gzip => Ok(ByteCodec::Gzip),
+            Self::Brotli => Ok(ByteCodec::Brotli),
+            #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+            Self::Zstd => Ok(ByteCodec::Zstd),
+            #[cfg(not(all(feature = "compression", not(target_arch = "wasm32"))))]
+            Self::Zstd => Err(StreamTransportError::Compression(
+                "zstd is not available in this build".into(),
+            )),
         }
     }
 }
 
+/// Marks `response` as encoded with `encoding` and as varying by
+/// `Accept-Encoding`, so caches keep encoded and plain bodies apart.
+pub fn set_content_encoding(response: &mut Response, encoding: ContentEncoding) {
+    let headers = response.headers_mut();
+    headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    if let Some(token) = encoding.token() {
+        headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(token));
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Shared helpers
 // ---------------------------------------------------------------------------
@@ -224,6 +390,8 @@ impl StreamFormat {
 /// Each batch is serialized as newline-delimited JSON objects (one object per
 /// frame). `StreamFormat::Json` and `StreamFormat::NdJson` produce identical
 /// wire bytes; only `content_type()` differs.
+/// `StreamFormat::PjsJson` uses the same line layout (spec §6.1) for the
+/// spec frame objects of [`pjs_json::encode_frame`].
 fn format_batch_owned(
     frames: &[Frame],
     format: StreamFormat,
@@ -249,6 +417,14 @@ fn format_batch_owned(
             Ok(out)
         }
         StreamFormat::Binary => Ok(sonic_rs::to_vec(frames)?),
+        StreamFormat::PjsJson => {
+            let mut out = Vec::new();
+            for frame in frames {
+                out.extend_from_slice(&sonic_rs::to_vec(&pjs_json::encode_frame(frame)?)?);
+                out.push(b'\n');
+            }
+            Ok(out)
+        }
     }
 }
 
@@ -261,6 +437,35 @@ pub struct BatchFrameStream<S> {
     inner: S,
     format: StreamFormat,
     batch_size: usize,
+    encoding: ContentEncoding,
+    drain: Option<Drain>,
+    on_disconnect: DisconnectHook,
+}
+
+/// Callback run when a response body is dropped before it finished.
+struct DisconnectHook(Option<Box<dyn FnOnce() + Send>>);
+
+impl DisconnectHook {
+    /// The body ran to completion: the client did not hang up.
+    fn disarm(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for DisconnectHook {
+    fn drop(&mut self) {
+        if let Some(on_disconnect) = self.0.take() {
+            on_disconnect();
+        }
+    }
+}
+
+/// Shutdown participation for one [`BatchFrameStream`].
+struct Drain {
+    signal: DrainSignal,
+    stream_id: StreamId,
+    resume: ResumePoint,
+    _guard: StreamGuard,
 }
 
 impl<S> BatchFrameStream<S>
@@ -273,9 +478,67 @@ where
             inner: stream,
             format,
             batch_size,
+            encoding: ContentEncoding::Identity,
+            drain: None,
+            on_disconnect: DisconnectHook(None),
         }
     }
 
+    /// Compress the emitted body with `encoding`, typically the result of
+    /// [`ContentEncoding::from_accept_encoding`].
+    ///
+    /// Every batch is flushed through one compression context shared by the
+    /// whole response, so each chunk decodes as soon as it arrives; the last
+    /// chunk closes the coding. Mark the response with
+    /// [`set_content_encoding`].
+    pub fn with_content_encoding(mut self, encoding: ContentEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// The content coding applied to the emitted body.
+    pub fn content_encoding(&self) -> ContentEncoding {
+        self.encoding
+    }
+
+    /// Make the stream cooperate with graceful shutdown.
+    ///
+    /// Once `signal` starts draining, only critical frames are forwarded; when
+    /// the drain deadline passes the stream stops. In either case — frames
+    /// skipped or stream cut — the last batch ends with the
+    /// [`shutdown_error_frame`] for `stream_id`, whose resume point is one past
+    /// the last sequence delivered without a gap. `since_sequence` is the
+    /// exclusive lower bound the client asked for, so a stream cut before its
+    /// first frame still tells the client to resume where it already was.
+    ///
+    /// Frames are assumed to arrive in ascending sequence order, as the
+    /// repository's frame queries return them. The stream holds a
+    /// [`StreamGuard`] from the moment this is called until it is dropped,
+    /// so the shutdown coordinator waits for it.
+    pub fn with_drain_signal(
+        mut self,
+        signal: DrainSignal,
+        stream_id: StreamId,
+        since_sequence: Option<u64>,
+    ) -> Self {
+        self.drain = Some(Drain {
+            _guard: signal.track_stream(),
+            signal,
+            stream_id,
+            resume: ResumePoint::new(since_sequence),
+        });
+        self
+    }
+
+    /// Run `on_disconnect` if the stream is dropped before its last frame was
+    /// read from the source — the transport drops a response body early when
+    /// the client hangs up mid-response. A stream cut short by a drain
+    /// deadline does not count as a disconnect.
+    pub fn with_disconnect_hook(mut self, on_disconnect: impl FnOnce() + Send + 'static) -> Self {
+        self.on_disconnect = DisconnectHook(Some(Box::new(on_disconnect)));
+        self
+    }
+
     /// Returns the `Content-Type` that accurately describes what this stream emits.
     ///
     /// `BatchFrameStream` serializes each batch as newline-delimited JSON objects,
@@ -292,9 +555,10 @@ where
     ///
     /// Each item is one full batch as `Vec<u8>`. For `StreamFormat::Json` and
     /// `StreamFormat::NdJson` the bytes hold one JSON object per frame, one
-    /// per line (NDJSON-of-objects, #167). The stream item type is binary
-    /// (`Vec<u8>`, not `String`) to leave room for future per-batch
-    /// compression (#226).
+    /// per line (NDJSON-of-objects, #167). With a content encoding other than
+    /// [`ContentEncoding::Identity`], each item is instead that batch
+    /// compressed and flushed, followed by one final item that ends the
+    /// coding.
     pub fn into_stream(
         self,
     ) -> impl Stream<Item = Result<Vec<u8>, StreamTransportError>> + Send + 'static {
@@ -302,28 +566,102 @@ where
             inner,
             format,
             batch_size,
+            encoding,
+            mut drain,
+            mut on_disconnect,
         } = self;
+        let deadline = drain.as_ref().map(|d| d.signal.deadline_reached());
         try_stream! {
+            let mut compressor = batch_compressor(encoding)?;
             let mut batch: Vec<Frame> = Vec::with_capacity(batch_size);
             futures::pin_mut!(inner);
+            let deadline = async move {
+                match deadline {
+                    Some(deadline) => deadline.await,
+                    None => std::future::pending().await,
+                }
+            };
+            futures::pin_mut!(deadline);
+            let mut cut = false;
+
+            loop {
+                let frame = tokio::select! {
+                    biased;
+                    () = &mut deadline => {
+                        cut = true;
+                        break;
+                    }
+                    frame = inner.next() => frame,
+                };
+                let Some(frame) = frame else { break };
+
+                if let Some(drain) = drain.as_mut() {
+                    if drain.signal.is_draining() && !frame.is_critical() {
+                        drain.resume.skipped();
+                        continue;
+                    }
+                    drain.resume.delivered(frame.sequence());
+                }
 
-            while let Some(frame) = inner.next().await {
                 batch.push(frame);
                 if batch.len() >= batch_size {
-                    let bytes = format_batch_owned(&batch, format)?;
+                    let bytes = compress_batch(&mut compressor, format_batch_owned(&batch, format)?)?;
                     batch.clear();
                     yield bytes;
                 }
             }
+            // Source exhausted or cut for shutdown: whatever happens to the
+            // rest of the body, the client did not walk away from it.
+            on_disconnect.disarm();
+
+            if let Some(drain) = drain.as_ref()
+                && (cut || drain.resume.has_gap())
+            {
+                batch.push(shutdown_error_frame(
+                    drain.stream_id,
+                    drain.resume.resume_from(),
+                ));
+            }
 
             if !batch.is_empty() {
-                let bytes = format_batch_owned(&batch, format)?;
+                let bytes = compress_batch(&mut compressor, format_batch_owned(&batch, format)?)?;
+                yield bytes;
+            }
+            if let Some(compressor) = compressor {
+                let bytes = compressor
+                    .finish()
+                    .map_err(|e| StreamTransportError::Compression(e.to_string()))?;
                 yield bytes;
             }
         }
     }
 }
 
+/// The compression context for a response body, if it is encoded at all.
+pub(crate) fn batch_compressor(
+    encoding: ContentEncoding,
+) -> Result<Option<SharedContextCompressor>, StreamTransportError> {
+    if encoding == ContentEncoding::Identity {
+        return Ok(None);
+    }
+    SharedContextCompressor::new(encoding.byte_codec()?)
+        .map(Some)
+        .map_err(|e| StreamTransportError::Compression(e.to_string()))
+}
+
+/// Compress and flush one formatted batch, or pass it through unencoded.
+pub(crate) fn compress_batch(
+    compressor: &mut Option<SharedContextCompressor>,
+    bytes: Vec<u8>,
+) -> Result<Vec<u8>, StreamTransportError> {
+    match compressor {
+        Some(compressor) => compressor
+            .compress_frame(&bytes)
+            .map_err(|e| StreamTransportError::Compression(e.to_string())),
+        None => Ok(bytes),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Stream error types
 // ---------------------------------------------------------------------------
@@ -335,10 +673,18 @@ pub enum StreamTransportError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] sonic_rs::Error),
 
+    /// Frame has no `application/pjs+json` representation.
+    #[error("Encoding error: {0}")]
+    Encoding(#[from] PjsJsonError),
+
     /// Underlying I/O or transport failure.
     #[error("IO error: {0}")]
     Io(String),
 
+    /// The response body could not be content-encoded.
+    #[error("Compression error: {0}")]
+    Compression(String),
+
     /// Internal buffer overflowed before consumers could drain it.
     #[error("Buffer overflow")]
     BufferOverflow,
@@ -384,8 +730,14 @@ where
     // `Content-Length` to collide with) but is not the reason for this rule — the
     // encoder owning framing is. `X-Accel-Buffering` is a reverse-proxy hint, not a
     // connection-management header, and is unaffected by either concern.
-    if let StreamFormat::ServerSentEvents = format {
-        response = response.header("X-Accel-Buffering", "no");
+    match format {
+        StreamFormat::ServerSentEvents => {
+            response = response.header("X-Accel-Buffering", "no");
+        }
+        StreamFormat::PjsJson => {
+            response = response.header("PJS-Version", pjs_json::PROTOCOL_VERSION);
+        }
+        _ => {}
     }
 
     response
@@ -435,12 +787,16 @@ where
 mod tests {
     use super::*;
     use crate::domain::entities::Frame;
-    use crate::domain::value_objects::{JsonData, StreamId};
+    use crate::domain::value_objects::{JsonData, JsonPath, Priority, StreamId};
+    use crate::infrastructure::shutdown::{
+        RESUME_FROM_SEQUENCE_KEY, SHUTDOWN_ERROR_CODE, SHUTDOWN_FRAME_SEQUENCE, ShutdownCoordinator,
+    };
     use axum::http::header;
     use futures::StreamExt;
     use futures::stream;
     use std::pin::Pin;
     use std::task::{Context, Poll};
+    use std::time::Duration;
 
     fn make_skeleton_frame() -> Frame {
         Frame::skeleton(StreamId::new(), 1, JsonData::Null)
@@ -773,4 +1129,207 @@ mod tests {
             }
         }
     }
+
+    // -----------------------------------------------------------------------
+    // Graceful shutdown (drain signal)
+    // -----------------------------------------------------------------------
+
+    fn patch_frame(stream_id: StreamId, sequence: u64, priority: Priority) -> Frame {
+        let patch = crate::domain::entities::frame::FramePatch::set(
+            JsonPath::new(format!("$.field_{sequence}")).unwrap(),
+            JsonData::Integer(sequence as i64),
+        );
+        Frame::patch(stream_id, sequence, priority, vec![patch]).unwrap()
+    }
+
+    async fn collect_frames<S>(batch: BatchFrameStream<S>) -> Vec<serde_json::Value>
+    where
+        S: Stream<Item = Frame> + Unpin + Send + 'static,
+    {
+        let batches: Vec<_> = batch.into_stream().collect().await;
+        batches
+            .into_iter()
+            .flat_map(|bytes| {
+                let bytes = bytes.expect("batch should not error");
+                String::from_utf8(bytes)
+                    .unwrap()
+                    .lines()
+                    .map(|line| serde_json::from_str(line).unwrap())
+                    .collect::<Vec<serde_json::Value>>()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_drain_signal_is_transparent_while_running() {
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(5));
+        let stream_id = StreamId::new();
+        let frames = (1..=3).map(move |seq| patch_frame(stream_id, seq, Priority::LOW));
+
+        let batch = BatchFrameStream::new(stream::iter(frames), StreamFormat::NdJson, 2)
+            .with_drain_signal(coordinator.signal(), stream_id, None);
+        assert_eq!(coordinator.active_streams(), 1);
+        let out = collect_frames(batch).await;
+
+        assert_eq!(out.len(), 3);
+        assert!(out.iter().all(|frame| frame["frame_type"] != "Error"));
+        assert_eq!(coordinator.active_streams(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_draining_forwards_only_critical_frames_then_resume_hint() {
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(5));
+        let signal = coordinator.signal();
+        let stream_id = StreamId::new();
+        let frames = vec![
+            patch_frame(stream_id, 3, Priority::LOW),
+            patch_frame(stream_id, 4, Priority::CRITICAL),
+            patch_frame(stream_id, 5, Priority::LOW),
+        ];
+        let batch = BatchFrameStream::new(stream::iter(frames), StreamFormat::NdJson, 10)
+            .with_drain_signal(signal, stream_id, Some(2));
+
+        let report = tokio::spawn(async move { coordinator.shutdown().await });
+        tokio::task::yield_now().await;
+        let out = collect_frames(batch).await;
+
+        let sequences: Vec<_> = out
+            .iter()
+            .map(|f| f["sequence"].as_u64().unwrap())
+            .collect();
+        assert_eq!(
+            sequences,
+            vec![4, SHUTDOWN_FRAME_SEQUENCE],
+            "critical frame, then the resume hint"
+        );
+        let hint = &out[1];
+        assert_eq!(hint["frame_type"], "Error");
+        assert_eq!(hint["payload"]["code"], SHUTDOWN_ERROR_CODE);
+        assert_eq!(hint["metadata"][RESUME_FROM_SEQUENCE_KEY], "3");
+        assert!(report.await.unwrap().is_clean());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_deadline_cuts_a_stalled_stream() {
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(5));
+        let stream_id = StreamId::new();
+        let frames = stream::iter(vec![
+            patch_frame(stream_id, 0, Priority::CRITICAL),
+            patch_frame(stream_id, 1, Priority::CRITICAL),
+        ])
+        .chain(stream::pending());
+        let batch = BatchFrameStream::new(frames, StreamFormat::NdJson, 10).with_drain_signal(
+            coordinator.signal(),
+            stream_id,
+            None,
+        );
+
+        let collected = tokio::spawn(collect_frames(batch));
+        let report = coordinator.shutdown().await;
+        let out = collected.await.unwrap();
+
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[2]["frame_type"], "Error");
+        assert_eq!(out[2]["sequence"], SHUTDOWN_FRAME_SEQUENCE);
+        assert_eq!(out[2]["metadata"][RESUME_FROM_SEQUENCE_KEY], "2");
+        assert!(report.is_clean(), "cut stream must release its guard");
+    }
+
+    fn accept_encoding(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_accept_encoding_without_a_supported_coding_is_identity() {
+        for value in [
+            "",
+            "identity",
+            "compress, deflate",
+            "gzip;q=0, *;q=0",
+            "gzip;q=nan",
+        ] {
+            assert_eq!(
+                ContentEncoding::from_accept_encoding(&accept_encoding(value)),
+                ContentEncoding::Identity,
+                "{value:?}"
+            );
+        }
+        assert_eq!(
+            ContentEncoding::from_accept_encoding(&HeaderMap::new()),
+            ContentEncoding::Identity
+        );
+        assert_eq!(
+            ContentEncoding::from_token(" IDENTITY "),
+            Some(ContentEncoding::Identity)
+        );
+        assert_eq!(ContentEncoding::from_token("compress"), None);
+    }
+
+    #[cfg(all(feature = "compression", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_accept_encoding_negotiation() {
+        for (value, expected) in [
+            ("gzip", ContentEncoding::Gzip),
+            ("GZIP, br", ContentEncoding::Brotli),
+            ("gzip, deflate, br, zstd", ContentEncoding::Zstd),
+            ("br;q=0.5, gzip;q=0.9", ContentEncoding::Gzip),
+            ("zstd;q=0, *", ContentEncoding::Brotli),
+            ("*;q=0.1, gzip;q=0.2", ContentEncoding::Gzip),
+            ("br;q=abc, gzip;q=0.1", ContentEncoding::Gzip),
+        ] {
+            assert_eq!(
+                ContentEncoding::from_accept_encoding(&accept_encoding(value)),
+                expected,
+                "{value:?}"
+            );
+        }
+
+        let mut response = Response::new(axum::body::Body::empty());
+        set_content_encoding(&mut response, ContentEncoding::Brotli);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "br");
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_encoded_batches_decode_as_they_arrive() {
+        use crate::compression::shared_context::SharedContextDecompressor;
+        use std::io::Read;
+
+        let stream_id = StreamId::new();
+        let frames: Vec<Frame> = (0..6)
+            .map(|sequence| patch_frame(stream_id, sequence, Priority::HIGH))
+            .collect();
+        let plain: Vec<Vec<u8>> =
+            BatchFrameStream::new(stream::iter(frames.clone()), StreamFormat::NdJson, 2)
+                .into_stream()
+                .map(Result::unwrap)
+                .collect()
+                .await;
+        let encoded: Vec<Vec<u8>> =
+            BatchFrameStream::new(stream::iter(frames), StreamFormat::NdJson, 2)
+                .with_content_encoding(ContentEncoding::Gzip)
+                .into_stream()
+                .map(Result::unwrap)
+                .collect()
+                .await;
+
+        // One chunk per batch, plus the one that ends the gzip member.
+        assert_eq!(encoded.len(), plain.len() + 1);
+        let mut decompressor = SharedContextDecompressor::with_default_security(
+            crate::compression::secure::ByteCodec::Gzip,
+        )
+        .unwrap();
+        for (chunk, batch) in encoded.iter().zip(&plain) {
+            assert_eq!(&decompressor.decompress_frame(chunk).unwrap(), batch);
+        }
+
+        let mut body = Vec::new();
+        flate2::read::GzDecoder::new(&encoded.concat()[..])
+            .read_to_end(&mut body)
+            .unwrap();
+        assert_eq!(body, plain.concat());
+    }
 }
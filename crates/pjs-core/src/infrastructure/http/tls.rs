@@ -0,0 +1,126 @@
+//! TLS termination support for [`serve_tls_with_shutdown`](super::serve_tls_with_shutdown).
+//!
+//! Gated behind the `http-tls` feature. Certificates and keys are read from
+//! PEM files, the format produced by every common CA tooling (certbot,
+//! cert-manager, `openssl req`). The crypto provider is pinned to `ring`
+//! explicitly rather than taken from rustls' process-wide default, so an
+//! application that also links another rustls provider cannot turn
+//! [`load_server_config`] into a runtime panic.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub use tokio_rustls::rustls;
+use tokio_rustls::rustls::{
+    ServerConfig,
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+};
+
+/// Errors produced while building a TLS server configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum TlsConfigError {
+    /// A PEM file could not be read or decoded.
+    #[error("failed to load {kind} from `{}`: {source}", path.display())]
+    Pem {
+        /// What the file was expected to contain (`"certificate chain"` or `"private key"`)
+        kind: &'static str,
+        /// Path of the offending file
+        path: PathBuf,
+        /// Underlying PEM or I/O error
+        #[source]
+        source: rustls::pki_types::pem::Error,
+    },
+
+    /// The certificate file contained no certificates.
+    #[error("no certificates found in `{}`", path.display())]
+    NoCertificates {
+        /// Path of the certificate file
+        path: PathBuf,
+    },
+
+    /// rustls rejected the certificate/key pair (e.g. the key does not match
+    /// the leaf certificate).
+    #[error("invalid TLS configuration: {0}")]
+    Rustls(#[from] rustls::Error),
+}
+
+/// Build a rustls [`ServerConfig`] from a PEM certificate chain and a PEM
+/// private key (PKCS#8, PKCS#1 or SEC1).
+///
+/// Client certificates are not requested. ALPN is left empty here;
+/// [`serve_tls_with_shutdown`](super::serve_tls_with_shutdown) fills in `h2`
+/// and `http/1.1` when it is.
+///
+/// # Errors
+///
+/// - [`TlsConfigError::Pem`] if either file cannot be read or parsed.
+/// - [`TlsConfigError::NoCertificates`] if `cert_path` holds no certificate.
+/// - [`TlsConfigError::Rustls`] if the key does not match the certificate or
+///   uses an unsupported algorithm.
+pub fn load_server_config(
+    cert_path: &Path,
+    key_path: &Path,
+) -> Result<Arc<ServerConfig>, TlsConfigError> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|source| TlsConfigError::Pem {
+            kind: "certificate chain",
+            path: cert_path.to_path_buf(),
+            source,
+        })?;
+    if certs.is_empty() {
+        return Err(TlsConfigError::NoCertificates {
+            path: cert_path.to_path_buf(),
+        });
+    }
+
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(|source| TlsConfigError::Pem {
+        kind: "private key",
+        path: key_path.to_path_buf(),
+        source,
+    })?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(Arc::new(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_certificate_file_is_pem_error() {
+        let err = load_server_config(
+            Path::new("/nonexistent/pjs-cert.pem"),
+            Path::new("/nonexistent/pjs-key.pem"),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            TlsConfigError::Pem {
+                kind: "certificate chain",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn file_without_certificates_is_rejected() {
+        let path = std::env::temp_dir().join(format!("pjs-tls-{}.pem", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "not a certificate\n").unwrap();
+
+        let result = load_server_config(&path, &path);
+        std::fs::remove_file(&path).ok();
+
+        assert!(matches!(
+            result.unwrap_err(),
+            TlsConfigError::NoCertificates { .. }
+        ));
+    }
+}
@@ -9,6 +9,7 @@ pub mod bounded_channel;
 pub mod http;
 pub mod repositories;
 pub mod schema_repository;
+pub mod shutdown;
 #[cfg(feature = "http-server")]
 pub mod websocket;
 
@@ -24,19 +25,22 @@ pub use bounded_channel::{
 };
 #[cfg(feature = "http-server")]
 pub use http::{
-    BatchFrameStream, ConnectionLimits, CreateSessionRequest, CreateSessionResponse,
-    HttpExtensionConfig, HttpServerConfig, PjsAppState, PjsError, PjsExtension, RateLimitConfig,
-    RateLimitMiddleware, StartStreamRequest, StreamFormat, StreamParams, StreamTransportError,
-    TrustedProxyConfig, create_pjs_router, create_pjs_router_with_auth,
-    create_pjs_router_with_config, create_pjs_router_with_rate_limit,
+    BatchFrameStream, ConnectionLimits, ContentEncoding, CreateSessionRequest,
+    CreateSessionResponse, HttpExtensionConfig, HttpServerConfig, PjsAppState, PjsError,
+    PjsExtension, RateLimitConfig, RateLimitMiddleware, StartStreamRequest, StreamFormat,
+    StreamParams, StreamTransportError, TrustedProxyConfig, create_pjs_router,
+    create_pjs_router_with_auth, create_pjs_router_with_config, create_pjs_router_with_rate_limit,
     create_pjs_router_with_rate_limit_and_auth, create_pjs_router_with_rate_limit_and_config,
     create_streaming_response, create_streaming_response_with_content_type, serve_with_limits,
+    serve_with_shutdown, set_content_encoding,
 };
 pub use schema_repository::SchemaRepository;
+pub use shutdown::{DrainSignal, ShutdownCoordinator, ShutdownReport, StreamGuard};
 #[cfg(feature = "http-server")]
 pub use websocket::{
-    AdaptiveStreamController, AxumWebSocketTransport, ClientMetrics, SecureWebSocketHandler,
-    StreamOptions, WebSocketStreamSession, WebSocketTransport, WsMessage, create_websocket_router,
+    AdaptiveStreamController, AxumWebSocketTransport, ClientMetrics, DeliveryMode,
+    SecureWebSocketHandler, StreamOptions, WebSocketStreamSession, WebSocketTransport, WsMessage,
+    create_websocket_router,
 };
 #[cfg(all(feature = "http-server", feature = "websocket-client"))]
-pub use websocket::{PjsWebSocketClient, StreamStats};
+pub use websocket::{ConnectionEvent, PjsWebSocketClient, ReconnectPolicy, StreamStats};
@@ -0,0 +1,569 @@
+//! Graceful shutdown for in-flight PJS streams.
+//!
+//! Closing the listener is not enough for a streaming protocol: an SSE/NDJSON
+//! response or a WebSocket session that is cut mid-document leaves the client
+//! with a truncated skeleton and no way to tell where to pick up. A
+//! [`ShutdownCoordinator`] turns shutdown into a drain with three phases:
+//!
+//! 1. **Draining** — [`ShutdownCoordinator::shutdown`] flips every
+//!    [`DrainSignal`] clone. Listeners stop accepting (pass
+//!    [`DrainSignal::draining`] as the shutdown future of
+//!    [`serve_with_shutdown`](crate::infrastructure::http::serve_with_shutdown)),
+//!    new streams are refused, and streams already running switch to sending
+//!    only their remaining [`Priority::CRITICAL`](crate::domain::value_objects::Priority::CRITICAL)
+//!    frames.
+//! 2. **Deadline** — once the drain timeout elapses, every stream that has
+//!    not finished stops and ends with a terminal `Error` frame (see
+//!    [`shutdown_error_frame`]) whose `resume_from_sequence` metadata tells
+//!    the client which sequence to resume from on another instance.
+//! 3. **Flush** — after the streams are gone (or a short grace period past
+//!    the deadline), every registered [`Flush`] target — repositories, event
+//!    publishers — is flushed in registration order.
+//!
+//! Streams participate by holding a [`StreamGuard`] from
+//! [`DrainSignal::track_stream`] for as long as they run; the coordinator
+//! uses the guard count to finish early when everything has drained.
+
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::{
+    sync::{Notify, watch},
+    time::Instant,
+};
+use tracing::{info, warn};
+
+use crate::domain::{entities::Frame, ports::Flush, value_objects::StreamId};
+
+/// `code` carried by the terminal `Error` frame of a stream cut short by
+/// shutdown.
+pub const SHUTDOWN_ERROR_CODE: &str = "SERVER_SHUTTING_DOWN";
+
+/// Frame metadata key holding the first sequence number the client has not
+/// received, on the terminal frame of a stream cut short by shutdown.
+pub const RESUME_FROM_SEQUENCE_KEY: &str = "resume_from_sequence";
+
+/// Sequence number of the terminal frame of a stream cut short by shutdown.
+///
+/// No data frame uses it, so a client that deduplicates or reorders frames
+/// by sequence never mistakes the hint for a data frame, or a data frame
+/// for a duplicate of the hint.
+pub const SHUTDOWN_FRAME_SEQUENCE: u64 = u64::MAX;
+
+/// How long past the drain deadline [`ShutdownCoordinator::shutdown`] keeps
+/// waiting for streams to write their terminal frame before flushing anyway.
+///
+/// Streams stop producing at the deadline itself; this only covers the
+/// handful of writes needed to emit the resume hint and unwind.
+pub const TERMINATION_GRACE: Duration = Duration::from_secs(1);
+
+/// Human-readable message for a stream cut short by shutdown.
+pub fn shutdown_message(resume_from: u64) -> String {
+    format!("server shutting down, resume from sequence {resume_from}")
+}
+
+/// Build the terminal `Error` frame for a stream cut short by shutdown.
+///
+/// `resume_from` is the first sequence number the client is missing: it has
+/// every frame below it, and resumes by requesting frames after
+/// `resume_from - 1` (the frames endpoints' `since_sequence` parameter is
+/// exclusive). Frames above `resume_from` that were delivered anyway — e.g.
+/// critical frames sent after non-critical ones were skipped — arrive again on
+/// resume and are deduplicated by sequence. The frame itself carries
+/// [`SHUTDOWN_FRAME_SEQUENCE`] and [`SHUTDOWN_ERROR_CODE`]; `resume_from`
+/// appears only in its message and its [`RESUME_FROM_SEQUENCE_KEY`] metadata.
+pub fn shutdown_error_frame(stream_id: StreamId, resume_from: u64) -> Frame {
+    Frame::error(
+        stream_id,
+        SHUTDOWN_FRAME_SEQUENCE,
+        shutdown_message(resume_from),
+        Some(SHUTDOWN_ERROR_CODE.to_string()),
+    )
+    .with_metadata(
+        RESUME_FROM_SEQUENCE_KEY.to_string(),
+        resume_from.to_string(),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Running,
+    Draining { deadline: Instant },
+}
+
+#[derive(Debug, Default)]
+struct ActiveStreams {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+impl ActiveStreams {
+    async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            futures::pin_mut!(notified);
+            notified.as_mut().enable();
+            if self.count.load(Ordering::Acquire) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Owns the drain state and the flush targets; see the [module docs](self).
+///
+/// Build one per process, hand [`Self::signal`] clones to the HTTP state
+/// ([`PjsAppState::with_drain_signal`](crate::infrastructure::http::PjsAppState::with_drain_signal))
+/// and the WebSocket transport
+/// ([`AxumWebSocketTransport::with_drain_signal`](crate::infrastructure::websocket::AxumWebSocketTransport::with_drain_signal)),
+/// and call [`Self::shutdown`] when the process is asked to stop.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::{sync::Arc, time::Duration};
+/// use pjson_rs::infrastructure::{InMemoryEventPublisher, shutdown::ShutdownCoordinator};
+///
+/// # tokio_test::block_on(async {
+/// let publisher = Arc::new(InMemoryEventPublisher::new());
+/// let coordinator = ShutdownCoordinator::new(Duration::from_secs(5))
+///     .with_flush("events", publisher.clone());
+///
+/// let signal = coordinator.signal();
+/// assert!(!signal.is_draining());
+///
+/// let report = coordinator.shutdown().await;
+/// assert!(signal.is_draining());
+/// assert!(report.is_clean());
+/// # });
+/// ```
+pub struct ShutdownCoordinator {
+    phase: watch::Sender<Phase>,
+    streams: Arc<ActiveStreams>,
+    drain_timeout: Duration,
+    flush_targets: Vec<(String, Arc<dyn Flush>)>,
+}
+
+impl ShutdownCoordinator {
+    /// Create a coordinator that gives in-flight streams `drain_timeout` to
+    /// send their remaining critical frames.
+    pub fn new(drain_timeout: Duration) -> Self {
+        let (phase, _) = watch::channel(Phase::Running);
+        Self {
+            phase,
+            streams: Arc::new(ActiveStreams::default()),
+            drain_timeout,
+            flush_targets: Vec::new(),
+        }
+    }
+
+    /// Register a target to flush once streams have drained. `name` only
+    /// appears in logs and in [`ShutdownReport::flush_failures`].
+    pub fn with_flush(mut self, name: impl Into<String>, target: Arc<dyn Flush>) -> Self {
+        self.flush_targets.push((name.into(), target));
+        self
+    }
+
+    /// The drain timeout this coordinator was built with.
+    pub fn drain_timeout(&self) -> Duration {
+        self.drain_timeout
+    }
+
+    /// A cloneable handle that observes the drain and tracks streams.
+    pub fn signal(&self) -> DrainSignal {
+        DrainSignal {
+            phase: self.phase.subscribe(),
+            streams: Arc::clone(&self.streams),
+        }
+    }
+
+    /// Number of streams currently holding a [`StreamGuard`].
+    pub fn active_streams(&self) -> usize {
+        self.streams.count.load(Ordering::Acquire)
+    }
+
+    /// Drain in-flight streams, then flush every registered target.
+    ///
+    /// Starts the drain (a no-op if it already started, so the original
+    /// deadline is kept), waits until no stream holds a guard or
+    /// [`TERMINATION_GRACE`] past the deadline has elapsed, then flushes the
+    /// targets in registration order. Flush failures are logged and reported,
+    /// never propagated, so one failing target does not stop the others.
+    pub async fn shutdown(&self) -> ShutdownReport {
+        let deadline = Instant::now() + self.drain_timeout;
+        self.phase.send_if_modified(|phase| match phase {
+            Phase::Running => {
+                *phase = Phase::Draining { deadline };
+                true
+            }
+            Phase::Draining { .. } => false,
+        });
+        let deadline = match *self.phase.borrow() {
+            Phase::Draining { deadline } => deadline,
+            Phase::Running => deadline,
+        };
+
+        info!(
+            active_streams = self.active_streams(),
+            "draining in-flight streams"
+        );
+        let drained =
+            tokio::time::timeout_at(deadline + TERMINATION_GRACE, self.streams.wait_idle())
+                .await
+                .is_ok();
+        let interrupted_streams = if drained { 0 } else { self.active_streams() };
+        if interrupted_streams > 0 {
+            warn!(
+                interrupted_streams,
+                "streams still open after the drain deadline"
+            );
+        }
+
+        let mut flush_failures = Vec::new();
+        for (name, target) in &self.flush_targets {
+            if let Err(e) = target.flush().await {
+                warn!(target = %name, error = %e, "flush failed during shutdown");
+                flush_failures.push((name.clone(), e));
+            }
+        }
+
+        ShutdownReport {
+            interrupted_streams,
+            flush_failures,
+        }
+    }
+}
+
+impl std::fmt::Debug for ShutdownCoordinator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShutdownCoordinator")
+            .field("phase", &*self.phase.borrow())
+            .field("active_streams", &self.active_streams())
+            .field("drain_timeout", &self.drain_timeout)
+            .field(
+                "flush_targets",
+                &self
+                    .flush_targets
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Outcome of [`ShutdownCoordinator::shutdown`].
+#[derive(Debug)]
+pub struct ShutdownReport {
+    /// Streams that still held a guard when the coordinator stopped waiting.
+    pub interrupted_streams: usize,
+    /// Flush targets that returned an error, by registration name.
+    pub flush_failures: Vec<(String, crate::Error)>,
+}
+
+impl ShutdownReport {
+    /// Whether every stream finished and every flush succeeded.
+    pub fn is_clean(&self) -> bool {
+        self.interrupted_streams == 0 && self.flush_failures.is_empty()
+    }
+}
+
+/// Read side of a [`ShutdownCoordinator`], cheap to clone into every
+/// handler, transport and stream.
+#[derive(Debug, Clone)]
+pub struct DrainSignal {
+    phase: watch::Receiver<Phase>,
+    streams: Arc<ActiveStreams>,
+}
+
+impl DrainSignal {
+    /// Whether shutdown has started.
+    pub fn is_draining(&self) -> bool {
+        matches!(*self.phase.borrow(), Phase::Draining { .. })
+    }
+
+    /// The instant by which in-flight streams must stop, once draining.
+    pub fn deadline(&self) -> Option<Instant> {
+        match *self.phase.borrow() {
+            Phase::Running => None,
+            Phase::Draining { deadline } => Some(deadline),
+        }
+    }
+
+    /// Resolves when shutdown starts; never resolves if the coordinator is
+    /// dropped without shutting down.
+    ///
+    /// Suitable as the `shutdown` future of
+    /// [`serve_with_shutdown`](crate::infrastructure::http::serve_with_shutdown).
+    pub fn draining(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut phase = self.phase.clone();
+        async move {
+            let started = phase
+                .wait_for(|phase| matches!(phase, Phase::Draining { .. }))
+                .await
+                .is_ok();
+            if !started {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// Resolves once draining has started and its deadline has passed.
+    pub fn deadline_reached(&self) -> impl Future<Output = ()> + Send + 'static {
+        let signal = self.clone();
+        async move {
+            signal.draining().await;
+            if let Some(deadline) = signal.deadline() {
+                tokio::time::sleep_until(deadline).await;
+            }
+        }
+    }
+
+    /// Resolves once draining has started and either every tracked stream
+    /// has finished or [`TERMINATION_GRACE`] past the deadline has elapsed —
+    /// the point at which connections carrying those streams can be closed.
+    pub fn streams_finished(&self) -> impl Future<Output = ()> + Send + 'static {
+        let signal = self.clone();
+        async move {
+            signal.draining().await;
+            let Some(deadline) = signal.deadline() else {
+                return;
+            };
+            let _ =
+                tokio::time::timeout_at(deadline + TERMINATION_GRACE, signal.streams.wait_idle())
+                    .await;
+        }
+    }
+
+    /// Register a running stream; the coordinator waits for the returned
+    /// guard to drop before flushing.
+    pub fn track_stream(&self) -> StreamGuard {
+        self.streams.count.fetch_add(1, Ordering::AcqRel);
+        StreamGuard {
+            streams: Arc::clone(&self.streams),
+        }
+    }
+}
+
+/// Marks a stream as in flight until dropped; see [`DrainSignal::track_stream`].
+#[derive(Debug)]
+pub struct StreamGuard {
+    streams: Arc<ActiveStreams>,
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        if self.streams.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.streams.idle.notify_waiters();
+        }
+    }
+}
+
+/// Tracks what a draining stream has delivered, to compute the resume point
+/// for [`shutdown_error_frame`].
+///
+/// Sequences are assumed to arrive in ascending order. The resume point is
+/// one past the last sequence delivered *contiguously*: once a frame is
+/// skipped, later deliveries no longer advance it.
+#[cfg(feature = "http-server")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ResumePoint {
+    delivered_through: Option<u64>,
+    gap: bool,
+}
+
+#[cfg(feature = "http-server")]
+impl ResumePoint {
+    /// Start from a client that already has every sequence up to and
+    /// including `delivered_through`.
+    pub(crate) fn new(delivered_through: Option<u64>) -> Self {
+        Self {
+            delivered_through,
+            gap: false,
+        }
+    }
+
+    pub(crate) fn delivered(&mut self, sequence: u64) {
+        if !self.gap {
+            self.delivered_through = Some(sequence);
+        }
+    }
+
+    pub(crate) fn skipped(&mut self) {
+        self.gap = true;
+    }
+
+    /// Whether any frame has been skipped.
+    pub(crate) fn has_gap(&self) -> bool {
+        self.gap
+    }
+
+    /// First sequence the client is missing.
+    pub(crate) fn resume_from(&self) -> u64 {
+        self.delivered_through
+            .map_or(0, |seq| seq.saturating_add(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{entities::frame::FrameType, ports::FlushFuture};
+    use std::sync::atomic::AtomicBool;
+
+    struct RecordingFlush {
+        flushed: AtomicBool,
+        fail: bool,
+    }
+
+    impl Flush for RecordingFlush {
+        fn flush(&self) -> FlushFuture<'_> {
+            Box::pin(async move {
+                self.flushed.store(true, Ordering::SeqCst);
+                if self.fail {
+                    Err(crate::Error::other("disk full"))
+                } else {
+                    Ok(())
+                }
+            })
+        }
+    }
+
+    fn recording(fail: bool) -> Arc<RecordingFlush> {
+        Arc::new(RecordingFlush {
+            flushed: AtomicBool::new(false),
+            fail,
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_waits_for_tracked_streams_then_flushes() {
+        let target = recording(false);
+        let coordinator =
+            ShutdownCoordinator::new(Duration::from_secs(10)).with_flush("repo", target.clone());
+        let signal = coordinator.signal();
+        let guard = signal.track_stream();
+
+        let stream = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            drop(guard);
+        });
+
+        let started = Instant::now();
+        let report = coordinator.shutdown().await;
+        stream.await.unwrap();
+
+        assert!(report.is_clean());
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+        assert!(target.flushed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_gives_up_after_deadline_and_grace() {
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(3));
+        let signal = coordinator.signal();
+        let _stuck = signal.track_stream();
+
+        let started = Instant::now();
+        let report = coordinator.shutdown().await;
+
+        assert_eq!(report.interrupted_streams, 1);
+        assert_eq!(
+            started.elapsed(),
+            Duration::from_secs(3) + TERMINATION_GRACE
+        );
+    }
+
+    #[tokio::test]
+    async fn flush_failures_are_reported_without_stopping_other_targets() {
+        let failing = recording(true);
+        let healthy = recording(false);
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(1))
+            .with_flush("events", failing.clone())
+            .with_flush("repo", healthy.clone());
+
+        let report = coordinator.shutdown().await;
+
+        assert!(!report.is_clean());
+        assert_eq!(report.flush_failures.len(), 1);
+        assert_eq!(report.flush_failures[0].0, "events");
+        assert!(healthy.flushed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn signal_futures_follow_the_drain() {
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(5));
+        let signal = coordinator.signal();
+        assert!(!signal.is_draining());
+        assert_eq!(signal.deadline(), None);
+
+        let draining = tokio::spawn(signal.draining());
+        let deadline = tokio::spawn(signal.deadline_reached());
+        tokio::task::yield_now().await;
+        assert!(!draining.is_finished());
+
+        let started = Instant::now();
+        coordinator.shutdown().await;
+        draining.await.unwrap();
+        assert!(signal.is_draining());
+        assert_eq!(signal.deadline(), Some(started + Duration::from_secs(5)));
+        deadline.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn repeated_shutdown_keeps_the_original_deadline() {
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(5));
+        let signal = coordinator.signal();
+        coordinator.shutdown().await;
+        let first = signal.deadline();
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        coordinator.shutdown().await;
+        assert_eq!(signal.deadline(), first);
+    }
+
+    #[cfg(feature = "http-server")]
+    #[test]
+    fn resume_point_stops_advancing_at_the_first_gap() {
+        let mut resume = ResumePoint::new(None);
+        assert_eq!(resume.resume_from(), 0);
+
+        resume.delivered(0);
+        resume.delivered(1);
+        assert!(!resume.has_gap());
+        resume.skipped();
+        assert!(resume.has_gap());
+        resume.delivered(5);
+        assert_eq!(resume.resume_from(), 2);
+
+        assert_eq!(ResumePoint::new(Some(9)).resume_from(), 10);
+    }
+
+    #[test]
+    fn shutdown_error_frame_carries_the_resume_hint() {
+        let frame = shutdown_error_frame(StreamId::new(), 7);
+
+        assert_eq!(frame.frame_type(), &FrameType::Error);
+        assert_eq!(frame.sequence(), SHUTDOWN_FRAME_SEQUENCE);
+        assert!(frame.is_critical());
+        assert_eq!(
+            frame
+                .metadata_value(RESUME_FROM_SEQUENCE_KEY)
+                .map(String::as_str),
+            Some("7")
+        );
+        let payload = serde_json::to_value(frame.payload()).unwrap();
+        assert_eq!(payload["code"], SHUTDOWN_ERROR_CODE);
+        assert_eq!(
+            payload["message"],
+            "server shutting down, resume from sequence 7"
+        );
+    }
+}
@@ -1,20 +1,24 @@
 //! WebSocket client implementation for PJS streaming
 
 #[cfg(feature = "websocket-client")]
-use super::{StreamOptions, WsMessage};
+use super::{DeliveryMode, RETRANSMIT_BUFFER_CAPACITY, StreamOptions, WsMessage};
 use crate::{
     Error as PjsError, Result as PjsResult,
+    domain::value_objects::{IntegrityError, IntegrityKey},
     infrastructure::bounded_channel::{ByteBoundedSender, Envelope, byte_bounded_channel},
 };
-use futures::StreamExt;
+use futures::{SinkExt, StreamExt};
 use serde_json::Value;
 use std::{
-    collections::HashMap,
-    sync::Arc,
+    collections::{BTreeMap, HashMap},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
     time::{Duration, Instant},
 };
-use tokio::sync::{RwLock, mpsc};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio::sync::{RwLock, broadcast, mpsc};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
 use tracing::{debug, error, info, warn};
 use url::Url;
 
@@ -33,6 +37,97 @@ const MESSAGE_QUEUE_CAPACITY: usize = 1000;
 /// of individual message size (e.g. a large `StreamInit` payload).
 const MAX_QUEUED_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
 
+/// Capacity of the [`ConnectionEvent`] broadcast channel; a subscriber that
+/// falls further behind misses the oldest events.
+const CONNECTION_EVENT_CAPACITY: usize = 64;
+
+/// How [`PjsWebSocketClient::connect`] retries a dropped or failed
+/// connection: exponential backoff from `initial_delay`, capped at
+/// `max_delay`, with each delay randomly shortened or lengthened by up to
+/// `jitter` of itself so that clients dropped together do not reconnect in
+/// lockstep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnection attempt.
+    pub initial_delay: Duration,
+    /// Upper bound on the delay between attempts, before jitter.
+    pub max_delay: Duration,
+    /// Factor the delay grows by after each failed attempt.
+    pub multiplier: f64,
+    /// Fraction of each delay, in `0.0..=1.0`, to randomize it by.
+    pub jitter: f64,
+    /// Consecutive failed attempts after which the client gives up, or
+    /// `None` to keep trying.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: Some(10),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before connection attempt `attempt`, counting attempts since
+    /// the last successful connection from 1, with jitter applied.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let unit = uuid::Uuid::new_v4().as_u128() as f64 / u128::MAX as f64;
+        self.delay_with(attempt, unit)
+    }
+
+    /// [`Self::delay`] with the jitter drawn from `unit` in `0.0..=1.0`.
+    fn delay_with(&self, attempt: u32, unit: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let base = (self.initial_delay.as_secs_f64() * self.multiplier.max(1.0).powi(exponent))
+            .min(self.max_delay.as_secs_f64());
+        let jitter = self.jitter.clamp(0.0, 1.0) * (2.0 * unit - 1.0);
+        Duration::from_secs_f64((base * (1.0 + jitter)).max(0.0))
+    }
+}
+
+/// Change in the state of [`PjsWebSocketClient::connect`]'s connection,
+/// published to [`PjsWebSocketClient::subscribe_connection_events`]
+/// subscribers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// A connection was established; sessions requested on an earlier one
+    /// are being resumed.
+    Connected {
+        /// Number of sessions a resume was requested for.
+        resuming: usize,
+    },
+    /// The connection closed or failed.
+    Disconnected {
+        /// What ended the connection.
+        reason: String,
+    },
+    /// The client is waiting `delay` before connection attempt `attempt`.
+    Reconnecting {
+        /// Connection attempt about to be made, counting attempts since the
+        /// last successful connection from 1.
+        attempt: u32,
+        /// Backoff before the attempt.
+        delay: Duration,
+    },
+    /// The server no longer has the session, so it cannot be resumed; its
+    /// data stays as far as it was reconstructed.
+    SessionExpired {
+        /// Identifier of the lost session.
+        session_id: String,
+    },
+    /// The client stopped reconnecting after `attempts` failed attempts.
+    GaveUp {
+        /// Number of consecutive failed attempts.
+        attempts: u32,
+    },
+}
+
 /// WebSocket client for receiving PJS streams
 pub struct PjsWebSocketClient {
     url: Url,
@@ -40,6 +135,11 @@ pub struct PjsWebSocketClient {
     message_tx: ByteBoundedSender<String>,
     message_rx: Arc<RwLock<Option<mpsc::Receiver<Envelope<String>>>>>,
     write_timeout: Duration,
+    integrity_key: Option<IntegrityKey>,
+    reconnect: Option<ReconnectPolicy>,
+    events: broadcast::Sender<ConnectionEvent>,
+    /// Connections established so far.
+    connections: AtomicU64,
 }
 
 /// Client-side stream session
@@ -50,6 +150,56 @@ struct ClientStreamSession {
     received_frames: HashMap<u32, ReceivedFrame>,
     reconstructed_data: Value,
     is_complete: bool,
+    integrity: Option<Result<(), IntegrityError>>,
+    version: u64,
+    /// Connections established before the stream was requested; its
+    /// `StreamInit` goes out on the next one at the earliest.
+    requested_after: u64,
+    delivery: DeliveryMode,
+    /// Next `frame_id` expected, which a resume continues from. For
+    /// at-least-once sessions also the next frame to apply, with frames
+    /// received ahead of it and the gap a resend was last requested for.
+    next_frame_id: u32,
+    pending_frames: BTreeMap<u32, (Value, bool)>,
+    resend_requested_from: Option<u32>,
+}
+
+impl ClientStreamSession {
+    /// Apply an at-least-once frame, and every buffered frame it unblocks,
+    /// in `frame_id` order; duplicates are ignored. Returns the first
+    /// missing `frame_id` when frames are left waiting on a gap not yet
+    /// asked for.
+    fn apply_in_order(&mut self, frame_id: u32, payload: Value, is_complete: bool) -> Option<u32> {
+        // The server never has more than RETRANSMIT_BUFFER_CAPACITY frames
+        // unacknowledged, so anything further ahead is not a real frame.
+        if frame_id
+            .checked_sub(self.next_frame_id)
+            .is_some_and(|ahead| (ahead as usize) < RETRANSMIT_BUFFER_CAPACITY)
+        {
+            self.pending_frames
+                .entry(frame_id)
+                .or_insert((payload, is_complete));
+        }
+        while let Some((payload, is_complete)) = self.pending_frames.remove(&self.next_frame_id) {
+            super::apply_frame_payload(&mut self.reconstructed_data, &payload);
+            self.next_frame_id += 1;
+            if is_complete {
+                self.is_complete = true;
+            }
+        }
+        self.request_resend_once()
+    }
+
+    /// The first missing `frame_id`, unless a resend from it was already
+    /// requested or nothing is waiting on it.
+    fn request_resend_once(&mut self) -> Option<u32> {
+        if self.pending_frames.is_empty() || self.resend_requested_from == Some(self.next_frame_id)
+        {
+            return None;
+        }
+        self.resend_requested_from = Some(self.next_frame_id);
+        Some(self.next_frame_id)
+    }
 }
 
 /// Frame received by client
@@ -73,6 +223,10 @@ impl PjsWebSocketClient {
             message_tx,
             message_rx: Arc::new(RwLock::new(Some(message_rx))),
             write_timeout: super::WRITE_TIMEOUT,
+            integrity_key: None,
+            reconnect: None,
+            events: broadcast::channel(CONNECTION_EVENT_CAPACITY).0,
+            connections: AtomicU64::new(0),
         })
     }
 
@@ -111,19 +265,57 @@ impl PjsWebSocketClient {
         self
     }
 
-    /// Connect to WebSocket server and start message handling
-    pub async fn connect(&self) -> PjsResult<()> {
-        info!("Connecting to WebSocket server: {}", self.url);
-
-        let (ws_stream, _) = connect_async(self.url.as_str())
-            .await
-            .map_err(|e| PjsError::ConnectionFailed(e.to_string()))?;
+    /// Verifies completion digests as HMAC-SHA256 signatures under `key`.
+    ///
+    /// Must match the key the server was configured with (see
+    /// `AxumWebSocketTransport::with_integrity_key`). Without a key, the
+    /// client still checks unsigned SHA-256 digests but reports signed ones
+    /// as [`IntegrityError::KeyRequired`]; with a key, unsigned digests are
+    /// reported as [`IntegrityError::Unsigned`].
+    #[must_use]
+    pub fn with_integrity_key(mut self, key: IntegrityKey) -> Self {
+        self.integrity_key = Some(key);
+        self
+    }
 
-        info!("WebSocket connection established");
+    /// Reconnects after the connection drops, following `policy`, and
+    /// resumes every session on the new connection.
+    ///
+    /// Resumed sessions keep reconstructing into the same
+    /// [`Self::get_current_data`] snapshot: the server sends the frames
+    /// after the last one received, or the current document of a session
+    /// that has had live updates. A server that kept no state for a session
+    /// (restarted, or [`SESSION_RESUME_WINDOW`](super::SESSION_RESUME_WINDOW)
+    /// elapsed) reports it as [`ConnectionEvent::SessionExpired`].
+    ///
+    /// Without a policy, [`Self::connect`] returns when the connection
+    /// closes.
+    #[must_use]
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
 
-        let (mut write, mut read) = ws_stream.split();
+    /// Subscribe to changes in the connection's state.
+    pub fn subscribe_connection_events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.events.subscribe()
+    }
 
-        // Take the receiver (can only be done once)
+    /// Connect to WebSocket server and handle messages until the
+    /// connection closes.
+    ///
+    /// With a [`ReconnectPolicy`] (see [`Self::with_reconnect`]), a failed
+    /// or dropped connection is retried instead, and this only returns once
+    /// the policy gives up. Stream requests made while disconnected are
+    /// sent once a connection is up.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PjsError::ConnectionFailed`] if no connection could be
+    /// established (after the policy's last attempt, with one), and an
+    /// error if the client is already connected.
+    pub async fn connect(&self) -> PjsResult<()> {
+        // Take the receiver, so only one connection loop runs at a time
         let mut message_rx = self
             .message_rx
             .write()
@@ -131,42 +323,125 @@ impl PjsWebSocketClient {
             .take()
             .ok_or_else(|| PjsError::ClientError("Client already connected".to_string()))?;
 
-        // Spawn task to send outgoing messages. Messages are already
-        // serialized at the point they were queued (see `request_stream`
-        // and `handle_incoming_message`), so the byte-budget accounting
-        // there matches the bytes actually held in memory here. `split`
-        // (rather than `into_inner`) keeps the byte budget charged until
-        // the write actually completes, not just until the item leaves
-        // the channel.
-        let write_timeout = self.write_timeout;
-        let send_task = tokio::spawn(async move {
-            while let Some(envelope) = message_rx.recv().await {
-                let (json_str, _budget_permit) = envelope.split();
-                if let Err(e) = super::send_with_write_timeout(
-                    &mut write,
-                    Message::Text(json_str.into()),
-                    write_timeout,
-                )
-                .await
-                {
-                    error!("Failed to send message: {}", e);
-                    break;
+        let result = self.run(&mut message_rx).await;
+        *self.message_rx.write().await = Some(message_rx);
+        result
+    }
+
+    async fn run(&self, message_rx: &mut mpsc::Receiver<Envelope<String>>) -> PjsResult<()> {
+        let mut failed_attempts = 0;
+        loop {
+            info!("Connecting to WebSocket server: {}", self.url);
+            let reason = match connect_async(self.url.as_str()).await {
+                Ok((ws_stream, _)) => {
+                    failed_attempts = 0;
+                    let reason = self.run_connection(ws_stream, message_rx).await;
+                    info!("WebSocket connection closed: {}", reason);
+                    let _ = self.events.send(ConnectionEvent::Disconnected {
+                        reason: reason.clone(),
+                    });
+                    if self.reconnect.is_none() {
+                        return Ok(());
+                    }
+                    reason
+                }
+                Err(e) => {
+                    failed_attempts += 1;
+                    e.to_string()
                 }
+            };
+
+            let Some(policy) = &self.reconnect else {
+                return Err(PjsError::ConnectionFailed(reason));
+            };
+            if policy
+                .max_attempts
+                .is_some_and(|max_attempts| failed_attempts >= max_attempts)
+            {
+                warn!("Giving up after {} connection attempts", failed_attempts);
+                let _ = self.events.send(ConnectionEvent::GaveUp {
+                    attempts: failed_attempts,
+                });
+                return Err(PjsError::ConnectionFailed(reason));
             }
+
+            let attempt = failed_attempts + 1;
+            let delay = policy.delay(attempt);
+            info!("Reconnecting in {:?} (attempt {})", delay, attempt);
+            let _ = self
+                .events
+                .send(ConnectionEvent::Reconnecting { attempt, delay });
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Resume every session, then relay outgoing messages and handle
+    /// incoming ones until the connection ends. Returns why it ended.
+    async fn run_connection(
+        &self,
+        ws_stream: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+        message_rx: &mut mpsc::Receiver<Envelope<String>>,
+    ) -> String {
+        info!("WebSocket connection established");
+        let (mut write, mut read) = ws_stream.split();
+        let connection = self.connections.fetch_add(1, Ordering::AcqRel) + 1;
+
+        // Resumes go out before anything queued while disconnected. A
+        // session requested during an outage has its `StreamInit` still
+        // queued: it is refused as unknown, then started by the
+        // `StreamInit` itself.
+        let resumes = self.resume_messages(connection).await;
+        let _ = self.events.send(ConnectionEvent::Connected {
+            resuming: resumes.len(),
         });
+        for json_str in resumes {
+            if let Err(e) = super::send_with_write_timeout(
+                &mut write,
+                Message::Text(json_str.into()),
+                self.write_timeout,
+            )
+            .await
+            {
+                return format!("failed to send resume request: {e}");
+            }
+        }
 
-        // Handle incoming messages
-        let sessions = self.sessions.clone();
-        let message_tx = self.message_tx.clone();
-        let receive_task = tokio::spawn(async move {
-            while let Some(msg) = read.next().await {
-                match msg {
-                    Ok(Message::Text(text)) => match serde_json::from_str::<WsMessage>(&text) {
+        loop {
+            tokio::select! {
+                envelope = message_rx.recv() => {
+                    // Messages are already serialized at the point they
+                    // were queued (see `request_stream` and
+                    // `handle_incoming_message`), so the byte-budget
+                    // accounting there matches the bytes actually held in
+                    // memory here. `split` (rather than `into_inner`)
+                    // keeps the byte budget charged until the write
+                    // actually completes, not just until the item leaves
+                    // the channel.
+                    let Some(envelope) = envelope else {
+                        let _ = write.close().await;
+                        return "client dropped".to_string();
+                    };
+                    let (json_str, _budget_permit) = envelope.split();
+                    if let Err(e) = super::send_with_write_timeout(
+                        &mut write,
+                        Message::Text(json_str.into()),
+                        self.write_timeout,
+                    )
+                    .await
+                    {
+                        error!("Failed to send message: {}", e);
+                        return format!("failed to send message: {e}");
+                    }
+                }
+                msg = read.next() => match msg {
+                    Some(Ok(Message::Text(text))) => match serde_json::from_str::<WsMessage>(&text) {
                         Ok(ws_message) => {
+                            self.publish_session_expiry(&ws_message).await;
                             if let Err(e) = Self::handle_incoming_message(
-                                sessions.clone(),
-                                message_tx.clone(),
+                                self.sessions.clone(),
+                                self.message_tx.clone(),
                                 ws_message,
+                                self.integrity_key.as_ref(),
                             )
                             .await
                             {
@@ -177,47 +452,81 @@ impl PjsWebSocketClient {
                             warn!("Failed to parse incoming message: {}", e);
                         }
                     },
-                    Ok(Message::Binary(data)) => {
+                    Some(Ok(Message::Binary(data))) => {
                         debug!("Received binary data: {} bytes", data.len());
                     }
-                    Ok(Message::Ping(_data)) => {
+                    Some(Ok(Message::Ping(_data))) => {
                         debug!("Received ping, sending pong");
                         // Pong is handled automatically by tungstenite
                     }
-                    Ok(Message::Pong(_)) => {
+                    Some(Ok(Message::Pong(_))) => {
                         debug!("Received pong");
                     }
-                    Ok(Message::Close(_)) => {
-                        info!("Server closed connection");
-                        break;
+                    Some(Ok(Message::Close(_))) | None => {
+                        return "server closed connection".to_string();
                     }
-                    Ok(Message::Frame(_)) => {
+                    Some(Ok(Message::Frame(_))) => {
                         // Raw frame - usually handled internally by tungstenite
                         debug!("Received raw frame");
                     }
-                    Err(e) => {
+                    Some(Err(e)) => {
                         error!("WebSocket error: {}", e);
-                        break;
+                        return e.to_string();
                     }
-                }
-            }
-        });
-
-        // Wait for either task to complete
-        tokio::select! {
-            _ = send_task => {
-                debug!("Send task completed");
-            }
-            _ = receive_task => {
-                debug!("Receive task completed");
+                },
             }
         }
+    }
 
-        info!("WebSocket connection closed");
-        Ok(())
+    /// A serialized [`WsMessage::Resume`], continuing after the last frame
+    /// received, for every session requested on an earlier connection than
+    /// `connection`.
+    async fn resume_messages(&self, connection: u64) -> Vec<String> {
+        let mut sessions = self.sessions.write().await;
+        sessions
+            .values_mut()
+            .filter(|session| connection > session.requested_after + 1)
+            .filter_map(|session| {
+                session.resend_requested_from = None;
+                let message = WsMessage::Resume {
+                    session_id: session.id.clone(),
+                    last_frame_id: session.next_frame_id.checked_sub(1),
+                };
+                serde_json::to_string(&message)
+                    .inspect_err(|e| warn!("Failed to serialize resume request: {}", e))
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Publish [`ConnectionEvent::SessionExpired`] if `message` refuses to
+    /// resume a session that had received data. One that had not may just
+    /// have its `StreamInit` queued behind the resume.
+    async fn publish_session_expiry(&self, message: &WsMessage) {
+        let WsMessage::Error {
+            session_id: Some(session_id),
+            code: 404,
+            ..
+        } = message
+        else {
+            return;
+        };
+        let sessions = self.sessions.read().await;
+        if sessions.get(session_id).is_some_and(|session| {
+            session.next_frame_id > 0 || session.version > 0 || session.integrity.is_some()
+        }) {
+            warn!("Session {} expired before it could be resumed", session_id);
+            let _ = self.events.send(ConnectionEvent::SessionExpired {
+                session_id: session_id.clone(),
+            });
+        }
     }
 
     /// Request stream initialization
+    ///
+    /// Any number of streams can share the connection; the returned session
+    /// id tells them apart, and `options.stream_priority` breaks ties when
+    /// the server interleaves their frames.
     pub async fn request_stream(
         &self,
         data: Value,
@@ -225,6 +534,7 @@ impl PjsWebSocketClient {
     ) -> PjsResult<String> {
         let session_id = uuid::Uuid::new_v4().to_string();
         let options = options.unwrap_or_default();
+        let delivery = options.delivery;
 
         let message = WsMessage::StreamInit {
             session_id: session_id.clone(),
@@ -249,6 +559,13 @@ impl PjsWebSocketClient {
             received_frames: HashMap::new(),
             reconstructed_data: serde_json::json!({}),
             is_complete: false,
+            integrity: None,
+            version: 0,
+            requested_after: self.connections.load(Ordering::Acquire),
+            delivery,
+            next_frame_id: 0,
+            pending_frames: BTreeMap::new(),
+            resend_requested_from: None,
         };
 
         self.sessions
@@ -260,6 +577,60 @@ impl PjsWebSocketClient {
         Ok(session_id)
     }
 
+    /// Ask the server to send every unacknowledged frame of an
+    /// at-least-once session again from `from_frame_id` on.
+    ///
+    /// The client already does this when it notices a gap; call it to
+    /// recover from losses it cannot see, such as the end of a stream
+    /// that has gone quiet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request cannot be queued.
+    pub async fn request_resend(&self, session_id: &str, from_frame_id: u32) -> PjsResult<()> {
+        let message = WsMessage::Resend {
+            session_id: session_id.to_string(),
+            from_frame_id,
+        };
+        let json_str = serde_json::to_string(&message).map_err(|e| {
+            PjsError::ClientError(format!("Failed to serialize resend request: {e}"))
+        })?;
+        let len = json_str.len();
+
+        self.message_tx.send(json_str, len).await.map_err(|_| {
+            PjsError::ClientError(
+                "Failed to send resend request: outgoing channel closed".to_string(),
+            )
+        })
+    }
+
+    /// Cancel one of the client's streams: the server stops sending it,
+    /// and the client forgets the session and ignores any of its frames
+    /// still in flight. Other streams on the connection are unaffected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request cannot be queued.
+    pub async fn cancel_stream(&self, session_id: &str) -> PjsResult<()> {
+        let message = WsMessage::CancelStream {
+            session_id: session_id.to_string(),
+        };
+        let json_str = serde_json::to_string(&message).map_err(|e| {
+            PjsError::ClientError(format!("Failed to serialize stream cancellation: {e}"))
+        })?;
+        let len = json_str.len();
+
+        self.message_tx.send(json_str, len).await.map_err(|_| {
+            PjsError::ClientError(
+                "Failed to send stream cancellation: outgoing channel closed".to_string(),
+            )
+        })?;
+        self.sessions.write().await.remove(session_id);
+
+        info!("Cancelled stream: {}", session_id);
+        Ok(())
+    }
+
     /// Get current reconstructed data for session
     pub async fn get_current_data(&self, session_id: &str) -> PjsResult<Option<Value>> {
         let sessions = self.sessions.read().await;
@@ -277,6 +648,28 @@ impl PjsWebSocketClient {
             .unwrap_or(false)
     }
 
+    /// Outcome of verifying the session's completion digest against the
+    /// reconstructed document.
+    ///
+    /// `None` until the server's `StreamComplete` message has arrived (or
+    /// for an unknown session).
+    pub async fn integrity(&self, session_id: &str) -> Option<Result<(), IntegrityError>> {
+        let sessions = self.sessions.read().await;
+        sessions
+            .get(session_id)
+            .and_then(|session| session.integrity.clone())
+    }
+
+    /// Latest document version applied from the server's live updates:
+    /// `0` until the first `StreamPatch` arrives (or for an unknown session).
+    pub async fn document_version(&self, session_id: &str) -> u64 {
+        let sessions = self.sessions.read().await;
+        sessions
+            .get(session_id)
+            .map(|session| session.version)
+            .unwrap_or(0)
+    }
+
     /// Get stream statistics
     pub async fn get_stream_stats(&self, session_id: &str) -> Option<StreamStats> {
         let sessions = self.sessions.read().await;
@@ -344,6 +737,7 @@ impl PjsWebSocketClient {
         sessions: Arc<RwLock<HashMap<String, ClientStreamSession>>>,
         message_tx: ByteBoundedSender<String>,
         message: WsMessage,
+        integrity_key: Option<&IntegrityKey>,
     ) -> PjsResult<()> {
         match message {
             WsMessage::StreamFrame {
@@ -356,6 +750,7 @@ impl PjsWebSocketClient {
                 debug!("Received frame {} for session {}", frame_id, session_id);
 
                 let processing_start = Instant::now();
+                let mut resend_from = None;
 
                 {
                     let mut sessions = sessions.write().await;
@@ -368,10 +763,24 @@ impl PjsWebSocketClient {
                         session.received_frames.insert(frame_id, frame);
 
                         // Apply frame to reconstructed data
-                        Self::apply_frame_to_data(&mut session.reconstructed_data, &payload)?;
-
-                        if is_complete {
-                            session.is_complete = true;
+                        match session.delivery {
+                            DeliveryMode::FireAndForget => {
+                                super::apply_frame_payload(
+                                    &mut session.reconstructed_data,
+                                    &payload,
+                                );
+                                session.next_frame_id =
+                                    session.next_frame_id.max(frame_id.saturating_add(1));
+                                if is_complete {
+                                    session.is_complete = true;
+                                }
+                            }
+                            DeliveryMode::AtLeastOnce => {
+                                resend_from =
+                                    session.apply_in_order(frame_id, payload, is_complete);
+                            }
+                        }
+                        if session.is_complete {
                             info!("Stream completed for session {}", session_id);
                         }
 
@@ -386,7 +795,7 @@ impl PjsWebSocketClient {
 
                 // Send acknowledgment
                 let ack_message = WsMessage::FrameAck {
-                    session_id,
+                    session_id: session_id.clone(),
                     frame_id,
                     processing_time_ms: processing_time.as_millis() as u64,
                 };
@@ -395,6 +804,47 @@ impl PjsWebSocketClient {
                 // can re-send or time out the frame); stalling the whole
                 // read loop is not. See `try_send_control_message`'s doc.
                 Self::try_send_control_message(&message_tx, &ack_message, "frame acknowledgment");
+
+                if let Some(from_frame_id) = resend_from {
+                    warn!(
+                        "Frames missing for session {}; requesting resend from {}",
+                        session_id, from_frame_id
+                    );
+                    let resend = WsMessage::Resend {
+                        session_id,
+                        from_frame_id,
+                    };
+                    Self::try_send_control_message(&message_tx, &resend, "resend request");
+                }
+            }
+            WsMessage::StreamPatch {
+                session_id,
+                version,
+                priority,
+                patches,
+            } => {
+                debug!(
+                    "Received {} priority-{} patches for session {} version {}",
+                    patches.len(),
+                    priority,
+                    session_id,
+                    version
+                );
+
+                let mut sessions = sessions.write().await;
+                if let Some(session) = sessions.get_mut(&session_id) {
+                    // The previous version's digest no longer describes the
+                    // document; the update's own `StreamComplete` follows.
+                    session.integrity = None;
+                    session.version = version;
+                    for patch in &patches {
+                        if let Err(e) =
+                            super::apply_frame_patch(&mut session.reconstructed_data, patch)
+                        {
+                            error!("Failed to apply patch for session {}: {}", session_id, e);
+                        }
+                    }
+                }
             }
             WsMessage::StreamComplete {
                 session_id,
@@ -404,6 +854,27 @@ impl PjsWebSocketClient {
 
                 let mut sessions = sessions.write().await;
                 if let Some(session) = sessions.get_mut(&session_id) {
+                    // An at-least-once stream ending before its last frame
+                    // has been applied lost frames on the way: ask for them,
+                    // and verify the completion the server resends after.
+                    if session.delivery == DeliveryMode::AtLeastOnce && !session.is_complete {
+                        let resend = WsMessage::Resend {
+                            session_id: session_id.clone(),
+                            from_frame_id: session.next_frame_id,
+                        };
+                        session.resend_requested_from = Some(session.next_frame_id);
+                        warn!(
+                            "Stream {} completed with frames missing; requesting resend from {}",
+                            session_id, session.next_frame_id
+                        );
+                        Self::try_send_control_message(&message_tx, &resend, "resend request");
+                        return Ok(());
+                    }
+                    let integrity = checksum.verify(&session.reconstructed_data, integrity_key);
+                    if let Err(e) = &integrity {
+                        error!("Stream {} failed integrity check: {}", session_id, e);
+                    }
+                    session.integrity = Some(integrity);
                     session.is_complete = true;
                 }
             }
@@ -411,10 +882,11 @@ impl PjsWebSocketClient {
                 session_id,
                 error,
                 code,
+                resume_from,
             } => {
                 error!(
-                    "Received error from server: session={:?}, error={}, code={}",
-                    session_id, error, code
+                    "Received error from server: session={:?}, error={}, code={}, resume_from={:?}",
+                    session_id, error, code, resume_from
                 );
             }
             WsMessage::Ping { timestamp } => {
@@ -433,21 +905,6 @@ impl PjsWebSocketClient {
         }
         Ok(())
     }
-
-    fn apply_frame_to_data(data: &mut Value, payload: &Value) -> PjsResult<()> {
-        // Simple merge strategy - in production, this would be more sophisticated
-        match (data.as_object_mut(), payload.as_object()) {
-            (Some(data_map), Some(payload_map)) => {
-                for (key, value) in payload_map {
-                    data_map.insert(key.clone(), value.clone());
-                }
-            }
-            _ => {
-                *data = payload.clone();
-            }
-        }
-        Ok(())
-    }
 }
 
 /// Stream statistics
@@ -487,6 +944,98 @@ mod tests {
         assert_eq!(client.write_timeout, Duration::from_secs(3));
     }
 
+    #[test]
+    fn test_reconnect_policy_backs_off_exponentially_with_bounded_jitter() {
+        let policy = ReconnectPolicy {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: 0.5,
+            max_attempts: None,
+        };
+
+        assert_eq!(policy.delay_with(1, 0.5), Duration::from_millis(100));
+        assert_eq!(policy.delay_with(3, 0.5), Duration::from_millis(400));
+        assert_eq!(policy.delay_with(30, 0.5), Duration::from_secs(1));
+        assert_eq!(policy.delay_with(3, 0.0), Duration::from_millis(200));
+        assert_eq!(policy.delay_with(3, 1.0), Duration::from_millis(600));
+        for attempt in 1..10 {
+            let delay = policy.delay(attempt);
+            assert!(delay >= policy.delay_with(attempt, 0.0));
+            assert!(delay <= policy.delay_with(attempt, 1.0));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_gives_up_after_max_attempts() {
+        let client = PjsWebSocketClient::new("ws://127.0.0.1:1/ws")
+            .unwrap()
+            .with_reconnect(ReconnectPolicy {
+                initial_delay: Duration::from_millis(1),
+                max_attempts: Some(2),
+                ..ReconnectPolicy::default()
+            });
+        let mut events = client.subscribe_connection_events();
+
+        assert!(matches!(
+            client.connect().await,
+            Err(PjsError::ConnectionFailed(_))
+        ));
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            ConnectionEvent::Reconnecting { attempt: 2, .. }
+        ));
+        assert_eq!(
+            events.recv().await.unwrap(),
+            ConnectionEvent::GaveUp { attempts: 2 }
+        );
+        assert!(
+            client.message_rx.read().await.is_some(),
+            "connect can be called again"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_continues_after_last_received_frame() {
+        let client = PjsWebSocketClient::new("ws://localhost:3001/ws").unwrap();
+        let streamed = client.request_stream(json!({}), None).await.unwrap();
+        let requested = client.request_stream(json!({}), None).await.unwrap();
+        for frame_id in 0..2 {
+            PjsWebSocketClient::handle_incoming_message(
+                client.sessions.clone(),
+                client.message_tx.clone(),
+                WsMessage::StreamFrame {
+                    session_id: streamed.clone(),
+                    frame_id,
+                    priority: 100,
+                    payload: json!({ "n": frame_id }),
+                    is_complete: false,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        // Their `StreamInit`s go out on the first connection: nothing to
+        // resume there.
+        assert!(client.resume_messages(1).await.is_empty());
+        let resumes: HashMap<_, _> = client
+            .resume_messages(2)
+            .await
+            .iter()
+            .map(|json_str| match serde_json::from_str(json_str).unwrap() {
+                WsMessage::Resume {
+                    session_id,
+                    last_frame_id,
+                } => (session_id, last_frame_id),
+                other => panic!("expected Resume, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(resumes[&streamed], Some(1));
+        assert_eq!(resumes[&requested], None);
+    }
+
     #[tokio::test]
     async fn test_stream_session() {
         let client = PjsWebSocketClient::new("ws://localhost:3001/ws").unwrap();
@@ -548,14 +1097,189 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_apply_frame_to_data() {
-        let mut data = json!({"existing": "value"});
-        let payload = json!({"new": "data", "existing": "updated"});
+    #[tokio::test]
+    async fn test_stream_complete_verifies_reconstructed_document() {
+        use crate::domain::value_objects::ContentDigest;
+
+        let client = PjsWebSocketClient::new("ws://localhost:3001/ws").unwrap();
+        let session_id = client.request_stream(json!({}), None).await.unwrap();
+        let payload = json!({"id": 1, "name": "test"});
+
+        PjsWebSocketClient::handle_incoming_message(
+            client.sessions.clone(),
+            client.message_tx.clone(),
+            WsMessage::StreamFrame {
+                session_id: session_id.clone(),
+                frame_id: 0,
+                priority: 100,
+                payload: payload.clone(),
+                is_complete: false,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(client.integrity(&session_id).await, None);
+
+        PjsWebSocketClient::handle_incoming_message(
+            client.sessions.clone(),
+            client.message_tx.clone(),
+            WsMessage::StreamComplete {
+                session_id: session_id.clone(),
+                checksum: ContentDigest::compute(&payload, None),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(client.is_stream_complete(&session_id).await);
+        assert_eq!(client.integrity(&session_id).await, Some(Ok(())));
+    }
 
-        PjsWebSocketClient::apply_frame_to_data(&mut data, &payload).unwrap();
+    #[tokio::test]
+    async fn test_at_least_once_frames_are_applied_in_order_and_gaps_requested() {
+        use crate::domain::value_objects::ContentDigest;
+
+        let client = PjsWebSocketClient::new("ws://localhost:3001/ws").unwrap();
+        let mut outgoing = client.message_rx.write().await.take().unwrap();
+        let options = StreamOptions {
+            delivery: DeliveryMode::AtLeastOnce,
+            ..StreamOptions::default()
+        };
+        let session_id = client
+            .request_stream(json!({}), Some(options))
+            .await
+            .unwrap();
+        let deliver = |message: WsMessage| {
+            PjsWebSocketClient::handle_incoming_message(
+                client.sessions.clone(),
+                client.message_tx.clone(),
+                message,
+                None,
+            )
+        };
+        let frame = |frame_id: u32, payload: Value| WsMessage::StreamFrame {
+            session_id: session_id.clone(),
+            frame_id,
+            priority: 100,
+            payload,
+            is_complete: frame_id == 2,
+        };
+        let document = json!({"a": 1, "b": 2, "c": 3});
+        let complete = WsMessage::StreamComplete {
+            session_id: session_id.clone(),
+            checksum: ContentDigest::compute(&document, None),
+        };
+
+        // Frame 1 is lost; the completion arrives before the resend.
+        deliver(frame(0, json!({"a": 1}))).await.unwrap();
+        deliver(frame(2, json!({"c": 3}))).await.unwrap();
+        deliver(frame(2, json!({"c": 3}))).await.unwrap();
+        deliver(complete.clone()).await.unwrap();
+        assert!(!client.is_stream_complete(&session_id).await);
+        assert_eq!(client.integrity(&session_id).await, None);
+
+        // The server's resend: every unacknowledged frame, then the completion.
+        deliver(frame(1, json!({"b": 2}))).await.unwrap();
+        deliver(complete).await.unwrap();
+        assert_eq!(
+            client.get_current_data(&session_id).await.unwrap(),
+            Some(document)
+        );
+        assert_eq!(client.integrity(&session_id).await, Some(Ok(())));
 
-        assert_eq!(data["existing"], "updated");
-        assert_eq!(data["new"], "data");
+        let mut resends = Vec::new();
+        while let Ok(envelope) = outgoing.try_recv() {
+            if let Ok(WsMessage::Resend { from_frame_id, .. }) =
+                serde_json::from_str(&envelope.into_inner())
+            {
+                resends.push(from_frame_id);
+            }
+        }
+        assert_eq!(
+            resends,
+            vec![1, 1],
+            "once for the gap, once for the early completion"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_complete_reports_digest_mismatch() {
+        use crate::domain::value_objects::ContentDigest;
+
+        let client = PjsWebSocketClient::new("ws://localhost:3001/ws").unwrap();
+        let session_id = client.request_stream(json!({}), None).await.unwrap();
+
+        PjsWebSocketClient::handle_incoming_message(
+            client.sessions.clone(),
+            client.message_tx.clone(),
+            WsMessage::StreamComplete {
+                session_id: session_id.clone(),
+                checksum: ContentDigest::compute(&json!({"tampered": true}), None),
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(client.is_stream_complete(&session_id).await);
+        assert!(matches!(
+            client.integrity(&session_id).await,
+            Some(Err(IntegrityError::Mismatch { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stream_patch_applies_live_update() {
+        use crate::domain::{JsonPath, entities::frame::FramePatch, value_objects::ContentDigest};
+
+        let client = PjsWebSocketClient::new("ws://localhost:3001/ws").unwrap();
+        let session_id = client.request_stream(json!({}), None).await.unwrap();
+        let deliver = |message| {
+            PjsWebSocketClient::handle_incoming_message(
+                client.sessions.clone(),
+                client.message_tx.clone(),
+                message,
+                None,
+            )
+        };
+
+        deliver(WsMessage::StreamFrame {
+            session_id: session_id.clone(),
+            frame_id: 0,
+            priority: 100,
+            payload: json!({"id": 1, "load": 0.5}),
+            is_complete: true,
+        })
+        .await
+        .unwrap();
+        deliver(WsMessage::StreamPatch {
+            session_id: session_id.clone(),
+            version: 1,
+            priority: 50,
+            patches: vec![FramePatch::set(
+                "$.load".parse::<JsonPath>().unwrap(),
+                json!(0.75).into(),
+            )],
+        })
+        .await
+        .unwrap();
+
+        let expected = json!({"id": 1, "load": 0.75});
+        assert_eq!(client.document_version(&session_id).await, 1);
+        assert_eq!(client.integrity(&session_id).await, None);
+        assert_eq!(
+            client.get_current_data(&session_id).await.unwrap(),
+            Some(expected.clone())
+        );
+
+        deliver(WsMessage::StreamComplete {
+            session_id: session_id.clone(),
+            checksum: ContentDigest::compute(&expected, None),
+        })
+        .await
+        .unwrap();
+        assert_eq!(client.integrity(&session_id).await, Some(Ok(())));
     }
 }
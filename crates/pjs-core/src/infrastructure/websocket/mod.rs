@@ -4,30 +4,42 @@
 //! and backpressure handling for optimal client performance.
 
 use crate::{
-    Error as PjsError, Result as PjsResult, StreamFrame, domain::Priority, security::RateLimitGuard,
+    Error as PjsError, Result as PjsResult, StreamFrame,
+    domain::{
+        Priority,
+        entities::{
+            frame::{FramePatch, PatchOperation},
+            stream::StreamConfig,
+        },
+        services::{FrameGenerator, PriorityHeuristicConfig, diff_documents},
+        value_objects::{ContentDigest, IntegrityKey, JsonData, JsonPath, PathSegment},
+    },
+    infrastructure::shutdown::{DrainSignal, ResumePoint, shutdown_message},
+    security::RateLimitGuard,
 };
 use futures::{Sink, SinkExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use sha2::{Digest, Sha256};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     future::Future,
-    sync::Arc,
+    sync::{Arc, OnceLock},
     time::{Duration, Instant},
 };
-use tokio::sync::{RwLock, broadcast};
+use tokio::sync::{Notify, RwLock, broadcast};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 #[cfg(feature = "websocket-client")]
 pub mod client;
+#[cfg(feature = "http-server")]
+mod scheduler;
 pub mod security;
 #[cfg(feature = "http-server")]
 pub mod server;
 
 #[cfg(feature = "websocket-client")]
-pub use client::{PjsWebSocketClient, StreamStats};
+pub use client::{ConnectionEvent, PjsWebSocketClient, ReconnectPolicy, StreamStats};
 pub use security::SecureWebSocketHandler;
 #[cfg(feature = "http-server")]
 pub use server::{AxumWebSocketTransport, create_websocket_router};
@@ -124,6 +136,10 @@ mod write_timeout_tests {
 }
 
 /// WebSocket message types for PJS streaming
+///
+/// A connection carries any number of concurrent streams, each identified
+/// by the `session_id` its `StreamInit` proposed; the server interleaves
+/// their frames by priority.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum WsMessage {
@@ -158,12 +174,55 @@ pub enum WsMessage {
         /// Time the client took to process the frame, in milliseconds.
         processing_time_ms: u64,
     },
-    /// Stream completion signal
+    /// Client request, after reconnecting, to continue a session whose
+    /// connection dropped: the server sends every frame after
+    /// `last_frame_id` again, or brings a live-updated session's document up
+    /// to date, then ends with `StreamComplete`.
+    Resume {
+        /// Identifier of the WebSocket session, as proposed in `StreamInit`.
+        session_id: String,
+        /// Last frame the client received, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_frame_id: Option<u32>,
+    },
+    /// Client request to stop one of the connection's streams. The server
+    /// stops sending its frames, drops those not yet sent and forgets the
+    /// session; the connection's other streams carry on.
+    CancelStream {
+        /// Identifier of the WebSocket session to cancel.
+        session_id: String,
+    },
+    /// Client request to send again every unacknowledged frame from
+    /// `from_frame_id` on, then the stream's final message if it has been
+    /// sent. Only honoured for [`DeliveryMode::AtLeastOnce`] sessions.
+    Resend {
+        /// Identifier of the WebSocket session.
+        session_id: String,
+        /// First frame the client is missing.
+        from_frame_id: u32,
+    },
+    /// Part of a live update: patches turning the session's previous
+    /// document version into `version`, one message per priority level,
+    /// highest first. The update ends with a `StreamComplete` carrying the
+    /// digest of the new version.
+    StreamPatch {
+        /// Identifier of the WebSocket session.
+        session_id: String,
+        /// Document version these patches produce.
+        version: u64,
+        /// Priority shared by every patch in this message.
+        priority: u8,
+        /// Patches to apply in order.
+        patches: Vec<FramePatch>,
+    },
+    /// Stream completion signal; sent again after every live update
     StreamComplete {
         /// Identifier of the WebSocket session.
         session_id: String,
-        /// SHA-256 checksum of the concatenated frame payloads.
-        checksum: String,
+        /// Digest of the document the stream's frames reconstruct to, i.e.
+        /// every `StreamFrame` payload folded in order with
+        /// [`apply_frame_payload`], then every `StreamPatch` applied.
+        checksum: ContentDigest,
     },
     /// Error message
     Error {
@@ -173,6 +232,11 @@ pub enum WsMessage {
         error: String,
         /// Numeric error code.
         code: u16,
+        /// For a stream cut short by server shutdown, the first `frame_id`
+        /// the client has not received; resume from there on another
+        /// instance. Absent for every other error.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        resume_from: Option<u32>,
     },
     /// Heartbeat/ping message
     Ping {
@@ -197,6 +261,13 @@ pub struct StreamOptions {
     pub compression: bool,
     /// Custom priority mapping
     pub priority_mapping: Option<HashMap<String, u8>>,
+    /// Delivery guarantee for the session's frames
+    #[serde(default)]
+    pub delivery: DeliveryMode,
+    /// Precedence among the streams sharing a connection when their next
+    /// frames have the same priority; higher goes first
+    #[serde(default)]
+    pub stream_priority: u8,
 }
 
 impl Default for StreamOptions {
@@ -206,10 +277,55 @@ impl Default for StreamOptions {
             client_fps: None,          // Auto-detect
             compression: true,
             priority_mapping: None,
+            delivery: DeliveryMode::default(),
+            stream_priority: 0,
         }
     }
 }
 
+/// Delivery guarantee for a session's `StreamFrame`s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryMode {
+    /// Frames are sent once; a dropped frame is lost
+    #[default]
+    FireAndForget,
+    /// The server keeps every frame until the client acknowledges it and
+    /// sends it again on [`WsMessage::Resend`]. At most
+    /// [`RETRANSMIT_BUFFER_CAPACITY`] frames are unacknowledged at a time:
+    /// the stream pauses until acknowledgements free room.
+    AtLeastOnce,
+}
+
+/// Unacknowledged frames an [`DeliveryMode::AtLeastOnce`] session holds
+/// before its stream waits for acknowledgements.
+///
+/// Bounds per-session retransmission memory to this many frames of at most
+/// `max_websocket_frame_size` each. 256 frames keeps a client acknowledging
+/// every frame well clear of the limit, so the stream only pauses for one
+/// that has stopped acknowledging.
+pub const RETRANSMIT_BUFFER_CAPACITY: usize = 256;
+
+/// How long a session outlives its connection, waiting for a
+/// [`WsMessage::Resume`], before it is removed.
+///
+/// Long enough for a client to back off and reconnect through a load
+/// balancer reset, short enough that abandoned sessions do not pile up.
+pub const SESSION_RESUME_WINDOW: Duration = Duration::from_secs(60);
+
+/// Frames sent to an at-least-once session that the client has not
+/// acknowledged yet
+#[derive(Debug, Default)]
+struct RetransmitBuffer {
+    /// Unacknowledged `StreamFrame` messages by `frame_id`
+    frames: BTreeMap<u32, WsMessage>,
+    /// The stream's last message (`StreamComplete`, or the shutdown
+    /// `Error`), once sent
+    final_message: Option<WsMessage>,
+    /// Woken when an acknowledgement frees room in `frames`
+    acked: Arc<Notify>,
+}
+
 /// WebSocket streaming session state.
 ///
 /// This type is intentionally distinct from the domain-layer
@@ -248,6 +364,14 @@ pub struct WebSocketStreamSession {
     /// this session-lifecycle detail out of the public struct-literal
     /// surface.
     stream_task: Option<tokio::task::AbortHandle>,
+    /// Latest document version and its number, for diffing live updates.
+    document: Value,
+    version: u64,
+    /// Frames awaiting acknowledgement, for [`DeliveryMode::AtLeastOnce`]
+    /// sessions only.
+    retransmit: Option<RetransmitBuffer>,
+    /// When the session's connection dropped, while it waits to be resumed.
+    detached_at: Option<Instant>,
 }
 
 /// Client performance metrics for adaptive streaming
@@ -366,10 +490,26 @@ pub trait WebSocketTransport: Send + Sync {
     fn close_stream(&self, session_id: &str) -> Self::CloseStreamFuture<'_>;
 }
 
+/// Controller state a streaming task holds on to
+#[derive(Clone)]
+struct StreamContext {
+    frame_tx: broadcast::Sender<(String, WsMessage)>,
+    sessions: Arc<RwLock<HashMap<String, WebSocketStreamSession>>>,
+    drain: Option<DrainSignal>,
+    integrity_key: Option<IntegrityKey>,
+}
+
 /// Adaptive streaming controller
 pub struct AdaptiveStreamController {
     sessions: Arc<RwLock<HashMap<String, WebSocketStreamSession>>>,
     frame_tx: broadcast::Sender<(String, WsMessage)>,
+    /// Set once by `AxumWebSocketTransport::with_drain_signal`; a
+    /// `OnceLock` because the controller is already shared (the session
+    /// sweep holds a `Weak` to it) by the time the transport is configured.
+    drain: OnceLock<DrainSignal>,
+    /// Set once by `AxumWebSocketTransport::with_integrity_key`, for the
+    /// same reason as `drain`.
+    integrity_key: OnceLock<IntegrityKey>,
 }
 
 impl AdaptiveStreamController {
@@ -380,17 +520,72 @@ impl AdaptiveStreamController {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             frame_tx,
+            drain: OnceLock::new(),
+            integrity_key: OnceLock::new(),
+        }
+    }
+
+    /// Attach a drain signal; only the first call has any effect.
+    pub(crate) fn set_drain_signal(&self, signal: DrainSignal) {
+        if self.drain.set(signal).is_err() {
+            warn!("drain signal already set on stream controller; ignoring");
+        }
+    }
+
+    /// Sign completion digests with `key`; only the first call has any
+    /// effect.
+    pub(crate) fn set_integrity_key(&self, key: IntegrityKey) {
+        if self.integrity_key.set(key).is_err() {
+            warn!("integrity key already set on stream controller; ignoring");
         }
     }
 
     /// Create new streaming session
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the controller's drain signal reports that the
+    /// server is shutting down.
     pub async fn create_session(&self, data: Value, options: StreamOptions) -> PjsResult<String> {
-        let session_id = Uuid::new_v4().to_string();
-        let plan = vec![StreamFrame {
-            data: data.clone(),
-            priority: Priority::HIGH,
-            metadata: std::collections::HashMap::new(),
-        }]; // Simplified for now
+        self.insert_session(Uuid::new_v4().to_string(), data, options)
+            .await
+    }
+
+    /// Create a streaming session under a client-chosen id, so that the
+    /// client knows which session to [resume](Self::resume_session) after
+    /// reconnecting.
+    ///
+    /// The id must be a UUID. Like any session id, it is all a client needs
+    /// to resume the session.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PjsError::InvalidSession`] if `session_id` is not a UUID
+    /// or is already in use, and an error if the server is shutting down.
+    pub async fn create_session_with_id(
+        &self,
+        session_id: &str,
+        data: Value,
+        options: StreamOptions,
+    ) -> PjsResult<String> {
+        let session_id = Uuid::parse_str(session_id)
+            .map_err(|_| PjsError::InvalidSession(session_id.to_string()))?;
+        self.insert_session(session_id.to_string(), data, options)
+            .await
+    }
+
+    async fn insert_session(
+        &self,
+        session_id: String,
+        data: Value,
+        options: StreamOptions,
+    ) -> PjsResult<String> {
+        if self.drain.get().is_some_and(DrainSignal::is_draining) {
+            return Err(PjsError::other("server is shutting down"));
+        }
+        let retransmit =
+            (options.delivery == DeliveryMode::AtLeastOnce).then(RetransmitBuffer::default);
+        let plan = session_plan(&data, &options)?;
 
         let session = WebSocketStreamSession {
             id: session_id.clone(),
@@ -402,12 +597,20 @@ impl AdaptiveStreamController {
             client_metrics: ClientMetrics::default(),
             rate_limit_guard: None, // Will be set when connection is established
             stream_task: None,      // Set when streaming starts
+            document: data,
+            version: 0,
+            retransmit,
+            detached_at: None,
         };
 
-        self.sessions
-            .write()
-            .await
-            .insert(session_id.clone(), session);
+        match self.sessions.write().await.entry(session_id.clone()) {
+            std::collections::hash_map::Entry::Occupied(_) => {
+                return Err(PjsError::InvalidSession(session_id));
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(session);
+            }
+        }
 
         info!("Created streaming session: {}", session_id);
         Ok(session_id)
@@ -419,17 +622,151 @@ impl AdaptiveStreamController {
         let session = sessions
             .get_mut(session_id)
             .ok_or_else(|| PjsError::InvalidSession(session_id.to_string()))?;
+        self.spawn_stream(session, 0);
+        Ok(())
+    }
+
+    /// Continue a session after its client reconnected.
+    ///
+    /// Sends every frame after `last_frame_id` (all of them for `None`) and
+    /// the final message again; a session that has received live updates
+    /// instead gets its current document as a single root
+    /// [`WsMessage::StreamPatch`], followed by its `StreamComplete`. Any
+    /// stream still running for the session, e.g. towards a connection that
+    /// has not noticed it is dead, is stopped first.
+    ///
+    /// An unknown or expired session is answered with a
+    /// [`WsMessage::Error`] with code 404, so the client can start over.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PjsError::InvalidSession`] for an unknown session, and an
+    /// error if the server is shutting down.
+    pub async fn resume_session(
+        &self,
+        session_id: &str,
+        last_frame_id: Option<u32>,
+    ) -> PjsResult<()> {
+        if self.drain.get().is_some_and(DrainSignal::is_draining) {
+            return Err(PjsError::other("server is shutting down"));
+        }
+
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(session_id) else {
+            let _ = self.frame_tx.send((
+                session_id.to_string(),
+                WsMessage::Error {
+                    session_id: Some(session_id.to_string()),
+                    error: "unknown or expired session; start a new stream".to_string(),
+                    code: 404,
+                    resume_from: None,
+                },
+            ));
+            return Err(PjsError::InvalidSession(session_id.to_string()));
+        };
+        session.detached_at = None;
+        if let Some(buffer) = &mut session.retransmit {
+            buffer.frames.clear();
+            buffer.final_message = None;
+        }
+
+        if session.version == 0 {
+            let first_frame = last_frame_id.map_or(0, |id| id as usize + 1);
+            self.spawn_stream(session, first_frame.min(session.plan.len()));
+        } else {
+            if let Some(task) = &session.stream_task {
+                task.abort();
+            }
+            for message in [
+                WsMessage::StreamPatch {
+                    session_id: session_id.to_string(),
+                    version: session.version,
+                    priority: Priority::CRITICAL.value(),
+                    patches: vec![FramePatch::set(
+                        JsonPath::root(),
+                        JsonData::from(session.document.clone()),
+                    )],
+                },
+                WsMessage::StreamComplete {
+                    session_id: session_id.to_string(),
+                    checksum: ContentDigest::compute(&session.document, self.integrity_key.get()),
+                },
+            ] {
+                let _ = self.frame_tx.send((session_id.to_string(), message));
+            }
+        }
+
+        info!(
+            "Resumed streaming session {} after frame {:?}",
+            session_id, last_frame_id
+        );
+        Ok(())
+    }
+
+    /// The session's [`StreamOptions::stream_priority`], or `None` for an
+    /// unknown session.
+    #[cfg(feature = "http-server")]
+    pub(crate) async fn stream_priority(&self, session_id: &str) -> Option<u8> {
+        self.sessions
+            .read()
+            .await
+            .get(session_id)
+            .map(|session| session.options.stream_priority)
+    }
+
+    /// Stop streaming to a session whose connection dropped, keeping it for
+    /// [`SESSION_RESUME_WINDOW`] so the client can
+    /// [resume](Self::resume_session) it; it is removed if it has not been
+    /// resumed by then. Returns `false` for an unknown session.
+    pub async fn detach_session(&self, session_id: &str) -> bool {
+        let mut sessions = self.sessions.write().await;
+        let Some(session) = sessions.get_mut(session_id) else {
+            return false;
+        };
+        if let Some(task) = &session.stream_task {
+            task.abort();
+        }
+        let detached_at = Instant::now();
+        session.detached_at = Some(detached_at);
+
+        let sessions = Arc::clone(&self.sessions);
+        let expired_id = session_id.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(SESSION_RESUME_WINDOW).await;
+            let mut sessions = sessions.write().await;
+            if sessions
+                .get(&expired_id)
+                .is_some_and(|session| session.detached_at == Some(detached_at))
+            {
+                sessions.remove(&expired_id);
+                info!("Removed unresumed streaming session: {}", expired_id);
+            }
+        });
+
+        debug!("Detached streaming session: {}", session_id);
+        true
+    }
 
-        // Start streaming task
-        let session_id = session_id.to_string();
-        let frame_tx = self.frame_tx.clone();
+    /// Spawn the task streaming `session`'s plan from `first_frame` on,
+    /// replacing any task already running for it.
+    fn spawn_stream(&self, session: &mut WebSocketStreamSession, first_frame: usize) {
+        let session_id = session.id.clone();
         let plan = session.plan.clone();
+        let acked = session
+            .retransmit
+            .as_ref()
+            .map(|buffer| Arc::clone(&buffer.acked));
+        let context = StreamContext {
+            frame_tx: self.frame_tx.clone(),
+            sessions: self.sessions.clone(),
+            drain: self.drain.get().cloned(),
+            integrity_key: self.integrity_key.get().cloned(),
+        };
 
         let task_session_id = session_id.clone();
-        let sessions_for_task = self.sessions.clone();
         let handle = tokio::spawn(async move {
             if let Err(e) =
-                Self::stream_frames(task_session_id, plan, frame_tx, sessions_for_task).await
+                Self::stream_frames(context, task_session_id, plan, first_frame, acked).await
             {
                 error!("Error streaming frames: {}", e);
             }
@@ -455,23 +792,82 @@ impl AdaptiveStreamController {
                 Err(_) => {} // task was aborted — expected on session teardown
             }
         });
-
-        Ok(())
     }
 
+    /// Send `plan` from `first_frame` on as `StreamFrame` messages, then
+    /// `StreamComplete` carrying the digest of the document the whole plan
+    /// reconstructs to, signed with the integrity key if one is set.
+    ///
+    /// With a drain signal, the task counts as an in-flight stream until it
+    /// returns (or is aborted). Once draining starts only critical frames are
+    /// sent; if the drain deadline passes first, or any frame was skipped,
+    /// the stream ends with an `Error` carrying `resume_from` instead of
+    /// `StreamComplete`.
+    ///
+    /// `acked` is set for [`DeliveryMode::AtLeastOnce`] sessions: every
+    /// message is then also kept in the session's retransmit buffer, and
+    /// the task waits on `acked` while that buffer is full.
     async fn stream_frames(
+        context: StreamContext,
         session_id: String,
         plan: Vec<StreamFrame>, // Simplified for now
-        frame_tx: broadcast::Sender<(String, WsMessage)>,
-        sessions: Arc<RwLock<HashMap<String, WebSocketStreamSession>>>,
+        first_frame: usize,
+        acked: Option<Arc<Notify>>,
     ) -> Result<(), PjsError> {
-        let mut frames_data = Vec::new();
+        let StreamContext {
+            frame_tx,
+            sessions,
+            drain,
+            integrity_key,
+        } = context;
+        let _guard = drain.as_ref().map(DrainSignal::track_stream);
+        let deadline = drain.as_ref().map(DrainSignal::deadline_reached);
+        let deadline = async move {
+            match deadline {
+                Some(deadline) => deadline.await,
+                None => std::future::pending().await,
+            }
+        };
+        futures::pin_mut!(deadline);
+        let mut resume = ResumePoint::new(first_frame.checked_sub(1).map(|last| last as u64));
+        let mut cut = false;
+        let mut document = Value::Object(serde_json::Map::new());
+        for frame in &plan[..first_frame] {
+            apply_frame_payload(&mut document, &frame.data);
+        }
+
+        'frames: for (frame_id, frame) in plan.iter().enumerate().skip(first_frame) {
+            if drain.as_ref().is_some_and(DrainSignal::is_draining) && !frame.priority.is_critical()
+            {
+                resume.skipped();
+                continue;
+            }
+
+            if let Some(acked) = &acked {
+                loop {
+                    // Registered before checking, so an ack in between is not missed.
+                    let notified = acked.notified();
+                    let full = sessions
+                        .read()
+                        .await
+                        .get(&session_id)
+                        .and_then(|session| session.retransmit.as_ref())
+                        .is_some_and(|buffer| buffer.frames.len() >= RETRANSMIT_BUFFER_CAPACITY);
+                    if !full {
+                        break;
+                    }
+                    tokio::select! {
+                        biased;
+                        () = &mut deadline => {
+                            cut = true;
+                            break 'frames;
+                        }
+                        () = notified => {}
+                    }
+                }
+            }
 
-        for (frame_id, frame) in plan.iter().enumerate() {
-            // Collect frame payload for checksum calculation
-            let payload_bytes =
-                serde_json::to_vec(&frame.data).map_err(|e| PjsError::Other(e.to_string()))?;
-            frames_data.push(payload_bytes);
+            apply_frame_payload(&mut document, &frame.data);
 
             let ws_message = WsMessage::StreamFrame {
                 session_id: session_id.clone(),
@@ -480,11 +876,18 @@ impl AdaptiveStreamController {
                 payload: frame.data.clone(),
                 is_complete: frame_id == (plan.len() - 1),
             };
+            if acked.is_some() {
+                Self::retain_for_retransmit(&sessions, &session_id, |buffer| {
+                    buffer.frames.insert(frame_id as u32, ws_message.clone());
+                })
+                .await;
+            }
 
             if let Err(e) = frame_tx.send((session_id.clone(), ws_message)) {
                 error!("Failed to send frame {}: {}", frame_id, e);
                 break;
             }
+            resume.delivered(frame_id as u64);
 
             let delay = sessions
                 .read()
@@ -492,19 +895,139 @@ impl AdaptiveStreamController {
                 .get(&session_id)
                 .map(|session| session.client_metrics.recommended_frame_delay())
                 .unwrap_or(Duration::from_millis(10));
-            tokio::time::sleep(delay).await;
+            tokio::select! {
+                biased;
+                () = &mut deadline => {
+                    cut = frame_id + 1 < plan.len();
+                    break;
+                }
+                () = tokio::time::sleep(delay) => {}
+            }
         }
 
-        // Send completion message with calculated checksum
-        let complete_message = WsMessage::StreamComplete {
-            session_id: session_id.clone(),
-            checksum: calculate_stream_checksum(&frames_data),
+        let final_message = if cut || resume.has_gap() {
+            let resume_from = resume.resume_from();
+            WsMessage::Error {
+                session_id: Some(session_id.clone()),
+                error: shutdown_message(resume_from),
+                code: 503,
+                resume_from: Some(u32::try_from(resume_from).unwrap_or(u32::MAX)),
+            }
+        } else {
+            WsMessage::StreamComplete {
+                session_id: session_id.clone(),
+                checksum: ContentDigest::compute(&document, integrity_key.as_ref()),
+            }
         };
 
-        let _ = frame_tx.send((session_id, complete_message));
+        if acked.is_some() {
+            Self::retain_for_retransmit(&sessions, &session_id, |buffer| {
+                buffer.final_message = Some(final_message.clone());
+            })
+            .await;
+        }
+        let _ = frame_tx.send((session_id, final_message));
         Ok(())
     }
 
+    /// Run `retain` on the session's retransmit buffer, if the session
+    /// still exists.
+    async fn retain_for_retransmit(
+        sessions: &RwLock<HashMap<String, WebSocketStreamSession>>,
+        session_id: &str,
+        retain: impl FnOnce(&mut RetransmitBuffer),
+    ) {
+        if let Some(buffer) = sessions
+            .write()
+            .await
+            .get_mut(session_id)
+            .and_then(|session| session.retransmit.as_mut())
+        {
+            retain(buffer);
+        }
+    }
+
+    /// Push a new version of a session's document to its subscribers.
+    ///
+    /// Diffs `data` against the previous version and broadcasts the changes
+    /// as [`WsMessage::StreamPatch`] messages, one per priority level with
+    /// critical changes first, followed by a [`WsMessage::StreamComplete`]
+    /// carrying the new version's digest. Sessions stay open for updates
+    /// until removed. Returns the new version number; an unchanged document
+    /// still gets a new version, with only the `StreamComplete`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PjsError::InvalidSession`] for an unknown session, and an
+    /// error if the server is shutting down or the session's initial
+    /// document has not been fully sent yet.
+    pub async fn update_session_data(&self, session_id: &str, data: Value) -> PjsResult<u64> {
+        if self.drain.get().is_some_and(DrainSignal::is_draining) {
+            return Err(PjsError::other("server is shutting down"));
+        }
+
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| PjsError::InvalidSession(session_id.to_string()))?;
+        if !session
+            .stream_task
+            .as_ref()
+            .is_some_and(tokio::task::AbortHandle::is_finished)
+        {
+            return Err(PjsError::other(
+                "session's initial document is still streaming",
+            ));
+        }
+
+        let mut config = PriorityHeuristicConfig::default();
+        if let Some(mapping) = &session.options.priority_mapping {
+            config.overrides = mapping
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), Priority::new(*value).ok()?)))
+                .collect();
+        }
+        let patches = diff_documents(
+            &JsonData::from(session.document.clone()),
+            &JsonData::from(data.clone()),
+            &config,
+        );
+
+        session.document = data;
+        session.version += 1;
+        let version = session.version;
+        let checksum = ContentDigest::compute(&session.document, self.integrity_key.get());
+
+        // Sent while still holding the session lock, so concurrent updates
+        // reach subscribers in version order.
+        let mut patches = patches.into_iter().peekable();
+        while let Some((patch, priority)) = patches.next() {
+            let mut group = vec![patch];
+            while let Some((patch, _)) = patches.next_if(|(_, next)| *next == priority) {
+                group.push(patch);
+            }
+            let _ = self.frame_tx.send((
+                session_id.to_string(),
+                WsMessage::StreamPatch {
+                    session_id: session_id.to_string(),
+                    version,
+                    priority: priority.value(),
+                    patches: group,
+                },
+            ));
+        }
+        let _ = self.frame_tx.send((
+            session_id.to_string(),
+            WsMessage::StreamComplete {
+                session_id: session_id.to_string(),
+                checksum,
+            },
+        ));
+
+        debug!("Session {} updated to version {}", session_id, version);
+        Ok(version)
+    }
+
     /// Handle frame acknowledgment
     pub async fn handle_frame_ack(
         &self,
@@ -521,6 +1044,11 @@ impl AdaptiveStreamController {
         session
             .client_metrics
             .update_processing_time(processing_time_ms);
+        if let Some(buffer) = &mut session.retransmit
+            && buffer.frames.remove(&frame_id).is_some()
+        {
+            buffer.acked.notify_waiters();
+        }
 
         debug!(
             "Frame {} acknowledged for session {} (processing: {}ms, avg: {:.1}ms)",
@@ -540,6 +1068,45 @@ impl AdaptiveStreamController {
         Ok(())
     }
 
+    /// Handle a [`WsMessage::Resend`] request.
+    ///
+    /// Broadcasts again every unacknowledged frame from `from_frame_id` on,
+    /// in order, followed by the stream's final message once it has been
+    /// sent. Returns how many messages were resent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PjsError::InvalidSession`] for an unknown session, and an
+    /// error if the session does not use [`DeliveryMode::AtLeastOnce`].
+    pub async fn handle_resend(&self, session_id: &str, from_frame_id: u32) -> PjsResult<usize> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| PjsError::InvalidSession(session_id.to_string()))?;
+        let buffer = session.retransmit.as_ref().ok_or_else(|| {
+            PjsError::other("session does not use at-least-once delivery; frames are not kept")
+        })?;
+
+        let mut resent = 0;
+        for message in buffer
+            .frames
+            .range(from_frame_id..)
+            .map(|(_, message)| message)
+            .chain(&buffer.final_message)
+        {
+            let _ = self
+                .frame_tx
+                .send((session_id.to_string(), message.clone()));
+            resent += 1;
+        }
+
+        debug!(
+            "Resent {} messages from frame {} for session {}",
+            resent, from_frame_id, session_id
+        );
+        Ok(resent)
+    }
+
     /// Get subscriber for frame events
     pub fn subscribe_frames(&self) -> broadcast::Receiver<(String, WsMessage)> {
         self.frame_tx.subscribe()
@@ -634,21 +1201,177 @@ impl Default for AdaptiveStreamController {
     }
 }
 
-/// Calculate SHA-256 checksum for stream completion verification
-fn calculate_stream_checksum(frames_data: &[Vec<u8>]) -> String {
-    let mut hasher = Sha256::new();
+/// The `StreamFrame`s a new session sends for `data`: the skeleton, then
+/// one frame per priority level, highest first, carrying the shared
+/// [`FrameGenerator`]'s patches for that level as a document fragment.
+///
+/// `options.priority_mapping` entries become
+/// [`StreamConfig::priority_rules`], keyed by field name or JSONPath query.
+fn session_plan(data: &Value, options: &StreamOptions) -> PjsResult<Vec<StreamFrame>> {
+    let priority_rules = options
+        .priority_mapping
+        .iter()
+        .flatten()
+        .map(|(key, priority)| {
+            Priority::new(*priority)
+                .map(|priority| (key.clone(), priority))
+                .map_err(|e| PjsError::other(e.to_string()))
+        })
+        .collect::<PjsResult<_>>()?;
+    let generator = StreamConfig {
+        priority_rules,
+        ..StreamConfig::default()
+    }
+    .frame_generator();
+
+    let document = JsonData::from(data.clone());
+    let mut plan = vec![StreamFrame {
+        data: serde_json::to_value(generator.skeleton(&document))?,
+        priority: Priority::CRITICAL,
+        metadata: HashMap::new(),
+    }];
+    let patches = generator.patches(&document, Priority::MIN);
+    for (priority, patches) in FrameGenerator::group_by_priority(patches, usize::MAX) {
+        let mut fragment = Value::Object(serde_json::Map::new());
+        for patch in patches {
+            let mut target = &mut fragment;
+            for segment in patch.path.segments() {
+                let PathSegment::Key(key) = segment else {
+                    return Err(PjsError::other(format!(
+                        "cannot stream patch at {}",
+                        patch.path
+                    )));
+                };
+                if !target.is_object() {
+                    *target = Value::Object(serde_json::Map::new());
+                }
+                target = target
+                    .as_object_mut()
+                    .expect("just made an object")
+                    .entry(key.clone())
+                    .or_insert(Value::Null);
+            }
+            *target = serde_json::to_value(&patch.value)?;
+        }
+        plan.push(StreamFrame {
+            data: fragment,
+            priority,
+            metadata: HashMap::new(),
+        });
+    }
+    Ok(plan)
+}
 
-    // Hash each frame's data
-    for frame_data in frames_data {
-        hasher.update(frame_data);
+/// Fold a `StreamFrame` payload into the document a client reconstructs:
+/// an object payload is merged member by member into an object document,
+/// recursively, and any other payload replaces the document. Documents
+/// start as `{}`.
+///
+/// Shared by the server, which digests the result for `StreamComplete`, and
+/// [`PjsWebSocketClient`](client::PjsWebSocketClient), which verifies it.
+pub(crate) fn apply_frame_payload(document: &mut Value, payload: &Value) {
+    match (document, payload) {
+        (Value::Object(document), Value::Object(payload)) => {
+            for (key, value) in payload {
+                match document.get_mut(key) {
+                    Some(existing) => apply_frame_payload(existing, value),
+                    None => {
+                        document.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (document, payload) => *document = payload.clone(),
     }
+}
 
-    // Hash frame count to ensure integrity
-    hasher.update((frames_data.len() as u64).to_le_bytes());
+/// Apply a [`WsMessage::StreamPatch`] patch to a reconstructed document.
+///
+/// Shared by the controller's tests and
+/// [`PjsWebSocketClient`](client::PjsWebSocketClient).
+///
+/// # Errors
+///
+/// Returns a description of the mismatch when the patch's path does not
+/// exist in `document` or points at a value of the wrong type.
+#[cfg_attr(not(feature = "websocket-client"), allow(dead_code))]
+pub(crate) fn apply_frame_patch(document: &mut Value, patch: &FramePatch) -> Result<(), String> {
+    let value = || serde_json::to_value(&patch.value).map_err(|e| e.to_string());
+    let missing = || format!("path {} does not exist", patch.path);
+
+    let Some((last, parents)) = patch.path.segments().split_last() else {
+        return match &patch.operation {
+            PatchOperation::Delete => {
+                *document = Value::Null;
+                Ok(())
+            }
+            PatchOperation::Append | PatchOperation::Merge => merge_or_append(document, patch),
+            _ => {
+                *document = value()?;
+                Ok(())
+            }
+        };
+    };
+
+    let mut parent = document;
+    for segment in parents {
+        parent = match (segment, parent) {
+            (PathSegment::Key(key), Value::Object(map)) => map.get_mut(key),
+            (PathSegment::Index(index), Value::Array(items)) => items.get_mut(*index),
+            _ => None,
+        }
+        .ok_or_else(missing)?;
+    }
 
-    let result = hasher.finalize();
-    let hex: String = result.iter().map(|byte| format!("{byte:02x}")).collect();
-    format!("sha256:{hex}")
+    match (&patch.operation, last, parent) {
+        (PatchOperation::Delete, PathSegment::Key(key), Value::Object(map)) => {
+            map.remove(key);
+        }
+        (PatchOperation::Delete, PathSegment::Index(index), Value::Array(items))
+            if *index < items.len() =>
+        {
+            items.remove(*index);
+        }
+        (
+            PatchOperation::Append | PatchOperation::Merge,
+            PathSegment::Key(key),
+            Value::Object(map),
+        ) => {
+            merge_or_append(map.get_mut(key).ok_or_else(missing)?, patch)?;
+        }
+        (
+            PatchOperation::Append | PatchOperation::Merge,
+            PathSegment::Index(index),
+            Value::Array(items),
+        ) => {
+            merge_or_append(items.get_mut(*index).ok_or_else(missing)?, patch)?;
+        }
+        (PatchOperation::Delete, ..) => return Err(missing()),
+        (_, PathSegment::Key(key), Value::Object(map)) => {
+            map.insert(key.clone(), value()?);
+        }
+        (_, PathSegment::Index(index), Value::Array(items)) if *index < items.len() => {
+            items[*index] = value()?;
+        }
+        _ => return Err(missing()),
+    }
+    Ok(())
+}
+
+/// `Append` extends an array target, `Merge` adds members to an object target.
+fn merge_or_append(target: &mut Value, patch: &FramePatch) -> Result<(), String> {
+    let value = serde_json::to_value(&patch.value).map_err(|e| e.to_string())?;
+    match (&patch.operation, target, value) {
+        (PatchOperation::Append, Value::Array(items), Value::Array(values)) => items.extend(values),
+        (PatchOperation::Merge, Value::Object(map), Value::Object(members)) => map.extend(members),
+        _ => {
+            return Err(format!(
+                "cannot apply {:?} at {}: type mismatch",
+                patch.operation, patch.path
+            ));
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -797,19 +1520,20 @@ mod tests {
             .await
             .unwrap();
 
-        {
+        let frame_count = {
             let mut sessions = controller.sessions.write().await;
-            sessions
-                .get_mut(&session_id)
-                .unwrap()
+            let session = sessions.get_mut(&session_id).unwrap();
+            session
                 .client_metrics
                 .update_processing_time(100_000_000_000);
-        }
+            session.plan.len() as u32
+        };
 
         let mut frames_rx = controller.subscribe_frames();
         controller.start_streaming(&session_id).await.unwrap();
 
-        let result = tokio::time::timeout(MAX_ADAPTIVE_FRAME_DELAY * 2, async {
+        // Every frame may wait out the clamped delay once; allow one frame of slack.
+        let result = tokio::time::timeout(MAX_ADAPTIVE_FRAME_DELAY * (frame_count + 1), async {
             loop {
                 match frames_rx
                     .recv()
@@ -825,40 +1549,491 @@ mod tests {
 
         assert!(
             result.is_ok(),
-            "stream must complete within 2x MAX_ADAPTIVE_FRAME_DELAY, not stall on malicious client metrics"
+            "stream must complete within one MAX_ADAPTIVE_FRAME_DELAY per frame, not stall on malicious client metrics"
+        );
+    }
+
+    fn plan(priorities: &[Priority]) -> Vec<StreamFrame> {
+        priorities
+            .iter()
+            .enumerate()
+            .map(|(i, &priority)| StreamFrame {
+                data: json!({ "frame": i }),
+                priority,
+                metadata: HashMap::new(),
+            })
+            .collect()
+    }
+
+    async fn run_stream(plan: Vec<StreamFrame>, drain: Option<DrainSignal>) -> Vec<WsMessage> {
+        let (frame_tx, mut frame_rx) = broadcast::channel(16);
+        let sessions = Arc::new(RwLock::new(HashMap::new()));
+        let context = StreamContext {
+            frame_tx,
+            sessions,
+            drain,
+            integrity_key: None,
+        };
+        AdaptiveStreamController::stream_frames(context, "s".to_string(), plan, 0, None)
+            .await
+            .unwrap();
+        let mut messages = Vec::new();
+        while let Ok((_, message)) = frame_rx.try_recv() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn test_create_session_refused_while_draining() {
+        use crate::infrastructure::shutdown::ShutdownCoordinator;
+
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(1));
+        let controller = AdaptiveStreamController::new();
+        controller.set_drain_signal(coordinator.signal());
+        coordinator.shutdown().await;
+
+        let result = controller
+            .create_session(json!({}), StreamOptions::default())
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_draining_stream_skips_non_critical_frames_and_reports_resume_point() {
+        use crate::infrastructure::shutdown::ShutdownCoordinator;
+
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(30));
+        let signal = coordinator.signal();
+        let stream = tokio::spawn(run_stream(
+            plan(&[Priority::CRITICAL, Priority::LOW, Priority::CRITICAL]),
+            Some(signal),
+        ));
+        tokio::task::yield_now().await;
+        coordinator.shutdown().await;
+        let messages = stream.await.unwrap();
+
+        let frame_ids: Vec<_> = messages
+            .iter()
+            .filter_map(|m| match m {
+                WsMessage::StreamFrame { frame_id, .. } => Some(*frame_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(frame_ids, vec![0, 2]);
+        match messages.last() {
+            Some(WsMessage::Error {
+                code, resume_from, ..
+            }) => {
+                assert_eq!(*code, 503);
+                assert_eq!(*resume_from, Some(1));
+            }
+            other => panic!("expected a shutdown error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_deadline_cuts_stream_before_completion() {
+        use crate::infrastructure::shutdown::ShutdownCoordinator;
+
+        let coordinator = ShutdownCoordinator::new(Duration::ZERO);
+        let signal = coordinator.signal();
+        coordinator.shutdown().await;
+
+        let messages = run_stream(plan(&[Priority::CRITICAL; 4]), Some(signal)).await;
+
+        assert_eq!(messages.len(), 2, "one frame, then the resume hint");
+        assert!(matches!(
+            messages[1],
+            WsMessage::Error {
+                resume_from: Some(1),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stream_without_drain_signal_completes() {
+        let messages = run_stream(plan(&[Priority::LOW, Priority::LOW]), None).await;
+        assert_eq!(messages.len(), 3);
+        assert!(matches!(messages[2], WsMessage::StreamComplete { .. }));
+    }
+
+    async fn at_least_once_session(controller: &AdaptiveStreamController, frames: usize) -> String {
+        let options = StreamOptions {
+            delivery: DeliveryMode::AtLeastOnce,
+            ..StreamOptions::default()
+        };
+        let session_id = controller.create_session(json!({}), options).await.unwrap();
+        controller
+            .sessions
+            .write()
+            .await
+            .get_mut(&session_id)
+            .unwrap()
+            .plan = plan(&vec![Priority::HIGH; frames]);
+        session_id
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_resend_replays_unacknowledged_frames_and_completion() {
+        let controller = AdaptiveStreamController::new();
+        let session_id = at_least_once_session(&controller, 3).await;
+        let mut rx = controller.subscribe_frames();
+        controller.start_streaming(&session_id).await.unwrap();
+        while !matches!(rx.recv().await.unwrap().1, WsMessage::StreamComplete { .. }) {}
+
+        controller
+            .handle_frame_ack(&session_id, 1, 5)
+            .await
+            .unwrap();
+        assert_eq!(controller.handle_resend(&session_id, 1).await.unwrap(), 2);
+
+        let resent: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|(_, message)| match message {
+                WsMessage::StreamFrame { frame_id, .. } => Some(frame_id),
+                WsMessage::StreamComplete { .. } => None,
+                other => panic!("unexpected message {other:?}"),
+            })
+            .collect();
+        assert_eq!(resent, vec![Some(2), None]);
+
+        let fire_and_forget = controller
+            .create_session(json!({}), StreamOptions::default())
+            .await
+            .unwrap();
+        assert!(controller.handle_resend(&fire_and_forget, 0).await.is_err());
+        assert!(controller.handle_resend("unknown", 0).await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_full_retransmit_buffer_pauses_stream_until_acknowledged() {
+        let controller = AdaptiveStreamController::new();
+        let session_id = at_least_once_session(&controller, RETRANSMIT_BUFFER_CAPACITY + 1).await;
+        let mut rx = controller.subscribe_frames();
+        controller.start_streaming(&session_id).await.unwrap();
+
+        for expected in 0..RETRANSMIT_BUFFER_CAPACITY as u32 {
+            assert!(matches!(
+                rx.recv().await.unwrap().1,
+                WsMessage::StreamFrame { frame_id, .. } if frame_id == expected
+            ));
+        }
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert!(
+            rx.try_recv().is_err(),
+            "stream must wait for acknowledgements"
+        );
+
+        controller
+            .handle_frame_ack(&session_id, 0, 5)
+            .await
+            .unwrap();
+        assert!(matches!(
+            rx.recv().await.unwrap().1,
+            WsMessage::StreamFrame { frame_id, .. } if frame_id == RETRANSMIT_BUFFER_CAPACITY as u32
+        ));
+    }
+
+    async fn drain_until_complete(
+        rx: &mut broadcast::Receiver<(String, WsMessage)>,
+    ) -> (Vec<u32>, ContentDigest) {
+        let mut frame_ids = Vec::new();
+        loop {
+            match rx.recv().await.unwrap().1 {
+                WsMessage::StreamFrame { frame_id, .. } => frame_ids.push(frame_id),
+                WsMessage::StreamComplete { checksum, .. } => return (frame_ids, checksum),
+                other => panic!("unexpected message {other:?}"),
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_resume_session_continues_after_last_received_frame() {
+        let controller = AdaptiveStreamController::new();
+        let session_id = controller
+            .create_session(json!({}), StreamOptions::default())
+            .await
+            .unwrap();
+        controller
+            .sessions
+            .write()
+            .await
+            .get_mut(&session_id)
+            .unwrap()
+            .plan = plan(&[Priority::HIGH; 3]);
+        let mut rx = controller.subscribe_frames();
+        controller.start_streaming(&session_id).await.unwrap();
+        let (sent, checksum) = drain_until_complete(&mut rx).await;
+        assert_eq!(sent, vec![0, 1, 2]);
+
+        assert!(controller.detach_session(&session_id).await);
+        controller
+            .resume_session(&session_id, Some(0))
+            .await
+            .unwrap();
+        let (resent, resumed_checksum) = drain_until_complete(&mut rx).await;
+        assert_eq!(resent, vec![1, 2]);
+        assert_eq!(resumed_checksum, checksum, "digest covers the whole stream");
+
+        controller
+            .resume_session(&session_id, Some(2))
+            .await
+            .unwrap();
+        assert_eq!(drain_until_complete(&mut rx).await.0, Vec::<u32>::new());
+    }
+
+    #[tokio::test]
+    async fn test_resume_unknown_session_reports_it_expired() {
+        let controller = AdaptiveStreamController::new();
+        let mut rx = controller.subscribe_frames();
+
+        assert!(matches!(
+            controller.resume_session("gone", Some(3)).await,
+            Err(PjsError::InvalidSession(_))
+        ));
+        assert!(matches!(
+            rx.try_recv().unwrap().1,
+            WsMessage::Error { code: 404, session_id: Some(id), .. } if id == "gone"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resume_live_updated_session_sends_current_document() {
+        let controller = AdaptiveStreamController::new();
+        let session_id = controller
+            .create_session(json!({"a": 1}), StreamOptions::default())
+            .await
+            .unwrap();
+        let mut rx = controller.subscribe_frames();
+        controller.start_streaming(&session_id).await.unwrap();
+        drain_until_complete(&mut rx).await;
+        // The connection drops before the update is sent.
+        controller.detach_session(&session_id).await;
+        while controller
+            .update_session_data(&session_id, json!({"a": 2, "b": [true]}))
+            .await
+            .is_err()
+        {
+            tokio::task::yield_now().await;
+        }
+        while rx.try_recv().is_ok() {}
+
+        controller.resume_session(&session_id, None).await.unwrap();
+
+        let WsMessage::StreamPatch { patches, .. } = rx.try_recv().unwrap().1 else {
+            panic!("expected the current document as a patch");
+        };
+        let mut document = json!({"stale": true});
+        for patch in &patches {
+            apply_frame_patch(&mut document, patch).unwrap();
+        }
+        assert_eq!(document, json!({"a": 2, "b": [true]}));
+        let WsMessage::StreamComplete { checksum, .. } = rx.try_recv().unwrap().1 else {
+            panic!("expected StreamComplete after the patch");
+        };
+        assert!(checksum.verify(&document, None).is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_detached_session_expires_unless_resumed() {
+        let controller = AdaptiveStreamController::new();
+        let expiring = controller
+            .create_session(json!({}), StreamOptions::default())
+            .await
+            .unwrap();
+        let resumed = controller
+            .create_session(json!({}), StreamOptions::default())
+            .await
+            .unwrap();
+        assert!(controller.detach_session(&expiring).await);
+        assert!(controller.detach_session(&resumed).await);
+        assert!(!controller.detach_session("unknown").await);
+
+        tokio::time::sleep(SESSION_RESUME_WINDOW / 2).await;
+        controller.resume_session(&resumed, None).await.unwrap();
+        tokio::time::sleep(SESSION_RESUME_WINDOW).await;
+        tokio::task::yield_now().await;
+
+        let sessions = controller.sessions.read().await;
+        assert!(!sessions.contains_key(&expiring));
+        assert!(sessions.contains_key(&resumed));
+    }
+
+    #[tokio::test]
+    async fn test_create_session_with_id_requires_unused_uuid() {
+        let controller = AdaptiveStreamController::new();
+        let session_id = Uuid::new_v4().to_string();
+
+        assert_eq!(
+            controller
+                .create_session_with_id(&session_id, json!({}), StreamOptions::default())
+                .await
+                .unwrap(),
+            session_id
+        );
+        for taken_or_invalid in [session_id.as_str(), "not-a-uuid"] {
+            assert!(matches!(
+                controller
+                    .create_session_with_id(taken_or_invalid, json!({}), StreamOptions::default())
+                    .await,
+                Err(PjsError::InvalidSession(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_apply_frame_payload() {
+        let mut document = json!({"existing": "value"});
+        apply_frame_payload(
+            &mut document,
+            &json!({"new": "data", "existing": "updated"}),
+        );
+        assert_eq!(document["existing"], "updated");
+        assert_eq!(document["new"], "data");
+
+        apply_frame_payload(&mut document, &json!({"new": {"a": 1, "b": null}}));
+        apply_frame_payload(&mut document, &json!({"new": {"b": 2}}));
+        assert_eq!(document["new"], json!({"a": 1, "b": 2}));
+
+        apply_frame_payload(&mut document, &json!([1, 2]));
+        assert_eq!(document, json!([1, 2]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stream_complete_carries_digest_of_reconstructed_document() {
+        let plan = vec![
+            StreamFrame {
+                data: json!({"id": 1, "name": null}),
+                priority: Priority::CRITICAL,
+                metadata: HashMap::new(),
+            },
+            StreamFrame {
+                data: json!({"name": "Ann"}),
+                priority: Priority::LOW,
+                metadata: HashMap::new(),
+            },
+        ];
+
+        let messages = run_stream(plan, None).await;
+        let WsMessage::StreamComplete { checksum, .. } = &messages[2] else {
+            panic!("expected StreamComplete, got {:?}", messages[2]);
+        };
+        assert!(
+            checksum
+                .verify(&json!({"id": 1, "name": "Ann"}), None)
+                .is_ok()
+        );
+        assert!(
+            checksum
+                .verify(&json!({"id": 1, "name": null}), None)
+                .is_err()
         );
     }
 
     #[test]
-    fn test_checksum_calculation() {
-        // Test empty frames
-        let empty_frames: Vec<Vec<u8>> = vec![];
-        let checksum = calculate_stream_checksum(&empty_frames);
-        assert!(checksum.starts_with("sha256:"));
-
-        // Test single frame
-        let single_frame = vec![vec![1, 2, 3, 4]];
-        let checksum1 = calculate_stream_checksum(&single_frame);
-        assert!(checksum1.starts_with("sha256:"));
-
-        // Test multiple frames
-        let multi_frames = vec![vec![1, 2], vec![3, 4], vec![5, 6]];
-        let checksum2 = calculate_stream_checksum(&multi_frames);
-        assert!(checksum2.starts_with("sha256:"));
-
-        // Same data should produce same checksum
-        let same_frames = vec![vec![1, 2], vec![3, 4], vec![5, 6]];
-        let checksum3 = calculate_stream_checksum(&same_frames);
-        assert_eq!(checksum2, checksum3);
-
-        // Different data should produce different checksum
-        let diff_frames = vec![vec![1, 2], vec![3, 4], vec![5, 7]]; // Last byte different
-        let checksum4 = calculate_stream_checksum(&diff_frames);
-        assert_ne!(checksum2, checksum4);
-
-        // Different order should produce different checksum
-        let reordered_frames = vec![vec![3, 4], vec![1, 2], vec![5, 6]];
-        let checksum5 = calculate_stream_checksum(&reordered_frames);
-        assert_ne!(checksum2, checksum5);
+    fn test_apply_frame_patch() {
+        let mut document = json!({"a": {"b": 1, "gone": true}, "xs": [1], "m": {"k": 1}});
+        let path = |p: &str| p.parse::<crate::domain::JsonPath>().unwrap();
+
+        for patch in [
+            FramePatch::set(path("$.a.b"), JsonData::Integer(2)),
+            FramePatch::delete(path("$.a.gone")),
+            FramePatch::append(path("$.xs"), json!([2, 3]).into()),
+            FramePatch::set(path("$.xs[0]"), JsonData::Integer(0)),
+            FramePatch::merge(path("$.m"), json!({"j": 2}).into()),
+        ] {
+            apply_frame_patch(&mut document, &patch).unwrap();
+        }
+        assert_eq!(
+            document,
+            json!({"a": {"b": 2}, "xs": [0, 2, 3], "m": {"k": 1, "j": 2}})
+        );
+
+        assert!(
+            apply_frame_patch(
+                &mut document,
+                &FramePatch::set(path("$.missing.b"), JsonData::Null)
+            )
+            .is_err()
+        );
+        assert!(
+            apply_frame_patch(
+                &mut document,
+                &FramePatch::append(path("$.a"), json!([1]).into())
+            )
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_session_data_broadcasts_priority_ordered_diff() {
+        let controller = AdaptiveStreamController::new();
+        let mut rx = controller.subscribe_frames();
+        let initial = json!({"id": 1, "status": "pending", "notes": "n", "tags": ["a"]});
+        let session_id = controller
+            .create_session(initial.clone(), StreamOptions::default())
+            .await
+            .unwrap();
+
+        assert!(
+            controller
+                .update_session_data(&session_id, json!({}))
+                .await
+                .is_err(),
+            "updates wait for the initial document to be streamed"
+        );
+
+        controller.start_streaming(&session_id).await.unwrap();
+        while !matches!(rx.recv().await.unwrap().1, WsMessage::StreamComplete { .. }) {}
+        // The task sends StreamComplete as its last action.
+        while controller
+            .update_session_data(&session_id, initial.clone())
+            .await
+            .is_err()
+        {
+            tokio::task::yield_now().await;
+        }
+        assert!(matches!(
+            rx.recv().await.unwrap().1,
+            WsMessage::StreamComplete { .. }
+        ));
+
+        let next = json!({"id": 2, "status": "shipped", "tags": ["a", "b"]});
+        let version = controller
+            .update_session_data(&session_id, next.clone())
+            .await
+            .unwrap();
+
+        let mut document = initial;
+        let mut priorities = Vec::new();
+        loop {
+            match rx.recv().await.unwrap().1 {
+                WsMessage::StreamPatch {
+                    version: patch_version,
+                    priority,
+                    patches,
+                    ..
+                } => {
+                    assert_eq!(patch_version, version);
+                    priorities.push(priority);
+                    for patch in &patches {
+                        apply_frame_patch(&mut document, patch).unwrap();
+                    }
+                }
+                WsMessage::StreamComplete { checksum, .. } => {
+                    assert!(checksum.verify(&document, None).is_ok());
+                    break;
+                }
+                other => panic!("unexpected message {other:?}"),
+            }
+        }
+
+        assert_eq!(document, next);
+        assert!(priorities.len() > 1);
+        assert!(
+            priorities.windows(2).all(|w| w[0] > w[1]),
+            "one message per priority level, critical first: {priorities:?}"
+        );
     }
 }
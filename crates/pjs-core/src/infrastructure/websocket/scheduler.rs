@@ -0,0 +1,190 @@
+//! Cross-stream frame scheduling for one WebSocket connection
+//!
+//! Every stream multiplexed on a connection queues its messages here, and
+//! the connection sends whichever stream's next message matters most. A
+//! stream's own messages always leave in the order they were queued.
+
+use super::WsMessage;
+use std::collections::{HashMap, VecDeque};
+
+/// Messages queued for one stream
+#[derive(Debug)]
+struct StreamQueue {
+    stream_priority: u8,
+    messages: VecDeque<(u64, WsMessage)>,
+}
+
+/// Interleaves the messages of the streams multiplexed on a connection by
+/// priority
+///
+/// [`Self::pop`] returns the next message of the stream whose next message
+/// has the highest priority, so a critical frame of one stream preempts
+/// background frames another stream queued earlier. Ties go to the stream
+/// with the higher stream priority, then to the message queued first.
+#[derive(Debug, Default)]
+pub(crate) struct StreamScheduler {
+    streams: HashMap<String, StreamQueue>,
+    next_seq: u64,
+    len: usize,
+}
+
+impl StreamScheduler {
+    /// Queue `message` for `session_id`, whose stream priority is
+    /// `stream_priority`.
+    pub(crate) fn push(&mut self, session_id: String, stream_priority: u8, message: WsMessage) {
+        let queue = self
+            .streams
+            .entry(session_id)
+            .or_insert_with(|| StreamQueue {
+                stream_priority,
+                messages: VecDeque::new(),
+            });
+        queue.stream_priority = stream_priority;
+        queue.messages.push_back((self.next_seq, message));
+        self.next_seq += 1;
+        self.len += 1;
+    }
+
+    /// Take the message to send next.
+    pub(crate) fn pop(&mut self) -> Option<WsMessage> {
+        let session_id = self
+            .streams
+            .iter()
+            .filter_map(|(session_id, queue)| {
+                let (seq, message) = queue.messages.front()?;
+                Some((
+                    (
+                        message_priority(message),
+                        queue.stream_priority,
+                        std::cmp::Reverse(*seq),
+                    ),
+                    session_id,
+                ))
+            })
+            .max_by_key(|(key, _)| *key)?
+            .1
+            .clone();
+
+        let queue = self.streams.get_mut(&session_id)?;
+        let (_, message) = queue.messages.pop_front()?;
+        if queue.messages.is_empty() {
+            self.streams.remove(&session_id);
+        }
+        self.len -= 1;
+        Some(message)
+    }
+
+    /// Drop every message queued for `session_id`, returning how many
+    /// there were.
+    pub(crate) fn cancel(&mut self, session_id: &str) -> usize {
+        let dropped = self
+            .streams
+            .remove(session_id)
+            .map_or(0, |queue| queue.messages.len());
+        self.len -= dropped;
+        dropped
+    }
+
+    /// Number of queued messages across every stream.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Priority a message is scheduled at. Frames and patches carry their own;
+/// completions and errors only ever reach the front of their stream's queue
+/// once everything before them was sent, and go out right away.
+fn message_priority(message: &WsMessage) -> u8 {
+    match message {
+        WsMessage::StreamFrame { priority, .. } | WsMessage::StreamPatch { priority, .. } => {
+            *priority
+        }
+        _ => u8::MAX,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Priority, value_objects::ContentDigest};
+    use serde_json::json;
+
+    fn frame(session_id: &str, frame_id: u32, priority: Priority) -> WsMessage {
+        WsMessage::StreamFrame {
+            session_id: session_id.to_string(),
+            frame_id,
+            priority: priority.value(),
+            payload: json!({}),
+            is_complete: false,
+        }
+    }
+
+    fn sent(scheduler: &mut StreamScheduler) -> Vec<(String, u32)> {
+        std::iter::from_fn(|| scheduler.pop())
+            .map(|message| match message {
+                WsMessage::StreamFrame {
+                    session_id,
+                    frame_id,
+                    ..
+                } => (session_id, frame_id),
+                other => (format!("{other:?}"), u32::MAX),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_critical_frames_preempt_background_frames_of_other_streams() {
+        let mut scheduler = StreamScheduler::default();
+        for frame_id in 0..3 {
+            scheduler.push("a".into(), 0, frame("a", frame_id, Priority::BACKGROUND));
+        }
+        scheduler.push("b".into(), 0, frame("b", 0, Priority::CRITICAL));
+        scheduler.push("b".into(), 0, frame("b", 1, Priority::BACKGROUND));
+        assert_eq!(scheduler.len(), 5);
+
+        let order: Vec<_> = sent(&mut scheduler)
+            .into_iter()
+            .map(|(session_id, frame_id)| format!("{session_id}{frame_id}"))
+            .collect();
+        assert_eq!(order, ["b0", "a0", "a1", "a2", "b1"]);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_stream_order_is_kept_and_ties_go_to_stream_priority() {
+        let mut scheduler = StreamScheduler::default();
+        // A low-priority frame ahead of a critical one holds it back.
+        scheduler.push("a".into(), 0, frame("a", 0, Priority::LOW));
+        scheduler.push("a".into(), 0, frame("a", 1, Priority::CRITICAL));
+        scheduler.push("b".into(), 5, frame("b", 0, Priority::LOW));
+        scheduler.push(
+            "b".into(),
+            5,
+            WsMessage::StreamComplete {
+                session_id: "b".into(),
+                checksum: ContentDigest::compute(&json!({}), None),
+            },
+        );
+
+        let order = sent(&mut scheduler);
+        assert_eq!(order[0], ("b".to_string(), 0));
+        assert!(order[1].0.starts_with("StreamComplete"));
+        assert_eq!(order[2..], [("a".to_string(), 0), ("a".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_cancel_drops_only_that_streams_messages() {
+        let mut scheduler = StreamScheduler::default();
+        scheduler.push("a".into(), 0, frame("a", 0, Priority::HIGH));
+        scheduler.push("a".into(), 0, frame("a", 1, Priority::HIGH));
+        scheduler.push("b".into(), 0, frame("b", 0, Priority::LOW));
+
+        assert_eq!(scheduler.cancel("a"), 2);
+        assert_eq!(scheduler.cancel("unknown"), 0);
+        assert_eq!(sent(&mut scheduler), [("b".to_string(), 0)]);
+    }
+}
@@ -1,11 +1,26 @@
 //! WebSocket server implementation for Axum
 
 #[cfg(feature = "http-server")]
-use super::{AdaptiveStreamController, StreamOptions, WebSocketTransport, WsMessage};
+use super::{
+    AdaptiveStreamController, StreamOptions, WebSocketTransport, WsMessage,
+    scheduler::StreamScheduler,
+};
 use crate::{
-    Result as PjsResult,
-    infrastructure::bounded_channel::{self, ByteBoundedSender, byte_bounded_channel},
-    security::{RateLimitConfig, RateLimitGuard, WebSocketRateLimiter},
+    Error as PjsError, Result as PjsResult,
+    domain::{
+        DomainResult,
+        events::DomainEvent,
+        ports::EventPublisherGat,
+        value_objects::{IntegrityKey, SessionId, StreamId},
+    },
+    infrastructure::{
+        bounded_channel::{self, ByteBoundedSender, byte_bounded_channel},
+        shutdown::DrainSignal,
+    },
+    security::{
+        RateLimitConfig, RateLimitGuard, RateLimitPolicy, RateLimitStore, WebSocketRateLimiter,
+        rate_limit_store::API_KEY_HEADER,
+    },
 };
 #[cfg(feature = "http-server")]
 use axum::{
@@ -21,13 +36,25 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::future::Future;
 use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
-use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
 use tracing::{debug, error, info, warn};
 use uuid;
 
+/// Most stream messages a connection holds for its scheduler to order.
+///
+/// While this many wait to be written the connection stops taking new
+/// ones, and the controller's broadcast drops the overflow for it, as it
+/// did before frames were scheduled.
+const SCHEDULER_CAPACITY: usize = 1024;
+
+/// The latest [`RateLimitPolicy`] for store-backed upgrade budgets; see
+/// [`AxumWebSocketTransport::with_reloadable_rate_limit_store`].
+type PolicyReceiver = tokio::sync::watch::Receiver<Arc<RateLimitPolicy>>;
+
 /// Capacity of each per-connection outgoing message channel.
 ///
 /// Bounds how many frames can queue for a slow client before `send_frame`
@@ -168,6 +195,12 @@ impl OriginAllowList {
     }
 }
 
+/// Publishes a domain event raised by the transport; see
+/// [`AxumWebSocketTransport::with_event_publisher`].
+type EventSink = Arc<
+    dyn Fn(DomainEvent) -> Pin<Box<dyn Future<Output = DomainResult<()>> + Send>> + Send + Sync,
+>;
+
 /// Axum WebSocket transport implementation
 pub struct AxumWebSocketTransport {
     controller: Arc<AdaptiveStreamController>,
@@ -175,17 +208,26 @@ pub struct AxumWebSocketTransport {
     active_connections: Arc<RwLock<Vec<String>>>,
     /// Per-connection outgoing senders; keyed by connection ID
     outgoing_channels: Arc<RwLock<HashMap<String, ByteBoundedSender<String>>>>,
-    /// Streaming session IDs created by each connection (via `StreamInit`),
-    /// so [`Self::handle_socket`]'s teardown can abort the right sessions'
-    /// streaming tasks when the connection closes.
-    connection_sessions: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    /// Streaming sessions multiplexed on each connection (via `StreamInit`
+    /// or `Resume`), with their stream priority. A connection only sends
+    /// its own sessions' messages, and [`Self::handle_socket`]'s teardown
+    /// detaches them when it closes.
+    connection_sessions: Arc<RwLock<HashMap<String, HashMap<String, u8>>>>,
     /// Per-IP rate limiter applied to upgrade requests, connection establishment,
     /// and inbound application-level messages.
     rate_limiter: Arc<WebSocketRateLimiter>,
+    /// Shared request budget for upgrade requests, replacing
+    /// `rate_limiter`'s per-IP request window; see
+    /// [`Self::with_rate_limit_store`].
+    request_store: Option<(Arc<dyn RateLimitStore>, PolicyReceiver)>,
     /// `Origin` allow-list applied to WebSocket upgrades, to block
     /// cross-site WebSocket hijacking (CSWSH) from browser clients. See
     /// [`Self::with_allowed_origins`].
     allowed_origins: OriginAllowList,
+    /// Graceful-shutdown signal; see [`Self::with_drain_signal`].
+    drain: Option<DrainSignal>,
+    /// Where stream lifecycle events go; see [`Self::with_event_publisher`].
+    event_sink: Option<EventSink>,
 }
 
 impl AxumWebSocketTransport {
@@ -229,7 +271,10 @@ impl AxumWebSocketTransport {
             outgoing_channels: Arc::new(RwLock::new(HashMap::new())),
             connection_sessions: Arc::new(RwLock::new(HashMap::new())),
             rate_limiter: Arc::new(WebSocketRateLimiter::new(config)),
+            request_store: None,
             allowed_origins: OriginAllowList::DenyAll,
+            drain: None,
+            event_sink: None,
         }
     }
 
@@ -261,11 +306,105 @@ impl AxumWebSocketTransport {
         self
     }
 
+    /// Charge upgrade requests against `store`, as resolved by `policy`,
+    /// instead of the per-IP request window of this transport's
+    /// [`RateLimitConfig`].
+    ///
+    /// Use a [`SharedRateLimitStore`](crate::security::rate_limit_store::SharedRateLimitStore)
+    /// so replicas share one budget. Upgrades presenting a registered API
+    /// key in `X-PJS-API-Key` are charged to the key. Connection caps, frame
+    /// sizes and per-connection message rates stay process-local: they bound
+    /// this process's own sockets and buffers.
+    ///
+    /// A store failure rejects the upgrade with HTTP 503 unless
+    /// [`RateLimitPolicy::with_fail_open`] is set.
+    pub fn with_rate_limit_store(
+        self,
+        store: Arc<dyn RateLimitStore>,
+        policy: RateLimitPolicy,
+    ) -> Self {
+        let (_, policy) = tokio::sync::watch::channel(Arc::new(policy));
+        self.with_reloadable_rate_limit_store(store, policy)
+    }
+
+    /// Like [`Self::with_rate_limit_store`], but resolve every upgrade with
+    /// the policy most recently sent on `policy`. Once the sender is dropped
+    /// the last policy stays in force.
+    pub fn with_reloadable_rate_limit_store(
+        mut self,
+        store: Arc<dyn RateLimitStore>,
+        policy: PolicyReceiver,
+    ) -> Self {
+        self.request_store = Some((store, policy));
+        self
+    }
+
+    /// The per-IP limiter guarding upgrades, connections and inbound
+    /// messages, e.g. for
+    /// [`ReloadableConfig::bind_rate_limiter`](crate::config::ReloadableConfig::bind_rate_limiter).
+    pub fn rate_limiter(&self) -> &Arc<WebSocketRateLimiter> {
+        &self.rate_limiter
+    }
+
+    /// Make the transport cooperate with graceful shutdown.
+    ///
+    /// While `signal` is draining:
+    /// - upgrades are refused with HTTP 503 and `StreamInit` no longer
+    ///   creates sessions;
+    /// - running streams send only their critical frames, and a stream that
+    ///   cannot finish before the drain deadline ends with a
+    ///   [`WsMessage::Error`] whose `resume_from` names the first `frame_id`
+    ///   the client is missing;
+    /// - once every stream has finished (or the deadline plus
+    ///   [`TERMINATION_GRACE`](crate::infrastructure::shutdown::TERMINATION_GRACE)
+    ///   has passed), each connection forwards its remaining queued frames
+    ///   and closes with code 1001 (Going Away).
+    ///
+    /// Closing upgraded connections is this transport's job: the HTTP
+    /// server's connection drain does not reach sockets after the upgrade.
+    pub fn with_drain_signal(mut self, signal: DrainSignal) -> Self {
+        self.controller.set_drain_signal(signal.clone());
+        self.drain = Some(signal);
+        self
+    }
+
+    /// Sign every stream's `StreamComplete` digest with HMAC-SHA256 under
+    /// `key` instead of plain SHA-256.
+    ///
+    /// Clients configured with the same key (see
+    /// `PjsWebSocketClient::with_integrity_key`) can then show the document they reconstructed came from a holder of
+    /// the key, not only that it arrived intact.
+    pub fn with_integrity_key(self, key: IntegrityKey) -> Self {
+        self.controller.set_integrity_key(key);
+        self
+    }
+
+    /// Publish [`DomainEvent::StreamCancelled`] on `publisher` whenever a
+    /// client cancels one of its streams with [`WsMessage::CancelStream`],
+    /// as `DELETE /pjs/sessions/{session_id}/streams/{stream_id}` does for
+    /// HTTP streams.
+    ///
+    /// A WebSocket session carries exactly one stream, so the event's
+    /// `session_id` and `stream_id` are both the session's UUID. The
+    /// stream's unsent and unacknowledged frames live in the session and
+    /// are released with it.
+    pub fn with_event_publisher<P>(mut self, publisher: Arc<P>) -> Self
+    where
+        P: EventPublisherGat + Send + Sync + 'static,
+    {
+        self.event_sink = Some(Arc::new(move |event| {
+            let publisher = publisher.clone();
+            Box::pin(async move { publisher.publish(event).await })
+        }));
+        self
+    }
+
     /// Handle WebSocket upgrade for Axum.
     ///
     /// Extracts the peer address via [`ConnectInfo`] and rejects upgrade
     /// requests that exceed the per-IP request budget with HTTP 429 before any
-    /// WebSocket frames are exchanged.
+    /// WebSocket frames are exchanged. With [`Self::with_rate_limit_store`]
+    /// the budget is the store's instead, and a failing store yields HTTP 503.
     ///
     /// Also rejects, with HTTP 403, upgrades carrying an `Origin` header not
     /// in [`Self::with_allowed_origins`]'s allow-list — see that method and
@@ -291,7 +430,43 @@ impl AxumWebSocketTransport {
     ) -> Response {
         let client_ip = addr.ip();
 
-        if let Err(e) = transport.rate_limiter.check_request(client_ip) {
+        if transport
+            .drain
+            .as_ref()
+            .is_some_and(DrainSignal::is_draining)
+        {
+            return (StatusCode::SERVICE_UNAVAILABLE, "server is shutting down").into_response();
+        }
+
+        if let Some((store, policy)) = &transport.request_store {
+            let policy = policy.borrow().clone();
+            let api_key = headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok());
+            let (key, algorithm) = policy.resolve(client_ip, api_key);
+            match store.check(&key, algorithm).await {
+                Ok(decision) if decision.allowed => {}
+                Ok(decision) => {
+                    warn!("WebSocket upgrade denied for {}: budget exhausted", key);
+                    return (
+                        StatusCode::TOO_MANY_REQUESTS,
+                        format!("Rate limit exceeded: {} requests for {key}", decision.limit),
+                    )
+                        .into_response();
+                }
+                Err(e) if policy.fail_open() => {
+                    warn!(
+                        "Rate limit store failed, admitting upgrade from {}: {}",
+                        key, e
+                    );
+                }
+                Err(e) => {
+                    warn!(
+                        "Rate limit store failed, rejecting upgrade from {}: {}",
+                        key, e
+                    );
+                    return (StatusCode::SERVICE_UNAVAILABLE, e.to_string()).into_response();
+                }
+            }
+        } else if let Err(e) = transport.rate_limiter.check_request(client_ip) {
             warn!("WebSocket upgrade denied for IP {}: {}", client_ip, e);
             return (StatusCode::TOO_MANY_REQUESTS, e.to_string()).into_response();
         }
@@ -373,37 +548,53 @@ impl AxumWebSocketTransport {
         let transport_clone = self.clone();
         let connection_id_clone = Arc::new(connection_id.clone());
         let guard_for_task = guard.clone();
+        let streams_finished = self.drain.as_ref().map(DrainSignal::streams_finished);
         let websocket_task = {
             let mut frame_rx = frame_rx;
             tokio::spawn(async move {
+                let streams_finished = async move {
+                    match streams_finished {
+                        Some(finished) => finished.await,
+                        None => std::future::pending().await,
+                    }
+                };
+                futures::pin_mut!(streams_finished);
+                // Messages of the connection's streams waiting to be
+                // written, most important first.
+                let mut scheduler = StreamScheduler::default();
                 loop {
                     tokio::select! {
-                        // Handle frames from stream controller. Match on the full
-                        // Result so Lagged is logged-and-skipped while Closed
-                        // ends the loop instead of busy-spinning.
-                        recv_result = frame_rx.recv() => {
-                            match recv_result {
-                                Ok((_session_id, message)) => {
-                                    match serde_json::to_string(&message) {
-                                        Ok(json_str) => {
-                                            if let Err(e) = super::send_with_write_timeout(&mut sender, Message::Text(json_str.into()), write_timeout).await {
-                                                error!("Failed to send message to client: {}", e);
-                                                break;
-                                            }
-                                        }
-                                        Err(e) => {
-                                            error!("Failed to serialize message: {}", e);
-                                        }
+                        biased;
+                        // Shutdown: every stream has sent its last frame (or
+                        // run out of time), so forward whatever is still
+                        // queued for this connection and say goodbye.
+                        () = &mut streams_finished => {
+                            loop {
+                                match frame_rx.try_recv() {
+                                    Ok((session_id, message)) => {
+                                        transport_clone.schedule(&connection_id_clone, &mut scheduler, session_id, message).await;
                                     }
+                                    Err(TryRecvError::Lagged(_)) => continue,
+                                    Err(_) => break,
                                 }
-                                Err(RecvError::Lagged(skipped)) => {
-                                    warn!("Frame broadcast lagged; skipped {} frames", skipped);
-                                }
-                                Err(RecvError::Closed) => {
-                                    debug!("Frame broadcast channel closed");
+                            }
+                            while let Some(message) = scheduler.pop() {
+                                let Ok(json_str) = serde_json::to_string(&message) else {
+                                    continue;
+                                };
+                                if super::send_with_write_timeout(&mut sender, Message::Text(json_str.into()), write_timeout).await.is_err() {
                                     break;
                                 }
                             }
+                            let _ = super::send_with_write_timeout(
+                                &mut sender,
+                                Message::Close(Some(axum::extract::ws::CloseFrame {
+                                    code: 1001, // Going Away
+                                    reason: "server shutting down".into(),
+                                })),
+                                write_timeout,
+                            ).await;
+                            break;
                         }
                         // Handle outgoing messages from application. Already
                         // serialized at `send_frame` time — see its doc for why.
@@ -438,8 +629,18 @@ impl AxumWebSocketTransport {
                                     }
                                     match serde_json::from_str::<WsMessage>(&text) {
                                         Ok(ws_message) => {
-                                            if let Err(e) = transport_clone.handle_websocket_message(Arc::clone(&connection_id_clone), ws_message).await {
-                                                error!("Failed to handle message: {}", e);
+                                            let cancelled = match &ws_message {
+                                                WsMessage::CancelStream { session_id } => Some(session_id.clone()),
+                                                _ => None,
+                                            };
+                                            match transport_clone.handle_websocket_message(Arc::clone(&connection_id_clone), ws_message).await {
+                                                Ok(()) => {
+                                                    if let Some(session_id) = cancelled {
+                                                        let dropped = scheduler.cancel(&session_id);
+                                                        debug!("Dropped {} unsent messages of cancelled session {}", dropped, session_id);
+                                                    }
+                                                }
+                                                Err(e) => error!("Failed to handle message: {}", e),
                                             }
                                         }
                                         Err(e) => {
@@ -484,6 +685,54 @@ impl AxumWebSocketTransport {
                                 }
                             }
                         }
+                        // Take frames from the stream controller into the
+                        // scheduler, along with everything else already
+                        // waiting so it can be ordered as a whole. Match on
+                        // the full Result so Lagged is logged-and-skipped
+                        // while Closed ends the loop instead of
+                        // busy-spinning.
+                        recv_result = frame_rx.recv(), if scheduler.len() < SCHEDULER_CAPACITY => {
+                            match recv_result {
+                                Ok((session_id, message)) => {
+                                    transport_clone.schedule(&connection_id_clone, &mut scheduler, session_id, message).await;
+                                    while scheduler.len() < SCHEDULER_CAPACITY {
+                                        match frame_rx.try_recv() {
+                                            Ok((session_id, message)) => {
+                                                transport_clone.schedule(&connection_id_clone, &mut scheduler, session_id, message).await;
+                                            }
+                                            Err(TryRecvError::Lagged(skipped)) => {
+                                                warn!("Frame broadcast lagged; skipped {} frames", skipped);
+                                            }
+                                            Err(_) => break,
+                                        }
+                                    }
+                                }
+                                Err(RecvError::Lagged(skipped)) => {
+                                    warn!("Frame broadcast lagged; skipped {} frames", skipped);
+                                }
+                                Err(RecvError::Closed) => {
+                                    debug!("Frame broadcast channel closed");
+                                    break;
+                                }
+                            }
+                        }
+                        // Write the most important queued stream message.
+                        () = std::future::ready(()), if !scheduler.is_empty() => {
+                            let Some(message) = scheduler.pop() else {
+                                continue;
+                            };
+                            match serde_json::to_string(&message) {
+                                Ok(json_str) => {
+                                    if let Err(e) = super::send_with_write_timeout(&mut sender, Message::Text(json_str.into()), write_timeout).await {
+                                        error!("Failed to send message to client: {}", e);
+                                        break;
+                                    }
+                                }
+                                Err(e) => {
+                                    error!("Failed to serialize message: {}", e);
+                                }
+                            }
+                        }
                         else => {
                             break;
                         }
@@ -507,18 +756,20 @@ impl AxumWebSocketTransport {
         drop(connections);
         drop(guard);
 
-        // Abort every streaming task this connection started — otherwise a
+        // Stop every streaming task this connection started — otherwise a
         // session's frame-streaming task keeps running (and its abort
         // handle stays unreachable) after the client that requested it has
-        // disconnected.
+        // disconnected. The sessions themselves are kept for
+        // `SESSION_RESUME_WINDOW` in case the client reconnects and
+        // resumes them.
         if let Some(session_ids) = self
             .connection_sessions
             .write()
             .await
             .remove(&connection_id)
         {
-            for session_id in session_ids {
-                self.controller.remove_session(&session_id).await;
+            for session_id in session_ids.keys() {
+                self.controller.detach_session(session_id).await;
             }
         }
 
@@ -537,6 +788,27 @@ impl AxumWebSocketTransport {
         self.active_connections.read().await.len()
     }
 
+    /// Queue a controller message for `connection_id` if its session is
+    /// multiplexed on that connection; other connections' messages are
+    /// dropped.
+    async fn schedule(
+        &self,
+        connection_id: &str,
+        scheduler: &mut StreamScheduler,
+        session_id: String,
+        message: WsMessage,
+    ) {
+        let stream_priority = self
+            .connection_sessions
+            .read()
+            .await
+            .get(connection_id)
+            .and_then(|session_ids| session_ids.get(&session_id).copied());
+        if let Some(stream_priority) = stream_priority {
+            scheduler.push(session_id, stream_priority, message);
+        }
+    }
+
     /// Handle WebSocket message for a specific connection.
     ///
     /// Thin wrapper around [`WebSocketTransport::handle_message`] that
@@ -678,9 +950,12 @@ impl WebSocketTransport for AxumWebSocketTransport {
         }
     }
 
-    /// The `StreamInit` arm records the created session under `connection`
-    /// in `connection_sessions` so [`Self::handle_socket`]'s teardown can
-    /// abort it on disconnect. Nothing else drains that map: a caller that
+    /// The `StreamInit` and `Resume` arms record the session under
+    /// `connection` in `connection_sessions`, which routes its messages to
+    /// the connection and lets [`Self::handle_socket`]'s teardown detach it
+    /// on disconnect; `CancelStream` removes it. `StreamInit` keeps the
+    /// client's proposed session id when it is a UUID, so the client can
+    /// resume the session after reconnecting. Nothing else drains that map: a caller that
     /// drives this method directly, bypassing `handle_socket` (e.g. a test,
     /// or a future non-axum trait caller), leaves its session's entry there
     /// indefinitely — [`WebSocketTransport::close_stream`] removes the
@@ -692,8 +967,19 @@ impl WebSocketTransport for AxumWebSocketTransport {
     ) -> Self::HandleMessageFuture<'_> {
         async move {
             match message {
-                WsMessage::StreamInit { data, options, .. } => {
-                    let session_id = self.controller.create_session(data, options).await?;
+                WsMessage::StreamInit {
+                    session_id,
+                    data,
+                    options,
+                } => {
+                    let stream_priority = options.stream_priority;
+                    let session_id = if uuid::Uuid::parse_str(&session_id).is_ok() {
+                        self.controller
+                            .create_session_with_id(&session_id, data, options)
+                            .await?
+                    } else {
+                        self.controller.create_session(data, options).await?
+                    };
                     // Tracked before `start_streaming` so a session that was
                     // successfully created is still reachable for cleanup
                     // even if `start_streaming` itself returns an error.
@@ -702,7 +988,7 @@ impl WebSocketTransport for AxumWebSocketTransport {
                         .await
                         .entry((*connection).clone())
                         .or_default()
-                        .push(session_id.clone());
+                        .insert(session_id.clone(), stream_priority);
                     self.controller.start_streaming(&session_id).await?;
                     info!(
                         "Created new streaming session for connection {}",
@@ -722,6 +1008,83 @@ impl WebSocketTransport for AxumWebSocketTransport {
                         .handle_frame_ack(&session_id, frame_id, processing_time_ms)
                         .await?;
                 }
+                WsMessage::Resume {
+                    session_id,
+                    last_frame_id,
+                } => {
+                    debug!(
+                        "Received resume request: session={}, last_frame={:?}",
+                        session_id, last_frame_id
+                    );
+                    // The session now belongs to this connection: its frames
+                    // go here, and it is detached when this one closes, not
+                    // when the stale one does. Moved before resuming so the
+                    // resumed frames (or the refusal) reach this connection.
+                    let stream_priority = self
+                        .controller
+                        .stream_priority(&session_id)
+                        .await
+                        .unwrap_or_default();
+                    {
+                        let mut connection_sessions = self.connection_sessions.write().await;
+                        for session_ids in connection_sessions.values_mut() {
+                            session_ids.remove(&session_id);
+                        }
+                        connection_sessions
+                            .entry((*connection).clone())
+                            .or_default()
+                            .insert(session_id.clone(), stream_priority);
+                    }
+                    self.controller
+                        .resume_session(&session_id, last_frame_id)
+                        .await?;
+                }
+                WsMessage::CancelStream { session_id } => {
+                    debug!("Received stream cancellation: session={}", session_id);
+                    // Only the connection a stream belongs to may cancel it.
+                    let owned = self
+                        .connection_sessions
+                        .write()
+                        .await
+                        .get_mut(connection.as_ref())
+                        .and_then(|session_ids| session_ids.remove(&session_id))
+                        .is_some();
+                    if !owned {
+                        return Err(PjsError::InvalidSession(session_id));
+                    }
+                    let removed = self.controller.remove_session(&session_id).await;
+                    if removed
+                        && let Some(publish) = &self.event_sink
+                        && let Ok(uuid) = uuid::Uuid::parse_str(&session_id)
+                    {
+                        let event = DomainEvent::StreamCancelled {
+                            session_id: SessionId::from_uuid(uuid),
+                            stream_id: StreamId::from_uuid(uuid),
+                            timestamp: chrono::Utc::now(),
+                        };
+                        // The stream is gone either way; only the event is lost.
+                        if let Err(e) = publish(event).await {
+                            warn!("Failed to publish cancellation of {}: {}", session_id, e);
+                        }
+                    }
+                    info!(
+                        "Cancelled streaming session {} for connection {}",
+                        session_id,
+                        connection.as_ref()
+                    );
+                }
+                WsMessage::Resend {
+                    session_id,
+                    from_frame_id,
+                } => {
+                    debug!(
+                        "Received resend request: session={}, from_frame={}",
+                        session_id, from_frame_id
+                    );
+                    self.controller
+                        .handle_resend(&session_id, from_frame_id)
+                        .await?;
+                }
                 WsMessage::Ping { timestamp } => {
                     debug!("Received ping with timestamp: {}", timestamp);
                     // Pong is handled automatically in handle_socket
@@ -730,6 +1093,7 @@ impl WebSocketTransport for AxumWebSocketTransport {
                     session_id,
                     error,
                     code,
+                    ..
                 } => {
                     // `session_id` and `error` are both arbitrary
                     // client-supplied strings with no length validation on
@@ -835,7 +1199,65 @@ mod tests {
             .get(connection.as_ref())
             .expect("connection_sessions must have an entry for this connection");
         assert_eq!(tracked.len(), 1);
-        assert!(!tracked[0].is_empty());
+        assert!(tracked.keys().all(|session_id| !session_id.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stream_publishes_stream_cancelled() {
+        use crate::infrastructure::adapters::event_publisher::InMemoryEventPublisher;
+
+        let publisher = Arc::new(InMemoryEventPublisher::new());
+        let transport = AxumWebSocketTransport::new().with_event_publisher(publisher.clone());
+        let connection = Arc::new("conn-cancel".to_string());
+        let session_id = uuid::Uuid::new_v4();
+
+        transport
+            .handle_message(
+                connection.clone(),
+                WsMessage::StreamInit {
+                    session_id: session_id.to_string(),
+                    data: json!({"id": 1, "items": [1, 2, 3]}),
+                    options: StreamOptions::default(),
+                },
+            )
+            .await
+            .unwrap();
+        transport
+            .handle_message(
+                connection.clone(),
+                WsMessage::CancelStream {
+                    session_id: session_id.to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let cancelled = publisher.events_by_type("stream_cancelled");
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(
+            cancelled[0].session_id,
+            Some(SessionId::from_uuid(session_id))
+        );
+        assert!(
+            !transport
+                .controller
+                .remove_session(&session_id.to_string())
+                .await
+        );
+
+        // Cancelling again is refused and publishes nothing more.
+        assert!(
+            transport
+                .handle_message(
+                    connection,
+                    WsMessage::CancelStream {
+                        session_id: session_id.to_string(),
+                    },
+                )
+                .await
+                .is_err()
+        );
+        assert_eq!(publisher.events_by_type("stream_cancelled").len(), 1);
     }
 
     #[tokio::test]
@@ -891,6 +1313,7 @@ mod tests {
             session_id: None,
             error: "x".repeat(MAX_QUEUED_OUTGOING_BYTES + 1),
             code: 0,
+            resume_from: None,
         };
 
         transport
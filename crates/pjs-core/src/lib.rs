@@ -61,6 +61,10 @@ pub use domain::{
     },
 };
 
+// Priorities declared on response types; the `derive` feature adds
+// `#[derive(PjsPriority)]` (use `#[pjs(crate = "pjson_rs")]` with it)
+pub use pjson_rs_domain::PjsPriority;
+
 // Events exports
 pub use domain::events::{PriorityDistribution, PriorityPercentages};
 
@@ -76,7 +80,8 @@ pub use application::{
 
 // Configuration exports
 pub use config::{
-    ConfigError, ParserConfig, PjsConfig, SecurityConfig, SimdConfig, StreamingConfig,
+    ConfigError, ConfigFormat, ConfigLoader, LayeredConfig, ParserConfig, PjsConfig, ReloadReport,
+    ReloadableConfig, SecurityConfig, SimdConfig, StreamingConfig,
     security::{BufferLimits, JsonLimits, NetworkLimits, RateLimitingConfig, SessionLimits},
 };
 
@@ -87,6 +92,7 @@ pub use compression::{
         ByteCodec, CompressionQuality, DecompressionContextStats, SecureCompressedData,
         SecureCompressor, SecureDecompressionContext,
     },
+    shared_context::{SharedContextCompressor, SharedContextDecompressor, SharedContextStats},
 };
 
 // Streaming exports
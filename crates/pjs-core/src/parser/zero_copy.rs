@@ -5,11 +5,11 @@
 
 use crate::{
     config::SecurityConfig,
-    domain::{DomainError, DomainResult},
+    domain::{DomainError, DomainResult, value_objects::JsonData},
     parser::ValueType,
     security::SecurityValidator,
 };
-use std::{marker::PhantomData, str::from_utf8};
+use std::{collections::HashMap, marker::PhantomData, str::from_utf8};
 
 /// Zero-copy lazy parser trait with lifetime management
 ///
@@ -66,6 +66,39 @@ impl<'a> ZeroCopyParser<'a> {
         }
     }
 
+    /// Parse `input` into an owned [`JsonData`] document in a single pass.
+    ///
+    /// Unlike [`parse_lazy`](LazyParser::parse_lazy), which only validates
+    /// containers and hands back slices of them, this builds the whole tree,
+    /// straight from the input bytes and without an intermediate
+    /// `serde_json::Value`. Every limit of the parser's [`SecurityConfig`]
+    /// (input size, nesting depth, array length, object key count, string
+    /// length) is checked while reading, so a document that breaks one is
+    /// rejected with [`DomainError::SecurityViolation`] before the rest of it
+    /// is built. Anything but whitespace after the value is an error.
+    ///
+    /// Numbers become [`JsonData::Integer`] when written without a fraction
+    /// or exponent and within `i64`, and [`JsonData::Float`] otherwise, as
+    /// with `JsonData`'s `Deserialize` impl.
+    pub fn parse_json_data(&mut self, input: &'a [u8]) -> DomainResult<JsonData> {
+        self.validator
+            .validate_input_size(input.len())
+            .map_err(|e| DomainError::SecurityViolation(e.to_string()))?;
+
+        self.input = input;
+        self.position = 0;
+        self.depth = 0;
+
+        let value = self.build_value()?;
+        self.skip_whitespace();
+        if self.position < self.input.len() {
+            return Err(DomainError::InvalidInput(
+                "Trailing characters after JSON value".to_string(),
+            ));
+        }
+        Ok(value)
+    }
+
     /// Parse JSON value starting at current position
     pub fn parse_value(&mut self) -> DomainResult<LazyJsonValue<'a>> {
         self.skip_whitespace();
@@ -318,6 +351,151 @@ impl<'a> ZeroCopyParser<'a> {
         Ok(LazyJsonValue::NumberSlice(number_slice))
     }
 
+    /// Build the [`JsonData`] value starting at the current position
+    fn build_value(&mut self) -> DomainResult<JsonData> {
+        self.skip_whitespace();
+
+        match self.input.get(self.position) {
+            Some(b'{') => self.build_object(),
+            Some(b'[') => self.build_array(),
+            Some(b'"') => self.build_string().map(JsonData::String),
+            _ => match self.parse_value()? {
+                LazyJsonValue::Boolean(value) => Ok(JsonData::Bool(value)),
+                LazyJsonValue::Null => Ok(JsonData::Null),
+                LazyJsonValue::NumberSlice(bytes) => Self::number_data(bytes),
+                other => Err(DomainError::InvalidInput(format!(
+                    "Unexpected {:?} value",
+                    other.value_type()
+                ))),
+            },
+        }
+    }
+
+    /// Build an object, checking depth and key count as members are read
+    fn build_object(&mut self) -> DomainResult<JsonData> {
+        self.enter_container()?;
+
+        let mut object = HashMap::new();
+        self.skip_whitespace();
+        if self.input.get(self.position) == Some(&b'}') {
+            self.position += 1;
+        } else {
+            loop {
+                self.skip_whitespace();
+                if self.input.get(self.position) != Some(&b'"') {
+                    return Err(DomainError::InvalidInput("Expected string key".to_string()));
+                }
+                let key = self.build_string()?;
+                self.skip_whitespace();
+                self.expect_char(b':')?;
+
+                if !object.contains_key(&key) {
+                    self.validator
+                        .validate_object_keys(object.len() + 1)
+                        .map_err(|e| DomainError::SecurityViolation(e.to_string()))?;
+                }
+                let value = self.build_value()?;
+                object.insert(key, value);
+
+                self.skip_whitespace();
+                match self.input.get(self.position) {
+                    Some(b',') => self.position += 1,
+                    Some(b'}') => {
+                        self.position += 1;
+                        break;
+                    }
+                    _ => {
+                        return Err(DomainError::InvalidInput("Expected ',' or '}'".to_string()));
+                    }
+                }
+            }
+        }
+
+        self.depth -= 1;
+        Ok(JsonData::Object(object))
+    }
+
+    /// Build an array, checking depth and length as elements are read
+    fn build_array(&mut self) -> DomainResult<JsonData> {
+        self.enter_container()?;
+
+        let mut array = Vec::new();
+        self.skip_whitespace();
+        if self.input.get(self.position) == Some(&b']') {
+            self.position += 1;
+        } else {
+            loop {
+                self.validator
+                    .validate_array_length(array.len() + 1)
+                    .map_err(|e| DomainError::SecurityViolation(e.to_string()))?;
+                array.push(self.build_value()?);
+
+                self.skip_whitespace();
+                match self.input.get(self.position) {
+                    Some(b',') => self.position += 1,
+                    Some(b']') => {
+                        self.position += 1;
+                        break;
+                    }
+                    _ => {
+                        return Err(DomainError::InvalidInput("Expected ',' or ']'".to_string()));
+                    }
+                }
+            }
+        }
+
+        self.depth -= 1;
+        Ok(JsonData::Array(array))
+    }
+
+    /// Step past the opening bracket of a container one level deeper
+    fn enter_container(&mut self) -> DomainResult<()> {
+        self.validator
+            .validate_json_depth(self.depth + 1)
+            .map_err(|e| DomainError::SecurityViolation(e.to_string()))?;
+        self.position += 1;
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Build a string, checking its length before copying it out of the input
+    fn build_string(&mut self) -> DomainResult<String> {
+        let check_length = |validator: &SecurityValidator, length: usize| {
+            validator
+                .validate_string_length(length)
+                .map_err(|e| DomainError::SecurityViolation(e.to_string()))
+        };
+
+        match self.parse_string()? {
+            LazyJsonValue::StringBorrowed(bytes) => {
+                check_length(&self.validator, bytes.len())?;
+                from_utf8(bytes)
+                    .map(str::to_owned)
+                    .map_err(|e| DomainError::InvalidInput(format!("Invalid UTF-8: {e}")))
+            }
+            LazyJsonValue::StringOwned(string) => {
+                check_length(&self.validator, string.len())?;
+                Ok(string)
+            }
+            _ => Err(DomainError::InvalidInput("Expected string".to_string())),
+        }
+    }
+
+    /// Convert a number slice to an integer or finite float
+    fn number_data(bytes: &[u8]) -> DomainResult<JsonData> {
+        let text = from_utf8(bytes)
+            .map_err(|e| DomainError::InvalidInput(format!("Invalid UTF-8: {e}")))?;
+        if !bytes.iter().any(|b| matches!(b, b'.' | b'e' | b'E'))
+            && let Ok(integer) = text.parse::<i64>()
+        {
+            return Ok(JsonData::Integer(integer));
+        }
+        let float = text
+            .parse::<f64>()
+            .map_err(|e| DomainError::InvalidInput(format!("Invalid number: {e}")))?;
+        JsonData::float(float)
+    }
+
     /// Skip whitespace characters
     fn skip_whitespace(&mut self) {
         while self.position < self.input.len() {
@@ -1127,4 +1305,74 @@ mod tests {
         assert_eq!(usage2.efficiency(), 0.0);
         assert!(usage2.allocated_bytes > 0);
     }
+
+    fn json_limits(
+        configure: impl FnOnce(&mut crate::config::security::JsonLimits),
+    ) -> SecurityConfig {
+        let mut config = SecurityConfig::default();
+        configure(&mut config.json);
+        config
+    }
+
+    #[test]
+    fn test_parse_json_data_matches_serde() {
+        let input =
+            br#" {"id": 7, "name": "caf\u00e9 \"bar\"", "ratio": 0.5, "big": 18446744073709551615,
+            "exp": 1e3, "tags": ["a", true, null, -3], "nested": {"empty": {}, "list": []}} "#;
+
+        let data = ZeroCopyParser::new().parse_json_data(input).unwrap();
+        let expected: JsonData = serde_json::from_slice(input).unwrap();
+        assert_eq!(data, expected);
+        assert_eq!(data.get("id"), Some(&JsonData::Integer(7)));
+        assert_eq!(data.get("exp"), Some(&JsonData::Float(1000.0)));
+    }
+
+    #[test]
+    fn test_parse_json_data_rejects_malformed_input() {
+        for input in [
+            &b"{\"a\": 1} x"[..],
+            b"{\"a\" 1}",
+            b"{1: 2}",
+            b"[1, 2",
+            b"[1 2]",
+            b"\"\xff\"",
+            b"1e999",
+            b"",
+        ] {
+            let result = ZeroCopyParser::new().parse_json_data(input);
+            assert!(
+                matches!(result, Err(DomainError::InvalidInput(_))),
+                "{:?} parsed as {result:?}",
+                String::from_utf8_lossy(input)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_json_data_enforces_limits_while_parsing() {
+        let cases: [(SecurityConfig, &[u8]); 5] = [
+            (json_limits(|l| l.max_input_size = 8), br#"{"a": "long"}"#),
+            (json_limits(|l| l.max_depth = 2), b"[[[1]]]"),
+            (json_limits(|l| l.max_array_length = 2), b"[1, 2, 3]"),
+            (
+                json_limits(|l| l.max_object_keys = 1),
+                br#"{"a": 1, "b": 2}"#,
+            ),
+            (json_limits(|l| l.max_string_length = 3), br#"["abcd"]"#),
+        ];
+        for (config, input) in cases {
+            let result = ZeroCopyParser::with_security_config(config).parse_json_data(input);
+            assert!(
+                matches!(result, Err(DomainError::SecurityViolation(_))),
+                "{:?} parsed as {result:?}",
+                String::from_utf8_lossy(input)
+            );
+        }
+
+        // Repeated keys replace each other and do not count twice.
+        let data = ZeroCopyParser::with_security_config(json_limits(|l| l.max_object_keys = 1))
+            .parse_json_data(br#"{"a": 1, "a": 2}"#)
+            .unwrap();
+        assert_eq!(data.get("a"), Some(&JsonData::Integer(2)));
+    }
 }
@@ -7,6 +7,7 @@ use crate::{
 
 pub mod compression_bomb;
 pub mod rate_limit;
+pub mod rate_limit_store;
 
 pub use compression_bomb::{
     CompressionBombConfig, CompressionBombDetector, CompressionBombError, CompressionBombProtector,
@@ -15,6 +16,10 @@ pub use compression_bomb::{
 pub use rate_limit::{
     RateLimitConfig, RateLimitError, RateLimitGuard, RateLimitStats, WebSocketRateLimiter,
 };
+pub use rate_limit_store::{
+    InMemoryRateLimitStore, InProcessBackend, RateLimitAlgorithm, RateLimitDecision, RateLimitKey,
+    RateLimitPolicy, RateLimitStore, RateLimitStoreError, SharedRateLimitStore, SharedStateBackend,
+};
 
 /// Security validator with configuration-based limits
 #[derive(Debug, Clone)]
@@ -48,6 +48,7 @@ pub enum CompressionBombError {
 /// brotli) can exceed 200x; increase this field if you see false positives. Default is 300.0
 /// which is permissive enough for real brotli/gzip workloads while still catching true bombs.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct CompressionBombConfig {
     /// Maximum allowed compression ratio (decompressed_size / compressed_size).
     /// This is a security parameter — see struct-level note.
@@ -268,6 +269,11 @@ impl CompressionBombDetector {
         Self { config }
     }
 
+    /// The limits this detector enforces.
+    pub fn config(&self) -> &CompressionBombConfig {
+        &self.config
+    }
+
     /// Validate compressed input size before decompression.
     ///
     /// Rejects inputs whose compressed size exceeds `max_compressed_size`. This guards against
@@ -1,6 +1,8 @@
 //! Rate limiting system for WebSocket connections to prevent DoS attacks
 
+use crate::config::security::RateLimitingConfig;
 use dashmap::DashMap;
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::{
     net::IpAddr,
@@ -129,6 +131,26 @@ impl Default for RateLimitConfig {
 }
 
 impl RateLimitConfig {
+    /// Overlay the per-client limits from a [`RateLimitingConfig`] onto this
+    /// configuration.
+    ///
+    /// Only the request window, connection, message-rate and burst limits are
+    /// taken from `limits`; `max_frame_size` and `write_timeout` are kept,
+    /// since they are not part of the file-level `security.network.rate_limiting`
+    /// section. `RateLimitingConfig::enabled` is not represented here — whether
+    /// a limiter is installed at all is decided by the embedding application
+    /// at startup, which is why
+    /// [`ReloadableConfig::apply`](crate::config::ReloadableConfig::apply)
+    /// refuses to change it.
+    pub fn with_rate_limiting(mut self, limits: &RateLimitingConfig) -> Self {
+        self.max_requests_per_window = limits.max_requests_per_window;
+        self.window_duration = Duration::from_secs(limits.window_duration_secs);
+        self.max_connections_per_ip = limits.max_connections_per_ip;
+        self.max_messages_per_second = limits.max_messages_per_second;
+        self.burst_allowance = limits.burst_allowance;
+        self
+    }
+
     /// Configuration for high-traffic scenarios
     pub fn high_traffic() -> Self {
         Self {
@@ -226,7 +248,10 @@ impl ClientRateLimit {
 /// Rate limiter for WebSocket connections
 #[derive(Debug)]
 pub struct WebSocketRateLimiter {
-    config: RateLimitConfig,
+    /// Behind a lock so [`Self::update_config`] can swap limits in place
+    /// (hot-reload via `config::ReloadableConfig`) without dropping the
+    /// per-client state accumulated in `clients`.
+    config: RwLock<RateLimitConfig>,
     clients: Arc<DashMap<IpAddr, ClientRateLimit>>,
     /// Guards [`WebSocketRateLimiter::spawn_cleanup_task`] so it spawns at
     /// most one background task per limiter even if called repeatedly (e.g.
@@ -252,15 +277,30 @@ impl WebSocketRateLimiter {
     /// Create new rate limiter with configuration
     pub fn new(config: RateLimitConfig) -> Self {
         Self {
-            config,
+            config: RwLock::new(config),
             clients: Arc::new(DashMap::new()),
             cleanup_spawned: AtomicBool::new(false),
         }
     }
 
-    /// Returns the rate-limit configuration this limiter was constructed with.
-    pub fn config(&self) -> &RateLimitConfig {
-        &self.config
+    /// Returns a snapshot of the rate-limit configuration currently in force.
+    ///
+    /// This is the configuration the limiter was constructed with unless a
+    /// later [`Self::update_config`] replaced it.
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.read().clone()
+    }
+
+    /// Replace the configuration in force for every subsequent check.
+    ///
+    /// Per-client state (request timestamps, connection counts, message
+    /// tokens) is kept: a lowered `max_requests_per_window` applies to the
+    /// requests already recorded in the current window, and a token bucket
+    /// holding more tokens than the new burst ceiling is clamped on its next
+    /// refill rather than immediately. Live connections are never closed by
+    /// lowering `max_connections_per_ip`; only new ones are refused.
+    pub fn update_config(&self, config: RateLimitConfig) {
+        *self.config.write() = config;
     }
 
     /// Returns the number of requests still permitted for `ip` within the
@@ -281,21 +321,20 @@ impl WebSocketRateLimiter {
     /// ([`Self::check_connection`]) or message-rate ([`Self::check_message`])
     /// limits.
     pub fn remaining_for(&self, ip: IpAddr) -> u32 {
+        let config = self.config();
         let Some(client) = self.clients.get(&ip) else {
-            return self.config.max_requests_per_window;
+            return config.max_requests_per_window;
         };
 
         let now = Instant::now();
-        let window_start = now.checked_sub(self.config.window_duration);
+        let window_start = now.checked_sub(config.window_duration);
         let used = client
             .requests
             .iter()
             .filter(|&&t| window_start.is_none_or(|start| t > start))
             .count();
 
-        self.config
-            .max_requests_per_window
-            .saturating_sub(used as u32)
+        config.max_requests_per_window.saturating_sub(used as u32)
     }
 
     /// Returns the duration until `ip`'s rate-limit window next admits at
@@ -318,21 +357,21 @@ impl WebSocketRateLimiter {
     /// `Duration::ZERO` (quota already fully available) for a client that
     /// is, in reality, still within its window.
     pub fn reset_after(&self, ip: IpAddr) -> Duration {
+        let window_duration = self.config.read().window_duration;
         let Some(client) = self.clients.get(&ip) else {
             return Duration::ZERO;
         };
 
         let now = Instant::now();
-        let Some(window_start) = now.checked_sub(self.config.window_duration) else {
-            return self.config.window_duration;
+        let Some(window_start) = now.checked_sub(window_duration) else {
+            return window_duration;
         };
         let earliest_active = client.requests.iter().find(|&&t| t > window_start);
 
         match earliest_active {
-            Some(&earliest) => self
-                .config
-                .window_duration
-                .saturating_sub(now.saturating_duration_since(earliest)),
+            Some(&earliest) => {
+                window_duration.saturating_sub(now.saturating_duration_since(earliest))
+            }
             None => Duration::ZERO,
         }
     }
@@ -410,8 +449,9 @@ impl WebSocketRateLimiter {
             });
         }
 
+        let config = self.config();
         let now = Instant::now();
-        let burst = self.config.burst_allowance;
+        let burst = config.burst_allowance;
         let mut client = self
             .clients
             .entry(ip)
@@ -442,15 +482,15 @@ impl WebSocketRateLimiter {
         // (self-limiting) duration this condition holds; once real uptime
         // exceeds `window_duration`, `checked_sub` succeeds again and
         // trimming resumes, catching up on the backlog in one pass.
-        if let Some(window_start) = now.checked_sub(self.config.window_duration) {
+        if let Some(window_start) = now.checked_sub(config.window_duration) {
             client.requests.retain(|&time| time > window_start);
         }
 
         // Check request rate limit
-        if client.requests.len() >= self.config.max_requests_per_window as usize {
+        if client.requests.len() >= config.max_requests_per_window as usize {
             return Err(RateLimitError::LimitExceeded {
-                limit: self.config.max_requests_per_window,
-                window: self.config.window_duration,
+                limit: config.max_requests_per_window,
+                window: config.window_duration,
             });
         }
 
@@ -467,16 +507,17 @@ impl WebSocketRateLimiter {
             });
         }
 
-        let burst = self.config.burst_allowance;
+        let config = self.config();
+        let burst = config.burst_allowance;
         let mut client = self
             .clients
             .entry(ip)
             .or_insert_with(|| ClientRateLimit::new(burst));
 
-        if client.connection_count >= self.config.max_connections_per_ip {
+        if client.connection_count >= config.max_connections_per_ip {
             return Err(RateLimitError::ConnectionLimitExceeded {
                 current: client.connection_count,
-                max: self.config.max_connections_per_ip,
+                max: config.max_connections_per_ip,
             });
         }
 
@@ -493,17 +534,19 @@ impl WebSocketRateLimiter {
 
     /// Check if WebSocket message is allowed
     pub fn check_message(&self, ip: IpAddr, frame_size: usize) -> Result<(), RateLimitError> {
+        let config = self.config();
+
         // Check frame size
-        if frame_size > self.config.max_frame_size {
+        if frame_size > config.max_frame_size {
             return Err(RateLimitError::FrameSizeExceeded {
                 size: frame_size,
-                max: self.config.max_frame_size,
+                max: config.max_frame_size,
             });
         }
 
         // Check message rate
         if let Some(mut client) = self.clients.get_mut(&ip) {
-            client.check_message_rate(&self.config)?;
+            client.check_message_rate(&config)?;
         }
 
         Ok(())
@@ -534,7 +577,8 @@ impl WebSocketRateLimiter {
         // `Instant` and panics, permanently killing whichever loop calls
         // this. Skip this pass instead — the next sweep, once enough
         // wall-clock time has elapsed, will succeed.
-        let Some(cutoff) = now.checked_sub(self.config.window_duration * 2) else {
+        let window_duration = self.config.read().window_duration;
+        let Some(cutoff) = now.checked_sub(window_duration * 2) else {
             return;
         };
 
@@ -627,6 +671,53 @@ mod tests {
         assert!(limiter.check_request(ip).is_ok());
     }
 
+    #[test]
+    fn test_update_config_applies_to_existing_clients() {
+        let limiter = WebSocketRateLimiter::new(RateLimitConfig {
+            max_requests_per_window: 1,
+            window_duration: Duration::from_secs(60),
+            ..Default::default()
+        });
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+
+        assert!(limiter.check_request(ip).is_ok());
+        assert!(limiter.check_request(ip).is_err());
+
+        limiter.update_config(RateLimitConfig {
+            max_requests_per_window: 3,
+            ..limiter.config()
+        });
+
+        // The request already recorded still counts against the raised limit.
+        assert_eq!(limiter.remaining_for(ip), 2);
+        assert!(limiter.check_request(ip).is_ok());
+        assert!(limiter.check_request(ip).is_ok());
+        assert!(limiter.check_request(ip).is_err());
+    }
+
+    #[test]
+    fn test_with_rate_limiting_keeps_frame_size_and_write_timeout() {
+        let base = RateLimitConfig::low_resource();
+        let limits = RateLimitingConfig {
+            max_requests_per_window: 7,
+            window_duration_secs: 5,
+            max_connections_per_ip: 3,
+            max_messages_per_second: 11,
+            burst_allowance: 4,
+            enabled: true,
+        };
+
+        let merged = base.clone().with_rate_limiting(&limits);
+
+        assert_eq!(merged.max_requests_per_window, 7);
+        assert_eq!(merged.window_duration, Duration::from_secs(5));
+        assert_eq!(merged.max_connections_per_ip, 3);
+        assert_eq!(merged.max_messages_per_second, 11);
+        assert_eq!(merged.burst_allowance, 4);
+        assert_eq!(merged.max_frame_size, base.max_frame_size);
+        assert_eq!(merged.write_timeout, base.write_timeout);
+    }
+
     #[test]
     fn test_connection_limits() {
         let config = RateLimitConfig {
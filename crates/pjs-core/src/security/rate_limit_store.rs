@@ -0,0 +1,1130 @@
+//! Pluggable request-budget storage shared by HTTP and WebSocket rate limiting.
+//!
+//! [`WebSocketRateLimiter`](super::rate_limit::WebSocketRateLimiter) keeps its
+//! counters in a process-local map, so behind a load balancer with `N`
+//! replicas every client effectively gets `N` times the configured budget.
+//! This module factors the *request budget* out behind the
+//! [`RateLimitStore`] port so the counters can live wherever the deployment
+//! needs them:
+//!
+//! - [`InMemoryRateLimitStore`] — process-local, for single-replica
+//!   deployments and tests;
+//! - [`SharedRateLimitStore`] — counters kept in a [`SharedStateBackend`]
+//!   every replica talks to, updated with optimistic compare-and-swap so
+//!   concurrent replicas never double-spend a slot. [`InProcessBackend`] is
+//!   an in-process stand-in (useful in tests and to share one budget between
+//!   several limiters in one binary); `RedisBackend` (feature
+//!   `rate-limit-redis`) is the production backend.
+//!
+//! Two algorithms are supported, see [`RateLimitAlgorithm`]. Both keep O(1)
+//! state per key and are evaluated by the same pure functions regardless of
+//! the store, so an in-memory and a shared store make identical decisions for
+//! identical traffic.
+//!
+//! Clients are identified by [`RateLimitKey`]: their IP address, or — for
+//! callers presenting an API key registered in the [`RateLimitPolicy`] — the
+//! API key itself, so a key's budget is shared across every address it is
+//! used from.
+//!
+//! Connection caps, frame sizes and per-connection message rates stay with
+//! [`WebSocketRateLimiter`](super::rate_limit::WebSocketRateLimiter): they
+//! describe resources of *this* process (open sockets, buffers), so they are
+//! correctly process-local.
+
+use dashmap::{DashMap, mapref::entry::Entry};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    net::IpAddr,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+use thiserror::Error;
+
+use super::rate_limit::MAX_TRACKED_CLIENTS;
+
+#[cfg(feature = "rate-limit-redis")]
+mod redis;
+
+#[cfg(feature = "rate-limit-redis")]
+pub use self::redis::{REDIS_TIMEOUT, RedisBackend};
+
+/// Request header carrying the caller's API key.
+///
+/// The same header [`ApiKeyAuthLayer`](crate::infrastructure::http::auth::ApiKeyAuthLayer)
+/// authenticates, so a client needs no extra configuration to be limited
+/// per key instead of per address.
+pub const API_KEY_HEADER: &str = "x-pjs-api-key";
+
+/// Default prefix prepended to every key [`SharedRateLimitStore`] writes, so
+/// rate-limit state can share a Redis database with other data.
+pub const DEFAULT_KEY_PREFIX: &str = "pjs:ratelimit:";
+
+/// Default number of compare-and-swap rounds [`SharedRateLimitStore::check`]
+/// attempts before giving up with [`RateLimitStoreError::Contention`].
+///
+/// A round only fails when another replica updated the *same key* between
+/// the load and the swap, so even a hot key rarely needs more than a few.
+pub const DEFAULT_MAX_CAS_ATTEMPTS: u32 = 16;
+
+/// Errors raised while consulting a [`RateLimitStore`].
+///
+/// None of these mean the client exceeded its budget — that is a successful
+/// check returning a denied [`RateLimitDecision`]. Callers decide whether a
+/// store failure admits or rejects the request; see
+/// [`RateLimitPolicy::with_fail_open`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitStoreError {
+    /// The backend could not be reached or returned an error.
+    #[error("Rate limit backend error: {0}")]
+    Backend(String),
+
+    /// Every compare-and-swap round lost to a concurrent update of the same
+    /// key.
+    #[error("Rate limit state contended: gave up after {attempts} attempts")]
+    Contention {
+        /// Number of rounds attempted.
+        attempts: u32,
+    },
+
+    /// The store already tracks its maximum number of keys; a not-yet-tracked
+    /// key is rejected rather than evicting an established one. See
+    /// [`MAX_TRACKED_CLIENTS`] for why this rejects instead of evicting.
+    #[error("Rate limit store at capacity: {max} tracked keys")]
+    CapacityExceeded {
+        /// Configured maximum number of tracked keys.
+        max: usize,
+    },
+}
+
+/// Identity a request budget is charged to.
+///
+/// API keys are stored as a SHA-256 digest, never verbatim: storage keys show
+/// up in `KEYS`/`MONITOR` output, backups and metrics of the shared backend,
+/// and the rate limiter has no business spreading credentials there.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    /// Budget charged per client address.
+    Ip(IpAddr),
+    /// Budget charged per API key; holds the hex SHA-256 digest of the key.
+    ApiKey(String),
+}
+
+impl RateLimitKey {
+    /// Key for a client address. IPv4-mapped IPv6 addresses are canonicalised
+    /// so a dual-stack listener charges the same bucket as an IPv4 one.
+    pub fn ip(ip: IpAddr) -> Self {
+        Self::Ip(ip.to_canonical())
+    }
+
+    /// Key for a raw API key, digested with SHA-256.
+    pub fn api_key(api_key: &str) -> Self {
+        Self::ApiKey(hex_digest(api_key))
+    }
+
+    /// Backend-independent string form, e.g. `ip:203.0.113.7` or
+    /// `key:<sha256-hex>`.
+    pub fn storage_key(&self) -> String {
+        match self {
+            Self::Ip(ip) => format!("ip:{ip}"),
+            Self::ApiKey(digest) => format!("key:{digest}"),
+        }
+    }
+}
+
+impl fmt::Display for RateLimitKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.storage_key())
+    }
+}
+
+fn hex_digest(input: &str) -> String {
+    use std::fmt::Write;
+
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+}
+
+/// How a request budget is measured.
+///
+/// Both algorithms keep a constant amount of state per key, which is what
+/// makes them practical in a shared backend: an exact sliding log like
+/// [`WebSocketRateLimiter::check_request`](super::rate_limit::WebSocketRateLimiter::check_request)
+/// stores one timestamp per request and would need every replica to rewrite
+/// the whole log on each request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitAlgorithm {
+    /// Sliding-window counter: at most `limit` requests in any `window`.
+    ///
+    /// Counts requests in fixed windows aligned to the Unix epoch and
+    /// estimates the sliding count as `current + previous * overlap`, where
+    /// `overlap` is the fraction of the previous window still inside the
+    /// sliding one. This is an approximation — it assumes the previous
+    /// window's requests were evenly spread — but it never admits more than
+    /// `limit` requests in the *current* fixed window and smooths the
+    /// boundary burst a plain fixed window allows.
+    SlidingWindow {
+        /// Maximum requests per window.
+        limit: u32,
+        /// Window length; clamped to at least one millisecond.
+        window: Duration,
+    },
+
+    /// Token bucket: bursts of up to `capacity` requests, refilled at one
+    /// token per `refill_interval`.
+    TokenBucket {
+        /// Bucket size, i.e. the largest burst admitted after idling.
+        capacity: u32,
+        /// Time to regenerate one token; clamped to at least one millisecond.
+        refill_interval: Duration,
+    },
+}
+
+impl RateLimitAlgorithm {
+    /// Sliding-window counter admitting `limit` requests per `window`.
+    pub fn sliding_window(limit: u32, window: Duration) -> Self {
+        Self::SlidingWindow { limit, window }
+    }
+
+    /// Token bucket holding `capacity` tokens and sustaining
+    /// `requests_per_window` requests every `window`.
+    ///
+    /// A zero `requests_per_window` yields a bucket that refills once per
+    /// `window`, rather than never, so a misconfiguration does not lock a
+    /// client out permanently.
+    pub fn token_bucket(capacity: u32, requests_per_window: u32, window: Duration) -> Self {
+        Self::TokenBucket {
+            capacity,
+            refill_interval: window / requests_per_window.max(1),
+        }
+    }
+
+    /// Requests admitted per window (sliding window) or burst size (token
+    /// bucket); reported to clients as `X-RateLimit-Limit`.
+    pub fn limit(&self) -> u32 {
+        match self {
+            Self::SlidingWindow { limit, .. } => *limit,
+            Self::TokenBucket { capacity, .. } => *capacity,
+        }
+    }
+
+    /// How long state for an idle key stays meaningful; after this the key
+    /// behaves exactly as if it had never been seen, so stores may drop it.
+    pub fn state_ttl(&self) -> Duration {
+        Duration::from_millis(self.state_ttl_ms())
+    }
+
+    fn state_ttl_ms(&self) -> u64 {
+        match self {
+            Self::SlidingWindow { window, .. } => clamp_ms(*window).saturating_mul(2),
+            Self::TokenBucket {
+                capacity,
+                refill_interval,
+            } => clamp_ms(*refill_interval)
+                .saturating_mul(u64::from(*capacity))
+                .max(1),
+        }
+    }
+}
+
+/// Outcome of a single [`RateLimitStore::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    /// Whether the request fits the budget. An admitted request has already
+    /// been charged.
+    pub allowed: bool,
+    /// [`RateLimitAlgorithm::limit`] of the algorithm applied.
+    pub limit: u32,
+    /// Requests still admissible right now.
+    pub remaining: u32,
+    /// Time until the budget is fully restored if the client stops sending.
+    pub reset_after: Duration,
+    /// For a denied request, time until the next request would be admitted;
+    /// zero when `allowed`.
+    pub retry_after: Duration,
+}
+
+/// Per-key algorithm state, serialised as JSON by [`SharedRateLimitStore`].
+///
+/// Timestamps are wall-clock Unix milliseconds rather than [`Instant`]s so
+/// every replica reads the same clock; replicas with skewed clocks see
+/// slightly shifted windows but cannot reset each other's counters (see
+/// [`evaluate`]).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "t", rename_all = "snake_case")]
+enum AlgorithmState {
+    SlidingWindow {
+        window_start_ms: u64,
+        current: u32,
+        previous: u32,
+    },
+    TokenBucket {
+        tokens: f64,
+        updated_ms: u64,
+    },
+}
+
+fn clamp_ms(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis())
+        .unwrap_or(u64::MAX)
+        .max(1)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// Charge one request against `state` at wall-clock time `now_ms`.
+///
+/// Returns the state to store and the decision. State produced by a
+/// different algorithm (the policy changed since it was written) is
+/// discarded. State timestamped *ahead* of `now_ms` — written by a replica
+/// whose clock runs fast — is kept rather than treated as stale, so clock
+/// skew can shift a window but never hand a client a fresh budget.
+fn evaluate(
+    algorithm: &RateLimitAlgorithm,
+    state: Option<AlgorithmState>,
+    now_ms: u64,
+) -> (AlgorithmState, RateLimitDecision) {
+    match *algorithm {
+        RateLimitAlgorithm::SlidingWindow { limit, window } => {
+            evaluate_sliding_window(limit, clamp_ms(window), state, now_ms)
+        }
+        RateLimitAlgorithm::TokenBucket {
+            capacity,
+            refill_interval,
+        } => evaluate_token_bucket(capacity, clamp_ms(refill_interval), state, now_ms),
+    }
+}
+
+fn evaluate_sliding_window(
+    limit: u32,
+    window_ms: u64,
+    state: Option<AlgorithmState>,
+    now_ms: u64,
+) -> (AlgorithmState, RateLimitDecision) {
+    let aligned_start = now_ms - now_ms % window_ms;
+
+    let (window_start_ms, mut current, previous) = match state {
+        Some(AlgorithmState::SlidingWindow {
+            window_start_ms,
+            current,
+            previous,
+        }) if window_start_ms >= aligned_start => (window_start_ms, current, previous),
+        Some(AlgorithmState::SlidingWindow {
+            window_start_ms,
+            current,
+            ..
+        }) if window_start_ms.saturating_add(window_ms) == aligned_start => {
+            (aligned_start, 0, current)
+        }
+        _ => (aligned_start, 0, 0),
+    };
+
+    let elapsed_ms = now_ms.saturating_sub(window_start_ms).min(window_ms);
+    let overlap = (window_ms - elapsed_ms) as f64 / window_ms as f64;
+    let estimate = f64::from(previous) * overlap + f64::from(current);
+    let allowed = estimate + 1.0 <= f64::from(limit);
+
+    if allowed {
+        current = current.saturating_add(1);
+    }
+    let used = f64::from(previous) * overlap + f64::from(current);
+    let remaining = (f64::from(limit) - used).max(0.0).floor() as u32;
+
+    // Everything counted so far has left the sliding window once the window
+    // after the current one has fully elapsed.
+    let reset_after_ms = window_start_ms
+        .saturating_add(window_ms.saturating_mul(2))
+        .saturating_sub(now_ms);
+
+    let retry_after_ms = if allowed {
+        0
+    } else if limit == 0 {
+        reset_after_ms
+    } else if u64::from(current) + 1 > u64::from(limit) {
+        // The current window alone is full: wait for it to end, then until
+        // its share of the next sliding window decays enough to fit one more.
+        let until_next = window_start_ms
+            .saturating_add(window_ms)
+            .saturating_sub(now_ms);
+        let decay = window_ms as f64 * (1.0 - f64::from(limit - 1) / f64::from(current));
+        until_next.saturating_add(decay.ceil() as u64)
+    } else {
+        // Only the previous window's tail is in the way: wait until enough
+        // of it has slid out.
+        let headroom = f64::from(limit - 1 - current) / f64::from(previous);
+        let target_elapsed = (window_ms as f64 * (1.0 - headroom)).ceil() as u64;
+        target_elapsed.saturating_sub(elapsed_ms).max(1)
+    };
+
+    (
+        AlgorithmState::SlidingWindow {
+            window_start_ms,
+            current,
+            previous,
+        },
+        RateLimitDecision {
+            allowed,
+            limit,
+            remaining,
+            reset_after: Duration::from_millis(reset_after_ms),
+            retry_after: Duration::from_millis(retry_after_ms),
+        },
+    )
+}
+
+fn evaluate_token_bucket(
+    capacity: u32,
+    refill_interval_ms: u64,
+    state: Option<AlgorithmState>,
+    now_ms: u64,
+) -> (AlgorithmState, RateLimitDecision) {
+    let capacity_f = f64::from(capacity);
+
+    let (mut tokens, updated_ms) = match state {
+        Some(AlgorithmState::TokenBucket { tokens, updated_ms }) if tokens.is_finite() => {
+            (tokens.clamp(0.0, capacity_f), updated_ms)
+        }
+        _ => (capacity_f, now_ms),
+    };
+
+    let elapsed_ms = now_ms.saturating_sub(updated_ms);
+    tokens = (tokens + elapsed_ms as f64 / refill_interval_ms as f64).min(capacity_f);
+
+    let allowed = tokens >= 1.0;
+    if allowed {
+        tokens -= 1.0;
+    }
+
+    let ms_for = |missing: f64| (missing.max(0.0) * refill_interval_ms as f64).ceil() as u64;
+    let retry_after_ms = if allowed {
+        0
+    } else {
+        ms_for(1.0 - tokens).max(1)
+    };
+
+    (
+        AlgorithmState::TokenBucket {
+            tokens,
+            updated_ms: updated_ms.max(now_ms),
+        },
+        RateLimitDecision {
+            allowed,
+            limit: capacity,
+            remaining: tokens.floor() as u32,
+            reset_after: Duration::from_millis(ms_for(capacity_f - tokens)),
+            retry_after: Duration::from_millis(retry_after_ms),
+        },
+    )
+}
+
+/// Boxed future returned by [`RateLimitStore::check`] and
+/// [`SharedStateBackend`] operations.
+pub type RateLimitStoreFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, RateLimitStoreError>> + Send + 'a>>;
+
+/// Port for wherever request budgets are kept.
+///
+/// Dyn-compatible (boxed futures rather than a GAT port) because the HTTP
+/// middleware and the WebSocket transport hold it as `Arc<dyn RateLimitStore>`
+/// chosen at start-up from configuration; one allocation per request is
+/// negligible next to the backend round-trip a shared store makes anyway.
+pub trait RateLimitStore: Send + Sync + fmt::Debug {
+    /// Charge one request for `key` under `algorithm` and report whether it
+    /// fits the budget.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimitStoreError`] if the budget could not be consulted;
+    /// an exhausted budget is `Ok` with [`RateLimitDecision::allowed`] unset.
+    fn check<'a>(
+        &'a self,
+        key: &'a RateLimitKey,
+        algorithm: &'a RateLimitAlgorithm,
+    ) -> RateLimitStoreFuture<'a, RateLimitDecision>;
+}
+
+/// Which key and algorithm a request is charged under.
+///
+/// Requests are charged per client address under the default algorithm.
+/// A request presenting one of the API keys registered with
+/// [`Self::with_api_key`] is charged to that key instead, under the key's own
+/// algorithm — so a partner's budget follows their key across addresses and
+/// replicas.
+///
+/// **Only registered keys get their own bucket.** The rate limiter runs in
+/// front of authentication, so an unregistered header value proves nothing;
+/// keying on it would let a client mint a fresh budget per request by
+/// sending random keys. Unregistered or missing keys fall back to the
+/// address.
+#[derive(Clone)]
+pub struct RateLimitPolicy {
+    default_algorithm: RateLimitAlgorithm,
+    api_keys: HashMap<String, RateLimitAlgorithm>,
+    fail_open: bool,
+}
+
+impl RateLimitPolicy {
+    /// Charge every client per address under `default_algorithm`.
+    pub fn new(default_algorithm: RateLimitAlgorithm) -> Self {
+        Self {
+            default_algorithm,
+            api_keys: HashMap::new(),
+            fail_open: false,
+        }
+    }
+
+    /// Give requests presenting `api_key` their own budget under `algorithm`.
+    ///
+    /// Only the SHA-256 digest of `api_key` is retained.
+    pub fn with_api_key(mut self, api_key: &str, algorithm: RateLimitAlgorithm) -> Self {
+        self.api_keys.insert(hex_digest(api_key), algorithm);
+        self
+    }
+
+    /// Admit requests when the store fails instead of rejecting them with
+    /// `503`.
+    ///
+    /// Off by default: an unreachable backend must not silently disable rate
+    /// limiting. Enable it when availability matters more than the limit and
+    /// a per-connection safeguard (e.g. the WebSocket message rate) remains.
+    pub fn with_fail_open(mut self, fail_open: bool) -> Self {
+        self.fail_open = fail_open;
+        self
+    }
+
+    /// Algorithm applied to address-keyed requests.
+    pub fn default_algorithm(&self) -> &RateLimitAlgorithm {
+        &self.default_algorithm
+    }
+
+    /// Whether store failures admit requests; see [`Self::with_fail_open`].
+    pub fn fail_open(&self) -> bool {
+        self.fail_open
+    }
+
+    /// Resolve the key and algorithm for a request from `ip`, optionally
+    /// presenting `api_key`.
+    pub fn resolve(
+        &self,
+        ip: IpAddr,
+        api_key: Option<&str>,
+    ) -> (RateLimitKey, &RateLimitAlgorithm) {
+        if let Some(api_key) = api_key {
+            let digest = hex_digest(api_key);
+            if let Some(algorithm) = self.api_keys.get(&digest) {
+                return (RateLimitKey::ApiKey(digest), algorithm);
+            }
+        }
+        (RateLimitKey::ip(ip), &self.default_algorithm)
+    }
+}
+
+impl fmt::Debug for RateLimitPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimitPolicy")
+            .field("default_algorithm", &self.default_algorithm)
+            .field("api_keys", &self.api_keys.len())
+            .field("fail_open", &self.fail_open)
+            .finish()
+    }
+}
+
+/// Process-local [`RateLimitStore`].
+///
+/// Bounded to [`MAX_TRACKED_CLIENTS`] keys with the same reject-new policy as
+/// [`WebSocketRateLimiter`](super::rate_limit::WebSocketRateLimiter). Instead
+/// of a background sweep, expired keys are pruned when the table is full, so
+/// a not-yet-tracked key is only rejected if every tracked key is still live.
+#[derive(Debug)]
+pub struct InMemoryRateLimitStore {
+    entries: DashMap<RateLimitKey, (AlgorithmState, u64)>,
+    max_keys: usize,
+}
+
+impl InMemoryRateLimitStore {
+    /// Store bounded to [`MAX_TRACKED_CLIENTS`] keys.
+    pub fn new() -> Self {
+        Self {
+            entries: DashMap::new(),
+            max_keys: MAX_TRACKED_CLIENTS,
+        }
+    }
+
+    /// Override the maximum number of tracked keys.
+    pub fn with_max_tracked_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys;
+        self
+    }
+
+    /// Number of keys currently tracked, including expired ones not yet
+    /// pruned.
+    pub fn tracked_keys(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn check_at(
+        &self,
+        key: &RateLimitKey,
+        algorithm: &RateLimitAlgorithm,
+        now_ms: u64,
+    ) -> Result<RateLimitDecision, RateLimitStoreError> {
+        if !self.entries.contains_key(key) && self.entries.len() >= self.max_keys {
+            self.entries
+                .retain(|_, (_, expires_at_ms)| *expires_at_ms > now_ms);
+            if self.entries.len() >= self.max_keys {
+                return Err(RateLimitStoreError::CapacityExceeded { max: self.max_keys });
+            }
+        }
+
+        let expires_at_ms = now_ms.saturating_add(algorithm.state_ttl_ms());
+        let decision = match self.entries.entry(key.clone()) {
+            Entry::Occupied(mut entry) => {
+                let (stored, stored_expiry) = *entry.get();
+                let live = (stored_expiry > now_ms).then_some(stored);
+                let (state, decision) = evaluate(algorithm, live, now_ms);
+                entry.insert((state, expires_at_ms));
+                decision
+            }
+            Entry::Vacant(entry) => {
+                let (state, decision) = evaluate(algorithm, None, now_ms);
+                entry.insert((state, expires_at_ms));
+                decision
+            }
+        };
+        Ok(decision)
+    }
+}
+
+impl Default for InMemoryRateLimitStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RateLimitStore for InMemoryRateLimitStore {
+    fn check<'a>(
+        &'a self,
+        key: &'a RateLimitKey,
+        algorithm: &'a RateLimitAlgorithm,
+    ) -> RateLimitStoreFuture<'a, RateLimitDecision> {
+        Box::pin(std::future::ready(self.check_at(key, algorithm, now_ms())))
+    }
+}
+
+/// Minimal key-value contract [`SharedRateLimitStore`] needs from a shared
+/// backend: an atomic compare-and-swap with expiry.
+///
+/// Values are opaque bytes. Implementations must make
+/// [`Self::compare_and_swap`] atomic with respect to every other replica;
+/// the read in [`Self::load`] need not be.
+pub trait SharedStateBackend: Send + Sync + fmt::Debug {
+    /// Current value under `key`, or `None` if absent or expired.
+    fn load<'a>(&'a self, key: &'a str) -> RateLimitStoreFuture<'a, Option<Vec<u8>>>;
+
+    /// Store `value` under `key`, expiring after `ttl`, if and only if the
+    /// current value equals `expected` (`None` meaning absent or expired).
+    /// Resolves to whether the swap happened.
+    fn compare_and_swap<'a>(
+        &'a self,
+        key: &'a str,
+        expected: Option<&'a [u8]>,
+        value: &'a [u8],
+        ttl: Duration,
+    ) -> RateLimitStoreFuture<'a, bool>;
+}
+
+/// In-process [`SharedStateBackend`]: a mutex-guarded map with expiry.
+///
+/// Stands in for a real shared backend in tests — several
+/// [`SharedRateLimitStore`]s over one `Arc<InProcessBackend>` behave like
+/// replicas sharing a Redis — and lets several limiters in one binary share
+/// a budget. Bounded to [`MAX_TRACKED_CLIENTS`] keys, pruning expired ones
+/// when full.
+#[derive(Debug, Default)]
+pub struct InProcessBackend {
+    entries: Mutex<HashMap<String, (Vec<u8>, Instant)>>,
+}
+
+impl InProcessBackend {
+    /// Empty backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SharedStateBackend for InProcessBackend {
+    fn load<'a>(&'a self, key: &'a str) -> RateLimitStoreFuture<'a, Option<Vec<u8>>> {
+        let now = Instant::now();
+        let value = self
+            .entries
+            .lock()
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(value, _)| value.clone());
+        Box::pin(std::future::ready(Ok(value)))
+    }
+
+    fn compare_and_swap<'a>(
+        &'a self,
+        key: &'a str,
+        expected: Option<&'a [u8]>,
+        value: &'a [u8],
+        ttl: Duration,
+    ) -> RateLimitStoreFuture<'a, bool> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock();
+
+        let current = entries
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(value, _)| value.as_slice());
+        if current != expected {
+            return Box::pin(std::future::ready(Ok(false)));
+        }
+
+        if !entries.contains_key(key) && entries.len() >= MAX_TRACKED_CLIENTS {
+            entries.retain(|_, (_, expires_at)| *expires_at > now);
+            if entries.len() >= MAX_TRACKED_CLIENTS {
+                return Box::pin(std::future::ready(Err(
+                    RateLimitStoreError::CapacityExceeded {
+                        max: MAX_TRACKED_CLIENTS,
+                    },
+                )));
+            }
+        }
+
+        entries.insert(key.to_owned(), (value.to_vec(), now + ttl));
+        Box::pin(std::future::ready(Ok(true)))
+    }
+}
+
+/// [`RateLimitStore`] whose counters live in a [`SharedStateBackend`], so
+/// every replica pointed at the same backend charges the same budget.
+///
+/// Each check loads the key's state, evaluates it locally and writes it back
+/// with [`SharedStateBackend::compare_and_swap`]; if another replica won the
+/// race the round is retried against the fresh state, up to
+/// [`DEFAULT_MAX_CAS_ATTEMPTS`] times. A denied request writes nothing, so
+/// a client hammering an exhausted budget costs one read per request.
+///
+/// State expires after [`RateLimitAlgorithm::state_ttl`], so the backend
+/// never accumulates keys for clients that went away.
+pub struct SharedRateLimitStore<B: ?Sized> {
+    backend: Arc<B>,
+    key_prefix: String,
+    max_attempts: u32,
+}
+
+impl<B: SharedStateBackend + ?Sized> SharedRateLimitStore<B> {
+    /// Store over `backend`, writing keys under [`DEFAULT_KEY_PREFIX`].
+    pub fn new(backend: Arc<B>) -> Self {
+        Self {
+            backend,
+            key_prefix: DEFAULT_KEY_PREFIX.to_owned(),
+            max_attempts: DEFAULT_MAX_CAS_ATTEMPTS,
+        }
+    }
+
+    /// Override the key prefix, e.g. to keep several deployments apart in
+    /// one backend.
+    pub fn with_key_prefix(mut self, key_prefix: impl Into<String>) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    /// Override the number of compare-and-swap rounds per check (minimum 1).
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    async fn check_shared(
+        &self,
+        key: &RateLimitKey,
+        algorithm: &RateLimitAlgorithm,
+    ) -> Result<RateLimitDecision, RateLimitStoreError> {
+        let storage_key = format!("{}{}", self.key_prefix, key.storage_key());
+        let ttl = algorithm.state_ttl();
+
+        for _ in 0..self.max_attempts {
+            let current = self.backend.load(&storage_key).await?;
+            let state = current.as_deref().and_then(|bytes| {
+                serde_json::from_slice(bytes)
+                    .inspect_err(|e| {
+                        tracing::warn!(
+                            "discarding unreadable rate-limit state under {storage_key}: {e}"
+                        );
+                    })
+                    .ok()
+            });
+
+            let (next, decision) = evaluate(algorithm, state, now_ms());
+            if !decision.allowed {
+                return Ok(decision);
+            }
+
+            let encoded = serde_json::to_vec(&next)
+                .map_err(|e| RateLimitStoreError::Backend(e.to_string()))?;
+            if self
+                .backend
+                .compare_and_swap(&storage_key, current.as_deref(), &encoded, ttl)
+                .await?
+            {
+                return Ok(decision);
+            }
+        }
+
+        Err(RateLimitStoreError::Contention {
+            attempts: self.max_attempts,
+        })
+    }
+}
+
+impl<B: SharedStateBackend + ?Sized> RateLimitStore for SharedRateLimitStore<B> {
+    fn check<'a>(
+        &'a self,
+        key: &'a RateLimitKey,
+        algorithm: &'a RateLimitAlgorithm,
+    ) -> RateLimitStoreFuture<'a, RateLimitDecision> {
+        Box::pin(self.check_shared(key, algorithm))
+    }
+}
+
+impl<B: SharedStateBackend + ?Sized> fmt::Debug for SharedRateLimitStore<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedRateLimitStore")
+            .field("backend", &self.backend)
+            .field("key_prefix", &self.key_prefix)
+            .field("max_attempts", &self.max_attempts)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const WINDOW: Duration = Duration::from_secs(10);
+    // Aligned to a window boundary so elapsed-time arithmetic is readable.
+    const T0: u64 = 1_700_000_000_000;
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(203, 0, 113, last))
+    }
+
+    fn run(
+        algorithm: &RateLimitAlgorithm,
+        state: &mut Option<AlgorithmState>,
+        now_ms: u64,
+    ) -> RateLimitDecision {
+        let (next, decision) = evaluate(algorithm, *state, now_ms);
+        *state = Some(next);
+        decision
+    }
+
+    #[test]
+    fn test_sliding_window_admits_limit_then_denies() {
+        let algorithm = RateLimitAlgorithm::sliding_window(3, WINDOW);
+        let mut state = None;
+
+        for expected_remaining in [2, 1, 0] {
+            let decision = run(&algorithm, &mut state, T0 + 100);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, expected_remaining);
+        }
+
+        let denied = run(&algorithm, &mut state, T0 + 200);
+        assert!(!denied.allowed);
+        assert_eq!(denied.limit, 3);
+        // Current window full: wait out the 9.8s left in it, then until its
+        // weight in the sliding window decays from 3 to 2 (a third of WINDOW).
+        assert_eq!(denied.retry_after, Duration::from_millis(9_800 + 3_334));
+    }
+
+    #[test]
+    fn test_sliding_window_weights_previous_window() {
+        let algorithm = RateLimitAlgorithm::sliding_window(4, WINDOW);
+        let mut state = None;
+        for _ in 0..4 {
+            assert!(run(&algorithm, &mut state, T0 + 9_000).allowed);
+        }
+
+        // A quarter into the next window, 3/4 of the previous four requests
+        // still count: 3 of 4 used, one slot left.
+        let decision = run(&algorithm, &mut state, T0 + 12_500);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+
+        let denied = run(&algorithm, &mut state, T0 + 12_500);
+        assert!(!denied.allowed);
+        assert!(denied.retry_after > Duration::ZERO);
+
+        // Two full windows later everything has slid out.
+        let fresh = run(&algorithm, &mut state, T0 + 30_000);
+        assert!(fresh.allowed);
+        assert_eq!(fresh.remaining, 3);
+    }
+
+    #[test]
+    fn test_sliding_window_keeps_state_written_by_fast_clock() {
+        let algorithm = RateLimitAlgorithm::sliding_window(1, WINDOW);
+        let mut state = None;
+        assert!(run(&algorithm, &mut state, T0 + WINDOW.as_millis() as u64).allowed);
+
+        // A replica whose clock lags a full window behind must not treat the
+        // newer window as stale and hand out a fresh budget.
+        assert!(!run(&algorithm, &mut state, T0 + 500).allowed);
+    }
+
+    #[test]
+    fn test_token_bucket_bursts_then_refills() {
+        let algorithm = RateLimitAlgorithm::TokenBucket {
+            capacity: 2,
+            refill_interval: Duration::from_secs(1),
+        };
+        let mut state = None;
+
+        assert!(run(&algorithm, &mut state, T0).allowed);
+        assert!(run(&algorithm, &mut state, T0).allowed);
+        let denied = run(&algorithm, &mut state, T0 + 250);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after, Duration::from_millis(750));
+
+        let refilled = run(&algorithm, &mut state, T0 + 1_000);
+        assert!(refilled.allowed);
+        assert_eq!(refilled.remaining, 0);
+        assert_eq!(refilled.reset_after, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_token_bucket_constructor_spreads_window() {
+        assert_eq!(
+            RateLimitAlgorithm::token_bucket(10, 60, Duration::from_secs(60)),
+            RateLimitAlgorithm::TokenBucket {
+                capacity: 10,
+                refill_interval: Duration::from_secs(1),
+            }
+        );
+    }
+
+    #[test]
+    fn test_state_from_other_algorithm_is_discarded() {
+        let bucket = RateLimitAlgorithm::TokenBucket {
+            capacity: 1,
+            refill_interval: WINDOW,
+        };
+        let mut state = None;
+        assert!(run(&bucket, &mut state, T0).allowed);
+        assert!(!run(&bucket, &mut state, T0).allowed);
+
+        let window = RateLimitAlgorithm::sliding_window(1, WINDOW);
+        assert!(run(&window, &mut state, T0).allowed);
+    }
+
+    #[test]
+    fn test_zero_limit_never_admits() {
+        let algorithm = RateLimitAlgorithm::sliding_window(0, WINDOW);
+        let decision = evaluate(&algorithm, None, T0).1;
+        assert!(!decision.allowed);
+        assert!(decision.retry_after > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_api_key_is_digested() {
+        let key = RateLimitKey::api_key("super-secret");
+        assert!(!key.storage_key().contains("super-secret"));
+        assert_eq!(key, RateLimitKey::api_key("super-secret"));
+        assert_ne!(key, RateLimitKey::api_key("other"));
+    }
+
+    #[test]
+    fn test_ip_key_is_canonical() {
+        let mapped: IpAddr = "::ffff:203.0.113.9".parse().unwrap();
+        assert_eq!(RateLimitKey::ip(mapped), RateLimitKey::ip(ip(9)));
+    }
+
+    #[test]
+    fn test_policy_only_honours_registered_api_keys() {
+        let partner = RateLimitAlgorithm::sliding_window(1_000, WINDOW);
+        let policy = RateLimitPolicy::new(RateLimitAlgorithm::sliding_window(10, WINDOW))
+            .with_api_key("partner-key", partner);
+
+        let (key, algorithm) = policy.resolve(ip(1), Some("partner-key"));
+        assert_eq!(key, RateLimitKey::api_key("partner-key"));
+        assert_eq!(*algorithm, partner);
+
+        let (key, algorithm) = policy.resolve(ip(1), Some("random-guess"));
+        assert_eq!(key, RateLimitKey::ip(ip(1)));
+        assert_eq!(algorithm.limit(), 10);
+
+        assert!(!format!("{policy:?}").contains("partner-key"));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_counts_per_key() {
+        let store = InMemoryRateLimitStore::new();
+        let algorithm = RateLimitAlgorithm::sliding_window(2, Duration::from_secs(60));
+        let a = RateLimitKey::ip(ip(1));
+        let b = RateLimitKey::ip(ip(2));
+
+        assert!(store.check(&a, &algorithm).await.unwrap().allowed);
+        assert!(store.check(&a, &algorithm).await.unwrap().allowed);
+        assert!(!store.check(&a, &algorithm).await.unwrap().allowed);
+        assert!(store.check(&b, &algorithm).await.unwrap().allowed);
+    }
+
+    #[test]
+    fn test_in_memory_store_rejects_new_keys_at_capacity_until_expired() {
+        let store = InMemoryRateLimitStore::new().with_max_tracked_keys(1);
+        let algorithm = RateLimitAlgorithm::sliding_window(5, WINDOW);
+        let a = RateLimitKey::ip(ip(1));
+        let b = RateLimitKey::ip(ip(2));
+
+        store.check_at(&a, &algorithm, T0).unwrap();
+        assert_eq!(
+            store.check_at(&b, &algorithm, T0),
+            Err(RateLimitStoreError::CapacityExceeded { max: 1 })
+        );
+        // The tracked key itself is unaffected.
+        assert!(store.check_at(&a, &algorithm, T0).unwrap().allowed);
+
+        // Once `a` has expired its slot is reclaimed.
+        let later = T0 + algorithm.state_ttl_ms() + 1;
+        assert!(store.check_at(&b, &algorithm, later).unwrap().allowed);
+        assert_eq!(store.tracked_keys(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shared_store_replicas_share_one_budget() {
+        let backend = Arc::new(InProcessBackend::new());
+        let replica_a = SharedRateLimitStore::new(backend.clone());
+        let replica_b = SharedRateLimitStore::new(backend);
+        let algorithm = RateLimitAlgorithm::sliding_window(3, Duration::from_secs(60));
+        let key = RateLimitKey::api_key("partner-key");
+
+        assert!(replica_a.check(&key, &algorithm).await.unwrap().allowed);
+        assert!(replica_b.check(&key, &algorithm).await.unwrap().allowed);
+        assert!(replica_a.check(&key, &algorithm).await.unwrap().allowed);
+        assert!(!replica_b.check(&key, &algorithm).await.unwrap().allowed);
+    }
+
+    #[tokio::test]
+    async fn test_shared_store_never_double_spends_under_concurrency() {
+        let backend = Arc::new(InProcessBackend::new());
+        let algorithm = RateLimitAlgorithm::TokenBucket {
+            capacity: 20,
+            refill_interval: Duration::from_secs(3600),
+        };
+        let key = RateLimitKey::ip(ip(7));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let store = SharedRateLimitStore::new(backend.clone()).with_max_attempts(1_000);
+                let key = key.clone();
+                tokio::spawn(async move {
+                    let mut admitted = 0;
+                    for _ in 0..10 {
+                        if store.check(&key, &algorithm).await.unwrap().allowed {
+                            admitted += 1;
+                        }
+                        tokio::task::yield_now().await;
+                    }
+                    admitted
+                })
+            })
+            .collect();
+
+        let mut admitted = 0;
+        for task in tasks {
+            admitted += task.await.unwrap();
+        }
+        assert_eq!(admitted, 20);
+    }
+
+    #[tokio::test]
+    async fn test_shared_store_gives_up_on_persistent_contention() {
+        /// Backend whose swaps always lose, as if another replica kept
+        /// updating the key between every load and swap.
+        #[derive(Debug)]
+        struct AlwaysContended;
+
+        impl SharedStateBackend for AlwaysContended {
+            fn load<'a>(&'a self, _key: &'a str) -> RateLimitStoreFuture<'a, Option<Vec<u8>>> {
+                Box::pin(std::future::ready(Ok(None)))
+            }
+
+            fn compare_and_swap<'a>(
+                &'a self,
+                _key: &'a str,
+                _expected: Option<&'a [u8]>,
+                _value: &'a [u8],
+                _ttl: Duration,
+            ) -> RateLimitStoreFuture<'a, bool> {
+                Box::pin(std::future::ready(Ok(false)))
+            }
+        }
+
+        let store = SharedRateLimitStore::new(Arc::new(AlwaysContended)).with_max_attempts(3);
+        let result = store
+            .check(
+                &RateLimitKey::ip(ip(1)),
+                &RateLimitAlgorithm::sliding_window(1, WINDOW),
+            )
+            .await;
+        assert_eq!(result, Err(RateLimitStoreError::Contention { attempts: 3 }));
+    }
+
+    #[tokio::test]
+    async fn test_shared_store_overwrites_unreadable_state() {
+        let backend = Arc::new(InProcessBackend::new());
+        backend
+            .compare_and_swap(
+                "pjs:ratelimit:ip:203.0.113.1",
+                None,
+                b"not json",
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+
+        let store = SharedRateLimitStore::new(backend.clone());
+        let algorithm = RateLimitAlgorithm::sliding_window(1, WINDOW);
+        let key = RateLimitKey::ip(ip(1));
+        assert!(store.check(&key, &algorithm).await.unwrap().allowed);
+        assert!(!store.check(&key, &algorithm).await.unwrap().allowed);
+    }
+
+    #[tokio::test]
+    async fn test_in_process_backend_expires_entries() {
+        let backend = InProcessBackend::new();
+        assert!(
+            backend
+                .compare_and_swap("k", None, b"v", Duration::ZERO)
+                .await
+                .unwrap()
+        );
+        assert_eq!(backend.load("k").await.unwrap(), None);
+        // An expired value counts as absent for the swap too.
+        assert!(
+            backend
+                .compare_and_swap("k", None, b"w", Duration::from_secs(60))
+                .await
+                .unwrap()
+        );
+        assert_eq!(backend.load("k").await.unwrap(), Some(b"w".to_vec()));
+    }
+}
@@ -0,0 +1,150 @@
+//! Redis-backed [`SharedStateBackend`].
+
+use ::redis::{
+    Client, Script,
+    aio::{ConnectionManager, ConnectionManagerConfig},
+};
+use std::{fmt, sync::Arc, time::Duration};
+use tokio::sync::OnceCell;
+
+use super::{RateLimitStoreError, RateLimitStoreFuture, SharedStateBackend};
+
+/// Compare-and-swap as a single server-side script, so the comparison and
+/// the write are atomic with respect to every other Redis client.
+///
+/// `ARGV[1]` flags whether a value is expected at all (`"0"` means the key
+/// must be absent), `ARGV[2]` is the expected value, `ARGV[3]` the new value
+/// and `ARGV[4]` its time-to-live in milliseconds.
+const COMPARE_AND_SWAP_SCRIPT: &str = r"
+local current = redis.call('GET', KEYS[1])
+if ARGV[1] == '0' then
+  if current then return 0 end
+elseif current ~= ARGV[2] then
+  return 0
+end
+redis.call('SET', KEYS[1], ARGV[3], 'PX', ARGV[4])
+return 1
+";
+
+/// Upper bound on connecting to, and on each command round-trip with, the
+/// Redis server.
+///
+/// A rate-limit check sits in front of every request, so a slow backend must
+/// surface as [`RateLimitStoreError::Backend`] — and from there as a `503` or,
+/// with [`RateLimitPolicy::with_fail_open`](super::RateLimitPolicy::with_fail_open),
+/// an admitted request — rather than stall the request path.
+pub const REDIS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Reconnect attempts made before a command fails while the server is down.
+const RECONNECT_RETRIES: usize = 2;
+
+/// [`SharedStateBackend`] over a Redis server (or anything speaking its
+/// protocol and Lua scripting, e.g. Valkey).
+///
+/// Uses a [`ConnectionManager`], which multiplexes every check over one
+/// connection and reconnects transparently after a failure; checks made while
+/// the server is unreachable fail with [`RateLimitStoreError::Backend`]
+/// within a few [`REDIS_TIMEOUT`]s.
+///
+/// [`Self::new`] connects lazily, on the first check, so a replica can start
+/// (and a configuration can be validated) while Redis is unreachable; until
+/// the first connection succeeds every check fails and is retried on the
+/// next one. [`Self::connect`] connects eagerly instead.
+#[derive(Clone)]
+pub struct RedisBackend {
+    client: Client,
+    connection: Arc<OnceCell<ConnectionManager>>,
+    script: Script,
+}
+
+impl RedisBackend {
+    /// Backend for the server at `url` (e.g. `redis://cache:6379/0`),
+    /// connecting on first use.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimitStoreError::Backend`] if `url` is invalid.
+    pub fn new(url: &str) -> Result<Self, RateLimitStoreError> {
+        Ok(Self {
+            client: Client::open(url).map_err(backend_error)?,
+            connection: Arc::new(OnceCell::new()),
+            script: Script::new(COMPARE_AND_SWAP_SCRIPT),
+        })
+    }
+
+    /// Backend for the server at `url`, connected before returning.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimitStoreError::Backend`] if `url` is invalid or the
+    /// initial connection fails.
+    pub async fn connect(url: &str) -> Result<Self, RateLimitStoreError> {
+        let backend = Self::new(url)?;
+        backend.connection().await?;
+        Ok(backend)
+    }
+
+    async fn connection(&self) -> Result<ConnectionManager, RateLimitStoreError> {
+        self.connection
+            .get_or_try_init(|| {
+                let config = ConnectionManagerConfig::new()
+                    .set_number_of_retries(RECONNECT_RETRIES)
+                    .set_max_delay(REDIS_TIMEOUT)
+                    .set_connection_timeout(Some(REDIS_TIMEOUT))
+                    .set_response_timeout(Some(REDIS_TIMEOUT));
+                ConnectionManager::new_with_config(self.client.clone(), config)
+            })
+            .await
+            .cloned()
+            .map_err(backend_error)
+    }
+}
+
+impl SharedStateBackend for RedisBackend {
+    fn load<'a>(&'a self, key: &'a str) -> RateLimitStoreFuture<'a, Option<Vec<u8>>> {
+        Box::pin(async move {
+            let mut connection = self.connection().await?;
+            ::redis::cmd("GET")
+                .arg(key)
+                .query_async(&mut connection)
+                .await
+                .map_err(backend_error)
+        })
+    }
+
+    fn compare_and_swap<'a>(
+        &'a self,
+        key: &'a str,
+        expected: Option<&'a [u8]>,
+        value: &'a [u8],
+        ttl: Duration,
+    ) -> RateLimitStoreFuture<'a, bool> {
+        let ttl_ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+        Box::pin(async move {
+            let mut connection = self.connection().await?;
+            let swapped: i64 = self
+                .script
+                .key(key)
+                .arg(if expected.is_some() { "1" } else { "0" })
+                .arg(expected.unwrap_or_default())
+                .arg(value)
+                .arg(ttl_ms)
+                .invoke_async(&mut connection)
+                .await
+                .map_err(backend_error)?;
+            Ok(swapped == 1)
+        })
+    }
+}
+
+impl fmt::Debug for RedisBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisBackend")
+            .field("connected", &self.connection.initialized())
+            .finish_non_exhaustive()
+    }
+}
+
+fn backend_error(err: ::redis::RedisError) -> RateLimitStoreError {
+    RateLimitStoreError::Backend(err.to_string())
+}
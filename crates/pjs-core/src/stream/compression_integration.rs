@@ -4,7 +4,7 @@
 //! to progressively decompress data as frames arrive.
 
 use crate::{
-    compression::{CompressedData, CompressionStrategy, DICT_SENTINEL, SchemaCompressor},
+    compression::{CompressedData, CompressionStrategy, DICT_SENTINEL, SchemaCompressor, columnar},
     domain::{DomainError, DomainResult},
     stream::{Priority, StreamFrame},
 };
@@ -327,9 +327,20 @@ impl StreamingDecompressor {
                 let delta_decompressed = self.decompress_delta(&compressed_data.data)?;
                 self.decompress_dictionary(&delta_decompressed, &metadata.dictionary_map)
             }
+
+            CompressionStrategy::Columnar => self.decompress_columnar(&compressed_data.data),
         }
     }
 
+    /// Decompress columnar tables back into arrays of objects.
+    ///
+    /// Tables are self-contained, so no context from earlier frames is used.
+    /// Re-expanding repeated keys and dictionary values is bounded by the same
+    /// total-size limit as run-length expansion.
+    pub fn decompress_columnar(&self, data: &JsonValue) -> DomainResult<JsonValue> {
+        columnar::decode(data, MAX_DECOMPRESSED_SIZE)
+    }
+
     /// Decompress dictionary-encoded strings back from sentinel-escaped markers.
     ///
     /// Structural pattern-match on each string value's shape (see
@@ -727,6 +738,54 @@ mod tests {
         assert!(stats.total_input_bytes > 0);
     }
 
+    #[test]
+    fn test_columnar_patch_frames_decode_independently() {
+        use crate::domain::{entities::stream::StreamConfig, value_objects::JsonData};
+
+        let document: JsonData = json!({
+            "title": "Orders",
+            "orders": (0..200).map(|i| json!({
+                "id": 5000 + i,
+                "customer": format!("customer-{}", i % 17),
+                "status": (["pending", "shipped", "delivered"][i % 3]),
+                "total": (i * 7 % 100) as f64 + 0.99,
+            })).collect::<Vec<_>>(),
+        })
+        .into();
+        let patches = StreamConfig::default()
+            .frame_generator()
+            .patches(&document, Priority::MIN);
+        assert!(patches.len() > 1);
+
+        let mut compressor = StreamingCompressor::with_strategies(
+            CompressionStrategy::Columnar,
+            CompressionStrategy::Columnar,
+        );
+        for (patch, priority) in patches {
+            let frame = StreamFrame {
+                data: json!({
+                    "path": patch.path.to_string(),
+                    "value": serde_json::to_value(&patch.value).unwrap(),
+                }),
+                priority,
+                metadata: HashMap::new(),
+            };
+            let compressed = compressor.compress_frame(frame.clone()).unwrap();
+            let decompressed = StreamingDecompressor::new()
+                .decompress_frame(compressed)
+                .unwrap();
+            assert_eq!(decompressed.data, frame.data);
+        }
+
+        let stats = compressor.stats();
+        assert!(
+            stats.overall_compression_ratio() < 0.6,
+            "{} -> {} bytes",
+            stats.total_input_bytes,
+            stats.total_output_bytes
+        );
+    }
+
     #[test]
     fn test_delta_decompression_basic() {
         let decompressor = StreamingDecompressor::new();
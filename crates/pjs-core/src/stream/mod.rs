@@ -4,6 +4,7 @@
 //! and compression integration.
 
 pub mod compression_integration;
+pub mod pjs_json;
 pub mod priority;
 pub mod reconstruction;
 
@@ -129,7 +130,7 @@ pub use compression_integration::{
     StreamingCompressor, StreamingDecompressor,
 };
 pub use priority::{PriorityStreamFrame, PriorityStreamer};
-pub use reconstruction::JsonReconstructor;
+pub use reconstruction::{ArrayProgress, FrameArrival, JsonReconstructor};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,685 @@
+//! Native `application/pjs+json` wire format
+//!
+//! Encodes domain [`Frame`]s as the frame objects of the PJS specification
+//! (§3) and decodes them back:
+//!
+//! - every frame carries `@type`, `@seq`, `@priority` and `@timestamp`
+//!   (Unix milliseconds); frame metadata travels as an `@metadata` object
+//! - skeletons carry the document under `data` with `@schema_version`
+//! - patches carry RFC 6901 `op`/`path` entries under `@patches`: `Set` is
+//!   `replace`, `Append` is `add` to `<array>/-` with the appended items as
+//!   `value`, `Delete` is `remove`, and `Merge` expands to one `replace` per
+//!   merged member. Patches sharing a parent are written relative to an
+//!   `@base_path` (§5.3)
+//! - chunked-array metadata (§3.4) is written as the frame's
+//!   `@array_metadata` when the frame carries one chunk, and per patch as
+//!   `array_metadata` otherwise
+//! - completion digests travel as `@checksum`, errors as `@error`
+//!
+//! Priorities are carried as-is on this implementation's scale, where
+//! [`Priority::CRITICAL`] is 100 and anything above is critical as well.
+//! Decoding accepts `add` for existing locations as a `Set`; `move`, `copy`
+//! and `test` need the target document and are rejected. Pointer tokens in
+//! canonical decimal form decode as array indices (see
+//! [`JsonPath::from_json_pointer`]).
+
+use crate::domain::{
+    DomainError,
+    entities::{
+        Frame,
+        frame::{ArrayChunkMetadata, FramePatch, FrameType},
+    },
+    value_objects::{ContentDigest, JsonData, JsonPath, Priority, StreamId},
+};
+use crate::infrastructure::shutdown::RESUME_FROM_SEQUENCE_KEY;
+use chrono::DateTime;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Media type of the native wire format (spec Appendix A)
+pub const CONTENT_TYPE: &str = "application/pjs+json";
+
+/// Protocol version written as `@schema_version` and the `PJS-Version` header
+pub const PROTOCOL_VERSION: &str = "1.0";
+
+/// Errors raised while encoding or decoding `application/pjs+json` frames
+#[derive(Debug, thiserror::Error)]
+pub enum PjsJsonError {
+    /// A required frame or patch member is absent
+    #[error("missing required field `{0}`")]
+    MissingField(&'static str),
+
+    /// A member is present but has the wrong type or an invalid value
+    #[error("invalid field `{field}`: {reason}")]
+    InvalidField {
+        /// Name of the offending member
+        field: &'static str,
+        /// Why the value was rejected
+        reason: String,
+    },
+
+    /// `@type` names a frame type this implementation does not know
+    #[error("unknown frame type `{0}`")]
+    UnknownFrameType(String),
+
+    /// A patch uses an operation that cannot be expressed as a frame patch
+    #[error("unsupported patch operation `{0}`")]
+    UnsupportedOperation(String),
+
+    /// The decoded frame violates a domain invariant
+    #[error("invalid frame: {0}")]
+    Domain(#[from] DomainError),
+
+    /// A payload value could not be converted to JSON
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// One `@patches` entry before relative paths are applied
+struct WireOp {
+    op: &'static str,
+    tokens: Vec<String>,
+    value: Option<JsonValue>,
+    array_metadata: Option<ArrayChunkMetadata>,
+}
+
+/// Encode `frame` as a spec frame object.
+///
+/// # Errors
+///
+/// Returns [`PjsJsonError::UnknownFrameType`] for frame types the format has
+/// no representation for, and [`PjsJsonError::InvalidField`] or
+/// [`PjsJsonError::UnsupportedOperation`] if a patch frame's payload is
+/// malformed (frames built through [`Frame::patch`] never are).
+pub fn encode_frame(frame: &Frame) -> Result<JsonValue, PjsJsonError> {
+    let mut out = JsonMap::new();
+    let frame_type = match frame.frame_type() {
+        FrameType::Skeleton => "skeleton",
+        FrameType::Patch => "patch",
+        FrameType::Complete => "complete",
+        FrameType::Error => "error",
+        other => return Err(PjsJsonError::UnknownFrameType(format!("{other:?}"))),
+    };
+    out.insert("@type".into(), frame_type.into());
+    out.insert("@seq".into(), frame.sequence().into());
+    out.insert("@priority".into(), frame.priority().value().into());
+    out.insert(
+        "@timestamp".into(),
+        frame.timestamp().timestamp_millis().into(),
+    );
+
+    let payload = frame.payload();
+    match frame.frame_type() {
+        FrameType::Skeleton => {
+            out.insert("@schema_version".into(), PROTOCOL_VERSION.into());
+            out.insert("data".into(), serde_json::to_value(payload)?);
+        }
+        FrameType::Patch => encode_patches(payload, &mut out)?,
+        FrameType::Complete => {
+            if let Some(checksum) = payload.get("checksum") {
+                out.insert("@checksum".into(), serde_json::to_value(checksum)?);
+            }
+        }
+        _ => {
+            let mut error = JsonMap::new();
+            if let Some(code) = payload.get("code") {
+                error.insert("code".into(), serde_json::to_value(code)?);
+            }
+            let message = payload.get("message").cloned().unwrap_or(JsonData::Null);
+            error.insert("message".into(), serde_json::to_value(message)?);
+            error.insert(
+                "recoverable".into(),
+                frame
+                    .metadata_value(RESUME_FROM_SEQUENCE_KEY)
+                    .is_some()
+                    .into(),
+            );
+            out.insert("@error".into(), error.into());
+        }
+    }
+
+    if !frame.metadata().is_empty() {
+        let metadata: BTreeMap<_, _> = frame.metadata().iter().collect();
+        out.insert("@metadata".into(), serde_json::to_value(metadata)?);
+    }
+    Ok(JsonValue::Object(out))
+}
+
+fn encode_patches(
+    payload: &JsonData,
+    out: &mut JsonMap<String, JsonValue>,
+) -> Result<(), PjsJsonError> {
+    let Some(JsonData::Array(patches)) = payload.get("patches") else {
+        return Err(invalid(
+            "patches",
+            "patch frame payload has no patches array",
+        ));
+    };
+
+    let mut ops = Vec::with_capacity(patches.len());
+    for patch in patches {
+        let path = match patch.get("path") {
+            Some(JsonData::String(path)) => {
+                JsonPath::new(path.as_str()).map_err(|e| invalid("path", e.to_string()))?
+            }
+            _ => return Err(invalid("path", "patch has no path")),
+        };
+        let tokens = pointer_tokens(&path);
+        let value = patch.get("value").cloned().unwrap_or(JsonData::Null);
+        let operation = match patch.get("operation") {
+            Some(JsonData::String(operation)) => operation.as_str(),
+            _ => return Err(invalid("operation", "patch has no operation")),
+        };
+        match operation {
+            "set" => ops.push(WireOp {
+                op: "replace",
+                tokens,
+                value: Some(serde_json::to_value(value)?),
+                array_metadata: None,
+            }),
+            "append" => {
+                let mut tokens = tokens;
+                tokens.push("-".to_string());
+                ops.push(WireOp {
+                    op: "add",
+                    tokens,
+                    value: Some(serde_json::to_value(value)?),
+                    array_metadata: patch
+                        .get("array_metadata")
+                        .and_then(ArrayChunkMetadata::from_json),
+                });
+            }
+            "merge" => match value {
+                JsonData::Object(members) => {
+                    let members: BTreeMap<_, _> = members.into_iter().collect();
+                    for (key, member) in members {
+                        let mut tokens = tokens.clone();
+                        tokens.push(escape_token(&key));
+                        ops.push(WireOp {
+                            op: "replace",
+                            tokens,
+                            value: Some(serde_json::to_value(member)?),
+                            array_metadata: None,
+                        });
+                    }
+                }
+                other => ops.push(WireOp {
+                    op: "replace",
+                    tokens,
+                    value: Some(serde_json::to_value(other)?),
+                    array_metadata: None,
+                }),
+            },
+            "delete" => ops.push(WireOp {
+                op: "remove",
+                tokens,
+                value: None,
+                array_metadata: None,
+            }),
+            other => return Err(PjsJsonError::UnsupportedOperation(other.to_string())),
+        }
+    }
+
+    // A single chunk is described at frame level, as in the specification;
+    // several chunks in one frame each carry their own metadata.
+    let chunks = ops.iter().filter(|op| op.array_metadata.is_some()).count();
+    if chunks == 1
+        && let Some(op) = ops.iter_mut().find(|op| op.array_metadata.is_some())
+        && let Some(metadata) = op.array_metadata.take()
+    {
+        let array = &op.tokens[..op.tokens.len() - 1];
+        let mut value = serde_json::to_value(metadata)?;
+        if let JsonValue::Object(fields) = &mut value {
+            fields.insert("path".into(), join_pointer(array).into());
+        }
+        out.insert("@array_metadata".into(), value);
+    }
+
+    let base = common_parent(&ops);
+    if base > 0 {
+        out.insert(
+            "@base_path".into(),
+            join_pointer(&ops[0].tokens[..base]).into(),
+        );
+    }
+    let encoded = ops
+        .into_iter()
+        .map(|op| {
+            let mut entry = JsonMap::new();
+            entry.insert("op".into(), op.op.into());
+            entry.insert("path".into(), join_pointer(&op.tokens[base..]).into());
+            if let Some(value) = op.value {
+                entry.insert("value".into(), value);
+            }
+            if let Some(metadata) = op.array_metadata {
+                entry.insert("array_metadata".into(), serde_json::to_value(metadata)?);
+            }
+            Ok(JsonValue::Object(entry))
+        })
+        .collect::<Result<Vec<_>, PjsJsonError>>()?;
+    out.insert("@patches".into(), encoded.into());
+    Ok(())
+}
+
+/// Number of leading pointer tokens shared by every op, leaving each op at
+/// least one token of its own. Zero unless there are several ops.
+fn common_parent(ops: &[WireOp]) -> usize {
+    let [first, rest @ ..] = ops else {
+        return 0;
+    };
+    if rest.is_empty() {
+        return 0;
+    }
+    let mut len = ops
+        .iter()
+        .map(|op| op.tokens.len())
+        .min()
+        .unwrap_or(0)
+        .saturating_sub(1);
+    for op in rest {
+        len = len.min(
+            first
+                .tokens
+                .iter()
+                .zip(&op.tokens)
+                .take_while(|(a, b)| a == b)
+                .count(),
+        );
+    }
+    len
+}
+
+/// Decode one spec frame object into a [`Frame`] of `stream_id`.
+///
+/// Returns `Ok(None)` for `heartbeat` frames, which carry no data. Patch
+/// frames without `@priority` default to [`Priority::MEDIUM`]; skeleton,
+/// completion and error frames are always critical.
+///
+/// # Errors
+///
+/// Returns [`PjsJsonError::MissingField`] or [`PjsJsonError::InvalidField`]
+/// for malformed frames, [`PjsJsonError::UnknownFrameType`] for an unknown
+/// `@type`, [`PjsJsonError::UnsupportedOperation`] for `move`, `copy`,
+/// `test` or unknown patch operations, and [`PjsJsonError::Domain`] if the
+/// result is not a valid frame (e.g. a patch frame without patches).
+pub fn decode_frame(stream_id: StreamId, value: &JsonValue) -> Result<Option<Frame>, PjsJsonError> {
+    let Some(fields) = value.as_object() else {
+        return Err(invalid("@type", "frame must be a JSON object"));
+    };
+    let frame_type = fields
+        .get("@type")
+        .ok_or(PjsJsonError::MissingField("@type"))?
+        .as_str()
+        .ok_or_else(|| invalid("@type", "must be a string"))?;
+    if frame_type == "heartbeat" {
+        return Ok(None);
+    }
+    let sequence = fields
+        .get("@seq")
+        .ok_or(PjsJsonError::MissingField("@seq"))?
+        .as_u64()
+        .ok_or_else(|| invalid("@seq", "must be a non-negative integer"))?;
+
+    let mut frame = match frame_type {
+        "skeleton" => {
+            let data = fields
+                .get("data")
+                .ok_or(PjsJsonError::MissingField("data"))?;
+            Frame::skeleton(stream_id, sequence, data.clone().into())
+        }
+        "patch" => {
+            let priority = match fields.get("@priority") {
+                Some(priority) => decode_priority(priority)?,
+                None => Priority::MEDIUM,
+            };
+            Frame::patch(stream_id, sequence, priority, decode_patches(fields)?)?
+        }
+        "complete" => {
+            let checksum = fields
+                .get("@checksum")
+                .map(|checksum| {
+                    checksum
+                        .as_str()
+                        .ok_or_else(|| invalid("@checksum", "must be a string"))
+                        .and_then(|checksum| {
+                            ContentDigest::from_str(checksum)
+                                .map_err(|e| invalid("@checksum", e.to_string()))
+                        })
+                })
+                .transpose()?;
+            Frame::complete(stream_id, sequence, checksum)
+        }
+        "error" => {
+            let error = fields
+                .get("@error")
+                .ok_or(PjsJsonError::MissingField("@error"))?;
+            let message = error
+                .get("message")
+                .and_then(JsonValue::as_str)
+                .ok_or(PjsJsonError::MissingField("message"))?;
+            let code = error
+                .get("code")
+                .map(|code| {
+                    code.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| invalid("code", "must be a string"))
+                })
+                .transpose()?;
+            Frame::error(stream_id, sequence, message.to_string(), code)
+        }
+        other => return Err(PjsJsonError::UnknownFrameType(other.to_string())),
+    };
+
+    if let Some(timestamp) = fields.get("@timestamp") {
+        let timestamp = timestamp
+            .as_i64()
+            .and_then(DateTime::from_timestamp_millis)
+            .ok_or_else(|| invalid("@timestamp", "must be Unix milliseconds"))?;
+        frame = frame.with_timestamp(timestamp);
+    }
+    if let Some(metadata) = fields.get("@metadata") {
+        let metadata = metadata
+            .as_object()
+            .ok_or_else(|| invalid("@metadata", "must be an object"))?;
+        for (key, value) in metadata {
+            let value = value
+                .as_str()
+                .ok_or_else(|| invalid("@metadata", "values must be strings"))?;
+            frame = frame.with_metadata(key.clone(), value.to_string());
+        }
+    }
+    Ok(Some(frame))
+}
+
+fn decode_patches(fields: &JsonMap<String, JsonValue>) -> Result<Vec<FramePatch>, PjsJsonError> {
+    let entries = fields
+        .get("@patches")
+        .ok_or(PjsJsonError::MissingField("@patches"))?
+        .as_array()
+        .ok_or_else(|| invalid("@patches", "must be an array"))?;
+    let base = match fields.get("@base_path") {
+        Some(base) => {
+            let base = base
+                .as_str()
+                .ok_or_else(|| invalid("@base_path", "must be a string"))?;
+            if !base.is_empty() && !base.starts_with('/') {
+                return Err(invalid("@base_path", "must be a JSON Pointer"));
+            }
+            base
+        }
+        None => "",
+    };
+    let frame_chunk = fields
+        .get("@array_metadata")
+        .map(|metadata| {
+            let path = metadata
+                .get("path")
+                .and_then(JsonValue::as_str)
+                .ok_or(PjsJsonError::MissingField("path"))?;
+            let path = decode_pointer("@array_metadata", path)?;
+            let metadata = ArrayChunkMetadata::from_json(&metadata.clone().into())
+                .ok_or_else(|| invalid("@array_metadata", "malformed chunk metadata"))?;
+            Ok::<_, PjsJsonError>((path, metadata))
+        })
+        .transpose()?;
+
+    let mut patches = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let op = entry
+            .get("op")
+            .ok_or(PjsJsonError::MissingField("op"))?
+            .as_str()
+            .ok_or_else(|| invalid("op", "must be a string"))?;
+        let relative = entry
+            .get("path")
+            .ok_or(PjsJsonError::MissingField("path"))?
+            .as_str()
+            .ok_or_else(|| invalid("path", "must be a string"))?;
+        let pointer = format!("{base}{relative}");
+        let value = || {
+            entry
+                .get("value")
+                .cloned()
+                .map(JsonData::from)
+                .ok_or(PjsJsonError::MissingField("value"))
+        };
+
+        let patch = match op {
+            "add" if pointer.ends_with("/-") => {
+                let array = decode_pointer("path", &pointer[..pointer.len() - 2])?;
+                let items = match value()? {
+                    JsonData::Array(items) => items,
+                    item => vec![item],
+                };
+                let metadata = match entry.get("array_metadata") {
+                    Some(metadata) => Some(
+                        ArrayChunkMetadata::from_json(&metadata.clone().into())
+                            .ok_or_else(|| invalid("array_metadata", "malformed chunk metadata"))?,
+                    ),
+                    None => frame_chunk
+                        .as_ref()
+                        .filter(|(path, _)| *path == array)
+                        .map(|(_, metadata)| *metadata),
+                };
+                match metadata {
+                    Some(metadata) => FramePatch::append_chunk(array, items, metadata),
+                    None => FramePatch::append(array, JsonData::Array(items)),
+                }
+            }
+            "replace" | "add" => FramePatch::set(decode_pointer("path", &pointer)?, value()?),
+            "remove" => FramePatch::delete(decode_pointer("path", &pointer)?),
+            other => return Err(PjsJsonError::UnsupportedOperation(other.to_string())),
+        };
+        patches.push(patch);
+    }
+    Ok(patches)
+}
+
+fn decode_priority(value: &JsonValue) -> Result<Priority, PjsJsonError> {
+    value
+        .as_u64()
+        .and_then(|priority| u8::try_from(priority).ok())
+        .and_then(|priority| Priority::new(priority).ok())
+        .ok_or_else(|| invalid("@priority", "must be an integer in 1..=255"))
+}
+
+fn decode_pointer(field: &'static str, pointer: &str) -> Result<JsonPath, PjsJsonError> {
+    JsonPath::from_json_pointer(pointer).map_err(|e| invalid(field, e.to_string()))
+}
+
+/// Escaped RFC 6901 reference tokens of `path`; empty for the root.
+fn pointer_tokens(path: &JsonPath) -> Vec<String> {
+    if path.depth() == 0 {
+        return Vec::new();
+    }
+    path.to_json_pointer()[1..]
+        .split('/')
+        .map(str::to_string)
+        .collect()
+}
+
+fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Join escaped tokens into a pointer; no tokens is the whole document (`""`).
+fn join_pointer(tokens: &[String]) -> String {
+    tokens.iter().map(|token| format!("/{token}")).collect()
+}
+
+fn invalid(field: &'static str, reason: impl Into<String>) -> PjsJsonError {
+    PjsJsonError::InvalidField {
+        field,
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn path(raw: &str) -> JsonPath {
+        JsonPath::new(raw).unwrap()
+    }
+
+    /// The wire carries millisecond timestamps, so pin the frame to one.
+    fn on_the_millisecond(frame: Frame) -> Frame {
+        frame.with_timestamp(DateTime::from_timestamp_millis(1_700_000_000_123).unwrap())
+    }
+
+    #[test]
+    fn patches_share_a_base_path() {
+        let frame = on_the_millisecond(
+            Frame::patch(
+                StreamId::new(),
+                3,
+                Priority::HIGH,
+                vec![
+                    FramePatch::set(path("$.user.profile.bio"), JsonData::from("hi")),
+                    FramePatch::delete(path("$.user.profile.avatar")),
+                ],
+            )
+            .unwrap(),
+        );
+
+        let encoded = encode_frame(&frame).unwrap();
+        assert_eq!(encoded["@type"], "patch");
+        assert_eq!(encoded["@seq"], 3);
+        assert_eq!(encoded["@priority"], Priority::HIGH.value());
+        assert_eq!(encoded["@base_path"], "/user/profile");
+        assert_eq!(
+            encoded["@patches"],
+            json!([
+                {"op": "replace", "path": "/bio", "value": "hi"},
+                {"op": "remove", "path": "/avatar"}
+            ])
+        );
+
+        let decoded = decode_frame(frame.stream_id(), &encoded).unwrap().unwrap();
+        assert_eq!(decoded, frame);
+    }
+
+    #[test]
+    fn merge_expands_to_member_replacements() {
+        let frame = on_the_millisecond(
+            Frame::patch(
+                StreamId::new(),
+                1,
+                Priority::MEDIUM,
+                vec![FramePatch::merge(
+                    path("$.stats"),
+                    json!({"posts": 2, "a/b": 1}).into(),
+                )],
+            )
+            .unwrap(),
+        );
+
+        let encoded = encode_frame(&frame).unwrap();
+        assert_eq!(encoded["@base_path"], "/stats");
+        assert_eq!(
+            encoded["@patches"],
+            json!([
+                {"op": "replace", "path": "/a~1b", "value": 1},
+                {"op": "replace", "path": "/posts", "value": 2}
+            ])
+        );
+    }
+
+    #[test]
+    fn several_chunks_keep_per_patch_metadata() {
+        let metadata = ArrayChunkMetadata {
+            total_items: 4,
+            chunk_index: 0,
+            chunk_size: 2,
+        };
+        let frame = on_the_millisecond(
+            Frame::patch(
+                StreamId::new(),
+                2,
+                Priority::HIGH,
+                vec![
+                    FramePatch::append_chunk(
+                        path("$.a"),
+                        vec![JsonData::Integer(1), JsonData::Integer(2)],
+                        metadata,
+                    ),
+                    FramePatch::append_chunk(
+                        path("$.b"),
+                        vec![JsonData::Integer(3), JsonData::Integer(4)],
+                        metadata,
+                    ),
+                ],
+            )
+            .unwrap(),
+        );
+
+        let encoded = encode_frame(&frame).unwrap();
+        assert!(encoded.get("@array_metadata").is_none());
+        assert!(encoded.get("@base_path").is_none());
+        assert_eq!(encoded["@patches"][1]["path"], "/b/-");
+        assert_eq!(encoded["@patches"][1]["array_metadata"]["chunk_size"], 2);
+
+        let decoded = decode_frame(frame.stream_id(), &encoded).unwrap().unwrap();
+        assert_eq!(decoded, frame);
+    }
+
+    #[test]
+    fn error_frames_report_resume_points_as_recoverable() {
+        let frame = on_the_millisecond(crate::infrastructure::shutdown::shutdown_error_frame(
+            StreamId::new(),
+            7,
+        ));
+        let encoded = encode_frame(&frame).unwrap();
+        assert_eq!(encoded["@error"]["code"], "SERVER_SHUTTING_DOWN");
+        assert_eq!(encoded["@error"]["recoverable"], true);
+        assert_eq!(encoded["@metadata"][RESUME_FROM_SEQUENCE_KEY], "7");
+
+        let decoded = decode_frame(frame.stream_id(), &encoded).unwrap().unwrap();
+        assert_eq!(decoded, frame);
+    }
+
+    #[test]
+    fn rejects_malformed_frames() {
+        let id = StreamId::new();
+        assert!(
+            decode_frame(id, &json!({"@type": "heartbeat"}))
+                .unwrap()
+                .is_none()
+        );
+        assert!(matches!(
+            decode_frame(id, &json!({"@seq": 1})),
+            Err(PjsJsonError::MissingField("@type"))
+        ));
+        assert!(matches!(
+            decode_frame(id, &json!({"@type": "nope", "@seq": 1})),
+            Err(PjsJsonError::UnknownFrameType(_))
+        ));
+        assert!(matches!(
+            decode_frame(
+                id,
+                &json!({"@type": "patch", "@seq": 1, "@patches": [
+                    {"op": "move", "from": "/a", "path": "/b"}
+                ]})
+            ),
+            Err(PjsJsonError::UnsupportedOperation(_))
+        ));
+        assert!(matches!(
+            decode_frame(id, &json!({"@type": "patch", "@seq": 1, "@patches": []})),
+            Err(PjsJsonError::Domain(_))
+        ));
+        assert!(matches!(
+            decode_frame(
+                id,
+                &json!({"@type": "patch", "@seq": 1, "@priority": 0, "@patches": [
+                    {"op": "remove", "path": "/a"}
+                ]})
+            ),
+            Err(PjsJsonError::InvalidField {
+                field: "@priority",
+                ..
+            })
+        ));
+    }
+}
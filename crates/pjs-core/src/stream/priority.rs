@@ -5,10 +5,24 @@
 //! - JSON Path based patching
 //! - Priority-based field ordering
 //! - Incremental reconstruction
+//!
+//! [`PriorityStreamer`] is an adapter over the shared
+//! [`FrameGenerator`]: it works on `serde_json::Value` and produces
+//! [`PriorityStreamFrame`]s, which convert to and from the domain
+//! [`Frame`] every other transport sends.
 
 use crate::Result;
-use crate::domain::value_objects::{JsonPath, Priority};
-use serde_json::{Map as JsonMap, Value as JsonValue};
+use crate::domain::entities::Frame;
+use crate::domain::entities::frame::{
+    ArrayChunkMetadata, FramePatch, FrameType, PatchOperation as FrameOperation,
+};
+use crate::domain::entities::stream::ArrayChunking;
+use crate::domain::services::{FrameGenerator, PjsPriority, PriorityHeuristicConfig};
+use crate::domain::value_objects::{
+    ContentDigest, IntegrityKey, JsonData, JsonPath, JsonPathQuery, Priority, StreamId,
+};
+use crate::stream::reconstruction::JsonReconstructor;
+use serde_json::Value as JsonValue;
 use std::collections::VecDeque;
 
 /// Custom serde for Priority in stream module
@@ -34,6 +48,10 @@ pub struct JsonPatch {
     /// Priority assigned to this patch.
     #[serde(with = "serde_priority")]
     pub priority: Priority,
+    /// Where this chunk falls in its array, for an `Append` patch carrying
+    /// one chunk of a chunked array.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array_metadata: Option<ArrayChunkMetadata>,
 }
 
 /// Operation a [`JsonPatch`] performs at its target path.
@@ -81,11 +99,135 @@ pub enum PriorityStreamFrame {
     },
     /// Terminal frame indicating the stream is complete.
     Complete {
-        /// Optional checksum of the reconstructed payload.
-        checksum: Option<u64>,
+        /// Digest of the document the preceding frames reconstruct to,
+        /// checked by [`JsonReconstructor`](crate::stream::reconstruction::JsonReconstructor)
+        /// when present.
+        checksum: Option<ContentDigest>,
     },
 }
 
+impl JsonPatch {
+    /// The patch `patch`, sent at `priority`, stands for.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidFrame`](crate::Error::InvalidFrame) for a
+    /// `merge` patch, which has no counterpart here, and for an `append`
+    /// patch whose value is not an array.
+    pub fn from_frame_patch(patch: &FramePatch, priority: Priority) -> Result<Self> {
+        let value = serde_json::to_value(&patch.value)?;
+        let operation = match patch.operation {
+            FrameOperation::Set => PatchOperation::Set { value },
+            FrameOperation::Append => match value {
+                JsonValue::Array(values) => PatchOperation::Append { values },
+                _ => {
+                    return Err(crate::Error::InvalidFrame(format!(
+                        "append to {} carries no array",
+                        patch.path
+                    )));
+                }
+            },
+            FrameOperation::Delete => PatchOperation::Remove,
+            ref other => {
+                return Err(crate::Error::InvalidFrame(format!(
+                    "unsupported patch operation {other:?}"
+                )));
+            }
+        };
+        Ok(Self {
+            path: patch.path.clone(),
+            operation,
+            priority,
+            array_metadata: patch.array_metadata,
+        })
+    }
+
+    /// The domain patch this patch stands for.
+    pub fn to_frame_patch(&self) -> FramePatch {
+        let path = self.path.clone();
+        let patch = match &self.operation {
+            PatchOperation::Set { value } | PatchOperation::Replace { value } => {
+                FramePatch::set(path, value.clone().into())
+            }
+            PatchOperation::Append { values } => FramePatch::append(
+                path,
+                JsonData::Array(values.iter().cloned().map(Into::into).collect()),
+            ),
+            PatchOperation::Remove => FramePatch::delete(path),
+        };
+        FramePatch {
+            array_metadata: self.array_metadata,
+            ..patch
+        }
+    }
+}
+
+impl PriorityStreamFrame {
+    /// The domain frame a transport sends for this frame, as frame
+    /// `sequence` of stream `stream_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidFrame`](crate::Error::InvalidFrame) for a
+    /// patch frame without patches.
+    pub fn to_frame(&self, stream_id: StreamId, sequence: u64) -> Result<Frame> {
+        match self {
+            Self::Skeleton { data, .. } => {
+                Ok(Frame::skeleton(stream_id, sequence, data.clone().into()))
+            }
+            Self::Patch { patches, priority } => Frame::patch(
+                stream_id,
+                sequence,
+                *priority,
+                patches.iter().map(JsonPatch::to_frame_patch).collect(),
+            )
+            .map_err(|e| crate::Error::InvalidFrame(e.to_string())),
+            Self::Complete { checksum } => Ok(Frame::complete(stream_id, sequence, *checksum)),
+        }
+    }
+}
+
+/// The frame [`JsonReconstructor`] applies for a domain frame.
+impl TryFrom<&Frame> for PriorityStreamFrame {
+    type Error = crate::Error;
+
+    fn try_from(frame: &Frame) -> Result<Self> {
+        let invalid = |message: String| crate::Error::InvalidFrame(message);
+        match frame.frame_type() {
+            FrameType::Skeleton => Ok(Self::Skeleton {
+                data: serde_json::to_value(frame.payload())?,
+                priority: frame.priority(),
+                complete: false,
+            }),
+            FrameType::Patch => {
+                let patches: Vec<FramePatch> = match frame.payload().get("patches") {
+                    Some(patches) => serde_json::from_value(serde_json::to_value(patches)?)?,
+                    None => return Err(invalid("patch frame without patches".to_string())),
+                };
+                Ok(Self::Patch {
+                    patches: patches
+                        .iter()
+                        .map(|patch| JsonPatch::from_frame_patch(patch, frame.priority()))
+                        .collect::<Result<_>>()?,
+                    priority: frame.priority(),
+                })
+            }
+            FrameType::Complete => {
+                let checksum = match frame.payload().get("checksum") {
+                    Some(JsonData::String(checksum)) => Some(
+                        checksum
+                            .parse::<ContentDigest>()
+                            .map_err(|e| invalid(e.to_string()))?,
+                    ),
+                    _ => None,
+                };
+                Ok(Self::Complete { checksum })
+            }
+            _ => Err(invalid(format!("stream error: {}", frame.payload()))),
+        }
+    }
+}
+
 /// Priority-based JSON streamer
 pub struct PriorityStreamer {
     config: StreamerConfig,
@@ -94,12 +236,23 @@ pub struct PriorityStreamer {
 /// Configuration for [`PriorityStreamer`].
 #[derive(Debug, Clone)]
 pub struct StreamerConfig {
-    /// Enable name-based heuristics that infer priorities from common field names.
+    /// Enable name-based heuristics that infer priorities from common field
+    /// names; with them off, priorities follow `priority_rules` and the
+    /// depth and value-shape fallback only.
     pub detect_semantics: bool,
-    /// Maximum number of patches per [`PriorityStreamFrame::Patch`] batch.
+    /// Maximum number of patches per [`PriorityStreamFrame::Patch`] batch,
+    /// and the chunk size longer arrays are appended in.
     pub max_patch_size: usize,
-    /// Patches with priority below this threshold are dropped.
+    /// Patches with priority below this threshold are dropped; the default,
+    /// [`Priority::MIN`], sends everything.
     pub priority_threshold: Priority,
+    /// Key signing each plan's completion digest with HMAC-SHA256; plain
+    /// SHA-256 when `None`. Clients must hold the same key to verify.
+    pub integrity_key: Option<IntegrityKey>,
+    /// Priorities assigned by JSONPath query, overriding the name
+    /// heuristics: every field or array a query selects gets the rule's
+    /// priority, the highest matching rule winning.
+    pub priority_rules: Vec<(JsonPathQuery, Priority)>,
 }
 
 impl Default for StreamerConfig {
@@ -107,8 +260,42 @@ impl Default for StreamerConfig {
         Self {
             detect_semantics: true,
             max_patch_size: 100,
-            priority_threshold: Priority::LOW,
+            priority_threshold: Priority::MIN,
+            integrity_key: None,
+            priority_rules: Vec::new(),
+        }
+    }
+}
+
+impl StreamerConfig {
+    /// The [`FrameGenerator`] [`PriorityStreamer::analyze`] generates plans
+    /// with under this configuration.
+    pub fn frame_generator(&self) -> FrameGenerator {
+        let mut priorities = PriorityHeuristicConfig::default();
+        if !self.detect_semantics {
+            priorities.critical_fields.clear();
+            priorities.high_fields.clear();
+            priorities.medium_fields.clear();
+            priorities.low_fields.clear();
+            priorities.background_fields.clear();
+        }
+        for (query, priority) in &self.priority_rules {
+            priorities.add_path_rule(query.clone(), *priority);
         }
+        FrameGenerator::new(priorities)
+            .with_default_array_chunking(ArrayChunking::new(self.max_patch_size))
+    }
+
+    /// Add a priority rule for every priority `T` declares, see
+    /// [`PjsPriority`].
+    #[must_use]
+    pub fn with_priorities_of<T: PjsPriority + ?Sized>(mut self) -> Self {
+        self.priority_rules.extend(
+            T::priority_map()
+                .into_iter()
+                .map(|(path, priority)| (JsonPathQuery::from(&path), priority)),
+        );
+        self
     }
 }
 
@@ -124,237 +311,60 @@ impl PriorityStreamer {
     }
 
     /// Analyze JSON and create streaming plan
+    ///
+    /// The skeleton and patches come from the shared [`FrameGenerator`], so
+    /// the plan carries the same patches, with the same priorities, as the
+    /// domain [`Stream`](crate::domain::Stream) and the other transports
+    /// would send for `json`. Patches sharing a priority are batched, at
+    /// most [`StreamerConfig::max_patch_size`] per frame.
+    ///
+    /// The plan ends with a [`PriorityStreamFrame::Complete`] carrying the
+    /// [`ContentDigest`] of the document the plan's frames reconstruct to:
+    /// `json` itself unless [`StreamerConfig::priority_threshold`] left
+    /// patches out.
     pub fn analyze(&self, json: &JsonValue) -> Result<StreamingPlan> {
+        let document = JsonData::from(json.clone());
+        let generator = self.config.frame_generator();
         let mut plan = StreamingPlan::new();
 
-        // Generate skeleton
-        let skeleton = self.generate_skeleton(json);
         plan.frames.push_back(PriorityStreamFrame::Skeleton {
-            data: skeleton,
+            data: serde_json::to_value(generator.skeleton(&document))?,
             priority: Priority::CRITICAL,
             complete: false,
         });
 
-        // Extract patches by priority
-        let mut patches = Vec::new();
-        self.extract_patches(json, &JsonPath::root(), &mut patches)?;
-
-        // Group patches by priority
-        patches.sort_by_key(|patch| std::cmp::Reverse(patch.priority));
-
-        let mut current_priority = Priority::CRITICAL;
-        let mut current_batch = Vec::new();
-
-        for patch in patches {
-            if patch.priority != current_priority && !current_batch.is_empty() {
-                plan.frames.push_back(PriorityStreamFrame::Patch {
-                    patches: current_batch,
-                    priority: current_priority,
-                });
-                current_batch = Vec::new();
-            }
-            current_priority = patch.priority;
-            current_batch.push(patch);
-
-            if current_batch.len() >= self.config.max_patch_size {
-                plan.frames.push_back(PriorityStreamFrame::Patch {
-                    patches: current_batch,
-                    priority: current_priority,
-                });
-                current_batch = Vec::new();
-            }
-        }
-
-        // Add remaining patches
-        if !current_batch.is_empty() {
+        let threshold = self.config.priority_threshold;
+        let patches = generator.patches(&document, threshold);
+        for (priority, patches) in
+            FrameGenerator::group_by_priority(patches, self.config.max_patch_size)
+        {
             plan.frames.push_back(PriorityStreamFrame::Patch {
-                patches: current_batch,
-                priority: current_priority,
-            });
-        }
-
-        // Add completion frame
-        plan.frames
-            .push_back(PriorityStreamFrame::Complete { checksum: None });
-
-        Ok(plan)
-    }
-
-    /// Generate skeleton structure with null/empty values
-    fn generate_skeleton(&self, json: &JsonValue) -> JsonValue {
-        match json {
-            JsonValue::Object(map) => {
-                let mut skeleton = JsonMap::new();
-                for (key, value) in map {
-                    skeleton.insert(
-                        key.clone(),
-                        match value {
-                            JsonValue::Array(_) => JsonValue::Array(vec![]),
-                            JsonValue::Object(_) => self.generate_skeleton(value),
-                            JsonValue::String(_) => JsonValue::Null,
-                            JsonValue::Number(_) => JsonValue::Number(0.into()),
-                            JsonValue::Bool(_) => JsonValue::Bool(false),
-                            JsonValue::Null => JsonValue::Null,
-                        },
-                    );
-                }
-                JsonValue::Object(skeleton)
-            }
-            JsonValue::Array(_) => JsonValue::Array(vec![]),
-            _ => JsonValue::Null,
-        }
-    }
-
-    /// Deep-clone `value`, emptying every array reachable via a JsonPath-encodable
-    /// key — mirrors `extract_patches`'s own traversal exactly. A subtree under a
-    /// key `JsonPath` cannot encode (`.`/`[`/`]`) is left fully populated, since
-    /// `extract_patches`'s recursion will never reach it to emit a compensating
-    /// `Append` (see #394 C3). Encodability depends only on the key string itself
-    /// (`JsonPath::append_key`'s sole failure mode), not on the accumulated path,
-    /// so this check does not need to thread a `JsonPath` through the recursion.
-    fn skeletonize_arrays(value: &JsonValue) -> JsonValue {
-        match value {
-            JsonValue::Object(map) => {
-                let skeleton = map
+                patches: patches
                     .iter()
-                    .map(|(key, v)| match JsonPath::root().append_key(key) {
-                        Ok(_) => (key.clone(), Self::skeletonize_arrays(v)),
-                        Err(_) => (key.clone(), v.clone()),
-                    })
-                    .collect();
-                JsonValue::Object(skeleton)
-            }
-            JsonValue::Array(_) => JsonValue::Array(vec![]),
-            other => other.clone(),
-        }
-    }
-
-    /// Extract patches from JSON structure
-    fn extract_patches(
-        &self,
-        json: &JsonValue,
-        current_path: &JsonPath,
-        patches: &mut Vec<JsonPatch>,
-    ) -> Result<()> {
-        match json {
-            JsonValue::Object(map) => {
-                for (key, value) in map {
-                    // Keys JsonPath cannot encode (`.`, `[`, `]`) are skipped:
-                    // one weird key must not abort the whole streaming plan.
-                    let Ok(field_path) = current_path.append_key(key) else {
-                        continue;
-                    };
-                    let own_priority = self.calculate_field_priority(&field_path, key, value);
-
-                    let mut child_patches = Vec::new();
-                    self.extract_patches(value, &field_path, &mut child_patches)?;
-
-                    // Hoist the Set's priority to at least the highest Append
-                    // priority anywhere in its subtree, so a Set can never be
-                    // sorted/applied after an Append it must precede (#394 C1/C2).
-                    let append_ceiling = child_patches
-                        .iter()
-                        .filter(|p| matches!(p.operation, PatchOperation::Append { .. }))
-                        .map(|p| p.priority)
-                        .max();
-                    let priority =
-                        append_ceiling.map_or(own_priority, |ceiling| own_priority.max(ceiling));
-
-                    patches.push(JsonPatch {
-                        path: field_path.clone(),
-                        operation: PatchOperation::Set {
-                            value: Self::skeletonize_arrays(value),
-                        },
-                        priority,
-                    });
-
-                    patches.extend(child_patches);
-                }
-            }
-            JsonValue::Array(arr) => {
-                // For arrays, create append operations in chunks
-                if arr.len() > 10 {
-                    // Priority is computed once from the full array so every
-                    // chunk of the same array shares it: computing it per-chunk
-                    // let a short tail chunk outrank the bulk chunks and jump
-                    // ahead of them in the priority sort, corrupting element
-                    // order on reconstruction (#394 C2, chunked variant).
-                    let priority = self.calculate_array_priority(current_path, arr);
-                    for chunk in arr.chunks(self.config.max_patch_size) {
-                        patches.push(JsonPatch {
-                            path: current_path.clone(),
-                            operation: PatchOperation::Append {
-                                values: chunk.to_vec(),
-                            },
-                            priority,
-                        });
-                    }
-                } else if !arr.is_empty() {
-                    patches.push(JsonPatch {
-                        path: current_path.clone(),
-                        operation: PatchOperation::Append {
-                            values: arr.clone(),
-                        },
-                        priority: self.calculate_array_priority(current_path, arr),
-                    });
-                }
-            }
-            _ => {
-                // Primitive values handled by parent object/array
-            }
-        }
-
-        Ok(())
-    }
-
-    /// Calculate priority for a field based on path and content
-    fn calculate_field_priority(&self, _path: &JsonPath, key: &str, value: &JsonValue) -> Priority {
-        // Critical fields
-        if matches!(key, "id" | "uuid" | "status" | "type" | "kind") {
-            return Priority::CRITICAL;
-        }
-
-        // High priority fields
-        if matches!(key, "name" | "title" | "label" | "email" | "username") {
-            return Priority::HIGH;
-        }
-
-        // Low priority patterns
-        if key.contains("analytics") || key.contains("stats") || key.contains("meta") {
-            return Priority::LOW;
-        }
-
-        if matches!(key, "reviews" | "comments" | "logs" | "history") {
-            return Priority::BACKGROUND;
-        }
-
-        // Content-based priority
-        match value {
-            JsonValue::Array(arr) if arr.len() > 100 => Priority::BACKGROUND,
-            JsonValue::Object(obj) if obj.contains_key("timestamp") => Priority::MEDIUM,
-            JsonValue::String(s) if s.len() > 1000 => Priority::LOW,
-            _ => Priority::MEDIUM,
-        }
-    }
-
-    /// Calculate priority for array elements
-    fn calculate_array_priority(&self, path: &JsonPath, elements: &[JsonValue]) -> Priority {
-        // Large arrays get background priority
-        if elements.len() > 50 {
-            return Priority::BACKGROUND;
+                    .map(|patch| JsonPatch::from_frame_patch(patch, priority))
+                    .collect::<Result<_>>()?,
+                priority,
+            });
         }
 
-        // Arrays in certain paths get different priorities
-        if let Some(last_key) = path.last_key() {
-            if matches!(last_key, "reviews" | "comments" | "logs") {
-                return Priority::BACKGROUND;
-            }
-            if matches!(last_key, "items" | "data" | "results") {
-                return Priority::MEDIUM;
+        let delivered = if threshold <= Priority::MIN {
+            serde_json::to_value(&document)?
+        } else {
+            let mut reconstructor = JsonReconstructor::new();
+            for frame in plan.frames() {
+                reconstructor.add_frame(frame.clone());
             }
-        }
+            reconstructor.process_all_frames()?;
+            reconstructor.current_state().clone()
+        };
+        plan.frames.push_back(PriorityStreamFrame::Complete {
+            checksum: Some(ContentDigest::compute(
+                &delivered,
+                self.config.integrity_key.as_ref(),
+            )),
+        });
 
-        Priority::MEDIUM
+        Ok(plan)
     }
 }
 
@@ -448,6 +458,16 @@ mod tests {
         assert!(Priority::LOW > Priority::BACKGROUND);
     }
 
+    /// Every patch in `plan`, in order.
+    fn plan_patches(plan: &StreamingPlan) -> Vec<&JsonPatch> {
+        plan.frames()
+            .flat_map(|frame| match frame {
+                PriorityStreamFrame::Patch { patches, .. } => patches.iter().collect(),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
     #[test]
     fn test_skeleton_generation() {
         let streamer = PriorityStreamer::new();
@@ -458,7 +478,10 @@ mod tests {
             "posts": ["post1", "post2"]
         });
 
-        let skeleton = streamer.generate_skeleton(&json);
+        let plan = streamer.analyze(&json).unwrap();
+        let Some(PriorityStreamFrame::Skeleton { data, .. }) = plan.frames.front() else {
+            panic!("plan must start with a skeleton");
+        };
         let expected = json!({
             "name": null,
             "age": 0,
@@ -466,30 +489,107 @@ mod tests {
             "posts": []
         });
 
-        assert_eq!(skeleton, expected);
+        assert_eq!(data, &expected);
     }
 
     #[test]
     fn test_field_priority_calculation() {
         let streamer = PriorityStreamer::new();
-        let path = JsonPath::root();
+        let plan = streamer
+            .analyze(&json!({"id": 123, "name": "John", "reviews": ["ok"]}))
+            .unwrap();
+        let priority_of = |path: &str| {
+            plan_patches(&plan)
+                .into_iter()
+                .find(|patch| patch.path.to_string() == path)
+                .map(|patch| patch.priority)
+        };
 
-        assert_eq!(
-            streamer.calculate_field_priority(&path, "id", &json!(123)),
-            Priority::CRITICAL
-        );
+        assert_eq!(priority_of("$.id"), Some(Priority::CRITICAL));
+        assert_eq!(priority_of("$.name"), Some(Priority::HIGH));
+        assert_eq!(priority_of("$.reviews"), Some(Priority::BACKGROUND));
+    }
 
-        assert_eq!(
-            streamer.calculate_field_priority(&path, "name", &json!("John")),
-            Priority::HIGH
-        );
+    #[test]
+    fn test_plan_matches_domain_stream_patches() {
+        use crate::domain::entities::Stream;
+        use crate::domain::value_objects::SessionId;
+
+        let payload = json!({
+            "id": 1,
+            "user": {"name": "Ann", "tags": ["a", "b"]},
+            "logs": ["started"]
+        });
+        let plan = PriorityStreamer::new().analyze(&payload).unwrap();
+
+        let mut stream = Stream::new(SessionId::new(), payload.clone().into(), Default::default());
+        stream.start_streaming().unwrap();
+        let expected = stream.extract_prioritized_patches(Priority::MIN).unwrap();
+        let actual: Vec<_> = plan_patches(&plan)
+            .into_iter()
+            .map(|patch| (patch.to_frame_patch(), patch.priority))
+            .collect();
+
+        assert_eq!(actual, expected);
+    }
 
+    #[test]
+    fn test_frames_convert_to_and_from_domain_frames() {
+        let payload = json!({"id": 7, "rows": (0..5).collect::<Vec<_>>(), "name": "Ann"});
+        let plan = PriorityStreamer::with_config(StreamerConfig {
+            max_patch_size: 2,
+            ..StreamerConfig::default()
+        })
+        .analyze(&payload)
+        .unwrap();
+
+        let stream_id = StreamId::new();
+        let mut reconstructor = JsonReconstructor::new();
+        for (sequence, frame) in plan.frames().enumerate() {
+            let frame = frame.to_frame(stream_id, sequence as u64).unwrap();
+            reconstructor.add_frame(PriorityStreamFrame::try_from(&frame).unwrap());
+        }
+        reconstructor.process_all_frames().unwrap();
+
+        assert!(reconstructor.is_complete());
+        assert_eq!(reconstructor.current_state(), &payload);
+    }
+
+    #[test]
+    fn test_threshold_drops_patches_and_digest_follows() {
+        let streamer = PriorityStreamer::with_config(StreamerConfig {
+            priority_threshold: Priority::MEDIUM,
+            ..StreamerConfig::default()
+        });
+        let payload = json!({"id": 1, "logs": ["started"]});
+
+        let plan = streamer.analyze(&payload).unwrap();
+        let paths: Vec<String> = plan_patches(&plan)
+            .into_iter()
+            .map(|patch| patch.path.to_string())
+            .collect();
+        assert_eq!(paths, ["$.id"]);
         assert_eq!(
-            streamer.calculate_field_priority(&path, "reviews", &json!([])),
-            Priority::BACKGROUND
+            round_trip(&streamer, &payload),
+            json!({"id": 1, "logs": []})
         );
     }
 
+    #[test]
+    fn test_semantics_detection_can_be_disabled() {
+        let streamer = PriorityStreamer::with_config(StreamerConfig {
+            detect_semantics: false,
+            ..StreamerConfig::default()
+        });
+
+        let plan = streamer.analyze(&json!({"id": 1, "zip": 2})).unwrap();
+        let priorities: Vec<Priority> = plan_patches(&plan)
+            .into_iter()
+            .map(|patch| patch.priority)
+            .collect();
+        assert_eq!(priorities[0], priorities[1]);
+    }
+
     #[test]
     fn test_streaming_plan_creation() {
         let streamer = PriorityStreamer::new();
@@ -672,8 +772,9 @@ mod tests {
     // land in an earlier-processed, higher-priority batch than its own or an
     // ancestor's `Set`. Pre-fix this was harmless (`Set` always carried the
     // full pristine value); post-fix `Set` carries a skeleton, so an
-    // out-of-order `Set` destructively wipes already-applied data. These are
-    // expected to FAIL until the ordering issue is fixed (see task #9).
+    // out-of-order `Set` destructively wipes already-applied data. The shared
+    // `FrameGenerator` only patches values that are not expanded objects, so
+    // no patch can overwrite another one any more.
 
     #[test]
     fn test_round_trip_same_path_priority_inversion() {
@@ -727,12 +828,9 @@ mod tests {
 
     #[test]
     fn test_round_trip_unencodable_key_parent_wipe() {
-        // `extract_patches` skips recursion into keys JsonPath cannot encode
-        // (containing '.', '[', ']'), so no Set/Append is ever emitted for
-        // "weird.key" itself. Pre-fix, the parent "outer" field's Set carried
-        // the full pristine value (including "weird.key"'s array) as a safety
-        // net. Post-fix, `skeletonize_arrays` recurses into "outer" and empties
-        // "weird.key"'s array too, permanently losing the only copy of its data.
+        // No patch can address keys JsonPath cannot encode (containing '.',
+        // '[', ']'), so "weird.key" must travel inside a `Set` of "outer",
+        // whole, rather than being emptied in the skeleton and lost.
         let streamer = PriorityStreamer::new();
         let payload = json!({"outer": {"weird.key": [1, 2, 3]}});
 
@@ -740,4 +838,102 @@ mod tests {
 
         assert_eq!(result, payload);
     }
+
+    #[test]
+    fn test_plan_completes_with_digest_of_source() {
+        use crate::domain::value_objects::ContentDigest;
+
+        let payload = json!({"id": 7, "tags": ["a", "b"], "profile": {"name": "Ann"}});
+        let plan = PriorityStreamer::new().analyze(&payload).unwrap();
+
+        let Some(PriorityStreamFrame::Complete { checksum }) = plan.frames.back() else {
+            panic!("plan must end with a Complete frame");
+        };
+        assert_eq!(*checksum, Some(ContentDigest::compute(&payload, None)));
+    }
+
+    #[test]
+    fn test_priority_rules_override_name_heuristics() {
+        let streamer = PriorityStreamer::with_config(StreamerConfig {
+            priority_rules: vec![
+                (JsonPathQuery::new("$.footer").unwrap(), Priority::CRITICAL),
+                (JsonPathQuery::new("$..id").unwrap(), Priority::BACKGROUND),
+            ],
+            ..StreamerConfig::default()
+        });
+        let payload = json!({"id": 1, "footer": "legal", "tags": ["a", "b"]});
+
+        let plan = streamer.analyze(&payload).unwrap();
+        let priority_of = |path: &str| {
+            plan_patches(&plan)
+                .into_iter()
+                .find(|patch| patch.path.to_string() == path)
+                .map(|patch| patch.priority)
+        };
+        assert_eq!(priority_of("$.footer"), Some(Priority::CRITICAL));
+        assert_eq!(priority_of("$.id"), Some(Priority::BACKGROUND));
+        assert_eq!(
+            priority_of("$.tags"),
+            Some(Priority::MEDIUM.increase_by(20))
+        );
+
+        assert_eq!(round_trip(&streamer, &payload), payload);
+    }
+
+    #[test]
+    fn test_priorities_declared_on_response_type() {
+        #[derive(serde::Serialize, pjson_rs_derive::PjsPriority)]
+        #[serde(rename_all = "camelCase")]
+        struct Profile {
+            #[pjs(priority = "critical")]
+            display_name: String,
+            #[pjs(priority = "background")]
+            user_id: u64,
+        }
+
+        let streamer = PriorityStreamer::with_config(
+            StreamerConfig::default().with_priorities_of::<Profile>(),
+        );
+        let payload = serde_json::to_value(Profile {
+            display_name: "Ada".to_string(),
+            user_id: 7,
+        })
+        .unwrap();
+
+        let plan = streamer.analyze(&payload).unwrap();
+        let paths: Vec<String> = plan_patches(&plan)
+            .into_iter()
+            .map(|patch| patch.path.to_string())
+            .collect();
+        assert_eq!(paths, ["$.displayName", "$.userId"]);
+        assert_eq!(round_trip(&streamer, &payload), payload);
+    }
+
+    #[test]
+    fn test_plan_digest_signed_with_configured_key() {
+        let key = IntegrityKey::new([9u8; 32]).unwrap();
+        let streamer = PriorityStreamer::with_config(StreamerConfig {
+            integrity_key: Some(key.clone()),
+            ..StreamerConfig::default()
+        });
+        let payload = json!({"id": 7});
+
+        let plan = streamer.analyze(&payload).unwrap();
+        let mut reconstructor = JsonReconstructor::new().with_integrity_key(key);
+        for frame in plan.frames {
+            reconstructor.add_frame(frame);
+        }
+        reconstructor.process_all_frames().unwrap();
+
+        assert!(reconstructor.is_complete());
+        assert_eq!(reconstructor.current_state(), &payload);
+    }
+
+    #[test]
+    fn test_round_trip_scalar_root() {
+        let streamer = PriorityStreamer::new();
+        for payload in [json!("text"), json!(42), json!(true), json!(null)] {
+            assert_eq!(round_trip(&streamer, &payload), payload);
+        }
+    }
 }
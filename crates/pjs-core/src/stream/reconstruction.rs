@@ -2,16 +2,36 @@
 //!
 //! This module provides functionality to reconstruct complete JSON from
 //! skeleton + patch stream frames, enabling progressive data loading.
+//!
+//! Frames delivered over more than one path (e.g. a WebSocket reconnect
+//! plus an HTTP replay) can arrive out of order, twice, or not at all.
+//! [`JsonReconstructor::add_sequenced_frame`] puts them back in sequence
+//! order, drops duplicates, and reports gaps.
 
 use crate::Result;
-use crate::domain::value_objects::{JsonPath, PathSegment};
+use crate::domain::entities::frame::ArrayChunkMetadata;
+use crate::domain::value_objects::{IntegrityKey, JsonPath, PathSegment, verify_completion};
 use crate::stream::priority::{JsonPatch, PatchOperation, PriorityStreamFrame};
 use serde_json::Value as JsonValue;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Default for [`JsonReconstructor::with_gap_timeout`]
+pub const DEFAULT_GAP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Default for [`JsonReconstructor::with_reorder_window`]
+pub const DEFAULT_REORDER_WINDOW: u64 = 1024;
+
+/// Asks the sender to retransmit the given sequence numbers
+pub type RetransmitCallback = Box<dyn FnMut(&[u64]) + Send>;
 
 /// Client-side JSON reconstruction engine
 ///
-/// Applies streaming patches to progressively build complete JSON structure
+/// Applies streaming patches to progressively build complete JSON structure.
+/// When the completion frame carries a
+/// [`ContentDigest`](crate::domain::value_objects::ContentDigest), the
+/// reconstructed document is checked against it before reconstruction is
+/// reported complete.
 pub struct JsonReconstructor {
     /// Current state of JSON being reconstructed
     current_state: JsonValue,
@@ -21,6 +41,86 @@ pub struct JsonReconstructor {
     is_complete: bool,
     /// Statistics about reconstruction process
     stats: ReconstructionStats,
+    /// Key completion digests must be signed with, if any
+    integrity_key: Option<IntegrityKey>,
+    /// Reordering state for [`Self::add_sequenced_frame`]
+    sequencing: Sequencing,
+    /// Last chunk applied of each chunked array, by array path
+    array_progress: HashMap<JsonPath, ArrayChunkMetadata>,
+}
+
+/// Items of a chunked array received so far, as returned by
+/// [`JsonReconstructor::array_progress`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrayProgress {
+    /// Elements of the array applied so far
+    pub items_received: usize,
+    /// Elements in the complete array
+    pub total_items: usize,
+}
+
+/// Reordering and gap-tracking state of a [`JsonReconstructor`]
+struct Sequencing {
+    /// Sequence number expected first, if known before the skeleton arrives
+    first: Option<u64>,
+    /// Next sequence number to queue; `None` until anchored
+    next: Option<u64>,
+    /// Frames received ahead of a gap, by sequence number
+    pending: BTreeMap<u64, PriorityStreamFrame>,
+    /// When the current gap was first seen or last asked to be filled
+    gap_since: Option<Instant>,
+    /// How long a gap may stay open before retransmission is requested
+    gap_timeout: Duration,
+    /// How far ahead of `next` frames are buffered
+    window: u64,
+    /// Called with the missing sequence numbers once a gap times out
+    retransmit: Option<RetransmitCallback>,
+}
+
+impl Sequencing {
+    fn new() -> Self {
+        Self {
+            first: None,
+            next: None,
+            pending: BTreeMap::new(),
+            gap_since: None,
+            gap_timeout: DEFAULT_GAP_TIMEOUT,
+            window: DEFAULT_REORDER_WINDOW,
+            retransmit: None,
+        }
+    }
+
+    /// Forget received frames, keeping the configuration
+    fn clear(&mut self) {
+        self.next = self.first;
+        self.pending.clear();
+        self.gap_since = None;
+    }
+
+    /// Start expecting `sequence` next, dropping buffered frames that fall
+    /// before it or outside the window. Returns how many were stale.
+    fn anchor(&mut self, sequence: u64) -> u32 {
+        self.next = Some(sequence);
+        let before = self.pending.len();
+        self.pending = self.pending.split_off(&sequence);
+        let stale = before - self.pending.len();
+        let window = self.window;
+        self.pending
+            .retain(|buffered, _| buffered - sequence < window);
+        stale as u32
+    }
+
+    /// Remove the frames that are now in sequence, in order
+    fn release(&mut self) -> Vec<PriorityStreamFrame> {
+        let mut released = Vec::new();
+        while let Some(next) = self.next
+            && let Some(frame) = self.pending.remove(&next)
+        {
+            released.push(frame);
+            self.next = next.checked_add(1);
+        }
+        released
+    }
 }
 
 /// Statistics about the reconstruction process
@@ -38,6 +138,10 @@ pub struct ReconstructionStats {
     pub start_time: Option<std::time::Instant>,
     /// Time when reconstruction completed
     pub end_time: Option<std::time::Instant>,
+    /// Sequenced frames dropped because they had already been received
+    pub duplicate_frames: u32,
+    /// Times retransmission of missing frames was requested
+    pub retransmission_requests: u32,
 }
 
 impl JsonReconstructor {
@@ -48,10 +152,73 @@ impl JsonReconstructor {
             frame_queue: VecDeque::new(),
             is_complete: false,
             stats: ReconstructionStats::default(),
+            integrity_key: None,
+            sequencing: Sequencing::new(),
+            array_progress: HashMap::new(),
         }
     }
 
+    /// Require completion digests to be HMAC-signed with `key`.
+    ///
+    /// Without a key, plain SHA-256 digests are verified, signed ones are
+    /// rejected with [`IntegrityError::KeyRequired`], and a completion frame
+    /// without a digest is accepted. With a key, only a digest signed with
+    /// that key is accepted.
+    ///
+    /// [`IntegrityError::KeyRequired`]: crate::domain::value_objects::IntegrityError::KeyRequired
+    #[must_use]
+    pub fn with_integrity_key(mut self, key: IntegrityKey) -> Self {
+        self.integrity_key = Some(key);
+        self
+    }
+
+    /// Expect the stream's first frame to carry `sequence`.
+    ///
+    /// Without it, sequencing starts at whatever sequence number the
+    /// skeleton carries, so a lost skeleton cannot show up in
+    /// [`Self::missing_sequences`]. The domain `Stream` numbers its frames
+    /// from 1.
+    #[must_use]
+    pub fn with_first_sequence(mut self, sequence: u64) -> Self {
+        self.sequencing.first = Some(sequence);
+        self.sequencing.next = Some(sequence);
+        self
+    }
+
+    /// How long a gap in the sequence may stay open before retransmission is
+    /// requested (default [`DEFAULT_GAP_TIMEOUT`]).
+    #[must_use]
+    pub fn with_gap_timeout(mut self, timeout: Duration) -> Self {
+        self.sequencing.gap_timeout = timeout;
+        self
+    }
+
+    /// How many sequence numbers ahead of the next expected one frames are
+    /// buffered (default [`DEFAULT_REORDER_WINDOW`]); frames further ahead
+    /// are dropped.
+    #[must_use]
+    pub fn with_reorder_window(mut self, window: u64) -> Self {
+        self.sequencing.window = window;
+        self
+    }
+
+    /// Call `retransmit` with the missing sequence numbers whenever a gap
+    /// has stayed open for the gap timeout, and again after every further
+    /// timeout until it is filled.
+    #[must_use]
+    pub fn with_retransmit_callback(
+        mut self,
+        retransmit: impl FnMut(&[u64]) + Send + 'static,
+    ) -> Self {
+        self.sequencing.retransmit = Some(Box::new(retransmit));
+        self
+    }
+
     /// Add a frame to the reconstruction queue
+    ///
+    /// Frames added this way are processed in arrival order; use
+    /// [`Self::add_sequenced_frame`] when they may arrive out of order or
+    /// more than once.
     pub fn add_frame(&mut self, frame: PriorityStreamFrame) {
         if self.stats.start_time.is_none() {
             self.stats.start_time = Some(std::time::Instant::now());
@@ -60,7 +227,112 @@ impl JsonReconstructor {
         self.frame_queue.push_back(frame);
     }
 
+    /// Add a frame carrying sequence number `sequence`.
+    ///
+    /// The frame is queued once every earlier frame has been queued, so the
+    /// queue always holds frames in sequence order; until then it is
+    /// buffered. A frame whose sequence number has already been received is
+    /// dropped, which makes redelivery idempotent. Sequencing starts at
+    /// [`Self::with_first_sequence`] or, failing that, at the skeleton's
+    /// sequence number.
+    ///
+    /// Also requests retransmission if a gap has timed out, as
+    /// [`Self::check_gaps`] does.
+    pub fn add_sequenced_frame(
+        &mut self,
+        sequence: u64,
+        frame: PriorityStreamFrame,
+    ) -> FrameArrival {
+        if self.stats.start_time.is_none() {
+            self.stats.start_time = Some(Instant::now());
+        }
+
+        let sequencing = &mut self.sequencing;
+        if sequencing.next.is_none() && matches!(frame, PriorityStreamFrame::Skeleton { .. }) {
+            self.stats.duplicate_frames += sequencing.anchor(sequence);
+        }
+
+        let arrival = match sequencing.next {
+            Some(next) if sequence < next => FrameArrival::Duplicate,
+            Some(next) if sequence - next >= sequencing.window => FrameArrival::BeyondWindow,
+            _ if sequencing.pending.contains_key(&sequence) => FrameArrival::Duplicate,
+            None if sequencing.pending.len() as u64 >= sequencing.window => {
+                FrameArrival::BeyondWindow
+            }
+            _ => {
+                sequencing.pending.insert(sequence, frame);
+                FrameArrival::Buffered
+            }
+        };
+        if arrival == FrameArrival::Duplicate {
+            self.stats.duplicate_frames += 1;
+        }
+
+        let released = sequencing.release();
+        let arrival = if released.is_empty() {
+            arrival
+        } else {
+            FrameArrival::Queued
+        };
+        if sequencing.pending.is_empty() {
+            sequencing.gap_since = None;
+        } else if !released.is_empty() || sequencing.gap_since.is_none() {
+            sequencing.gap_since = Some(Instant::now());
+        }
+        self.frame_queue.extend(released);
+
+        self.check_gaps();
+        arrival
+    }
+
+    /// Sequence numbers the buffered frames are waiting on, in order.
+    ///
+    /// Empty while nothing is buffered, and until sequencing has started
+    /// (see [`Self::add_sequenced_frame`]).
+    pub fn missing_sequences(&self) -> Vec<u64> {
+        let sequencing = &self.sequencing;
+        match (sequencing.next, sequencing.pending.last_key_value()) {
+            (Some(next), Some((&last, _))) => (next..last)
+                .filter(|sequence| !sequencing.pending.contains_key(sequence))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Request retransmission if a gap has stayed open for the gap timeout.
+    ///
+    /// Calls the [retransmit callback](Self::with_retransmit_callback), if
+    /// any, and returns the sequence numbers requested; returns an empty
+    /// list when no request is due. Call it periodically while waiting for
+    /// frames: a gap left by the last frames sent is only noticed here.
+    pub fn check_gaps(&mut self) -> Vec<u64> {
+        let due = self
+            .sequencing
+            .gap_since
+            .is_some_and(|since| since.elapsed() >= self.sequencing.gap_timeout);
+        if !due {
+            return Vec::new();
+        }
+
+        let missing = self.missing_sequences();
+        if !missing.is_empty() {
+            self.sequencing.gap_since = Some(Instant::now());
+            self.stats.retransmission_requests += 1;
+            if let Some(retransmit) = &mut self.sequencing.retransmit {
+                retransmit(&missing);
+            }
+        }
+        missing
+    }
+
     /// Process next frame in the queue
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a patch cannot be applied, or
+    /// [`crate::Error::Integrity`] if a completion frame's digest does not
+    /// match the reconstructed document (the reconstructor then stays
+    /// incomplete).
     pub fn process_next_frame(&mut self) -> Result<ProcessResult> {
         if let Some(frame) = self.frame_queue.pop_front() {
             self.process_frame(frame)
@@ -69,7 +341,11 @@ impl JsonReconstructor {
         }
     }
 
-    /// Process all queued frames
+    /// Process all queued frames, stopping at the first error
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::process_next_frame`].
     pub fn process_all_frames(&mut self) -> Result<Vec<ProcessResult>> {
         let mut results = Vec::new();
 
@@ -92,6 +368,7 @@ impl JsonReconstructor {
             } => {
                 self.stats.skeleton_frames += 1;
                 self.current_state = data;
+                self.array_progress.clear();
                 Ok(ProcessResult::SkeletonApplied)
             }
 
@@ -101,7 +378,11 @@ impl JsonReconstructor {
 
                 for patch in patches {
                     let path = patch.path.clone();
+                    let array_metadata = patch.array_metadata;
                     self.apply_patch(patch)?;
+                    if let Some(metadata) = array_metadata {
+                        self.array_progress.insert(path.clone(), metadata);
+                    }
                     applied_paths.push(path);
                     self.stats.patches_applied += 1;
                 }
@@ -113,7 +394,12 @@ impl JsonReconstructor {
                 })
             }
 
-            PriorityStreamFrame::Complete { checksum: _ } => {
+            PriorityStreamFrame::Complete { checksum } => {
+                verify_completion(
+                    checksum.as_ref(),
+                    &self.current_state,
+                    self.integrity_key.as_ref(),
+                )?;
                 self.is_complete = true;
                 self.stats.end_time = Some(std::time::Instant::now());
                 Ok(ProcessResult::ReconstructionComplete)
@@ -315,10 +601,23 @@ impl JsonReconstructor {
         &self.stats
     }
 
+    /// Items received so far of the chunked array at `path`.
+    ///
+    /// `None` until a chunk of that array has been applied, and for arrays
+    /// sent whole.
+    pub fn array_progress(&self, path: &JsonPath) -> Option<ArrayProgress> {
+        self.array_progress.get(path).map(|metadata| ArrayProgress {
+            items_received: metadata.items_received(),
+            total_items: metadata.total_items,
+        })
+    }
+
     /// Reset reconstructor to initial state
     pub fn reset(&mut self) {
         self.current_state = JsonValue::Null;
         self.frame_queue.clear();
+        self.sequencing.clear();
+        self.array_progress.clear();
         self.is_complete = false;
         self.stats = ReconstructionStats::default();
     }
@@ -367,6 +666,19 @@ pub enum ProcessResult {
     ReconstructionComplete,
 }
 
+/// What [`JsonReconstructor::add_sequenced_frame`] did with a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameArrival {
+    /// Queued for processing, together with any buffered frames it unblocked
+    Queued,
+    /// Buffered until the frames before it arrive
+    Buffered,
+    /// Already received, so dropped
+    Duplicate,
+    /// Too far ahead of the next expected frame, so dropped
+    BeyondWindow,
+}
+
 impl Default for JsonReconstructor {
     fn default() -> Self {
         Self::new()
@@ -462,6 +774,7 @@ mod tests {
                 value: json!("John Doe"),
             },
             priority: Priority::HIGH,
+            array_metadata: None,
         };
 
         let frame = PriorityStreamFrame::Patch {
@@ -507,6 +820,7 @@ mod tests {
                 values: vec![json!("item1"), json!("item2")],
             },
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         let frame = PriorityStreamFrame::Patch {
@@ -613,6 +927,7 @@ mod tests {
                 value: json!("deep value"),
             },
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         let frame = PriorityStreamFrame::Patch {
@@ -662,6 +977,7 @@ mod tests {
                 value: json!({"final": "value"}),
             },
             priority: Priority::HIGH,
+            array_metadata: None,
         };
 
         let frame = PriorityStreamFrame::Patch {
@@ -692,6 +1008,7 @@ mod tests {
                 value: json!("third"),
             },
             priority: Priority::LOW,
+            array_metadata: None,
         };
 
         let patch_a = JsonPatch {
@@ -700,6 +1017,7 @@ mod tests {
                 value: json!("first"),
             },
             priority: Priority::LOW,
+            array_metadata: None,
         };
 
         let patch_b = JsonPatch {
@@ -708,6 +1026,7 @@ mod tests {
                 value: json!("second"),
             },
             priority: Priority::LOW,
+            array_metadata: None,
         };
 
         reconstructor.add_frame(PriorityStreamFrame::Patch {
@@ -749,6 +1068,7 @@ mod tests {
                 value: json!("updated"),
             },
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         let frame = PriorityStreamFrame::Patch {
@@ -780,6 +1100,7 @@ mod tests {
                 values: vec![json!(1), json!(2), json!(3)],
             },
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         let frame = PriorityStreamFrame::Patch {
@@ -806,6 +1127,7 @@ mod tests {
             path,
             operation: PatchOperation::Remove,
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         let frame = PriorityStreamFrame::Patch {
@@ -836,6 +1158,7 @@ mod tests {
             path,
             operation: PatchOperation::Remove,
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         let frame = PriorityStreamFrame::Patch {
@@ -867,6 +1190,7 @@ mod tests {
                 value: json!("new"),
             },
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         let frame = PriorityStreamFrame::Patch {
@@ -891,6 +1215,7 @@ mod tests {
                 value: json!("new"),
             },
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         reconstructor.apply_patch(patch).unwrap();
@@ -914,6 +1239,7 @@ mod tests {
                 value: json!("new"),
             },
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         let result = reconstructor.apply_patch(patch);
@@ -938,6 +1264,7 @@ mod tests {
                 value: json!("value"),
             },
             priority: Priority::MEDIUM,
+            array_metadata: None,
         };
 
         let result = reconstructor.apply_patch(patch);
@@ -976,4 +1303,254 @@ mod tests {
         // Should have duration now
         assert!(reconstructor.duration().is_some());
     }
+
+    #[test]
+    fn test_chunked_array_progress_is_tracked() {
+        use crate::domain::entities::Stream;
+        use crate::domain::entities::stream::{ArrayChunking, StreamConfig};
+        use crate::domain::value_objects::SessionId;
+
+        let rows: Vec<JsonValue> = (0..25).map(|i| json!({"row": i})).collect();
+        let source = json!({"title": "page", "rows": rows});
+        let config = StreamConfig {
+            default_array_chunking: Some(ArrayChunking::new(10)),
+            ..StreamConfig::default()
+        };
+        let mut stream = Stream::new(SessionId::new(), source.clone().into(), config);
+        stream.start_streaming().unwrap();
+        let mut frames = vec![stream.create_skeleton_frame().unwrap()];
+        frames.extend(
+            stream
+                .create_patch_frames(Priority::BACKGROUND, 16)
+                .unwrap(),
+        );
+
+        let rows_path = JsonPath::new("$.rows").unwrap();
+        let mut reconstructor = JsonReconstructor::new();
+        let mut seen = Vec::new();
+        for frame in &frames {
+            reconstructor.add_frame(PriorityStreamFrame::try_from(frame).unwrap());
+            reconstructor.process_next_frame().unwrap();
+            if let Some(progress) = reconstructor.array_progress(&rows_path) {
+                seen.push(progress.items_received);
+                assert_eq!(progress.total_items, 25);
+            }
+        }
+        seen.dedup();
+        assert_eq!(seen, vec![10, 20, 25]);
+        assert_eq!(reconstructor.current_state(), &source);
+
+        reconstructor.reset();
+        assert_eq!(reconstructor.array_progress(&rows_path), None);
+    }
+
+    fn skeleton(data: JsonValue) -> PriorityStreamFrame {
+        PriorityStreamFrame::Skeleton {
+            data,
+            priority: Priority::CRITICAL,
+            complete: false,
+        }
+    }
+
+    fn set(key: &str, value: JsonValue) -> PriorityStreamFrame {
+        PriorityStreamFrame::Patch {
+            patches: vec![JsonPatch {
+                path: JsonPath::from_segments(vec![PathSegment::Key(key.to_string())]).unwrap(),
+                operation: PatchOperation::Set { value },
+                priority: Priority::MEDIUM,
+                array_metadata: None,
+            }],
+            priority: Priority::MEDIUM,
+        }
+    }
+
+    fn append(key: &str, values: Vec<JsonValue>) -> PriorityStreamFrame {
+        PriorityStreamFrame::Patch {
+            patches: vec![JsonPatch {
+                path: JsonPath::from_segments(vec![PathSegment::Key(key.to_string())]).unwrap(),
+                operation: PatchOperation::Append { values },
+                priority: Priority::MEDIUM,
+                array_metadata: None,
+            }],
+            priority: Priority::MEDIUM,
+        }
+    }
+
+    #[test]
+    fn test_sequenced_frames_are_applied_in_sequence_order() {
+        let mut reconstructor = JsonReconstructor::new();
+
+        assert_eq!(
+            reconstructor.add_sequenced_frame(3, append("items", vec![json!(3)])),
+            FrameArrival::Buffered
+        );
+        assert_eq!(
+            reconstructor.add_sequenced_frame(4, PriorityStreamFrame::Complete { checksum: None }),
+            FrameArrival::Buffered
+        );
+        assert_eq!(
+            reconstructor.add_sequenced_frame(1, skeleton(json!({"items": []}))),
+            FrameArrival::Queued
+        );
+        assert_eq!(reconstructor.missing_sequences(), vec![2]);
+        assert_eq!(
+            reconstructor.add_sequenced_frame(2, append("items", vec![json!(1), json!(2)])),
+            FrameArrival::Queued
+        );
+        assert!(reconstructor.missing_sequences().is_empty());
+
+        reconstructor.process_all_frames().unwrap();
+        assert_eq!(reconstructor.current_state(), &json!({"items": [1, 2, 3]}));
+        assert!(reconstructor.is_complete());
+    }
+
+    #[test]
+    fn test_duplicate_sequenced_frames_are_dropped() {
+        let mut reconstructor = JsonReconstructor::new();
+        reconstructor.add_sequenced_frame(0, skeleton(json!({"items": []})));
+        reconstructor.add_sequenced_frame(2, append("items", vec![json!(2)]));
+
+        // Redelivered before and after being applied.
+        assert_eq!(
+            reconstructor.add_sequenced_frame(2, append("items", vec![json!(2)])),
+            FrameArrival::Duplicate
+        );
+        reconstructor.add_sequenced_frame(1, append("items", vec![json!(1)]));
+        for sequence in [0, 1, 2] {
+            assert_eq!(
+                reconstructor.add_sequenced_frame(sequence, append("items", vec![json!(0)])),
+                FrameArrival::Duplicate
+            );
+        }
+
+        reconstructor.process_all_frames().unwrap();
+        assert_eq!(reconstructor.current_state(), &json!({"items": [1, 2]}));
+        assert_eq!(reconstructor.stats().duplicate_frames, 4);
+    }
+
+    #[test]
+    fn test_gaps_are_reported_and_retransmission_requested() {
+        use std::sync::{Arc, Mutex};
+
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&requested);
+        let mut reconstructor = JsonReconstructor::new()
+            .with_first_sequence(1)
+            .with_gap_timeout(Duration::from_secs(3600))
+            .with_retransmit_callback(move |missing| sink.lock().unwrap().push(missing.to_vec()));
+
+        // The skeleton is lost: known to be missing only through the first sequence.
+        reconstructor.add_sequenced_frame(3, set("a", json!(1)));
+        reconstructor.add_sequenced_frame(5, set("b", json!(2)));
+        assert_eq!(reconstructor.missing_sequences(), vec![1, 2, 4]);
+        assert!(reconstructor.check_gaps().is_empty(), "not timed out yet");
+
+        reconstructor.sequencing.gap_timeout = Duration::ZERO;
+        assert_eq!(reconstructor.check_gaps(), vec![1, 2, 4]);
+        reconstructor.add_sequenced_frame(1, skeleton(json!({"a": null, "b": null})));
+        reconstructor.add_sequenced_frame(2, set("a", json!(0)));
+        reconstructor.add_sequenced_frame(4, set("b", json!(0)));
+
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec![vec![1, 2, 4], vec![2, 4], vec![4]]
+        );
+        assert_eq!(reconstructor.stats().retransmission_requests, 3);
+        assert!(reconstructor.check_gaps().is_empty());
+
+        reconstructor.process_all_frames().unwrap();
+        assert_eq!(reconstructor.current_state(), &json!({"a": 1, "b": 2}));
+    }
+
+    #[test]
+    fn test_sequencing_window_and_reset() {
+        let mut reconstructor = JsonReconstructor::new().with_reorder_window(4);
+
+        // Buffered before the skeleton: stale and far-ahead frames are dropped on anchoring.
+        reconstructor.add_sequenced_frame(5, set("stale", json!(true)));
+        reconstructor.add_sequenced_frame(20, set("far", json!(true)));
+        reconstructor.add_sequenced_frame(10, skeleton(json!({})));
+        assert!(reconstructor.missing_sequences().is_empty());
+        assert_eq!(reconstructor.stats().duplicate_frames, 1);
+        assert_eq!(
+            reconstructor.add_sequenced_frame(15, set("far", json!(true))),
+            FrameArrival::BeyondWindow
+        );
+
+        reconstructor.reset();
+        assert_eq!(
+            reconstructor.add_sequenced_frame(2, skeleton(json!({"id": 2}))),
+            FrameArrival::Queued
+        );
+        reconstructor.process_all_frames().unwrap();
+        assert_eq!(reconstructor.current_state(), &json!({"id": 2}));
+    }
+
+    fn key(byte: u8) -> IntegrityKey {
+        IntegrityKey::new(vec![byte; IntegrityKey::MIN_LEN]).unwrap()
+    }
+
+    fn skeleton_then_complete(
+        reconstructor: &mut JsonReconstructor,
+        document: JsonValue,
+        checksum: Option<crate::domain::value_objects::ContentDigest>,
+    ) -> Result<Vec<ProcessResult>> {
+        reconstructor.add_frame(PriorityStreamFrame::Skeleton {
+            data: document,
+            priority: Priority::CRITICAL,
+            complete: true,
+        });
+        reconstructor.add_frame(PriorityStreamFrame::Complete { checksum });
+        reconstructor.process_all_frames()
+    }
+
+    #[test]
+    fn test_completion_digest_mismatch_is_reported() {
+        use crate::domain::value_objects::{ContentDigest, IntegrityError};
+
+        let sent = json!({"id": 1, "items": [1, 2]});
+        let digest = ContentDigest::compute(&sent, None);
+
+        let mut reconstructor = JsonReconstructor::new();
+        let err = skeleton_then_complete(
+            &mut reconstructor,
+            json!({"id": 1, "items": [2, 1]}),
+            Some(digest),
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            crate::Error::Integrity(IntegrityError::Mismatch { expected, .. }) if expected == digest
+        ));
+        assert!(err.to_string().contains("content digest mismatch"));
+        assert!(!reconstructor.is_complete());
+    }
+
+    #[test]
+    fn test_signed_completion_digest() {
+        use crate::domain::value_objects::{ContentDigest, IntegrityError};
+
+        let document = json!({"id": 1});
+        let signed = ContentDigest::compute(&document, Some(&key(1)));
+
+        let mut holder = JsonReconstructor::new().with_integrity_key(key(1));
+        skeleton_then_complete(&mut holder, document.clone(), Some(signed)).unwrap();
+        assert!(holder.is_complete());
+
+        let mut keyless = JsonReconstructor::new();
+        assert!(matches!(
+            skeleton_then_complete(&mut keyless, document.clone(), Some(signed)),
+            Err(crate::Error::Integrity(IntegrityError::KeyRequired))
+        ));
+
+        // A key holder cannot be downgraded by stripping or replacing the signature.
+        for checksum in [None, Some(ContentDigest::compute(&document, None))] {
+            let mut holder = JsonReconstructor::new().with_integrity_key(key(1));
+            assert!(matches!(
+                skeleton_then_complete(&mut holder, document.clone(), checksum),
+                Err(crate::Error::Integrity(IntegrityError::Unsigned))
+            ));
+        }
+    }
 }
@@ -127,12 +127,21 @@ impl StreamRepositoryGat for MockRepository {
         = impl Future<Output = DomainResult<Vec<DomainEvent>>> + Send + 'a
     where
         Self: 'a;
+    type CancelStreamAtomicFuture<'a>
+        = impl Future<Output = DomainResult<Vec<DomainEvent>>> + Send + 'a
+    where
+        Self: 'a;
 
     type CreateStreamPatchFramesAtomicFuture<'a>
         = impl Future<Output = DomainResult<(Vec<Frame>, Vec<DomainEvent>)>> + Send + 'a
     where
         Self: 'a;
 
+    type UpdateStreamDataAtomicFuture<'a>
+        = impl Future<Output = DomainResult<(Vec<Frame>, Vec<DomainEvent>)>> + Send + 'a
+    where
+        Self: 'a;
+
     type BatchGenerateFramesAtomicFuture<'a>
         = impl Future<Output = DomainResult<(Vec<Frame>, Vec<DomainEvent>)>> + Send + 'a
     where
@@ -225,6 +234,21 @@ impl StreamRepositoryGat for MockRepository {
         }
     }
 
+    fn cancel_stream_atomic(
+        &self,
+        session_id: SessionId,
+        stream_id: StreamId,
+    ) -> Self::CancelStreamAtomicFuture<'_> {
+        async move {
+            let mut sessions = self.sessions.lock();
+            let session = sessions.get_mut(&session_id).ok_or_else(|| {
+                DomainError::SessionNotFound(format!("Session {session_id} not found"))
+            })?;
+            session.cancel_stream(stream_id)?;
+            Ok(session.take_events().into_iter().collect())
+        }
+    }
+
     fn create_stream_patch_frames_atomic(
         &self,
         session_id: SessionId,
@@ -238,7 +262,24 @@ impl StreamRepositoryGat for MockRepository {
                 DomainError::SessionNotFound(format!("Session {session_id} not found"))
             })?;
             let frames =
-                session.create_stream_patch_frames(stream_id, priority_threshold, max_frames)?;
+                session.next_stream_patch_frames(stream_id, priority_threshold, max_frames)?;
+            Ok((frames, session.take_events().into_iter().collect()))
+        }
+    }
+
+    fn update_stream_data_atomic(
+        &self,
+        session_id: SessionId,
+        stream_id: StreamId,
+        source_data: JsonData,
+        max_frames: usize,
+    ) -> Self::UpdateStreamDataAtomicFuture<'_> {
+        async move {
+            let mut sessions = self.sessions.lock();
+            let session = sessions.get_mut(&session_id).ok_or_else(|| {
+                DomainError::SessionNotFound(format!("Session {session_id} not found"))
+            })?;
+            let frames = session.update_stream_data(stream_id, source_data, max_frames)?;
             Ok((frames, session.take_events().into_iter().collect()))
         }
     }
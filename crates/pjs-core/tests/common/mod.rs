@@ -90,6 +90,12 @@ impl StreamRepositoryGat for MockRepository {
         + 'a
     where
         Self: 'a;
+    type CancelStreamAtomicFuture<'a>
+        = impl std::future::Future<Output = pjson_rs::domain::DomainResult<Vec<DomainEvent>>>
+        + Send
+        + 'a
+    where
+        Self: 'a;
 
     type CreateStreamPatchFramesAtomicFuture<'a>
         = impl std::future::Future<
@@ -102,6 +108,17 @@ impl StreamRepositoryGat for MockRepository {
     where
         Self: 'a;
 
+    type UpdateStreamDataAtomicFuture<'a>
+        = impl std::future::Future<
+            Output = pjson_rs::domain::DomainResult<(
+                Vec<pjson_rs::domain::entities::Frame>,
+                Vec<DomainEvent>,
+            )>,
+        > + Send
+        + 'a
+    where
+        Self: 'a;
+
     type BatchGenerateFramesAtomicFuture<'a>
         = impl std::future::Future<
             Output = pjson_rs::domain::DomainResult<(
@@ -214,6 +231,23 @@ impl StreamRepositoryGat for MockRepository {
         }
     }
 
+    fn cancel_stream_atomic(
+        &self,
+        session_id: SessionId,
+        stream_id: StreamId,
+    ) -> Self::CancelStreamAtomicFuture<'_> {
+        async move {
+            let mut sessions = self.sessions.lock();
+            let session = sessions.get_mut(&session_id).ok_or_else(|| {
+                pjson_rs::domain::DomainError::SessionNotFound(format!(
+                    "Session {session_id} not found"
+                ))
+            })?;
+            session.cancel_stream(stream_id)?;
+            Ok(session.take_events().into_iter().collect())
+        }
+    }
+
     fn create_stream_patch_frames_atomic(
         &self,
         session_id: SessionId,
@@ -229,7 +263,26 @@ impl StreamRepositoryGat for MockRepository {
                 ))
             })?;
             let frames =
-                session.create_stream_patch_frames(stream_id, priority_threshold, max_frames)?;
+                session.next_stream_patch_frames(stream_id, priority_threshold, max_frames)?;
+            Ok((frames, session.take_events().into_iter().collect()))
+        }
+    }
+
+    fn update_stream_data_atomic(
+        &self,
+        session_id: SessionId,
+        stream_id: StreamId,
+        source_data: pjson_rs::domain::value_objects::JsonData,
+        max_frames: usize,
+    ) -> Self::UpdateStreamDataAtomicFuture<'_> {
+        async move {
+            let mut sessions = self.sessions.lock();
+            let session = sessions.get_mut(&session_id).ok_or_else(|| {
+                pjson_rs::domain::DomainError::SessionNotFound(format!(
+                    "Session {session_id} not found"
+                ))
+            })?;
+            let frames = session.update_stream_data(stream_id, source_data, max_frames)?;
             Ok((frames, session.take_events().into_iter().collect()))
         }
     }
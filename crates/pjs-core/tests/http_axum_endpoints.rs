@@ -416,6 +416,76 @@ async fn test_get_stream_not_found() {
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
+#[tokio::test]
+async fn test_cancel_stream_success() {
+    let mut session = common::SessionBuilder::new().build();
+    let session_id = session.id();
+
+    let stream_id = session
+        .create_stream(serde_json::json!({"test": "data"}).into())
+        .unwrap();
+    session.start_stream(stream_id).unwrap();
+
+    let state = common::create_test_app_state_with_session(session);
+    let app = create_pjs_router().with_state(state);
+
+    let cancel = || {
+        Request::builder()
+            .uri(format!("/pjs/sessions/{session_id}/streams/{stream_id}"))
+            .method("DELETE")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(cancel()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: JsonValue = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["status"], "cancelled");
+
+    let stats = Request::builder()
+        .uri(format!("/pjs/sessions/{session_id}/stats"))
+        .method("GET")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(stats).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: JsonValue = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["stats"]["cancelled_streams"], 1);
+    assert_eq!(json["stats"]["active_streams"], 0);
+
+    // Already cancelled.
+    let response = app.oneshot(cancel()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_cancel_stream_not_found() {
+    let session = common::SessionBuilder::new().build();
+    let session_id = session.id();
+
+    let state = common::create_test_app_state_with_session(session);
+    let app = create_pjs_router().with_state(state);
+
+    let request = Request::builder()
+        .uri(format!(
+            "/pjs/sessions/{}/streams/{}",
+            session_id,
+            StreamId::new()
+        ))
+        .method("DELETE")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
 // ===== System Health Tests =====
 
 #[tokio::test]
@@ -760,18 +830,24 @@ mod stream_frames_stream {
     /// Builds a router with a session/stream that already has `frame_count` frames
     /// generated via `POST .../generate-frames`.
     ///
-    /// Each frame is produced from its own top-level object key (`extract_patches`
-    /// emits one patch per leaf-level value, and `chunk_patches_for_commit` chunks
-    /// 1:1 into frames when `patches.len() == max_frames`), so `frame_count` keys
-    /// generated at `max_frames = frame_count` yields exactly `frame_count` frames.
+    /// Each frame is produced from its own top-level object key: the stream
+    /// emits one patch per leaf-level value, and a one-byte `max_frame_size`
+    /// gives every patch a frame of its own, so `frame_count` keys generated
+    /// at `max_frames = frame_count` yields exactly `frame_count` frames.
     async fn seed_streamed_frames(frame_count: usize) -> (axum::Router, SessionId, StreamId) {
+        use pjson_rs::domain::entities::stream::StreamConfig;
+
         let mut session = common::SessionBuilder::new().build();
         let session_id = session.id();
         let data: serde_json::Map<String, JsonValue> = (0..frame_count)
             .map(|i| (format!("k{i}"), JsonValue::from(i)))
             .collect();
+        let config = StreamConfig {
+            max_frame_size: 1,
+            ..StreamConfig::default()
+        };
         let stream_id = session
-            .create_stream(JsonValue::Object(data).into())
+            .create_stream_with_config(JsonValue::Object(data).into(), Some(config))
             .unwrap();
         session.start_stream(stream_id).unwrap();
 
@@ -825,6 +901,57 @@ mod stream_frames_stream {
         builder.body(Body::empty()).unwrap()
     }
 
+    async fn cancelled_streams(app: &axum::Router, session_id: SessionId) -> u64 {
+        let request = Request::builder()
+            .uri(format!("/pjs/sessions/{session_id}/stats"))
+            .method("GET")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let stats: JsonValue = serde_json::from_slice(&body).unwrap();
+        stats["stats"]["cancelled_streams"].as_u64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stream_frames_client_disconnect_cancels_stream() {
+        let (app, session_id, stream_id) = seed_streamed_frames(4).await;
+
+        // Reading the whole page leaves the stream alone.
+        let response = app
+            .clone()
+            .oneshot(stream_request(session_id, stream_id, None))
+            .await
+            .unwrap();
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        tokio::task::yield_now().await;
+        assert_eq!(cancelled_streams(&app, session_id).await, 0);
+
+        // Hanging up after the first frame cancels it.
+        let response = app
+            .clone()
+            .oneshot(stream_request(session_id, stream_id, None))
+            .await
+            .unwrap();
+        let mut body = response.into_body().into_data_stream();
+        assert!(body.next().await.is_some());
+        drop(body);
+
+        let mut cancelled = 0;
+        for _ in 0..100 {
+            cancelled = cancelled_streams(&app, session_id).await;
+            if cancelled == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(cancelled, 1, "disconnect must cancel the stream");
+    }
+
     #[tokio::test]
     async fn test_stream_frames_default_accept_returns_ndjson() {
         let (app, session_id, stream_id) = seed_streamed_frames(4).await;
@@ -859,6 +986,43 @@ mod stream_frames_stream {
         assert_eq!(line_count, 4);
     }
 
+    #[tokio::test]
+    async fn test_stream_frames_negotiates_pjs_json() {
+        let (app, session_id, stream_id) = seed_streamed_frames(2).await;
+
+        let response = app
+            .oneshot(stream_request(
+                session_id,
+                stream_id,
+                Some("application/pjs+json"),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/pjs+json"
+        );
+        assert_eq!(response.headers().get("PJS-Version").unwrap(), "1.0");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        let mut decoded = Vec::new();
+        for line in text.lines().filter(|l| !l.is_empty()) {
+            let v: JsonValue = serde_json::from_str(line).unwrap();
+            assert!(v["@seq"].is_u64());
+            let frame = pjson_rs::stream::pjs_json::decode_frame(stream_id, &v)
+                .expect("server output must decode")
+                .expect("no heartbeats on this route");
+            decoded.push(frame);
+        }
+        assert!(!decoded.is_empty());
+        assert!(decoded.iter().all(|frame| frame.stream_id() == stream_id));
+    }
+
     /// Regression test for #516: the streaming route's per-frame JSON shape must
     /// match the buffered `/frames` route's shape exactly — same field names
     /// (`frame_type`, not the old hand-rolled `type`), `stream_id` present, and
@@ -1104,6 +1268,46 @@ mod stream_frames_stream {
         );
     }
 
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_stream_frames_negotiates_content_encoding() {
+        use pjson_rs::compression::secure::{ByteCodec, SecureCompressedData, SecureCompressor};
+
+        let (app, session_id, stream_id) = seed_streamed_frames(3).await;
+
+        let plain = app
+            .clone()
+            .oneshot(stream_request(session_id, stream_id, None))
+            .await
+            .unwrap();
+        assert!(plain.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(plain.headers()[header::VARY], "accept-encoding");
+        let plain = axum::body::to_bytes(plain.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        let mut request = stream_request(session_id, stream_id, None);
+        request
+            .headers_mut()
+            .insert(header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        let decoded = SecureCompressor::with_default_security(ByteCodec::Gzip)
+            .decompress_protected(&SecureCompressedData {
+                data: body.to_vec(),
+                original_size: plain.len(),
+                compression_ratio: 0.0,
+                codec: ByteCodec::Gzip,
+            })
+            .unwrap();
+        assert_eq!(decoded, plain);
+    }
+
     #[tokio::test]
     async fn test_stream_frames_not_found() {
         let state = common::create_test_app_state();
@@ -1153,4 +1357,21 @@ mod stream_frames_stream {
             .unwrap();
         assert!(body.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_stream_frames_refused_while_shutting_down() {
+        use pjson_rs::infrastructure::shutdown::ShutdownCoordinator;
+
+        let coordinator = ShutdownCoordinator::new(std::time::Duration::from_secs(1));
+        let state = common::create_test_app_state().with_drain_signal(coordinator.signal());
+        let app = create_pjs_router().with_state(state);
+        coordinator.shutdown().await;
+
+        let response = app
+            .oneshot(stream_request(SessionId::new(), StreamId::new(), None))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
 }
@@ -17,7 +17,7 @@
 use axum::{
     Router,
     body::{Body, to_bytes},
-    extract::Request,
+    extract::{ConnectInfo, Request},
     http::{Method, StatusCode, header},
     response::IntoResponse,
 };
@@ -27,6 +27,7 @@ use pjson_rs::infrastructure::http::axum_extension::{
     StreamResponse,
 };
 use serde_json::json;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use tower::ServiceExt;
@@ -263,6 +264,19 @@ fn test_stream_error_internal_error_response() {
     assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
 }
 
+#[test]
+fn test_stream_error_capacity_responses() {
+    let error = StreamExtensionError::TooManyStreams(10);
+    assert_eq!(error.to_string(), "Too many streams: at most 10 per client");
+    assert_eq!(
+        error.into_response().status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let response = StreamExtensionError::StoreFull.into_response();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
 // ============================================================================
 // StreamRequest and StreamResponse Tests
 // ============================================================================
@@ -443,6 +457,7 @@ async fn test_stream_request_endpoint_creates_stream() {
             Request::builder()
                 .method(Method::POST)
                 .uri("/pjs/stream")
+                .extension(ConnectInfo(SocketAddr::from(([192, 0, 2, 1], 4000))))
                 .header(header::CONTENT_TYPE, "application/json")
                 .body(Body::from(serde_json::to_vec(&request_body).unwrap()))
                 .unwrap(),
@@ -480,6 +495,7 @@ async fn test_stream_request_format_detection_from_accept_header() {
             Request::builder()
                 .method(Method::POST)
                 .uri("/pjs/stream")
+                .extension(ConnectInfo(SocketAddr::from(([192, 0, 2, 1], 4000))))
                 .header(header::CONTENT_TYPE, "application/json")
                 .header(header::ACCEPT, "text/event-stream")
                 .body(Body::from(serde_json::to_vec(&request_body).unwrap()))
@@ -513,6 +529,7 @@ async fn test_stream_request_format_detection_ndjson() {
             Request::builder()
                 .method(Method::POST)
                 .uri("/pjs/stream")
+                .extension(ConnectInfo(SocketAddr::from(([192, 0, 2, 1], 4000))))
                 .header(header::CONTENT_TYPE, "application/json")
                 .header(header::ACCEPT, "application/x-ndjson")
                 .body(Body::from(serde_json::to_vec(&request_body).unwrap()))
@@ -531,6 +548,28 @@ async fn test_stream_request_format_detection_ndjson() {
 // SSE Stream Endpoint Tests
 // ============================================================================
 
+/// Create a stream through `POST /pjs/stream`, returning its `Location`.
+async fn create_stream(app: &Router) -> String {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/pjs/stream")
+                .extension(ConnectInfo(SocketAddr::from(([192, 0, 2, 1], 4000))))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"data":{"id":1,"name":"test"}}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    response.headers()[header::LOCATION]
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
 #[tokio::test]
 async fn test_sse_stream_endpoint_returns_event_stream() {
     let config = HttpExtensionConfig::default();
@@ -539,10 +578,11 @@ async fn test_sse_stream_endpoint_returns_event_stream() {
     let app = Router::new();
     let app = extension.extend_router(app);
 
+    let location = create_stream(&app).await;
     let response = app
         .oneshot(
             Request::builder()
-                .uri("/pjs/stream/test-123/sse")
+                .uri(format!("{location}/sse"))
                 .body(Body::empty())
                 .unwrap(),
         )
@@ -675,10 +715,11 @@ async fn test_sse_stream_endpoint_invalid_allowed_origins_fails_closed() {
     let app = Router::new();
     let app = extension.extend_router(app);
 
+    let location = create_stream(&app).await;
     let response = app
         .oneshot(
             Request::builder()
-                .uri("/pjs/stream/test-123/sse")
+                .uri(format!("{location}/sse"))
                 .header("Origin", "https://app.example.com")
                 .body(Body::empty())
                 .unwrap(),
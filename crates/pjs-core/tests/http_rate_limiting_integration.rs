@@ -567,3 +567,182 @@ async fn test_rate_limit_overhead_minimal() {
         avg_per_request
     );
 }
+
+// ============================================================================
+// Shared RateLimitStore Tests
+// ============================================================================
+
+mod shared_store {
+    use super::*;
+    use pjson_rs::security::{
+        InProcessBackend, RateLimitAlgorithm, RateLimitDecision, RateLimitKey, RateLimitPolicy,
+        RateLimitStore, RateLimitStoreError, SharedRateLimitStore,
+        rate_limit_store::RateLimitStoreFuture,
+    };
+    use std::sync::Arc;
+
+    fn replica(backend: &Arc<InProcessBackend>, policy: RateLimitPolicy) -> Router {
+        let store: Arc<dyn RateLimitStore> = Arc::new(SharedRateLimitStore::new(backend.clone()));
+        Router::new()
+            .route("/test", get(test_handler))
+            .layer(RateLimitMiddleware::from_store(store, policy))
+    }
+
+    async fn send(app: &Router, from: [u8; 4], api_key: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri("/test");
+        if let Some(api_key) = api_key {
+            request = request.header("X-PJS-API-Key", api_key);
+        }
+        let mut request = request.body(Body::empty()).unwrap();
+        request.extensions_mut().insert(peer(from, 40_000));
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_replicas_share_one_budget() {
+        let backend = Arc::new(InProcessBackend::new());
+        let policy = RateLimitPolicy::new(RateLimitAlgorithm::sliding_window(
+            3,
+            Duration::from_secs(60),
+        ));
+        let replica_a = replica(&backend, policy.clone());
+        let replica_b = replica(&backend, policy);
+
+        assert_eq!(
+            send(&replica_a, [198, 51, 100, 1], None).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&replica_b, [198, 51, 100, 1], None).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&replica_a, [198, 51, 100, 1], None).await,
+            StatusCode::OK
+        );
+        // A load balancer sending the fourth request to the other replica no
+        // longer grants a fresh budget.
+        assert_eq!(
+            send(&replica_b, [198, 51, 100, 1], None).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            send(&replica_b, [198, 51, 100, 2], None).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registered_api_key_budget_follows_key_across_addresses() {
+        let backend = Arc::new(InProcessBackend::new());
+        let policy = RateLimitPolicy::new(RateLimitAlgorithm::sliding_window(
+            1,
+            Duration::from_secs(60),
+        ))
+        .with_api_key(
+            "partner-key",
+            RateLimitAlgorithm::TokenBucket {
+                capacity: 2,
+                refill_interval: Duration::from_secs(3600),
+            },
+        );
+        let app = replica(&backend, policy);
+
+        assert_eq!(
+            send(&app, [198, 51, 100, 1], Some("partner-key")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&app, [198, 51, 100, 2], Some("partner-key")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&app, [198, 51, 100, 3], Some("partner-key")).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // Unregistered keys cannot mint fresh budgets: they are charged to
+        // the address under the default algorithm.
+        assert_eq!(
+            send(&app, [198, 51, 100, 4], Some("guess-1")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&app, [198, 51, 100, 4], Some("guess-2")).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decision_drives_headers() {
+        let backend = Arc::new(InProcessBackend::new());
+        let app = replica(
+            &backend,
+            RateLimitPolicy::new(RateLimitAlgorithm::sliding_window(
+                1,
+                Duration::from_secs(60),
+            )),
+        );
+
+        let mut request = Request::builder().uri("/test").body(Body::empty()).unwrap();
+        request.extensions_mut().insert(peer([198, 51, 100, 9], 1));
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["X-RateLimit-Limit"], "1");
+        assert_eq!(response.headers()["X-RateLimit-Remaining"], "0");
+
+        let mut request = Request::builder().uri("/test").body(Body::empty()).unwrap();
+        request.extensions_mut().insert(peer([198, 51, 100, 9], 1));
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(retry_after >= 1);
+    }
+
+    #[derive(Debug)]
+    struct UnreachableStore;
+
+    impl RateLimitStore for UnreachableStore {
+        fn check<'a>(
+            &'a self,
+            _key: &'a RateLimitKey,
+            _algorithm: &'a RateLimitAlgorithm,
+        ) -> RateLimitStoreFuture<'a, RateLimitDecision> {
+            Box::pin(std::future::ready(Err(RateLimitStoreError::Backend(
+                "connection refused".to_string(),
+            ))))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_failure_fails_closed_unless_fail_open() {
+        let policy = RateLimitPolicy::new(RateLimitAlgorithm::sliding_window(
+            10,
+            Duration::from_secs(60),
+        ));
+
+        let closed =
+            Router::new()
+                .route("/test", get(test_handler))
+                .layer(RateLimitMiddleware::from_store(
+                    Arc::new(UnreachableStore),
+                    policy.clone(),
+                ));
+        assert_eq!(
+            send(&closed, [198, 51, 100, 1], None).await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        let open =
+            Router::new()
+                .route("/test", get(test_handler))
+                .layer(RateLimitMiddleware::from_store(
+                    Arc::new(UnreachableStore),
+                    policy.with_fail_open(true),
+                ));
+        assert_eq!(send(&open, [198, 51, 100, 1], None).await, StatusCode::OK);
+    }
+}
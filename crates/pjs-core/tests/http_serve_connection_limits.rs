@@ -13,10 +13,12 @@ use std::net::SocketAddr;
 use std::time::{Duration, Instant};
 
 use axum::{Router, extract::ConnectInfo, routing::get};
-use pjson_rs::infrastructure::http::{ConnectionLimits, serve_with_limits};
+use pjson_rs::infrastructure::http::{ConnectionLimits, serve_with_limits, serve_with_shutdown};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     net::{TcpListener, TcpStream},
+    sync::oneshot,
+    task::JoinHandle,
     time::timeout,
 };
 
@@ -435,3 +437,108 @@ async fn max_connections_backpressures_additional_connections() {
         "unexpected response for client B: {response}"
     );
 }
+
+/// Binds `router` behind `serve_with_shutdown`, returning the bound address,
+/// the sender that triggers shutdown, and the server task.
+async fn spawn_stoppable_server(
+    router: Router,
+    limits: ConnectionLimits,
+) -> (
+    SocketAddr,
+    oneshot::Sender<()>,
+    JoinHandle<std::io::Result<()>>,
+) {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind random port");
+    let addr = listener.local_addr().expect("local_addr");
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_with_shutdown(listener, router, limits, async {
+        let _ = stop_rx.await;
+    }));
+    (addr, stop_tx, server)
+}
+
+/// A request that is already in flight when shutdown starts still gets its
+/// full response, the server returns once it has, and the listener stops
+/// accepting new connections.
+#[tokio::test]
+async fn shutdown_drains_in_flight_request_then_stops_accepting() {
+    let router = Router::new().route(
+        "/slow",
+        get(|| async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            "done"
+        }),
+    );
+    let (addr, stop_tx, server) = spawn_stoppable_server(router, ConnectionLimits::default()).await;
+
+    let mut client = TcpStream::connect(addr).await.expect("connect");
+    client
+        .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .expect("write request");
+    // Let the request reach the handler before shutting down.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    stop_tx.send(()).expect("server still running");
+
+    let mut response = Vec::new();
+    timeout(Duration::from_secs(3), client.read_to_end(&mut response))
+        .await
+        .expect("connection was not closed after the drained response")
+        .expect("read error");
+    let response = String::from_utf8_lossy(&response);
+    assert!(
+        response.starts_with("HTTP/1.1 200") && response.ends_with("done"),
+        "in-flight request was cut off by shutdown: {response}"
+    );
+
+    timeout(Duration::from_secs(3), server)
+        .await
+        .expect("server did not return after draining")
+        .expect("server task panicked")
+        .expect("server returned an error");
+    assert!(
+        TcpStream::connect(addr).await.is_err(),
+        "listener must be closed once shutdown has started"
+    );
+}
+
+/// A connection that never finishes within `drain_timeout` is closed
+/// forcibly rather than holding the shutdown open indefinitely.
+#[tokio::test]
+async fn drain_timeout_closes_connections_that_do_not_finish() {
+    let router = Router::new().route("/hang", get(std::future::pending::<&'static str>));
+    let mut limits = ConnectionLimits::default();
+    limits.drain_timeout = Duration::from_millis(150);
+    let (addr, stop_tx, server) = spawn_stoppable_server(router, limits).await;
+
+    let mut client = TcpStream::connect(addr).await.expect("connect");
+    client
+        .write_all(b"GET /hang HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .expect("write request");
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let start = Instant::now();
+    stop_tx.send(()).expect("server still running");
+    timeout(Duration::from_secs(3), server)
+        .await
+        .expect("server ignored drain_timeout")
+        .expect("server task panicked")
+        .expect("server returned an error");
+    assert!(
+        start.elapsed() >= Duration::from_millis(100),
+        "server returned before drain_timeout elapsed"
+    );
+
+    let mut buf = [0u8; 16];
+    let read = timeout(Duration::from_secs(3), client.read(&mut buf))
+        .await
+        .expect("hung connection was not closed");
+    assert_eq!(
+        read.expect("read error"),
+        0,
+        "expected EOF on the forcibly closed connection"
+    );
+}
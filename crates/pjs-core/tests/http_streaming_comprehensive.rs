@@ -69,6 +69,21 @@ fn test_stream_format_from_accept_header_default() {
     assert!(matches!(format, StreamFormat::Json));
 }
 
+#[test]
+fn test_stream_format_from_accept_header_pjs_json() {
+    for accept in [
+        "application/pjs+json",
+        "application/pjs+json; version=1.0",
+        "application/pjs+json, application/json;q=0.9",
+    ] {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(accept));
+
+        let format = StreamFormat::from_accept_header(&headers);
+        assert!(matches!(format, StreamFormat::PjsJson), "{accept}");
+    }
+}
+
 #[test]
 fn test_stream_format_from_accept_header_missing() {
     let headers = HeaderMap::new();
@@ -486,6 +501,28 @@ async fn test_batch_frame_stream_sse_format() {
     assert!(result_str.contains("data: "));
 }
 
+#[tokio::test]
+async fn test_batch_frame_stream_pjs_json_format() {
+    let frames = vec![
+        Frame::skeleton(StreamId::new(), 0, JsonData::Null),
+        create_test_frame(200, 1, r#"{"id": 1}"#),
+    ];
+
+    let batch = BatchFrameStream::new(futures::stream::iter(frames), StreamFormat::PjsJson, 10);
+    assert_eq!(batch.content_type(), "application/pjs+json");
+
+    let collected: Vec<_> = batch.into_stream().collect().await;
+    let text = std::str::from_utf8(collected[0].as_ref().unwrap()).unwrap();
+    let lines: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["@type"], "skeleton");
+    assert_eq!(lines[1]["@type"], "patch");
+    assert_eq!(lines[1]["@seq"], 1);
+}
+
 #[tokio::test]
 async fn test_batch_frame_stream_empty() {
     let frames: Vec<Frame> = vec![];
@@ -612,6 +649,19 @@ async fn test_create_streaming_response_binary() {
     );
 }
 
+#[tokio::test]
+async fn test_create_streaming_response_pjs_json_advertises_version() {
+    let stream = futures::stream::iter(vec![Ok::<Vec<u8>, StreamTransportError>(b"{}\n".to_vec())]);
+
+    let response = create_streaming_response(stream, StreamFormat::PjsJson).unwrap();
+
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/pjs+json"
+    );
+    assert_eq!(response.headers().get("PJS-Version").unwrap(), "1.0");
+}
+
 // ============================================================================
 // Integration Tests
 // ============================================================================
@@ -0,0 +1,318 @@
+// Conformance tests for the native `application/pjs+json` wire format
+//
+// Every frame below is an example from docs/architecture/SPECIFICATION.md.
+// Each is decoded into a domain `Frame`, checked, and re-encoded; the
+// re-encoded frame must reproduce every member of the example (it may add
+// members such as `@timestamp`).
+
+use pjson_rs::{
+    domain::entities::frame::{ArrayChunkMetadata, FrameType},
+    domain::value_objects::{ContentDigest, JsonData, JsonPath, Priority, StreamId},
+    stream::pjs_json::{PjsJsonError, decode_frame, encode_frame},
+};
+use serde_json::{Value, json};
+
+fn decode(example: &Value) -> pjson_rs::domain::entities::Frame {
+    decode_frame(StreamId::new(), example)
+        .expect("spec example must decode")
+        .expect("spec example is not a heartbeat")
+}
+
+/// Assert that every member of `expected` appears in `actual` with the same
+/// value, recursing into objects.
+fn assert_contains(actual: &Value, expected: &Value, at: &str) {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => {
+            for (key, value) in expected {
+                let member = actual
+                    .get(key)
+                    .unwrap_or_else(|| panic!("missing {at}/{key}"));
+                assert_contains(member, value, &format!("{at}/{key}"));
+            }
+        }
+        _ => assert_eq!(actual, expected, "at {at}"),
+    }
+}
+
+fn patches(frame: &pjson_rs::domain::entities::Frame) -> Vec<JsonData> {
+    frame
+        .payload()
+        .get("patches")
+        .and_then(JsonData::as_array)
+        .cloned()
+        .unwrap()
+}
+
+/// §3.2 Skeleton Frame
+#[test]
+fn skeleton_frame() {
+    let example = json!({
+        "@type": "skeleton",
+        "@seq": 0,
+        "@priority": 255,
+        "@schema_version": "1.0",
+        "data": {
+            "user": {
+                "id": null,
+                "name": "",
+                "profile": {"bio": "", "stats": {"followers": 0, "posts": 0}},
+                "posts": []
+            }
+        }
+    });
+
+    let frame = decode(&example);
+    assert_eq!(frame.frame_type(), &FrameType::Skeleton);
+    assert_eq!(frame.sequence(), 0);
+    assert!(frame.is_critical());
+    assert_eq!(frame.payload(), &JsonData::from(example["data"].clone()));
+
+    // Skeletons are always critical; the priority is written on this
+    // implementation's scale rather than echoed.
+    let mut expected = example.clone();
+    expected["@priority"] = Priority::CRITICAL.value().into();
+    assert_contains(&encode_frame(&frame).unwrap(), &expected, "");
+}
+
+/// §3.3 Patch Frame
+#[test]
+fn patch_frame() {
+    let example = json!({
+        "@type": "patch",
+        "@seq": 1,
+        "@priority": 100,
+        "@patches": [
+            {"op": "replace", "path": "/user/id", "value": 12345},
+            {"op": "replace", "path": "/user/name", "value": "Alice Johnson"}
+        ]
+    });
+
+    let frame = decode(&example);
+    assert_eq!(frame.frame_type(), &FrameType::Patch);
+    assert_eq!(frame.priority().value(), 100);
+    let patches = patches(&frame);
+    assert_eq!(patches[0].get("path"), Some(&JsonData::from("$.user.id")));
+    assert_eq!(patches[1].get("operation"), Some(&JsonData::from("set")));
+
+    // Both patches share `/user`, which the encoder factors out (§5.3).
+    let encoded = encode_frame(&frame).unwrap();
+    assert_eq!(encoded["@base_path"], "/user");
+    assert_eq!(
+        encoded["@patches"],
+        json!([
+            {"op": "replace", "path": "/id", "value": 12345},
+            {"op": "replace", "path": "/name", "value": "Alice Johnson"}
+        ])
+    );
+    let roundtrip = decode(&encoded);
+    assert_eq!(patches, self::patches(&roundtrip));
+}
+
+/// §3.3 supported operations that map onto frame patches
+#[test]
+fn add_and_remove_operations() {
+    let frame = decode(&json!({
+        "@type": "patch",
+        "@seq": 4,
+        "@patches": [
+            {"op": "add", "path": "/user/nickname", "value": "al"},
+            {"op": "add", "path": "/user/posts/-", "value": [{"id": 1}]},
+            {"op": "remove", "path": "/user/legacy"}
+        ]
+    }));
+
+    assert_eq!(frame.priority(), Priority::MEDIUM);
+    let operations: Vec<_> = patches(&frame)
+        .iter()
+        .map(|patch| patch.get("operation").cloned().unwrap())
+        .collect();
+    assert_eq!(
+        operations,
+        vec![
+            JsonData::from("set"),
+            JsonData::from("append"),
+            JsonData::from("delete")
+        ]
+    );
+
+    for op in ["move", "copy", "test"] {
+        let result = decode_frame(
+            StreamId::new(),
+            &json!({
+                "@type": "patch",
+                "@seq": 5,
+                "@patches": [{"op": op, "from": "/a", "path": "/b", "value": 1}]
+            }),
+        );
+        assert!(
+            matches!(result, Err(PjsJsonError::UnsupportedOperation(_))),
+            "{op}"
+        );
+    }
+}
+
+/// §3.4 Array Streaming
+#[test]
+fn array_chunk_frame() {
+    let example = json!({
+        "@type": "patch",
+        "@seq": 2,
+        "@priority": 50,
+        "@array_metadata": {
+            "path": "/user/posts",
+            "total_items": 1000,
+            "chunk_index": 0,
+            "chunk_size": 10
+        },
+        "@patches": [
+            {
+                "op": "add",
+                "path": "/user/posts/-",
+                "value": [
+                    {"id": 1, "title": "Post 1"},
+                    {"id": 2, "title": "Post 2"}
+                ]
+            }
+        ]
+    });
+
+    let frame = decode(&example);
+    let patches = patches(&frame);
+    assert_eq!(patches.len(), 1);
+    assert_eq!(
+        patches[0].get("path"),
+        Some(&JsonData::from("$.user.posts"))
+    );
+    assert_eq!(
+        patches[0]
+            .get("array_metadata")
+            .and_then(ArrayChunkMetadata::from_json),
+        Some(ArrayChunkMetadata {
+            total_items: 1000,
+            chunk_index: 0,
+            chunk_size: 10
+        })
+    );
+
+    assert_contains(&encode_frame(&frame).unwrap(), &example, "");
+}
+
+/// §3.5 Complete Frame
+///
+/// The specification's example abbreviates the digest (`sha256:abcd1234...`);
+/// a full-length digest is used here. `@stats` is informational and ignored.
+#[test]
+fn complete_frame() {
+    let digest = ContentDigest::compute(&json!({"user": {"id": 12345}}), None);
+    let example = json!({
+        "@type": "complete",
+        "@seq": 99,
+        "@stats": {"total_frames": 100, "total_bytes": 45678, "duration_ms": 234},
+        "@checksum": digest.to_string()
+    });
+
+    let frame = decode(&example);
+    assert_eq!(frame.frame_type(), &FrameType::Complete);
+    assert_eq!(
+        frame.payload().get("checksum"),
+        Some(&JsonData::from(digest.to_string()))
+    );
+
+    let encoded = encode_frame(&frame).unwrap();
+    assert_eq!(encoded["@type"], "complete");
+    assert_eq!(encoded["@seq"], 99);
+    assert_eq!(encoded["@checksum"], example["@checksum"]);
+
+    let malformed = json!({"@type": "complete", "@seq": 99, "@checksum": "sha256:abcd1234..."});
+    assert!(matches!(
+        decode_frame(StreamId::new(), &malformed),
+        Err(PjsJsonError::InvalidField {
+            field: "@checksum",
+            ..
+        })
+    ));
+}
+
+/// §3.6 Error Frame
+#[test]
+fn error_frame() {
+    let example = json!({
+        "@type": "error",
+        "@seq": 5,
+        "@error": {
+            "code": "PATCH_FAILED",
+            "message": "Invalid path: /user/invalid",
+            "recoverable": false
+        }
+    });
+
+    let frame = decode(&example);
+    assert_eq!(frame.frame_type(), &FrameType::Error);
+    assert_eq!(
+        frame.payload().get("code"),
+        Some(&JsonData::from("PATCH_FAILED"))
+    );
+
+    assert_contains(&encode_frame(&frame).unwrap(), &example, "");
+}
+
+/// §5.3 Relative Paths
+///
+/// The specification's example omits `@seq`, which §3.1 requires.
+#[test]
+fn relative_paths() {
+    let example = json!({
+        "@type": "patch",
+        "@seq": 3,
+        "@base_path": "/user/profile",
+        "@patches": [
+            {"op": "replace", "path": "/bio", "value": "..."},
+            {"op": "replace", "path": "/avatar", "value": "..."}
+        ]
+    });
+
+    let frame = decode(&example);
+    let paths: Vec<_> = patches(&frame)
+        .iter()
+        .map(|patch| patch.get("path").cloned().unwrap())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            JsonData::from("$.user.profile.bio"),
+            JsonData::from("$.user.profile.avatar")
+        ]
+    );
+
+    assert_contains(&encode_frame(&frame).unwrap(), &example, "");
+
+    let mut missing_seq = example.clone();
+    missing_seq.as_object_mut().unwrap().remove("@seq");
+    assert!(matches!(
+        decode_frame(StreamId::new(), &missing_seq),
+        Err(PjsJsonError::MissingField("@seq"))
+    ));
+}
+
+/// §3.1 `heartbeat` frames keep the connection alive and carry no data.
+#[test]
+fn heartbeat_frame() {
+    let frame = decode_frame(StreamId::new(), &json!({"@type": "heartbeat", "@seq": 7}));
+    assert!(frame.unwrap().is_none());
+}
+
+/// §5.1 JSON Pointer addressing round-trips through patch paths.
+#[test]
+fn json_pointer_addressing() {
+    for (pointer, path) in [
+        ("/user/profile/bio", "$.user.profile.bio"),
+        ("/posts/0/title", "$.posts[0].title"),
+        ("/stats/total_users", "$.stats.total_users"),
+    ] {
+        assert_eq!(
+            JsonPath::from_json_pointer(pointer).unwrap(),
+            JsonPath::new(path).unwrap()
+        );
+        assert_eq!(JsonPath::new(path).unwrap().to_json_pointer(), pointer);
+    }
+}
@@ -0,0 +1,214 @@
+// Integration tests for the Redis-backed shared rate-limit store.
+//
+// Runs `RedisBackend` against an in-process stand-in speaking just enough of
+// RESP2 for the commands the backend issues (`GET`, `EVALSHA`, `SCRIPT LOAD`
+// and the client handshake), so the tests need no Redis server. The stand-in
+// performs the compare-and-swap natively; what is verified here is the wire
+// contract — script loading on `NOSCRIPT`, argument layout, value encoding —
+// and that several replicas connected to one server share a budget.
+
+#![cfg(feature = "rate-limit-redis")]
+
+use parking_lot::Mutex;
+use pjson_rs::security::{
+    RateLimitAlgorithm, RateLimitKey, RateLimitStore, SharedRateLimitStore,
+    rate_limit_store::RedisBackend,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+#[derive(Default)]
+struct FakeRedis {
+    values: HashMap<Vec<u8>, Vec<u8>>,
+    scripts: Vec<String>,
+    last_missing_sha: Vec<u8>,
+    unknown: Vec<String>,
+}
+
+async fn spawn_fake_redis() -> (String, Arc<Mutex<FakeRedis>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("redis://{}/", listener.local_addr().unwrap());
+    let state = Arc::new(Mutex::new(FakeRedis::default()));
+
+    let shared = state.clone();
+    tokio::spawn(async move {
+        loop {
+            let Ok((socket, _)) = listener.accept().await else {
+                break;
+            };
+            tokio::spawn(serve(socket, shared.clone()));
+        }
+    });
+
+    (url, state)
+}
+
+async fn serve(socket: TcpStream, state: Arc<Mutex<FakeRedis>>) {
+    let (read, mut write) = socket.into_split();
+    let mut read = BufReader::new(read);
+
+    while let Some(command) = read_command(&mut read).await {
+        let reply = execute(&command, &state);
+        if write.write_all(&reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn read_command(
+    read: &mut BufReader<tokio::net::tcp::OwnedReadHalf>,
+) -> Option<Vec<Vec<u8>>> {
+    let mut line = String::new();
+    read.read_line(&mut line).await.ok()?;
+    let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+        line.clear();
+        read.read_line(&mut line).await.ok()?;
+        let len: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+        let mut arg = vec![0; len + 2];
+        read.read_exact(&mut arg).await.ok()?;
+        arg.truncate(len);
+        args.push(arg);
+    }
+    Some(args)
+}
+
+fn bulk(value: &[u8]) -> Vec<u8> {
+    let mut out = format!("${}\r\n", value.len()).into_bytes();
+    out.extend_from_slice(value);
+    out.extend_from_slice(b"\r\n");
+    out
+}
+
+fn execute(command: &[Vec<u8>], state: &Mutex<FakeRedis>) -> Vec<u8> {
+    let mut state = state.lock();
+    let name = String::from_utf8_lossy(&command[0]).to_ascii_uppercase();
+
+    match name.as_str() {
+        "CLIENT" => b"+OK\r\n".to_vec(),
+        "PING" => b"+PONG\r\n".to_vec(),
+        "SCRIPT" => {
+            state
+                .scripts
+                .push(String::from_utf8_lossy(&command[2]).into_owned());
+            // The client checks the reply against its own SHA-1 of the
+            // script; echo back the digest it just failed to find.
+            let sha = state.last_missing_sha.clone();
+            bulk(&sha)
+        }
+        "GET" => match state.values.get(&command[1]) {
+            Some(value) => bulk(value),
+            None => b"$-1\r\n".to_vec(),
+        },
+        "EVALSHA" if state.scripts.is_empty() => {
+            state.last_missing_sha = command[1].clone();
+            b"-NOSCRIPT No matching script. Please use EVAL.\r\n".to_vec()
+        }
+        "EVALSHA" => {
+            // EVALSHA sha 1 key has_expected expected value ttl_ms
+            let key = &command[3];
+            let expected = (command[4] == b"1").then_some(&command[5]);
+            if state.values.get(key) != expected {
+                return b":0\r\n".to_vec();
+            }
+            assert!(
+                String::from_utf8_lossy(&command[7])
+                    .parse::<u64>()
+                    .is_ok_and(|ttl| ttl > 0),
+                "TTL must be a positive millisecond count"
+            );
+            state.values.insert(key.clone(), command[6].clone());
+            b":1\r\n".to_vec()
+        }
+        _ => {
+            state.unknown.push(name);
+            b"-ERR unknown command\r\n".to_vec()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_redis_replicas_share_one_budget() {
+    let (url, server) = spawn_fake_redis().await;
+
+    let replica_a = SharedRateLimitStore::new(Arc::new(RedisBackend::connect(&url).await.unwrap()));
+    let replica_b = SharedRateLimitStore::new(Arc::new(RedisBackend::connect(&url).await.unwrap()));
+    let algorithm = RateLimitAlgorithm::sliding_window(3, Duration::from_secs(60));
+    let key = RateLimitKey::api_key("partner-key");
+
+    assert!(replica_a.check(&key, &algorithm).await.unwrap().allowed);
+    assert!(replica_b.check(&key, &algorithm).await.unwrap().allowed);
+    assert!(replica_a.check(&key, &algorithm).await.unwrap().allowed);
+    let denied = replica_b.check(&key, &algorithm).await.unwrap();
+    assert!(!denied.allowed);
+    assert_eq!(denied.remaining, 0);
+
+    let server = server.lock();
+    assert_eq!(
+        server.scripts.len(),
+        1,
+        "script is loaded once, on NOSCRIPT"
+    );
+    assert!(server.scripts[0].contains("'PX'"));
+    assert!(
+        server.unknown.is_empty(),
+        "unexpected commands: {:?}",
+        server.unknown
+    );
+
+    // State is keyed under the default prefix with the digested API key, never the key itself.
+    let keys: Vec<String> = server
+        .values
+        .keys()
+        .map(|k| String::from_utf8_lossy(k).into_owned())
+        .collect();
+    assert_eq!(keys.len(), 1);
+    assert!(keys[0].starts_with("pjs:ratelimit:key:"));
+    assert!(!keys[0].contains("partner-key"));
+}
+
+#[tokio::test]
+async fn test_redis_backend_unreachable_is_backend_error() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("redis://{}/", listener.local_addr().unwrap());
+    drop(listener);
+
+    assert!(matches!(
+        RedisBackend::connect(&url).await,
+        Err(pjson_rs::security::RateLimitStoreError::Backend(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_redis_backend_connects_lazily_and_retries() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    // Construction never touches the network.
+    let store = SharedRateLimitStore::new(Arc::new(
+        RedisBackend::new(&format!("redis://{addr}/")).unwrap(),
+    ));
+    let algorithm = RateLimitAlgorithm::sliding_window(1, Duration::from_secs(60));
+    let key = RateLimitKey::ip([192, 0, 2, 1].into());
+    assert!(matches!(
+        store.check(&key, &algorithm).await,
+        Err(pjson_rs::security::RateLimitStoreError::Backend(_))
+    ));
+
+    // Once the server is up, the next check connects.
+    let listener = TcpListener::bind(addr).await.unwrap();
+    let state = Arc::new(Mutex::new(FakeRedis::default()));
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(serve(socket, state.clone()));
+        }
+    });
+    assert!(store.check(&key, &algorithm).await.unwrap().allowed);
+    assert!(!store.check(&key, &algorithm).await.unwrap().allowed);
+}
@@ -2,7 +2,7 @@
 //
 // This test file covers the infrastructure/websocket/server.rs module with focus on:
 // - AxumWebSocketTransport creation and initialization
-// - WebSocket message handling (StreamInit, FrameAck, Ping, Error)
+// - WebSocket message handling (StreamInit, FrameAck, Resume, CancelStream, Resend, Ping, Error)
 // - Session management and controller integration
 // - Connection lifecycle management
 // - WebSocket router creation
@@ -14,9 +14,10 @@
 
 use pjson_rs::{
     Error as PjsError,
+    domain::value_objects::ContentDigest,
     infrastructure::websocket::{
-        AdaptiveStreamController, AxumWebSocketTransport, StreamOptions, WebSocketTransport,
-        WsMessage,
+        AdaptiveStreamController, AxumWebSocketTransport, DeliveryMode, StreamOptions,
+        WebSocketTransport, WsMessage,
     },
 };
 use serde_json::json;
@@ -96,6 +97,8 @@ async fn test_websocket_transport_start_stream_with_custom_options() {
         client_fps: Some(30),
         compression: false,
         priority_mapping: None,
+        delivery: DeliveryMode::FireAndForget,
+        stream_priority: 0,
     };
 
     let result = transport.start_stream(connection, data, options).await;
@@ -145,6 +148,107 @@ async fn test_websocket_transport_handle_message_frame_ack() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_websocket_transport_handle_message_resend() {
+    let transport = AxumWebSocketTransport::new();
+    let controller = transport.controller();
+    let connection = Arc::new("test-connection-resend".to_string());
+    let resend = |session_id: &str| WsMessage::Resend {
+        session_id: session_id.to_string(),
+        from_frame_id: 0,
+    };
+
+    let options = StreamOptions {
+        delivery: DeliveryMode::AtLeastOnce,
+        ..StreamOptions::default()
+    };
+    let session_id = controller
+        .create_session(json!({"test": "data"}), options)
+        .await
+        .unwrap();
+    let result = transport
+        .handle_message(connection.clone(), resend(&session_id))
+        .await;
+    assert!(result.is_ok());
+
+    // Fire-and-forget sessions keep no frames to resend.
+    let session_id = controller
+        .create_session(json!({"test": "data"}), StreamOptions::default())
+        .await
+        .unwrap();
+    let result = transport
+        .handle_message(connection, resend(&session_id))
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_websocket_transport_handle_message_resume() {
+    let transport = AxumWebSocketTransport::new();
+    let resume = |session_id: &str| WsMessage::Resume {
+        session_id: session_id.to_string(),
+        last_frame_id: Some(0),
+    };
+
+    // StreamInit keeps the client's UUID, so the client can resume it from
+    // a new connection.
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let init = WsMessage::StreamInit {
+        session_id: session_id.clone(),
+        data: json!({"test": "data"}),
+        options: StreamOptions::default(),
+    };
+    transport
+        .handle_message(Arc::new("first-connection".to_string()), init)
+        .await
+        .unwrap();
+    let result = transport
+        .handle_message(
+            Arc::new("second-connection".to_string()),
+            resume(&session_id),
+        )
+        .await;
+    assert!(result.is_ok());
+
+    let result = transport
+        .handle_message(
+            Arc::new("second-connection".to_string()),
+            resume(&uuid::Uuid::new_v4().to_string()),
+        )
+        .await;
+    assert!(matches!(result, Err(PjsError::InvalidSession(_))));
+}
+
+#[tokio::test]
+async fn test_websocket_transport_handle_message_cancel_stream() {
+    let transport = AxumWebSocketTransport::new();
+    let owner = Arc::new("owner-connection".to_string());
+    let other = Arc::new("other-connection".to_string());
+    let cancel = |session_id: &str| WsMessage::CancelStream {
+        session_id: session_id.to_string(),
+    };
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let init = WsMessage::StreamInit {
+        session_id: session_id.clone(),
+        data: json!({"test": "data"}),
+        options: StreamOptions::default(),
+    };
+    transport.handle_message(owner.clone(), init).await.unwrap();
+
+    // Only the connection the stream is multiplexed on may cancel it.
+    let result = transport.handle_message(other, cancel(&session_id)).await;
+    assert!(matches!(result, Err(PjsError::InvalidSession(_))));
+
+    transport
+        .handle_message(owner.clone(), cancel(&session_id))
+        .await
+        .unwrap();
+    assert!(!transport.controller().remove_session(&session_id).await);
+    let result = transport.handle_message(owner, cancel(&session_id)).await;
+    assert!(result.is_err());
+}
+
 #[tokio::test]
 async fn test_websocket_transport_handle_message_ping() {
     let transport = AxumWebSocketTransport::new();
@@ -168,6 +272,7 @@ async fn test_websocket_transport_handle_message_error() {
         session_id: Some("test-session".to_string()),
         error: "Test error message".to_string(),
         code: 500,
+        resume_from: None,
     };
 
     let result = transport.handle_message(connection, message).await;
@@ -372,10 +477,13 @@ fn test_stream_options_custom() {
         client_fps: Some(60),
         compression: false,
         priority_mapping: Some(priority_mapping.clone()),
+        delivery: DeliveryMode::AtLeastOnce,
+        stream_priority: 7,
     };
 
     assert_eq!(options.max_frame_size, 1024 * 1024);
     assert_eq!(options.client_fps, Some(60));
+    assert_eq!(options.delivery, DeliveryMode::AtLeastOnce);
     assert!(!options.compression);
     assert_eq!(
         options.priority_mapping.as_ref().unwrap().get("critical"),
@@ -462,15 +570,28 @@ fn test_ws_message_frame_ack_serialization() {
 
 #[test]
 fn test_ws_message_stream_complete_serialization() {
+    let checksum = ContentDigest::compute(&json!({"id": 1}), None);
     let message = WsMessage::StreamComplete {
         session_id: "test-complete".to_string(),
-        checksum: "sha256:abcd1234".to_string(),
+        checksum,
     };
 
     let json = serde_json::to_value(&message).unwrap();
 
     assert_eq!(json["type"], "StreamComplete");
-    assert_eq!(json["data"]["checksum"], "sha256:abcd1234");
+    assert_eq!(json["data"]["checksum"], checksum.to_string());
+    assert!(
+        json["data"]["checksum"]
+            .as_str()
+            .unwrap()
+            .starts_with("sha256:")
+    );
+
+    let decoded: WsMessage = serde_json::from_value(json).unwrap();
+    assert!(matches!(
+        decoded,
+        WsMessage::StreamComplete { checksum: c, .. } if c == checksum
+    ));
 }
 
 #[test]
@@ -479,6 +600,7 @@ fn test_ws_message_error_serialization() {
         session_id: Some("error-session".to_string()),
         error: "Connection lost".to_string(),
         code: 1006,
+        resume_from: None,
     };
 
     let json = serde_json::to_value(&message).unwrap();
@@ -486,6 +608,10 @@ fn test_ws_message_error_serialization() {
     assert_eq!(json["type"], "Error");
     assert_eq!(json["data"]["error"], "Connection lost");
     assert_eq!(json["data"]["code"], 1006);
+    assert!(
+        json["data"].get("resume_from").is_none(),
+        "resume_from is omitted unless set, keeping older clients' payloads unchanged"
+    );
 }
 
 #[test]
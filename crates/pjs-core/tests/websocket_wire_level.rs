@@ -17,7 +17,7 @@ use tokio::time::timeout;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 use pjson_rs::infrastructure::websocket::{
-    AxumWebSocketTransport, WsMessage, server::create_websocket_router,
+    AxumWebSocketTransport, PjsWebSocketClient, WsMessage, server::create_websocket_router,
 };
 use pjson_rs::security::RateLimitConfig;
 
@@ -175,6 +175,80 @@ async fn test_wire_stream_init_yields_frame() {
     assert!(received_stream_frame, "never received a StreamFrame");
 }
 
+/// Verify that several streams share one connection: the client reconstructs
+/// each of its streams, and another connection receives none of their
+/// frames.
+#[tokio::test]
+async fn test_wire_streams_are_multiplexed_on_one_connection() {
+    // The client acknowledges every frame of every stream at once, which
+    // would exhaust the default message burst.
+    let config = RateLimitConfig {
+        burst_allowance: 100,
+        ..Default::default()
+    };
+    let (addr, _transport) =
+        spawn_ws_test_server_with(AxumWebSocketTransport::with_rate_limit_config(config)).await;
+
+    let (mut bystander, _) = connect_async(ws_url(addr))
+        .await
+        .expect("WebSocket handshake failed");
+
+    let client = Arc::new(PjsWebSocketClient::new(ws_url(addr)).expect("valid url"));
+    let documents = [
+        json!({"user": {"id": 1, "name": "Ann"}, "posts": [1, 2, 3]}),
+        json!({"orders": [{"id": 7}], "total": 12.5}),
+        json!({"settings": {"theme": "dark"}}),
+    ];
+    let mut session_ids = Vec::new();
+    for document in &documents {
+        session_ids.push(
+            client
+                .request_stream(document.clone(), None)
+                .await
+                .expect("request stream"),
+        );
+    }
+    let connection = tokio::spawn({
+        let client = client.clone();
+        async move { client.connect().await }
+    });
+
+    timeout(Duration::from_secs(5), async {
+        for session_id in &session_ids {
+            // Complete once the StreamComplete digest has been checked.
+            while client.integrity(session_id).await.is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for the streams to complete");
+
+    for (session_id, document) in session_ids.iter().zip(&documents) {
+        assert_eq!(
+            client.get_current_data(session_id).await.unwrap().as_ref(),
+            Some(document)
+        );
+        assert_eq!(client.integrity(session_id).await, Some(Ok(())));
+    }
+
+    let stray = timeout(Duration::from_millis(200), async {
+        while let Some(Ok(message)) = bystander.next().await {
+            if let Message::Text(text) = message {
+                return Some(text);
+            }
+        }
+        None
+    })
+    .await;
+    assert!(
+        matches!(stray, Err(_) | Ok(None)),
+        "another connection received {stray:?}"
+    );
+
+    connection.abort();
+}
+
 /// Verify that after a client-initiated close the server cleans up the
 /// connection record.
 #[tokio::test]
@@ -315,6 +389,38 @@ async fn test_wire_inbound_messages_rate_limited() {
     }
 }
 
+/// Verify that `with_rate_limit_store` charges upgrades against the shared
+/// store: two transports (replicas) over one backend share a single budget.
+#[tokio::test]
+async fn test_wire_upgrades_charged_against_shared_store() {
+    use pjson_rs::security::{
+        InProcessBackend, RateLimitAlgorithm, RateLimitPolicy, SharedRateLimitStore,
+    };
+    use tokio_tungstenite::tungstenite::Error as WsError;
+
+    let backend = Arc::new(InProcessBackend::new());
+    let policy = RateLimitPolicy::new(RateLimitAlgorithm::sliding_window(
+        2,
+        Duration::from_secs(60),
+    ));
+    let replica = || {
+        AxumWebSocketTransport::new().with_rate_limit_store(
+            Arc::new(SharedRateLimitStore::new(backend.clone())),
+            policy.clone(),
+        )
+    };
+    let (addr_a, _) = spawn_ws_test_server_with(replica()).await;
+    let (addr_b, _) = spawn_ws_test_server_with(replica()).await;
+
+    let _a = connect_async(ws_url(addr_a)).await.expect("first upgrade");
+    let _b = connect_async(ws_url(addr_b)).await.expect("second upgrade");
+
+    match connect_async(ws_url(addr_a)).await {
+        Err(WsError::Http(response)) => assert_eq!(response.status(), 429),
+        other => panic!("expected HTTP 429, got {other:?}"),
+    }
+}
+
 /// Verify that `upgrade_handler` configures axum/tungstenite's
 /// transport-level `max_message_size`/`max_frame_size` from the
 /// transport's `RateLimitConfig`, rather than relying solely on the
@@ -0,0 +1,475 @@
+//! `#[derive(PjsPriority)]`: field priorities declared on the response type
+//!
+//! Priorities for `Stream` frame generation and `PriorityStreamer` are
+//! usually configured as maps from field names or JSONPath queries to a
+//! priority, kept away from the type that produces the JSON. This derive
+//! puts them on the fields themselves and implements `PjsPriority`, whose
+//! map is keyed by each field's serde name:
+//!
+//! ```ignore
+//! use pjson_rs_domain::PjsPriority;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize, PjsPriority)]
+//! #[serde(rename_all = "camelCase")]
+//! struct Article {
+//!     #[pjs(priority = "critical")]
+//!     article_id: u64, // $.articleId
+//!     #[pjs(priority = 90)]
+//!     headline: String,
+//!     #[pjs(nested)]
+//!     author: Author, // Author's own priorities, below $.author
+//!     #[pjs(priority = "background")]
+//!     comments: Vec<String>,
+//! }
+//! ```
+//!
+//! Field attributes:
+//!
+//! - `#[pjs(priority = ...)]`: `"critical"`, `"high"`, `"medium"`, `"low"`,
+//!   `"background"` (case-insensitive) or a level in `1..=255`.
+//! - `#[pjs(nested)]`: the field's type implements `PjsPriority` too; its
+//!   priorities are added below the field's path. Combined with
+//!   `#[serde(flatten)]` they are added at the struct's own level.
+//!
+//! Container attribute `#[pjs(crate = "pjson_rs")]` names the crate the
+//! generated impl refers to, for users depending on `pjson-rs` rather than
+//! `pjson-rs-domain`.
+//!
+//! serde's `rename`, `rename_all`, `skip`, `skip_serializing` and `flatten`
+//! are honoured, so the declared paths follow the serialized form.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{ToTokens, quote};
+use syn::{
+    Data, DeriveInput, Expr, ExprLit, Fields, Lit, LitInt, LitStr, Meta, Path, Token,
+    ext::IdentExt, parse_macro_input, punctuated::Punctuated, spanned::Spanned,
+};
+
+/// Derive `PjsPriority` from `#[pjs(...)]` field attributes.
+///
+/// See the [crate documentation](crate) for the supported attributes.
+#[proc_macro_derive(PjsPriority, attributes(pjs))]
+pub fn derive_pjs_priority(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Priority declared with `#[pjs(priority = ...)]`
+enum PriorityLevel {
+    /// One of the named `Priority` constants
+    Named(&'static str),
+    /// Numeric level, already checked to be non-zero
+    Level(LitInt),
+}
+
+impl PriorityLevel {
+    fn to_tokens(&self, krate: &Path) -> TokenStream2 {
+        match self {
+            Self::Named(name) => {
+                let name = syn::Ident::new(name, proc_macro2::Span::call_site());
+                quote!(#krate::Priority::#name)
+            }
+            Self::Level(level) => quote! {
+                #krate::Priority::new(#level)
+                    .expect("priority level validated by #[derive(PjsPriority)]")
+            },
+        }
+    }
+}
+
+/// `#[pjs(...)]` and the relevant `#[serde(...)]` settings of one field
+#[derive(Default)]
+struct FieldAttrs {
+    priority: Option<PriorityLevel>,
+    nested: bool,
+    rename: Option<String>,
+    skipped: bool,
+    flatten: bool,
+}
+
+/// serde's `rename_all` rules, applied to field names as serde does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            other => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!("unknown serde rename rule `{other}`"),
+                ));
+            }
+        })
+    }
+
+    fn apply(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => {
+                let mut pascal = String::with_capacity(field.len());
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+                pascal
+            }
+            Self::Camel => {
+                let pascal = Self::Pascal.apply(field);
+                let mut chars = pascal.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake.apply(field).replace('_', "-"),
+        }
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "PjsPriority can only be derived for structs with named fields",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            data.fields.span(),
+            "PjsPriority can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut krate: Path = syn::parse_quote!(::pjson_rs_domain);
+    let mut rename_all = None;
+    for attr in &input.attrs {
+        if attr.path().is_ident("pjs") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    krate = meta.value()?.parse::<LitStr>()?.parse()?;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown pjs container attribute, expected `crate`"))
+                }
+            })?;
+        } else if attr.path().is_ident("serde") {
+            for meta in serde_metas(attr)? {
+                if meta.path().is_ident("rename_all")
+                    && let Some(rule) = serialize_name(&meta)?
+                {
+                    rename_all = Some(RenameRule::from_lit(&rule)?);
+                }
+            }
+        }
+    }
+
+    let mut own = Vec::new();
+    let mut nested = Vec::new();
+    let mut nested_types = Vec::new();
+    for field in &fields.named {
+        let attrs = field_attrs(field)?;
+        if attrs.priority.is_none() && !attrs.nested {
+            continue;
+        }
+        if attrs.skipped {
+            return Err(syn::Error::new(
+                field.span(),
+                "a field serde skips has no path to prioritize",
+            ));
+        }
+
+        let ident = field.ident.as_ref().expect("named field");
+        let key = match (&attrs.rename, rename_all) {
+            (Some(rename), _) => rename.clone(),
+            (None, Some(rule)) => rule.apply(&ident.unraw().to_string()),
+            (None, None) => ident.unraw().to_string(),
+        };
+        if attrs.flatten {
+            if attrs.priority.is_some() {
+                return Err(syn::Error::new(
+                    field.span(),
+                    "a flattened field has no path of its own; use `#[pjs(nested)]` alone",
+                ));
+            }
+        } else if key.is_empty() || key.contains(['.', '[', ']']) {
+            return Err(syn::Error::new(
+                field.span(),
+                format!("field name `{key}` cannot be addressed by a JsonPath"),
+            ));
+        }
+
+        if let Some(priority) = &attrs.priority {
+            let priority = priority.to_tokens(&krate);
+            own.push(quote! {
+                map.insert(
+                    #krate::JsonPath::root()
+                        .append_key(#key)
+                        .expect("field name validated by #[derive(PjsPriority)]"),
+                    #priority,
+                );
+            });
+        }
+        if attrs.nested {
+            let ty = &field.ty;
+            let base = if attrs.flatten {
+                quote!(#krate::JsonPath::root())
+            } else {
+                quote! {
+                    #krate::JsonPath::root()
+                        .append_key(#key)
+                        .expect("field name validated by #[derive(PjsPriority)]")
+                }
+            };
+            nested.push(quote! {
+                let base = #base;
+                for (path, priority) in <#ty as #krate::PjsPriority>::priority_map() {
+                    map.insert(base.join(&path), priority);
+                }
+            });
+            nested_types.push(ty.clone());
+        }
+    }
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        for ty in nested_types {
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#ty: #krate::PjsPriority));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let binding = if own.is_empty() && nested.is_empty() {
+        quote!(let map)
+    } else {
+        quote!(let mut map)
+    };
+
+    // Nested maps go first so a field's own annotation wins over a nested
+    // type's entry for the same path (possible with `flatten`).
+    Ok(quote! {
+        impl #impl_generics #krate::PjsPriority for #name #ty_generics #where_clause {
+            fn priority_map() -> ::std::collections::HashMap<#krate::JsonPath, #krate::Priority> {
+                #binding = ::std::collections::HashMap::new();
+                #(#nested)*
+                #(#own)*
+                map
+            }
+        }
+    })
+}
+
+fn field_attrs(field: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in &field.attrs {
+        if attr.path().is_ident("pjs") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("priority") {
+                    attrs.priority = Some(parse_priority(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("nested") {
+                    attrs.nested = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown pjs field attribute, expected `priority` or `nested`"))
+                }
+            })?;
+        } else if attr.path().is_ident("serde") {
+            for meta in serde_metas(attr)? {
+                let path = meta.path();
+                if path.is_ident("rename") {
+                    if let Some(name) = serialize_name(&meta)? {
+                        attrs.rename = Some(name.value());
+                    }
+                } else if path.is_ident("skip") || path.is_ident("skip_serializing") {
+                    attrs.skipped = true;
+                } else if path.is_ident("flatten") {
+                    attrs.flatten = true;
+                }
+            }
+        }
+    }
+    Ok(attrs)
+}
+
+fn parse_priority(expr: &Expr) -> syn::Result<PriorityLevel> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(name),
+            ..
+        }) => Ok(PriorityLevel::Named(
+            match name.value().to_ascii_lowercase().as_str() {
+                "critical" => "CRITICAL",
+                "high" => "HIGH",
+                "medium" => "MEDIUM",
+                "low" => "LOW",
+                "background" => "BACKGROUND",
+                _ => {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "expected `critical`, `high`, `medium`, `low` or `background`",
+                    ));
+                }
+            },
+        )),
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(level),
+            ..
+        }) => match level.base10_parse::<u8>() {
+            Ok(1..=255) => Ok(PriorityLevel::Level(LitInt::new(
+                level.base10_digits(),
+                level.span(),
+            ))),
+            _ => Err(syn::Error::new(
+                level.span(),
+                "priority level must be in 1..=255",
+            )),
+        },
+        other => Err(syn::Error::new(
+            other.span(),
+            "expected a priority name such as \"high\" or a level in 1..=255",
+        )),
+    }
+}
+
+/// Entries of a `#[serde(...)]` attribute. Every serde attribute is a
+/// well-formed [`Meta`], so unknown ones can be parsed and ignored.
+fn serde_metas(attr: &syn::Attribute) -> syn::Result<Punctuated<Meta, Token![,]>> {
+    attr.parse_args_with(Punctuated::parse_terminated)
+}
+
+/// Name given by `rename = "..."` / `rename_all = "..."`, or the `serialize`
+/// half of `rename(serialize = "...", deserialize = "...")`; `None` when
+/// only the deserialized name changes.
+fn serialize_name(meta: &Meta) -> syn::Result<Option<LitStr>> {
+    match meta {
+        Meta::NameValue(name_value) => lit_str(&name_value.value).map(Some),
+        Meta::List(list) => {
+            let entries = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+            for entry in entries {
+                if let Meta::NameValue(name_value) = entry
+                    && name_value.path.is_ident("serialize")
+                {
+                    return lit_str(&name_value.value).map(Some);
+                }
+            }
+            Ok(None)
+        }
+        Meta::Path(path) => Err(syn::Error::new(path.span(), "expected a name")),
+    }
+}
+
+fn lit_str(expr: &Expr) -> syn::Result<LitStr> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => Ok(lit.clone()),
+        other => Err(syn::Error::new(
+            other.span(),
+            format!("expected a string, found `{}`", other.to_token_stream()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_err(input: DeriveInput) -> String {
+        expand(&input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_rename_rules_match_serde() {
+        let cases = [
+            ("lowercase", "user_id"),
+            ("UPPERCASE", "USER_ID"),
+            ("PascalCase", "UserId"),
+            ("camelCase", "userId"),
+            ("snake_case", "user_id"),
+            ("SCREAMING_SNAKE_CASE", "USER_ID"),
+            ("kebab-case", "user-id"),
+            ("SCREAMING-KEBAB-CASE", "USER-ID"),
+        ];
+        for (rule, expected) in cases {
+            let rule = RenameRule::from_lit(&LitStr::new(rule, proc_macro2::Span::call_site()));
+            assert_eq!(rule.unwrap().apply("user_id"), expected);
+        }
+    }
+
+    #[test]
+    fn test_invalid_declarations_are_rejected() {
+        let error = expand_err(syn::parse_quote! {
+            enum Status { Active }
+        });
+        assert!(error.contains("structs with named fields"));
+
+        let error = expand_err(syn::parse_quote! {
+            struct Item { #[pjs(priority = "urgent")] id: u64 }
+        });
+        assert!(error.contains("expected `critical`"));
+
+        let error = expand_err(syn::parse_quote! {
+            struct Item { #[pjs(priority = 0)] id: u64 }
+        });
+        assert!(error.contains("1..=255"));
+
+        let error = expand_err(syn::parse_quote! {
+            struct Item { #[serde(rename = "item.id")] #[pjs(priority = "high")] id: u64 }
+        });
+        assert!(error.contains("cannot be addressed"));
+
+        let error = expand_err(syn::parse_quote! {
+            struct Item { #[serde(skip)] #[pjs(priority = "high")] id: u64 }
+        });
+        assert!(error.contains("serde skips"));
+    }
+
+    #[test]
+    fn test_unrelated_serde_attributes_are_ignored() {
+        let input: DeriveInput = syn::parse_quote! {
+            #[serde(deny_unknown_fields, bound(serialize = "T: Serialize"))]
+            struct Item<T> {
+                #[serde(default, skip_serializing_if = "Option::is_none", rename(deserialize = "x"))]
+                #[pjs(priority = 200)]
+                id: Option<T>,
+            }
+        };
+        let expanded = expand(&input).unwrap().to_string();
+        assert!(expanded.contains("append_key (\"id\")"));
+        assert!(expanded.contains("Priority :: new (200)"));
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     DomainError, DomainResult,
-    value_objects::{JsonData, JsonPath, Priority, StreamId},
+    value_objects::{ContentDigest, JsonData, JsonPath, Priority, StreamId},
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -149,6 +149,9 @@ impl Frame {
                     ),
                 );
                 patch_obj.insert("value".into(), patch.value);
+                if let Some(metadata) = patch.array_metadata {
+                    patch_obj.insert("array_metadata".into(), metadata.to_json());
+                }
                 JsonData::Object(patch_obj)
             })
             .collect();
@@ -168,10 +171,16 @@ impl Frame {
     }
 
     /// Create completion frame
-    pub fn complete(stream_id: StreamId, sequence: u64, checksum: Option<String>) -> Self {
+    ///
+    /// `checksum` is the [`ContentDigest`] of the document the stream's
+    /// frames reconstruct to, carried as `{"checksum": "<digest>"}`.
+    pub fn complete(stream_id: StreamId, sequence: u64, checksum: Option<ContentDigest>) -> Self {
         let payload = if let Some(checksum) = checksum {
             let mut obj = HashMap::new();
-            obj.insert("checksum".to_string(), JsonData::String(checksum));
+            obj.insert(
+                "checksum".to_string(),
+                JsonData::String(checksum.to_string()),
+            );
             JsonData::Object(obj)
         } else {
             JsonData::Object(HashMap::new())
@@ -253,6 +262,13 @@ impl Frame {
         self
     }
 
+    /// Replace the creation timestamp, e.g. with the one a decoded frame
+    /// carried on the wire
+    pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
     /// Get metadata
     pub fn metadata(&self) -> &HashMap<String, String> {
         &self.metadata
@@ -263,6 +279,30 @@ impl Frame {
         self.metadata.get(key)
     }
 
+    /// Keep only the patches whose target path satisfies `keep`, e.g. to
+    /// serve a subscriber interested in part of the document.
+    ///
+    /// Sequence, priority, timestamp and metadata are preserved, and frames
+    /// other than patch frames are returned unchanged. Returns `None` if no
+    /// patch survives, since a patch frame must carry at least one patch.
+    /// Patches whose path does not parse are dropped.
+    pub fn retain_patches(mut self, mut keep: impl FnMut(&JsonPath) -> bool) -> Option<Self> {
+        if self.frame_type != FrameType::Patch {
+            return Some(self);
+        }
+        let JsonData::Object(payload) = &mut self.payload else {
+            return None;
+        };
+        let Some(JsonData::Array(patches)) = payload.get_mut("patches") else {
+            return None;
+        };
+        patches.retain(|patch| match patch.get("path") {
+            Some(JsonData::String(path)) => JsonPath::new(path.as_str()).is_ok_and(|p| keep(&p)),
+            _ => false,
+        });
+        (!patches.is_empty()).then_some(self)
+    }
+
     /// Check if frame is critical priority
     pub fn is_critical(&self) -> bool {
         self.priority.is_critical()
@@ -345,6 +385,65 @@ pub struct FramePatch {
     pub operation: PatchOperation,
     /// Value to apply with the operation
     pub value: JsonData,
+    /// Position of this patch within a chunked array, for `Append` patches
+    /// that carry one slice of a large array
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub array_metadata: Option<ArrayChunkMetadata>,
+}
+
+/// Progress metadata for one chunk of a large array streamed as ordered
+/// `Append` patches (the `@array_metadata` of spec §3.4).
+///
+/// Chunks of one array are emitted in order, so once chunk `chunk_index`
+/// has been applied the client holds [`Self::items_received`] of
+/// `total_items` elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ArrayChunkMetadata {
+    /// Number of elements in the complete array
+    pub total_items: usize,
+    /// Zero-based index of this chunk
+    pub chunk_index: usize,
+    /// Elements per chunk (the final chunk may carry fewer)
+    pub chunk_size: usize,
+}
+
+impl ArrayChunkMetadata {
+    /// Elements received once this chunk and every earlier one are applied
+    pub fn items_received(&self) -> usize {
+        (self.chunk_index + 1)
+            .saturating_mul(self.chunk_size)
+            .min(self.total_items)
+    }
+
+    /// Check if this is the array's final chunk
+    pub fn is_last_chunk(&self) -> bool {
+        self.items_received() == self.total_items
+    }
+
+    /// Parse the metadata object carried in a patch frame payload.
+    ///
+    /// Returns `None` if `value` is not an object with non-negative integer
+    /// `total_items`, `chunk_index` and `chunk_size` fields.
+    pub fn from_json(value: &JsonData) -> Option<Self> {
+        let field = |name: &str| match value.get(name)? {
+            JsonData::Integer(n) => usize::try_from(*n).ok(),
+            _ => None,
+        };
+        Some(Self {
+            total_items: field("total_items")?,
+            chunk_index: field("chunk_index")?,
+            chunk_size: field("chunk_size")?,
+        })
+    }
+
+    fn to_json(self) -> JsonData {
+        let as_integer = |n: usize| JsonData::Integer(i64::try_from(n).unwrap_or(i64::MAX));
+        let mut obj = HashMap::with_capacity(3);
+        obj.insert("total_items".into(), as_integer(self.total_items));
+        obj.insert("chunk_index".into(), as_integer(self.chunk_index));
+        obj.insert("chunk_size".into(), as_integer(self.chunk_size));
+        JsonData::Object(obj)
+    }
 }
 
 /// Patch operation types
@@ -369,6 +468,7 @@ impl FramePatch {
             path,
             operation: PatchOperation::Set,
             value,
+            array_metadata: None,
         }
     }
 
@@ -378,6 +478,7 @@ impl FramePatch {
             path,
             operation: PatchOperation::Append,
             value,
+            array_metadata: None,
         }
     }
 
@@ -387,6 +488,7 @@ impl FramePatch {
             path,
             operation: PatchOperation::Merge,
             value,
+            array_metadata: None,
         }
     }
 
@@ -396,6 +498,19 @@ impl FramePatch {
             path,
             operation: PatchOperation::Delete,
             value: JsonData::Null,
+            array_metadata: None,
+        }
+    }
+
+    /// Create an append patch carrying one chunk of a large array
+    pub fn append_chunk(
+        path: JsonPath,
+        items: Vec<JsonData>,
+        metadata: ArrayChunkMetadata,
+    ) -> Self {
+        Self {
+            array_metadata: Some(metadata),
+            ..Self::append(path, JsonData::Array(items))
         }
     }
 }
@@ -439,7 +554,8 @@ mod tests {
     #[test]
     fn test_complete_frame_creation() {
         let stream_id = StreamId::new();
-        let frame = Frame::complete(stream_id, 10, Some("abc123".to_string()));
+        let digest = ContentDigest::compute(&serde_json::json!({"id": 1}), None);
+        let frame = Frame::complete(stream_id, 10, Some(digest));
 
         assert_eq!(frame.frame_type(), &FrameType::Complete);
         assert_eq!(frame.priority(), Priority::CRITICAL);
@@ -467,4 +583,62 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_array_chunk_metadata_in_patch_payload() {
+        let metadata = ArrayChunkMetadata {
+            total_items: 25,
+            chunk_index: 2,
+            chunk_size: 10,
+        };
+        let patch = FramePatch::append_chunk(
+            JsonPath::new("$.rows").unwrap(),
+            vec![JsonData::Integer(20)],
+            metadata,
+        );
+        let frame = Frame::patch(StreamId::new(), 3, Priority::LOW, vec![patch]).unwrap();
+
+        let JsonData::Array(patches) = frame.payload().get("patches").unwrap() else {
+            panic!("patches must be an array");
+        };
+        let carried = patches[0].get("array_metadata").unwrap();
+        assert_eq!(ArrayChunkMetadata::from_json(carried), Some(metadata));
+        assert_eq!(metadata.items_received(), 25);
+        assert!(metadata.is_last_chunk());
+
+        let plain = FramePatch::set(JsonPath::root(), JsonData::Null);
+        let frame = Frame::patch(StreamId::new(), 4, Priority::LOW, vec![plain]).unwrap();
+        let JsonData::Array(patches) = frame.payload().get("patches").unwrap() else {
+            panic!("patches must be an array");
+        };
+        assert!(patches[0].get("array_metadata").is_none());
+    }
+
+    #[test]
+    fn test_retain_patches() {
+        let stream_id = StreamId::new();
+        let patches = vec![
+            FramePatch::set(JsonPath::new("$.a").unwrap(), JsonData::Integer(1)),
+            FramePatch::set(JsonPath::new("$.b").unwrap(), JsonData::Integer(2)),
+        ];
+        let frame = Frame::patch(stream_id, 7, Priority::HIGH, patches)
+            .unwrap()
+            .with_metadata("source".to_string(), "api".to_string());
+
+        let kept = frame
+            .clone()
+            .retain_patches(|path| path.to_string() == "$.b")
+            .unwrap();
+        assert_eq!(kept.sequence(), 7);
+        assert_eq!(kept.metadata_value("source"), Some(&"api".to_string()));
+        let JsonData::Array(remaining) = kept.payload().get("patches").unwrap() else {
+            panic!("patches must be an array");
+        };
+        assert_eq!(remaining.len(), 1);
+
+        assert!(frame.retain_patches(|_| false).is_none());
+
+        let skeleton = Frame::skeleton(stream_id, 0, JsonData::Null);
+        assert!(skeleton.retain_patches(|_| false).is_some());
+    }
 }
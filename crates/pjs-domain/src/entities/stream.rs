@@ -3,7 +3,10 @@
 use crate::{
     DomainError, DomainResult,
     entities::{Frame, frame::FramePatch},
-    value_objects::{JsonData, JsonPath, Priority, SessionId, StreamId},
+    services::PatchCursor,
+    value_objects::{
+        ContentDigest, JsonData, JsonPath, JsonPathQuery, Priority, SessionId, StreamId,
+    },
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -89,6 +92,9 @@ pub enum StreamState {
     Preparing,
     /// Stream is actively sending data
     Streaming,
+    /// Stream delivered its initial document and stays open, streaming a
+    /// diff for every new version pushed with [`Stream::update_source`]
+    Live,
     /// Stream completed successfully
     Completed,
     /// Stream failed with error
@@ -106,9 +112,109 @@ pub struct StreamConfig {
     pub max_frames_per_batch: usize,
     /// Compression settings
     pub enable_compression: bool,
-    /// Custom priority rules
+    /// Custom priority rules, keyed either by a field name (matching the
+    /// last key of a path) or by a JSONPath query such as
+    /// `$.user.posts[*].title`; query rules win over field-name rules
     #[serde(with = "serde_priority_map")]
     pub priority_rules: HashMap<String, Priority>,
+    /// Chunking for arrays without an entry in `array_chunking`; `None`
+    /// streams such arrays as a single patch
+    #[serde(default)]
+    pub default_array_chunking: Option<ArrayChunking>,
+    /// Per-array chunking, keyed by JSON path (e.g. `$.user.posts`)
+    #[serde(default)]
+    pub array_chunking: HashMap<String, ArrayChunking>,
+}
+
+impl StreamConfig {
+    /// Chunking that applies to the array at `path`, if any
+    pub fn array_chunking_for(&self, path: &JsonPath) -> Option<&ArrayChunking> {
+        if self.array_chunking.is_empty() {
+            return self.default_array_chunking.as_ref();
+        }
+        self.array_chunking
+            .get(&path.to_string())
+            .or(self.default_array_chunking.as_ref())
+    }
+
+    /// The [`FrameGenerator`](crate::services::FrameGenerator) streams with
+    /// this configuration generate frames with.
+    ///
+    /// `priority_rules` keyed by a JSONPath query (starting with `$`) become
+    /// [`PriorityHeuristicConfig::path_rules`]; every other key, including
+    /// a `$` key that does not parse as a query, is an exact last-key
+    /// override. Either way user-provided rules win over the shared
+    /// heuristic; see #242.
+    ///
+    /// [`PriorityHeuristicConfig::path_rules`]: crate::services::PriorityHeuristicConfig::path_rules
+    pub fn frame_generator(&self) -> crate::services::FrameGenerator {
+        let mut priorities = crate::services::PriorityHeuristicConfig::default();
+        for (key, priority) in &self.priority_rules {
+            match key
+                .starts_with('$')
+                .then(|| JsonPathQuery::new(key.as_str()).ok())
+                .flatten()
+            {
+                Some(query) => priorities.add_path_rule(query, *priority),
+                None => priorities.add_override(key.clone(), *priority),
+            }
+        }
+        let mut generator = crate::services::FrameGenerator::new(priorities);
+        if let Some(chunking) = self.default_array_chunking {
+            generator = generator.with_default_array_chunking(chunking);
+        }
+        for (path, chunking) in &self.array_chunking {
+            generator = generator.with_array_chunking(path.clone(), *chunking);
+        }
+        generator
+    }
+
+    /// Add a `priority_rules` entry for every priority `T` declares, keyed
+    /// by a query selecting exactly that path; see
+    /// [`PjsPriority`](crate::services::PjsPriority).
+    #[must_use]
+    pub fn with_priorities_of<T: crate::services::PjsPriority + ?Sized>(mut self) -> Self {
+        for (path, priority) in T::priority_map() {
+            self.priority_rules
+                .insert(JsonPathQuery::from(&path).to_string(), priority);
+        }
+        self
+    }
+}
+
+/// How a large array is split into ordered `Append` chunks (spec §3.4).
+///
+/// Arrays longer than `chunk_size` are streamed as consecutive chunks, each
+/// carrying [`ArrayChunkMetadata`] so clients can show "items so far /
+/// total". Chunks covering the first `high_priority_items` elements are sent
+/// at [`Priority::HIGH`] or above, so the top of a list renders before the
+/// long tail arrives.
+///
+/// [`ArrayChunkMetadata`]: crate::entities::frame::ArrayChunkMetadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArrayChunking {
+    /// Elements per chunk; values below 1 are treated as 1
+    pub chunk_size: usize,
+    /// Leading elements streamed at high priority
+    #[serde(default)]
+    pub high_priority_items: usize,
+}
+
+impl ArrayChunking {
+    /// Chunk arrays into `chunk_size` elements, with no high-priority head
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size,
+            high_priority_items: 0,
+        }
+    }
+
+    /// Stream the first `count` elements at high priority
+    #[must_use]
+    pub fn with_high_priority_items(mut self, count: usize) -> Self {
+        self.high_priority_items = count;
+        self
+    }
 }
 
 impl Default for StreamConfig {
@@ -118,6 +224,8 @@ impl Default for StreamConfig {
             max_frames_per_batch: 10,
             enable_compression: true,
             priority_rules: HashMap::new(),
+            default_array_chunking: None,
+            array_chunking: HashMap::new(),
         }
     }
 }
@@ -160,7 +268,13 @@ pub struct Stream {
     completed_at: Option<DateTime<Utc>>,
     next_sequence: u64,
     source_data: Option<JsonData>,
+    #[serde(default)]
+    version: u64,
     metadata: HashMap<String, String>,
+    /// Where [`Self::next_patch_frames`] resumes, for the source version it
+    /// was started on
+    #[serde(skip)]
+    patch_cursor: Option<(u64, PatchCursor)>,
 }
 
 impl Stream {
@@ -179,7 +293,9 @@ impl Stream {
             completed_at: None,
             next_sequence: 1,
             source_data: Some(source_data),
+            version: 0,
             metadata: HashMap::new(),
+            patch_cursor: None,
         }
     }
 
@@ -228,6 +344,12 @@ impl Stream {
         self.source_data.as_ref()
     }
 
+    /// Get the source document version: `0` for the document the stream was
+    /// created with, incremented by every [`Self::update_source`]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
     /// Get metadata
     pub fn metadata(&self) -> &HashMap<String, String> {
         &self.metadata
@@ -257,7 +379,7 @@ impl Stream {
     /// Complete stream successfully
     pub fn complete(&mut self) -> DomainResult<()> {
         match self.state {
-            StreamState::Streaming => {
+            StreamState::Streaming | StreamState::Live => {
                 self.state = StreamState::Completed;
                 self.completed_at = Some(Utc::now());
                 self.update_timestamp();
@@ -273,7 +395,7 @@ impl Stream {
     /// Fail stream with error
     pub fn fail(&mut self, error: String) -> DomainResult<()> {
         match self.state {
-            StreamState::Preparing | StreamState::Streaming => {
+            StreamState::Preparing | StreamState::Streaming | StreamState::Live => {
                 self.state = StreamState::Failed;
                 self.completed_at = Some(Utc::now());
                 self.add_metadata("error".to_string(), error);
@@ -289,7 +411,7 @@ impl Stream {
     /// Cancel stream
     pub fn cancel(&mut self) -> DomainResult<()> {
         match self.state {
-            StreamState::Preparing | StreamState::Streaming => {
+            StreamState::Preparing | StreamState::Streaming | StreamState::Live => {
                 self.state = StreamState::Cancelled;
                 self.completed_at = Some(Utc::now());
                 self.update_timestamp();
@@ -302,9 +424,55 @@ impl Stream {
         }
     }
 
+    /// Replace the stream's source document with a new version, returning
+    /// the prioritized patches that turn the previous version into it
+    /// (highest priority first, see [`crate::services::diff_documents`]).
+    ///
+    /// Moves a `Streaming` stream to [`StreamState::Live`], where it stays —
+    /// accepting further updates — until completed, failed or cancelled.
+    /// The returned patches are not filtered by any priority threshold: a
+    /// subscriber that already holds the previous version needs every one
+    /// of them to converge on the new one. Commit them with
+    /// [`Self::commit_patch_frames`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::InvalidStreamState`] unless the stream is
+    /// `Streaming` or `Live`.
+    pub fn update_source(
+        &mut self,
+        source_data: JsonData,
+    ) -> DomainResult<Vec<(FramePatch, Priority)>> {
+        if !self.can_emit_frames() {
+            return Err(DomainError::InvalidStreamState(
+                "Stream must be streaming or live to accept source updates".to_string(),
+            ));
+        }
+
+        let patches = match &self.source_data {
+            Some(previous) => crate::services::diff_documents(
+                previous,
+                &source_data,
+                self.config.frame_generator().priorities(),
+            ),
+            None => vec![(
+                FramePatch::set(JsonPath::root(), source_data.clone()),
+                Priority::CRITICAL,
+            )],
+        };
+
+        self.source_data = Some(source_data);
+        self.version += 1;
+        self.patch_cursor = None;
+        self.state = StreamState::Live;
+        self.update_timestamp();
+
+        Ok(patches)
+    }
+
     /// Generate skeleton frame for the stream
     pub fn create_skeleton_frame(&mut self) -> DomainResult<Frame> {
-        if !matches!(self.state, StreamState::Streaming) {
+        if !self.can_emit_frames() {
             return Err(DomainError::InvalidStreamState(
                 "Stream must be in streaming state to create frames".to_string(),
             ));
@@ -314,7 +482,7 @@ impl Stream {
             DomainError::InvalidStreamState("No source data available for skeleton".to_string())
         })?;
 
-        let skeleton = self.generate_skeleton(skeleton_data)?;
+        let skeleton = self.config.frame_generator().skeleton(skeleton_data);
         let frame = Frame::skeleton(self.id, self.next_sequence, skeleton);
 
         self.record_frame_created(&frame);
@@ -322,7 +490,8 @@ impl Stream {
         Ok(frame)
     }
 
-    /// Create batch of patch frames based on priority
+    /// Create batch of patch frames based on priority, from every patch of
+    /// the document; [`Self::next_patch_frames`] generates them on demand
     pub fn create_patch_frames(
         &mut self,
         priority_threshold: Priority,
@@ -332,6 +501,85 @@ impl Stream {
         self.commit_patch_frames(patches, max_frames)
     }
 
+    /// Create the next batch of patch frames, resuming where the previous
+    /// call stopped.
+    ///
+    /// Unlike [`Self::create_patch_frames`], which regenerates every patch
+    /// of the document on each call, this keeps a
+    /// [`PatchCursor`] over `source_data` and only walks as much of it as
+    /// the returned frames carry, so the first critical frames of a very
+    /// large document cost time and memory proportional to that batch; see
+    /// the [frame generation docs](crate::services::frame_generation#lazy-generation)
+    /// for the order this yields patches in. Each frame holds patches of one
+    /// priority, up to `max_frame_size` bytes of values (a single larger
+    /// value gets a frame of its own).
+    ///
+    /// Returns no frames once the document has been sent. The walk starts
+    /// over from the top when `priority_threshold` differs from the previous
+    /// call's, after [`Self::update_source`] and after
+    /// [`Self::update_config`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pjson_rs_domain::entities::Stream;
+    /// use pjson_rs_domain::value_objects::{JsonData, Priority, SessionId};
+    ///
+    /// let document: JsonData =
+    ///     serde_json::json!({"id": 1, "name": "Ada", "bio": "..."}).into();
+    /// let mut stream = Stream::new(SessionId::new(), document, Default::default());
+    /// stream.start_streaming().unwrap();
+    ///
+    /// let first = stream.next_patch_frames(Priority::MIN, 1).unwrap();
+    /// assert_eq!(first[0].priority(), Priority::CRITICAL);
+    ///
+    /// let rest = stream.next_patch_frames(Priority::MIN, 10).unwrap();
+    /// assert!(!rest.is_empty());
+    /// assert!(stream.next_patch_frames(Priority::MIN, 10).unwrap().is_empty());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::InvalidStreamState`] unless the stream is
+    /// `Streaming` or `Live`.
+    pub fn next_patch_frames(
+        &mut self,
+        priority_threshold: Priority,
+        max_frames: usize,
+    ) -> DomainResult<Vec<Frame>> {
+        if !self.can_emit_frames() {
+            return Err(DomainError::InvalidStreamState(
+                "Stream must be in streaming state to create frames".to_string(),
+            ));
+        }
+
+        let previous = self.patch_cursor.take();
+        let source_data = self.source_data.as_ref().ok_or_else(|| {
+            DomainError::InvalidStreamState("No source data available for patches".to_string())
+        })?;
+        let generator = self.config.frame_generator();
+        let mut cursor = match previous {
+            Some((version, cursor))
+                if version == self.version && cursor.threshold() == priority_threshold =>
+            {
+                cursor
+            }
+            _ => generator.cursor(source_data, priority_threshold),
+        };
+
+        let max_frame_size = self.config.max_frame_size;
+        let groups: Vec<_> =
+            std::iter::from_fn(|| cursor.next_group(&generator, source_data, max_frame_size))
+                .take(max_frames)
+                .collect();
+        self.patch_cursor = Some((self.version, cursor));
+
+        groups
+            .into_iter()
+            .map(|(priority, frame_patches)| self.finalize_patch_frame(priority, frame_patches))
+            .collect()
+    }
+
     /// Compute prioritized patches for this stream's current `source_data`,
     /// without mutating any state — the expensive half of
     /// [`Self::create_patch_frames`] (a full traversal of `source_data`,
@@ -343,13 +591,41 @@ impl Stream {
     /// run this traversal lock-free and only take the lock for the commit
     /// step — which is itself `O(patches.len())`, not cheap or
     /// `max_frames`-bounded; see [`Self::commit_patch_frames`]'s docs. Safe
-    /// to call without holding any lock: `source_data` is set once at
-    /// stream creation and never mutated afterward.
+    /// to call without holding any lock on a snapshot of the stream, as long
+    /// as the caller re-extracts if [`Self::version`] moved on before the
+    /// commit: [`Self::update_source`] replaces `source_data` on live
+    /// streams.
     pub fn extract_prioritized_patches(
         &self,
         priority_threshold: Priority,
     ) -> DomainResult<Vec<(FramePatch, Priority)>> {
-        if !matches!(self.state, StreamState::Streaming) {
+        self.extract_prioritized_patches_with(|generator, source_data| {
+            generator.patches(source_data, priority_threshold)
+        })
+    }
+
+    /// [`Self::extract_prioritized_patches`] with the traversal supplied by
+    /// the caller, which receives this stream's
+    /// [`FrameGenerator`](crate::services::FrameGenerator) and `source_data`.
+    ///
+    /// Lets a caller with a thread pool compute
+    /// [`FrameGenerator::patches`](crate::services::FrameGenerator::patches)
+    /// in parallel, through
+    /// [`FrameGenerator::subtrees`](crate::services::FrameGenerator::subtrees),
+    /// under the same preconditions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::InvalidStreamState`] unless the stream is
+    /// `Streaming` or `Live` and has source data.
+    pub fn extract_prioritized_patches_with<F>(
+        &self,
+        extract: F,
+    ) -> DomainResult<Vec<(FramePatch, Priority)>>
+    where
+        F: FnOnce(&crate::services::FrameGenerator, &JsonData) -> Vec<(FramePatch, Priority)>,
+    {
+        if !self.can_emit_frames() {
             return Err(DomainError::InvalidStreamState(
                 "Stream must be in streaming state to create frames".to_string(),
             ));
@@ -359,7 +635,7 @@ impl Stream {
             DomainError::InvalidStreamState("No source data available for patches".to_string())
         })?;
 
-        self.extract_patches(source_data, priority_threshold)
+        Ok(extract(&self.config.frame_generator(), source_data))
     }
 
     /// Turn already-extracted prioritized patches (from
@@ -373,7 +649,7 @@ impl Stream {
     /// the total number of patches extracted, not to `max_frames`.
     ///
     /// Re-checks the streaming-state precondition itself: if the stream
-    /// transitioned out of `Streaming` between the caller's earlier
+    /// stopped accepting frames (left `Streaming`/`Live`) between the caller's earlier
     /// [`Self::extract_prioritized_patches`] call and this one, this fails
     /// cleanly instead of committing frames for a stream that can no longer
     /// accept them.
@@ -388,7 +664,7 @@ impl Stream {
         patches: Vec<(FramePatch, Priority)>,
         max_frames: usize,
     ) -> DomainResult<Vec<Frame>> {
-        if !matches!(self.state, StreamState::Streaming) {
+        if !self.can_emit_frames() {
             return Err(DomainError::InvalidStreamState(
                 "Stream must be in streaming state to create frames".to_string(),
             ));
@@ -438,7 +714,7 @@ impl Stream {
         priority: Priority,
         frame_patches: Vec<FramePatch>,
     ) -> DomainResult<Frame> {
-        if !matches!(self.state, StreamState::Streaming) {
+        if !self.can_emit_frames() {
             return Err(DomainError::InvalidStreamState(
                 "Stream must be in streaming state to create frames".to_string(),
             ));
@@ -449,9 +725,12 @@ impl Stream {
         Ok(frame)
     }
 
-    /// Create completion frame
-    pub fn create_completion_frame(&mut self, checksum: Option<String>) -> DomainResult<Frame> {
-        if !matches!(self.state, StreamState::Streaming) {
+    /// Create completion frame carrying the reconstructed document's digest
+    pub fn create_completion_frame(
+        &mut self,
+        checksum: Option<ContentDigest>,
+    ) -> DomainResult<Frame> {
+        if !self.can_emit_frames() {
             return Err(DomainError::InvalidStreamState(
                 "Stream must be in streaming state to create frames".to_string(),
             ));
@@ -465,7 +744,15 @@ impl Stream {
 
     /// Check if stream is active
     pub fn is_active(&self) -> bool {
-        matches!(self.state, StreamState::Preparing | StreamState::Streaming)
+        matches!(
+            self.state,
+            StreamState::Preparing | StreamState::Streaming | StreamState::Live
+        )
+    }
+
+    /// Check if the stream's state allows emitting frames
+    pub fn can_emit_frames(&self) -> bool {
+        matches!(self.state, StreamState::Streaming | StreamState::Live)
     }
 
     /// Check if stream is finished
@@ -494,7 +781,8 @@ impl Stream {
                     (self.stats.total_frames as f64 / 100.0).min(0.9)
                 }
             }
-            StreamState::Completed => 1.0,
+            // Every version so far has been delivered in full
+            StreamState::Live | StreamState::Completed => 1.0,
             StreamState::Failed | StreamState::Cancelled => {
                 // Partial progress before failure/cancellation
                 (self.stats.total_frames as f64 / 100.0).min(0.99)
@@ -511,6 +799,7 @@ impl Stream {
         }
 
         self.config = config;
+        self.patch_cursor = None;
         self.update_timestamp();
         Ok(())
     }
@@ -558,99 +847,6 @@ impl Stream {
         self.update_timestamp();
     }
 
-    /// Private helper: Generate skeleton from source data
-    fn generate_skeleton(&self, data: &JsonData) -> DomainResult<JsonData> {
-        // Simplified skeleton generation - create empty structure
-        match data {
-            JsonData::Object(obj) => {
-                let mut skeleton = HashMap::new();
-                for (key, value) in obj.iter() {
-                    skeleton.insert(
-                        key.clone(),
-                        match value {
-                            JsonData::Array(_) => JsonData::Array(Vec::new()),
-                            JsonData::Object(_) => self.generate_skeleton(value)?,
-                            JsonData::Integer(_) => JsonData::Integer(0),
-                            JsonData::Float(_) => JsonData::Float(0.0),
-                            JsonData::String(_) => JsonData::Null,
-                            JsonData::Bool(_) => JsonData::Bool(false),
-                            JsonData::Null => JsonData::Null,
-                        },
-                    );
-                }
-                Ok(JsonData::Object(skeleton))
-            }
-            JsonData::Array(_) => Ok(JsonData::Array(Vec::new())),
-            _ => Ok(JsonData::Null),
-        }
-    }
-
-    /// Private helper: Extract patches with priority filtering.
-    ///
-    /// Walks `data` recursively, emitting one `Set` patch per leaf-level
-    /// value (primitives and arrays — objects are traversed without emitting
-    /// a patch, since their structure is already conveyed by the skeleton
-    /// frame). Each patch is paired with a computed priority so that
-    /// [`Self::chunk_patches_for_commit`] can group chunks by maximum
-    /// priority. Patches whose priority falls below `threshold` are dropped.
-    fn extract_patches(
-        &self,
-        data: &JsonData,
-        threshold: Priority,
-    ) -> DomainResult<Vec<(crate::entities::frame::FramePatch, Priority)>> {
-        let mut patches = Vec::new();
-        self.collect_patches(data, &JsonPath::root(), threshold, &mut patches)?;
-        // Sort by priority descending so high-priority patches land in earlier
-        // frames within the chunk-based batch layout.
-        patches.sort_by_key(|p| core::cmp::Reverse(p.1));
-        Ok(patches)
-    }
-
-    /// Recursive walker that emits prioritized patches into `out`.
-    fn collect_patches(
-        &self,
-        data: &JsonData,
-        path: &JsonPath,
-        threshold: Priority,
-        out: &mut Vec<(crate::entities::frame::FramePatch, Priority)>,
-    ) -> DomainResult<()> {
-        if let JsonData::Object(map) = data {
-            for (key, value) in map.iter() {
-                // Keys with characters JsonPath cannot encode (`.`, `[`, `]`)
-                // are skipped: a domain-internal walker must not refuse the
-                // entire document because of one weird key.
-                let Ok(child_path) = path.append_key(key) else {
-                    continue;
-                };
-                self.collect_patches(value, &child_path, threshold, out)?;
-            }
-            return Ok(());
-        }
-
-        let priority = self.compute_priority(path, data);
-        if priority >= threshold {
-            let patch = crate::entities::frame::FramePatch::set(path.clone(), data.clone());
-            out.push((patch, priority));
-        }
-        Ok(())
-    }
-
-    /// Compute a priority for a patch by delegating to
-    /// [`crate::services::compute_priority`].
-    ///
-    /// The per-stream `priority_rules` map is mapped onto
-    /// [`PriorityHeuristicConfig::overrides`] so that user-provided rules keep
-    /// winning over the shared heuristic. This is the single entry point used
-    /// by both the HTTP transport (via `extract_patches`) and the WebAssembly
-    /// bindings; see #242 for the divergence this resolves.
-    fn compute_priority(&self, path: &JsonPath, value: &JsonData) -> Priority {
-        let mut cfg = crate::services::PriorityHeuristicConfig::default();
-        if !self.config.priority_rules.is_empty() {
-            cfg.overrides = self.config.priority_rules.clone();
-        }
-        crate::services::compute_priority(&cfg, path, value)
-    }
-
     /// Group prioritized patches into per-frame chunks without constructing
     /// any [`Frame`] or mutating stream state.
     ///
@@ -849,7 +1045,7 @@ mod tests {
 
         assert!(
             !frames.is_empty(),
-            "extract_patches must produce at least one patch for non-empty source data"
+            "frame generation must produce at least one patch for non-empty source data"
         );
 
         let id_frame_priority_max = frames
@@ -908,4 +1104,146 @@ mod tests {
             "frame priority must reflect the highest-priority patch in the chunk"
         );
     }
+
+    #[test]
+    fn test_update_source_goes_live_and_returns_diff() {
+        let session_id = SessionId::new();
+        let source_data = serde_json::json!({"id": 1, "status": "pending", "notes": "a"});
+        let mut stream = Stream::new(session_id, source_data.into(), StreamConfig::default());
+
+        assert!(
+            stream
+                .update_source(serde_json::json!({"id": 1}).into())
+                .is_err(),
+            "a stream that has not started cannot accept updates"
+        );
+
+        stream.start_streaming().unwrap();
+        let patches = stream
+            .update_source(serde_json::json!({"id": 1, "status": "shipped", "notes": "a"}).into())
+            .unwrap();
+
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].0.path.to_string(), "$.status");
+        assert_eq!(stream.state(), &StreamState::Live);
+        assert_eq!(stream.version(), 1);
+        assert!(stream.is_active());
+        assert_eq!(
+            stream.source_data(),
+            Some(&serde_json::json!({"id": 1, "status": "shipped", "notes": "a"}).into())
+        );
+
+        // Live streams keep emitting frames and can be completed.
+        let frames = stream.commit_patch_frames(patches, 4).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert!(stream.create_completion_frame(None).is_ok());
+        stream.complete().unwrap();
+        assert!(stream.update_source(serde_json::json!({}).into()).is_err());
+    }
+
+    #[test]
+    fn test_large_arrays_stream_as_ordered_chunks() {
+        let rows: Vec<serde_json::Value> = (0..25).map(|i| serde_json::json!(i)).collect();
+        let mut config = StreamConfig::default();
+        config.array_chunking.insert(
+            "$.rows".to_string(),
+            ArrayChunking::new(10).with_high_priority_items(10),
+        );
+        let stream = Stream::new(
+            SessionId::new(),
+            serde_json::json!({"rows": rows, "tags": [1, 2, 3]}).into(),
+            config,
+        );
+
+        let patches = stream
+            .config()
+            .frame_generator()
+            .patches(stream.source_data().unwrap(), Priority::BACKGROUND);
+        let chunks: Vec<_> = patches
+            .iter()
+            .filter_map(|(patch, priority)| {
+                let metadata = patch.array_metadata?;
+                assert_eq!(
+                    patch.operation,
+                    crate::entities::frame::PatchOperation::Append
+                );
+                assert_eq!(patch.path.to_string(), "$.rows");
+                Some((metadata, *priority))
+            })
+            .collect();
+
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(m, _)| m.chunk_index)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert!(
+            chunks
+                .iter()
+                .all(|(m, _)| m.total_items == 25 && m.chunk_size == 10)
+        );
+        assert_eq!(chunks[2].0.items_received(), 25);
+        assert!(chunks[0].1 >= Priority::HIGH);
+        assert!(chunks[0].1 > chunks[1].1);
+
+        // Arrays without chunking configured are still a single `Set`.
+        assert!(patches.iter().any(|(patch, _)| {
+            patch.path.to_string() == "$.tags" && patch.array_metadata.is_none()
+        }));
+
+        // A threshold between head and tail priority drops only the tail.
+        let head_only = stream
+            .config()
+            .frame_generator()
+            .patches(stream.source_data().unwrap(), Priority::HIGH);
+        assert_eq!(
+            head_only
+                .iter()
+                .filter_map(|(patch, _)| patch.array_metadata)
+                .map(|m| m.chunk_index)
+                .collect::<Vec<_>>(),
+            vec![0]
+        );
+    }
+
+    #[test]
+    fn test_priority_rules_accept_jsonpath_queries() {
+        let mut config = StreamConfig::default();
+        config
+            .priority_rules
+            .insert("$.posts[?@.pinned].title".to_string(), Priority::CRITICAL);
+        config
+            .priority_rules
+            .insert("body".to_string(), Priority::BACKGROUND);
+        let stream = Stream::new(
+            SessionId::new(),
+            serde_json::json!({
+                "title": "page",
+                "posts": {
+                    "first": {"title": "a", "body": "x"},
+                    "second": {"title": "b", "body": "y", "pinned": true}
+                }
+            })
+            .into(),
+            config,
+        );
+
+        let patches = stream
+            .config()
+            .frame_generator()
+            .patches(stream.source_data().unwrap(), Priority::BACKGROUND);
+        let priority_of = |path: &str| {
+            patches
+                .iter()
+                .find(|(patch, _)| patch.path.to_string() == path)
+                .map(|(_, priority)| *priority)
+                .unwrap()
+        };
+        assert_eq!(priority_of("$.posts.second.title"), Priority::CRITICAL);
+        assert_ne!(priority_of("$.posts.first.title"), Priority::CRITICAL);
+        assert_eq!(priority_of("$.posts.first.body"), Priority::BACKGROUND);
+        assert_ne!(priority_of("$.title"), Priority::CRITICAL);
+    }
 }
@@ -199,6 +199,22 @@ pub enum DomainEvent {
         timestamp: DateTime<Utc>,
     },
 
+    /// Live stream received a new version of its source document
+    StreamUpdated {
+        /// ID of the session containing the stream
+        #[serde(with = "serde_session_id")]
+        session_id: SessionId,
+        /// ID of the updated stream
+        #[serde(with = "serde_stream_id")]
+        stream_id: StreamId,
+        /// Source document version after the update
+        version: u64,
+        /// Number of patches in the diff against the previous version
+        patch_count: usize,
+        /// When the update was applied
+        timestamp: DateTime<Utc>,
+    },
+
     /// Stream failed with error
     StreamFailed {
         /// ID of the session containing the stream
@@ -479,6 +495,7 @@ impl DomainEvent {
             Self::StreamCreated { session_id, .. } => *session_id,
             Self::StreamStarted { session_id, .. } => *session_id,
             Self::StreamCompleted { session_id, .. } => *session_id,
+            Self::StreamUpdated { session_id, .. } => *session_id,
             Self::StreamFailed { session_id, .. } => *session_id,
             Self::StreamCancelled { session_id, .. } => *session_id,
             Self::SkeletonGenerated { session_id, .. } => *session_id,
@@ -502,6 +519,7 @@ impl DomainEvent {
             Self::StreamCreated { stream_id, .. } => Some(*stream_id),
             Self::StreamStarted { stream_id, .. } => Some(*stream_id),
             Self::StreamCompleted { stream_id, .. } => Some(*stream_id),
+            Self::StreamUpdated { stream_id, .. } => Some(*stream_id),
             Self::StreamFailed { stream_id, .. } => Some(*stream_id),
             Self::StreamCancelled { stream_id, .. } => Some(*stream_id),
             Self::SkeletonGenerated { stream_id, .. } => Some(*stream_id),
@@ -531,6 +549,7 @@ impl DomainEvent {
             Self::StreamCreated { timestamp, .. } => *timestamp,
             Self::StreamStarted { timestamp, .. } => *timestamp,
             Self::StreamCompleted { timestamp, .. } => *timestamp,
+            Self::StreamUpdated { timestamp, .. } => *timestamp,
             Self::StreamFailed { timestamp, .. } => *timestamp,
             Self::StreamCancelled { timestamp, .. } => *timestamp,
             Self::SkeletonGenerated { timestamp, .. } => *timestamp,
@@ -557,6 +576,7 @@ impl DomainEvent {
             Self::StreamCreated { .. } => "stream_created",
             Self::StreamStarted { .. } => "stream_started",
             Self::StreamCompleted { .. } => "stream_completed",
+            Self::StreamUpdated { .. } => "stream_updated",
             Self::StreamFailed { .. } => "stream_failed",
             Self::StreamCancelled { .. } => "stream_cancelled",
             Self::SkeletonGenerated { .. } => "skeleton_generated",
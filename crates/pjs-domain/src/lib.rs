@@ -18,6 +18,7 @@
 //! - `std` (default): Standard library support
 //! - `serde`: Serialization support for WASM interop
 //! - `wasm`: Enables WASM-specific optimizations
+//! - `derive`: `#[derive(PjsPriority)]` for declaring field priorities
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
@@ -29,6 +30,8 @@ extern crate alloc;
 use alloc::{format, string::String, vec::Vec};
 
 pub mod entities;
+// `events` declares `EventId` and `EventSubscriber` after its test module.
+#[allow(clippy::items_after_test_module)]
 pub mod events;
 pub mod services;
 pub mod value_objects;
@@ -36,9 +39,16 @@ pub mod value_objects;
 // Re-export core types
 pub use entities::{Frame, Stream};
 pub use events::{DomainEvent, SessionState};
-pub use services::{PriorityHeuristicConfig, compute_priority};
+/// Derive macro for [`PjsPriority`]; see the trait for the attributes.
+#[cfg(feature = "derive")]
+pub use pjson_rs_derive::PjsPriority;
+pub use services::{
+    FrameGenerator, PjsPriority, PriorityHeuristicConfig, compute_priority, compute_priority_in,
+    diff_documents,
+};
 pub use value_objects::{
-    JsonData, JsonPath, MAX_DESERIALIZE_DEPTH, PathSegment, Priority, Schema, SessionId, StreamId,
+    JsonData, JsonPath, JsonPathQuery, MAX_DESERIALIZE_DEPTH, PathSegment, Priority, Schema,
+    SessionId, StreamId,
 };
 
 /// Domain Result type
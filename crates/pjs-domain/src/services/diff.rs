@@ -0,0 +1,260 @@
+//! Structural diff between two versions of a document.
+//!
+//! [`diff_documents`] turns "the document changed" into the smallest set of
+//! [`FramePatch`]es this walker can express, each paired with the priority
+//! [`compute_priority_in`] assigns to the changed value, so a live stream can
+//! push critical changes before cosmetic ones:
+//!
+//! - object keys that disappeared become `Delete` patches;
+//! - new keys, and values whose type changed, become `Set` patches;
+//! - an array that only grew at the end becomes one `Append` patch carrying
+//!   the new elements; an array of unchanged length is diffed element by
+//!   element; any other array change replaces the array with `Set`;
+//! - objects with keys a [`JsonPath`] cannot address (`.`, `[`, `]`, empty)
+//!   are replaced wholesale rather than silently skipped, so applying the
+//!   patches to `previous` always yields `next`.
+
+use crate::entities::frame::FramePatch;
+use crate::services::priority::{PriorityHeuristicConfig, compute_priority_in};
+use crate::value_objects::{JsonData, JsonPath, Priority};
+use std::collections::HashMap;
+
+/// Compute the patches that turn `previous` into `next`, sorted by priority
+/// (highest first). Returns an empty vector when the documents are equal.
+///
+/// Patches with equal priority keep document order (object keys sorted), so
+/// the output is deterministic. Path rules in `config` are matched against
+/// `next`, or against `previous` for deleted values.
+///
+/// # Examples
+///
+/// ```
+/// use pjson_rs_domain::services::{PriorityHeuristicConfig, diff_documents};
+/// use pjson_rs_domain::value_objects::JsonData;
+///
+/// let previous: JsonData = serde_json::json!({"id": 1, "status": "pending"}).into();
+/// let next: JsonData = serde_json::json!({"id": 1, "status": "shipped"}).into();
+///
+/// let patches = diff_documents(&previous, &next, &PriorityHeuristicConfig::default());
+/// assert_eq!(patches.len(), 1);
+/// assert_eq!(patches[0].0.path.to_string(), "$.status");
+/// ```
+pub fn diff_documents(
+    previous: &JsonData,
+    next: &JsonData,
+    config: &PriorityHeuristicConfig,
+) -> Vec<(FramePatch, Priority)> {
+    let mut patches = Vec::new();
+    let ctx = DiffContext {
+        previous,
+        next,
+        config,
+    };
+    diff_at(&ctx, previous, next, &JsonPath::root(), &mut patches);
+    patches.sort_by_key(|(_, priority)| core::cmp::Reverse(*priority));
+    patches
+}
+
+/// The two document roots and the heuristic, shared by the whole walk.
+struct DiffContext<'a> {
+    previous: &'a JsonData,
+    next: &'a JsonData,
+    config: &'a PriorityHeuristicConfig,
+}
+
+fn diff_at(
+    ctx: &DiffContext<'_>,
+    previous: &JsonData,
+    next: &JsonData,
+    path: &JsonPath,
+    out: &mut Vec<(FramePatch, Priority)>,
+) {
+    if previous == next {
+        return;
+    }
+
+    match (previous, next) {
+        (JsonData::Object(before), JsonData::Object(after)) => {
+            diff_objects(ctx, before, after, next, path, out);
+        }
+        (JsonData::Array(before), JsonData::Array(after))
+            if after.len() > before.len() && after[..before.len()] == before[..] =>
+        {
+            let appended = JsonData::Array(after[before.len()..].to_vec());
+            let priority = compute_priority_in(ctx.config, ctx.next, path, &appended);
+            out.push((FramePatch::append(path.clone(), appended), priority));
+        }
+        (JsonData::Array(before), JsonData::Array(after)) if after.len() == before.len() => {
+            for (index, (old, new)) in before.iter().zip(after).enumerate() {
+                diff_at(ctx, old, new, &path.append_index(index), out);
+            }
+        }
+        _ => set(ctx, path, next, out),
+    }
+}
+
+fn diff_objects(
+    ctx: &DiffContext<'_>,
+    before: &HashMap<String, JsonData>,
+    after: &HashMap<String, JsonData>,
+    next: &JsonData,
+    path: &JsonPath,
+    out: &mut Vec<(FramePatch, Priority)>,
+) {
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort_unstable();
+    keys.dedup();
+
+    let Ok(children) = keys
+        .iter()
+        .map(|key| path.append_key(key).map(|child| (*key, child)))
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return set(ctx, path, next, out);
+    };
+
+    for (key, child) in children {
+        match (before.get(key), after.get(key)) {
+            (Some(old), Some(new)) => diff_at(ctx, old, new, &child, out),
+            (None, Some(new)) => set(ctx, &child, new, out),
+            (Some(old), None) => {
+                let priority = compute_priority_in(ctx.config, ctx.previous, &child, old);
+                out.push((FramePatch::delete(child), priority));
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn set(
+    ctx: &DiffContext<'_>,
+    path: &JsonPath,
+    value: &JsonData,
+    out: &mut Vec<(FramePatch, Priority)>,
+) {
+    let priority = compute_priority_in(ctx.config, ctx.next, path, value);
+    out.push((FramePatch::set(path.clone(), value.clone()), priority));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::frame::PatchOperation;
+    use serde_json::json;
+
+    fn diff(previous: serde_json::Value, next: serde_json::Value) -> Vec<(String, PatchOperation)> {
+        diff_documents(
+            &previous.into(),
+            &next.into(),
+            &PriorityHeuristicConfig::default(),
+        )
+        .into_iter()
+        .map(|(patch, _)| (patch.path.to_string(), patch.operation))
+        .collect()
+    }
+
+    #[test]
+    fn test_equal_documents_produce_no_patches() {
+        let doc = json!({"id": 1, "items": [1, 2], "nested": {"a": true}});
+        assert!(diff(doc.clone(), doc).is_empty());
+    }
+
+    #[test]
+    fn test_changed_added_and_removed_keys() {
+        let mut patches = diff(
+            json!({"id": 1, "name": "a", "old": true}),
+            json!({"id": 1, "name": "b", "new": 3}),
+        );
+        patches.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            patches,
+            vec![
+                ("$.name".to_string(), PatchOperation::Set),
+                ("$.new".to_string(), PatchOperation::Set),
+                ("$.old".to_string(), PatchOperation::Delete),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_change_targets_leaf() {
+        assert_eq!(
+            diff(
+                json!({"user": {"profile": {"city": "Oslo", "zip": "0150"}}}),
+                json!({"user": {"profile": {"city": "Bergen", "zip": "0150"}}}),
+            ),
+            vec![("$.user.profile.city".to_string(), PatchOperation::Set)]
+        );
+    }
+
+    #[test]
+    fn test_array_growth_is_an_append() {
+        let patches = diff_documents(
+            &json!({"events": [1, 2]}).into(),
+            &json!({"events": [1, 2, 3, 4]}).into(),
+            &PriorityHeuristicConfig::default(),
+        );
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].0.operation, PatchOperation::Append);
+        assert_eq!(patches[0].0.value, json!([3, 4]).into());
+    }
+
+    #[test]
+    fn test_same_length_array_diffs_elements_and_other_changes_replace() {
+        assert_eq!(
+            diff(json!({"xs": [1, 2, 3]}), json!({"xs": [1, 5, 3]})),
+            vec![("$.xs[1]".to_string(), PatchOperation::Set)]
+        );
+        assert_eq!(
+            diff(json!({"xs": [1, 2, 3]}), json!({"xs": [1, 2]})),
+            vec![("$.xs".to_string(), PatchOperation::Set)]
+        );
+        assert_eq!(
+            diff(json!({"v": {"a": 1}}), json!({"v": [1]})),
+            vec![("$.v".to_string(), PatchOperation::Set)]
+        );
+    }
+
+    #[test]
+    fn test_unaddressable_keys_replace_the_object() {
+        assert_eq!(
+            diff(json!({"m": {"a.b": 1}}), json!({"m": {"a.b": 2}})),
+            vec![("$.m".to_string(), PatchOperation::Set)]
+        );
+    }
+
+    #[test]
+    fn test_critical_changes_come_first() {
+        let patches = diff_documents(
+            &json!({"id": 1, "description": "x"}).into(),
+            &json!({"id": 2, "description": "y"}).into(),
+            &PriorityHeuristicConfig::default(),
+        );
+        assert_eq!(patches[0].0.path.to_string(), "$.id");
+        assert!(patches[0].1 > patches[1].1);
+    }
+
+    #[test]
+    fn test_path_rules_apply_to_changes() {
+        let mut config = PriorityHeuristicConfig::default();
+        config.add_path_rule(
+            crate::value_objects::JsonPathQuery::new("$.posts[*].title").unwrap(),
+            Priority::CRITICAL,
+        );
+        let patches = diff_documents(
+            &json!({"id": 1, "posts": [{"title": "a", "body": "x"}]}).into(),
+            &json!({"id": 2, "posts": [{"title": "b", "body": "y"}]}).into(),
+            &config,
+        );
+        let title = patches
+            .iter()
+            .find(|(patch, _)| patch.path.to_string() == "$.posts[0].title")
+            .unwrap();
+        assert_eq!(title.1, Priority::CRITICAL);
+        let body = patches
+            .iter()
+            .find(|(patch, _)| patch.path.to_string() == "$.posts[0].body")
+            .unwrap();
+        assert!(body.1 < Priority::CRITICAL);
+    }
+}
@@ -0,0 +1,932 @@
+//! The frame-generation engine shared by every transport.
+//!
+//! [`FrameGenerator`] turns a document into what a PJS stream sends: a
+//! skeleton with the document's shape, then prioritized patches filling it
+//! in. The domain [`Stream`](crate::Stream), the HTTP streamer, the
+//! WebSocket transport and the WebAssembly bindings all delegate here, so the
+//! same document and priority configuration yield the same patches whichever
+//! way they reach the client; only how patches are packed into frames
+//! differs between transports.
+//!
+//! # Algorithm
+//!
+//! - The skeleton keeps every object member, replacing strings and nulls
+//!   with `null`, numbers with `0`, booleans with `false` and arrays with
+//!   `[]`. A scalar root is sent whole in the skeleton.
+//! - Every value that is not an expanded object gets one `Set` patch, with
+//!   the priority [`compute_priority_in`] assigns it. Arrays with
+//!   [`ArrayChunking`] and more elements than its chunk size become ordered
+//!   `Append` chunks instead.
+//! - Objects deeper than the [maximum depth](FrameGenerator::with_max_depth),
+//!   and objects with a key a [`JsonPath`] cannot address (`.`, `[`, `]`,
+//!   empty), are not expanded: the skeleton holds `null` and one `Set`
+//!   carries the whole object, so no member is lost.
+//! - Patches are sorted by priority, highest first; patches of equal
+//!   priority keep document order, object keys sorted, so the output is
+//!   deterministic.
+//!
+//! # Lazy generation
+//!
+//! [`FrameGenerator::patches`] builds every patch, cloning the whole
+//! document, before returning the first one. For very large documents
+//! [`FrameGenerator::cursor`] builds them on demand instead: a
+//! [`PatchCursor`] keeps a priority queue of the values not sent yet and
+//! opens an object only once its highest-priority member comes up, so the
+//! first patches clone only what they cover. An object is ranked when its
+//! parent is opened, looking at no more than [`RANK_LOOKAHEAD`] values
+//! below it, so the work and memory behind a batch do not grow with the
+//! rest of the document. The cursor yields exactly the patches of
+//! [`FrameGenerator::patches`], and in the same order as long as every
+//! object it ranks fits in the lookahead; a larger object is queued at the
+//! highest priority seen within the lookahead or, if higher, the priority
+//! it would have as a whole value, so members beyond the lookahead may come
+//! later than [`FrameGenerator::patches`] puts them.
+
+use crate::entities::frame::{ArrayChunkMetadata, FramePatch};
+use crate::entities::stream::ArrayChunking;
+use crate::services::priority::{PriorityHeuristicConfig, compute_priority_in};
+use crate::value_objects::{JsonData, JsonPath, PathSegment, Priority};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+
+/// How many values below an object a [`PatchCursor`] looks at to rank it;
+/// see the [module docs](self#lazy-generation).
+pub const RANK_LOOKAHEAD: usize = 64;
+
+/// Skeleton and prioritized patches for a document; see the
+/// [module docs](self).
+///
+/// # Examples
+///
+/// ```
+/// use pjson_rs_domain::services::FrameGenerator;
+/// use pjson_rs_domain::value_objects::{JsonData, Priority};
+///
+/// let document: JsonData = serde_json::json!({"id": 7, "name": "Ada"}).into();
+/// let generator = FrameGenerator::default();
+///
+/// let skeleton: JsonData = serde_json::json!({"id": 0, "name": null}).into();
+/// assert_eq!(generator.skeleton(&document), skeleton);
+///
+/// let patches = generator.patches(&document, Priority::MIN);
+/// let paths: Vec<String> = patches.iter().map(|(patch, _)| patch.path.to_string()).collect();
+/// assert_eq!(paths, ["$.id", "$.name"]);
+/// assert_eq!(patches[0].1, Priority::CRITICAL);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FrameGenerator {
+    priorities: PriorityHeuristicConfig,
+    default_array_chunking: Option<ArrayChunking>,
+    array_chunking: HashMap<String, ArrayChunking>,
+    max_depth: Option<usize>,
+}
+
+impl FrameGenerator {
+    /// Generate with `priorities`, streaming every array as a single patch
+    /// and expanding objects at any depth.
+    pub fn new(priorities: PriorityHeuristicConfig) -> Self {
+        Self {
+            priorities,
+            ..Self::default()
+        }
+    }
+
+    /// Chunk arrays without a [path-specific](Self::with_array_chunking)
+    /// setting with `chunking`.
+    #[must_use]
+    pub fn with_default_array_chunking(mut self, chunking: ArrayChunking) -> Self {
+        self.default_array_chunking = Some(chunking);
+        self
+    }
+
+    /// Chunk the array at `path` (e.g. `$.user.posts`) with `chunking`.
+    #[must_use]
+    pub fn with_array_chunking(mut self, path: impl Into<String>, chunking: ArrayChunking) -> Self {
+        self.array_chunking.insert(path.into(), chunking);
+        self
+    }
+
+    /// Expand objects at most `max_depth` levels below the root; deeper
+    /// objects are sent whole.
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// The priority configuration patches are prioritized with.
+    pub fn priorities(&self) -> &PriorityHeuristicConfig {
+        &self.priorities
+    }
+
+    /// Chunking that applies to the array at `path`, if any.
+    pub fn array_chunking_for(&self, path: &JsonPath) -> Option<&ArrayChunking> {
+        if self.array_chunking.is_empty() {
+            return self.default_array_chunking.as_ref();
+        }
+        self.array_chunking
+            .get(&path.to_string())
+            .or(self.default_array_chunking.as_ref())
+    }
+
+    /// The skeleton of `data`: its shape, with placeholder values.
+    pub fn skeleton(&self, data: &JsonData) -> JsonData {
+        match data {
+            JsonData::Object(_) | JsonData::Array(_) => self.placeholder(data, 0),
+            scalar => scalar.clone(),
+        }
+    }
+
+    /// The patches filling [`Self::skeleton`] in to `data`, highest priority
+    /// first. Patches below `threshold` are dropped; chunks of an array only
+    /// ever lose a suffix, so the ones sent still apply in order.
+    pub fn patches(&self, data: &JsonData, threshold: Priority) -> Vec<(FramePatch, Priority)> {
+        Self::merge_patches(
+            self.subtrees(data)
+                .into_iter()
+                .map(|(path, value)| self.subtree_patches(data, &path, value, threshold)),
+        )
+    }
+
+    /// The independent parts [`Self::patches`] is computed from: the members
+    /// of the root object, in sorted key order, or the root itself when it
+    /// is not expanded. Empty for a scalar root.
+    ///
+    /// Callers with a thread pool can run [`Self::subtree_patches`] on every
+    /// part concurrently and pass the results, in this order, to
+    /// [`Self::merge_patches`] to get exactly what [`Self::patches`]
+    /// returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pjson_rs_domain::services::FrameGenerator;
+    /// use pjson_rs_domain::value_objects::{JsonData, Priority};
+    ///
+    /// let document: JsonData = serde_json::json!({"b": [1, 2], "a": {"id": 1}}).into();
+    /// let generator = FrameGenerator::default();
+    ///
+    /// let parts: Vec<_> = generator
+    ///     .subtrees(&document)
+    ///     .into_iter()
+    ///     .map(|(path, value)| generator.subtree_patches(&document, &path, value, Priority::MIN))
+    ///     .collect();
+    /// assert_eq!(
+    ///     FrameGenerator::merge_patches(parts),
+    ///     generator.patches(&document, Priority::MIN)
+    /// );
+    /// ```
+    pub fn subtrees<'a>(&self, data: &'a JsonData) -> Vec<(JsonPath, &'a JsonData)> {
+        match data {
+            JsonData::Object(map) if self.expands(map, 0) => {
+                let mut members: Vec<_> = map
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        Some((JsonPath::root().append_key(key).ok()?, value))
+                    })
+                    .collect();
+                members.sort_unstable_by(|(a, _), (b, _)| a.last_key().cmp(&b.last_key()));
+                members
+            }
+            JsonData::Object(_) | JsonData::Array(_) => vec![(JsonPath::root(), data)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// The patches for `value`, found at `path` in the document `root`, in
+    /// document order; see [`Self::subtrees`].
+    pub fn subtree_patches(
+        &self,
+        root: &JsonData,
+        path: &JsonPath,
+        value: &JsonData,
+        threshold: Priority,
+    ) -> Vec<(FramePatch, Priority)> {
+        let mut patches = Vec::new();
+        self.collect(root, value, path, path.depth(), threshold, &mut patches);
+        patches
+    }
+
+    /// Combine [`Self::subtree_patches`] results, given in
+    /// [`Self::subtrees`] order, into [`Self::patches`] order: highest
+    /// priority first, document order among equals.
+    pub fn merge_patches(
+        parts: impl IntoIterator<Item = Vec<(FramePatch, Priority)>>,
+    ) -> Vec<(FramePatch, Priority)> {
+        let mut patches: Vec<_> = parts.into_iter().flatten().collect();
+        patches.sort_by_key(|(_, priority)| Reverse(*priority));
+        patches
+    }
+
+    /// A [`PatchCursor`] yielding the patches of [`Self::patches`] on demand,
+    /// without visiting the whole document up front; see the
+    /// [module docs](self#lazy-generation).
+    ///
+    /// The cursor does not borrow `data`: pass the same document, and this
+    /// generator, to every [`PatchCursor::next_patch`] call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pjson_rs_domain::services::FrameGenerator;
+    /// use pjson_rs_domain::value_objects::{JsonData, Priority};
+    ///
+    /// let document: JsonData = serde_json::json!({"id": 7, "bio": "..."}).into();
+    /// let generator = FrameGenerator::default();
+    ///
+    /// let mut cursor = generator.cursor(&document, Priority::MIN);
+    /// let (first, priority) = cursor.next_patch(&generator, &document).unwrap();
+    /// assert_eq!(first.path.to_string(), "$.id");
+    /// assert_eq!(priority, Priority::CRITICAL);
+    /// ```
+    pub fn cursor(&self, data: &JsonData, threshold: Priority) -> PatchCursor {
+        let mut cursor = PatchCursor {
+            threshold,
+            frontier: BinaryHeap::new(),
+            held: None,
+            visited: 0,
+        };
+        if let JsonData::Object(map) = data
+            && self.expands(map, 0)
+        {
+            // Alone in the queue, the root is opened first whatever its
+            // rank, so it is not ranked.
+            cursor.frontier.push(Pending {
+                priority: Priority::MIN,
+                order: Vec::new(),
+                path: JsonPath::root(),
+                step: Step::Open,
+            });
+        } else if matches!(data, JsonData::Object(_) | JsonData::Array(_)) {
+            cursor.push(self, data, data, JsonPath::root(), Vec::new());
+        }
+        cursor
+    }
+
+    /// Pack prioritized patches, as returned by [`Self::patches`], into
+    /// groups sharing one priority, at most `max_patches` each, keeping
+    /// their order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pjson_rs_domain::entities::frame::FramePatch;
+    /// use pjson_rs_domain::services::FrameGenerator;
+    /// use pjson_rs_domain::value_objects::{JsonData, JsonPath, Priority};
+    ///
+    /// let patch = |key: &str| FramePatch::set(JsonPath::new(key).unwrap(), JsonData::Null);
+    /// let groups = FrameGenerator::group_by_priority(
+    ///     vec![
+    ///         (patch("$.a"), Priority::HIGH),
+    ///         (patch("$.b"), Priority::HIGH),
+    ///         (patch("$.c"), Priority::HIGH),
+    ///         (patch("$.d"), Priority::LOW),
+    ///     ],
+    ///     2,
+    /// );
+    ///
+    /// let sizes: Vec<_> = groups.iter().map(|(p, patches)| (*p, patches.len())).collect();
+    /// assert_eq!(sizes, [(Priority::HIGH, 2), (Priority::HIGH, 1), (Priority::LOW, 1)]);
+    /// ```
+    pub fn group_by_priority(
+        patches: Vec<(FramePatch, Priority)>,
+        max_patches: usize,
+    ) -> Vec<(Priority, Vec<FramePatch>)> {
+        let max_patches = max_patches.max(1);
+        let mut groups: Vec<(Priority, Vec<FramePatch>)> = Vec::new();
+        for (patch, priority) in patches {
+            match groups.last_mut() {
+                Some((current, group)) if *current == priority && group.len() < max_patches => {
+                    group.push(patch);
+                }
+                _ => groups.push((priority, vec![patch])),
+            }
+        }
+        groups
+    }
+
+    /// Whether `map`, `depth` levels below the root, is expanded member by
+    /// member rather than sent whole.
+    fn expands(&self, map: &HashMap<String, JsonData>, depth: usize) -> bool {
+        self.max_depth.is_none_or(|max_depth| depth < max_depth)
+            && map
+                .keys()
+                .all(|key| JsonPath::root().append_key(key).is_ok())
+    }
+
+    fn placeholder(&self, value: &JsonData, depth: usize) -> JsonData {
+        match value {
+            JsonData::Object(map) if self.expands(map, depth) => JsonData::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), self.placeholder(value, depth + 1)))
+                    .collect(),
+            ),
+            JsonData::Array(_) => JsonData::Array(Vec::new()),
+            JsonData::Integer(_) => JsonData::Integer(0),
+            JsonData::Float(_) => JsonData::Float(0.0),
+            JsonData::Bool(_) => JsonData::Bool(false),
+            JsonData::Object(_) | JsonData::String(_) | JsonData::Null => JsonData::Null,
+        }
+    }
+
+    /// Recursive walker emitting prioritized patches into `out`; `root` is
+    /// the whole document, against which path rules match.
+    fn collect(
+        &self,
+        root: &JsonData,
+        data: &JsonData,
+        path: &JsonPath,
+        depth: usize,
+        threshold: Priority,
+        out: &mut Vec<(FramePatch, Priority)>,
+    ) {
+        if let JsonData::Object(map) = data
+            && self.expands(map, depth)
+        {
+            let mut members: Vec<_> = map.iter().collect();
+            members.sort_unstable_by_key(|(key, _)| *key);
+            for (key, value) in members {
+                if let Ok(child) = path.append_key(key) {
+                    self.collect(root, value, &child, depth + 1, threshold, out);
+                }
+            }
+            return;
+        }
+
+        let priority = compute_priority_in(&self.priorities, root, path, data);
+        if let JsonData::Array(items) = data
+            && let Some(chunking) = self.array_chunking_for(path)
+            && items.len() > chunking.chunk_size.max(1)
+        {
+            collect_array_chunks(items, path, chunking, priority, threshold, out);
+            return;
+        }
+
+        if priority >= threshold {
+            out.push((FramePatch::set(path.clone(), data.clone()), priority));
+        }
+    }
+}
+
+/// A resumable walk over a document yielding its patches on demand,
+/// created by [`FrameGenerator::cursor`].
+///
+/// Values not sent yet wait in a priority queue, highest priority first and
+/// document order among equals. Objects wait there ranked by their
+/// highest-priority member above the threshold, within
+/// [`RANK_LOOKAHEAD`], and are only opened, queueing their members, when
+/// they reach the front; objects known to have no such member are never
+/// queued. Array chunks are queued one at a time. Every value is cloned
+/// only when its patch is yielded.
+#[derive(Debug, Clone)]
+pub struct PatchCursor {
+    threshold: Priority,
+    frontier: BinaryHeap<Pending>,
+    held: Option<(FramePatch, Priority)>,
+    /// Values looked at so far, see [`Self::values_visited`].
+    visited: usize,
+}
+
+impl PatchCursor {
+    /// Patches below this priority are skipped.
+    pub fn threshold(&self) -> Priority {
+        self.threshold
+    }
+
+    /// How many values the cursor has looked at so far, ranking objects and
+    /// opening them: the work behind the patches yielded, which grows with
+    /// what they cover rather than with the document.
+    pub fn values_visited(&self) -> usize {
+        self.visited
+    }
+
+    /// The next patch and its priority, or `None` once the document is
+    /// exhausted. `generator` and `data` must be the ones the cursor was
+    /// created with.
+    pub fn next_patch(
+        &mut self,
+        generator: &FrameGenerator,
+        data: &JsonData,
+    ) -> Option<(FramePatch, Priority)> {
+        if let Some(held) = self.held.take() {
+            return Some(held);
+        }
+
+        while let Some(pending) = self.frontier.pop() {
+            let Some(value) = resolve(data, &pending.path) else {
+                continue;
+            };
+            match (pending.step, value) {
+                (Step::Open, JsonData::Object(map)) => {
+                    self.visited += map.len();
+                    let mut members: Vec<_> = map.iter().collect();
+                    members.sort_unstable_by_key(|(key, _)| *key);
+                    for (index, (key, member)) in members.into_iter().enumerate() {
+                        if let Ok(path) = pending.path.append_key(key) {
+                            let mut order = pending.order.clone();
+                            order.push(index);
+                            self.push(generator, data, member, path, order);
+                        }
+                    }
+                }
+                (Step::Chunk { index, base }, JsonData::Array(items)) => {
+                    let Some(chunking) = generator.array_chunking_for(&pending.path) else {
+                        continue;
+                    };
+                    let chunk_size = chunking.chunk_size.max(1);
+                    let Some(chunk) = items.chunks(chunk_size).nth(index) else {
+                        continue;
+                    };
+                    let metadata = ArrayChunkMetadata {
+                        total_items: items.len(),
+                        chunk_index: index,
+                        chunk_size,
+                    };
+                    let patch =
+                        FramePatch::append_chunk(pending.path.clone(), chunk.to_vec(), metadata);
+                    let priority = pending.priority;
+                    let next = index + 1;
+                    let next_priority = chunk_priority(chunking, base, next);
+                    if next * chunk_size < items.len() && next_priority >= self.threshold {
+                        self.frontier.push(Pending {
+                            priority: next_priority,
+                            step: Step::Chunk { index: next, base },
+                            ..pending
+                        });
+                    }
+                    return Some((patch, priority));
+                }
+                (Step::Set, value) => {
+                    return Some((
+                        FramePatch::set(pending.path, value.clone()),
+                        pending.priority,
+                    ));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// The next patches sharing one priority, as one frame would carry
+    /// them: at least one patch, then more while their values stay within
+    /// `max_bytes` in total (by [`JsonData::memory_size`]). `None` once the
+    /// document is exhausted.
+    ///
+    /// Objects of another priority stay unopened until a later call reaches
+    /// them.
+    pub fn next_group(
+        &mut self,
+        generator: &FrameGenerator,
+        data: &JsonData,
+        max_bytes: usize,
+    ) -> Option<(Priority, Vec<FramePatch>)> {
+        let (first, priority) = self.next_patch(generator, data)?;
+        let mut size = first.value.memory_size();
+        let mut group = vec![first];
+        // Stop before dequeuing anything of another priority, so a group
+        // never opens an object it does not send from.
+        while self
+            .frontier
+            .peek()
+            .is_some_and(|next| next.priority == priority)
+            && let Some((patch, next_priority)) = self.next_patch(generator, data)
+        {
+            let patch_size = patch.value.memory_size();
+            if next_priority != priority || size + patch_size > max_bytes {
+                self.held = Some((patch, next_priority));
+                break;
+            }
+            size += patch_size;
+            group.push(patch);
+        }
+        Some((priority, group))
+    }
+
+    /// Queue `value` at `path`: an object to open, an array to chunk, or a
+    /// value to send whole if it clears the threshold.
+    fn push(
+        &mut self,
+        generator: &FrameGenerator,
+        root: &JsonData,
+        value: &JsonData,
+        path: JsonPath,
+        order: Vec<usize>,
+    ) {
+        if let JsonData::Object(map) = value
+            && generator.expands(map, path.depth())
+        {
+            let mut budget = RANK_LOOKAHEAD;
+            let (rank, complete) = self.rank(generator, root, value, &path, &mut budget);
+            let priority = if complete {
+                rank
+            } else {
+                // Too large to rank: there may be members above the
+                // threshold beyond the lookahead, so the object is queued
+                // regardless.
+                let whole = compute_priority_in(&generator.priorities, root, &path, value);
+                Some(
+                    rank.map_or(whole, |rank| rank.max(whole))
+                        .max(self.threshold),
+                )
+            };
+            if let Some(priority) = priority {
+                self.frontier.push(Pending {
+                    priority,
+                    order,
+                    path,
+                    step: Step::Open,
+                });
+            }
+            return;
+        }
+
+        let mut priority = compute_priority_in(&generator.priorities, root, &path, value);
+        let mut step = Step::Set;
+        if let JsonData::Array(items) = value
+            && let Some(chunking) = generator.array_chunking_for(&path)
+            && items.len() > chunking.chunk_size.max(1)
+        {
+            step = Step::Chunk {
+                index: 0,
+                base: priority,
+            };
+            priority = chunk_priority(chunking, priority, 0);
+        }
+
+        if priority >= self.threshold {
+            self.frontier.push(Pending {
+                priority,
+                order,
+                path,
+                step,
+            });
+        }
+    }
+
+    /// The highest priority above the threshold of the patches `value` at
+    /// `path` yields, if any, and whether every value below it was looked
+    /// at. Looks at no more than `budget` values, in document order; an
+    /// object with more members than are left is not looked into.
+    fn rank(
+        &mut self,
+        generator: &FrameGenerator,
+        root: &JsonData,
+        value: &JsonData,
+        path: &JsonPath,
+        budget: &mut usize,
+    ) -> (Option<Priority>, bool) {
+        if let JsonData::Object(map) = value
+            && generator.expands(map, path.depth())
+        {
+            if map.len() > *budget {
+                *budget = 0;
+                return (None, false);
+            }
+            *budget -= map.len();
+            self.visited += map.len();
+            let mut members: Vec<_> = map.iter().collect();
+            members.sort_unstable_by_key(|(key, _)| *key);
+            let mut rank = None;
+            let mut complete = true;
+            for (key, member) in members {
+                if let Ok(child) = path.append_key(key) {
+                    let (member_rank, member_complete) =
+                        self.rank(generator, root, member, &child, budget);
+                    rank = rank.max(member_rank);
+                    complete &= member_complete;
+                }
+            }
+            return (rank, complete);
+        }
+
+        let mut priority = compute_priority_in(&generator.priorities, root, path, value);
+        if let JsonData::Array(items) = value
+            && let Some(chunking) = generator.array_chunking_for(path)
+            && items.len() > chunking.chunk_size.max(1)
+        {
+            priority = chunk_priority(chunking, priority, 0);
+        }
+        ((priority >= self.threshold).then_some(priority), true)
+    }
+}
+
+/// A value waiting in a [`PatchCursor`]'s queue.
+#[derive(Debug, Clone)]
+struct Pending {
+    priority: Priority,
+    /// Position in document order: the sorted-key index at every level.
+    order: Vec<usize>,
+    path: JsonPath,
+    step: Step,
+}
+
+/// What a [`PatchCursor`] does with a value once it is dequeued.
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    /// Queue the members of the object.
+    Open,
+    /// Send the chunk at `index`, then queue the next one.
+    Chunk { index: usize, base: Priority },
+    /// Send the value whole.
+    Set,
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.order.cmp(&self.order))
+    }
+}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+/// The value at `path` in `data`, if it still exists.
+fn resolve<'a>(data: &'a JsonData, path: &JsonPath) -> Option<&'a JsonData> {
+    path.segments()
+        .iter()
+        .try_fold(data, |value, segment| match segment {
+            PathSegment::Key(key) => value.get(key),
+            PathSegment::Index(index) => value.as_array()?.get(*index),
+        })
+}
+
+/// Priority of chunk `chunk_index` of an array prioritized at `base`.
+fn chunk_priority(chunking: &ArrayChunking, base: Priority, chunk_index: usize) -> Priority {
+    if chunk_index * chunking.chunk_size.max(1) < chunking.high_priority_items {
+        base.max(Priority::HIGH)
+    } else {
+        base
+    }
+}
+
+/// Emit `items` as ordered `Append` chunks, each tagged with its
+/// [`ArrayChunkMetadata`].
+///
+/// The tail shares the priority computed for the whole array; chunks
+/// overlapping the first `high_priority_items` elements are raised to at
+/// least [`Priority::HIGH`]. Priorities are therefore non-increasing in chunk
+/// order, so the stable priority sort keeps chunks in order and the
+/// threshold only ever drops a suffix.
+fn collect_array_chunks(
+    items: &[JsonData],
+    path: &JsonPath,
+    chunking: &ArrayChunking,
+    priority: Priority,
+    threshold: Priority,
+    out: &mut Vec<(FramePatch, Priority)>,
+) {
+    let chunk_size = chunking.chunk_size.max(1);
+    for (chunk_index, chunk) in items.chunks(chunk_size).enumerate() {
+        let chunk_priority = chunk_priority(chunking, priority, chunk_index);
+        if chunk_priority < threshold {
+            break;
+        }
+        let metadata = ArrayChunkMetadata {
+            total_items: items.len(),
+            chunk_index,
+            chunk_size,
+        };
+        out.push((
+            FramePatch::append_chunk(path.clone(), chunk.to_vec(), metadata),
+            chunk_priority,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::frame::PatchOperation;
+    use serde_json::json;
+
+    fn data(value: serde_json::Value) -> JsonData {
+        value.into()
+    }
+
+    fn paths(patches: &[(FramePatch, Priority)]) -> Vec<String> {
+        patches
+            .iter()
+            .map(|(patch, _)| patch.path.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_equal_priorities_keep_sorted_key_order() {
+        let document = data(json!({"zeta": "z", "alpha": "a", "mid": "m"}));
+        let patches = FrameGenerator::default().patches(&document, Priority::MIN);
+        assert_eq!(paths(&patches), ["$.alpha", "$.mid", "$.zeta"]);
+    }
+
+    #[test]
+    fn test_scalar_root_is_sent_in_the_skeleton() {
+        let generator = FrameGenerator::default();
+        for value in [json!("text"), json!(42), json!(null)] {
+            let document = data(value);
+            assert_eq!(generator.skeleton(&document), document);
+            assert!(generator.patches(&document, Priority::MIN).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_unaddressable_keys_send_their_object_whole() {
+        let document = data(json!({"id": 1, "outer": {"weird.key": [1, 2], "plain": true}}));
+        let generator = FrameGenerator::default();
+
+        assert_eq!(
+            generator.skeleton(&document),
+            data(json!({"id": 0, "outer": null}))
+        );
+        let patches = generator.patches(&document, Priority::MIN);
+        assert_eq!(paths(&patches), ["$.id", "$.outer"]);
+        assert_eq!(patches[1].0.value, document.get("outer").unwrap().clone());
+    }
+
+    #[test]
+    fn test_objects_below_max_depth_are_sent_whole() {
+        let document = data(json!({"a": {"b": {"c": 1}}}));
+        let generator = FrameGenerator::default().with_max_depth(1);
+
+        assert_eq!(generator.skeleton(&document), data(json!({"a": null})));
+        let patches = generator.patches(&document, Priority::MIN);
+        assert_eq!(paths(&patches), ["$.a"]);
+        assert_eq!(patches[0].0.value, data(json!({"b": {"c": 1}})));
+    }
+
+    #[test]
+    fn test_threshold_drops_only_a_suffix_of_chunks() {
+        let document = data(json!({"rows": [1, 2, 3, 4, 5]}));
+        let generator = FrameGenerator::default()
+            .with_array_chunking("$.rows", ArrayChunking::new(2).with_high_priority_items(2));
+
+        let patches = generator.patches(&document, Priority::HIGH);
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].0.operation, PatchOperation::Append);
+        assert_eq!(patches[0].0.value, data(json!([1, 2])));
+    }
+
+    #[test]
+    fn test_cursor_matches_patches_for_flat_documents() {
+        let document = data(json!({
+            "id": 1,
+            "title": "Report",
+            "tags": ["a", "b"],
+            "rows": [1, 2, 3, 4, 5, 6, 7],
+            "notes": "x".repeat(2000),
+        }));
+        let generator = FrameGenerator::default()
+            .with_array_chunking("$.rows", ArrayChunking::new(2).with_high_priority_items(2));
+
+        for threshold in [Priority::MIN, Priority::MEDIUM, Priority::HIGH] {
+            let mut cursor = generator.cursor(&document, threshold);
+            let lazy: Vec<_> =
+                std::iter::from_fn(|| cursor.next_patch(&generator, &document)).collect();
+            assert_eq!(
+                lazy,
+                generator.patches(&document, threshold),
+                "{threshold:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cursor_ranks_objects_by_their_highest_member() {
+        let document = data(json!({
+            "details": {"id": 7, "body": "text", "meta": {"status": "ok"}},
+            "name": "Ada",
+        }));
+        let generator = FrameGenerator::default();
+
+        let mut cursor = generator.cursor(&document, Priority::MIN);
+        let lazy: Vec<_> =
+            std::iter::from_fn(|| cursor.next_patch(&generator, &document)).collect();
+        assert_eq!(
+            paths(&lazy),
+            [
+                "$.details.id",
+                "$.details.meta.status",
+                "$.name",
+                "$.details.body"
+            ]
+        );
+        assert_eq!(lazy, generator.patches(&document, Priority::MIN));
+    }
+
+    #[test]
+    fn test_cursor_skips_objects_without_members_above_the_threshold() {
+        let document = data(json!({"id": 1, "details": {"body": "text"}}));
+        let generator = FrameGenerator::default();
+
+        let cursor = generator.cursor(&document, Priority::CRITICAL);
+        assert_eq!(cursor.frontier.len(), 1, "only $.id is queued");
+    }
+
+    #[test]
+    fn test_cursor_leaves_unreached_objects_unopened() {
+        let archive: serde_json::Map<_, _> = (0..10_000)
+            .map(|i| (format!("entry{i}"), json!({"value": i})))
+            .collect();
+        let document = data(json!({"id": 1, "status": "ok", "archive": archive}));
+        let generator = FrameGenerator::default();
+
+        let mut cursor = generator.cursor(&document, Priority::MIN);
+        let (priority, group) = cursor
+            .next_group(&generator, &document, usize::MAX)
+            .unwrap();
+        assert_eq!(priority, Priority::CRITICAL);
+        let sent: Vec<_> = group.iter().map(|patch| patch.path.to_string()).collect();
+        assert_eq!(sent, ["$.id", "$.status"]);
+        assert_eq!(
+            cursor.frontier.len(),
+            1,
+            "only the archive object is queued"
+        );
+    }
+
+    #[test]
+    fn test_cursor_first_batch_work_does_not_grow_with_the_document() {
+        let first_batch = |entries: usize| {
+            let archive: serde_json::Map<_, _> = (0..entries)
+                .map(|i| {
+                    let entry = json!({"value": i, "history": {"notes": ["x", "y", "z"]}});
+                    (format!("entry{i}"), entry)
+                })
+                .collect();
+            let document = data(json!({
+                "id": 1,
+                "meta": {"title": "Report", "owner": {"name": "Ada"}},
+                "archive": archive,
+            }));
+            let generator = FrameGenerator::default();
+            let mut cursor = generator.cursor(&document, Priority::MIN);
+            let (_, group) = cursor
+                .next_group(&generator, &document, usize::MAX)
+                .unwrap();
+            (group, cursor.values_visited(), cursor.frontier.len())
+        };
+
+        let small = first_batch(100);
+        let large = first_batch(100_000);
+        let sent: Vec<_> = small.0.iter().map(|patch| patch.path.to_string()).collect();
+        assert_eq!(sent, ["$.id"]);
+        assert_eq!(small, large);
+        assert!(small.1 <= 3 + RANK_LOOKAHEAD * 3, "{}", small.1);
+    }
+
+    #[test]
+    fn test_cursor_queues_objects_beyond_the_lookahead_at_their_own_priority() {
+        // More members than the lookahead: `archive` cannot be ranked by its
+        // members, so it waits at the priority it has as a whole value and
+        // its `id` comes after the medium-priority `title`.
+        let archive: serde_json::Map<_, _> = (0..RANK_LOOKAHEAD)
+            .map(|i| (format!("entry{i:03}"), json!("x".repeat(2000))))
+            .chain([("id".to_string(), json!(7))])
+            .collect();
+        let document = data(json!({"archive": archive, "title": "Report"}));
+        let generator = FrameGenerator::default();
+
+        let mut cursor = generator.cursor(&document, Priority::MIN);
+        let lazy: Vec<_> =
+            std::iter::from_fn(|| cursor.next_patch(&generator, &document)).collect();
+        let mut eager = generator.patches(&document, Priority::MIN);
+        assert_ne!(paths(&lazy), paths(&eager));
+        let title = paths(&lazy).iter().position(|p| p == "$.title").unwrap();
+        let id = paths(&lazy)
+            .iter()
+            .position(|p| p == "$.archive.id")
+            .unwrap();
+        assert!(title < id);
+
+        let mut lazy = lazy;
+        let key = |(patch, _): &(FramePatch, Priority)| patch.path.to_string();
+        lazy.sort_by_key(key);
+        eager.sort_by_key(key);
+        assert_eq!(lazy, eager, "the same patches, in another order");
+    }
+
+    #[test]
+    fn test_cursor_groups_respect_the_byte_budget() {
+        let document = data(json!({"a": "x", "b": "y", "c": "z"}));
+        let generator = FrameGenerator::default();
+        let budget = document.get("a").unwrap().memory_size() * 2;
+
+        let mut cursor = generator.cursor(&document, Priority::MIN);
+        let sizes: Vec<_> = std::iter::from_fn(|| cursor.next_group(&generator, &document, budget))
+            .map(|(_, group)| group.len())
+            .collect();
+        assert_eq!(sizes, [2, 1]);
+    }
+}
@@ -3,6 +3,10 @@
 //! Stateless domain logic that does not naturally belong to a single entity
 //! or value object. Services here are pure and WASM-compatible.
 
+pub mod diff;
+pub mod frame_generation;
 pub mod priority;
 
-pub use priority::{PriorityHeuristicConfig, compute_priority};
+pub use diff::diff_documents;
+pub use frame_generation::{FrameGenerator, PatchCursor};
+pub use priority::{PjsPriority, PriorityHeuristicConfig, compute_priority, compute_priority_in};
@@ -8,6 +8,10 @@
 //!
 //! # Algorithm
 //!
+//! 0. **Path rules** — [`compute_priority_in`] only: the highest priority
+//!    among the [`PriorityHeuristicConfig::path_rules`] whose
+//!    [`JsonPathQuery`] selects the node wins. Queries may contain filters,
+//!    so matching needs the document the path addresses.
 //! 1. **Per-call overrides** — case-sensitive lookup of the last path key in
 //!    [`PriorityHeuristicConfig::overrides`] short-circuits the heuristic.
 //!    This is the hook used by `Stream` for its per-stream `priority_rules`.
@@ -40,7 +44,7 @@
 //! assert_eq!(priority.value(), 50 + 20 + 5);
 //! ```
 
-use crate::value_objects::{JsonData, JsonPath, PathSegment, Priority};
+use crate::value_objects::{JsonData, JsonPath, JsonPathQuery, PathSegment, Priority};
 use std::collections::HashMap;
 
 /// Tunable parameters for the priority heuristic.
@@ -73,6 +77,9 @@ pub struct PriorityHeuristicConfig {
     pub large_object_threshold: usize,
     /// Per-call exact-key overrides (case-sensitive). Wins over heuristics.
     pub overrides: HashMap<String, Priority>,
+    /// JSONPath rules, applied by [`compute_priority_in`] before everything
+    /// else. When several queries select a node, the highest priority wins.
+    pub path_rules: Vec<(JsonPathQuery, Priority)>,
 }
 
 impl Default for PriorityHeuristicConfig {
@@ -107,6 +114,7 @@ impl Default for PriorityHeuristicConfig {
             small_string_threshold: 50,
             large_object_threshold: 10,
             overrides: HashMap::new(),
+            path_rules: Vec::new(),
         }
     }
 }
@@ -146,6 +154,74 @@ impl PriorityHeuristicConfig {
     pub fn add_override(&mut self, key: impl Into<String>, priority: Priority) {
         self.overrides.insert(key.into(), priority);
     }
+
+    /// Give every node `query` selects `priority`, e.g.
+    /// `$.user.posts[*].title`. Applied by [`compute_priority_in`] only.
+    pub fn add_path_rule(&mut self, query: JsonPathQuery, priority: Priority) {
+        self.path_rules.push((query, priority));
+    }
+
+    /// Add a path rule for every priority `T` declares, see [`PjsPriority`].
+    pub fn add_priorities_of<T: PjsPriority + ?Sized>(&mut self) {
+        for (path, priority) in T::priority_map() {
+            self.add_path_rule(JsonPathQuery::from(&path), priority);
+        }
+    }
+
+    /// Priority of the highest path rule selecting `path` in `root`.
+    fn path_rule_priority(&self, root: &JsonData, path: &JsonPath) -> Option<Priority> {
+        self.path_rules
+            .iter()
+            .filter(|(query, _)| query.matches(root, path))
+            .map(|(_, priority)| *priority)
+            .max()
+    }
+}
+
+/// Priorities a type declares for its serialized fields.
+///
+/// Usually derived: with the `derive` feature, `#[derive(PjsPriority)]`
+/// reads `#[pjs(priority = "critical")]` (or `high`, `medium`, `low`,
+/// `background`, or a level in `1..=255`) from each field and keys it by
+/// the field's serde name, so renaming a field cannot leave its priority
+/// behind. A field marked `#[pjs(nested)]` contributes its own type's
+/// priorities below its path.
+///
+/// # Examples
+///
+/// ```
+/// use pjson_rs_domain::services::PjsPriority;
+/// use pjson_rs_domain::value_objects::{JsonPath, Priority};
+/// use std::collections::HashMap;
+///
+/// struct Article;
+///
+/// impl PjsPriority for Article {
+///     fn priority_map() -> HashMap<JsonPath, Priority> {
+///         HashMap::from([(JsonPath::new("$.headline").unwrap(), Priority::CRITICAL)])
+///     }
+/// }
+///
+/// let map = Article::priority_map();
+/// assert_eq!(map[&JsonPath::new("$.headline").unwrap()], Priority::CRITICAL);
+/// ```
+pub trait PjsPriority {
+    /// Priority of each annotated location, keyed by its path relative to
+    /// the serialized value.
+    fn priority_map() -> HashMap<JsonPath, Priority>;
+}
+
+impl<T: PjsPriority + ?Sized> PjsPriority for Box<T> {
+    fn priority_map() -> HashMap<JsonPath, Priority> {
+        T::priority_map()
+    }
+}
+
+/// `None` serializes as `null`, which has no fields to prioritize.
+impl<T: PjsPriority> PjsPriority for Option<T> {
+    fn priority_map() -> HashMap<JsonPath, Priority> {
+        T::priority_map()
+    }
 }
 
 fn to_strings(items: &[&str]) -> Vec<String> {
@@ -162,10 +238,44 @@ fn matches_lower(list: &[String], lower_key: &str) -> bool {
     list.iter().any(|f| f.eq_ignore_ascii_case(lower_key))
 }
 
+/// Compute the priority of the node at `path` in the document `root`,
+/// applying [`PriorityHeuristicConfig::path_rules`] first and falling back to
+/// [`compute_priority`].
+///
+/// # Examples
+///
+/// ```
+/// use pjson_rs_domain::services::{PriorityHeuristicConfig, compute_priority_in};
+/// use pjson_rs_domain::value_objects::{JsonData, JsonPath, JsonPathQuery, Priority};
+///
+/// let mut cfg = PriorityHeuristicConfig::default();
+/// cfg.add_path_rule(
+///     JsonPathQuery::new("$.posts[*].title").unwrap(),
+///     Priority::CRITICAL,
+/// );
+///
+/// let doc: JsonData = serde_json::json!({"posts": [{"title": "a"}]}).into();
+/// let path = JsonPath::new("$.posts[0].title").unwrap();
+/// let value = JsonData::String("a".to_string());
+/// assert_eq!(compute_priority_in(&cfg, &doc, &path, &value), Priority::CRITICAL);
+/// ```
+pub fn compute_priority_in(
+    config: &PriorityHeuristicConfig,
+    root: &JsonData,
+    path: &JsonPath,
+    value: &JsonData,
+) -> Priority {
+    config
+        .path_rule_priority(root, path)
+        .unwrap_or_else(|| compute_priority(config, path, value))
+}
+
 /// Compute the priority for a single `(path, value)` pair using the supplied
 /// heuristic configuration.
 ///
-/// See the [module-level docs](self) for the algorithm description.
+/// See the [module-level docs](self) for the algorithm description. Path
+/// rules are not applied, since they need the whole document; use
+/// [`compute_priority_in`] when it is available.
 pub fn compute_priority(
     config: &PriorityHeuristicConfig,
     path: &JsonPath,
@@ -301,4 +411,38 @@ mod tests {
         // depth 7 > 5 → -10. MEDIUM (50) - 10 = 40
         assert_eq!(compute_priority(&cfg, &path, &value).value(), 40);
     }
+
+    #[test]
+    fn path_rules_need_the_document_and_highest_match_wins() {
+        use crate::value_objects::JsonPathQuery;
+
+        let mut cfg = PriorityHeuristicConfig::default();
+        cfg.add_path_rule(
+            JsonPathQuery::new("$.posts[*].title").unwrap(),
+            Priority::LOW,
+        );
+        cfg.add_path_rule(
+            JsonPathQuery::new("$.posts[?@.pinned].title").unwrap(),
+            Priority::CRITICAL,
+        );
+        let doc: JsonData = serde_json::json!({
+            "posts": [{"title": "a", "pinned": true}, {"title": "b"}]
+        })
+        .into();
+        let value = JsonData::String("a".to_string());
+
+        let pinned = JsonPath::new("$.posts[0].title").unwrap();
+        let other = JsonPath::new("$.posts[1].title").unwrap();
+        assert_eq!(
+            compute_priority_in(&cfg, &doc, &pinned, &value),
+            Priority::CRITICAL
+        );
+        assert_eq!(
+            compute_priority_in(&cfg, &doc, &other, &value),
+            Priority::LOW
+        );
+
+        // Without the document, path rules are not applied.
+        assert_ne!(compute_priority(&cfg, &pinned, &value), Priority::CRITICAL);
+    }
 }
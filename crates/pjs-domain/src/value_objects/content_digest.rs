@@ -0,0 +1,566 @@
+//! Content digest of a reconstructed document
+//!
+//! A stream's completion frame carries one [`ContentDigest`]: SHA-256 — or,
+//! with an [`IntegrityKey`] configured, HMAC-SHA256 — over the *canonical
+//! JSON* form of the document the frames reconstruct to. Every reconstructor
+//! (Rust, WASM) recomputes it over what it actually built and compares, so a
+//! client can prove it holds exactly what the server sent, regardless of how
+//! the document was split into frames or in which order they arrived.
+//!
+//! # Canonical JSON
+//!
+//! [`canonical_json`] writes a value with no insignificant whitespace and:
+//!
+//! - object members sorted by key, comparing keys as UTF-16 code units (the
+//!   order RFC 8785 and JavaScript's default sort use);
+//! - strings escaped as `serde_json` (and `JSON.stringify`) do: `"` and `\`,
+//!   control characters as `\b \f \n \r \t` or lowercase `\u00xx`, everything
+//!   else verbatim;
+//! - integers in decimal, and floats with no fractional part and a magnitude
+//!   below 2^53 written as integers, so `1.0` and `1` digest identically;
+//!   other floats use the shortest representation that round-trips.
+//!
+//! The digest is rendered as `<algorithm>:<lowercase hex>`, e.g.
+//! `sha256:9f86…` or `hmac-sha256:1b2c…`.
+
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Length in bytes of every digest this module produces.
+const DIGEST_LEN: usize = 32;
+
+/// Floats with no fractional part below this magnitude are written as
+/// integers: every such value is exactly representable as both.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Errors raised while parsing or verifying a [`ContentDigest`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum IntegrityError {
+    /// The reconstructed document does not match the server's digest.
+    #[error(
+        "content digest mismatch: server sent {expected}, reconstructed document digests to {actual}"
+    )]
+    Mismatch {
+        /// Digest carried by the completion frame.
+        expected: ContentDigest,
+        /// Digest of the document the client reconstructed.
+        actual: ContentDigest,
+    },
+
+    /// The completion digest is HMAC-signed but no key is configured to
+    /// check it.
+    #[error("completion digest is HMAC-signed but no integrity key is configured")]
+    KeyRequired,
+
+    /// An integrity key is configured but the completion frame carries no
+    /// HMAC-signed digest, so the stream's authenticity cannot be shown.
+    #[error("integrity key configured but the completion frame carries no HMAC-signed digest")]
+    Unsigned,
+
+    /// The digest is not of the form `<algorithm>:<64 hex digits>`.
+    #[error("malformed content digest: {0}")]
+    Malformed(String),
+
+    /// The integrity key is shorter than [`IntegrityKey::MIN_LEN`].
+    #[error("integrity key must be at least {min} bytes, got {len}")]
+    KeyTooShort {
+        /// Length of the rejected key.
+        len: usize,
+        /// Minimum accepted length.
+        min: usize,
+    },
+}
+
+/// Algorithm a [`ContentDigest`] was computed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DigestAlgorithm {
+    /// Plain SHA-256: detects corruption and reconstruction bugs.
+    Sha256,
+    /// HMAC-SHA256 under a shared [`IntegrityKey`]: additionally proves the
+    /// digest came from a holder of the key.
+    HmacSha256,
+}
+
+impl DigestAlgorithm {
+    /// Prefix used in the digest's string form.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::HmacSha256 => "hmac-sha256",
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Shared secret used to sign completion digests with HMAC-SHA256.
+///
+/// Servers and clients configured with the same key produce and accept
+/// [`DigestAlgorithm::HmacSha256`] digests; a client holding a key rejects
+/// unsigned ones (see [`IntegrityError::Unsigned`]), so stripping or
+/// replacing the signature cannot downgrade the check.
+#[derive(Clone, PartialEq, Eq)]
+pub struct IntegrityKey(Vec<u8>);
+
+impl IntegrityKey {
+    /// Shortest accepted key, in bytes: the HMAC-SHA256 output size, below
+    /// which RFC 2104 notes a key weakens the MAC.
+    pub const MIN_LEN: usize = DIGEST_LEN;
+
+    /// Wrap `bytes` as an integrity key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IntegrityError::KeyTooShort`] if `bytes` is shorter than
+    /// [`Self::MIN_LEN`].
+    pub fn new(bytes: impl Into<Vec<u8>>) -> Result<Self, IntegrityError> {
+        let bytes = bytes.into();
+        if bytes.len() < Self::MIN_LEN {
+            return Err(IntegrityError::KeyTooShort {
+                len: bytes.len(),
+                min: Self::MIN_LEN,
+            });
+        }
+        Ok(Self(bytes))
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        // PANIC: HMAC accepts keys of any length; `new_from_slice` only
+        // fails for MACs with fixed key sizes.
+        HmacSha256::new_from_slice(&self.0).expect("HMAC-SHA256 accepts any key length")
+    }
+}
+
+impl fmt::Debug for IntegrityKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IntegrityKey([redacted])")
+    }
+}
+
+/// Digest of a document's canonical JSON form; see the [module docs](self).
+///
+/// Serializes as its string form, e.g. `"sha256:9f86…"`.
+///
+/// # Example
+/// ```
+/// use pjson_rs_domain::value_objects::ContentDigest;
+/// use serde_json::json;
+///
+/// let sent = ContentDigest::compute(&json!({"b": 1, "a": [true, null]}), None);
+/// assert!(sent.to_string().starts_with("sha256:"));
+///
+/// // Member order and integral floats do not affect the digest.
+/// assert!(sent.verify(&json!({"a": [true, null], "b": 1.0}), None).is_ok());
+/// assert!(sent.verify(&json!({"a": [true], "b": 1}), None).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ContentDigest {
+    algorithm: DigestAlgorithm,
+    bytes: [u8; DIGEST_LEN],
+}
+
+impl ContentDigest {
+    /// Digest of `document`: HMAC-SHA256 under `key` when one is given,
+    /// SHA-256 otherwise.
+    pub fn compute(document: &Value, key: Option<&IntegrityKey>) -> Self {
+        Self::of_canonical(canonical_json(document).as_bytes(), key)
+    }
+
+    fn of_canonical(canonical: &[u8], key: Option<&IntegrityKey>) -> Self {
+        match key {
+            Some(key) => {
+                let mut mac = key.mac();
+                mac.update(canonical);
+                Self {
+                    algorithm: DigestAlgorithm::HmacSha256,
+                    bytes: mac.finalize().into_bytes().into(),
+                }
+            }
+            None => Self {
+                algorithm: DigestAlgorithm::Sha256,
+                bytes: Sha256::digest(canonical).into(),
+            },
+        }
+    }
+
+    /// Algorithm this digest was computed with.
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    /// Raw digest bytes.
+    pub fn as_bytes(&self) -> &[u8; DIGEST_LEN] {
+        &self.bytes
+    }
+
+    /// Whether this digest is an HMAC signature.
+    pub fn is_signed(&self) -> bool {
+        self.algorithm == DigestAlgorithm::HmacSha256
+    }
+
+    /// Check that `document` is what this digest was computed over.
+    ///
+    /// Signed digests are compared in constant time.
+    ///
+    /// # Errors
+    ///
+    /// - [`IntegrityError::Mismatch`] if `document` digests differently;
+    /// - [`IntegrityError::KeyRequired`] if this digest is signed and `key`
+    ///   is `None`;
+    /// - [`IntegrityError::Unsigned`] if `key` is given but this digest is
+    ///   plain SHA-256.
+    pub fn verify(
+        &self,
+        document: &Value,
+        key: Option<&IntegrityKey>,
+    ) -> Result<(), IntegrityError> {
+        let canonical = canonical_json(document);
+        match (self.algorithm, key) {
+            (DigestAlgorithm::HmacSha256, None) => Err(IntegrityError::KeyRequired),
+            (DigestAlgorithm::Sha256, Some(_)) => Err(IntegrityError::Unsigned),
+            (DigestAlgorithm::HmacSha256, Some(key)) => {
+                let mut mac = key.mac();
+                mac.update(canonical.as_bytes());
+                mac.verify_slice(&self.bytes)
+                    .map_err(|_| self.mismatch(canonical.as_bytes(), Some(key)))
+            }
+            (DigestAlgorithm::Sha256, None) => {
+                let actual = Self::of_canonical(canonical.as_bytes(), None);
+                if actual == *self {
+                    Ok(())
+                } else {
+                    Err(IntegrityError::Mismatch {
+                        expected: *self,
+                        actual,
+                    })
+                }
+            }
+        }
+    }
+
+    fn mismatch(&self, canonical: &[u8], key: Option<&IntegrityKey>) -> IntegrityError {
+        IntegrityError::Mismatch {
+            expected: *self,
+            actual: Self::of_canonical(canonical, key),
+        }
+    }
+}
+
+/// Verify a completion frame's optional digest against the reconstructed
+/// `document`.
+///
+/// A missing digest is accepted only when no `key` is configured: streams
+/// from servers that do not compute digests still complete, but a client
+/// expecting signed streams cannot be downgraded by stripping the digest.
+///
+/// # Errors
+///
+/// Returns [`IntegrityError::Unsigned`] if `key` is given and `digest` is
+/// `None`, and otherwise whatever [`ContentDigest::verify`] returns.
+pub fn verify_completion(
+    digest: Option<&ContentDigest>,
+    document: &Value,
+    key: Option<&IntegrityKey>,
+) -> Result<(), IntegrityError> {
+    match (digest, key) {
+        (Some(digest), key) => digest.verify(document, key),
+        (None, Some(_)) => Err(IntegrityError::Unsigned),
+        (None, None) => Ok(()),
+    }
+}
+
+impl fmt::Display for ContentDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.algorithm)?;
+        self.bytes
+            .iter()
+            .try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl FromStr for ContentDigest {
+    type Err = IntegrityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || IntegrityError::Malformed(s.chars().take(80).collect());
+
+        let (prefix, hex) = s.split_once(':').ok_or_else(malformed)?;
+        let algorithm = match prefix {
+            "sha256" => DigestAlgorithm::Sha256,
+            "hmac-sha256" => DigestAlgorithm::HmacSha256,
+            _ => return Err(malformed()),
+        };
+        if hex.len() != DIGEST_LEN * 2 || !hex.is_ascii() {
+            return Err(malformed());
+        }
+
+        let nibble = |digit: u8| {
+            char::from(digit)
+                .to_digit(16)
+                .map(|value| value as u8)
+                .ok_or_else(malformed)
+        };
+        let mut bytes = [0u8; DIGEST_LEN];
+        let (pairs, _) = hex.as_bytes().as_chunks::<2>();
+        for (byte, [high, low]) in bytes.iter_mut().zip(pairs) {
+            *byte = (nibble(*high)? << 4) | nibble(*low)?;
+        }
+        Ok(Self { algorithm, bytes })
+    }
+}
+
+impl TryFrom<String> for ContentDigest {
+    type Error = IntegrityError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ContentDigest> for String {
+    fn from(digest: ContentDigest) -> Self {
+        digest.to_string()
+    }
+}
+
+/// Canonical JSON form of `value`; see the [module docs](self).
+///
+/// # Example
+/// ```
+/// use pjson_rs_domain::value_objects::canonical_json;
+/// use serde_json::json;
+///
+/// assert_eq!(
+///     canonical_json(&json!({"b": [1.0, 2.5], "a": "x\ny"})),
+///     r#"{"a":"x\ny","b":[1,2.5]}"#
+/// );
+/// ```
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                let _ = write!(out, "{i}");
+            } else if let Some(u) = n.as_u64() {
+                let _ = write!(out, "{u}");
+            } else if let Some(f) = n.as_f64() {
+                write_float(f, out);
+            }
+        }
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut members: Vec<_> = map.iter().collect();
+            members.sort_unstable_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            out.push('{');
+            for (i, (key, item)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_float(f: f64, out: &mut String) {
+    if f.fract() == 0.0 && f.abs() < MAX_SAFE_INTEGER {
+        // Exact: `f` is integral and within i64's exactly-representable range.
+        let _ = write!(out, "{}", f as i64);
+    } else {
+        // `serde_json::Value` cannot hold non-finite floats, so this is the
+        // shortest round-trip representation.
+        out.push_str(&Value::from(f).to_string());
+    }
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push_str(&Value::from(s).to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key(byte: u8) -> IntegrityKey {
+        IntegrityKey::new(vec![byte; IntegrityKey::MIN_LEN]).unwrap()
+    }
+
+    #[test]
+    fn test_canonical_form_is_order_and_whitespace_independent() {
+        let a: Value =
+            serde_json::from_str(r#"{ "z": {"y": 1, "x": [1, 2]}, "a": null }"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"a":null,"z":{"x":[1,2],"y":1}}"#).unwrap();
+
+        assert_eq!(canonical_json(&a), r#"{"a":null,"z":{"x":[1,2],"y":1}}"#);
+        assert_eq!(canonical_json(&a), canonical_json(&b));
+    }
+
+    #[test]
+    fn test_canonical_numbers() {
+        assert_eq!(canonical_json(&json!(1.0)), "1");
+        assert_eq!(canonical_json(&json!(-0.0)), "0");
+        assert_eq!(canonical_json(&json!(2.5)), "2.5");
+        assert_eq!(canonical_json(&json!(u64::MAX)), u64::MAX.to_string());
+        assert_eq!(canonical_json(&json!(i64::MIN)), i64::MIN.to_string());
+        assert_eq!(canonical_json(&json!(1e300)), "1e+300");
+    }
+
+    #[test]
+    fn test_canonical_keys_sort_by_utf16_code_units() {
+        // U+FF61 sorts after U+1F600 in UTF-8 but before it in UTF-16
+        // (the emoji's leading surrogate is 0xD83D).
+        let value = json!({"\u{1F600}": 1, "\u{FF61}": 2, "a": 3});
+        assert_eq!(
+            canonical_json(&value),
+            "{\"a\":3,\"\u{1F600}\":1,\"\u{FF61}\":2}"
+        );
+    }
+
+    #[test]
+    fn test_canonical_string_escaping() {
+        assert_eq!(
+            canonical_json(&json!("q\"b\\c\u{1}/é")),
+            r#""q\"b\\c\u0001/é""#
+        );
+    }
+
+    #[test]
+    fn test_digest_round_trips_through_string() {
+        for digest in [
+            ContentDigest::compute(&json!({"a": 1}), None),
+            ContentDigest::compute(&json!({"a": 1}), Some(&key(7))),
+        ] {
+            let parsed: ContentDigest = digest.to_string().parse().unwrap();
+            assert_eq!(parsed, digest);
+
+            let json = serde_json::to_value(digest).unwrap();
+            assert_eq!(json, Value::String(digest.to_string()));
+            assert_eq!(
+                serde_json::from_value::<ContentDigest>(json).unwrap(),
+                digest
+            );
+        }
+    }
+
+    #[test]
+    fn test_sha256_matches_known_vector() {
+        // SHA-256 of the canonical form `{"a":1}`.
+        let digest = ContentDigest::compute(&json!({"a": 1}), None);
+        assert_eq!(
+            digest.to_string(),
+            "sha256:015abd7f5cc57a2dd94b7590f04ad8084273905ee33ec5cebeae62276a97f862"
+        );
+    }
+
+    #[test]
+    fn test_malformed_digests_rejected() {
+        for input in [
+            "",
+            "sha256",
+            "md5:00",
+            "sha256:abc",
+            &format!("sha256:{}", "zz".repeat(32)),
+            &format!("sha256:{}", "é".repeat(32)),
+            &format!("sha256:{}", "+f".repeat(32)),
+        ] {
+            assert!(
+                matches!(
+                    input.parse::<ContentDigest>(),
+                    Err(IntegrityError::Malformed(_))
+                ),
+                "{input:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_detects_mismatch() {
+        let digest = ContentDigest::compute(&json!({"items": [1, 2, 3]}), None);
+        let err = digest
+            .verify(&json!({"items": [1, 3, 2]}), None)
+            .unwrap_err();
+
+        let IntegrityError::Mismatch { expected, actual } = err else {
+            panic!("expected a mismatch, got {err:?}");
+        };
+        assert_eq!(expected, digest);
+        assert_ne!(actual, digest);
+    }
+
+    #[test]
+    fn test_signed_digest_requires_matching_key() {
+        let document = json!({"id": 1});
+        let signed = ContentDigest::compute(&document, Some(&key(1)));
+
+        assert!(signed.is_signed());
+        assert!(signed.verify(&document, Some(&key(1))).is_ok());
+        assert!(matches!(
+            signed.verify(&document, Some(&key(2))),
+            Err(IntegrityError::Mismatch { .. })
+        ));
+        assert_eq!(
+            signed.verify(&document, None),
+            Err(IntegrityError::KeyRequired)
+        );
+    }
+
+    #[test]
+    fn test_key_holder_rejects_unsigned_or_missing_digest() {
+        let document = json!({"id": 1});
+        let plain = ContentDigest::compute(&document, None);
+
+        assert_eq!(
+            plain.verify(&document, Some(&key(1))),
+            Err(IntegrityError::Unsigned)
+        );
+        assert_eq!(
+            verify_completion(None, &document, Some(&key(1))),
+            Err(IntegrityError::Unsigned)
+        );
+        assert!(verify_completion(None, &document, None).is_ok());
+        assert!(verify_completion(Some(&plain), &document, None).is_ok());
+    }
+
+    #[test]
+    fn test_short_key_rejected_and_key_redacted() {
+        assert_eq!(
+            IntegrityKey::new(b"short".to_vec()),
+            Err(IntegrityError::KeyTooShort { len: 5, min: 32 })
+        );
+        assert_eq!(format!("{:?}", key(1)), "IntegrityKey([redacted])");
+    }
+}
@@ -132,6 +132,24 @@ impl JsonPath {
         Self { segments }
     }
 
+    /// Append every segment of `suffix`, producing a new path.
+    ///
+    /// Infallible: both paths already hold only valid keys.
+    ///
+    /// # Examples
+    /// ```
+    /// use pjson_rs_domain::value_objects::JsonPath;
+    ///
+    /// let user = JsonPath::new("$.users[0]").unwrap();
+    /// let name = JsonPath::new("$.profile.name").unwrap();
+    /// assert_eq!(user.join(&name).to_string(), "$.users[0].profile.name");
+    /// ```
+    pub fn join(&self, suffix: &JsonPath) -> Self {
+        let mut segments = self.segments.clone();
+        segments.extend_from_slice(&suffix.segments);
+        Self { segments }
+    }
+
     /// Borrow the path's segments.
     pub fn segments(&self) -> &[PathSegment] {
         &self.segments
@@ -187,7 +205,8 @@ impl JsonPath {
 
     /// Convert to a JSON Pointer (RFC 6901) string.
     ///
-    /// Does not escape `~` or `/` within keys; see follow-up issue for #379.
+    /// `~` and `/` within keys are escaped as `~0` and `~1`. The root renders
+    /// as `"/"`, which [`JsonPath::from_json_pointer`] reads back as the root.
     ///
     /// # Examples
     /// ```
@@ -196,6 +215,9 @@ impl JsonPath {
     /// let path = JsonPath::new("$.users[0].name").unwrap();
     /// assert_eq!(path.to_json_pointer(), "/users/0/name");
     /// assert_eq!(JsonPath::root().to_json_pointer(), "/");
+    ///
+    /// let escaped = JsonPath::root().append_key("a/b~c").unwrap();
+    /// assert_eq!(escaped.to_json_pointer(), "/a~1b~0c");
     /// ```
     pub fn to_json_pointer(&self) -> String {
         if self.segments.is_empty() {
@@ -205,12 +227,88 @@ impl JsonPath {
         for segment in &self.segments {
             pointer.push('/');
             match segment {
-                PathSegment::Key(key) => pointer.push_str(key),
+                PathSegment::Key(key) => {
+                    pointer.push_str(&key.replace('~', "~0").replace('/', "~1"))
+                }
                 PathSegment::Index(idx) => pointer.push_str(&idx.to_string()),
             }
         }
         pointer
     }
+
+    /// Parse a JSON Pointer (RFC 6901), e.g. `"/users/0/name"`.
+    ///
+    /// A pointer does not say whether a numeric token names an array element
+    /// or an object member, so tokens in canonical decimal form (`0`, `12`,
+    /// not `012`) become [`PathSegment::Index`] and every other token a
+    /// [`PathSegment::Key`]. Both `""` and `"/"` denote the root, mirroring
+    /// [`JsonPath::to_json_pointer`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::InvalidPath`] if the pointer does not start
+    /// with `/`, contains an escape other than `~0`/`~1`, or a token is not
+    /// a valid key (empty, or containing `.`, `[` or `]`).
+    ///
+    /// # Examples
+    /// ```
+    /// use pjson_rs_domain::value_objects::JsonPath;
+    ///
+    /// let path = JsonPath::from_json_pointer("/users/0/name").unwrap();
+    /// assert_eq!(path.to_string(), "$.users[0].name");
+    /// assert_eq!(JsonPath::from_json_pointer("").unwrap(), JsonPath::root());
+    /// assert!(JsonPath::from_json_pointer("users").is_err());
+    /// ```
+    pub fn from_json_pointer(pointer: &str) -> DomainResult<Self> {
+        if pointer.is_empty() || pointer == "/" {
+            return Ok(Self::root());
+        }
+        let Some(tokens) = pointer.strip_prefix('/') else {
+            return Err(DomainError::InvalidPath(format!(
+                "JSON Pointer must start with '/': {pointer}"
+            )));
+        };
+        let mut segments = Vec::new();
+        for token in tokens.split('/') {
+            if is_canonical_index(token)
+                && let Ok(index) = token.parse()
+            {
+                segments.push(PathSegment::Index(index));
+                continue;
+            }
+            let key = unescape_pointer_token(token).ok_or_else(|| {
+                DomainError::InvalidPath(format!("Invalid escape in JSON Pointer: {pointer}"))
+            })?;
+            validate_key(&key)?;
+            segments.push(PathSegment::Key(key));
+        }
+        Ok(Self { segments })
+    }
+}
+
+/// Whether `token` is an array index in RFC 6901's canonical form.
+fn is_canonical_index(token: &str) -> bool {
+    !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'))
+}
+
+/// Decode the `~0`/`~1` escapes of one JSON Pointer token.
+fn unescape_pointer_token(token: &str) -> Option<String> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c == '~' {
+            match chars.next()? {
+                '0' => out.push('~'),
+                '1' => out.push('/'),
+                _ => return None,
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    Some(out)
 }
 
 /// **INVARIANT (JP-1):** `Display` is injective and total over representable
@@ -462,4 +560,32 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_json_pointer_round_trip() {
+        for raw in ["$.users[0].name", "$.a~b.c/d[12]", "$.x[0][3]"] {
+            let path = JsonPath::new(raw).unwrap();
+            let pointer = path.to_json_pointer();
+            assert_eq!(JsonPath::from_json_pointer(&pointer).unwrap(), path);
+        }
+        assert_eq!(
+            JsonPath::new("$.a~b.c/d").unwrap().to_json_pointer(),
+            "/a~0b/c~1d"
+        );
+    }
+
+    #[test]
+    fn test_from_json_pointer_tokens() {
+        // Non-canonical numbers stay keys.
+        assert_eq!(
+            JsonPath::from_json_pointer("/posts/01").unwrap().segments(),
+            &[
+                PathSegment::Key("posts".to_string()),
+                PathSegment::Key("01".to_string())
+            ]
+        );
+        assert!(JsonPath::from_json_pointer("/a~2").is_err());
+        assert!(JsonPath::from_json_pointer("/a//b").is_err());
+        assert!(JsonPath::from_json_pointer("/a.b").is_err());
+    }
 }
@@ -0,0 +1,1256 @@
+//! JSONPath query expressions (RFC 9535) evaluated against [`JsonData`].
+//!
+//! A [`JsonPath`] addresses exactly one node; a [`JsonPathQuery`] selects a
+//! set of nodes, so one rule can cover every `title` under `posts` instead of
+//! enumerating concrete paths:
+//!
+//! | Syntax | Selects |
+//! |---|---|
+//! | `$.user.name`, `$['user']['name']` | member by name |
+//! | `$.posts[*]`, `$.posts.*` | every element or member |
+//! | `$.posts[0]`, `$.posts[-1]` | element by index; negative counts from the end |
+//! | `$.posts[1:5]`, `$.posts[::-1]` | array slice (`start:end:step`) |
+//! | `$.posts[0,2]`, `$['a','b']` | union of selectors |
+//! | `$..title` | `title` members at any depth |
+//! | `$.posts[?@.public]`, `$.posts[?(@.score >= 10 && @.tag == 'x')]` | filter |
+//!
+//! Filters support existence tests, comparisons (`==`, `!=`, `<`, `<=`, `>`,
+//! `>=`) between literals and singular queries (`@.a`, `$.b[0]`), `&&`, `||`,
+//! `!` and parentheses. Function extensions (`length()`, `match()`, ...) are
+//! not supported and are rejected at parse time. Object members are visited
+//! in sorted key order, so results are deterministic.
+
+use crate::value_objects::{JsonData, JsonPath, PathSegment};
+use crate::{DomainError, DomainResult};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// Parsed JSONPath query (RFC 9535), e.g. `$.user.posts[*].title`.
+///
+/// Equality, hashing, `Display` and serde all use the query text.
+///
+/// # Examples
+///
+/// ```
+/// use pjson_rs_domain::value_objects::{JsonData, JsonPath, JsonPathQuery};
+///
+/// let doc: JsonData = serde_json::json!({
+///     "posts": [
+///         {"title": "a", "public": true},
+///         {"title": "b", "public": false}
+///     ]
+/// })
+/// .into();
+///
+/// let titles = JsonPathQuery::new("$.posts[*].title").unwrap();
+/// assert_eq!(titles.select(&doc).len(), 2);
+///
+/// let public = JsonPathQuery::new("$.posts[?@.public == true].title").unwrap();
+/// assert_eq!(
+///     public.select_paths(&doc),
+///     vec![JsonPath::new("$.posts[0].title").unwrap()]
+/// );
+/// assert!(public.matches(&doc, &JsonPath::new("$.posts[0].title").unwrap()));
+/// assert!(!public.matches(&doc, &JsonPath::new("$.posts[1].title").unwrap()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonPathQuery {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: i64,
+    },
+    Filter(Box<Filter>),
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Or(Vec<Filter>),
+    And(Vec<Filter>),
+    Not(Box<Filter>),
+    Exists(EmbeddedQuery),
+    Compare(Comparable, CompareOp, Comparable),
+}
+
+/// A query inside a filter, relative to the current node (`@`) or the root.
+#[derive(Debug, Clone)]
+struct EmbeddedQuery {
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Comparable {
+    Literal(JsonData),
+    Query(EmbeddedQuery),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Step from a node to one of its children.
+#[derive(Debug, Clone, Copy)]
+enum Step<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Node<'a> {
+    steps: Vec<Step<'a>>,
+    value: &'a JsonData,
+}
+
+impl JsonPathQuery {
+    /// Parse a query from its textual form.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DomainError::InvalidPath`] if `query` is not a well-formed
+    /// RFC 9535 query or uses an unsupported function extension.
+    pub fn new(query: impl Into<String>) -> DomainResult<Self> {
+        let source = query.into();
+        let segments = Parser::new(&source).parse_query()?;
+        Ok(Self { source, segments })
+    }
+
+    /// The query text.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Values of the nodes the query selects in `root`, in result order.
+    pub fn select<'a>(&self, root: &'a JsonData) -> Vec<&'a JsonData> {
+        self.nodes(root)
+            .into_iter()
+            .map(|node| node.value)
+            .collect()
+    }
+
+    /// Paths of the nodes the query selects in `root`, in result order.
+    ///
+    /// Nodes below an object key a [`JsonPath`] cannot encode (`.`, `[`,
+    /// `]` or the empty key) are left out.
+    pub fn select_paths(&self, root: &JsonData) -> Vec<JsonPath> {
+        self.nodes(root)
+            .into_iter()
+            .filter_map(|node| {
+                JsonPath::from_segments(node.steps.into_iter().map(|step| match step {
+                    Step::Key(key) => PathSegment::Key(key.to_string()),
+                    Step::Index(index) => PathSegment::Index(index),
+                }))
+                .ok()
+            })
+            .collect()
+    }
+
+    /// Check whether the node at `path` in `root` is selected by the query.
+    ///
+    /// Equivalent to `self.select_paths(root).contains(path)`, but walks
+    /// only `path` instead of the whole document. Returns `false` if `path`
+    /// does not exist in `root`.
+    pub fn matches(&self, root: &JsonData, path: &JsonPath) -> bool {
+        let count = self.segments.len();
+        let mut states = vec![false; count + 1];
+        states[0] = true;
+        let mut current = root;
+
+        for segment in path.segments() {
+            let Some((step, child)) = child_at(current, segment) else {
+                return false;
+            };
+            let mut next = vec![false; count + 1];
+            for (index, query_segment) in self.segments.iter().enumerate() {
+                if !states[index] {
+                    continue;
+                }
+                if query_segment.descendant {
+                    next[index] = true;
+                }
+                if query_segment
+                    .selectors
+                    .iter()
+                    .any(|selector| selector.selects_child(current, step, child, root))
+                {
+                    next[index + 1] = true;
+                }
+            }
+            if !next.contains(&true) {
+                return false;
+            }
+            states = next;
+            current = child;
+        }
+
+        states[count]
+    }
+
+    fn nodes<'a>(&self, root: &'a JsonData) -> Vec<Node<'a>> {
+        let start = Node {
+            steps: Vec::new(),
+            value: root,
+        };
+        select_segments(&self.segments, vec![start], root)
+    }
+}
+
+/// Query selecting exactly the node at the path, written as an RFC 9535
+/// normalized path (`$['users'][0]`), so any key is representable.
+///
+/// ```
+/// use pjson_rs_domain::value_objects::{JsonPath, JsonPathQuery};
+///
+/// let path = JsonPath::new("$.user-info[0].name").unwrap();
+/// let query = JsonPathQuery::from(&path);
+/// assert_eq!(query.as_str(), "$['user-info'][0]['name']");
+/// assert_eq!(JsonPathQuery::new(query.as_str()).unwrap(), query);
+/// ```
+impl From<&JsonPath> for JsonPathQuery {
+    fn from(path: &JsonPath) -> Self {
+        let mut source = String::from("$");
+        let mut segments = Vec::with_capacity(path.depth());
+        for segment in path.segments() {
+            let selector = match segment {
+                PathSegment::Key(key) => {
+                    source.push_str("['");
+                    for c in key.chars() {
+                        match c {
+                            '\'' => source.push_str("\\'"),
+                            '\\' => source.push_str("\\\\"),
+                            '\u{8}' => source.push_str("\\b"),
+                            '\u{c}' => source.push_str("\\f"),
+                            '\n' => source.push_str("\\n"),
+                            '\r' => source.push_str("\\r"),
+                            '\t' => source.push_str("\\t"),
+                            c if (c as u32) < 0x20 => {
+                                source.push_str(&format!("\\u{:04x}", c as u32));
+                            }
+                            c => source.push(c),
+                        }
+                    }
+                    source.push_str("']");
+                    Selector::Name(key.clone())
+                }
+                PathSegment::Index(index) => {
+                    source.push_str(&format!("[{index}]"));
+                    // Indices beyond `i64::MAX` cannot exist in memory.
+                    Selector::Index(i64::try_from(*index).unwrap_or(i64::MAX))
+                }
+            };
+            segments.push(Segment {
+                descendant: false,
+                selectors: vec![selector],
+            });
+        }
+        Self { source, segments }
+    }
+}
+
+impl PartialEq for JsonPathQuery {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for JsonPathQuery {}
+
+impl Hash for JsonPathQuery {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+    }
+}
+
+impl fmt::Display for JsonPathQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for JsonPathQuery {
+    type Err = DomainError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Self::new(query)
+    }
+}
+
+impl Serialize for JsonPathQuery {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.source.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonPathQuery {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Evaluation
+// ---------------------------------------------------------------------------
+
+fn select_segments<'a>(
+    segments: &[Segment],
+    mut nodes: Vec<Node<'a>>,
+    root: &'a JsonData,
+) -> Vec<Node<'a>> {
+    for segment in segments {
+        let mut next = Vec::new();
+        for node in &nodes {
+            if segment.descendant {
+                let mut visit = vec![node.clone()];
+                while let Some(current) = visit.pop() {
+                    for selector in &segment.selectors {
+                        selector.select(&current, root, &mut next);
+                    }
+                    let mut children = children_of(&current);
+                    children.reverse();
+                    visit.extend(children);
+                }
+            } else {
+                for selector in &segment.selectors {
+                    selector.select(node, root, &mut next);
+                }
+            }
+        }
+        nodes = next;
+        if nodes.is_empty() {
+            break;
+        }
+    }
+    nodes
+}
+
+fn child_node<'a>(parent: &Node<'a>, step: Step<'a>, value: &'a JsonData) -> Node<'a> {
+    let mut steps = Vec::with_capacity(parent.steps.len() + 1);
+    steps.extend_from_slice(&parent.steps);
+    steps.push(step);
+    Node { steps, value }
+}
+
+/// Children of `node` in document order (object members by sorted key).
+fn children_of<'a>(node: &Node<'a>) -> Vec<Node<'a>> {
+    match node.value {
+        JsonData::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| child_node(node, Step::Index(index), item))
+            .collect(),
+        JsonData::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort_unstable();
+            keys.into_iter()
+                .map(|key| child_node(node, Step::Key(key), &map[key]))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn child_at<'a>(value: &'a JsonData, segment: &'a PathSegment) -> Option<(Step<'a>, &'a JsonData)> {
+    match (value, segment) {
+        (JsonData::Object(map), PathSegment::Key(key)) => Some((Step::Key(key), map.get(key)?)),
+        (JsonData::Array(items), PathSegment::Index(index)) => {
+            Some((Step::Index(*index), items.get(*index)?))
+        }
+        _ => None,
+    }
+}
+
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let len = i64::try_from(len).ok()?;
+    let index = if index < 0 { len + index } else { index };
+    (0..len).contains(&index).then_some(index as usize)
+}
+
+/// Slice bounds per RFC 9535 §2.3.4.2.2: `(lower, upper)` to iterate
+/// `lower..upper` for a positive step, or `upper` down to (excluding)
+/// `lower` for a negative one.
+fn slice_bounds(start: Option<i64>, end: Option<i64>, step: i64, len: usize) -> (i64, i64) {
+    let len = i64::try_from(len).unwrap_or(i64::MAX);
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        (lower, upper)
+    } else {
+        let upper = start.map_or(len - 1, |s| normalize(s).clamp(-1, len - 1));
+        let lower = end.map_or(-1, |e| normalize(e).clamp(-1, len - 1));
+        (lower, upper)
+    }
+}
+
+impl Selector {
+    fn select<'a>(&self, node: &Node<'a>, root: &'a JsonData, out: &mut Vec<Node<'a>>) {
+        match (self, node.value) {
+            (Self::Name(name), JsonData::Object(map)) => {
+                if let Some((key, value)) = map.get_key_value(name) {
+                    out.push(child_node(node, Step::Key(key), value));
+                }
+            }
+            (Self::Wildcard, _) => out.extend(children_of(node)),
+            (Self::Index(index), JsonData::Array(items)) => {
+                if let Some(index) = normalize_index(*index, items.len()) {
+                    out.push(child_node(node, Step::Index(index), &items[index]));
+                }
+            }
+            (Self::Slice { start, end, step }, JsonData::Array(items)) => {
+                let (lower, upper) = slice_bounds(*start, *end, *step, items.len());
+                let mut push = |i: i64| {
+                    let i = i as usize;
+                    out.push(child_node(node, Step::Index(i), &items[i]));
+                };
+                if *step > 0 {
+                    let mut i = lower;
+                    while i < upper {
+                        push(i);
+                        i += step;
+                    }
+                } else if *step < 0 {
+                    let mut i = upper;
+                    while lower < i {
+                        push(i);
+                        i += step;
+                    }
+                }
+            }
+            (Self::Filter(filter), _) => out.extend(
+                children_of(node)
+                    .into_iter()
+                    .filter(|child| filter.test(child.value, root)),
+            ),
+            _ => {}
+        }
+    }
+
+    /// Whether this selector, applied to `parent`, selects the child reached
+    /// by `step`.
+    fn selects_child(
+        &self,
+        parent: &JsonData,
+        step: Step<'_>,
+        child: &JsonData,
+        root: &JsonData,
+    ) -> bool {
+        match (self, step, parent) {
+            (Self::Name(name), Step::Key(key), JsonData::Object(_)) => name == key,
+            (Self::Wildcard, _, _) => true,
+            (Self::Index(index), Step::Index(at), JsonData::Array(items)) => {
+                normalize_index(*index, items.len()) == Some(at)
+            }
+            (Self::Slice { start, end, step }, Step::Index(at), JsonData::Array(items)) => {
+                let (lower, upper) = slice_bounds(*start, *end, *step, items.len());
+                let Ok(at) = i64::try_from(at) else {
+                    return false;
+                };
+                if *step > 0 {
+                    lower <= at && at < upper && (at - lower) % step == 0
+                } else if *step < 0 {
+                    lower < at && at <= upper && (upper - at) % -step == 0
+                } else {
+                    false
+                }
+            }
+            (Self::Filter(filter), _, _) => filter.test(child, root),
+            _ => false,
+        }
+    }
+}
+
+impl Filter {
+    fn test(&self, current: &JsonData, root: &JsonData) -> bool {
+        match self {
+            Self::Or(terms) => terms.iter().any(|term| term.test(current, root)),
+            Self::And(terms) => terms.iter().all(|term| term.test(current, root)),
+            Self::Not(inner) => !inner.test(current, root),
+            Self::Exists(query) => !query.evaluate(current, root).is_empty(),
+            Self::Compare(left, op, right) => {
+                let left = left.value(current, root);
+                let right = right.value(current, root);
+                compare(left, *op, right)
+            }
+        }
+    }
+}
+
+impl EmbeddedQuery {
+    fn evaluate<'a>(&self, current: &'a JsonData, root: &'a JsonData) -> Vec<Node<'a>> {
+        let start = Node {
+            steps: Vec::new(),
+            value: if self.relative { current } else { root },
+        };
+        select_segments(&self.segments, vec![start], root)
+    }
+
+    /// Singular query: only single name or index selectors, no descendants.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            !segment.descendant
+                && matches!(
+                    segment.selectors.as_slice(),
+                    [Selector::Name(_) | Selector::Index(_)]
+                )
+        })
+    }
+}
+
+impl Comparable {
+    /// The compared value, or `None` for an empty node list ("Nothing").
+    fn value<'a>(&'a self, current: &'a JsonData, root: &'a JsonData) -> Option<&'a JsonData> {
+        match self {
+            Self::Literal(value) => Some(value),
+            Self::Query(query) => match query.evaluate(current, root).as_slice() {
+                [node] => Some(node.value),
+                _ => None,
+            },
+        }
+    }
+}
+
+fn compare(left: Option<&JsonData>, op: CompareOp, right: Option<&JsonData>) -> bool {
+    match op {
+        CompareOp::Eq => values_equal(left, right),
+        CompareOp::Ne => !values_equal(left, right),
+        CompareOp::Lt => less_than(left, right),
+        CompareOp::Le => less_than(left, right) || values_equal(left, right),
+        CompareOp::Gt => less_than(right, left),
+        CompareOp::Ge => less_than(right, left) || values_equal(left, right),
+    }
+}
+
+fn as_number(value: &JsonData) -> Option<f64> {
+    match value {
+        JsonData::Integer(n) => Some(*n as f64),
+        JsonData::Float(n) => Some(*n),
+        _ => None,
+    }
+}
+
+fn values_equal(left: Option<&JsonData>, right: Option<&JsonData>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => json_equal(left, right),
+        _ => false,
+    }
+}
+
+fn json_equal(left: &JsonData, right: &JsonData) -> bool {
+    match (left, right) {
+        (JsonData::Integer(a), JsonData::Integer(b)) => a == b,
+        (JsonData::Array(a), JsonData::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_equal(a, b))
+        }
+        (JsonData::Object(a), JsonData::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| json_equal(a, b)))
+        }
+        _ => match (as_number(left), as_number(right)) {
+            (Some(a), Some(b)) => a == b,
+            _ => left == right,
+        },
+    }
+}
+
+fn less_than(left: Option<&JsonData>, right: Option<&JsonData>) -> bool {
+    match (left, right) {
+        (Some(JsonData::Integer(a)), Some(JsonData::Integer(b))) => a < b,
+        (Some(JsonData::String(a)), Some(JsonData::String(b))) => a < b,
+        (Some(left), Some(right)) => match (as_number(left), as_number(right)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b) == Some(Ordering::Less),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+struct Parser<'s> {
+    source: &'s str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn new(source: &'s str) -> Self {
+        Self {
+            source,
+            chars: source.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn error(&self, message: impl fmt::Display) -> DomainError {
+        DomainError::InvalidPath(format!(
+            "Invalid JSONPath query '{}': {message} at position {}",
+            self.source, self.pos
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> DomainResult<()> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(format_args!("expected '{expected}'")))
+        }
+    }
+
+    fn eat_str(&mut self, expected: &str) -> bool {
+        let matches = expected
+            .chars()
+            .enumerate()
+            .all(|(offset, c)| self.peek_at(offset) == Some(c));
+        if matches {
+            self.pos += expected.chars().count();
+        }
+        matches
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_query(mut self) -> DomainResult<Vec<Segment>> {
+        self.expect('$')?;
+        let segments = self.parse_segments()?;
+        if self.pos < self.chars.len() {
+            return Err(self.error("unexpected trailing input"));
+        }
+        Ok(segments)
+    }
+
+    fn parse_segments(&mut self) -> DomainResult<Vec<Segment>> {
+        let mut segments = Vec::new();
+        loop {
+            let before = self.pos;
+            self.skip_blank();
+            match self.peek() {
+                Some('.' | '[') => segments.push(self.parse_segment()?),
+                _ => {
+                    self.pos = before;
+                    return Ok(segments);
+                }
+            }
+        }
+    }
+
+    fn parse_segment(&mut self) -> DomainResult<Segment> {
+        if self.eat_str("..") {
+            let selectors = match self.peek() {
+                Some('[') => self.parse_bracketed()?,
+                Some('*') => {
+                    self.pos += 1;
+                    vec![Selector::Wildcard]
+                }
+                _ => vec![Selector::Name(self.parse_member_name()?)],
+            };
+            return Ok(Segment {
+                descendant: true,
+                selectors,
+            });
+        }
+        let selectors = if self.eat('.') {
+            if self.eat('*') {
+                vec![Selector::Wildcard]
+            } else {
+                vec![Selector::Name(self.parse_member_name()?)]
+            }
+        } else {
+            self.parse_bracketed()?
+        };
+        Ok(Segment {
+            descendant: false,
+            selectors,
+        })
+    }
+
+    fn parse_member_name(&mut self) -> DomainResult<String> {
+        let is_first = |c: char| c.is_ascii_alphabetic() || c == '_' || !c.is_ascii();
+        match self.peek() {
+            Some(c) if is_first(c) => {}
+            _ => return Err(self.error("expected a member name")),
+        }
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if !(is_first(c) || c.is_ascii_digit()) {
+                break;
+            }
+            name.push(c);
+            self.pos += 1;
+        }
+        Ok(name)
+    }
+
+    fn parse_bracketed(&mut self) -> DomainResult<Vec<Selector>> {
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_blank();
+            selectors.push(self.parse_selector()?);
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(selectors);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn parse_selector(&mut self) -> DomainResult<Selector> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.parse_string()?)),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.pos += 1;
+                self.skip_blank();
+                Ok(Selector::Filter(Box::new(self.parse_logical_or()?)))
+            }
+            _ => self.parse_index_or_slice(),
+        }
+    }
+
+    fn parse_index_or_slice(&mut self) -> DomainResult<Selector> {
+        let start = self.parse_optional_int()?;
+        self.skip_blank();
+        if !self.eat(':') {
+            return start
+                .map(Selector::Index)
+                .ok_or_else(|| self.error("expected a selector"));
+        }
+        self.skip_blank();
+        let end = self.parse_optional_int()?;
+        self.skip_blank();
+        let step = if self.eat(':') {
+            self.skip_blank();
+            self.parse_optional_int()?
+        } else {
+            None
+        };
+        Ok(Selector::Slice {
+            start,
+            end,
+            step: step.unwrap_or(1),
+        })
+    }
+
+    fn parse_optional_int(&mut self) -> DomainResult<Option<i64>> {
+        match self.peek() {
+            Some('-') | Some('0'..='9') => self.parse_int().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_int(&mut self) -> DomainResult<i64> {
+        let start = self.pos;
+        let negative = self.eat('-');
+        let digits_start = self.pos;
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[digits_start..self.pos].iter().collect();
+        if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0')) {
+            self.pos = start;
+            return Err(self.error("invalid integer"));
+        }
+        if negative && digits == "0" {
+            self.pos = start;
+            return Err(self.error("'-0' is not a valid integer"));
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse().map_err(|_| {
+            self.pos = start;
+            self.error("integer out of range")
+        })
+    }
+
+    fn parse_string(&mut self) -> DomainResult<String> {
+        let quote = self.peek().ok_or_else(|| self.error("expected a string"))?;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let c = self
+                .peek()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += 1;
+            match c {
+                c if c == quote => return Ok(out),
+                '\\' => {
+                    let escaped = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated escape"))?;
+                    self.pos += 1;
+                    match escaped {
+                        'b' => out.push('\u{8}'),
+                        'f' => out.push('\u{c}'),
+                        'n' => out.push('\n'),
+                        'r' => out.push('\r'),
+                        't' => out.push('\t'),
+                        '/' => out.push('/'),
+                        '\\' => out.push('\\'),
+                        '\'' | '"' if escaped == quote => out.push(escaped),
+                        'u' => out.push(self.parse_unicode_escape()?),
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                c if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> DomainResult<u32> {
+        let hex: String = (0..4).filter_map(|offset| self.peek_at(offset)).collect();
+        let value = (hex.len() == 4)
+            .then(|| u32::from_str_radix(&hex, 16).ok())
+            .flatten()
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(value)
+    }
+
+    fn parse_unicode_escape(&mut self) -> DomainResult<char> {
+        let high = self.parse_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.eat_str("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            let low = self.parse_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn parse_logical_or(&mut self) -> DomainResult<Filter> {
+        let mut terms = vec![self.parse_logical_and()?];
+        loop {
+            let before = self.pos;
+            self.skip_blank();
+            if !self.eat_str("||") {
+                self.pos = before;
+                break;
+            }
+            self.skip_blank();
+            terms.push(self.parse_logical_and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Filter::Or(terms)
+        })
+    }
+
+    fn parse_logical_and(&mut self) -> DomainResult<Filter> {
+        let mut terms = vec![self.parse_basic()?];
+        loop {
+            let before = self.pos;
+            self.skip_blank();
+            if !self.eat_str("&&") {
+                self.pos = before;
+                break;
+            }
+            self.skip_blank();
+            terms.push(self.parse_basic()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Filter::And(terms)
+        })
+    }
+
+    fn parse_basic(&mut self) -> DomainResult<Filter> {
+        if self.eat('!') {
+            self.skip_blank();
+            if self.peek() == Some('(') {
+                return Ok(Filter::Not(Box::new(self.parse_parenthesized()?)));
+            }
+            let query = self.parse_embedded_query()?;
+            return Ok(Filter::Not(Box::new(Filter::Exists(query))));
+        }
+        if self.peek() == Some('(') {
+            return self.parse_parenthesized();
+        }
+
+        let left = self.parse_comparable()?;
+        let before = self.pos;
+        self.skip_blank();
+        let Some(op) = self.parse_compare_op() else {
+            self.pos = before;
+            return match left {
+                Comparable::Query(query) => Ok(Filter::Exists(query)),
+                Comparable::Literal(_) => Err(self.error("a literal is not a valid test")),
+            };
+        };
+        self.skip_blank();
+        let right = self.parse_comparable()?;
+        for side in [&left, &right] {
+            if let Comparable::Query(query) = side
+                && !query.is_singular()
+            {
+                return Err(self.error("comparisons require singular queries"));
+            }
+        }
+        Ok(Filter::Compare(left, op, right))
+    }
+
+    fn parse_parenthesized(&mut self) -> DomainResult<Filter> {
+        self.expect('(')?;
+        self.skip_blank();
+        let inner = self.parse_logical_or()?;
+        self.skip_blank();
+        self.expect(')')?;
+        Ok(inner)
+    }
+
+    fn parse_compare_op(&mut self) -> Option<CompareOp> {
+        for (text, op) in [
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ] {
+            if self.eat_str(text) {
+                return Some(op);
+            }
+        }
+        None
+    }
+
+    fn parse_embedded_query(&mut self) -> DomainResult<EmbeddedQuery> {
+        let relative = match self.peek() {
+            Some('@') => true,
+            Some('$') => false,
+            _ => return Err(self.error("expected '@' or '$'")),
+        };
+        self.pos += 1;
+        Ok(EmbeddedQuery {
+            relative,
+            segments: self.parse_segments()?,
+        })
+    }
+
+    fn parse_comparable(&mut self) -> DomainResult<Comparable> {
+        match self.peek() {
+            Some('@' | '$') => {
+                let query = self.parse_embedded_query()?;
+                Ok(Comparable::Query(query))
+            }
+            Some('\'' | '"') => Ok(Comparable::Literal(JsonData::String(self.parse_string()?))),
+            Some('-' | '0'..='9') => Ok(Comparable::Literal(self.parse_number()?)),
+            _ => {
+                for (text, value) in [
+                    ("true", JsonData::Bool(true)),
+                    ("false", JsonData::Bool(false)),
+                    ("null", JsonData::Null),
+                ] {
+                    if self.eat_str(text) {
+                        return Ok(Comparable::Literal(value));
+                    }
+                }
+                if self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    return Err(self.error("function extensions are not supported"));
+                }
+                Err(self.error("expected a comparable"))
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> DomainResult<JsonData> {
+        let start = self.pos;
+        self.parse_int()?;
+        let mut is_float = false;
+        if self.peek() == Some('.') && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+            is_float = true;
+            self.pos += 1;
+            while matches!(self.peek(), Some('0'..='9')) {
+                self.pos += 1;
+            }
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            is_float = true;
+            self.pos += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.pos += 1;
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error("invalid exponent"));
+            }
+            while matches!(self.peek(), Some('0'..='9')) {
+                self.pos += 1;
+            }
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if is_float {
+            text.parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(JsonData::Float)
+                .ok_or_else(|| self.error("invalid number"))
+        } else {
+            text.parse::<i64>()
+                .map(JsonData::Integer)
+                .map_err(|_| self.error("invalid number"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc() -> JsonData {
+        json!({
+            "user": {
+                "name": "Ann",
+                "posts": [
+                    {"title": "first", "public": true, "score": 3},
+                    {"title": "second", "public": false, "score": 12},
+                    {"title": "third", "score": 7, "tags": ["x"]}
+                ]
+            },
+            "title": "top"
+        })
+        .into()
+    }
+
+    fn paths(query: &str) -> Vec<String> {
+        JsonPathQuery::new(query)
+            .unwrap()
+            .select_paths(&doc())
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_names_wildcards_and_indices() {
+        assert_eq!(paths("$"), vec!["$"]);
+        assert_eq!(paths("$.user.name"), vec!["$.user.name"]);
+        assert_eq!(paths("$['user'][\"name\"]"), vec!["$.user.name"]);
+        assert_eq!(
+            paths("$.user.posts[*].title"),
+            vec![
+                "$.user.posts[0].title",
+                "$.user.posts[1].title",
+                "$.user.posts[2].title"
+            ]
+        );
+        assert_eq!(
+            paths("$.user.posts[-1].title"),
+            vec!["$.user.posts[2].title"]
+        );
+        assert_eq!(paths("$.user.posts[5]"), Vec::<String>::new());
+        assert_eq!(paths("$.user.*"), vec!["$.user.name", "$.user.posts"]);
+        assert_eq!(
+            paths("$.user.posts[2,0].score"),
+            vec!["$.user.posts[2].score", "$.user.posts[0].score"]
+        );
+    }
+
+    #[test]
+    fn test_slices() {
+        assert_eq!(
+            paths("$.user.posts[1:]"),
+            vec!["$.user.posts[1]", "$.user.posts[2]"]
+        );
+        assert_eq!(
+            paths("$.user.posts[::-1]"),
+            vec!["$.user.posts[2]", "$.user.posts[1]", "$.user.posts[0]"]
+        );
+        assert_eq!(
+            paths("$.user.posts[0:3:2]"),
+            vec!["$.user.posts[0]", "$.user.posts[2]"]
+        );
+        assert_eq!(paths("$.user.posts[:-2]"), vec!["$.user.posts[0]"]);
+        assert_eq!(paths("$.user.posts[::0]"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_descendants() {
+        assert_eq!(
+            paths("$..title"),
+            vec![
+                "$.title",
+                "$.user.posts[0].title",
+                "$.user.posts[1].title",
+                "$.user.posts[2].title"
+            ]
+        );
+        assert_eq!(paths("$..tags[0]"), vec!["$.user.posts[2].tags[0]"]);
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(
+            paths("$.user.posts[?@.public].title"),
+            vec!["$.user.posts[0].title", "$.user.posts[1].title"]
+        );
+        assert_eq!(
+            paths("$.user.posts[?(@.public == true)].title"),
+            vec!["$.user.posts[0].title"]
+        );
+        assert_eq!(
+            paths("$.user.posts[?@.score > 5 && !@.public].title"),
+            vec!["$.user.posts[2].title"]
+        );
+        assert_eq!(
+            paths("$.user.posts[?@.score >= 12 || @.title == 'first']"),
+            vec!["$.user.posts[0]", "$.user.posts[1]"]
+        );
+        assert_eq!(
+            paths("$.user.posts[?@.score < $.user.posts[2].score].title"),
+            vec!["$.user.posts[0].title"]
+        );
+        assert_eq!(
+            paths("$.user.posts[?@.missing == @.also_missing].score").len(),
+            3
+        );
+        assert_eq!(paths("$.user.posts[?@.score == 7.0].title").len(), 1);
+    }
+
+    #[test]
+    fn test_matches_agrees_with_select() {
+        let doc = doc();
+        for query in [
+            "$.user.posts[*].title",
+            "$.user.posts[-1]",
+            "$.user.posts[::-2]",
+            "$..title",
+            "$..*",
+            "$.user.posts[?@.public].title",
+            "$",
+        ] {
+            let query = JsonPathQuery::new(query).unwrap();
+            let selected = query.select_paths(&doc);
+            let mut candidates = JsonPathQuery::new("$..*").unwrap().select_paths(&doc);
+            candidates.push(JsonPath::root());
+            for path in candidates {
+                assert_eq!(
+                    query.matches(&doc, &path),
+                    selected.contains(&path),
+                    "{query} vs {path}"
+                );
+            }
+        }
+        let query = JsonPathQuery::new("$.user.name").unwrap();
+        assert!(!query.matches(&doc, &JsonPath::new("$.user.missing").unwrap()));
+    }
+
+    #[test]
+    fn test_invalid_queries_are_rejected() {
+        for query in [
+            "",
+            "user",
+            "$.",
+            "$[",
+            "$[01]",
+            "$[-0]",
+            "$.1abc",
+            "$['unterminated]",
+            "$[?@.a == ]",
+            "$[?length(@) > 1]",
+            "$[?1]",
+            "$.a b",
+            "$[?@.a[*] == 1]",
+        ] {
+            assert!(JsonPathQuery::new(query).is_err(), "{query} should fail");
+        }
+    }
+
+    #[test]
+    fn test_from_path_selects_exactly_that_node() {
+        let doc: JsonData = json!({"it's": {"a\\b\n": [1, 2]}}).into();
+        let path = JsonPath::from_segments([
+            PathSegment::Key("it's".to_string()),
+            PathSegment::Key("a\\b\n".to_string()),
+            PathSegment::Index(1),
+        ])
+        .unwrap();
+
+        let query = JsonPathQuery::from(&path);
+        assert_eq!(query.as_str(), r"$['it\'s']['a\\b\n'][1]");
+        assert_eq!(query.select_paths(&doc), vec![path.clone()]);
+        assert!(query.matches(&doc, &path));
+        assert_eq!(
+            JsonPathQuery::new(query.as_str())
+                .unwrap()
+                .select_paths(&doc),
+            vec![path]
+        );
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let query = JsonPathQuery::new("$.user.posts[*].title").unwrap();
+        let json = serde_json::to_string(&query).unwrap();
+        assert_eq!(json, "\"$.user.posts[*].title\"");
+        let back: JsonPathQuery = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, query);
+        assert!(serde_json::from_str::<JsonPathQuery>("\"$[\"").is_err());
+    }
+}
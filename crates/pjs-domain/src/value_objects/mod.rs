@@ -4,17 +4,23 @@
 //! with no conceptual identity, only defined by their attributes.
 
 mod backpressure;
+mod content_digest;
 mod depth_guard;
 mod id;
 mod json_data;
 mod json_path;
+mod json_path_query;
 mod priority;
 mod schema;
 
 pub use backpressure::BackpressureSignal;
+pub use content_digest::{
+    ContentDigest, DigestAlgorithm, IntegrityError, IntegrityKey, canonical_json, verify_completion,
+};
 pub use depth_guard::{DepthGuard, enter_deserialize_depth};
 pub use id::{Id, IdMarker, SessionId, SessionMarker, StreamId, StreamMarker};
 pub use json_data::{JsonData, MAX_DESERIALIZE_DEPTH};
 pub use json_path::{JsonPath, PathSegment};
+pub use json_path_query::JsonPathQuery;
 pub use priority::Priority;
 pub use schema::{Schema, SchemaId, SchemaType, SchemaValidationError, SchemaValidationResult};
@@ -40,6 +40,9 @@ impl Priority {
     /// Background priority - for analytics, logs, etc.
     pub const BACKGROUND: Self = Self::new_unchecked(10);
 
+    /// Lowest priority there is - as a threshold, lets everything through
+    pub const MIN: Self = Self::new_unchecked(1);
+
     /// Create priority with validation
     pub fn new(value: u8) -> DomainResult<Self> {
         NonZeroU8::new(value)
@@ -213,6 +216,7 @@ mod tests {
         assert_eq!(Priority::MEDIUM.value(), 50);
         assert_eq!(Priority::LOW.value(), 25);
         assert_eq!(Priority::BACKGROUND.value(), 10);
+        assert_eq!(Priority::MIN.value(), 1);
     }
 
     #[test]
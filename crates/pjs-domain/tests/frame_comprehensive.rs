@@ -4,9 +4,13 @@
 //! frame operations, validation, metadata, and edge cases.
 
 use pjson_rs_domain::entities::frame::{Frame, FramePatch, FrameType, PatchOperation};
-use pjson_rs_domain::value_objects::{JsonData, JsonPath, Priority, StreamId};
+use pjson_rs_domain::value_objects::{ContentDigest, JsonData, JsonPath, Priority, StreamId};
 use std::collections::HashMap;
 
+fn digest_of(document: serde_json::Value) -> ContentDigest {
+    ContentDigest::compute(&document, None)
+}
+
 // ============================================================================
 // FrameType Tests
 // ============================================================================
@@ -194,14 +198,17 @@ fn test_patch_frame_with_different_priorities() {
 #[test]
 fn test_complete_frame_with_checksum() {
     let stream_id = StreamId::new();
-    let frame = Frame::complete(stream_id, 10, Some("abc123def456".to_string()));
+    let digest = digest_of(serde_json::json!({"id": 1}));
+    let frame = Frame::complete(stream_id, 10, Some(digest));
 
     assert_eq!(frame.frame_type(), &FrameType::Complete);
     assert_eq!(frame.priority(), Priority::CRITICAL);
     assert_eq!(frame.sequence(), 10);
 
     if let JsonData::Object(obj) = frame.payload() {
-        assert_eq!(obj.get("checksum").unwrap().as_str(), Some("abc123def456"));
+        let checksum = obj.get("checksum").unwrap().as_str().unwrap();
+        assert!(checksum.starts_with("sha256:"));
+        assert_eq!(checksum.parse::<ContentDigest>().unwrap(), digest);
     } else {
         panic!("Expected object payload");
     }
@@ -225,7 +232,7 @@ fn test_complete_frame_without_checksum() {
 #[test]
 fn test_complete_frame_validate_success() {
     let stream_id = StreamId::new();
-    let frame = Frame::complete(stream_id, 10, Some("checksum".to_string()));
+    let frame = Frame::complete(stream_id, 10, Some(digest_of(serde_json::json!(null))));
     assert!(frame.validate().is_ok());
 }
 
@@ -663,15 +670,14 @@ fn test_frame_sequence_large() {
 }
 
 #[test]
-fn test_complete_frame_empty_checksum() {
+fn test_complete_frame_signed_checksum() {
     let stream_id = StreamId::new();
-    let frame = Frame::complete(stream_id, 1, Some("".to_string()));
+    let key = pjson_rs_domain::value_objects::IntegrityKey::new([7u8; 32]).unwrap();
+    let digest = ContentDigest::compute(&serde_json::json!({}), Some(&key));
+    let frame = Frame::complete(stream_id, 1, Some(digest));
 
-    if let JsonData::Object(obj) = frame.payload() {
-        assert_eq!(obj.get("checksum").unwrap().as_str(), Some(""));
-    } else {
-        panic!("Expected object payload");
-    }
+    let checksum = frame.payload().get("checksum").and_then(|v| v.as_str());
+    assert!(checksum.unwrap().starts_with("hmac-sha256:"));
 }
 
 #[test]
@@ -723,13 +729,14 @@ fn test_frame_roundtrip_skeleton() {
 #[test]
 fn test_frame_roundtrip_complete_with_checksum() {
     let stream_id = StreamId::new();
-    let frame = Frame::complete(stream_id, 5, Some("abc123".to_string()));
+    let digest = digest_of(serde_json::json!([1, 2, 3]));
+    let frame = Frame::complete(stream_id, 5, Some(digest));
     let rt = roundtrip(&frame);
 
     assert_eq!(frame, rt);
     assert_eq!(
         rt.payload().get("checksum").and_then(|v| v.as_str()),
-        Some("abc123")
+        Some(digest.to_string().as_str())
     );
 }
 
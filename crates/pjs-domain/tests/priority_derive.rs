@@ -0,0 +1,141 @@
+//! Tests for `#[derive(PjsPriority)]` and the configs it plugs into
+
+use pjson_rs_derive::PjsPriority;
+use pjson_rs_domain::{
+    PjsPriority as _,
+    entities::{Stream, stream::StreamConfig},
+    services::{PriorityHeuristicConfig, compute_priority_in},
+    value_objects::{JsonData, JsonPath, JsonPathQuery, Priority, SessionId},
+};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Serialize, PjsPriority)]
+struct Author {
+    #[pjs(priority = "HIGH")]
+    name: String,
+    bio: String,
+}
+
+#[derive(Serialize, PjsPriority)]
+struct Paging {
+    #[pjs(priority = "low")]
+    cursor: String,
+}
+
+#[derive(Serialize, PjsPriority)]
+#[serde(rename_all = "camelCase")]
+struct Article {
+    #[pjs(priority = "critical")]
+    article_id: u64,
+    #[serde(rename = "title")]
+    #[pjs(priority = 90)]
+    headline: String,
+    #[pjs(nested)]
+    author: Option<Author>,
+    #[serde(flatten)]
+    #[pjs(nested)]
+    paging: Paging,
+    #[pjs(priority = "background")]
+    related_links: Vec<String>,
+    #[serde(skip)]
+    #[allow(dead_code)]
+    cache_key: u64,
+}
+
+fn article() -> Article {
+    Article {
+        article_id: 7,
+        headline: "Launch".to_string(),
+        author: Some(Author {
+            name: "Ada".to_string(),
+            bio: "…".to_string(),
+        }),
+        paging: Paging {
+            cursor: "c1".to_string(),
+        },
+        related_links: vec!["/a".to_string()],
+        cache_key: 0,
+    }
+}
+
+fn path(path: &str) -> JsonPath {
+    JsonPath::new(path).unwrap()
+}
+
+#[test]
+fn test_priority_map_follows_serialized_names() {
+    let map = Article::priority_map();
+
+    assert_eq!(map.len(), 5);
+    assert_eq!(map[&path("$.articleId")], Priority::CRITICAL);
+    assert_eq!(map[&path("$.title")], Priority::new(90).unwrap());
+    assert_eq!(map[&path("$.author.name")], Priority::HIGH);
+    assert_eq!(map[&path("$.cursor")], Priority::LOW);
+    assert_eq!(map[&path("$.relatedLinks")], Priority::BACKGROUND);
+
+    // Every declared path exists in the serialized document.
+    let document: JsonData = serde_json::to_value(article()).unwrap().into();
+    for declared in map.keys() {
+        let query = JsonPathQuery::from(declared);
+        assert_eq!(query.select_paths(&document), vec![declared.clone()]);
+    }
+}
+
+#[test]
+fn test_generic_structs_and_raw_identifiers() {
+    #[derive(Serialize, PjsPriority)]
+    struct Envelope<T> {
+        #[pjs(priority = "critical")]
+        r#type: String,
+        #[pjs(nested)]
+        data: T,
+    }
+
+    let map = Envelope::<Author>::priority_map();
+    assert_eq!(map[&path("$.type")], Priority::CRITICAL);
+    assert_eq!(map[&path("$.data.name")], Priority::HIGH);
+}
+
+#[test]
+fn test_declared_priorities_drive_stream_frames() {
+    let document: JsonData = serde_json::to_value(article()).unwrap().into();
+    let config = StreamConfig::default().with_priorities_of::<Article>();
+    let mut stream = Stream::new(SessionId::new(), document, config);
+    stream.start_streaming().unwrap();
+
+    let patches = stream
+        .extract_prioritized_patches(Priority::BACKGROUND)
+        .unwrap();
+    let priority_of = |target: &str| {
+        patches
+            .iter()
+            .find(|(patch, _)| patch.path == path(target))
+            .map(|(_, priority)| *priority)
+    };
+
+    assert_eq!(priority_of("$.title"), Some(Priority::new(90).unwrap()));
+    // The heuristics would rank this short top-level array above `cursor`.
+    assert_eq!(priority_of("$.relatedLinks"), Some(Priority::BACKGROUND));
+    assert_eq!(priority_of("$.cursor"), Some(Priority::LOW));
+    assert_eq!(patches.last().unwrap().0.path, path("$.relatedLinks"));
+}
+
+#[test]
+fn test_declared_priorities_become_path_rules() {
+    let mut config = PriorityHeuristicConfig::default();
+    config.add_priorities_of::<Article>();
+    assert_eq!(config.path_rules.len(), 5);
+
+    let document: JsonData = serde_json::to_value(article()).unwrap().into();
+    let bio = JsonData::from(json!("…"));
+    // Undeclared fields keep the heuristics.
+    assert_eq!(
+        compute_priority_in(&config, &document, &path("$.author.bio"), &bio),
+        Priority::MEDIUM.increase_by(10).increase_by(5)
+    );
+    assert_eq!(
+        compute_priority_in(&config, &document, &path("$.cursor"), &bio),
+        Priority::LOW
+    );
+}
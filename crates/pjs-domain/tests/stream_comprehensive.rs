@@ -14,7 +14,7 @@ use pjson_rs_domain::{
         frame::FrameType,
         stream::{StreamConfig, StreamState},
     },
-    value_objects::{JsonData, Priority, SessionId},
+    value_objects::{ContentDigest, JsonData, Priority, SessionId},
 };
 use std::collections::HashMap;
 
@@ -42,6 +42,8 @@ mod stream_lifecycle_tests {
             max_frames_per_batch: 20,
             enable_compression: false,
             priority_rules: HashMap::new(),
+            default_array_chunking: None,
+            array_chunking: HashMap::new(),
         };
 
         let stream = Stream::new(session_id, source_data, config.clone());
@@ -199,9 +201,9 @@ mod frame_creation_tests {
     #[test]
     fn test_create_patch_frames() {
         let session_id = SessionId::new();
-        // Root-level string at path `$` clears the MEDIUM threshold (depth=0
-        // shallow boost + short-string boost), so one patch frame is emitted.
-        let source_data = JsonData::String("test".to_string());
+        // A short `title` member is HIGH priority, clearing the MEDIUM
+        // threshold, so one patch frame is emitted.
+        let source_data: JsonData = serde_json::json!({"title": "test"}).into();
         let mut stream = Stream::new(session_id, source_data, StreamConfig::default());
 
         assert!(stream.start_streaming().is_ok());
@@ -209,7 +211,23 @@ mod frame_creation_tests {
             .create_patch_frames(Priority::MEDIUM, 5)
             .expect("should create patches");
 
-        assert_eq!(frames.len(), 1, "root-level primitive yields one patch");
+        assert_eq!(frames.len(), 1, "one member yields one patch");
+    }
+
+    #[test]
+    fn test_scalar_root_is_sent_in_the_skeleton() {
+        let source_data = JsonData::String("test".to_string());
+        let mut stream = Stream::new(
+            SessionId::new(),
+            source_data.clone(),
+            StreamConfig::default(),
+        );
+        stream.start_streaming().unwrap();
+
+        let skeleton = stream.create_skeleton_frame().unwrap();
+        assert_eq!(skeleton.payload(), &source_data);
+        let frames = stream.create_patch_frames(Priority::MIN, 5).unwrap();
+        assert!(frames.is_empty(), "nothing is left to patch in");
     }
 
     #[test]
@@ -222,6 +240,76 @@ mod frame_creation_tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_next_patch_frames_resumes_until_the_document_is_sent() {
+        let source_data: JsonData =
+            serde_json::json!({"id": 1, "name": "Ada", "meta": {"tags": ["x"]}}).into();
+        let mut stream = Stream::new(SessionId::new(), source_data, StreamConfig::default());
+        stream.start_streaming().unwrap();
+
+        let first = stream.next_patch_frames(Priority::MIN, 1).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].priority(), Priority::CRITICAL);
+
+        let rest = stream.next_patch_frames(Priority::MIN, 10).unwrap();
+        let sent: usize = first
+            .iter()
+            .chain(&rest)
+            .map(|frame| {
+                frame
+                    .payload()
+                    .get("patches")
+                    .unwrap()
+                    .as_array()
+                    .unwrap()
+                    .len()
+            })
+            .sum();
+        assert_eq!(
+            sent,
+            stream
+                .extract_prioritized_patches(Priority::MIN)
+                .unwrap()
+                .len()
+        );
+        assert!(
+            stream
+                .next_patch_frames(Priority::MIN, 10)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(rest.windows(2).all(|w| w[0].sequence() < w[1].sequence()));
+    }
+
+    #[test]
+    fn test_next_patch_frames_restarts_on_new_threshold_or_version() {
+        let source_data: JsonData = serde_json::json!({"id": 1, "name": "Ada"}).into();
+        let mut stream = Stream::new(SessionId::new(), source_data, StreamConfig::default());
+        stream.start_streaming().unwrap();
+
+        assert_eq!(
+            stream.next_patch_frames(Priority::MIN, 10).unwrap().len(),
+            2
+        );
+        assert!(
+            stream
+                .next_patch_frames(Priority::MIN, 10)
+                .unwrap()
+                .is_empty()
+        );
+
+        let critical = stream.next_patch_frames(Priority::CRITICAL, 10).unwrap();
+        assert_eq!(critical.len(), 1, "a new threshold starts over");
+
+        stream
+            .update_source(serde_json::json!({"id": 2, "name": "Ada"}).into())
+            .unwrap();
+        let frames = stream.next_patch_frames(Priority::CRITICAL, 10).unwrap();
+        assert_eq!(frames.len(), 1, "a new version starts over");
+        let payload = serde_json::to_string(frames[0].payload()).unwrap();
+        assert!(payload.contains(r#""value":2"#), "{payload}");
+    }
+
     /// Regression for #506: `finalize_patch_frame` is `pub` and must not let
     /// a caller mint a patch frame (advancing `next_sequence`/`stats`) on a
     /// stream that never entered `Streaming`.
@@ -247,7 +335,7 @@ mod frame_creation_tests {
 
         assert!(stream.start_streaming().is_ok());
         let frame = stream
-            .create_completion_frame(Some("checksum123".to_string()))
+            .create_completion_frame(Some(ContentDigest::compute(&serde_json::Value::Null, None)))
             .expect("should create completion frame");
 
         assert_eq!(frame.frame_type(), &FrameType::Complete);
@@ -0,0 +1,710 @@
+//! The `pjs-server` configuration document
+//!
+//! A single TOML or JSON file describes the whole deployment. The
+//! server-specific sections sit next to the library's own [`PjsConfig`]
+//! sections (`[parser]`, `[streaming]`, `[security]`, ...), which are
+//! flattened into the same document:
+//!
+//! ```toml
+//! [server]
+//! listen = "0.0.0.0:3000"
+//! allowed_origins = ["https://app.example.com"]
+//!
+//! [server.tls]
+//! cert_path = "/etc/pjs/tls/cert.pem"
+//! key_path = "/etc/pjs/tls/key.pem"
+//!
+//! [auth]
+//! api_keys = ["replace-me-with-a-long-random-key"]
+//!
+//! [limits]
+//! max_connections = 4096
+//! drain_timeout_secs = 20
+//!
+//! [security.network.rate_limiting]
+//! max_requests_per_window = 600
+//!
+//! [rate_limit]
+//! store = "redis"
+//! redis_url = "redis://cache:6379/0"
+//!
+//! [integrity]
+//! hmac_key = "at-least-32-bytes-of-shared-secret-material"
+//! ```
+//!
+//! Loading goes through [`ConfigLoader`], so every key can also be set from
+//! the environment with the usual `PJS_` prefix and `__` nesting separator
+//! (`PJS_SERVER__LISTEN=0.0.0.0:8080`, `PJS_AUTH__JWT__SECRET=...`), which is
+//! the recommended way to pass secrets. Unknown keys are rejected.
+
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use pjson_rs::{
+    ConfigError, ConfigLoader, LayeredConfig, PjsConfig, domain::value_objects::IntegrityKey,
+    infrastructure::http::ConnectionLimits,
+};
+use serde::{Deserialize, Serialize};
+
+/// Complete configuration of a `pjs-server` process.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Listener, TLS, CORS and endpoint settings (`[server]`)
+    pub server: ListenerConfig,
+    /// Authentication for every route except `/pjs/health` (`[auth]`)
+    pub auth: AuthConfig,
+    /// Connection-level limits enforced by the accept loop (`[limits]`)
+    pub limits: LimitsConfig,
+    /// Where and how request budgets are counted (`[rate_limit]`)
+    pub rate_limit: RateLimitSettings,
+    /// Signing of stream completion digests (`[integrity]`)
+    pub integrity: IntegritySettings,
+    /// Library configuration, flattened into the top level of the document
+    #[serde(flatten)]
+    pub pjs: PjsConfig,
+}
+
+impl ServerConfig {
+    /// Load a configuration from an optional file plus `PJS_`-prefixed
+    /// environment variables, on top of the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns any [`ConfigError`] raised while reading, merging or
+    /// validating the layers.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut loader = ConfigLoader::with_base(Self::default());
+        if let Some(path) = path {
+            loader = loader.file(path);
+        }
+        loader.env().load()
+    }
+
+    /// The [`ConnectionLimits`] described by `[limits]`.
+    ///
+    /// See [`LimitsConfig::max_connection_duration_secs`] for how the
+    /// connection-duration ceiling depends on whether the WebSocket endpoint
+    /// is enabled.
+    pub fn connection_limits(&self) -> ConnectionLimits {
+        let limits = &self.limits;
+        let non_zero_secs = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+
+        let mut connection_limits = ConnectionLimits::default();
+        connection_limits.header_read_timeout = non_zero_secs(limits.header_read_timeout_secs);
+        connection_limits.max_connection_duration = match limits.max_connection_duration_secs {
+            Some(secs) => non_zero_secs(secs),
+            None if self.server.websocket => None,
+            None => ConnectionLimits::default().max_connection_duration,
+        };
+        connection_limits.max_connections = limits.max_connections;
+        connection_limits.max_concurrent_streams =
+            (limits.max_concurrent_streams > 0).then_some(limits.max_concurrent_streams);
+        connection_limits.max_connections_per_ip =
+            (limits.max_connections_per_ip > 0).then_some(limits.max_connections_per_ip);
+        connection_limits.drain_timeout = Duration::from_secs(limits.drain_timeout_secs);
+        connection_limits
+    }
+}
+
+impl LayeredConfig for ServerConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        self.pjs.validate()?;
+
+        let tls = &self.server.tls;
+        if tls.cert_path.is_some() != tls.key_path.is_some() {
+            return Err(ConfigError::InconsistentBounds {
+                section: "server.tls",
+                message: "cert_path and key_path must be set together",
+            });
+        }
+
+        if self.server.dictionary.enabled && self.server.dictionary.target_size == 0 {
+            return Err(ConfigError::MustBePositive {
+                section: "server.dictionary",
+                field: "target_size",
+            });
+        }
+
+        let jwt = &self.auth.jwt;
+        if jwt.secret.is_some() && jwt.public_key_path.is_some() {
+            return Err(ConfigError::InconsistentBounds {
+                section: "auth.jwt",
+                message: "secret and public_key_path are mutually exclusive",
+            });
+        }
+        match (!self.auth.api_keys.is_empty(), jwt.is_configured()) {
+            (true, true) => {
+                return Err(ConfigError::InconsistentBounds {
+                    section: "auth",
+                    message: "api_keys and jwt are mutually exclusive",
+                });
+            }
+            (false, false) if !self.auth.allow_unauthenticated => {
+                return Err(ConfigError::InconsistentBounds {
+                    section: "auth",
+                    message: "no api_keys or jwt configured; set \
+                              auth.allow_unauthenticated = true to serve without authentication",
+                });
+            }
+            _ => {}
+        }
+
+        let rate_limit = &self.rate_limit;
+        if rate_limit.store == RateLimitStoreKind::Redis && rate_limit.redis_url.is_none() {
+            return Err(ConfigError::InconsistentBounds {
+                section: "rate_limit",
+                message: "store = \"redis\" requires redis_url",
+            });
+        }
+        #[cfg(not(feature = "redis"))]
+        if rate_limit.store == RateLimitStoreKind::Redis {
+            return Err(ConfigError::InconsistentBounds {
+                section: "rate_limit",
+                message: "store = \"redis\" requires pjs-server built with the `redis` feature",
+            });
+        }
+
+        if self
+            .integrity
+            .hmac_key
+            .as_ref()
+            .is_some_and(|key| key.len() < IntegrityKey::MIN_LEN)
+        {
+            return Err(ConfigError::InconsistentBounds {
+                section: "integrity",
+                message: "hmac_key must be at least 32 bytes",
+            });
+        }
+
+        if self.limits.max_connections == 0 {
+            return Err(ConfigError::MustBePositive {
+                section: "limits",
+                field: "max_connections",
+            });
+        }
+        Ok(())
+    }
+}
+
+/// `[server]`: where and how the server listens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListenerConfig {
+    /// Socket address to bind (default `127.0.0.1:3000`)
+    pub listen: SocketAddr,
+    /// CORS and WebSocket `Origin` allow-list; `[]` denies browser
+    /// cross-origin requests, `["*"]` allows any origin
+    pub allowed_origins: Vec<String>,
+    /// Mount the WebSocket transport at `/pjs/ws` (default `true`)
+    pub websocket: bool,
+    /// Reverse proxies whose `X-Forwarded-For` / `X-Real-IP` headers are
+    /// trusted when keying the HTTP rate limiter
+    pub trusted_proxies: Vec<IpAddr>,
+    /// TLS termination (`[server.tls]`)
+    pub tls: TlsSettings,
+    /// Per-session zstd dictionary training (`[server.dictionary]`)
+    pub dictionary: DictionaryConfig,
+}
+
+impl Default for ListenerConfig {
+    fn default() -> Self {
+        Self {
+            listen: SocketAddr::from(([127, 0, 0, 1], 3000)),
+            allowed_origins: Vec::new(),
+            websocket: true,
+            trusted_proxies: Vec::new(),
+            tls: TlsSettings::default(),
+            dictionary: DictionaryConfig::default(),
+        }
+    }
+}
+
+/// `[server.tls]`: PEM certificate chain and private key. TLS is enabled
+/// when both paths are set; plain HTTP is served when neither is.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsSettings {
+    /// PEM file holding the certificate chain, leaf first
+    pub cert_path: Option<PathBuf>,
+    /// PEM file holding the private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_path: Option<PathBuf>,
+}
+
+impl TlsSettings {
+    /// Both paths, when TLS is enabled.
+    pub fn paths(&self) -> Option<(&Path, &Path)> {
+        Some((self.cert_path.as_deref()?, self.key_path.as_deref()?))
+    }
+}
+
+/// `[server.dictionary]`: serve trained zstd dictionaries from
+/// `/pjs/sessions/{id}/dictionary`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DictionaryConfig {
+    /// Train and serve per-session dictionaries (default `true`)
+    pub enabled: bool,
+    /// Target dictionary size in bytes, clamped to the library maximum
+    pub target_size: usize,
+}
+
+impl Default for DictionaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_size: pjson_rs::compression::zstd::MAX_DICT_SIZE,
+        }
+    }
+}
+
+/// `[auth]`: exactly one of `api_keys` or `[auth.jwt]`, unless
+/// `allow_unauthenticated` is set.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Accepted API keys (`X-PJS-API-Key` or `Authorization: Bearer`)
+    pub api_keys: Vec<String>,
+    /// JWT Bearer authentication (`[auth.jwt]`)
+    pub jwt: JwtSettings,
+    /// Serve without authentication when nothing else is configured.
+    /// Defaults to `false` so a missing secret fails startup instead of
+    /// silently exposing the API.
+    pub allow_unauthenticated: bool,
+}
+
+impl fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthConfig")
+            .field(
+                "api_keys",
+                &format_args!("[{} redacted]", self.api_keys.len()),
+            )
+            .field("jwt", &self.jwt)
+            .field("allow_unauthenticated", &self.allow_unauthenticated)
+            .finish()
+    }
+}
+
+/// `[auth.jwt]`: token validation parameters. Enabled when `secret` (HMAC
+/// algorithms) or `public_key_path` (RSA, ECDSA, EdDSA) is set.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JwtSettings {
+    /// Signature algorithm, e.g. `"HS256"`, `"RS256"`, `"ES256"`, `"EdDSA"`
+    pub algorithm: String,
+    /// Shared secret for `HS*` algorithms
+    pub secret: Option<String>,
+    /// PEM public key for asymmetric algorithms
+    pub public_key_path: Option<PathBuf>,
+    /// Required `iss` claim
+    pub issuer: Option<String>,
+    /// Required `aud` claim; the audience is not checked when unset
+    pub audience: Option<String>,
+    /// Clock skew tolerated on `exp`/`nbf`, in seconds
+    pub leeway_secs: u64,
+}
+
+impl JwtSettings {
+    /// Whether JWT authentication is configured at all.
+    pub fn is_configured(&self) -> bool {
+        self.secret.is_some() || self.public_key_path.is_some()
+    }
+}
+
+impl Default for JwtSettings {
+    fn default() -> Self {
+        Self {
+            algorithm: "HS256".to_string(),
+            secret: None,
+            public_key_path: None,
+            issuer: None,
+            audience: None,
+            leeway_secs: 60,
+        }
+    }
+}
+
+impl fmt::Debug for JwtSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtSettings")
+            .field("algorithm", &self.algorithm)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .field("public_key_path", &self.public_key_path)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("leeway_secs", &self.leeway_secs)
+            .finish()
+    }
+}
+
+/// `[rate_limit]`: where request budgets are kept and how they are counted.
+///
+/// The budget itself — requests per window, and whether limiting is on at
+/// all — comes from `[security.network.rate_limiting]`. HTTP requests and
+/// WebSocket upgrades from one client draw from the same budget. With
+/// `store = "redis"` every replica pointed at the same server shares it, so
+/// adding replicas behind a load balancer no longer multiplies what each
+/// client may send.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitSettings {
+    /// Where budgets are kept (default `"memory"`)
+    pub store: RateLimitStoreKind,
+    /// Server for `store = "redis"`, e.g. `redis://cache:6379/0`
+    pub redis_url: Option<String>,
+    /// Counting algorithm (default `"sliding_window"`)
+    pub algorithm: RateLimitAlgorithmKind,
+    /// Token-bucket capacity, i.e. the largest burst after idling; `0`
+    /// uses `max_requests_per_window`
+    pub burst: u32,
+    /// Admit requests while the store is unreachable instead of answering
+    /// `503` (default `false`)
+    pub fail_open: bool,
+    /// Budgets for individual API keys (`[[rate_limit.api_keys]]`).
+    /// Requests presenting one of these keys are charged to the key, across
+    /// every address it is used from, instead of to their address
+    pub api_keys: Vec<ApiKeyBudget>,
+}
+
+/// `[[rate_limit.api_keys]]`: a request budget of its own for one API key.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyBudget {
+    /// The API key, as sent in `X-PJS-API-Key`
+    pub key: String,
+    /// Requests per `window_duration_secs` for this key
+    pub max_requests_per_window: u32,
+}
+
+impl fmt::Debug for ApiKeyBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeyBudget")
+            .field("key", &"[redacted]")
+            .field("max_requests_per_window", &self.max_requests_per_window)
+            .finish()
+    }
+}
+
+impl fmt::Debug for RateLimitSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimitSettings")
+            .field("store", &self.store)
+            .field("redis_url", &self.redis_url.as_ref().map(|_| "[redacted]"))
+            .field("algorithm", &self.algorithm)
+            .field("burst", &self.burst)
+            .field("fail_open", &self.fail_open)
+            .field("api_keys", &self.api_keys)
+            .finish()
+    }
+}
+
+/// Backend for request budgets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitStoreKind {
+    /// Counters local to this process
+    #[default]
+    Memory,
+    /// Counters shared through Redis (requires the `redis` feature)
+    Redis,
+}
+
+/// Request-budget algorithm; see `pjson_rs::security::RateLimitAlgorithm`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitAlgorithmKind {
+    /// At most `max_requests_per_window` requests in any window
+    #[default]
+    SlidingWindow,
+    /// Bursts of up to `burst` requests, refilled at
+    /// `max_requests_per_window` per window
+    TokenBucket,
+}
+
+/// `[integrity]`: how stream completion digests are signed.
+///
+/// Every stream ends with a content digest of the reconstructed document.
+/// Without a key it is a plain SHA-256; with `hmac_key` set it is an
+/// HMAC-SHA256 that clients holding the same key can verify, proving the
+/// document came from this server unmodified.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntegritySettings {
+    /// Shared HMAC key, at least 32 bytes
+    pub hmac_key: Option<String>,
+}
+
+impl IntegritySettings {
+    /// The configured signing key, if any.
+    ///
+    /// Returns `None` for a key shorter than [`IntegrityKey::MIN_LEN`],
+    /// which [`ServerConfig`] validation rejects.
+    pub fn key(&self) -> Option<IntegrityKey> {
+        self.hmac_key
+            .as_ref()
+            .and_then(|key| IntegrityKey::new(key.as_bytes()).ok())
+    }
+}
+
+impl fmt::Debug for IntegritySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntegritySettings")
+            .field("hmac_key", &self.hmac_key.as_ref().map(|_| "[redacted]"))
+            .finish()
+    }
+}
+
+/// `[limits]`: the file form of [`ConnectionLimits`]. Durations are whole
+/// seconds and `0` disables the corresponding limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Deadline for request headers (and the TLS handshake)
+    pub header_read_timeout_secs: u64,
+    /// Hard ceiling on a connection's lifetime.
+    ///
+    /// When unset, the ceiling is disabled if the WebSocket endpoint is
+    /// enabled (it would otherwise cut long-lived sessions) and uses the
+    /// library default of 300s if it is not.
+    pub max_connection_duration_secs: Option<u64>,
+    /// Maximum number of concurrently open connections
+    pub max_connections: usize,
+    /// Maximum concurrent HTTP/2 streams per connection (`0` keeps hyper's default)
+    pub max_concurrent_streams: u32,
+    /// Maximum concurrent connections per source IP
+    pub max_connections_per_ip: usize,
+    /// Time in-flight streams get on shutdown to send their remaining
+    /// critical frames before they are cut with a resume hint
+    pub drain_timeout_secs: u64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        let defaults = ConnectionLimits::default();
+        Self {
+            header_read_timeout_secs: defaults.header_read_timeout.map_or(0, |d| d.as_secs()),
+            max_connection_duration_secs: None,
+            max_connections: defaults.max_connections,
+            max_concurrent_streams: defaults.max_concurrent_streams.unwrap_or(0),
+            max_connections_per_ip: defaults.max_connections_per_ip.unwrap_or(0),
+            drain_timeout_secs: defaults.drain_timeout.as_secs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pjson_rs::ConfigFormat;
+
+    fn load(toml: &str) -> Result<ServerConfig, ConfigError> {
+        ConfigLoader::with_base(ServerConfig::default())
+            .inline(ConfigFormat::Toml, toml)
+            .load()
+    }
+
+    const ANONYMOUS: &str = "[auth]\nallow_unauthenticated = true\n";
+
+    #[test]
+    fn defaults_require_authentication() {
+        let err = ConfigLoader::with_base(ServerConfig::default())
+            .load()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InconsistentBounds {
+                section: "auth",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn server_and_library_sections_share_one_document() {
+        let config = load(
+            r#"
+            [server]
+            listen = "0.0.0.0:8080"
+            allowed_origins = ["https://app.example.com"]
+
+            [auth]
+            api_keys = ["test-key-1234"]
+
+            [streaming]
+            max_frame_size = 4096
+
+            [security.network.rate_limiting]
+            max_requests_per_window = 7
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.server.listen, SocketAddr::from(([0, 0, 0, 0], 8080)));
+        assert_eq!(config.auth.api_keys, ["test-key-1234"]);
+        assert_eq!(config.pjs.streaming.max_frame_size, 4096);
+        assert_eq!(
+            config
+                .pjs
+                .security
+                .network
+                .rate_limiting
+                .max_requests_per_window,
+            7
+        );
+    }
+
+    #[test]
+    fn rate_limit_section_selects_store_and_per_key_budgets() {
+        let config = load(&format!(
+            r#"{ANONYMOUS}
+            [rate_limit]
+            store = "redis"
+            redis_url = "redis://:hunter2@cache:6379/0"
+            algorithm = "token_bucket"
+
+            [[rate_limit.api_keys]]
+            key = "partner-secret"
+            max_requests_per_window = 5000
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(config.rate_limit.store, RateLimitStoreKind::Redis);
+        assert_eq!(
+            config.rate_limit.algorithm,
+            RateLimitAlgorithmKind::TokenBucket
+        );
+        assert_eq!(config.rate_limit.api_keys[0].key, "partner-secret");
+        assert_eq!(config.rate_limit.api_keys[0].max_requests_per_window, 5000);
+
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("hunter2"));
+        assert!(!debug.contains("partner-secret"));
+    }
+
+    #[test]
+    fn redis_store_requires_url() {
+        let err = load(&format!("{ANONYMOUS}[rate_limit]\nstore = \"redis\"\n")).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InconsistentBounds {
+                section: "rate_limit",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let err = load("[server]\nlisten_addr = \"0.0.0.0:1\"\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::UnknownKey { ref key, .. } if key == "server.listen_addr"
+        ));
+    }
+
+    #[test]
+    fn tls_paths_must_be_set_together() {
+        let err = load(&format!(
+            "{ANONYMOUS}[server.tls]\ncert_path = \"/etc/pjs/cert.pem\"\n"
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InconsistentBounds {
+                section: "server.tls",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn api_keys_and_jwt_are_mutually_exclusive() {
+        let err =
+            load("[auth]\napi_keys = [\"k-123456\"]\n[auth.jwt]\nsecret = \"s\"\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InconsistentBounds {
+                section: "auth",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn secrets_can_come_from_the_environment() {
+        let config = ConfigLoader::with_base(ServerConfig::default())
+            .env_vars([
+                ("PJS_AUTH__JWT__SECRET", "from-env"),
+                ("PJS_SERVER__LISTEN", "127.0.0.1:9000"),
+            ])
+            .load()
+            .unwrap();
+        assert_eq!(config.auth.jwt.secret.as_deref(), Some("from-env"));
+        assert_eq!(config.server.listen.port(), 9000);
+    }
+
+    #[test]
+    fn debug_output_redacts_secrets() {
+        let mut config = ServerConfig::default();
+        config.auth.api_keys = vec!["super-secret-key".to_string()];
+        config.auth.jwt.secret = Some("super-secret-jwt".to_string());
+        config.integrity.hmac_key = Some("super-secret-hmac-key-material-0123456789".to_string());
+
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("super-secret"), "secret leaked: {debug}");
+    }
+
+    #[test]
+    fn integrity_key_must_be_long_enough() {
+        let err = load(&format!("{ANONYMOUS}[integrity]\nhmac_key = \"short\"\n")).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InconsistentBounds {
+                section: "integrity",
+                ..
+            }
+        ));
+
+        let config = load(&format!(
+            "{ANONYMOUS}[integrity]\nhmac_key = \"{}\"\n",
+            "k".repeat(IntegrityKey::MIN_LEN)
+        ))
+        .unwrap();
+        assert!(config.integrity.key().is_some());
+        assert!(ServerConfig::default().integrity.key().is_none());
+    }
+
+    #[test]
+    fn connection_duration_ceiling_depends_on_websocket() {
+        let mut config = ServerConfig::default();
+        assert_eq!(config.connection_limits().max_connection_duration, None);
+
+        config.server.websocket = false;
+        assert_eq!(
+            config.connection_limits().max_connection_duration,
+            ConnectionLimits::default().max_connection_duration
+        );
+
+        config.limits.max_connection_duration_secs = Some(0);
+        assert_eq!(config.connection_limits().max_connection_duration, None);
+    }
+
+    #[test]
+    fn zero_disables_optional_limits() {
+        let mut config = ServerConfig::default();
+        config.limits.header_read_timeout_secs = 0;
+        config.limits.max_connections_per_ip = 0;
+        config.limits.max_concurrent_streams = 0;
+
+        let limits = config.connection_limits();
+        assert_eq!(limits.header_read_timeout, None);
+        assert_eq!(limits.max_connections_per_ip, None);
+        assert_eq!(limits.max_concurrent_streams, None);
+    }
+}
@@ -0,0 +1,46 @@
+//! Errors raised while building or running the server
+
+use std::{io, net::SocketAddr};
+
+use pjson_rs::{
+    ConfigError, infrastructure::http::tls::TlsConfigError, security::RateLimitStoreError,
+};
+
+/// Everything that can stop `pjs-server` from starting or keep it from
+/// serving.
+#[derive(Debug, thiserror::Error)]
+pub enum ServerError {
+    /// The configuration could not be loaded or failed validation.
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    /// The TLS certificate or key could not be loaded.
+    #[error(transparent)]
+    Tls(#[from] TlsConfigError),
+
+    /// API keys or JWT settings were rejected.
+    #[error("invalid auth configuration: {0}")]
+    Auth(String),
+
+    /// The rate-limit store could not be set up (e.g. a malformed `redis_url`).
+    #[error("invalid rate limit store: {0}")]
+    RateLimit(#[from] RateLimitStoreError),
+
+    /// The HTTP router could not be built (e.g. malformed CORS origins).
+    #[error("failed to build HTTP router: {0}")]
+    Router(String),
+
+    /// The listen address could not be bound.
+    #[error("failed to bind {addr}: {source}")]
+    Bind {
+        /// Address from `server.listen`
+        addr: SocketAddr,
+        /// Underlying I/O error
+        #[source]
+        source: io::Error,
+    },
+
+    /// The accept loop failed.
+    #[error("server error: {0}")]
+    Serve(#[source] io::Error),
+}
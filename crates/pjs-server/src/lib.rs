@@ -0,0 +1,96 @@
+//! # PJS Server
+//!
+//! A standalone, production-oriented PJS server: the session/stream HTTP API
+//! (`create_pjs_router`), the WebSocket transport and the connection-level
+//! protections of `serve_with_shutdown`, assembled from a single
+//! configuration file so PJS can be deployed (for example as a sidecar)
+//! without writing Rust.
+//!
+//! The `pjs-server` binary is a thin wrapper around [`ServerConfig::load`],
+//! [`Server::new`] and [`Server::serve`]; see [`config`] for the file format.
+
+pub mod config;
+mod error;
+mod server;
+
+pub use config::ServerConfig;
+pub use error::ServerError;
+pub use server::Server;
+
+use std::{path::PathBuf, sync::Arc};
+
+use pjson_rs::config::ReloadableConfig;
+
+/// Resolve when the process receives `SIGINT` (Ctrl-C) or, on Unix,
+/// `SIGTERM` — the signal container orchestrators send before killing a
+/// process.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(error) = tokio::signal::ctrl_c().await {
+            tracing::error!(%error, "failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(error) => {
+                tracing::error!(%error, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+    tracing::info!("shutdown signal received, draining connections");
+}
+
+/// Re-read the configuration and apply it to `config` every time the
+/// process receives `SIGHUP`. Never returns; on non-Unix targets it waits
+/// forever.
+///
+/// Each reload re-runs [`ServerConfig::load`] with `path`, so the file and
+/// `PJS_` environment layers are merged exactly as at startup, then hands the
+/// library section to [`ReloadableConfig::apply`]. Only rate limits and
+/// `streaming.priority_threshold` change live; a file that fails to load or
+/// validate, or that turns rate limiting on or off, is logged and leaves the
+/// running configuration untouched. Server-level sections (`[server]`,
+/// `[auth]`, `[limits]`, `[rate_limit]`, `[integrity]`) need a restart.
+pub async fn reload_on_sighup(path: Option<PathBuf>, config: Arc<ReloadableConfig>) {
+    #[cfg(unix)]
+    {
+        let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        {
+            Ok(signal) => signal,
+            Err(error) => {
+                tracing::error!(%error, "failed to listen for SIGHUP");
+                return std::future::pending().await;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            match ServerConfig::load(path.as_deref()).and_then(|loaded| config.apply(loaded.pjs)) {
+                Ok(report) if report.applied.is_empty() => {
+                    tracing::info!("SIGHUP: no hot-reloadable configuration changed");
+                }
+                Ok(report) => {
+                    tracing::info!(applied = ?report.applied, "SIGHUP: configuration reloaded");
+                }
+                Err(error) => {
+                    tracing::error!(%error, "SIGHUP: configuration reload rejected");
+                }
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (path, config);
+    std::future::pending().await
+}
@@ -0,0 +1,59 @@
+//! `pjs-server` command-line entry point
+
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use pjs_server::{Server, ServerConfig, reload_on_sighup, shutdown_signal};
+
+/// Standalone Priority JSON Streaming server
+#[derive(Debug, Parser)]
+#[command(name = "pjs-server", version, about)]
+struct Args {
+    /// Configuration file (`.toml` or `.json`). `PJS_`-prefixed environment
+    /// variables override its values.
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Validate the configuration, including TLS and JWT key material, then
+    /// exit without binding the listen address.
+    #[arg(long)]
+    check_config: bool,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    tracing_subscriber::fmt::init();
+    let args = Args::parse();
+
+    let server = match ServerConfig::load(args.config.as_deref())
+        .map_err(Into::into)
+        .and_then(Server::new)
+    {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("pjs-server: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if args.check_config {
+        let config = server.config();
+        println!(
+            "configuration OK: listen={} tls={} websocket={}",
+            config.server.listen,
+            server.is_tls(),
+            config.server.websocket
+        );
+        return ExitCode::SUCCESS;
+    }
+
+    tokio::spawn(reload_on_sighup(args.config, server.reloadable_config()));
+
+    match server.serve(shutdown_signal()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("pjs-server: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,502 @@
+//! Assembling the router, transports and listener from a [`ServerConfig`]
+
+use std::{future::Future, net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::Router;
+use pjson_rs::{
+    config::{ReloadableConfig, security::RateLimitingConfig},
+    domain::ports::{DictionaryStore, NoopDictionaryStore},
+    infrastructure::{
+        adapters::{GatInMemoryStreamRepository, GatInMemoryStreamStore, InMemoryEventPublisher},
+        http::{
+            HttpServerConfig, PjsAppState, RateLimitMiddleware, TrustedProxyConfig,
+            auth::{ApiKeyAuthLayer, ApiKeyConfig},
+            create_pjs_router_with_auth, create_pjs_router_with_config,
+            create_pjs_router_with_rate_limit_and_auth,
+            create_pjs_router_with_rate_limit_and_config, serve_tls_with_shutdown,
+            serve_with_shutdown,
+            tls::{load_server_config, rustls},
+        },
+        repositories::InMemoryDictionaryStore,
+        shutdown::{ShutdownCoordinator, TERMINATION_GRACE},
+        websocket::{AxumWebSocketTransport, create_websocket_router},
+    },
+    security::{
+        CompressionBombDetector, InMemoryRateLimitStore, RateLimitAlgorithm,
+        RateLimitConfig as WebSocketRateLimitConfig, RateLimitPolicy, RateLimitStore,
+    },
+};
+use tokio::{net::TcpListener, sync::watch};
+use tracing::{info, warn};
+
+use crate::{
+    ServerConfig, ServerError,
+    config::{RateLimitAlgorithmKind, RateLimitSettings, RateLimitStoreKind},
+};
+
+type Repository = GatInMemoryStreamRepository;
+type Publisher = InMemoryEventPublisher;
+type Store = GatInMemoryStreamStore;
+type RateLimit = (
+    Arc<dyn RateLimitStore>,
+    watch::Receiver<Arc<RateLimitPolicy>>,
+);
+
+/// The authentication scheme selected by `[auth]`.
+#[derive(Clone)]
+enum Auth {
+    None,
+    ApiKey(ApiKeyAuthLayer),
+    #[cfg(feature = "jwt")]
+    Jwt(pjson_rs::infrastructure::http::auth::JwtAuthLayer<serde_json::Value>),
+}
+
+/// A fully assembled server, ready to be bound.
+///
+/// Construction does every check that does not need the network: TLS
+/// material is loaded, auth keys are parsed and the router is built, so a
+/// `Server` that was created successfully only fails later if the listen
+/// address cannot be bound. `pjs-server --check-config` relies on this.
+pub struct Server {
+    config: ServerConfig,
+    router: Router,
+    tls: Option<Arc<rustls::ServerConfig>>,
+    websocket: Option<Arc<AxumWebSocketTransport>>,
+    shutdown: ShutdownCoordinator,
+    reloadable: Arc<ReloadableConfig>,
+}
+
+impl Server {
+    /// Build the router, WebSocket transport and TLS configuration
+    /// described by `config`.
+    ///
+    /// Must be called from within a Tokio runtime: the rate limiters,
+    /// dictionary store and WebSocket transport spawn their background
+    /// cleanup tasks here.
+    ///
+    /// # Errors
+    ///
+    /// - [`ServerError::Config`] if `config` fails validation.
+    /// - [`ServerError::Tls`] if the certificate or key cannot be loaded.
+    /// - [`ServerError::Auth`] if API keys or JWT settings are invalid.
+    /// - [`ServerError::RateLimit`] if `rate_limit.redis_url` is malformed.
+    /// - [`ServerError::Router`] if `server.allowed_origins` is malformed.
+    pub fn new(config: ServerConfig) -> Result<Self, ServerError> {
+        pjson_rs::LayeredConfig::validate(&config)?;
+
+        let tls = config
+            .server
+            .tls
+            .paths()
+            .map(|(cert, key)| load_server_config(cert, key))
+            .transpose()?;
+
+        let reloadable = Arc::new(ReloadableConfig::new(config.pjs.clone())?);
+        let repository = Arc::new(Repository::new());
+        let publisher = Arc::new(Publisher::new());
+        let store = Arc::new(Store::new());
+        let shutdown =
+            ShutdownCoordinator::new(Duration::from_secs(config.limits.drain_timeout_secs))
+                .with_flush("stream repository", repository.clone())
+                .with_flush("stream store", store.clone())
+                .with_flush("event publisher", publisher.clone());
+        let state = PjsAppState::with_dictionary_store(
+            repository,
+            publisher.clone(),
+            store,
+            build_dictionary_store(&config),
+        )
+        .with_drain_signal(shutdown.signal())
+        .with_json_limits(config.pjs.security.json.clone())
+        .with_reloadable_config(&reloadable);
+
+        let auth = build_auth(&config)?;
+        let rate_limit = build_rate_limit(&config, &reloadable)?;
+        let http = build_http_router(&config, auth.clone(), rate_limit.clone(), state)?;
+
+        let (router, websocket) = if config.server.websocket {
+            let mut transport =
+                AxumWebSocketTransport::with_rate_limit_config(WebSocketRateLimitConfig::default())
+                    .with_allowed_origins(config.server.allowed_origins.clone())
+                    .with_drain_signal(shutdown.signal())
+                    .with_event_publisher(publisher);
+            if config.pjs.security.network.rate_limiting.enabled {
+                reloadable.bind_rate_limiter(transport.rate_limiter());
+            }
+            if let Some((store, policy)) = rate_limit {
+                transport = transport.with_reloadable_rate_limit_store(store, policy);
+            }
+            if let Some(key) = config.integrity.key() {
+                transport = transport.with_integrity_key(key);
+            }
+            let transport = Arc::new(transport);
+            let ws_router = with_auth(
+                create_websocket_router().with_state(Arc::clone(&transport)),
+                auth,
+            );
+            (http.nest("/pjs", ws_router), Some(transport))
+        } else {
+            (http, None)
+        };
+
+        Ok(Self {
+            config,
+            router,
+            tls,
+            websocket,
+            shutdown,
+            reloadable,
+        })
+    }
+
+    /// The validated configuration this server was built from.
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    /// Whether connections are served over TLS.
+    pub fn is_tls(&self) -> bool {
+        self.tls.is_some()
+    }
+
+    /// The router serving every endpoint (useful for in-process testing).
+    pub fn router(&self) -> Router {
+        self.router.clone()
+    }
+
+    /// The WebSocket transport, when `server.websocket` is enabled.
+    pub fn websocket(&self) -> Option<&Arc<AxumWebSocketTransport>> {
+        self.websocket.as_ref()
+    }
+
+    /// The live library configuration.
+    ///
+    /// [`ReloadableConfig::apply`] changes the request budgets, WebSocket
+    /// connection and message limits and `streaming.priority_threshold` of
+    /// the running server; [`crate::reload_on_sighup`] does this whenever
+    /// the process receives `SIGHUP`. The handle stays usable after
+    /// [`Self::serve`] consumes the server.
+    pub fn reloadable_config(&self) -> Arc<ReloadableConfig> {
+        Arc::clone(&self.reloadable)
+    }
+
+    /// Bind `server.listen` and serve until `shutdown` completes, then shut
+    /// down gracefully.
+    ///
+    /// Shutdown stops accepting connections and gives in-flight HTTP and
+    /// WebSocket streams `limits.drain_timeout_secs` to send their remaining
+    /// critical frames. Streams that cannot finish in time end with an error
+    /// frame telling the client which sequence to resume from. The stream
+    /// repository, stream store and event publisher are flushed last.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ServerError::Bind`] if the address cannot be bound, or
+    /// [`ServerError::Serve`] if the accept loop fails.
+    pub async fn serve<F>(self, shutdown: F) -> Result<(), ServerError>
+    where
+        F: Future<Output = ()> + Send,
+    {
+        let addr = self.config.server.listen;
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|source| ServerError::Bind { addr, source })?;
+        self.serve_on(listener, shutdown).await
+    }
+
+    /// Like [`Self::serve`], on an already bound listener.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ServerError::Serve`] if the accept loop fails.
+    pub async fn serve_on<F>(self, listener: TcpListener, shutdown: F) -> Result<(), ServerError>
+    where
+        F: Future<Output = ()> + Send,
+    {
+        let local_addr = listener.local_addr().map_err(ServerError::Serve)?;
+        log_startup(&self, local_addr);
+
+        // Streams get the configured drain timeout; their connections get
+        // the termination grace on top to write the final resume hint.
+        let mut limits = self.config.connection_limits();
+        limits.drain_timeout += TERMINATION_GRACE;
+
+        let Self {
+            router,
+            tls,
+            shutdown: coordinator,
+            ..
+        } = self;
+        let draining = coordinator.signal().draining();
+        let serve = async move {
+            match tls {
+                Some(tls) => serve_tls_with_shutdown(listener, router, limits, tls, draining).await,
+                None => serve_with_shutdown(listener, router, limits, draining).await,
+            }
+        };
+        tokio::pin!(serve);
+
+        let result = tokio::select! {
+            result = &mut serve => result,
+            () = shutdown => {
+                info!(
+                    drain_timeout = ?coordinator.drain_timeout(),
+                    "shutdown requested, draining in-flight streams"
+                );
+                let (result, report) = tokio::join!(&mut serve, coordinator.shutdown());
+                if report.is_clean() {
+                    info!("all streams drained and flushed");
+                } else {
+                    warn!(
+                        interrupted_streams = report.interrupted_streams,
+                        flush_failures = report.flush_failures.len(),
+                        "shutdown was not clean"
+                    );
+                }
+                result
+            }
+        };
+        result.map_err(ServerError::Serve)?;
+
+        info!("pjs-server stopped");
+        Ok(())
+    }
+}
+
+fn log_startup(server: &Server, local_addr: SocketAddr) {
+    let scheme = if server.is_tls() { "https" } else { "http" };
+    info!("pjs-server listening on {scheme}://{local_addr}");
+    if server.websocket.is_some() {
+        let scheme = if server.is_tls() { "wss" } else { "ws" };
+        info!("WebSocket endpoint: {scheme}://{local_addr}/pjs/ws");
+    }
+    if server.config.auth.allow_unauthenticated
+        && server.config.auth.api_keys.is_empty()
+        && !server.config.auth.jwt.is_configured()
+    {
+        warn!("authentication is disabled (auth.allow_unauthenticated = true)");
+    }
+}
+
+fn build_auth(config: &ServerConfig) -> Result<Auth, ServerError> {
+    if !config.auth.api_keys.is_empty() {
+        let keys: Vec<&str> = config.auth.api_keys.iter().map(String::as_str).collect();
+        let api_keys = ApiKeyConfig::new(&keys).map_err(|e| ServerError::Auth(e.to_string()))?;
+        return Ok(Auth::ApiKey(ApiKeyAuthLayer::new(api_keys)));
+    }
+    if config.auth.jwt.is_configured() {
+        return build_jwt_auth(config);
+    }
+    Ok(Auth::None)
+}
+
+#[cfg(feature = "jwt")]
+fn build_jwt_auth(config: &ServerConfig) -> Result<Auth, ServerError> {
+    use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+    use pjson_rs::infrastructure::http::auth::{JwtAuthLayer, JwtConfig};
+
+    let jwt = &config.auth.jwt;
+    let algorithm: Algorithm = jwt
+        .algorithm
+        .parse()
+        .map_err(|_| ServerError::Auth(format!("unknown JWT algorithm `{}`", jwt.algorithm)))?;
+
+    let decoding_key = match (algorithm, &jwt.secret, &jwt.public_key_path) {
+        (Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512, Some(secret), _) => {
+            DecodingKey::from_secret(secret.as_bytes())
+        }
+        (Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512, None, _) => {
+            return Err(ServerError::Auth(format!(
+                "{algorithm:?} requires auth.jwt.secret"
+            )));
+        }
+        (_, _, Some(path)) => {
+            let pem = std::fs::read(path)
+                .map_err(|e| ServerError::Auth(format!("cannot read {}: {e}", path.display())))?;
+            match algorithm {
+                Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(&pem),
+                Algorithm::EdDSA => DecodingKey::from_ed_pem(&pem),
+                _ => DecodingKey::from_rsa_pem(&pem),
+            }
+            .map_err(|e| ServerError::Auth(format!("invalid key in {}: {e}", path.display())))?
+        }
+        (_, _, None) => {
+            return Err(ServerError::Auth(format!(
+                "{algorithm:?} requires auth.jwt.public_key_path"
+            )));
+        }
+    };
+
+    let mut validation = Validation::new(algorithm);
+    validation.leeway = jwt.leeway_secs;
+    if let Some(issuer) = &jwt.issuer {
+        validation.set_issuer(&[issuer]);
+    }
+    match &jwt.audience {
+        Some(audience) => validation.set_audience(&[audience]),
+        None => validation.validate_aud = false,
+    }
+
+    Ok(Auth::Jwt(JwtAuthLayer::new(JwtConfig {
+        decoding_key,
+        validation,
+    })))
+}
+
+#[cfg(not(feature = "jwt"))]
+fn build_jwt_auth(_config: &ServerConfig) -> Result<Auth, ServerError> {
+    Err(ServerError::Auth(
+        "JWT support was not compiled in (enable the `jwt` feature)".to_string(),
+    ))
+}
+
+fn build_dictionary_store(config: &ServerConfig) -> Arc<dyn DictionaryStore> {
+    if config.server.dictionary.enabled {
+        Arc::new(InMemoryDictionaryStore::new(
+            Arc::new(CompressionBombDetector::new(
+                config.pjs.security.network.compression_bomb.clone(),
+            )),
+            config.server.dictionary.target_size,
+        ))
+    } else {
+        Arc::new(NoopDictionaryStore)
+    }
+}
+
+/// The request-budget store and policy described by
+/// `[security.network.rate_limiting]` and `[rate_limit]`, shared by the HTTP
+/// middleware and WebSocket upgrades; `None` when rate limiting is disabled.
+///
+/// The policy is rebuilt whenever `reloadable` publishes new rate limits.
+/// `[rate_limit]` itself (store, algorithm, per-key budgets) is fixed at
+/// startup. The Redis backend connects on first use, so this never touches
+/// the network.
+fn build_rate_limit(
+    config: &ServerConfig,
+    reloadable: &ReloadableConfig,
+) -> Result<Option<RateLimit>, ServerError> {
+    let rate_limiting = &config.pjs.security.network.rate_limiting;
+    if !rate_limiting.enabled {
+        return Ok(None);
+    }
+
+    let settings = config.rate_limit.clone();
+    let (sender, policy) = watch::channel(Arc::new(build_policy(&settings, rate_limiting)));
+    let mut updates = reloadable.subscribe();
+    tokio::spawn(async move {
+        while updates.changed().await.is_ok() {
+            let config = updates.borrow_and_update().clone();
+            sender.send_replace(Arc::new(build_policy(
+                &settings,
+                &config.security.network.rate_limiting,
+            )));
+        }
+    });
+
+    let store: Arc<dyn RateLimitStore> = match config.rate_limit.store {
+        RateLimitStoreKind::Memory => Arc::new(InMemoryRateLimitStore::new()),
+        #[cfg(feature = "redis")]
+        RateLimitStoreKind::Redis => {
+            use pjson_rs::security::{SharedRateLimitStore, rate_limit_store::RedisBackend};
+
+            let url = config.rate_limit.redis_url.as_deref().unwrap_or_default();
+            Arc::new(SharedRateLimitStore::new(Arc::new(RedisBackend::new(url)?)))
+        }
+        // Rejected by `ServerConfig::validate`.
+        #[cfg(not(feature = "redis"))]
+        RateLimitStoreKind::Redis => unreachable!("redis store requires the `redis` feature"),
+    };
+
+    Ok(Some((store, policy)))
+}
+
+/// The [`RateLimitPolicy`] charging requests under `rate_limiting`'s window
+/// with `settings`' algorithm and per-key budgets.
+fn build_policy(
+    settings: &RateLimitSettings,
+    rate_limiting: &RateLimitingConfig,
+) -> RateLimitPolicy {
+    let window = Duration::from_secs(rate_limiting.window_duration_secs);
+    let algorithm = |requests_per_window: u32| match settings.algorithm {
+        RateLimitAlgorithmKind::SlidingWindow => {
+            RateLimitAlgorithm::sliding_window(requests_per_window, window)
+        }
+        RateLimitAlgorithmKind::TokenBucket => {
+            let burst = if settings.burst > 0 {
+                settings.burst
+            } else {
+                requests_per_window
+            };
+            RateLimitAlgorithm::token_bucket(burst, requests_per_window, window)
+        }
+    };
+
+    settings.api_keys.iter().fold(
+        RateLimitPolicy::new(algorithm(rate_limiting.max_requests_per_window))
+            .with_fail_open(settings.fail_open),
+        |policy, budget| {
+            policy.with_api_key(&budget.key, algorithm(budget.max_requests_per_window))
+        },
+    )
+}
+
+fn build_http_router(
+    config: &ServerConfig,
+    auth: Auth,
+    rate_limit: Option<RateLimit>,
+    state: PjsAppState<Repository, Publisher, Store>,
+) -> Result<Router, ServerError> {
+    let http_config = HttpServerConfig::new(config.server.allowed_origins.clone());
+    let rate_limit = rate_limit.map(|(store, policy)| {
+        let middleware = RateLimitMiddleware::from_reloadable_store(store, policy);
+        if config.server.trusted_proxies.is_empty() {
+            middleware
+        } else {
+            middleware.with_trusted_proxies(TrustedProxyConfig::new(
+                config.server.trusted_proxies.clone(),
+            ))
+        }
+    });
+
+    let router = match (auth, rate_limit) {
+        (Auth::None, None) => {
+            create_pjs_router_with_config::<Repository, Publisher, Store>(&http_config)
+        }
+        (Auth::None, Some(rate_limit)) => create_pjs_router_with_rate_limit_and_config::<
+            Repository,
+            Publisher,
+            Store,
+        >(&http_config, rate_limit),
+        (Auth::ApiKey(auth), None) => {
+            create_pjs_router_with_auth::<Repository, Publisher, Store>(&http_config, auth)
+        }
+        (Auth::ApiKey(auth), Some(rate_limit)) => create_pjs_router_with_rate_limit_and_auth::<
+            Repository,
+            Publisher,
+            Store,
+        >(&http_config, rate_limit, auth),
+        #[cfg(feature = "jwt")]
+        (Auth::Jwt(auth), rate_limit) => {
+            pjson_rs::infrastructure::http::create_pjs_router_with_jwt_auth::<
+                Repository,
+                Publisher,
+                Store,
+                _,
+            >(&http_config, rate_limit, auth)
+        }
+    }
+    .map_err(|e| ServerError::Router(e.to_string()))?;
+
+    Ok(router.with_state(state))
+}
+
+/// Apply the configured authentication to a router that has no auth of its
+/// own (the WebSocket upgrade route).
+fn with_auth(router: Router, auth: Auth) -> Router {
+    match auth {
+        Auth::None => router,
+        Auth::ApiKey(layer) => router.layer(layer),
+        #[cfg(feature = "jwt")]
+        Auth::Jwt(layer) => router.layer(layer),
+    }
+}
@@ -0,0 +1,506 @@
+//! End-to-end tests for `pjs-server`: a real listener, real sockets and the
+//! real binary for `--check-config`.
+
+use std::{net::SocketAddr, path::PathBuf, process::Command, sync::Arc, time::Duration};
+
+use pjs_server::{Server, ServerConfig, config::ApiKeyBudget};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+    task::JoinHandle,
+    time::timeout,
+};
+
+const API_KEY: &str = "integration-test-key";
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn api_key_config() -> ServerConfig {
+    let mut config = ServerConfig::default();
+    config.auth.api_keys = vec![API_KEY.to_string()];
+    config
+}
+
+struct Running {
+    addr: SocketAddr,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<Result<(), pjs_server::ServerError>>,
+}
+
+async fn start(config: ServerConfig) -> Running {
+    serve(Server::new(config).expect("valid server configuration")).await
+}
+
+async fn serve(server: Server) -> Running {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let addr = listener.local_addr().expect("local_addr");
+    let (stop, stopped) = oneshot::channel::<()>();
+    let task = tokio::spawn(server.serve_on(listener, async {
+        let _ = stopped.await;
+    }));
+    Running { addr, stop, task }
+}
+
+/// Send a raw HTTP/1.1 request with `Connection: close` and return the
+/// status code and full response text.
+async fn request<S>(
+    mut stream: S,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+) -> (u16, String)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut raw = format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n");
+    for (name, value) in headers {
+        raw.push_str(&format!("{name}: {value}\r\n"));
+    }
+    if method == "POST" {
+        raw.push_str("Content-Type: application/json\r\nContent-Length: 2\r\n\r\n{}");
+    } else {
+        raw.push_str("\r\n");
+    }
+    stream
+        .write_all(raw.as_bytes())
+        .await
+        .expect("write request");
+
+    let mut response = Vec::new();
+    timeout(Duration::from_secs(5), stream.read_to_end(&mut response))
+        .await
+        .expect("timed out waiting for response")
+        .expect("read response");
+    let response = String::from_utf8_lossy(&response).into_owned();
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .unwrap_or_else(|| panic!("malformed response: {response}"));
+    (status, response)
+}
+
+async fn get(addr: SocketAddr, path: &str, headers: &[(&str, &str)]) -> u16 {
+    let stream = TcpStream::connect(addr).await.expect("connect");
+    request(stream, "GET", path, headers).await.0
+}
+
+#[tokio::test]
+async fn health_is_public_and_api_requires_the_configured_key() {
+    let server = start(api_key_config()).await;
+
+    assert_eq!(get(server.addr, "/pjs/health", &[]).await, 200);
+    assert_eq!(get(server.addr, "/pjs/sessions", &[]).await, 401);
+    assert_eq!(
+        get(
+            server.addr,
+            "/pjs/sessions",
+            &[("X-PJS-API-Key", "wrong-key")]
+        )
+        .await,
+        401
+    );
+    assert_eq!(
+        get(server.addr, "/pjs/sessions", &[("X-PJS-API-Key", API_KEY)]).await,
+        200
+    );
+
+    let stream = TcpStream::connect(server.addr).await.expect("connect");
+    let (status, _) = request(
+        stream,
+        "POST",
+        "/pjs/sessions",
+        &[("X-PJS-API-Key", API_KEY)],
+    )
+    .await;
+    assert!(
+        (200..300).contains(&status),
+        "creating a session failed: {status}"
+    );
+}
+
+#[tokio::test]
+async fn registered_api_keys_get_their_own_request_budget() {
+    let mut config = api_key_config();
+    config
+        .pjs
+        .security
+        .network
+        .rate_limiting
+        .max_requests_per_window = 1;
+    config.rate_limit.api_keys.push(ApiKeyBudget {
+        key: API_KEY.to_string(),
+        max_requests_per_window: 3,
+    });
+    let server = start(config).await;
+
+    // Anonymous requests share the address budget of one request...
+    assert_eq!(get(server.addr, "/pjs/sessions", &[]).await, 401);
+    assert_eq!(get(server.addr, "/pjs/sessions", &[]).await, 429);
+
+    // ...while the registered key has its own budget of three.
+    for _ in 0..3 {
+        assert_eq!(
+            get(server.addr, "/pjs/sessions", &[("X-PJS-API-Key", API_KEY)]).await,
+            200
+        );
+    }
+    assert_eq!(
+        get(server.addr, "/pjs/sessions", &[("X-PJS-API-Key", API_KEY)]).await,
+        429
+    );
+}
+
+#[tokio::test]
+async fn reloaded_request_budgets_apply_to_the_running_server() {
+    let mut config = api_key_config();
+    config
+        .pjs
+        .security
+        .network
+        .rate_limiting
+        .max_requests_per_window = 1;
+    let server = Server::new(config.clone()).expect("valid server configuration");
+    let reloadable = server.reloadable_config();
+    let server = serve(server).await;
+    let authorized = [("X-PJS-API-Key", API_KEY)];
+
+    assert_eq!(get(server.addr, "/pjs/sessions", &authorized).await, 200);
+    assert_eq!(get(server.addr, "/pjs/sessions", &authorized).await, 429);
+
+    config
+        .pjs
+        .security
+        .network
+        .rate_limiting
+        .max_requests_per_window = 10;
+    let report = reloadable.apply(config.pjs).expect("valid reload");
+    assert_eq!(report.applied, ["security.network.rate_limiting"]);
+
+    let mut status = 429;
+    for _ in 0..50 {
+        status = get(server.addr, "/pjs/sessions", &authorized).await;
+        if status != 429 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn websocket_upgrade_is_authenticated_too() {
+    let server = start(api_key_config()).await;
+    let upgrade = [
+        ("Upgrade", "websocket"),
+        ("Connection", "Upgrade"),
+        ("Sec-WebSocket-Version", "13"),
+        ("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="),
+    ];
+
+    let stream = TcpStream::connect(server.addr).await.expect("connect");
+    let (status, _) = request(stream, "GET", "/pjs/ws", &upgrade).await;
+    assert_eq!(
+        status, 401,
+        "unauthenticated WebSocket upgrade must be rejected"
+    );
+
+    let mut authenticated = upgrade.to_vec();
+    authenticated.push(("X-PJS-API-Key", API_KEY));
+    let mut stream = TcpStream::connect(server.addr).await.expect("connect");
+    let mut raw = "GET /pjs/ws HTTP/1.1\r\nHost: localhost\r\n".to_string();
+    for (name, value) in &authenticated {
+        raw.push_str(&format!("{name}: {value}\r\n"));
+    }
+    raw.push_str("\r\n");
+    stream
+        .write_all(raw.as_bytes())
+        .await
+        .expect("write upgrade");
+    let mut buf = [0u8; 64];
+    let read = timeout(Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .expect("timed out waiting for upgrade response")
+        .expect("read upgrade response");
+    assert!(
+        buf[..read].starts_with(b"HTTP/1.1 101"),
+        "authenticated upgrade failed: {}",
+        String::from_utf8_lossy(&buf[..read])
+    );
+}
+
+#[tokio::test]
+async fn websocket_endpoint_can_be_disabled() {
+    let mut config = api_key_config();
+    config.server.websocket = false;
+    let server = start(config).await;
+
+    assert_eq!(
+        get(server.addr, "/pjs/ws", &[("X-PJS-API-Key", API_KEY)]).await,
+        404
+    );
+}
+
+#[cfg(feature = "jwt")]
+#[tokio::test]
+async fn jwt_bearer_tokens_are_validated() {
+    use jsonwebtoken::{EncodingKey, Header, encode};
+
+    let mut config = ServerConfig::default();
+    config.auth.jwt.secret = Some("jwt-test-secret".to_string());
+    config.auth.jwt.issuer = Some("pjs-tests".to_string());
+    let server = start(config).await;
+
+    let claims = serde_json::json!({
+        "sub": "sidecar",
+        "iss": "pjs-tests",
+        "exp": 4_102_444_800u64, // 2100-01-01
+    });
+    let sign = |secret: &[u8]| {
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret),
+        )
+        .expect("sign")
+    };
+    let valid = format!("Bearer {}", sign(b"jwt-test-secret"));
+    let forged = format!("Bearer {}", sign(b"some-other-secret"));
+
+    assert_eq!(
+        get(server.addr, "/pjs/sessions", &[("Authorization", &valid)]).await,
+        200
+    );
+    assert_eq!(
+        get(server.addr, "/pjs/sessions", &[("Authorization", &forged)]).await,
+        401
+    );
+}
+
+#[tokio::test]
+async fn tls_is_terminated_when_certificates_are_configured() {
+    use tokio_rustls::{
+        TlsConnector,
+        rustls::{
+            ClientConfig, RootCertStore,
+            pki_types::{CertificateDer, ServerName, pem::PemObject},
+        },
+    };
+
+    let mut config = api_key_config();
+    config.server.tls.cert_path = Some(fixture("localhost.cert.pem"));
+    config.server.tls.key_path = Some(fixture("localhost.key.pem"));
+    let server = start(config).await;
+
+    let mut roots = RootCertStore::empty();
+    for cert in CertificateDer::pem_file_iter(fixture("localhost.cert.pem")).expect("read cert") {
+        roots.add(cert.expect("parse cert")).expect("add root");
+    }
+    let provider = Arc::new(tokio_rustls::rustls::crypto::ring::default_provider());
+    let client_config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .expect("protocol versions")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(client_config));
+
+    let tcp = TcpStream::connect(server.addr).await.expect("connect");
+    let tls = connector
+        .connect(ServerName::try_from("localhost").unwrap(), tcp)
+        .await
+        .expect("TLS handshake");
+    let (status, _) = request(tls, "GET", "/pjs/health", &[]).await;
+    assert_eq!(status, 200);
+
+    // Plain-text HTTP on the TLS port is not served.
+    let mut plain = TcpStream::connect(server.addr).await.expect("connect");
+    plain
+        .write_all(b"GET /pjs/health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .expect("write");
+    let mut response = Vec::new();
+    let _ = timeout(Duration::from_secs(5), plain.read_to_end(&mut response)).await;
+    assert!(!response.starts_with(b"HTTP/1.1 200"));
+}
+
+#[tokio::test]
+async fn shutdown_signal_stops_the_server() {
+    let server = start(api_key_config()).await;
+    assert_eq!(get(server.addr, "/pjs/health", &[]).await, 200);
+
+    server.stop.send(()).expect("server running");
+    timeout(Duration::from_secs(5), server.task)
+        .await
+        .expect("server did not stop")
+        .expect("server task panicked")
+        .expect("server returned an error");
+    assert!(TcpStream::connect(server.addr).await.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_closes_open_websockets_with_going_away() {
+    let server = start(api_key_config()).await;
+
+    let mut stream = TcpStream::connect(server.addr).await.expect("connect");
+    stream
+        .write_all(
+            format!(
+                "GET /pjs/ws HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nSec-WebSocket-Version: 13\r\n\
+                 Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nX-PJS-API-Key: {API_KEY}\r\n\r\n"
+            )
+            .as_bytes(),
+        )
+        .await
+        .expect("write upgrade");
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let byte = timeout(Duration::from_secs(5), stream.read_u8())
+            .await
+            .expect("timed out waiting for upgrade response")
+            .expect("read upgrade response");
+        head.push(byte);
+    }
+    assert!(head.starts_with(b"HTTP/1.1 101"));
+
+    server.stop.send(()).expect("server running");
+    let mut frames = Vec::new();
+    timeout(Duration::from_secs(5), stream.read_to_end(&mut frames))
+        .await
+        .expect("socket was not closed on shutdown")
+        .expect("read close frame");
+
+    // Unmasked server Close frame whose payload starts with status 1001.
+    let close = frames
+        .windows(4)
+        .position(|w| w[0] == 0x88 && w[2..4] == 1001u16.to_be_bytes())
+        .map(|at| {
+            String::from_utf8_lossy(&frames[at + 4..at + 2 + frames[at + 1] as usize]).into_owned()
+        });
+    assert_eq!(close.as_deref(), Some("server shutting down"));
+
+    timeout(Duration::from_secs(5), server.task)
+        .await
+        .expect("server did not stop")
+        .expect("server task panicked")
+        .expect("server returned an error");
+}
+
+#[tokio::test]
+async fn websocket_streams_end_with_a_signed_digest() {
+    use futures::{SinkExt, StreamExt};
+    use pjson_rs::{
+        domain::value_objects::{IntegrityError, IntegrityKey},
+        infrastructure::websocket::{StreamOptions, WsMessage},
+    };
+    use tokio_tungstenite::tungstenite::{Message, client::IntoClientRequest};
+
+    let hmac_key = "integration-test-hmac-key-0123456789abcdef";
+    let mut config = api_key_config();
+    config.integrity.hmac_key = Some(hmac_key.to_string());
+    let server = start(config).await;
+
+    let mut request = format!("ws://{}/pjs/ws", server.addr)
+        .into_client_request()
+        .expect("request");
+    request
+        .headers_mut()
+        .insert("X-PJS-API-Key", API_KEY.parse().expect("header"));
+    let (mut socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .expect("upgrade");
+
+    let data = serde_json::json!({"id": 42, "name": "Alice", "tags": ["a", "b"]});
+    let init = WsMessage::StreamInit {
+        session_id: String::new(),
+        data: data.clone(),
+        options: StreamOptions::default(),
+    };
+    socket
+        .send(Message::Text(
+            serde_json::to_string(&init).expect("encode").into(),
+        ))
+        .await
+        .expect("send StreamInit");
+
+    let mut document = serde_json::json!({});
+    let checksum = timeout(Duration::from_secs(5), async {
+        while let Some(message) = socket.next().await {
+            let Message::Text(text) = message.expect("read") else {
+                continue;
+            };
+            match serde_json::from_str::<WsMessage>(&text).expect("decode") {
+                WsMessage::StreamFrame { payload, .. } => {
+                    match (document.as_object_mut(), payload) {
+                        (Some(document), serde_json::Value::Object(fields)) => {
+                            document.extend(fields)
+                        }
+                        (_, payload) => document = payload,
+                    }
+                }
+                WsMessage::StreamComplete { checksum, .. } => return checksum,
+                _ => {}
+            }
+        }
+        panic!("connection closed before StreamComplete");
+    })
+    .await
+    .expect("timed out waiting for StreamComplete");
+
+    assert!(checksum.is_signed());
+    let key = IntegrityKey::new(hmac_key).expect("key");
+    assert_eq!(checksum.verify(&document, Some(&key)), Ok(()));
+    assert_eq!(
+        checksum.verify(&document, None),
+        Err(IntegrityError::KeyRequired)
+    );
+}
+
+#[test]
+fn check_config_accepts_a_valid_file_and_rejects_an_invalid_one() {
+    let dir = std::env::temp_dir();
+    let valid = dir.join(format!("pjs-server-valid-{}.toml", std::process::id()));
+    let invalid = dir.join(format!("pjs-server-invalid-{}.toml", std::process::id()));
+    std::fs::write(
+        &valid,
+        format!(
+            "[server]\nlisten = \"127.0.0.1:1\"\n\n[server.tls]\ncert_path = {:?}\nkey_path = {:?}\n\n[auth]\napi_keys = [\"{API_KEY}\"]\n",
+            fixture("localhost.cert.pem"),
+            fixture("localhost.key.pem"),
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        &invalid,
+        "[server]\nlisten = \"127.0.0.1:1\"\nunknown = 1\n",
+    )
+    .unwrap();
+
+    let run = |path: &PathBuf| {
+        Command::new(env!("CARGO_BIN_EXE_pjs-server"))
+            .arg("--check-config")
+            .arg("--config")
+            .arg(path)
+            .env_remove("RUST_LOG")
+            .output()
+            .expect("run pjs-server")
+    };
+    let ok = run(&valid);
+    let err = run(&invalid);
+    std::fs::remove_file(&valid).ok();
+    std::fs::remove_file(&invalid).ok();
+
+    assert!(
+        ok.status.success(),
+        "valid config rejected: {}",
+        String::from_utf8_lossy(&ok.stderr)
+    );
+    assert!(String::from_utf8_lossy(&ok.stdout).contains("tls=true"));
+    assert!(!err.status.success(), "invalid config accepted");
+    assert!(String::from_utf8_lossy(&err.stderr).contains("server.unknown"));
+}
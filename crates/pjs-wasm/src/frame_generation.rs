@@ -1,106 +1,49 @@
-//! Shared frame-generation algorithm for the WASM bindings.
+//! Frame generation for the WASM bindings.
 //!
 //! `PjsParser::generate_frames_internal` and `PriorityStream::generate_frames_internal`
-//! both build a depth-limited skeleton, extract prioritized fields, group and sort them
-//! by priority, and emit patch frames per level above a threshold followed by a
-//! completion frame. This module is the single implementation both structs wrap.
+//! both wrap [`generate_frames`], which packs the output of the shared
+//! [`FrameGenerator`] into one patch frame per priority level, so the bindings
+//! send the same skeleton and patches as every other transport.
 
-use crate::priority_assignment::{PriorityAssigner, group_by_priority, sort_priorities};
+use crate::priority_assignment::PriorityAssigner;
 use pjson_rs_domain::entities::Frame;
-use pjson_rs_domain::entities::frame::FramePatch;
-use pjson_rs_domain::value_objects::{JsonData, Priority, StreamId};
-use std::collections::HashMap;
-
-/// Build a skeleton structure from `data`: same shape, but with null/empty leaf values.
-///
-/// Recursion stops at `max_depth`, replacing anything deeper with `JsonData::Null`.
-pub(crate) fn build_skeleton(data: &JsonData, current_depth: usize, max_depth: usize) -> JsonData {
-    if current_depth >= max_depth {
-        return JsonData::Null;
-    }
-
-    match data {
-        JsonData::Object(map) => {
-            let mut skeleton_map = HashMap::with_capacity(map.len());
-
-            for (k, v) in map.iter() {
-                let skeleton_value = match v {
-                    JsonData::Object(_) => build_skeleton(v, current_depth + 1, max_depth),
-                    JsonData::Array(_) => JsonData::Array(vec![]),
-                    JsonData::String(_) => JsonData::Null,
-                    JsonData::Integer(_) => JsonData::Integer(0),
-                    JsonData::Float(_) => JsonData::Float(0.0),
-                    JsonData::Bool(_) => JsonData::Bool(false),
-                    JsonData::Null => JsonData::Null,
-                    _ => JsonData::Null,
-                };
-                skeleton_map.insert(k.clone(), skeleton_value);
-            }
-
-            JsonData::Object(skeleton_map)
-        }
-        JsonData::Array(_) => JsonData::Array(vec![]),
-        _ => JsonData::Null,
-    }
-}
+use pjson_rs_domain::services::FrameGenerator;
+use pjson_rs_domain::value_objects::{IntegrityKey, JsonData, Priority, StreamId};
 
 /// Generate priority-ordered frames for `data`: a skeleton frame, one patch frame per
 /// priority level at or above `min_priority`, then a completion frame.
+///
+/// The completion frame carries the content digest of the document the preceding
+/// frames reconstruct to, HMAC-signed when `integrity_key` is set.
 pub(crate) fn generate_frames(
     priority_assigner: &PriorityAssigner,
     data: &JsonData,
     stream_id: StreamId,
     min_priority: Priority,
     max_depth: usize,
+    integrity_key: Option<&IntegrityKey>,
 ) -> Result<Vec<Frame>, String> {
-    // Pre-allocate frames Vec with estimated capacity
-    // Typical: 1 skeleton + ~2-4 priority groups + 1 complete = ~4-6 frames
-    // Conservative estimate to avoid over-allocation
-    let mut frames = Vec::with_capacity(6);
-    let mut sequence = 0u64;
-
-    // 1. Generate skeleton frame (always first, critical priority)
-    let skeleton = build_skeleton(data, 0, max_depth);
-    frames.push(Frame::skeleton(stream_id, sequence, skeleton));
-    sequence += 1;
-
-    // 2. Extract all fields with priorities (depth-limited)
-    let prioritized_fields =
-        priority_assigner.extract_prioritized_fields_with_limit(data, max_depth);
-
-    // 3. Group fields by priority level
-    let grouped = group_by_priority(prioritized_fields);
-
-    // 4. Get sorted priorities (descending order)
-    let mut priorities: Vec<Priority> = grouped.keys().copied().collect();
-    priorities = sort_priorities(priorities);
-
-    // 5. Generate patch frames for each priority level (above threshold)
-    for priority in priorities {
-        if priority < min_priority {
-            continue; // Skip priorities below threshold
-        }
-
-        if let Some(fields) = grouped.get(&priority) {
-            // Pre-allocate patches Vec with exact capacity
-            let mut patches = Vec::with_capacity(fields.len());
-            for field in fields.iter() {
-                patches.push(FramePatch::set(field.path.clone(), field.value.clone()));
-            }
-
-            if !patches.is_empty() {
-                // Create patch frame
-                let frame = Frame::patch(stream_id, sequence, priority, patches)
-                    .map_err(|e| format!("Failed to create patch frame: {:?}", e))?;
-
-                frames.push(frame);
-                sequence += 1;
-            }
-        }
+    let generator =
+        FrameGenerator::new(priority_assigner.heuristic_config()).with_max_depth(max_depth);
+
+    // 1. Skeleton frame (always first, critical priority)
+    let mut frames = vec![Frame::skeleton(stream_id, 0, generator.skeleton(data))];
+
+    // 2. One patch frame per priority level, highest first
+    let patches = generator.patches(data, min_priority);
+    for (priority, patches) in FrameGenerator::group_by_priority(patches, usize::MAX) {
+        let frame = Frame::patch(stream_id, frames.len() as u64, priority, patches)
+            .map_err(|e| format!("Failed to create patch frame: {:?}", e))?;
+        frames.push(frame);
     }
 
-    // 6. Add completion frame (always last, critical priority)
-    frames.push(Frame::complete(stream_id, sequence, None));
+    // 3. Completion frame (always last, critical priority)
+    let digest = crate::reconstruction::digest_of_frames(&frames, integrity_key)?;
+    frames.push(Frame::complete(
+        stream_id,
+        frames.len() as u64,
+        Some(digest),
+    ));
 
     Ok(frames)
 }
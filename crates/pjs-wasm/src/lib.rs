@@ -78,6 +78,7 @@ mod parser;
 pub mod priority_assignment;
 mod priority_config;
 mod priority_constants;
+mod reconstruction;
 pub mod security;
 mod streaming;
 mod utils;
@@ -85,6 +86,7 @@ mod utils;
 pub use parser::PjsParser;
 pub use priority_config::PriorityConfigBuilder;
 pub use priority_constants::PriorityConstants;
+pub use reconstruction::PjsReconstructor;
 pub use security::SecurityConfig;
 pub use streaming::{FrameData, PriorityStream, StreamStats};
 
@@ -8,7 +8,7 @@ use crate::priority_assignment::PriorityAssigner;
 use crate::priority_config::PriorityConfigBuilder;
 use crate::security::{SecurityConfig, validate_input_size, validate_json_structure};
 use pjson_rs_domain::entities::Frame;
-use pjson_rs_domain::value_objects::{JsonData, Priority, StreamId};
+use pjson_rs_domain::value_objects::{IntegrityKey, JsonData, Priority, StreamId};
 use wasm_bindgen::prelude::*;
 
 /// PJS Parser for WebAssembly.
@@ -36,6 +36,7 @@ use wasm_bindgen::prelude::*;
 pub struct PjsParser {
     priority_assigner: PriorityAssigner,
     security_config: SecurityConfig,
+    integrity_key: Option<IntegrityKey>,
 }
 
 #[wasm_bindgen]
@@ -56,6 +57,7 @@ impl PjsParser {
         Self {
             priority_assigner: PriorityAssigner::new(),
             security_config: SecurityConfig::default(),
+            integrity_key: None,
         }
     }
 
@@ -91,6 +93,7 @@ impl PjsParser {
         Self {
             priority_assigner: PriorityAssigner::with_config(config),
             security_config: SecurityConfig::default(),
+            integrity_key: None,
         }
     }
 
@@ -121,9 +124,29 @@ impl PjsParser {
         Self {
             priority_assigner: PriorityAssigner::new(),
             security_config: security_config.clone(),
+            integrity_key: None,
         }
     }
 
+    /// Sign the completion frame's content digest with an HMAC-SHA256 key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` is shorter than 32 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```javascript
+    /// const parser = new PjsParser();
+    /// parser.setIntegrityKey(new TextEncoder().encode(sharedSecret));
+    /// ```
+    #[wasm_bindgen(js_name = setIntegrityKey)]
+    pub fn set_integrity_key(&mut self, key: &[u8]) -> Result<(), JsValue> {
+        let key = IntegrityKey::new(key).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.integrity_key = Some(key);
+        Ok(())
+    }
+
     /// Parse a JSON string and return the result.
     ///
     /// This method parses a JSON string using `serde_json` (WASM-compatible)
@@ -219,36 +242,51 @@ impl PjsParser {
     /// ```
     #[wasm_bindgen(js_name = generateFrames)]
     pub fn generate_frames(&self, json_str: &str, min_priority: u8) -> Result<JsValue, JsValue> {
+        let frames = self
+            .frames_for(json_str, min_priority)
+            .map_err(|e| JsValue::from_str(&e))?;
+
+        // Convert frames to JsValue
+        serde_wasm_bindgen::to_value(&frames)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    }
+}
+
+/// Native entry points for Rust callers, such as the `pjs-conformance`
+/// harness: `JsValue` can only be created inside a WebAssembly host.
+impl PjsParser {
+    /// The frames `generateFrames` returns for `json_str`, as domain frames.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message `generateFrames` would throw: the input exceeds
+    /// the security limits, is not valid JSON, or `min_priority` is zero.
+    pub fn frames_for(&self, json_str: &str, min_priority: u8) -> Result<Vec<Frame>, String> {
         // Security: Validate input size
         validate_input_size(json_str, &self.security_config)
-            .map_err(|e| JsValue::from_str(&format!("Security error: {}", e)))?;
+            .map_err(|e| format!("Security error: {}", e))?;
 
         // Parse JSON
-        let value: serde_json::Value = serde_json::from_str(json_str)
-            .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+        let value: serde_json::Value =
+            serde_json::from_str(json_str).map_err(|e| format!("Parse error: {}", e))?;
 
         // Security: Validate array/object sizes at every nesting level
         validate_json_structure(&value, &self.security_config)
-            .map_err(|e| JsValue::from_str(&format!("Security error: {}", e)))?;
+            .map_err(|e| format!("Security error: {}", e))?;
 
         let json_data: JsonData = value.into();
 
         // Create stream ID (use fixed UUID for WASM to avoid Node.js crypto issues)
         let stream_id = StreamId::from_string("00000000-0000-0000-0000-000000000001")
-            .map_err(|e| JsValue::from_str(&format!("StreamId creation error: {}", e)))?;
+            .map_err(|e| format!("StreamId creation error: {}", e))?;
 
         // Validate minimum priority threshold
-        let min_priority_threshold = Priority::new(min_priority)
-            .map_err(|e| JsValue::from_str(&format!("Invalid priority: {:?}", e)))?;
+        let min_priority_threshold =
+            Priority::new(min_priority).map_err(|e| format!("Invalid priority: {:?}", e))?;
 
         // Generate frames using domain logic and priority assignment
-        let frames = self
-            .generate_frames_internal(&json_data, stream_id, min_priority_threshold)
-            .map_err(|e| JsValue::from_str(&format!("Frame generation error: {:?}", e)))?;
-
-        // Convert frames to JsValue
-        serde_wasm_bindgen::to_value(&frames)
-            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+        self.generate_frames_internal(&json_data, stream_id, min_priority_threshold)
+            .map_err(|e| format!("Frame generation error: {:?}", e))
     }
 
     /// Internal frame generation logic (not exposed to JS)
@@ -264,6 +302,7 @@ impl PjsParser {
             stream_id,
             min_priority,
             self.security_config.max_depth(),
+            self.integrity_key.as_ref(),
         )
     }
 }
@@ -278,6 +317,7 @@ impl Default for PjsParser {
 mod tests {
     use super::*;
     use pjson_rs_domain::entities::frame::FrameType;
+    use pjson_rs_domain::services::FrameGenerator;
     use std::collections::HashMap;
 
     #[test]
@@ -313,8 +353,9 @@ mod tests {
         obj.insert("age".to_string(), JsonData::Integer(30));
         let data = JsonData::Object(obj);
 
-        let skeleton =
-            crate::frame_generation::build_skeleton(&data, 0, crate::security::DEFAULT_MAX_DEPTH);
+        let skeleton = FrameGenerator::default()
+            .with_max_depth(crate::security::DEFAULT_MAX_DEPTH)
+            .skeleton(&data);
 
         if let JsonData::Object(map) = skeleton {
             assert_eq!(map.get("name"), Some(&JsonData::Null));
@@ -332,8 +373,9 @@ mod tests {
         outer.insert("address".to_string(), JsonData::Object(inner));
         let data = JsonData::Object(outer);
 
-        let skeleton =
-            crate::frame_generation::build_skeleton(&data, 0, crate::security::DEFAULT_MAX_DEPTH);
+        let skeleton = FrameGenerator::default()
+            .with_max_depth(crate::security::DEFAULT_MAX_DEPTH)
+            .skeleton(&data);
 
         if let JsonData::Object(map) = skeleton {
             if let Some(JsonData::Object(inner_map)) = map.get("address") {
@@ -350,8 +392,9 @@ mod tests {
     fn test_create_skeleton_array() {
         let data = JsonData::Array(vec![JsonData::Integer(1), JsonData::Integer(2)]);
 
-        let skeleton =
-            crate::frame_generation::build_skeleton(&data, 0, crate::security::DEFAULT_MAX_DEPTH);
+        let skeleton = FrameGenerator::default()
+            .with_max_depth(crate::security::DEFAULT_MAX_DEPTH)
+            .skeleton(&data);
         assert_eq!(skeleton, JsonData::Array(vec![]));
     }
 
@@ -486,7 +529,9 @@ mod tests {
         }
 
         // With depth limit of 5, deeper levels should be replaced with Null
-        let skeleton = crate::frame_generation::build_skeleton(&current, 0, 5);
+        let skeleton = FrameGenerator::default()
+            .with_max_depth(5)
+            .skeleton(&current);
 
         // Verify skeleton doesn't exceed depth limit
         fn count_depth(data: &JsonData, current: usize) -> usize {
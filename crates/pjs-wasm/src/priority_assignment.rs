@@ -142,6 +142,12 @@ impl PriorityAssigner {
         self.config.to_heuristic()
     }
 
+    /// The domain heuristic config for the current configuration, as used
+    /// by the shared frame generator.
+    pub fn heuristic_config(&self) -> PriorityHeuristicConfig {
+        self.refreshed_heuristic()
+    }
+
     /// Calculate priority for a single `(path, value)` pair.
     ///
     /// Delegates entirely to
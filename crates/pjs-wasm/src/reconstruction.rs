@@ -0,0 +1,531 @@
+//! Client-side reconstruction of PJS frame streams in WebAssembly.
+//!
+//! `PjsReconstructor` applies the frames a `PriorityStream` (or a server)
+//! emits — skeleton, patches, completion — to rebuild the source document,
+//! and verifies the completion frame's content digest against the result.
+//!
+//! # Example
+//!
+//! ```javascript
+//! import { PriorityStream, PjsReconstructor } from 'pjs-wasm';
+//!
+//! const reconstructor = new PjsReconstructor();
+//! const stream = new PriorityStream();
+//!
+//! stream.onFrame((frame) => {
+//!     // Throws if the completion digest does not match.
+//!     reconstructor.applyFrame(frame);
+//! });
+//!
+//! stream.start(jsonString);
+//! console.log(reconstructor.isComplete(), reconstructor.getState());
+//! ```
+
+use crate::streaming::FrameData;
+use pjson_rs_domain::entities::Frame;
+use pjson_rs_domain::entities::frame::ArrayChunkMetadata;
+use pjson_rs_domain::value_objects::{
+    ContentDigest, IntegrityError, IntegrityKey, JsonPath, PathSegment, verify_completion,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// Document state rebuilt from a frame sequence.
+///
+/// Patches that cannot be applied to the current state (missing parent,
+/// index out of bounds, wrong container type) are skipped and counted
+/// rather than failing the stream: frame generation truncates the skeleton
+/// at the configured depth, so some patches legitimately target structure
+/// the skeleton does not carry. The server digests the document as replayed
+/// under the same rules, so skipped patches do not cause a mismatch.
+///
+/// Applied `append` patches that carry `array_metadata` (large arrays
+/// streamed in chunks) are tracked per array path, so callers can show
+/// how many items of a list have arrived so far.
+#[derive(Debug, Default)]
+pub(crate) struct Reconstruction {
+    document: Value,
+    complete: bool,
+    skipped_patches: u32,
+    array_progress: HashMap<String, ArrayChunkMetadata>,
+}
+
+/// Items of a chunked array received so far, as returned by
+/// `PjsReconstructor.getArrayProgress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArrayProgress {
+    pub(crate) items_received: usize,
+    pub(crate) total_items: usize,
+}
+
+impl Reconstruction {
+    /// Apply one frame, identified by its wire `type` name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for a malformed payload, an `error` frame, or a
+    /// completion frame whose digest does not verify against the document.
+    pub(crate) fn apply(
+        &mut self,
+        frame_type: &str,
+        payload: Value,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<(), String> {
+        match frame_type {
+            "skeleton" => {
+                self.document = payload;
+                self.array_progress.clear();
+            }
+            "patch" => {
+                let patches = payload
+                    .get("patches")
+                    .and_then(Value::as_array)
+                    .ok_or("Patch frame payload has no patches array")?;
+                for patch in patches {
+                    if self.apply_patch(patch) {
+                        self.record_array_chunk(patch);
+                    } else {
+                        self.skipped_patches += 1;
+                    }
+                }
+            }
+            "complete" => {
+                let checksum = match payload.get("checksum") {
+                    Some(Value::String(digest)) => Some(
+                        digest
+                            .parse::<ContentDigest>()
+                            .map_err(|e| format!("Integrity check failed: {e}"))?,
+                    ),
+                    Some(_) => {
+                        return Err(format!(
+                            "Integrity check failed: {}",
+                            IntegrityError::Malformed("checksum is not a string".to_string())
+                        ));
+                    }
+                    None => None,
+                };
+                verify_completion(checksum.as_ref(), &self.document, integrity_key)
+                    .map_err(|e| format!("Integrity check failed: {e}"))?;
+                self.complete = true;
+            }
+            "error" => return Err(format!("Stream error: {payload}")),
+            other => return Err(format!("Unknown frame type: {other}")),
+        }
+        Ok(())
+    }
+
+    /// Apply a domain frame, as produced by frame generation.
+    pub(crate) fn apply_frame(
+        &mut self,
+        frame: &Frame,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<(), String> {
+        let payload = serde_json::to_value(frame.payload())
+            .map_err(|e| format!("Failed to encode frame payload: {e}"))?;
+        self.apply(
+            crate::streaming::frame_type_name(frame.frame_type()),
+            payload,
+            integrity_key,
+        )
+    }
+
+    pub(crate) fn document(&self) -> &Value {
+        &self.document
+    }
+
+    /// Progress of the chunked array at `path`, or `None` if no chunk of it
+    /// has been applied.
+    pub(crate) fn array_progress(&self, path: &JsonPath) -> Option<ArrayProgress> {
+        self.array_progress
+            .get(&path.to_string())
+            .map(|metadata| ArrayProgress {
+                items_received: metadata.items_received(),
+                total_items: metadata.total_items,
+            })
+    }
+
+    fn record_array_chunk(&mut self, patch: &Value) {
+        let (Some(path), Some(metadata)) = (
+            patch.get("path").and_then(Value::as_str),
+            patch.get("array_metadata"),
+        ) else {
+            return;
+        };
+        if let Ok(metadata) = serde_json::from_value::<ArrayChunkMetadata>(metadata.clone()) {
+            self.array_progress.insert(path.to_string(), metadata);
+        }
+    }
+
+    /// Returns `false` if the patch could not be applied.
+    fn apply_patch(&mut self, patch: &Value) -> bool {
+        let Some(path) = patch
+            .get("path")
+            .and_then(Value::as_str)
+            .and_then(|path| JsonPath::new(path).ok())
+        else {
+            return false;
+        };
+        let value = patch.get("value").cloned().unwrap_or(Value::Null);
+
+        let Some((last, parents)) = path.segments().split_last() else {
+            // Root path: only `set` is meaningful.
+            return match patch.get("operation").and_then(Value::as_str) {
+                Some("set") => {
+                    self.document = value;
+                    true
+                }
+                _ => false,
+            };
+        };
+        let Some(parent) = navigate(&mut self.document, parents) else {
+            return false;
+        };
+
+        match patch.get("operation").and_then(Value::as_str) {
+            Some("set") => match (parent, last) {
+                (Value::Object(map), PathSegment::Key(key)) => {
+                    map.insert(key.clone(), value);
+                    true
+                }
+                (Value::Array(items), PathSegment::Index(index)) => match items.get_mut(*index) {
+                    Some(slot) => {
+                        *slot = value;
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            },
+            Some("append") => match navigate(parent, std::slice::from_ref(last)) {
+                Some(Value::Array(items)) => {
+                    match value {
+                        Value::Array(values) => items.extend(values),
+                        value => items.push(value),
+                    }
+                    true
+                }
+                _ => false,
+            },
+            Some("merge") => match (navigate(parent, std::slice::from_ref(last)), value) {
+                (Some(Value::Object(target)), Value::Object(fields)) => {
+                    target.extend(fields);
+                    true
+                }
+                _ => false,
+            },
+            Some("delete") => match (parent, last) {
+                (Value::Object(map), PathSegment::Key(key)) => map.remove(key).is_some(),
+                (Value::Array(items), PathSegment::Index(index)) if *index < items.len() => {
+                    items.remove(*index);
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+fn navigate<'a>(mut current: &'a mut Value, segments: &[PathSegment]) -> Option<&'a mut Value> {
+    for segment in segments {
+        current = match (current, segment) {
+            (Value::Object(map), PathSegment::Key(key)) => map.get_mut(key)?,
+            (Value::Array(items), PathSegment::Index(index)) => items.get_mut(*index)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Digest of the document `frames` reconstruct to.
+///
+/// Replays `frames` through [`Reconstruction`], so the digest covers exactly
+/// what a conforming client will hold — including the effect of depth
+/// truncation and skipped patches.
+pub(crate) fn digest_of_frames(
+    frames: &[Frame],
+    integrity_key: Option<&IntegrityKey>,
+) -> Result<ContentDigest, String> {
+    let mut reconstruction = Reconstruction::default();
+    for frame in frames {
+        reconstruction.apply_frame(frame, None)?;
+    }
+    Ok(ContentDigest::compute(
+        reconstruction.document(),
+        integrity_key,
+    ))
+}
+
+/// Frame reconstructor for WebAssembly.
+///
+/// Rebuilds the streamed document from `FrameData` objects and verifies the
+/// completion frame's content digest. Configure the same HMAC key as the
+/// producer with `withIntegrityKey` to require signed digests.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct PjsReconstructor {
+    state: Reconstruction,
+    integrity_key: Option<IntegrityKey>,
+}
+
+#[wasm_bindgen]
+impl PjsReconstructor {
+    /// Create a reconstructor that verifies unsigned SHA-256 digests.
+    ///
+    /// # Example
+    ///
+    /// ```javascript
+    /// const reconstructor = new PjsReconstructor();
+    /// ```
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a reconstructor that requires HMAC-SHA256-signed digests.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` is shorter than 32 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```javascript
+    /// const key = new TextEncoder().encode(sharedSecret);
+    /// const reconstructor = PjsReconstructor.withIntegrityKey(key);
+    /// ```
+    #[wasm_bindgen(js_name = withIntegrityKey)]
+    pub fn with_integrity_key(key: &[u8]) -> Result<PjsReconstructor, JsValue> {
+        let key = IntegrityKey::new(key).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(Self {
+            state: Reconstruction::default(),
+            integrity_key: Some(key),
+        })
+    }
+
+    /// Apply one frame, as delivered by `PriorityStream.onFrame`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for a malformed payload, an `error` frame, or a
+    /// completion frame whose digest does not match the reconstructed
+    /// document (or is unsigned while a key is configured).
+    #[wasm_bindgen(js_name = applyFrame)]
+    pub fn apply_frame(&mut self, frame: FrameData) -> Result<(), JsValue> {
+        self.apply_frame_data(&frame)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Whether a completion frame has been applied and verified.
+    #[wasm_bindgen(js_name = isComplete)]
+    pub fn is_complete(&self) -> bool {
+        self.state.complete
+    }
+
+    /// Number of patches skipped because they did not fit the current state.
+    #[wasm_bindgen(js_name = skippedPatches)]
+    pub fn skipped_patches(&self) -> u32 {
+        self.state.skipped_patches
+    }
+
+    /// Progress of a large array streamed in chunks, as
+    /// `{ itemsReceived, totalItems }`, or `undefined` if no chunk of the
+    /// array at `path` has arrived yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` is not a valid JSON path.
+    ///
+    /// # Example
+    ///
+    /// ```javascript
+    /// const progress = reconstructor.getArrayProgress('$.rows');
+    /// if (progress) {
+    ///     console.log(`${progress.itemsReceived} / ${progress.totalItems}`);
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = getArrayProgress)]
+    pub fn get_array_progress(&self, path: &str) -> Result<JsValue, JsValue> {
+        let path = JsonPath::new(path).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        match self.state.array_progress(&path) {
+            Some(progress) => serde_wasm_bindgen::to_value(&progress)
+                .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e))),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    /// The document reconstructed so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document cannot be converted to a JS value.
+    #[wasm_bindgen(js_name = getState)]
+    pub fn get_state(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.state.document)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    }
+}
+
+/// Native entry points for Rust callers, such as the `pjs-conformance`
+/// harness: `JsValue` can only be created inside a WebAssembly host.
+impl PjsReconstructor {
+    /// Apply one frame; the native counterpart of `applyFrame`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message `applyFrame` would throw.
+    pub fn apply_frame_data(&mut self, frame: &FrameData) -> Result<(), String> {
+        let payload: Value =
+            serde_json::from_str(&frame.payload).map_err(|e| format!("Parse error: {}", e))?;
+        self.state
+            .apply(&frame.frame_type, payload, self.integrity_key.as_ref())
+    }
+
+    /// The document reconstructed so far; the native counterpart of
+    /// `getState`.
+    pub fn document(&self) -> &Value {
+        self.state.document()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PriorityStream;
+    use pjson_rs_domain::value_objects::{JsonData, Priority, StreamId};
+    use serde_json::json;
+
+    fn frames_for(value: Value, key: Option<IntegrityKey>) -> Vec<Frame> {
+        let mut stream = PriorityStream::new();
+        stream.integrity_key = key;
+        stream
+            .generate_frames_internal(&JsonData::from(value), StreamId::new(), Priority::LOW)
+            .unwrap()
+    }
+
+    fn replay(frames: &[Frame], key: Option<&IntegrityKey>) -> Result<Reconstruction, String> {
+        let mut reconstruction = Reconstruction::default();
+        for frame in frames {
+            reconstruction.apply_frame(frame, key)?;
+        }
+        Ok(reconstruction)
+    }
+
+    #[test]
+    fn generated_frames_verify_on_reconstruction() {
+        let source = json!({"id": 7, "name": "Alice", "address": {"city": "Oslo"}});
+        let reconstruction = replay(&frames_for(source.clone(), None), None).unwrap();
+
+        assert!(reconstruction.complete);
+        assert_eq!(reconstruction.document, source);
+    }
+
+    #[test]
+    fn signed_frames_require_matching_key() {
+        let key = IntegrityKey::new([3u8; 32]).unwrap();
+        let frames = frames_for(json!({"id": 1, "name": "Bob"}), Some(key.clone()));
+
+        assert!(replay(&frames, Some(&key)).unwrap().complete);
+        assert!(replay(&frames, None).is_err());
+        let other = IntegrityKey::new([4u8; 32]).unwrap();
+        assert!(replay(&frames, Some(&other)).is_err());
+    }
+
+    #[test]
+    fn tampered_patch_fails_verification() {
+        let frames = frames_for(json!({"id": 1, "name": "Bob"}), None);
+        let mut reconstruction = Reconstruction::default();
+        for frame in &frames[..frames.len() - 1] {
+            reconstruction.apply_frame(frame, None).unwrap();
+        }
+        reconstruction.document["name"] = json!("Mallory");
+
+        let error = reconstruction
+            .apply_frame(frames.last().unwrap(), None)
+            .unwrap_err();
+        assert!(error.contains("digest mismatch"), "{error}");
+        assert!(!reconstruction.complete);
+    }
+
+    #[test]
+    fn unapplicable_patches_are_skipped_and_counted() {
+        let mut reconstruction = Reconstruction::default();
+        reconstruction
+            .apply("skeleton", json!({"items": []}), None)
+            .unwrap();
+        reconstruction
+            .apply(
+                "patch",
+                json!({"patches": [
+                    {"path": "$.items[3]", "operation": "set", "value": 1},
+                    {"path": "$.missing.field", "operation": "set", "value": 1},
+                    {"path": "$.items", "operation": "append", "value": [1, 2]},
+                    {"path": "$.extra", "operation": "set", "value": {"a": 1}},
+                    {"path": "$.extra", "operation": "merge", "value": {"b": 2}},
+                ]}),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(reconstruction.skipped_patches, 2);
+        assert_eq!(
+            reconstruction.document,
+            json!({"items": [1, 2], "extra": {"a": 1, "b": 2}})
+        );
+    }
+
+    #[test]
+    fn completion_without_digest_is_accepted_only_without_key() {
+        let key = IntegrityKey::new([5u8; 32]).unwrap();
+        let mut reconstruction = Reconstruction::default();
+        assert!(
+            reconstruction
+                .apply("complete", json!({}), Some(&key))
+                .is_err()
+        );
+        reconstruction.apply("complete", json!({}), None).unwrap();
+        assert!(reconstruction.complete);
+    }
+
+    #[test]
+    fn chunked_array_progress_is_tracked() {
+        use pjson_rs_domain::entities::Stream;
+        use pjson_rs_domain::entities::stream::{ArrayChunking, StreamConfig};
+        use pjson_rs_domain::value_objects::SessionId;
+
+        let rows: Vec<Value> = (0..25).map(|i| json!({"row": i})).collect();
+        let source = json!({"title": "page", "rows": rows});
+        let config = StreamConfig {
+            default_array_chunking: Some(ArrayChunking::new(10)),
+            ..StreamConfig::default()
+        };
+        let mut stream = Stream::new(SessionId::new(), source.clone().into(), config);
+        stream.start_streaming().unwrap();
+        let mut frames = vec![stream.create_skeleton_frame().unwrap()];
+        frames.extend(
+            stream
+                .create_patch_frames(Priority::BACKGROUND, 16)
+                .unwrap(),
+        );
+
+        let rows_path = JsonPath::new("$.rows").unwrap();
+        let mut reconstruction = Reconstruction::default();
+        reconstruction.apply_frame(&frames[0], None).unwrap();
+        assert_eq!(reconstruction.array_progress(&rows_path), None);
+
+        let mut seen = Vec::new();
+        for frame in &frames[1..] {
+            reconstruction.apply_frame(frame, None).unwrap();
+            if let Some(progress) = reconstruction.array_progress(&rows_path) {
+                seen.push(progress.items_received);
+                assert_eq!(progress.total_items, 25);
+            }
+        }
+        seen.dedup();
+        assert_eq!(seen, vec![10, 20, 25]);
+        assert_eq!(reconstruction.document, source);
+    }
+}
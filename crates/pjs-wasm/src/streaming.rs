@@ -32,7 +32,7 @@ use crate::priority_config::PriorityConfigBuilder;
 use crate::security::{SecurityConfig, validate_input_size, validate_json_structure};
 use pjson_rs_domain::entities::Frame;
 use pjson_rs_domain::entities::frame::FrameType;
-use pjson_rs_domain::value_objects::{JsonData, Priority, StreamId};
+use pjson_rs_domain::value_objects::{IntegrityKey, JsonData, Priority, StreamId};
 use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 use wasm_bindgen::prelude::*;
@@ -78,15 +78,20 @@ pub struct FrameData {
     pub payload: String,
 }
 
+/// Wire name of a frame type, as carried in [`FrameData`]'s `type` field.
+pub(crate) fn frame_type_name(frame_type: &FrameType) -> &'static str {
+    match frame_type {
+        FrameType::Skeleton => "skeleton",
+        FrameType::Patch => "patch",
+        FrameType::Complete => "complete",
+        FrameType::Error => "error",
+        _ => "unknown",
+    }
+}
+
 impl From<&Frame> for FrameData {
     fn from(frame: &Frame) -> Self {
-        let frame_type = match frame.frame_type() {
-            FrameType::Skeleton => "skeleton".to_string(),
-            FrameType::Patch => "patch".to_string(),
-            FrameType::Complete => "complete".to_string(),
-            FrameType::Error => "error".to_string(),
-            _ => "unknown".to_string(),
-        };
+        let frame_type = frame_type_name(frame.frame_type()).to_string();
 
         let payload = serde_json::to_string(frame.payload()).unwrap_or_else(|_| "null".to_string());
 
@@ -133,6 +138,7 @@ pub struct PriorityStream {
     priority_assigner: PriorityAssigner,
     min_priority: u8,
     security_config: SecurityConfig,
+    pub(crate) integrity_key: Option<IntegrityKey>,
     on_frame: Option<js_sys::Function>,
     on_complete: Option<js_sys::Function>,
     on_error: Option<js_sys::Function>,
@@ -153,6 +159,7 @@ impl PriorityStream {
             priority_assigner: PriorityAssigner::new(),
             min_priority: 1,
             security_config: SecurityConfig::default(),
+            integrity_key: None,
             on_frame: None,
             on_complete: None,
             on_error: None,
@@ -182,6 +189,7 @@ impl PriorityStream {
             priority_assigner: PriorityAssigner::with_config(config),
             min_priority: 1,
             security_config: SecurityConfig::default(),
+            integrity_key: None,
             on_frame: None,
             on_complete: None,
             on_error: None,
@@ -210,6 +218,27 @@ impl PriorityStream {
         self.security_config = config.clone();
     }
 
+    /// Sign completion digests with an HMAC-SHA256 key.
+    ///
+    /// Consumers must verify with the same key, e.g. via
+    /// `PjsReconstructor.withIntegrityKey`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` is shorter than 32 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```javascript
+    /// stream.setIntegrityKey(new TextEncoder().encode(sharedSecret));
+    /// ```
+    #[wasm_bindgen(js_name = setIntegrityKey)]
+    pub fn set_integrity_key(&mut self, key: &[u8]) -> Result<(), JsValue> {
+        let key = IntegrityKey::new(key).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.integrity_key = Some(key);
+        Ok(())
+    }
+
     /// Set the minimum priority threshold.
     ///
     /// Frames with priority below this threshold will not be delivered.
@@ -389,6 +418,7 @@ impl PriorityStream {
             stream_id,
             min_priority,
             self.security_config.max_depth(),
+            self.integrity_key.as_ref(),
         )
     }
 